@@ -0,0 +1,101 @@
+import java.io.BufferedReader;
+import java.io.ByteArrayOutputStream;
+import java.io.IOException;
+import java.io.InputStreamReader;
+import java.io.OutputStreamWriter;
+import java.io.Writer;
+import java.net.InetAddress;
+import java.net.ServerSocket;
+import java.net.Socket;
+import java.net.SocketTimeoutException;
+import java.nio.charset.StandardCharsets;
+import java.nio.file.Files;
+import java.nio.file.Path;
+import java.nio.file.Paths;
+import java.nio.file.StandardCopyOption;
+import java.security.SecureRandom;
+import javax.tools.JavaCompiler;
+import javax.tools.ToolProvider;
+
+/**
+ * Long-lived javac server used by `jargo daemon`.
+ *
+ * Listens on a loopback port and writes "{port} {token} {java.home}" to the
+ * state file given as the first argument. Each connection sends the token, then one of
+ * PING, STOP, or COMPILE followed by the path of a javac argument file.
+ * COMPILE replies with the exit code on the first line, then javac's output.
+ */
+public final class CompileDaemon {
+    private static final int IDLE_TIMEOUT_MS = 3 * 60 * 60 * 1000;
+
+    public static void main(String[] args) throws Exception {
+        Path stateFile = Paths.get(args[0]);
+        JavaCompiler compiler = ToolProvider.getSystemJavaCompiler();
+        if (compiler == null) {
+            System.err.println("no system Java compiler available");
+            System.exit(1);
+        }
+
+        byte[] bytes = new byte[16];
+        new SecureRandom().nextBytes(bytes);
+        StringBuilder token = new StringBuilder();
+        for (byte b : bytes) {
+            token.append(String.format("%02x", b));
+        }
+
+        try (ServerSocket server = new ServerSocket(0, 50, InetAddress.getLoopbackAddress())) {
+            server.setSoTimeout(IDLE_TIMEOUT_MS);
+            Path tmp = stateFile.resolveSibling(stateFile.getFileName() + ".tmp");
+            String state = server.getLocalPort() + " " + token + " "
+                    + System.getProperty("java.home") + "\n";
+            Files.write(tmp, state.getBytes(StandardCharsets.UTF_8));
+            Files.move(tmp, stateFile, StandardCopyOption.REPLACE_EXISTING);
+
+            boolean running = true;
+            while (running) {
+                Socket socket;
+                try {
+                    socket = server.accept();
+                } catch (SocketTimeoutException e) {
+                    break;
+                }
+                try (Socket s = socket) {
+                    running = handle(s, compiler, token.toString());
+                } catch (IOException e) {
+                    // A misbehaving client must not take the daemon down.
+                }
+            }
+        } finally {
+            Files.deleteIfExists(stateFile);
+        }
+    }
+
+    /** Serve one request. Returns false when the daemon should stop. */
+    private static boolean handle(Socket socket, JavaCompiler compiler, String token)
+            throws IOException {
+        BufferedReader in = new BufferedReader(
+                new InputStreamReader(socket.getInputStream(), StandardCharsets.UTF_8));
+        Writer out = new OutputStreamWriter(socket.getOutputStream(), StandardCharsets.UTF_8);
+
+        if (!token.equals(in.readLine())) {
+            return true;
+        }
+
+        String command = in.readLine();
+        if ("PING".equals(command)) {
+            out.write("PONG\n");
+        } else if ("STOP".equals(command)) {
+            out.write("STOPPING\n");
+            out.flush();
+            return false;
+        } else if ("COMPILE".equals(command)) {
+            String argsFile = in.readLine();
+            ByteArrayOutputStream diagnostics = new ByteArrayOutputStream();
+            int code = compiler.run(null, diagnostics, diagnostics, "@" + argsFile);
+            out.write(code + "\n");
+            out.write(new String(diagnostics.toByteArray(), StandardCharsets.UTF_8));
+        }
+        out.flush();
+        return true;
+    }
+}
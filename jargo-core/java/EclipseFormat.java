@@ -0,0 +1,63 @@
+import java.nio.file.Files;
+import java.nio.file.Path;
+import java.util.HashMap;
+import java.util.Map;
+import javax.xml.parsers.DocumentBuilderFactory;
+import org.eclipse.jdt.core.ToolFactory;
+import org.eclipse.jdt.core.formatter.CodeFormatter;
+import org.eclipse.jface.text.Document;
+import org.eclipse.text.edits.TextEdit;
+import org.w3c.dom.Element;
+import org.w3c.dom.NodeList;
+
+/**
+ * Eclipse JDT formatter used by `jargo fmt` with `[fmt] formatter = "eclipse"`.
+ *
+ * Arguments: the formatter profile XML exported from Eclipse, the Java release
+ * to parse sources as, then the files to format in place. Exits with 1 when a
+ * file cannot be parsed, after formatting the others.
+ */
+public final class EclipseFormat {
+    public static void main(String[] args) throws Exception {
+        Map<String, String> options = new HashMap<>();
+        options.put("org.eclipse.jdt.core.compiler.source", args[1]);
+        options.put("org.eclipse.jdt.core.compiler.compliance", args[1]);
+        options.put("org.eclipse.jdt.core.compiler.codegen.targetPlatform", args[1]);
+        options.putAll(readProfile(Path.of(args[0])));
+        CodeFormatter formatter = ToolFactory.createCodeFormatter(options);
+
+        int failed = 0;
+        for (int i = 2; i < args.length; i++) {
+            Path file = Path.of(args[i]);
+            String source = Files.readString(file);
+            TextEdit edit = formatter.format(
+                    CodeFormatter.K_COMPILATION_UNIT | CodeFormatter.F_INCLUDE_COMMENTS,
+                    source, 0, source.length(), 0, "\n");
+            if (edit == null) {
+                System.err.println(args[i] + ": error: could not be parsed");
+                failed++;
+                continue;
+            }
+            Document document = new Document(source);
+            edit.apply(document);
+            Files.writeString(file, document.get());
+        }
+        if (failed > 0) {
+            System.exit(1);
+        }
+    }
+
+    /** The `<setting id=... value=...>` entries of the first profile. */
+    private static Map<String, String> readProfile(Path path) throws Exception {
+        NodeList settings = DocumentBuilderFactory.newInstance()
+                .newDocumentBuilder()
+                .parse(path.toFile())
+                .getElementsByTagName("setting");
+        Map<String, String> options = new HashMap<>();
+        for (int i = 0; i < settings.getLength(); i++) {
+            Element setting = (Element) settings.item(i);
+            options.putIfAbsent(setting.getAttribute("id"), setting.getAttribute("value"));
+        }
+        return options;
+    }
+}
@@ -0,0 +1,511 @@
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::jar::files_under;
+
+/// Where `jargo init` moves what is left of a re-rooted `src/`, such as
+/// resources that sat next to package-rooted sources.
+pub const LEFTOVER_DIR: &str = "src.orig";
+
+/// Source layouts `jargo init` recognizes in an existing directory.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LayoutKind {
+    /// `src/main/java` and `src/test/java`, with `resources` next to them,
+    /// as Maven and Gradle use.
+    Maven,
+    /// Sources under `src/` (and `test/`) in directories mirroring their
+    /// packages, as Ant and plain IDE projects use.
+    PackageRooted,
+    /// jargo's own layout: sources under `src/` relative to a base package.
+    Flat,
+}
+
+impl std::fmt::Display for LayoutKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            LayoutKind::Maven => "Maven",
+            LayoutKind::PackageRooted => "package-rooted",
+            LayoutKind::Flat => "jargo",
+        })
+    }
+}
+
+/// What `jargo init` found in a directory with Java sources, and how to
+/// move them into jargo's layout.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Adoption {
+    pub kind: LayoutKind,
+    /// Longest package every main source is in.
+    pub base_package: String,
+    /// The class with a `main` method, relative to `base_package`, preferring
+    /// one named `Main`. `None` for a library.
+    pub main_class: Option<String>,
+    /// Whether the test sources import TestNG rather than JUnit.
+    pub testng: bool,
+    /// Renames to run in order, relative to the project root.
+    pub moves: Vec<(PathBuf, PathBuf)>,
+}
+
+/// How the Java files under one source directory relate to their packages.
+#[derive(Debug, PartialEq)]
+enum SourceRoot {
+    /// No Java files.
+    Empty,
+    /// Directories mirror packages; the longest package all files share.
+    Mirrored(String),
+    /// Directories are relative to this base package, as jargo lays out.
+    Flat(String),
+}
+
+/// Inspect `project_root` for Java sources to adopt. `None` when there are
+/// none, so `jargo init` scaffolds a new package instead.
+///
+/// Fails when the sources fit no layout: files whose directory disagrees
+/// with their `package` declaration, classes in the default package, or
+/// test sources outside the base package.
+pub fn detect(project_root: &Path) -> Result<Option<Adoption>> {
+    let adoption = detect_layout(project_root)?;
+    if adoption.as_ref().is_some_and(|a| !a.moves.is_empty())
+        && project_root.join(LEFTOVER_DIR).exists()
+    {
+        bail!(
+            "cannot move the sources into place: {}/ already exists",
+            LEFTOVER_DIR
+        );
+    }
+    Ok(adoption)
+}
+
+fn detect_layout(project_root: &Path) -> Result<Option<Adoption>> {
+    let maven_main = project_root.join("src/main/java");
+    if maven_main.is_dir() {
+        return detect_maven(project_root).map(Some);
+    }
+
+    let src = project_root.join("src");
+    let (kind, base_package) = match analyze(&src)? {
+        SourceRoot::Empty => return Ok(None),
+        SourceRoot::Mirrored(base) => (LayoutKind::PackageRooted, base),
+        SourceRoot::Flat(base) => (LayoutKind::Flat, base),
+    };
+    let mut moves = Vec::new();
+    if kind == LayoutKind::PackageRooted {
+        moves.extend(reroot("src", &base_package));
+    }
+    match analyze(&project_root.join("test"))? {
+        SourceRoot::Empty => {}
+        SourceRoot::Flat(base) if base == base_package => {}
+        SourceRoot::Mirrored(base) if is_within(&base, &base_package) => {
+            moves.extend(reroot("test", &base_package));
+        }
+        _ => bail!(
+            "test sources in test/ are not in the base package `{}`",
+            base_package
+        ),
+    }
+
+    Ok(Some(Adoption {
+        kind,
+        main_class: find_main_class(&src, &base_package, kind != LayoutKind::Flat)?,
+        testng: uses_testng(&project_root.join("test"))?,
+        base_package,
+        moves,
+    }))
+}
+
+fn detect_maven(project_root: &Path) -> Result<Adoption> {
+    let main = project_root.join("src/main/java");
+    let test = project_root.join("src/test/java");
+    let main_base = match analyze(&main)? {
+        SourceRoot::Mirrored(base) => Some(base),
+        SourceRoot::Empty => None,
+        SourceRoot::Flat(_) => {
+            bail!("sources in src/main/java are not in directories matching their packages")
+        }
+    };
+    let test_base = match analyze(&test)? {
+        SourceRoot::Mirrored(base) => Some(base),
+        SourceRoot::Empty => None,
+        SourceRoot::Flat(_) => {
+            bail!("sources in src/test/java are not in directories matching their packages")
+        }
+    };
+    let has_main = main_base.is_some();
+    let has_test = test_base.is_some();
+    let base_package = match (main_base, &test_base) {
+        (Some(base), _) => base,
+        (None, Some(base)) => base.clone(),
+        (None, None) => bail!("src/main/java has no Java sources"),
+    };
+    if test_base.is_some_and(|base| !is_within(&base, &base_package)) {
+        bail!(
+            "test sources in src/test/java are not in the base package `{}`",
+            base_package
+        );
+    }
+
+    for dir in ["test", "resources", "test-resources"] {
+        if project_root.join(dir).exists() {
+            bail!(
+                "cannot move the Maven layout into place: {}/ already exists",
+                dir
+            );
+        }
+    }
+    let orig = Path::new(LEFTOVER_DIR);
+    let package_path = base_package.replace('.', "/");
+    let mut moves = vec![(PathBuf::from("src"), orig.to_path_buf())];
+    if has_main {
+        moves.push((orig.join("main/java").join(&package_path), "src".into()));
+    }
+    if has_test {
+        moves.push((orig.join("test/java").join(&package_path), "test".into()));
+    }
+    for (from, to) in [
+        ("main/resources", "resources"),
+        ("test/resources", "test-resources"),
+    ] {
+        if project_root.join("src").join(from).is_dir() {
+            moves.push((orig.join(from), to.into()));
+        }
+    }
+
+    Ok(Adoption {
+        kind: LayoutKind::Maven,
+        main_class: find_main_class(&main, &base_package, true)?,
+        testng: uses_testng(&test)?,
+        base_package,
+        moves,
+    })
+}
+
+/// Run the renames of `adoption` in `project_root`, then remove
+/// [`LEFTOVER_DIR`] if only empty directories remain in it. Returns whether
+/// it still holds files.
+pub fn apply(project_root: &Path, adoption: &Adoption) -> Result<bool> {
+    for (from, to) in &adoption.moves {
+        let (from, to) = (project_root.join(from), project_root.join(to));
+        if let Some(parent) = to.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        fs::rename(&from, &to)
+            .with_context(|| format!("failed to move {} to {}", from.display(), to.display()))?;
+    }
+    let leftover = project_root.join(LEFTOVER_DIR);
+    if !leftover.exists() {
+        return Ok(false);
+    }
+    if !files_under(&leftover)?.is_empty() {
+        return Ok(true);
+    }
+    fs::remove_dir_all(&leftover)
+        .with_context(|| format!("failed to remove {}", leftover.display()))?;
+    Ok(false)
+}
+
+/// Renames making `{dir}/{base-package-as-path}` the new `{dir}`. What
+/// remains of the old one ends up in [`LEFTOVER_DIR`].
+fn reroot(dir: &str, base_package: &str) -> Vec<(PathBuf, PathBuf)> {
+    let holding = if dir == "src" {
+        PathBuf::from(LEFTOVER_DIR)
+    } else {
+        PathBuf::from(format!("{}.orig", dir))
+    };
+    let mut moves = vec![(PathBuf::from(dir), holding.clone())];
+    moves.push((holding.join(base_package.replace('.', "/")), dir.into()));
+    if dir != "src" {
+        // Nothing but the packages above the base lives in test/
+        moves.push((holding.clone(), PathBuf::from(LEFTOVER_DIR).join(dir)));
+    }
+    moves
+}
+
+fn analyze(dir: &Path) -> Result<SourceRoot> {
+    if !dir.is_dir() {
+        return Ok(SourceRoot::Empty);
+    }
+    let mut bases: Vec<String> = Vec::new();
+    let mut packages: Vec<String> = Vec::new();
+    for path in java_files(dir)? {
+        let source = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let package = declared_package(&source).unwrap_or_default();
+        let relative = path.strip_prefix(dir).expect("under dir");
+        let dirs: Vec<String> = relative
+            .parent()
+            .into_iter()
+            .flat_map(|p| p.components())
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        let segments: Vec<&str> = package.split('.').filter(|s| !s.is_empty()).collect();
+        if segments.len() < dirs.len() || segments[segments.len() - dirs.len()..] != dirs {
+            bail!(
+                "{} declares package `{}`, which does not match its directory",
+                path.display(),
+                package
+            );
+        }
+        bases.push(segments[..segments.len() - dirs.len()].join("."));
+        packages.push(package);
+    }
+    if packages.is_empty() {
+        return Ok(SourceRoot::Empty);
+    }
+
+    if bases.iter().all(|b| b.is_empty()) {
+        let common = common_package(&packages);
+        if common.is_empty() {
+            bail!(
+                "the sources in {} share no package; jargo needs a base package",
+                dir.display()
+            );
+        }
+        return Ok(SourceRoot::Mirrored(common));
+    }
+    let base = &bases[0];
+    if bases.iter().any(|b| b != base) {
+        bail!(
+            "the sources in {} do not share a base package (found `{}` and `{}`)",
+            dir.display(),
+            base,
+            bases.iter().find(|b| *b != base).expect("a differing base")
+        );
+    }
+    Ok(SourceRoot::Flat(base.clone()))
+}
+
+/// `.java` files under `dir`, leaving out `module-info.java`.
+fn java_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    Ok(files_under(dir)?
+        .into_iter()
+        .filter(|p| p.extension().is_some_and(|e| e == "java"))
+        .filter(|p| p.file_name().is_some_and(|n| n != "module-info.java"))
+        .collect())
+}
+
+/// The longest package every one of `packages` is in or below.
+fn common_package(packages: &[String]) -> String {
+    let mut common: Vec<&str> = packages[0].split('.').collect();
+    for package in &packages[1..] {
+        let segments: Vec<&str> = package.split('.').collect();
+        let shared = common
+            .iter()
+            .zip(&segments)
+            .take_while(|(a, b)| a == b)
+            .count();
+        common.truncate(shared);
+    }
+    common.join(".")
+}
+
+/// Whether `package` is `base` or one of its subpackages.
+fn is_within(package: &str, base: &str) -> bool {
+    package == base
+        || package
+            .strip_prefix(base)
+            .is_some_and(|rest| rest.starts_with('.'))
+}
+
+/// The `package` a Java source declares, skipping leading comments and
+/// annotations. `None` for the default package.
+fn declared_package(source: &str) -> Option<String> {
+    let mut rest = source.trim_start_matches('\u{feff}');
+    loop {
+        rest = rest.trim_start();
+        if let Some(after) = rest.strip_prefix("//") {
+            rest = after.split_once('\n').map_or("", |(_, r)| r);
+        } else if let Some(after) = rest.strip_prefix("/*") {
+            rest = after.split_once("*/").map_or("", |(_, r)| r);
+        } else if rest.starts_with('@') {
+            rest = rest.split_once('\n').map_or("", |(_, r)| r);
+        } else {
+            break;
+        }
+    }
+    let declaration = rest.strip_prefix("package")?;
+    if !declaration.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let (name, _) = declaration.split_once(';')?;
+    Some(name.chars().filter(|c| !c.is_whitespace()).collect())
+}
+
+/// The class with `static void main` under `dir`, relative to
+/// `base_package`. In a mirrored `dir`, the base package's directories come
+/// first and are dropped.
+fn find_main_class(dir: &Path, base_package: &str, mirrored: bool) -> Result<Option<String>> {
+    let root = if mirrored {
+        dir.join(base_package.replace('.', "/"))
+    } else {
+        dir.to_path_buf()
+    };
+    if !root.is_dir() {
+        return Ok(None);
+    }
+    let mut found = Vec::new();
+    for path in java_files(&root)? {
+        let source = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        if source.contains("static void main(") {
+            let relative = path
+                .strip_prefix(&root)
+                .expect("under root")
+                .with_extension("");
+            let class: Vec<String> = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect();
+            found.push(class.join("."));
+        }
+    }
+    Ok(found
+        .iter()
+        .find(|class| *class == "Main")
+        .or(found.first())
+        .cloned())
+}
+
+fn uses_testng(dir: &Path) -> Result<bool> {
+    if !dir.is_dir() {
+        return Ok(false);
+    }
+    for path in java_files(dir)? {
+        let source = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        if source.contains("import org.testng.") {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, path: &str, contents: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn test_declared_package() {
+        assert_eq!(
+            declared_package("/* header */\n// more\npackage com.acme . app ;\n"),
+            Some("com.acme.app".to_string())
+        );
+        assert_eq!(
+            declared_package("@Deprecated\npackage com.acme;\n"),
+            Some("com.acme".to_string())
+        );
+        assert_eq!(
+            declared_package("import java.util.List;\nclass A {}\n"),
+            None
+        );
+        assert_eq!(declared_package("packageless;"), None);
+    }
+
+    #[test]
+    fn test_detect_maven() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        write(
+            root,
+            "src/main/java/com/acme/app/App.java",
+            "package com.acme.app;\nclass App { public static void main(String[] a) {} }\n",
+        );
+        write(
+            root,
+            "src/main/java/com/acme/app/util/Strings.java",
+            "package com.acme.app.util;\n",
+        );
+        write(
+            root,
+            "src/test/java/com/acme/app/AppTest.java",
+            "package com.acme.app;\nimport org.testng.annotations.Test;\n",
+        );
+        write(root, "src/main/resources/app.properties", "x=1\n");
+
+        let adoption = detect(root).unwrap().unwrap();
+        assert_eq!(adoption.kind, LayoutKind::Maven);
+        assert_eq!(adoption.base_package, "com.acme.app");
+        assert_eq!(adoption.main_class.as_deref(), Some("App"));
+        assert!(adoption.testng);
+
+        assert!(!apply(root, &adoption).unwrap());
+        assert!(root.join("src/App.java").is_file());
+        assert!(root.join("src/util/Strings.java").is_file());
+        assert!(root.join("test/AppTest.java").is_file());
+        assert!(root.join("resources/app.properties").is_file());
+        assert!(!root.join(LEFTOVER_DIR).exists());
+        assert_eq!(detect(root).unwrap().unwrap().kind, LayoutKind::Flat);
+    }
+
+    #[test]
+    fn test_detect_package_rooted() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        write(
+            root,
+            "src/org/demo/Main.java",
+            "package org.demo;\nclass Main { static void main(String[] a) {} }\n",
+        );
+        write(
+            root,
+            "src/org/demo/cli/Tool.java",
+            "package org.demo.cli;\nclass Tool { public static void main(String[] a) {} }\n",
+        );
+        write(root, "src/log4j.properties", "x=1\n");
+        write(root, "test/org/demo/MainTest.java", "package org.demo;\n");
+
+        let adoption = detect(root).unwrap().unwrap();
+        assert_eq!(adoption.kind, LayoutKind::PackageRooted);
+        assert_eq!(adoption.base_package, "org.demo");
+        assert_eq!(adoption.main_class.as_deref(), Some("Main"));
+        assert!(!adoption.testng);
+
+        // The properties file is kept for the user to move
+        assert!(apply(root, &adoption).unwrap());
+        assert!(root.join("src/cli/Tool.java").is_file());
+        assert!(root.join("test/MainTest.java").is_file());
+        assert!(root.join(LEFTOVER_DIR).join("log4j.properties").is_file());
+        assert!(!root.join("test.orig").exists());
+    }
+
+    #[test]
+    fn test_detect_flat_and_errors() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        assert_eq!(detect(root).unwrap(), None);
+
+        write(root, "src/Lib.java", "package com.acme.lib;\n");
+        write(root, "src/io/Reader.java", "package com.acme.lib.io;\n");
+        let adoption = detect(root).unwrap().unwrap();
+        assert_eq!(adoption.kind, LayoutKind::Flat);
+        assert_eq!(adoption.base_package, "com.acme.lib");
+        assert_eq!(adoption.main_class, None);
+        assert!(adoption.moves.is_empty());
+
+        write(root, "src/io/Writer.java", "package com.acme.io;\n");
+        assert!(detect(root)
+            .unwrap_err()
+            .to_string()
+            .contains("do not share a base package"));
+        fs::remove_file(root.join("src/io/Writer.java")).unwrap();
+        write(root, "src/io/Writer.java", "package com.acme.lib;\n");
+        assert!(detect(root)
+            .unwrap_err()
+            .to_string()
+            .contains("does not match its directory"));
+        fs::remove_file(root.join("src/io/Writer.java")).unwrap();
+
+        write(root, "test/OtherTest.java", "package org.other;\n");
+        assert!(detect(root)
+            .unwrap_err()
+            .to_string()
+            .contains("not in the base package"));
+    }
+}
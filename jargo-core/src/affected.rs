@@ -0,0 +1,342 @@
+use anyhow::{bail, Context, Result};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use crate::toolchain::Toolchain;
+
+/// Which tests are affected by a set of changes.
+#[derive(Debug, PartialEq)]
+pub enum AffectedTests {
+    /// A change that cannot be traced to classes (manifest, lock file,
+    /// resources, codegen inputs) affects every test. Carries the path that
+    /// triggered it.
+    All(String),
+    /// Fully-qualified test classes whose code or dependencies changed.
+    Classes(Vec<String>),
+}
+
+/// Compute the test classes affected by changes since the git ref `since`.
+///
+/// Requires `target/classes/` and `target/test-classes/` to be compiled. Class
+/// dependencies come from `jdeps`; a test is affected when it changed itself or
+/// transitively depends on a changed main class. Constants inlined by `javac`
+/// leave no class reference behind and are not tracked.
+pub fn affected_tests(
+    project_root: &Path,
+    toolchain: &Toolchain,
+    base_package: &str,
+    since: &str,
+) -> Result<AffectedTests> {
+    let changed = changed_files(project_root, since)?;
+
+    let mut changed_classes = HashSet::new();
+    for path in &changed {
+        if let Some(trigger) = global_trigger(path) {
+            return Ok(AffectedTests::All(trigger.to_string()));
+        }
+        if let Some(class) = source_file_class(path, base_package) {
+            changed_classes.insert(class);
+        }
+    }
+
+    let classes_dir = project_root.join("target/classes");
+    let test_classes_dir = project_root.join("target/test-classes");
+
+    let mut graph = HashMap::new();
+    for dir in [&classes_dir, &test_classes_dir] {
+        if dir.is_dir() {
+            for (from, to) in parse_jdeps(&run_jdeps(toolchain, dir)?) {
+                graph.entry(to).or_insert_with(HashSet::new).insert(from);
+            }
+        }
+    }
+
+    let test_classes = list_test_classes(&test_classes_dir)?;
+    Ok(AffectedTests::Classes(select_affected(
+        &graph,
+        &changed_classes,
+        &test_classes,
+    )))
+}
+
+/// Files changed relative to `since`, including uncommitted and untracked
+/// files, as paths relative to `project_root`.
+fn changed_files(project_root: &Path, since: &str) -> Result<Vec<String>> {
+    let diff = git(
+        project_root,
+        &["diff", "--name-only", "--relative", since, "--"],
+    )?;
+    let untracked = git(
+        project_root,
+        &["ls-files", "--others", "--exclude-standard"],
+    )?;
+    Ok(diff
+        .lines()
+        .chain(untracked.lines())
+        .filter(|l| !l.is_empty())
+        .map(|l| l.to_string())
+        .collect())
+}
+
+/// Run `git` in `project_root` and return its stdout.
+pub(crate) fn git(project_root: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(project_root)
+        .output()
+        .context("failed to run git")?;
+    if !output.status.success() {
+        bail!(
+            "`git {}` failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Source roots with the flat layout. Classes from `it/`, `examples/`, and
+/// `bench/` are not on the unit test classpath, so they reach no test.
+const SOURCE_ROOTS: [&str; 5] = ["src/", "test/", "it/", "examples/", "bench/"];
+
+/// Parent of the `[[codegen]]` output roots, which keep their own packages.
+const GENERATED_ROOT: &str = "target/generated-sources/";
+
+/// Return the path itself when a change to it invalidates every test: any
+/// change but a source file [`source_file_class`] maps, or other build
+/// output under `target/`.
+fn global_trigger(path: &str) -> Option<&str> {
+    let traced = path.ends_with(".java")
+        && (SOURCE_ROOTS.iter().any(|root| path.starts_with(root))
+            || path.starts_with(GENERATED_ROOT));
+    let global = !traced && !path.starts_with("target/");
+    global.then_some(path)
+}
+
+/// Map a source file to its top-level class name.
+///
+/// `("src/util/Helper.java", "myapp")` → `"myapp.util.Helper"`;
+/// `("target/generated-sources/api/com/acme/Api.java", _)` → `"com.acme.Api"`
+fn source_file_class(path: &str, base_package: &str) -> Option<String> {
+    if let Some(rest) = path.strip_prefix(GENERATED_ROOT) {
+        let (_, rel) = rest.split_once('/')?;
+        return Some(rel.strip_suffix(".java")?.replace('/', "."));
+    }
+    let rel = SOURCE_ROOTS
+        .iter()
+        .find_map(|root| path.strip_prefix(root))?;
+    let stem = rel.strip_suffix(".java")?;
+    Some(format!("{}.{}", base_package, stem.replace('/', ".")))
+}
+
+fn run_jdeps(toolchain: &Toolchain, dir: &Path) -> Result<String> {
+    let output = Command::new(toolchain.tool("jdeps"))
+        .args(["-verbose:class", "-filter:none"])
+        .arg(dir)
+        .output()
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                anyhow::anyhow!("jdeps not found in PATH")
+            } else {
+                anyhow::Error::from(e).context("failed to run jdeps")
+            }
+        })?;
+    if !output.status.success() {
+        bail!(
+            "jdeps failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Parse `jdeps -verbose:class` output into `(from, to)` edges between
+/// top-level classes. Nested classes are folded into their outer class.
+fn parse_jdeps(output: &str) -> Vec<(String, String)> {
+    output
+        .lines()
+        // Summary lines ("classes -> java.base") are not indented.
+        .filter(|line| line.starts_with(char::is_whitespace))
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let from = parts.next()?;
+            if parts.next()? != "->" {
+                return None;
+            }
+            let to = parts.next()?;
+            let (from, to) = (top_level(from), top_level(to));
+            (from != to).then(|| (from.to_string(), to.to_string()))
+        })
+        .collect()
+}
+
+fn top_level(class: &str) -> &str {
+    class.split('$').next().unwrap_or(class)
+}
+
+/// List top-level classes under `test_classes_dir` that match JUnit's default
+/// test class naming (`Test*`, `*Test`, `*Tests`).
+pub(crate) fn list_test_classes(test_classes_dir: &Path) -> Result<Vec<String>> {
+    let mut classes = Vec::new();
+    if test_classes_dir.is_dir() {
+        collect_classes(test_classes_dir, test_classes_dir, &mut classes)?;
+    }
+    classes.retain(|c| {
+        let simple = c.rsplit('.').next().unwrap_or(c);
+        simple.starts_with("Test") || simple.ends_with("Test") || simple.ends_with("Tests")
+    });
+    classes.sort();
+    Ok(classes)
+}
+
+fn collect_classes(dir: &Path, base: &Path, out: &mut Vec<String>) -> Result<()> {
+    for entry in
+        fs::read_dir(dir).with_context(|| format!("failed to read directory {}", dir.display()))?
+    {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_classes(&path, base, out)?;
+        } else if path.extension().is_some_and(|e| e == "class") {
+            let rel = path
+                .strip_prefix(base)
+                .with_context(|| "failed to compute relative path")?
+                .with_extension("");
+            let name = rel.to_string_lossy().replace(['/', '\\'], ".");
+            if !name.contains('$') {
+                out.push(name);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Walk reverse dependency edges from the changed classes and return the test
+/// classes reached (including changed tests themselves), sorted.
+fn select_affected(
+    dependents: &HashMap<String, HashSet<String>>,
+    changed: &HashSet<String>,
+    test_classes: &[String],
+) -> Vec<String> {
+    let mut reached: HashSet<&str> = changed.iter().map(String::as_str).collect();
+    let mut queue: VecDeque<&str> = reached.iter().copied().collect();
+
+    while let Some(class) = queue.pop_front() {
+        if let Some(users) = dependents.get(class) {
+            for user in users {
+                if reached.insert(user) {
+                    queue.push_back(user);
+                }
+            }
+        }
+    }
+
+    test_classes
+        .iter()
+        .filter(|t| reached.contains(t.as_str()))
+        .cloned()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_file_class() {
+        assert_eq!(
+            source_file_class("src/Main.java", "myapp"),
+            Some("myapp.Main".to_string())
+        );
+        assert_eq!(
+            source_file_class("src/util/Helper.java", "com.example"),
+            Some("com.example.util.Helper".to_string())
+        );
+        assert_eq!(
+            source_file_class("test/MainTest.java", "myapp"),
+            Some("myapp.MainTest".to_string())
+        );
+        assert_eq!(
+            source_file_class("it/db/RepositoryIT.java", "myapp"),
+            Some("myapp.db.RepositoryIT".to_string())
+        );
+        assert_eq!(
+            source_file_class("examples/Hello.java", "myapp"),
+            Some("myapp.Hello".to_string())
+        );
+        assert_eq!(
+            source_file_class("target/generated-sources/api/com/acme/Api.java", "myapp"),
+            Some("com.acme.Api".to_string())
+        );
+        assert_eq!(source_file_class("README.md", "myapp"), None);
+        assert_eq!(source_file_class("src/notes.txt", "myapp"), None);
+    }
+
+    #[test]
+    fn test_global_trigger() {
+        assert_eq!(global_trigger("Jargo.toml"), Some("Jargo.toml"));
+        assert_eq!(global_trigger("Jargo.lock"), Some("Jargo.lock"));
+        assert!(global_trigger("resources/app.properties").is_some());
+        assert!(global_trigger("it-resources/db.sql").is_some());
+        assert!(global_trigger("proto/api.proto").is_some());
+        assert!(global_trigger("src/notes.txt").is_some());
+        assert!(global_trigger("src/Main.java").is_none());
+        assert!(global_trigger("bench/ParseBench.java").is_none());
+        assert!(global_trigger("target/generated-sources/api/com/acme/Api.java").is_none());
+        assert!(global_trigger("target/classes/myapp/Main.class").is_none());
+    }
+
+    #[test]
+    fn test_parse_jdeps() {
+        let output = "\
+classes -> java.base
+   demo.Main                 -> demo.util.Helper          classes
+   demo.Main                 -> java.lang.Object          java.base
+   demo.util.Helper          -> demo.util.Helper$Inner    classes
+   demo.util.Helper$Inner    -> demo.Main                 classes
+";
+        let edges = parse_jdeps(output);
+        assert_eq!(
+            edges,
+            vec![
+                ("demo.Main".to_string(), "demo.util.Helper".to_string()),
+                ("demo.Main".to_string(), "java.lang.Object".to_string()),
+                ("demo.util.Helper".to_string(), "demo.Main".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_select_affected_transitive() {
+        // CalcTest -> Calc -> MathUtil; OtherTest -> Other
+        let mut dependents: HashMap<String, HashSet<String>> = HashMap::new();
+        for (from, to) in [
+            ("app.CalcTest", "app.Calc"),
+            ("app.Calc", "app.MathUtil"),
+            ("app.OtherTest", "app.Other"),
+        ] {
+            dependents
+                .entry(to.to_string())
+                .or_default()
+                .insert(from.to_string());
+        }
+        let tests = vec!["app.CalcTest".to_string(), "app.OtherTest".to_string()];
+
+        let changed = HashSet::from(["app.MathUtil".to_string()]);
+        assert_eq!(
+            select_affected(&dependents, &changed, &tests),
+            vec!["app.CalcTest"]
+        );
+
+        let changed = HashSet::from(["app.OtherTest".to_string()]);
+        assert_eq!(
+            select_affected(&dependents, &changed, &tests),
+            vec!["app.OtherTest"]
+        );
+
+        assert!(select_affected(&dependents, &HashSet::new(), &tests).is_empty());
+    }
+}
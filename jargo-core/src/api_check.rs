@@ -0,0 +1,599 @@
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use crate::bytecode::{self, Constant};
+use crate::resolver;
+
+const ACC_PUBLIC: u16 = 0x0001;
+const ACC_PROTECTED: u16 = 0x0004;
+const ACC_STATIC: u16 = 0x0008;
+const ACC_FINAL: u16 = 0x0010;
+const ACC_BRIDGE: u16 = 0x0040;
+const ACC_INTERFACE: u16 = 0x0200;
+const ACC_ABSTRACT: u16 = 0x0400;
+const ACC_SYNTHETIC: u16 = 0x1000;
+const ACC_ANNOTATION: u16 = 0x2000;
+const ACC_ENUM: u16 = 0x4000;
+
+/// The public API of a JAR: every public class, by binary name
+/// (`com.example.Parser$Options`).
+pub type Api = BTreeMap<String, ClassApi>;
+
+/// What other code can see of one public class.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassApi {
+    pub access: u16,
+    /// Binary name of the superclass; `None` for `java.lang.Object` itself.
+    pub superclass: Option<String>,
+    pub interfaces: Vec<String>,
+    /// Public and protected fields and methods, keyed by name and
+    /// descriptor, with their access flags.
+    pub members: BTreeMap<MemberKey, u16>,
+}
+
+/// A field or method, by name and JVM descriptor.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MemberKey {
+    pub name: String,
+    pub descriptor: String,
+}
+
+impl MemberKey {
+    fn is_method(&self) -> bool {
+        self.descriptor.starts_with('(')
+    }
+
+    /// Java-like rendering in `class`: `java.lang.String parse(int)`,
+    /// `Parser(java.lang.String)` for a constructor, `int MAX` for a field.
+    fn display(&self, class: &str) -> String {
+        let simple = class.rsplit(['.', '$']).next().unwrap_or(class);
+        if !self.is_method() {
+            let mut chars = self.descriptor.chars().peekable();
+            return format!("{} {}", java_type(&mut chars), self.name);
+        }
+        let mut chars = self.descriptor[1..].chars().peekable();
+        let mut params = Vec::new();
+        while chars.peek().is_some_and(|&c| c != ')') {
+            params.push(java_type(&mut chars));
+        }
+        chars.next();
+        let params = params.join(", ");
+        if self.name == "<init>" {
+            format!("{}({})", simple, params)
+        } else {
+            format!("{} {}({})", java_type(&mut chars), self.name, params)
+        }
+    }
+}
+
+/// Read one type from a JVM descriptor as Java source spells it.
+fn java_type(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) -> String {
+    match chars.next() {
+        Some('B') => "byte".to_string(),
+        Some('C') => "char".to_string(),
+        Some('D') => "double".to_string(),
+        Some('F') => "float".to_string(),
+        Some('I') => "int".to_string(),
+        Some('J') => "long".to_string(),
+        Some('S') => "short".to_string(),
+        Some('Z') => "boolean".to_string(),
+        Some('V') => "void".to_string(),
+        Some('[') => format!("{}[]", java_type(chars)),
+        Some('L') => chars
+            .by_ref()
+            .take_while(|&c| c != ';')
+            .map(|c| if c == '/' { '.' } else { c })
+            .collect(),
+        _ => "?".to_string(),
+    }
+}
+
+/// One difference between two versions of an API.
+#[derive(Debug, PartialEq)]
+pub enum ApiChange {
+    ClassRemoved(String),
+    ClassAdded(String),
+    /// A class became an interface, enum, or annotation, or the reverse.
+    KindChanged(String),
+    ClassMadeFinal(String),
+    ClassMadeAbstract(String),
+    SuperclassChanged {
+        class: String,
+        old: String,
+        new: String,
+    },
+    InterfaceRemoved {
+        class: String,
+        interface: String,
+    },
+    /// A member was removed, made package-private or private, or changed
+    /// its parameter, return, or field type.
+    MemberRemoved {
+        class: String,
+        member: String,
+    },
+    MemberAdded {
+        class: String,
+        member: String,
+    },
+    /// An abstract method subclasses and implementations must now provide.
+    AbstractMethodAdded {
+        class: String,
+        member: String,
+    },
+    MemberMadeProtected {
+        class: String,
+        member: String,
+    },
+    MemberMadeFinal {
+        class: String,
+        member: String,
+    },
+    MemberStaticChanged {
+        class: String,
+        member: String,
+    },
+}
+
+impl ApiChange {
+    /// Whether code compiled against the old API can break with the new one.
+    pub fn is_breaking(&self) -> bool {
+        !matches!(
+            self,
+            ApiChange::ClassAdded(_) | ApiChange::MemberAdded { .. }
+        )
+    }
+}
+
+impl fmt::Display for ApiChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiChange::ClassRemoved(class) => write!(f, "class {} was removed", class),
+            ApiChange::ClassAdded(class) => write!(f, "class {} was added", class),
+            ApiChange::KindChanged(class) => {
+                write!(
+                    f,
+                    "{} changed between class, interface, enum, and annotation",
+                    class
+                )
+            }
+            ApiChange::ClassMadeFinal(class) => write!(f, "class {} was made final", class),
+            ApiChange::ClassMadeAbstract(class) => {
+                write!(f, "class {} was made abstract", class)
+            }
+            ApiChange::SuperclassChanged { class, old, new } => {
+                write!(f, "superclass of {} changed from {} to {}", class, old, new)
+            }
+            ApiChange::InterfaceRemoved { class, interface } => {
+                write!(f, "{} no longer implements {}", class, interface)
+            }
+            ApiChange::MemberRemoved { class, member } => {
+                write!(f, "{}: `{}` was removed", class, member)
+            }
+            ApiChange::MemberAdded { class, member } => {
+                write!(f, "{}: `{}` was added", class, member)
+            }
+            ApiChange::AbstractMethodAdded { class, member } => {
+                write!(f, "{}: abstract `{}` was added", class, member)
+            }
+            ApiChange::MemberMadeProtected { class, member } => {
+                write!(f, "{}: `{}` was made protected", class, member)
+            }
+            ApiChange::MemberMadeFinal { class, member } => {
+                write!(f, "{}: `{}` was made final", class, member)
+            }
+            ApiChange::MemberStaticChanged { class, member } => {
+                write!(
+                    f,
+                    "{}: `{}` changed between static and instance",
+                    class, member
+                )
+            }
+        }
+    }
+}
+
+/// How far a version moved: semver major, minor, or patch. Before 1.0.0 a
+/// minor change counts as major and a patch change as minor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Level::Patch => "patch",
+            Level::Minor => "minor",
+            Level::Major => "major",
+        })
+    }
+}
+
+/// The level a release with `changes` needs: major with a breaking change,
+/// minor with additions, else patch.
+pub fn required_level(changes: &[ApiChange]) -> Level {
+    if changes.iter().any(ApiChange::is_breaking) {
+        Level::Major
+    } else if changes.is_empty() {
+        Level::Patch
+    } else {
+        Level::Minor
+    }
+}
+
+/// The level of the change from version `old` to `new`.
+pub fn bump_level(old: &str, new: &str) -> Level {
+    let parts = |v: &str| -> [u64; 3] {
+        let numeric = &v[..v.find(['-', '+']).unwrap_or(v.len())];
+        let mut parts = [0; 3];
+        for (part, value) in parts.iter_mut().zip(numeric.split('.')) {
+            *part = value.parse().unwrap_or(0);
+        }
+        parts
+    };
+    let (old, new) = (parts(old), parts(new));
+    let level = if old[0] != new[0] {
+        Level::Major
+    } else if old[1] != new[1] {
+        Level::Minor
+    } else {
+        Level::Patch
+    };
+    match (old[0], level) {
+        (0, Level::Minor) => Level::Major,
+        (0, Level::Patch) if old[1] > 0 || old[2] != new[2] => Level::Minor,
+        _ => level,
+    }
+}
+
+/// Why releasing `changes` as `version` after `baseline` breaks semver, or
+/// `None` when the bump is big enough.
+pub fn semver_violation(baseline: &str, version: &str, changes: &[ApiChange]) -> Option<String> {
+    let bump = bump_level(baseline, version);
+    let required = required_level(changes);
+    (bump < required).then(|| {
+        format!(
+            "`{}` is a {} release after `{}`, but the API changes need a {} release",
+            version, bump, baseline, required
+        )
+    })
+}
+
+/// The newest release in `versions` that is older than `current`: the
+/// baseline a new version is compared with. Snapshots are ignored.
+pub fn baseline(versions: &[String], current: &str) -> Option<String> {
+    versions
+        .iter()
+        .filter(|v| !v.ends_with("-SNAPSHOT") && resolver::version_gt(current, v))
+        .fold(None, |newest: Option<&String>, v| match newest {
+            Some(n) if !resolver::version_gt(v, n) => Some(n),
+            _ => Some(v),
+        })
+        .cloned()
+}
+
+/// Read the public API from the class files in `jar`. Multi-release entries,
+/// `module-info`, and `package-info` are skipped.
+pub fn read_jar(jar: &Path) -> Result<Api> {
+    let file = File::open(jar).with_context(|| format!("failed to open {}", jar.display()))?;
+    let mut archive =
+        zip::ZipArchive::new(file).with_context(|| format!("failed to read {}", jar.display()))?;
+    let mut api = Api::new();
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        let name = entry.name().to_string();
+        if !name.ends_with(".class")
+            || name.ends_with("module-info.class")
+            || name.ends_with("package-info.class")
+            || bytecode::is_versioned_entry(&name)
+        {
+            continue;
+        }
+        let mut class = Vec::new();
+        entry
+            .read_to_end(&mut class)
+            .with_context(|| format!("failed to read {} in {}", name, jar.display()))?;
+        let parsed = read_class(&class).with_context(|| format!("failed to parse {}", name))?;
+        if let Some((class_name, class_api)) = parsed {
+            api.insert(class_name, class_api);
+        }
+    }
+    Ok(api)
+}
+
+/// The binary name and API of a class file, or `None` when it is not public.
+fn read_class(class: &[u8]) -> Result<Option<(String, ClassApi)>> {
+    let (constants, end) = bytecode::constant_pool(class)?;
+    let access = bytecode::u16_at(class, end)?;
+    if access & ACC_PUBLIC == 0 || access & ACC_SYNTHETIC != 0 {
+        return Ok(None);
+    }
+    let class_name = |index: u16| -> Result<String> {
+        let &(_, _, start, _) = constants
+            .iter()
+            .find(|&&(i, tag, _, _)| i == index && tag == 7)
+            .context("class constant not found")?;
+        let name_index = bytecode::u16_at(class, start + 1)?;
+        let name = bytecode::utf8_constant(class, &constants, name_index)
+            .context("class name not found")?;
+        Ok(name.replace('/', "."))
+    };
+    let name = class_name(bytecode::u16_at(class, end + 2)?)?;
+    let superclass = match bytecode::u16_at(class, end + 4)? {
+        0 => None,
+        index => Some(class_name(index)?),
+    };
+    let mut pos = end + 6;
+    let interface_count = bytecode::u16_at(class, pos)?;
+    let mut interfaces = Vec::new();
+    for i in 0..usize::from(interface_count) {
+        interfaces.push(class_name(bytecode::u16_at(class, pos + 2 + 2 * i)?)?);
+    }
+    pos += 2 + 2 * usize::from(interface_count);
+
+    let mut members = BTreeMap::new();
+    let mut visit = |access: u16, name: u16, descriptor: u16| {
+        visible_member(class, &constants, &mut members, access, name, descriptor)
+    };
+    pos = bytecode::walk_members(class, pos, &mut visit)?;
+    bytecode::walk_members(class, pos, &mut visit)?;
+
+    Ok(Some((
+        name,
+        ClassApi {
+            access,
+            superclass,
+            interfaces,
+            members,
+        },
+    )))
+}
+
+fn visible_member(
+    class: &[u8],
+    constants: &[Constant],
+    members: &mut BTreeMap<MemberKey, u16>,
+    access: u16,
+    name: u16,
+    descriptor: u16,
+) {
+    if access & (ACC_PUBLIC | ACC_PROTECTED) == 0 || access & (ACC_SYNTHETIC | ACC_BRIDGE) != 0 {
+        return;
+    }
+    let name = bytecode::utf8_constant(class, constants, name);
+    let descriptor = bytecode::utf8_constant(class, constants, descriptor);
+    if let (Some(name), Some(descriptor)) = (name, descriptor) {
+        if name != "<clinit>" {
+            members.insert(MemberKey { name, descriptor }, access);
+        }
+    }
+}
+
+/// Every change from `old` to `new`: breaking ones first, then additions,
+/// each by class name.
+pub fn compare(old: &Api, new: &Api) -> Vec<ApiChange> {
+    let mut breaking = Vec::new();
+    let mut added = Vec::new();
+    for (name, old_class) in old {
+        let Some(new_class) = new.get(name) else {
+            breaking.push(ApiChange::ClassRemoved(name.clone()));
+            continue;
+        };
+        compare_class(name, old_class, new_class, &mut breaking, &mut added);
+    }
+    for name in new.keys().filter(|name| !old.contains_key(*name)) {
+        added.push(ApiChange::ClassAdded(name.clone()));
+    }
+    breaking.extend(added);
+    breaking
+}
+
+fn compare_class(
+    name: &str,
+    old: &ClassApi,
+    new: &ClassApi,
+    breaking: &mut Vec<ApiChange>,
+    added: &mut Vec<ApiChange>,
+) {
+    let kind = |access: u16| access & (ACC_INTERFACE | ACC_ANNOTATION | ACC_ENUM);
+    if kind(old.access) != kind(new.access) {
+        breaking.push(ApiChange::KindChanged(name.to_string()));
+        return;
+    }
+    let is_interface = new.access & ACC_INTERFACE != 0;
+    let gained = |flag: u16| old.access & flag == 0 && new.access & flag != 0;
+    if gained(ACC_FINAL) {
+        breaking.push(ApiChange::ClassMadeFinal(name.to_string()));
+    }
+    if gained(ACC_ABSTRACT) && !is_interface {
+        breaking.push(ApiChange::ClassMadeAbstract(name.to_string()));
+    }
+    if old.superclass != new.superclass {
+        let show = |s: &Option<String>| s.clone().unwrap_or_else(|| "none".to_string());
+        breaking.push(ApiChange::SuperclassChanged {
+            class: name.to_string(),
+            old: show(&old.superclass),
+            new: show(&new.superclass),
+        });
+    }
+    for interface in &old.interfaces {
+        if !new.interfaces.contains(interface) {
+            breaking.push(ApiChange::InterfaceRemoved {
+                class: name.to_string(),
+                interface: interface.clone(),
+            });
+        }
+    }
+
+    // Protected members are only reachable through subclasses
+    let visible =
+        |class: &ClassApi, access: u16| access & ACC_PUBLIC != 0 || class.access & ACC_FINAL == 0;
+    let change = |key: &MemberKey| (name.to_string(), key.display(name));
+    for (key, &old_access) in &old.members {
+        if !visible(old, old_access) {
+            continue;
+        }
+        let (class, member) = change(key);
+        let Some(&new_access) = new.members.get(key).filter(|&&a| visible(new, a)) else {
+            breaking.push(ApiChange::MemberRemoved { class, member });
+            continue;
+        };
+        let gained = |flag: u16| old_access & flag == 0 && new_access & flag != 0;
+        if old_access & ACC_PUBLIC != 0 && new_access & ACC_PUBLIC == 0 {
+            breaking.push(ApiChange::MemberMadeProtected { class, member });
+        } else if (old_access ^ new_access) & ACC_STATIC != 0 {
+            breaking.push(ApiChange::MemberStaticChanged { class, member });
+        } else if gained(ACC_FINAL) && (!key.is_method() || new.access & ACC_FINAL == 0) {
+            breaking.push(ApiChange::MemberMadeFinal { class, member });
+        } else if gained(ACC_ABSTRACT) {
+            breaking.push(ApiChange::AbstractMethodAdded { class, member });
+        }
+    }
+    for (key, &new_access) in &new.members {
+        if old.members.contains_key(key) || !visible(new, new_access) {
+            continue;
+        }
+        let (class, member) = change(key);
+        if new_access & ACC_ABSTRACT != 0 {
+            breaking.push(ApiChange::AbstractMethodAdded { class, member });
+        } else {
+            added.push(ApiChange::MemberAdded { class, member });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn class(access: u16, members: &[(&str, &str, u16)]) -> ClassApi {
+        ClassApi {
+            access,
+            superclass: Some("java.lang.Object".to_string()),
+            interfaces: Vec::new(),
+            members: members
+                .iter()
+                .map(|&(name, descriptor, access)| {
+                    let key = MemberKey {
+                        name: name.to_string(),
+                        descriptor: descriptor.to_string(),
+                    };
+                    (key, access)
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_member_display() {
+        let key = |name: &str, descriptor: &str| MemberKey {
+            name: name.to_string(),
+            descriptor: descriptor.to_string(),
+        };
+        let class = "com.example.Parser";
+        assert_eq!(
+            key("parse", "(Ljava/lang/String;[IJ)Ljava/util/List;").display(class),
+            "java.util.List parse(java.lang.String, int[], long)"
+        );
+        assert_eq!(key("<init>", "()V").display(class), "Parser()");
+        assert_eq!(key("MAX", "I").display(class), "int MAX");
+    }
+
+    #[test]
+    fn test_compare() {
+        let public = ACC_PUBLIC;
+        let old: Api = [
+            (
+                "com.example.Parser".to_string(),
+                class(
+                    public,
+                    &[
+                        ("parse", "(Ljava/lang/String;)V", public),
+                        ("reset", "()V", public),
+                        ("hook", "()V", ACC_PROTECTED),
+                    ],
+                ),
+            ),
+            (
+                "com.example.Visitor".to_string(),
+                class(public | ACC_INTERFACE | ACC_ABSTRACT, &[]),
+            ),
+            ("com.example.Old".to_string(), class(public, &[])),
+        ]
+        .into_iter()
+        .collect();
+        let new: Api = [
+            (
+                "com.example.Parser".to_string(),
+                class(
+                    public,
+                    &[
+                        ("parse", "(Ljava/lang/String;)V", public | ACC_FINAL),
+                        ("hook", "()V", ACC_PROTECTED),
+                        ("parse", "(Ljava/io/Reader;)V", public),
+                    ],
+                ),
+            ),
+            (
+                "com.example.Visitor".to_string(),
+                class(
+                    public | ACC_INTERFACE | ACC_ABSTRACT,
+                    &[("visit", "()V", public | ACC_ABSTRACT)],
+                ),
+            ),
+            ("com.example.New".to_string(), class(public, &[])),
+        ]
+        .into_iter()
+        .collect();
+
+        let changes = compare(&old, &new);
+        let text: Vec<String> = changes.iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            text,
+            [
+                "class com.example.Old was removed",
+                "com.example.Parser: `void parse(java.lang.String)` was made final",
+                "com.example.Parser: `void reset()` was removed",
+                "com.example.Visitor: abstract `void visit()` was added",
+                "com.example.Parser: `void parse(java.io.Reader)` was added",
+                "class com.example.New was added",
+            ]
+        );
+        assert_eq!(required_level(&changes), Level::Major);
+        assert_eq!(required_level(&changes[4..]), Level::Minor);
+        assert_eq!(required_level(&[]), Level::Patch);
+    }
+
+    #[test]
+    fn test_bump_level() {
+        assert_eq!(bump_level("1.2.3", "2.0.0"), Level::Major);
+        assert_eq!(bump_level("1.2.3", "1.3.0"), Level::Minor);
+        assert_eq!(bump_level("1.2.3", "1.2.4"), Level::Patch);
+        assert_eq!(bump_level("1.2.3", "1.3.0-SNAPSHOT"), Level::Minor);
+        assert_eq!(bump_level("0.2.3", "0.3.0"), Level::Major);
+        assert_eq!(bump_level("0.2.3", "0.2.4"), Level::Minor);
+
+        let removed = [ApiChange::ClassRemoved("com.example.Old".to_string())];
+        assert_eq!(
+            semver_violation("1.0.0", "1.0.1", &removed).unwrap(),
+            "`1.0.1` is a patch release after `1.0.0`, but the API changes need a major release"
+        );
+        assert_eq!(semver_violation("1.0.0", "2.0.0", &removed), None);
+    }
+
+    #[test]
+    fn test_baseline() {
+        let versions: Vec<String> = ["1.0.0", "1.2.0", "1.1.5", "1.3.0-SNAPSHOT", "2.0.0"]
+            .map(String::from)
+            .to_vec();
+        assert_eq!(baseline(&versions, "1.3.0"), Some("1.2.0".to_string()));
+        assert_eq!(baseline(&versions, "1.0.0"), None);
+    }
+}
@@ -0,0 +1,496 @@
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::context::GlobalContext;
+use crate::daemon;
+use crate::error_prone::ErrorProneFlags;
+use crate::errors::JargoError;
+use crate::manifest::{CompilerKind, JargoToml};
+use crate::resolver;
+use crate::toolchain::Toolchain;
+
+/// ECJ release used for `[build] compiler = "ecj"`. Runs on Java 17 and
+/// compiles up to Java 21.
+pub const ECJ_VERSION: &str = "3.36.0";
+
+/// ECJ's command-line entry point.
+const ECJ_MAIN: &str = "org.eclipse.jdt.internal.compiler.batch.Main";
+
+/// One compile of a source set, for a backend to spell out in its own flags.
+pub struct Invocation<'a> {
+    pub project_root: &'a Path,
+    /// Where the backend writes its argument file.
+    pub args_file: &'a Path,
+    /// The staged source root, then any generated source roots.
+    pub source_roots: &'a [PathBuf],
+    pub classes_dir: &'a Path,
+    /// `package.java`, the release compiled for.
+    pub release: &'a str,
+    pub classpath: &'a [PathBuf],
+    pub source_files: &'a [PathBuf],
+    /// javac-style flags, such as `-processorpath` or the `[javac]` flags
+    /// from [`CompilerBackend::warning_args`].
+    pub extra_args: &'a [&'a str],
+}
+
+/// A compiler jargo can build source sets with, chosen by `[build] compiler`.
+pub trait CompilerBackend {
+    fn kind(&self) -> CompilerKind;
+
+    /// Flags for `[javac] lint` and `deny-warnings`.
+    fn warning_args(&self, manifest: &JargoToml) -> Vec<String>;
+
+    /// Flags for recompiling some of the sources after a failed compile,
+    /// skipping annotation processors so generated files are not emitted
+    /// twice.
+    fn batch_args(&self) -> &'static [&'static str];
+
+    /// Run the compile, returning whether it succeeded and its diagnostics
+    /// in javac's `file:line: error: message` form.
+    fn compile(&self, gctx: &GlobalContext, invocation: &Invocation) -> Result<(bool, String)>;
+}
+
+/// Extensions of the source files `kind` compiles.
+pub fn source_extensions(kind: CompilerKind) -> &'static [&'static str] {
+    match kind {
+        CompilerKind::Javac | CompilerKind::Ecj => &["java"],
+        CompilerKind::Kotlinc => &["kt", "java"],
+    }
+}
+
+/// The backend for the manifest's `[build] compiler`, running on
+/// `toolchain`. `error_prone` is only supported by javac, as a plugin.
+pub fn select(
+    gctx: &GlobalContext,
+    toolchain: &Toolchain,
+    manifest: &JargoToml,
+    error_prone: Option<&ErrorProneFlags>,
+) -> Result<Box<dyn CompilerBackend>> {
+    let kind = manifest.get_compiler();
+    if error_prone.is_some() && kind != CompilerKind::Javac {
+        bail!(
+            "[error-prone] runs as a javac plugin and cannot be used with `[build] compiler = \"{}\"`",
+            kind.name()
+        );
+    }
+    let javac = Javac::new(toolchain, manifest, error_prone)?;
+    Ok(match kind {
+        CompilerKind::Javac => Box::new(javac),
+        CompilerKind::Ecj => Box::new(Ecj {
+            java: toolchain.java(),
+            classpath: resolver::resolve_tool(gctx, &[("org.eclipse.jdt", "ecj", ECJ_VERSION)])?,
+            jvm_args: manifest.get_javac_jvm_args()?,
+        }),
+        CompilerKind::Kotlinc => Box::new(Kotlinc {
+            kotlinc: kotlinc_path(),
+            jvm_args: manifest.get_javac_jvm_args()?,
+            javac,
+        }),
+    })
+}
+
+/// The JDK's `javac`, through a running compile daemon when there is one.
+pub struct Javac {
+    toolchain: Toolchain,
+    /// For the javac JVM, passed as `-J` flags.
+    jvm_args: Vec<String>,
+    /// Error Prone needs javac exports the daemon's JVM was not started with.
+    use_daemon: bool,
+}
+
+impl Javac {
+    fn new(
+        toolchain: &Toolchain,
+        manifest: &JargoToml,
+        error_prone: Option<&ErrorProneFlags>,
+    ) -> Result<Self> {
+        let mut jvm_args = manifest.get_javac_jvm_args()?;
+        if let Some(flags) = error_prone {
+            jvm_args.extend(flags.jvm_args.iter().cloned());
+        }
+        Ok(Javac {
+            toolchain: toolchain.clone(),
+            jvm_args,
+            use_daemon: error_prone.is_none(),
+        })
+    }
+}
+
+impl CompilerBackend for Javac {
+    fn kind(&self) -> CompilerKind {
+        CompilerKind::Javac
+    }
+
+    fn warning_args(&self, manifest: &JargoToml) -> Vec<String> {
+        manifest.get_javac_args()
+    }
+
+    fn batch_args(&self) -> &'static [&'static str] {
+        // Sources pulled in through -sourcepath get their own batch
+        &["-proc:none", "-implicit:none"]
+    }
+
+    fn compile(&self, gctx: &GlobalContext, invocation: &Invocation) -> Result<(bool, String)> {
+        write_javac_args(invocation)?;
+        let args_file = invocation.args_file;
+        if self.use_daemon {
+            if let Some(result) = daemon::compile(gctx, &self.toolchain, args_file) {
+                gctx.shell
+                    .verbose(|sh| sh.print("  [verbose] compiled via compile daemon"));
+                return Ok(result);
+            }
+        }
+
+        let mut command = Command::new(self.toolchain.javac());
+        command.args(self.jvm_args.iter().map(|a| format!("-J{}", a)));
+        run(gctx, command, invocation).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => JargoError::JavacNotFound.into(),
+            _ => e.into(),
+        })
+    }
+}
+
+/// The Eclipse compiler, run from its JAR on the toolchain's `java`.
+pub struct Ecj {
+    java: PathBuf,
+    classpath: Vec<PathBuf>,
+    jvm_args: Vec<String>,
+}
+
+impl CompilerBackend for Ecj {
+    fn kind(&self) -> CompilerKind {
+        CompilerKind::Ecj
+    }
+
+    /// `lint` keys become `-warn:` tokens, enabling (`+`) or disabling (`-`)
+    /// ECJ's warnings of that name.
+    fn warning_args(&self, manifest: &JargoToml) -> Vec<String> {
+        let Some(javac) = &manifest.javac else {
+            return Vec::new();
+        };
+        let mut args = Vec::new();
+        for key in &javac.lint {
+            args.push(match key.strip_prefix('-') {
+                Some(key) => format!("-warn:-{}", key),
+                None if key == "all" => "-warn:all".to_string(),
+                None => format!("-warn:+{}", key),
+            });
+        }
+        if javac.deny_warnings {
+            args.push("--failOnWarning".to_string());
+        }
+        args
+    }
+
+    fn batch_args(&self) -> &'static [&'static str] {
+        &["-proc:none"]
+    }
+
+    fn compile(&self, gctx: &GlobalContext, invocation: &Invocation) -> Result<(bool, String)> {
+        write_javac_args(invocation)?;
+        let classpath =
+            std::env::join_paths(&self.classpath).context("invalid ECJ classpath entry")?;
+        let mut command = Command::new(&self.java);
+        command
+            .args(&self.jvm_args)
+            .arg("-cp")
+            .arg(classpath)
+            .arg(ECJ_MAIN)
+            // Diagnostics as `file:line: error: message`, like javac's
+            .arg("-Xemacs");
+        run(gctx, command, invocation).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => {
+                anyhow::anyhow!(
+                    "`{}` not found; ECJ needs a JDK or JRE",
+                    self.java.display()
+                )
+            }
+            _ => e.into(),
+        })
+    }
+}
+
+/// `kotlinc` for the Kotlin sources, which sees the Java sources next to
+/// them; `javac` then compiles the Java sources against its output.
+pub struct Kotlinc {
+    kotlinc: PathBuf,
+    jvm_args: Vec<String>,
+    javac: Javac,
+}
+
+impl CompilerBackend for Kotlinc {
+    fn kind(&self) -> CompilerKind {
+        CompilerKind::Kotlinc
+    }
+
+    /// javac's flags: kotlinc has no lint keys, and takes only `-Werror`.
+    fn warning_args(&self, manifest: &JargoToml) -> Vec<String> {
+        self.javac.warning_args(manifest)
+    }
+
+    fn batch_args(&self) -> &'static [&'static str] {
+        self.javac.batch_args()
+    }
+
+    fn compile(&self, gctx: &GlobalContext, invocation: &Invocation) -> Result<(bool, String)> {
+        let (kotlin, java): (Vec<PathBuf>, Vec<PathBuf>) = invocation
+            .source_files
+            .iter()
+            .cloned()
+            .partition(|file| file.extension().is_some_and(|ext| ext == "kt"));
+
+        let mut stderr = String::new();
+        if !kotlin.is_empty() {
+            let args_file = kotlinc_args_file(invocation.args_file);
+            write_kotlinc_args(&args_file, invocation, &kotlin, &java)?;
+            let mut command = Command::new(&self.kotlinc);
+            command.args(self.jvm_args.iter().map(|a| format!("-J{}", a)));
+            let (success, output) = run(
+                gctx,
+                command,
+                &Invocation {
+                    args_file: &args_file,
+                    ..*invocation
+                },
+            )
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => anyhow::anyhow!(
+                    "`{}` not found; install Kotlin or set KOTLIN_HOME",
+                    self.kotlinc.display()
+                ),
+                _ => e.into(),
+            })?;
+            if !success || java.is_empty() {
+                return Ok((success, output));
+            }
+            stderr = output;
+        }
+
+        // javac takes the Kotlin classes from the classpath
+        let classpath: Vec<PathBuf> = std::iter::once(invocation.classes_dir.to_path_buf())
+            .chain(invocation.classpath.iter().cloned())
+            .collect();
+        let (success, output) = self.javac.compile(
+            gctx,
+            &Invocation {
+                classpath: &classpath,
+                source_files: &java,
+                ..*invocation
+            },
+        )?;
+        stderr.push_str(&output);
+        Ok((success, stderr))
+    }
+}
+
+/// `kotlinc` from `KOTLIN_HOME`, else looked up on `PATH`.
+fn kotlinc_path() -> PathBuf {
+    let name = if cfg!(windows) {
+        "kotlinc.bat"
+    } else {
+        "kotlinc"
+    };
+    match std::env::var_os("KOTLIN_HOME").filter(|h| !h.is_empty()) {
+        Some(home) => PathBuf::from(home).join("bin").join(name),
+        None => PathBuf::from(name),
+    }
+}
+
+/// `target/.jargo/javac-args.txt` → `target/.jargo/kotlinc-args.txt`
+fn kotlinc_args_file(javac_args_file: &Path) -> PathBuf {
+    let name = javac_args_file
+        .file_name()
+        .map(|name| name.to_string_lossy().replacen("javac", "kotlinc", 1))
+        .unwrap_or_else(|| "kotlinc-args.txt".to_string());
+    javac_args_file.with_file_name(name)
+}
+
+/// Run `command @{args_file}` in the project root, returning whether it
+/// succeeded and its stderr.
+fn run(
+    gctx: &GlobalContext,
+    mut command: Command,
+    invocation: &Invocation,
+) -> std::io::Result<(bool, String)> {
+    let args_file = invocation.args_file;
+    gctx.shell.verbose(|sh| {
+        let mut line = command.get_program().to_string_lossy().into_owned();
+        for arg in command.get_args() {
+            line.push(' ');
+            line.push_str(&arg.to_string_lossy());
+        }
+        let relative = args_file
+            .strip_prefix(invocation.project_root)
+            .unwrap_or(args_file);
+        sh.print(format!("  [verbose] {} @{}", line, relative.display()))
+    });
+    let output = command
+        .arg(format!("@{}", args_file.display()))
+        .current_dir(invocation.project_root)
+        .output()?;
+    Ok((
+        output.status.success(),
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+    ))
+}
+
+#[cfg(windows)]
+const PATH_SEPARATOR: &str = ";";
+#[cfg(not(windows))]
+const PATH_SEPARATOR: &str = ":";
+
+fn join(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|p| p.display().to_string())
+        .collect::<Vec<_>>()
+        .join(PATH_SEPARATOR)
+}
+
+/// javac's argument file, which ECJ reads too. `source_roots` become
+/// `-sourcepath`.
+fn write_javac_args(invocation: &Invocation) -> Result<()> {
+    let mut args = format!(
+        "--release\n{}\n-d\n{}\n-sourcepath\n{}\n",
+        invocation.release,
+        invocation.classes_dir.display(),
+        join(invocation.source_roots)
+    );
+
+    for arg in invocation.extra_args {
+        args.push_str(&format!("{}\n", arg));
+    }
+
+    if !invocation.classpath.is_empty() {
+        args.push_str(&format!("-classpath\n{}\n", join(invocation.classpath)));
+    }
+
+    // Add all source files
+    for file in invocation.source_files {
+        args.push_str(&format!("{}\n", file.display()));
+    }
+
+    write_args_file(invocation.args_file, &args)
+}
+
+/// kotlinc's argument file: the Kotlin sources to compile and the Java
+/// sources they may refer to. Only flags kotlinc shares with javac, such as
+/// `-Werror`, are passed on.
+fn write_kotlinc_args(
+    args_file: &Path,
+    invocation: &Invocation,
+    kotlin: &[PathBuf],
+    java: &[PathBuf],
+) -> Result<()> {
+    let mut args = format!(
+        "-d\n{}\n-jvm-target\n{}\n",
+        invocation.classes_dir.display(),
+        jvm_target(invocation.release)
+    );
+    if invocation.extra_args.contains(&"-Werror") {
+        args.push_str("-Werror\n");
+    }
+    if !invocation.classpath.is_empty() {
+        args.push_str(&format!("-classpath\n{}\n", join(invocation.classpath)));
+    }
+    for file in kotlin.iter().chain(java) {
+        args.push_str(&format!("{}\n", file.display()));
+    }
+    write_args_file(args_file, &args)
+}
+
+/// kotlinc's `-jvm-target` for a Java release: `1.8` for 8, else the
+/// release itself.
+fn jvm_target(release: &str) -> &str {
+    match release {
+        "8" | "1.8" => "1.8",
+        release => release,
+    }
+}
+
+fn write_args_file(args_file: &Path, args: &str) -> Result<()> {
+    if let Some(dir) = args_file.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    }
+    fs::write(args_file, args).with_context(|| {
+        format!(
+            "failed to write compiler arguments to {}",
+            args_file.display()
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest_with(extra: &str) -> JargoToml {
+        toml::from_str(&format!(
+            "[package]\nname = \"demo\"\nversion = \"1.0.0\"\njava = \"21\"\n{}",
+            extra
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_ecj_warning_args() {
+        let manifest = manifest_with(
+            r#"
+[build]
+compiler = "ecj"
+
+[javac]
+lint = ["all", "-serial", "unusedImport"]
+deny-warnings = true
+"#,
+        );
+        let ecj = Ecj {
+            java: PathBuf::from("java"),
+            classpath: Vec::new(),
+            jvm_args: Vec::new(),
+        };
+        assert_eq!(manifest.get_compiler(), CompilerKind::Ecj);
+        assert_eq!(
+            ecj.warning_args(&manifest),
+            [
+                "-warn:all",
+                "-warn:-serial",
+                "-warn:+unusedImport",
+                "--failOnWarning"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_write_kotlinc_args() {
+        let tmp = tempfile::tempdir().unwrap();
+        let args_file = kotlinc_args_file(&tmp.path().join("javac-test-args.txt"));
+        assert!(args_file.ends_with("kotlinc-test-args.txt"));
+
+        let classpath = [PathBuf::from("/cache/kotlin-stdlib.jar")];
+        let invocation = Invocation {
+            project_root: tmp.path(),
+            args_file: &args_file,
+            source_roots: &[],
+            classes_dir: Path::new("target/classes"),
+            release: "8",
+            classpath: &classpath,
+            source_files: &[],
+            extra_args: &["-Xlint:all", "-Werror"],
+        };
+        write_kotlinc_args(
+            &args_file,
+            &invocation,
+            &[PathBuf::from("src/Main.kt")],
+            &[PathBuf::from("src/Util.java")],
+        )
+        .unwrap();
+        assert_eq!(
+            fs::read_to_string(&args_file).unwrap(),
+            "-d\ntarget/classes\n-jvm-target\n1.8\n-Werror\n\
+             -classpath\n/cache/kotlin-stdlib.jar\nsrc/Main.kt\nsrc/Util.java\n"
+        );
+    }
+}
@@ -0,0 +1,297 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::cache;
+use crate::context::GlobalContext;
+use crate::manifest::BenchConfig;
+
+/// JMH version used to compile and run benchmarks.
+pub const JMH_VERSION: &str = "1.37";
+
+/// Directory the JSON results of every `jargo bench` run are kept in.
+pub const BENCH_DIR: &str = "target/bench";
+
+/// JMH's runtime: the harness and its dependencies, as `(group, artifact, version)`.
+const JMH_RUNTIME: [(&str, &str, &str); 3] = [
+    ("org.openjdk.jmh", "jmh-core", JMH_VERSION),
+    ("net.sf.jopt-simple", "jopt-simple", "5.0.4"),
+    ("org.apache.commons", "commons-math3", "3.6.1"),
+];
+
+/// The JMH JARs a benchmark build needs besides the project's own.
+pub struct JmhJars {
+    /// Harness classes, on the compile and runtime classpaths.
+    pub runtime: Vec<PathBuf>,
+    /// The annotation processor generating the benchmark classes, for
+    /// `-processorpath`.
+    pub processor: Vec<PathBuf>,
+}
+
+/// Fetch JMH into the local cache. Its dependencies are pinned here rather
+/// than resolved, like the JUnit console launcher.
+pub fn fetch_jmh(gctx: &GlobalContext) -> Result<JmhJars> {
+    let mut runtime = Vec::new();
+    for (group, artifact, version) in JMH_RUNTIME {
+        let (path, _sha256) =
+            cache::fetch_jar(gctx, group, artifact, version).context("failed to fetch JMH")?;
+        runtime.push(path);
+    }
+    let (generator, _sha256) = cache::fetch_jar(
+        gctx,
+        "org.openjdk.jmh",
+        "jmh-generator-annprocess",
+        JMH_VERSION,
+    )
+    .context("failed to fetch the JMH annotation processor")?;
+    let mut processor = vec![generator];
+    processor.extend(runtime.iter().cloned());
+    Ok(JmhJars { runtime, processor })
+}
+
+/// Options for a single JMH run. Unset counts use `[bench]`, then the
+/// benchmark annotations.
+#[derive(Debug, Default)]
+pub struct BenchOptions {
+    /// Regular expressions selecting benchmarks; empty runs all of them.
+    pub filters: Vec<String>,
+    pub forks: Option<u32>,
+    pub warmup_iterations: Option<u32>,
+    pub iterations: Option<u32>,
+}
+
+/// Arguments for `org.openjdk.jmh.Main`, writing JSON results to `result_file`.
+pub fn jmh_args(
+    opts: &BenchOptions,
+    config: Option<&BenchConfig>,
+    result_file: &Path,
+) -> Vec<String> {
+    let mut args = opts.filters.clone();
+    let counts = [
+        ("-f", opts.forks, config.and_then(|c| c.forks)),
+        (
+            "-wi",
+            opts.warmup_iterations,
+            config.and_then(|c| c.warmup_iterations),
+        ),
+        ("-i", opts.iterations, config.and_then(|c| c.iterations)),
+    ];
+    for (flag, value, default) in counts {
+        if let Some(value) = value.or(default) {
+            args.push(flag.to_string());
+            args.push(value.to_string());
+        }
+    }
+    args.extend([
+        "-rf".to_string(),
+        "json".to_string(),
+        "-rff".to_string(),
+        result_file.display().to_string(),
+    ]);
+    args
+}
+
+/// Path for the results of a new run, `target/bench/{timestamp}.json`, and
+/// the results of the latest earlier run, if any. Creates `target/bench/`.
+pub fn result_paths(project_root: &Path) -> Result<(PathBuf, Option<PathBuf>)> {
+    let dir = project_root.join(BENCH_DIR);
+    fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+
+    let mut earlier: Vec<PathBuf> = fs::read_dir(&dir)
+        .with_context(|| format!("failed to read {}", dir.display()))?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    earlier.sort();
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    Ok((dir.join(format!("{}.json", timestamp)), earlier.pop()))
+}
+
+/// One benchmark result from a JMH JSON results file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchResult {
+    /// Fully-qualified benchmark method, e.g. `myapp.ParseBench.parse`.
+    pub benchmark: String,
+    /// JMH mode: `thrpt`, `avgt`, `sample`, `ss`.
+    pub mode: String,
+    /// `@Param` values, e.g. `size=100`.
+    pub params: BTreeMap<String, String>,
+    pub score: f64,
+    /// Half-width of the 99.9% confidence interval; NaN with too few iterations.
+    pub error: f64,
+    pub unit: String,
+}
+
+impl BenchResult {
+    /// `myapp.ParseBench.parse` or `myapp.ParseBench.parse(size=100)`, the
+    /// key results are matched on across runs (together with the mode).
+    pub fn name(&self) -> String {
+        if self.params.is_empty() {
+            return self.benchmark.clone();
+        }
+        let params: Vec<String> = self
+            .params
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        format!("{}({})", self.benchmark, params.join(","))
+    }
+
+    /// Relative change of the score from `previous`, in percent, when
+    /// `previous` holds this benchmark in the same mode and unit.
+    pub fn change_from(&self, previous: &[BenchResult]) -> Option<f64> {
+        let before = previous.iter().find(|p| {
+            p.benchmark == self.benchmark
+                && p.params == self.params
+                && p.mode == self.mode
+                && p.unit == self.unit
+        })?;
+        (before.score != 0.0).then(|| (self.score - before.score) * 100.0 / before.score)
+    }
+}
+
+#[derive(Deserialize)]
+struct RawResult {
+    benchmark: String,
+    mode: String,
+    #[serde(default)]
+    params: BTreeMap<String, String>,
+    #[serde(rename = "primaryMetric")]
+    primary_metric: RawMetric,
+}
+
+#[derive(Deserialize)]
+struct RawMetric {
+    score: f64,
+    /// A number, or the string `"NaN"`.
+    #[serde(rename = "scoreError")]
+    score_error: serde_json::Value,
+    #[serde(rename = "scoreUnit")]
+    score_unit: String,
+}
+
+/// Read a JMH JSON results file.
+pub fn read_results(path: &Path) -> Result<Vec<BenchResult>> {
+    let json =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    parse_results(&json).with_context(|| format!("failed to parse {}", path.display()))
+}
+
+fn parse_results(json: &str) -> Result<Vec<BenchResult>> {
+    let raw: Vec<RawResult> = serde_json::from_str(json)?;
+    Ok(raw
+        .into_iter()
+        .map(|r| BenchResult {
+            benchmark: r.benchmark,
+            mode: r.mode,
+            params: r.params,
+            score: r.primary_metric.score,
+            error: r.primary_metric.score_error.as_f64().unwrap_or(f64::NAN),
+            unit: r.primary_metric.score_unit,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESULTS: &str = r#"[
+    {
+        "jmhVersion" : "1.37",
+        "benchmark" : "myapp.ParseBench.parse",
+        "mode" : "thrpt",
+        "threads" : 1,
+        "forks" : 1,
+        "params" : { "size" : "100" },
+        "primaryMetric" : {
+            "score" : 2000.0,
+            "scoreError" : 12.5,
+            "scoreUnit" : "ops/s"
+        },
+        "secondaryMetrics" : {}
+    },
+    {
+        "jmhVersion" : "1.37",
+        "benchmark" : "myapp.ParseBench.tokenize",
+        "mode" : "avgt",
+        "primaryMetric" : {
+            "score" : 3.5,
+            "scoreError" : "NaN",
+            "scoreUnit" : "us/op"
+        }
+    }
+]"#;
+
+    #[test]
+    fn test_parse_results() {
+        let results = parse_results(RESULTS).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].name(), "myapp.ParseBench.parse(size=100)");
+        assert_eq!(results[0].error, 12.5);
+        assert_eq!(results[1].name(), "myapp.ParseBench.tokenize");
+        assert!(results[1].error.is_nan());
+        assert_eq!(results[1].unit, "us/op");
+    }
+
+    #[test]
+    fn test_change_from() {
+        let previous = parse_results(RESULTS).unwrap();
+        let mut current = previous[0].clone();
+        current.score = 2500.0;
+        assert_eq!(current.change_from(&previous), Some(25.0));
+        current
+            .params
+            .insert("size".to_string(), "1000".to_string());
+        assert_eq!(current.change_from(&previous), None);
+    }
+
+    #[test]
+    fn test_jmh_args() {
+        let opts = BenchOptions {
+            filters: vec!["Parse".to_string()],
+            forks: Some(2),
+            ..BenchOptions::default()
+        };
+        let config = BenchConfig {
+            forks: Some(1),
+            iterations: Some(5),
+            ..BenchConfig::default()
+        };
+        assert_eq!(
+            jmh_args(&opts, Some(&config), Path::new("target/bench/1.json")),
+            [
+                "Parse",
+                "-f",
+                "2",
+                "-i",
+                "5",
+                "-rf",
+                "json",
+                "-rff",
+                "target/bench/1.json"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_result_paths() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (first, previous) = result_paths(tmp.path()).unwrap();
+        assert!(first.starts_with(tmp.path().join(BENCH_DIR)));
+        assert_eq!(previous, None);
+
+        let dir = tmp.path().join(BENCH_DIR);
+        fs::write(dir.join("1700000000.json"), "[]").unwrap();
+        fs::write(dir.join("1700000100.json"), "[]").unwrap();
+        let (_, previous) = result_paths(tmp.path()).unwrap();
+        assert_eq!(previous, Some(dir.join("1700000100.json")));
+    }
+}
@@ -0,0 +1,144 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::manifest::JargoToml;
+
+/// Write `target/classes/META-INF/build-info.properties` for the project.
+///
+/// Records the project name, version, Java release, current git commit (when
+/// the project is inside a git repository), and a UTC build timestamp.
+pub fn write_build_info(project_root: &Path, manifest: &JargoToml) -> Result<PathBuf> {
+    let meta_inf = project_root.join("target/classes/META-INF");
+    fs::create_dir_all(&meta_inf)
+        .with_context(|| format!("failed to create {}", meta_inf.display()))?;
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let commit = git_commit(project_root);
+
+    let content = render_properties(manifest, commit.as_deref(), &format_utc(timestamp));
+    let path = meta_inf.join("build-info.properties");
+    fs::write(&path, content).with_context(|| format!("failed to write {}", path.display()))?;
+
+    Ok(path)
+}
+
+/// Return the full hash of `HEAD`, or `None` outside a git repository.
+fn git_commit(project_root: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(project_root)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let hash = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!hash.is_empty()).then_some(hash)
+}
+
+fn render_properties(manifest: &JargoToml, commit: Option<&str>, timestamp: &str) -> String {
+    let mut out = String::new();
+    out.push_str(&format!(
+        "project.name={}\n",
+        escape(&manifest.package.name)
+    ));
+    out.push_str(&format!(
+        "project.version={}\n",
+        escape(&manifest.package.version)
+    ));
+    out.push_str(&format!(
+        "java.release={}\n",
+        escape(&manifest.package.java)
+    ));
+    if let Some(commit) = commit {
+        out.push_str(&format!("git.commit={}\n", commit));
+    }
+    out.push_str(&format!("build.timestamp={}\n", timestamp));
+    out
+}
+
+/// Escape a value for a `.properties` file (backslashes and newlines).
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+/// Format seconds since the Unix epoch as an ISO 8601 UTC timestamp.
+///
+/// `0` → `"1970-01-01T00:00:00Z"`
+pub(crate) fn format_utc(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    let (h, m, s) = (rem / 3600, (rem % 3600) / 60, rem % 60);
+
+    // Civil-from-days conversion (proleptic Gregorian calendar).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year, month, day, h, m, s
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_format_utc() {
+        assert_eq!(format_utc(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_utc(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_utc(1_700_000_000), "2023-11-14T22:13:20Z");
+    }
+
+    #[test]
+    fn test_render_properties() {
+        let manifest = JargoToml::new_app("my-app");
+        let props = render_properties(&manifest, Some("abc123"), "2024-01-01T00:00:00Z");
+        assert_eq!(
+            props,
+            "project.name=my-app\n\
+             project.version=0.1.0\n\
+             java.release=21\n\
+             git.commit=abc123\n\
+             build.timestamp=2024-01-01T00:00:00Z\n"
+        );
+    }
+
+    #[test]
+    fn test_render_properties_without_git() {
+        let manifest = JargoToml::new_app("my-app");
+        let props = render_properties(&manifest, None, "2024-01-01T00:00:00Z");
+        assert!(!props.contains("git.commit"));
+    }
+
+    #[test]
+    fn test_write_build_info() {
+        let tmp = TempDir::new().unwrap();
+        let manifest = JargoToml::new_app("my-app");
+        let path = write_build_info(tmp.path(), &manifest).unwrap();
+
+        assert_eq!(
+            path,
+            tmp.path()
+                .join("target/classes/META-INF/build-info.properties")
+        );
+        let content = fs::read_to_string(path).unwrap();
+        assert!(content.contains("project.name=my-app"));
+        assert!(content.contains("build.timestamp="));
+    }
+}
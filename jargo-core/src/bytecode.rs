@@ -0,0 +1,237 @@
+use anyhow::{bail, Context, Result};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Class file major version of Java 1.0/1.1; release `N` (N ≥ 5) is `N + 44`.
+const MAJOR_OFFSET: u16 = 44;
+
+/// Read the major version from the header of a `.class` file.
+///
+/// Returns `None` when `bytes` does not start with the `0xCAFEBABE` magic.
+pub fn class_major_version(bytes: &[u8]) -> Option<u16> {
+    if bytes.len() < 8 || bytes[..4] != [0xCA, 0xFE, 0xBA, 0xBE] {
+        return None;
+    }
+    Some(u16::from_be_bytes([bytes[6], bytes[7]]))
+}
+
+/// Java release that emits class files with this major version (`65` → 21).
+pub fn release_of(major: u16) -> u32 {
+    u32::from(major.saturating_sub(MAJOR_OFFSET))
+}
+
+/// Class file major version emitted by `--release {release}` (21 → `65`).
+pub fn major_of(release: u32) -> u16 {
+    u16::try_from(release)
+        .unwrap_or(u16::MAX)
+        .saturating_add(MAJOR_OFFSET)
+}
+
+/// Parse `package.java` (`"21"`, legacy `"1.8"`) into a feature release.
+pub fn parse_release(java: &str) -> Option<u32> {
+    java.strip_prefix("1.").unwrap_or(java).parse().ok()
+}
+
+/// Multi-release entries (`META-INF/versions/{N}/...`) target newer releases
+/// on purpose.
+pub fn is_versioned_entry(name: &str) -> bool {
+    name.starts_with("META-INF/versions/")
+}
+
+/// A constant pool entry: `(index, tag, start, end)`, with the byte range
+/// covering the tag and its payload.
+pub(crate) type Constant = (u16, u8, usize, usize);
+
+/// Constant pool entries of a class file, plus the offset just past the pool.
+pub(crate) fn constant_pool(class: &[u8]) -> Result<(Vec<Constant>, usize)> {
+    let count = u16_at(class, 8)?;
+    let mut constants = Vec::new();
+    let mut pos = 10;
+    let mut index = 1;
+    while index < count {
+        let tag = *class.get(pos).ok_or_else(truncated)?;
+        let len = match tag {
+            1 => 3 + usize::from(u16_at(class, pos + 1)?),
+            7 | 8 | 16 | 19 | 20 => 3,
+            15 => 4,
+            3 | 4 | 9 | 10 | 11 | 12 | 17 | 18 => 5,
+            5 | 6 => 9,
+            _ => bail!("unknown constant pool tag {}", tag),
+        };
+        if pos + len > class.len() {
+            return Err(truncated());
+        }
+        constants.push((index, tag, pos, pos + len));
+        pos += len;
+        // Long and Double take two slots
+        index += if matches!(tag, 5 | 6) { 2 } else { 1 };
+    }
+    Ok((constants, pos))
+}
+
+/// Big-endian `u16` at `pos` in a class file.
+pub(crate) fn u16_at(class: &[u8], pos: usize) -> Result<u16> {
+    let bytes = class.get(pos..pos + 2).ok_or_else(truncated)?;
+    Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+/// The text of the `Utf8` constant at `index`, if there is one.
+pub(crate) fn utf8_constant(class: &[u8], constants: &[Constant], index: u16) -> Option<String> {
+    constants
+        .iter()
+        .find(|&&(i, tag, _, _)| i == index && tag == 1)
+        .map(|&(_, _, start, end)| String::from_utf8_lossy(&class[start + 3..end]).into_owned())
+}
+
+/// Names of the methods a class file declares, in declaration order.
+pub(crate) fn method_names(class: &[u8]) -> Result<Vec<String>> {
+    let (constants, end) = constant_pool(class)?;
+    // Skip access flags, this class, and super class
+    let mut pos = end + 6;
+    pos += 2 + 2 * usize::from(u16_at(class, pos)?);
+    pos = walk_members(class, pos, |_, _, _| ())?;
+    let mut names = Vec::new();
+    walk_members(class, pos, |_, name_index, _| {
+        names.extend(utf8_constant(class, &constants, name_index));
+    })?;
+    Ok(names)
+}
+
+/// Walk a `fields` or `methods` table at `pos`, passing each member's access
+/// flags, name index, and descriptor index to `visit`. Returns the offset
+/// just past the table.
+pub(crate) fn walk_members(
+    class: &[u8],
+    pos: usize,
+    mut visit: impl FnMut(u16, u16, u16),
+) -> Result<usize> {
+    let count = u16_at(class, pos)?;
+    let mut pos = pos + 2;
+    for _ in 0..count {
+        visit(
+            u16_at(class, pos)?,
+            u16_at(class, pos + 2)?,
+            u16_at(class, pos + 4)?,
+        );
+        let attributes = u16_at(class, pos + 6)?;
+        pos += 8;
+        for _ in 0..attributes {
+            let len = class.get(pos + 2..pos + 6).ok_or_else(truncated)?;
+            pos += 6 + u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize;
+        }
+    }
+    if pos > class.len() {
+        return Err(truncated());
+    }
+    Ok(pos)
+}
+
+fn truncated() -> anyhow::Error {
+    anyhow::anyhow!("truncated class file")
+}
+
+/// Class files in `jar` whose major version is newer than `release` can load,
+/// as `(entry name, major version)` in archive order.
+///
+/// Multi-release entries and `module-info.class` are skipped: older JVMs
+/// never load them from the classpath.
+pub fn classes_newer_than(jar: &Path, release: u32) -> Result<Vec<(String, u16)>> {
+    let file = File::open(jar).with_context(|| format!("failed to open {}", jar.display()))?;
+    let mut archive =
+        zip::ZipArchive::new(file).with_context(|| format!("failed to read {}", jar.display()))?;
+    let max_major = major_of(release);
+
+    let mut newer = Vec::new();
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        let name = entry.name().to_string();
+        if !name.ends_with(".class")
+            || name.ends_with("module-info.class")
+            || is_versioned_entry(&name)
+        {
+            continue;
+        }
+        let mut header = [0u8; 8];
+        if entry.read_exact(&mut header).is_err() {
+            continue;
+        }
+        if let Some(major) = class_major_version(&header) {
+            if major > max_major {
+                newer.push((name, major));
+            }
+        }
+    }
+    Ok(newer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_class_major_version() {
+        let header = [0xCA, 0xFE, 0xBA, 0xBE, 0x00, 0x00, 0x00, 0x41, 0x00];
+        assert_eq!(class_major_version(&header), Some(65));
+        assert_eq!(class_major_version(b"PK\x03\x04\0\0\0\0"), None);
+        assert_eq!(class_major_version(&header[..6]), None);
+    }
+
+    #[test]
+    fn test_method_names() {
+        let mut class = vec![0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 61, 0, 4];
+        for name in ["shouldWork", "helper", "Code"] {
+            class.push(1);
+            class.extend_from_slice(&(name.len() as u16).to_be_bytes());
+            class.extend_from_slice(name.as_bytes());
+        }
+        // Access flags, this class, super class, no interfaces
+        class.extend_from_slice(&[0, 0x21, 0, 0, 0, 0, 0, 0]);
+        // One field with no attributes
+        class.extend_from_slice(&[0, 1, 0, 2, 0, 2, 0, 2, 0, 0]);
+        // Two methods, the first with a 3-byte attribute
+        class.extend_from_slice(&[0, 2]);
+        class.extend_from_slice(&[0, 1, 0, 1, 0, 1, 0, 1, 0, 3, 0, 0, 0, 3, 1, 2, 3]);
+        class.extend_from_slice(&[0, 1, 0, 2, 0, 1, 0, 0]);
+        assert_eq!(method_names(&class).unwrap(), ["shouldWork", "helper"]);
+        assert!(method_names(&class[..class.len() - 4]).is_err());
+    }
+
+    #[test]
+    fn test_release_mapping() {
+        assert_eq!(release_of(65), 21);
+        assert_eq!(release_of(52), 8);
+        assert_eq!(major_of(17), 61);
+        assert_eq!(parse_release("21"), Some(21));
+        assert_eq!(parse_release("1.8"), Some(8));
+        assert_eq!(parse_release("latest"), None);
+    }
+
+    #[test]
+    fn test_classes_newer_than() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let jar = temp.path().join("dep.jar");
+        let mut zip = zip::ZipWriter::new(File::create(&jar).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        for (name, major) in [
+            ("com/acme/Old.class", 52u16),
+            ("com/acme/New.class", 65),
+            ("module-info.class", 65),
+            ("META-INF/versions/21/com/acme/Old.class", 65),
+        ] {
+            zip.start_file(name, options).unwrap();
+            zip.write_all(&[0xCA, 0xFE, 0xBA, 0xBE, 0, 0]).unwrap();
+            zip.write_all(&major.to_be_bytes()).unwrap();
+        }
+        zip.start_file("com/acme/readme.txt", options).unwrap();
+        zip.write_all(b"hello").unwrap();
+        zip.finish().unwrap();
+
+        assert_eq!(
+            classes_newer_than(&jar, 17).unwrap(),
+            vec![("com/acme/New.class".to_string(), 65)]
+        );
+        assert!(classes_newer_than(&jar, 21).unwrap().is_empty());
+    }
+}
@@ -1,8 +1,12 @@
-use anyhow::{bail, Context, Result};
+use anyhow::{Context, Result};
+use quick_xml::events::Event;
+use quick_xml::Reader;
 use sha2::{Digest, Sha256};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::cleanup::InProgress;
+use crate::config;
 use crate::context::GlobalContext;
 use crate::errors::JargoError;
 
@@ -29,7 +33,7 @@ pub fn fetch_metadata(
     artifact: &str,
     version: &str,
 ) -> Result<FetchedMetadata> {
-    let cache_dir = gctx.jargo_home.join("cache");
+    let cache_dir = gctx.cache_dir();
     let dir = artifact_dir(&cache_dir, group, artifact, version);
     fs::create_dir_all(&dir)
         .with_context(|| format!("failed to create cache dir {}", dir.display()))?;
@@ -68,7 +72,7 @@ pub fn fetch_metadata(
     let client = http_client()?;
 
     // Try .module first
-    let module_url = maven_central_url(group, artifact, version, "module");
+    let module_url = artifact_url(&repository_url(), group, artifact, version, "module");
     gctx.shell
         .verbose(|sh| sh.print(format!("  [verbose]   downloading .module: {}", module_url)));
     if try_download(&client, &module_url, &module_path)? {
@@ -83,7 +87,7 @@ pub fn fetch_metadata(
     }
 
     // Fall back to .pom
-    let pom_url = maven_central_url(group, artifact, version, "pom");
+    let pom_url = artifact_url(&repository_url(), group, artifact, version, "pom");
     gctx.shell.verbose(|sh| {
         sh.print(format!(
             "  [verbose]   .module not found, trying .pom: {}",
@@ -117,7 +121,7 @@ pub fn fetch_pom(
     artifact: &str,
     version: &str,
 ) -> Result<PathBuf> {
-    let cache_dir = gctx.jargo_home.join("cache");
+    let cache_dir = gctx.cache_dir();
     let dir = artifact_dir(&cache_dir, group, artifact, version);
     fs::create_dir_all(&dir)
         .with_context(|| format!("failed to create cache dir {}", dir.display()))?;
@@ -134,7 +138,7 @@ pub fn fetch_pom(
     }
 
     let client = http_client()?;
-    let pom_url = maven_central_url(group, artifact, version, "pom");
+    let pom_url = artifact_url(&repository_url(), group, artifact, version, "pom");
     gctx.shell.verbose(|sh| {
         sh.print(format!(
             "  [verbose]   downloading parent .pom: {}",
@@ -164,13 +168,43 @@ pub fn fetch_jar(
     artifact: &str,
     version: &str,
 ) -> Result<(PathBuf, String)> {
-    let cache_dir = gctx.jargo_home.join("cache");
+    fetch_jar_file(gctx, group, artifact, version, None)
+}
+
+/// Fetch the JAR with `classifier` for an artifact, e.g. JaCoCo's
+/// `org.jacoco.agent-0.8.12-runtime.jar`. Cached like [`fetch_jar`].
+pub fn fetch_classified_jar(
+    gctx: &GlobalContext,
+    group: &str,
+    artifact: &str,
+    version: &str,
+    classifier: &str,
+) -> Result<PathBuf> {
+    let (path, _sha256) = fetch_jar_file(gctx, group, artifact, version, Some(classifier))?;
+    Ok(path)
+}
+
+/// Fetch `{artifact}-{version}.jar`, or `{artifact}-{version}-{classifier}.jar`.
+#[tracing::instrument(level = "debug", skip(gctx))]
+fn fetch_jar_file(
+    gctx: &GlobalContext,
+    group: &str,
+    artifact: &str,
+    version: &str,
+    classifier: Option<&str>,
+) -> Result<(PathBuf, String)> {
+    let cache_dir = gctx.cache_dir();
     let dir = artifact_dir(&cache_dir, group, artifact, version);
     fs::create_dir_all(&dir)
         .with_context(|| format!("failed to create cache dir {}", dir.display()))?;
 
-    let jar_path = dir.join(artifact_filename(artifact, version, "jar"));
-    let sha_path = dir.join(artifact_filename(artifact, version, "jar.sha256"));
+    // The classifier goes between the version and the extension
+    let file_version = match classifier {
+        Some(classifier) => format!("{}-{}", version, classifier),
+        None => version.to_string(),
+    };
+    let jar_path = dir.join(artifact_filename(artifact, &file_version, "jar"));
+    let sha_path = dir.join(artifact_filename(artifact, &file_version, "jar.sha256"));
 
     if jar_path.exists() && sha_path.exists() {
         gctx.shell.verbose(|sh| {
@@ -187,12 +221,23 @@ pub fn fetch_jar(
     }
 
     // Download the JAR
-    let url = maven_central_url(group, artifact, version, "jar");
+    let url = format!(
+        "{}/{}/{}/{}/{}",
+        repository_url(),
+        group_to_path(group),
+        artifact,
+        version,
+        artifact_filename(artifact, &file_version, "jar"),
+    );
     gctx.shell
         .verbose(|sh| sh.print(format!("  [verbose]   downloading .jar: {}", url)));
+    let what = match classifier {
+        Some(classifier) => format!("{} jar", classifier),
+        None => "jar".to_string(),
+    };
     gctx.shell.status(
         "Fetching",
-        &format!("{}:{}:{} (jar)", group, artifact, version),
+        &format!("{}:{}:{} ({})", group, artifact, version, what),
     );
 
     let client = http_client()?;
@@ -231,10 +276,24 @@ pub fn group_to_path(group: &str) -> String {
     group.replace('.', "/")
 }
 
-/// Build the full Maven Central URL for a given artifact and file extension.
-pub fn maven_central_url(group: &str, artifact: &str, version: &str, ext: &str) -> String {
+/// Base URL of Maven Central.
+pub const MAVEN_CENTRAL: &str = "https://repo1.maven.org/maven2";
+
+/// Base URL artifacts are downloaded from: `JARGO_MAVEN_REPO` when set (a
+/// mirror, or a local repository in tests), else [`MAVEN_CENTRAL`].
+pub fn repository_url() -> String {
+    std::env::var("JARGO_MAVEN_REPO")
+        .ok()
+        .filter(|url| !url.is_empty())
+        .map(|url| url.trim_end_matches('/').to_string())
+        .unwrap_or_else(|| MAVEN_CENTRAL.to_string())
+}
+
+/// Build the full repository URL for a given artifact and file extension.
+pub fn artifact_url(base: &str, group: &str, artifact: &str, version: &str, ext: &str) -> String {
     format!(
-        "https://repo1.maven.org/maven2/{}/{}/{}/{}",
+        "{}/{}/{}/{}/{}",
+        base,
         group_to_path(group),
         artifact,
         version,
@@ -249,6 +308,95 @@ pub fn artifact_filename(artifact: &str, version: &str, ext: &str) -> String {
     format!("{}-{}.{}", artifact, version, ext)
 }
 
+/// Whether `repository` has the POM of `group:artifact:version`, i.e. that
+/// version has been published there. `auth` is a user and password for
+/// repositories that need one to read.
+pub fn artifact_exists(
+    repository: &str,
+    auth: Option<(&str, &str)>,
+    group: &str,
+    artifact: &str,
+    version: &str,
+) -> Result<bool> {
+    let url = artifact_url(repository, group, artifact, version, "pom");
+    config::ensure_online(&url)?;
+    let mut request = http_client()?.get(&url);
+    if let Some((user, password)) = auth {
+        request = request.basic_auth(user, Some(password));
+    }
+    let response = request
+        .send()
+        .with_context(|| format!("HTTP request failed: {}", url))?;
+    match response.status() {
+        reqwest::StatusCode::NOT_FOUND => Ok(false),
+        status if status.is_success() => Ok(true),
+        status => Err(JargoError::HttpStatus(status.to_string(), url).into()),
+    }
+}
+
+/// Every version of `group:artifact` the configured repository lists in its
+/// `maven-metadata.xml`, oldest first. An artifact that was never published
+/// has none.
+pub fn published_versions(group: &str, artifact: &str) -> Result<Vec<String>> {
+    let url = format!(
+        "{}/{}/{}/maven-metadata.xml",
+        repository_url(),
+        group_to_path(group),
+        artifact
+    );
+    config::ensure_online(&url)?;
+    let response = http_client()?
+        .get(&url)
+        .send()
+        .with_context(|| format!("HTTP request failed: {}", url))?;
+    match response.status() {
+        reqwest::StatusCode::NOT_FOUND => Ok(Vec::new()),
+        status if status.is_success() => {
+            let xml = response
+                .text()
+                .with_context(|| format!("failed to read {}", url))?;
+            parse_versions(&xml).with_context(|| format!("failed to parse {}", url))
+        }
+        status => Err(JargoError::HttpStatus(status.to_string(), url).into()),
+    }
+}
+
+/// Download any `url` to `dest`. Returns `Ok(false)` if the server returned
+/// 404.
+pub fn fetch_url(url: &str, dest: &Path) -> Result<bool> {
+    try_download(&http_client()?, url, dest)
+}
+
+/// `{dest}.tmp`, where `dest` is written before it is renamed into place.
+/// Keeping the extension apart keeps a JAR's and a POM's downloads apart.
+pub(crate) fn tmp_path(dest: &Path) -> PathBuf {
+    let mut name = dest.as_os_str().to_owned();
+    name.push(".tmp");
+    PathBuf::from(name)
+}
+
+/// The `<versions>` of a `maven-metadata.xml`.
+fn parse_versions(xml: &str) -> Result<Vec<String>> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+    let mut stack: Vec<Vec<u8>> = Vec::new();
+    let mut versions = Vec::new();
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) => stack.push(e.name().as_ref().to_vec()),
+            Event::End(_) => {
+                stack.pop();
+            }
+            Event::Text(e) if stack.ends_with(&[b"versions".to_vec(), b"version".to_vec()]) => {
+                versions.push(e.unescape()?.into_owned());
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(versions)
+}
+
 // --- Private helpers ---
 
 fn http_client() -> Result<reqwest::blocking::Client> {
@@ -258,11 +406,13 @@ fn http_client() -> Result<reqwest::blocking::Client> {
         .context("failed to create HTTP client")
 }
 
-/// Download `url` to `dest`, writing atomically via a `.tmp` sibling file.
+/// Download `url` to `dest`, writing atomically via a `.tmp` sibling file
+/// that is removed if the download fails or is interrupted.
 ///
 /// Returns `Ok(true)` on success, `Ok(false)` if the server returned 404,
 /// and `Err` on any other failure.
 fn try_download(client: &reqwest::blocking::Client, url: &str, dest: &Path) -> Result<bool> {
+    config::ensure_online(url)?;
     let response = client
         .get(url)
         .send()
@@ -273,7 +423,7 @@ fn try_download(client: &reqwest::blocking::Client, url: &str, dest: &Path) -> R
     }
 
     if !response.status().is_success() {
-        bail!("HTTP {} fetching {}", response.status(), url);
+        return Err(JargoError::HttpStatus(response.status().to_string(), url.to_string()).into());
     }
 
     let bytes = response
@@ -281,7 +431,8 @@ fn try_download(client: &reqwest::blocking::Client, url: &str, dest: &Path) -> R
         .with_context(|| format!("failed to read response body from {}", url))?;
 
     // Atomic write: write to .tmp first, then rename
-    let tmp = dest.with_extension("tmp");
+    let tmp = tmp_path(dest);
+    let _in_progress = InProgress::new(&tmp);
     fs::write(&tmp, &bytes)
         .with_context(|| format!("failed to write temporary file {}", tmp.display()))?;
     fs::rename(&tmp, dest)
@@ -303,6 +454,15 @@ mod tests {
     use super::*;
     use tempfile::TempDir;
 
+    #[test]
+    fn test_parse_versions() {
+        let xml = "<metadata><groupId>com.acme</groupId><artifactId>widget</artifactId>\
+                   <versioning><latest>1.1.0</latest><release>1.1.0</release>\
+                   <versions><version>1.0.0</version><version>1.1.0</version></versions>\
+                   </versioning></metadata>";
+        assert_eq!(parse_versions(xml).unwrap(), ["1.0.0", "1.1.0"]);
+    }
+
     #[test]
     fn test_group_to_path() {
         assert_eq!(group_to_path("com.google.guava"), "com/google/guava");
@@ -327,13 +487,19 @@ mod tests {
     }
 
     #[test]
-    fn test_maven_central_url() {
+    fn test_artifact_url() {
         assert_eq!(
-            maven_central_url("com.google.guava", "guava", "33.0.0-jre", "jar"),
+            artifact_url(
+                MAVEN_CENTRAL,
+                "com.google.guava",
+                "guava",
+                "33.0.0-jre",
+                "jar"
+            ),
             "https://repo1.maven.org/maven2/com/google/guava/guava/33.0.0-jre/guava-33.0.0-jre.jar"
         );
         assert_eq!(
-            maven_central_url("org.apache.commons", "commons-lang3", "3.14.0", "pom"),
+            artifact_url(MAVEN_CENTRAL, "org.apache.commons", "commons-lang3", "3.14.0", "pom"),
             "https://repo1.maven.org/maven2/org/apache/commons/commons-lang3/3.14.0/commons-lang3-3.14.0.pom"
         );
     }
@@ -0,0 +1,375 @@
+use anyhow::{bail, Context, Result};
+use base64::Engine;
+use md5::Md5;
+use reqwest::blocking::multipart::{Form, Part};
+use reqwest::blocking::Client;
+use sha1::{Digest, Sha1};
+use std::fs;
+use std::io::{Cursor, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use crate::cache::group_to_path;
+use crate::config;
+
+/// Base URL of the Central Portal publisher API.
+pub const CENTRAL_API: &str = "https://central.sonatype.com/api/v1/publisher";
+
+/// Where deployments that are not released automatically are reviewed and
+/// published.
+pub const DEPLOYMENTS_URL: &str = "https://central.sonatype.com/publishing/deployments";
+
+/// Where `jargo publish --central` writes the bundle it uploads.
+pub const BUNDLE_PATH: &str = "target/central-bundle.zip";
+
+/// `[package]` fields Maven Central requires in a POM: the description,
+/// license, project URL, developers, and SCM information come from these.
+pub const REQUIRED_METADATA: &[&str] = &["description", "license", "repository", "authors"];
+
+/// Zip the files of a release into a Central Portal bundle, in the Maven
+/// layout: `{group path}/{artifact}/{version}/{artifact}-{version}{suffix}`,
+/// each with its `.asc` signature from `sign` and `.md5` and `.sha1`
+/// checksums. `files` are `(suffix, path)` pairs, e.g. `("-sources.jar",
+/// target/demo-sources.jar)`.
+pub fn bundle(
+    group: &str,
+    artifact: &str,
+    version: &str,
+    files: &[(&str, &Path)],
+    sign: impl Fn(&Path) -> Result<Vec<u8>>,
+) -> Result<Vec<u8>> {
+    let dir = format!("{}/{}/{}", group_to_path(group), artifact, version);
+    let options = SimpleFileOptions::default().unix_permissions(0o644);
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+
+    for (suffix, path) in files {
+        let data = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+        let signature = sign(path)?;
+        let name = format!("{}/{}-{}{}", dir, artifact, version, suffix);
+        let entries = [
+            (name.clone(), data.clone()),
+            (format!("{}.asc", name), signature),
+            (
+                format!("{}.md5", name),
+                format!("{:x}", Md5::digest(&data)).into_bytes(),
+            ),
+            (
+                format!("{}.sha1", name),
+                format!("{:x}", Sha1::digest(&data)).into_bytes(),
+            ),
+        ];
+        for (name, contents) in entries {
+            zip.start_file(&name, options)
+                .with_context(|| format!("failed to add {} to the bundle", name))?;
+            zip.write_all(&contents)
+                .with_context(|| format!("failed to add {} to the bundle", name))?;
+        }
+    }
+
+    let bundle = zip.finish().context("failed to finish the bundle")?;
+    Ok(bundle.into_inner())
+}
+
+/// Detached, ASCII-armored signature of the file at `path` by `gpg`, with
+/// the key in `JARGO_GPG_KEY` (or gpg's default key) and the passphrase in
+/// `JARGO_GPG_PASSPHRASE`, if it needs one.
+pub fn gpg_sign(path: &Path) -> Result<Vec<u8>> {
+    let mut cmd = Command::new("gpg");
+    cmd.args([
+        "--batch",
+        "--yes",
+        "--armor",
+        "--detach-sign",
+        "--output",
+        "-",
+    ]);
+    if let Some(key) = std::env::var("JARGO_GPG_KEY")
+        .ok()
+        .filter(|k| !k.is_empty())
+    {
+        cmd.args(["--local-user", &key]);
+    }
+    let passphrase = std::env::var("JARGO_GPG_PASSPHRASE").ok();
+    if passphrase.is_some() {
+        cmd.args(["--pinentry-mode", "loopback", "--passphrase-fd", "0"]);
+    }
+    cmd.arg(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    let mut child = cmd.spawn().map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            anyhow::anyhow!("`gpg` not found; Maven Central requires signed artifacts")
+        } else {
+            anyhow::Error::from(e).context("failed to run gpg")
+        }
+    })?;
+    if let (Some(passphrase), Some(mut stdin)) = (passphrase, child.stdin.take()) {
+        stdin
+            .write_all(format!("{}\n", passphrase).as_bytes())
+            .context("failed to pass the passphrase to gpg")?;
+    }
+    let output = child.wait_with_output().context("failed to run gpg")?;
+    if !output.status.success() {
+        bail!(
+            "gpg failed to sign {}:\n{}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        );
+    }
+    Ok(output.stdout)
+}
+
+/// Where a deployment is in the Central Portal's pipeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeploymentState {
+    Pending,
+    Validating,
+    /// Passed validation; waiting to be published from the portal.
+    Validated,
+    Publishing,
+    Published,
+    Failed,
+}
+
+impl DeploymentState {
+    fn parse(state: &str) -> Result<Self> {
+        Ok(match state {
+            "PENDING" => DeploymentState::Pending,
+            "VALIDATING" => DeploymentState::Validating,
+            "VALIDATED" => DeploymentState::Validated,
+            "PUBLISHING" => DeploymentState::Publishing,
+            "PUBLISHED" => DeploymentState::Published,
+            "FAILED" => DeploymentState::Failed,
+            other => bail!("unknown deployment state `{}`", other),
+        })
+    }
+}
+
+impl std::fmt::Display for DeploymentState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            DeploymentState::Pending => "pending",
+            DeploymentState::Validating => "validating",
+            DeploymentState::Validated => "validated",
+            DeploymentState::Publishing => "publishing",
+            DeploymentState::Published => "published",
+            DeploymentState::Failed => "failed",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Status of a deployment, from the portal's `/status` endpoint.
+#[derive(Debug, PartialEq)]
+pub struct DeploymentStatus {
+    pub state: DeploymentState,
+    /// Validation errors of a failed deployment, as `{component}: {message}`.
+    pub errors: Vec<String>,
+}
+
+/// Client for the Central Portal publisher API.
+///
+/// Authenticates with a portal user token, `JARGO_CENTRAL_USERNAME` and
+/// `JARGO_CENTRAL_PASSWORD`.
+pub struct CentralClient {
+    client: Client,
+    base_url: String,
+    token: String,
+}
+
+impl CentralClient {
+    pub fn new(base_url: &str) -> Result<Self> {
+        config::ensure_online(base_url)?;
+        let (user, password) = match (
+            std::env::var("JARGO_CENTRAL_USERNAME"),
+            std::env::var("JARGO_CENTRAL_PASSWORD"),
+        ) {
+            (Ok(user), Ok(password)) if !user.is_empty() => (user, password),
+            _ => bail!(
+                "publishing to Maven Central requires a Central Portal user token: \
+                 set JARGO_CENTRAL_USERNAME and JARGO_CENTRAL_PASSWORD"
+            ),
+        };
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(300))
+            .build()
+            .context("failed to create HTTP client")?;
+        let token =
+            base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", user, password));
+        Ok(Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token,
+        })
+    }
+
+    /// Upload a bundle as deployment `name`. With `automatic`, the portal
+    /// publishes it as soon as it validates; otherwise it waits to be
+    /// published from the portal. Returns the deployment ID.
+    pub fn upload(&self, name: &str, bundle: Vec<u8>, automatic: bool) -> Result<String> {
+        let url = format!("{}/upload", self.base_url);
+        let part = Part::bytes(bundle)
+            .file_name("bundle.zip")
+            .mime_str("application/octet-stream")?;
+        let publishing_type = if automatic {
+            "AUTOMATIC"
+        } else {
+            "USER_MANAGED"
+        };
+        let response = self
+            .client
+            .post(&url)
+            .bearer_auth(&self.token)
+            .query(&[("name", name), ("publishingType", publishing_type)])
+            .multipart(Form::new().part("bundle", part))
+            .send()
+            .with_context(|| format!("failed to upload the bundle to {}", url))?;
+        let id = check(response, &url)?
+            .text()
+            .context("failed to read the deployment ID")?;
+        Ok(id.trim().to_string())
+    }
+
+    /// Current status of deployment `id`.
+    pub fn status(&self, id: &str) -> Result<DeploymentStatus> {
+        let url = format!("{}/status", self.base_url);
+        let response = self
+            .client
+            .post(&url)
+            .bearer_auth(&self.token)
+            .query(&[("id", id)])
+            .send()
+            .with_context(|| format!("HTTP request to {} failed", url))?;
+        let body = check(response, &url)?
+            .text()
+            .context("failed to read the deployment status")?;
+        parse_status(&body).with_context(|| format!("invalid deployment status from {}", url))
+    }
+}
+
+fn check(response: reqwest::blocking::Response, url: &str) -> Result<reqwest::blocking::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().unwrap_or_default();
+    bail!("HTTP {} from {}: {}", status, url, body.trim())
+}
+
+fn parse_status(json: &str) -> Result<DeploymentStatus> {
+    let value: serde_json::Value = serde_json::from_str(json)?;
+    let state = value
+        .get("deploymentState")
+        .and_then(|s| s.as_str())
+        .context("missing `deploymentState`")?;
+    let mut errors = Vec::new();
+    // `errors` maps each component to its messages
+    if let Some(components) = value.get("errors").and_then(|e| e.as_object()) {
+        for (component, messages) in components {
+            let messages = match messages {
+                serde_json::Value::Array(messages) => messages.clone(),
+                message => vec![message.clone()],
+            };
+            for message in messages {
+                let message = match message {
+                    serde_json::Value::String(message) => message,
+                    other => other.to_string(),
+                };
+                errors.push(format!("{}: {}", component, message));
+            }
+        }
+    }
+    Ok(DeploymentStatus {
+        state: DeploymentState::parse(state)?,
+        errors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn test_bundle() {
+        let tmp = tempfile::tempdir().unwrap();
+        let jar = tmp.path().join("parser.jar");
+        let pom = tmp.path().join("pom.xml");
+        fs::write(&jar, "jar").unwrap();
+        fs::write(&pom, "pom").unwrap();
+
+        let bundle = bundle(
+            "com.example",
+            "parser",
+            "1.2.0",
+            &[(".jar", &jar), (".pom", &pom)],
+            |path| Ok(format!("signature of {}", path.display()).into_bytes()),
+        )
+        .unwrap();
+
+        let mut archive = zip::ZipArchive::new(Cursor::new(bundle)).unwrap();
+        let mut names: Vec<&str> = archive.file_names().collect();
+        names.sort();
+        let dir = "com/example/parser/1.2.0";
+        let expected: Vec<String> = ["parser-1.2.0.jar", "parser-1.2.0.pom"]
+            .iter()
+            .flat_map(|file| {
+                ["", ".asc", ".md5", ".sha1"].map(|ext| format!("{}/{}{}", dir, file, ext))
+            })
+            .collect();
+        assert_eq!(names, expected);
+
+        let read = |archive: &mut zip::ZipArchive<Cursor<Vec<u8>>>, name: &str| {
+            let mut contents = String::new();
+            archive
+                .by_name(name)
+                .unwrap()
+                .read_to_string(&mut contents)
+                .unwrap();
+            contents
+        };
+        assert_eq!(
+            read(&mut archive, &format!("{}/parser-1.2.0.jar.sha1", dir)),
+            "f92e777f4341930bad9b2422283c4680d00dbc06"
+        );
+        assert_eq!(
+            read(&mut archive, &format!("{}/parser-1.2.0.jar.md5", dir)),
+            format!("{:x}", Md5::digest(b"jar"))
+        );
+        assert_eq!(
+            read(&mut archive, &format!("{}/parser-1.2.0.pom.asc", dir)),
+            format!("signature of {}", pom.display())
+        );
+    }
+
+    #[test]
+    fn test_parse_status() {
+        let status = parse_status(
+            r#"{"deploymentId":"28570f16-da32-4c14-bd2e-c1acc0782365","deploymentName":"parser-1.2.0","deploymentState":"PUBLISHED","purls":["pkg:maven/com.example/parser@1.2.0"]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            status,
+            DeploymentStatus {
+                state: DeploymentState::Published,
+                errors: Vec::new(),
+            }
+        );
+
+        let failed = parse_status(
+            r#"{"deploymentState":"FAILED","errors":{"pkg:maven/com.example/parser@1.2.0":["Missing signature for file: parser-1.2.0.jar"]}}"#,
+        )
+        .unwrap();
+        assert_eq!(failed.state, DeploymentState::Failed);
+        assert_eq!(
+            failed.errors,
+            ["pkg:maven/com.example/parser@1.2.0: Missing signature for file: parser-1.2.0.jar"]
+        );
+
+        assert!(parse_status(r#"{"deploymentState":"LOST"}"#).is_err());
+    }
+}
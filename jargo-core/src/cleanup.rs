@@ -0,0 +1,95 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Paths being written right now, removed by [`remove_in_progress`] when
+/// jargo is interrupted.
+static IN_PROGRESS: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// A file or directory that only exists while something is written, such as
+/// a download's `.tmp` file, a JAR before it is renamed into place, or a
+/// scratch directory. Whatever is left at the path is removed when the guard
+/// is dropped, or by [`remove_in_progress`] on Ctrl-C, so neither a failure
+/// nor an interrupt leaves it behind.
+#[derive(Debug)]
+pub struct InProgress {
+    path: PathBuf,
+}
+
+impl InProgress {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        lock().push(path.clone());
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for InProgress {
+    fn drop(&mut self) {
+        let mut paths = lock();
+        if let Some(i) = paths.iter().rposition(|p| *p == self.path) {
+            paths.remove(i);
+        }
+        drop(paths);
+        remove(&self.path);
+    }
+}
+
+/// Remove every path still being written. Called from the Ctrl-C handler
+/// just before jargo exits; the guards are never dropped then.
+pub fn remove_in_progress() {
+    for path in lock().drain(..) {
+        remove(&path);
+    }
+}
+
+fn lock() -> std::sync::MutexGuard<'static, Vec<PathBuf>> {
+    IN_PROGRESS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn remove(path: &Path) {
+    match fs::symlink_metadata(path) {
+        Ok(meta) if meta.is_dir() => {
+            let _ = fs::remove_dir_all(path);
+        }
+        Ok(_) => {
+            let _ = fs::remove_file(path);
+        }
+        Err(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_progress() {
+        let tmp = tempfile::tempdir().unwrap();
+        let file = tmp.path().join("demo.jar.tmp");
+        let dir = tmp.path().join("scratch");
+
+        // Renamed into place: nothing left to remove
+        let guard = InProgress::new(&file);
+        fs::write(guard.path(), "PK").unwrap();
+        fs::rename(&file, tmp.path().join("demo.jar")).unwrap();
+        drop(guard);
+        assert!(tmp.path().join("demo.jar").exists());
+
+        // Abandoned: removed when dropped
+        let guard = InProgress::new(&dir);
+        fs::create_dir_all(dir.join("target")).unwrap();
+        drop(guard);
+        assert!(!dir.exists());
+
+        // Registered while alive, for the Ctrl-C handler
+        let guard = InProgress::new(&file);
+        assert!(lock().contains(&file));
+        drop(guard);
+        assert!(!lock().contains(&file));
+    }
+}
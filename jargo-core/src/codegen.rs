@@ -0,0 +1,229 @@
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::SystemTime;
+
+use crate::context::GlobalContext;
+use crate::errors::JargoError;
+use crate::manifest::{CodegenStep, JargoToml};
+
+/// Directory holding one output directory per `[[codegen]]` step.
+pub const GENERATED_DIR: &str = "target/generated-sources";
+
+/// Written into each output directory after a successful run; holds the
+/// command so an edited command reruns the step.
+const STAMP_FILE: &str = ".jargo-codegen";
+
+/// Output directory of a `[[codegen]]` step: `target/generated-sources/{name}`.
+pub fn output_dir(project_root: &Path, step: &CodegenStep) -> PathBuf {
+    project_root.join(GENERATED_DIR).join(&step.name)
+}
+
+/// Run every `[[codegen]]` step that is out of date, in declaration order, and
+/// return all output directories (generated source roots).
+///
+/// A step is up to date when its stamp records the same command and none of
+/// its `inputs` changed since; steps without `inputs` always run. `{out}` in
+/// the command is replaced with the output directory, which is also exported
+/// as `JARGO_CODEGEN_OUT`. The output directory is emptied before each run.
+pub fn run_codegen(
+    gctx: &GlobalContext,
+    project_root: &Path,
+    manifest: &JargoToml,
+) -> Result<Vec<PathBuf>> {
+    let mut roots = Vec::new();
+    for step in &manifest.codegen {
+        validate_name(&step.name)?;
+        let out = output_dir(project_root, step);
+
+        if is_up_to_date(project_root, step, &out)? {
+            gctx.shell.verbose(|sh| {
+                sh.print(format!("  [verbose] codegen `{}` is up to date", step.name))
+            });
+        } else {
+            gctx.shell.status("Generating", &step.name);
+            if out.exists() {
+                fs::remove_dir_all(&out)
+                    .with_context(|| format!("failed to remove {}", out.display()))?;
+            }
+            fs::create_dir_all(&out)
+                .with_context(|| format!("failed to create {}", out.display()))?;
+
+            let command = step.command.replace("{out}", &out.display().to_string());
+            gctx.shell
+                .verbose(|sh| sh.print(format!("  [verbose] running `{}`", command)));
+            let status = shell_command(&command)
+                .env("JARGO_CODEGEN_OUT", &out)
+                .current_dir(project_root)
+                .status()
+                .with_context(|| format!("failed to run codegen step `{}`", step.name))?;
+            if !status.success() {
+                return Err(JargoError::CodegenFailed(step.name.clone()).into());
+            }
+
+            fs::write(out.join(STAMP_FILE), &step.command)
+                .with_context(|| format!("failed to write stamp in {}", out.display()))?;
+        }
+        roots.push(out);
+    }
+    Ok(roots)
+}
+
+/// Output directories of steps that have already run, without running any.
+pub fn generated_roots(project_root: &Path, manifest: &JargoToml) -> Vec<PathBuf> {
+    manifest
+        .codegen
+        .iter()
+        .map(|step| output_dir(project_root, step))
+        .filter(|dir| dir.is_dir())
+        .collect()
+}
+
+/// Step names become directory names.
+fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        bail!(
+            "invalid [[codegen]] name `{}`: use letters, digits, `-` and `_`",
+            name
+        );
+    }
+    Ok(())
+}
+
+fn is_up_to_date(project_root: &Path, step: &CodegenStep, out: &Path) -> Result<bool> {
+    if step.inputs.is_empty() {
+        return Ok(false);
+    }
+    let stamp = out.join(STAMP_FILE);
+    let (Ok(command), Ok(meta)) = (fs::read_to_string(&stamp), fs::metadata(&stamp)) else {
+        return Ok(false);
+    };
+    if command != step.command {
+        return Ok(false);
+    }
+    let stamped = meta.modified()?;
+    for input in &step.inputs {
+        match newest_mtime(&project_root.join(input))? {
+            Some(modified) if modified <= stamped => {}
+            // Changed, or missing (let the generator report it)
+            _ => return Ok(false),
+        }
+    }
+    Ok(true)
+}
+
+/// Newest modification time of a file, or of any file under a directory.
+fn newest_mtime(path: &Path) -> Result<Option<SystemTime>> {
+    let Ok(meta) = fs::metadata(path) else {
+        return Ok(None);
+    };
+    if !meta.is_dir() {
+        return Ok(Some(meta.modified()?));
+    }
+    let mut newest = meta.modified()?;
+    for entry in fs::read_dir(path)
+        .with_context(|| format!("failed to read directory {}", path.display()))?
+    {
+        if let Some(modified) = newest_mtime(&entry?.path())? {
+            newest = newest.max(modified);
+        }
+    }
+    Ok(Some(newest))
+}
+
+#[cfg(not(windows))]
+fn shell_command(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+#[cfg(windows)]
+fn shell_command(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::shell::{Shell, Verbosity};
+
+    fn setup(codegen: &str) -> (tempfile::TempDir, GlobalContext, JargoToml) {
+        let temp = tempfile::TempDir::new().unwrap();
+        let gctx = GlobalContext {
+            cwd: temp.path().to_path_buf(),
+            jargo_home: temp.path().join(".jargo"),
+            shell: Shell::new(Verbosity::Quiet),
+            config: Default::default(),
+            locked: false,
+            features: Vec::new(),
+        };
+        let manifest = toml::from_str(&format!(
+            "[package]\nname = \"demo\"\nversion = \"1.0.0\"\njava = \"21\"\n{}",
+            codegen
+        ))
+        .unwrap();
+        (temp, gctx, manifest)
+    }
+
+    #[test]
+    fn test_runs_step_into_output_dir() {
+        let (temp, gctx, manifest) = setup(
+            r#"
+[[codegen]]
+name = "hello"
+command = "mkdir -p {out}/gen && echo 'package gen; class Hello {}' > {out}/gen/Hello.java"
+"#,
+        );
+
+        let roots = run_codegen(&gctx, temp.path(), &manifest).unwrap();
+
+        let out = temp.path().join("target/generated-sources/hello");
+        assert_eq!(roots, vec![out.clone()]);
+        assert!(out.join("gen/Hello.java").exists());
+        assert_eq!(generated_roots(temp.path(), &manifest), vec![out]);
+    }
+
+    #[test]
+    fn test_skips_step_when_inputs_unchanged() {
+        let (temp, gctx, manifest) = setup(
+            r#"
+[[codegen]]
+name = "count"
+command = "echo run >> runs.txt"
+inputs = ["schema"]
+"#,
+        );
+        fs::create_dir_all(temp.path().join("schema")).unwrap();
+        fs::write(temp.path().join("schema/a.proto"), "x").unwrap();
+
+        run_codegen(&gctx, temp.path(), &manifest).unwrap();
+        run_codegen(&gctx, temp.path(), &manifest).unwrap();
+        let runs = fs::read_to_string(temp.path().join("runs.txt")).unwrap();
+        assert_eq!(runs.lines().count(), 1);
+
+        // Touch an input after the stamp
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        fs::write(temp.path().join("schema/b.proto"), "y").unwrap();
+        run_codegen(&gctx, temp.path(), &manifest).unwrap();
+        let runs = fs::read_to_string(temp.path().join("runs.txt")).unwrap();
+        assert_eq!(runs.lines().count(), 2);
+    }
+
+    #[test]
+    fn test_failed_step_and_invalid_name() {
+        let (temp, gctx, manifest) = setup("[[codegen]]\nname = \"bad\"\ncommand = \"exit 3\"\n");
+        let err = run_codegen(&gctx, temp.path(), &manifest).unwrap_err();
+        assert!(err.to_string().contains("`bad`"), "{}", err);
+
+        let (temp, gctx, manifest) = setup("[[codegen]]\nname = \"../x\"\ncommand = \"true\"\n");
+        assert!(run_codegen(&gctx, temp.path(), &manifest).is_err());
+    }
+}
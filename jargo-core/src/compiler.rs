@@ -1,93 +1,834 @@
 use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::process::Command;
 
+use crate::backend::{self, Invocation};
+use crate::codegen;
 use crate::context::GlobalContext;
+use crate::error_prone;
 use crate::errors::JargoError;
-use crate::manifest::JargoToml;
+use crate::manifest::{CompilerKind, JargoToml};
+use crate::message::Message;
+use crate::shell::{paint, Shell, Verbosity};
 use crate::staging;
+use crate::suggest;
+use crate::toolchain;
 
 pub struct CompileOutput {
     pub success: bool,
     pub errors: Vec<String>,
+    /// Sources (relative to the project root) that could not be compiled
+    /// around the errors. Only populated by [`compile_keep_going`].
+    pub failed_sources: Vec<PathBuf>,
+    /// javac output of a successful compile (warnings and notes), with paths
+    /// rewritten like `errors`.
+    pub warnings: Vec<String>,
+}
+
+impl CompileOutput {
+    /// Print the compile errors to stderr as Cargo-style diagnostics, colored
+    /// per `--color`. In JSON mode each diagnostic is emitted as a
+    /// `compiler-message` instead. A missing package of a well-known library
+    /// gets a note with the `jargo add` command for it.
+    pub fn print_errors(&self, shell: &Shell, package: &str) {
+        let mut diagnostics = parse_diagnostics(&self.errors);
+        for diagnostic in &mut diagnostics {
+            if let Some(help) = suggest::missing_dependency_help(&diagnostic.message) {
+                diagnostic.notes.push(help);
+            }
+        }
+        if shell.is_json() {
+            for diagnostic in &diagnostics {
+                shell.message(&Message::compiler_message(package, diagnostic));
+            }
+        } else if diagnostics.is_empty() {
+            let text: String = self.errors.iter().map(|e| format!("{}\n", e)).collect();
+            shell.diagnostics(&text);
+        } else {
+            shell.diagnostics(&render_diagnostics(&diagnostics, shell.err_color()));
+        }
+    }
+
+    /// Print the warnings of a successful compile like the errors, or emit
+    /// them as `compiler-message`s in JSON mode, and return how many javac
+    /// warnings there were. Quiet mode only counts them.
+    pub fn print_warnings(&self, shell: &Shell, package: &str) -> usize {
+        let diagnostics = parse_diagnostics(&self.warnings);
+        if shell.is_json() {
+            for diagnostic in &diagnostics {
+                shell.message(&Message::compiler_message(package, diagnostic));
+            }
+        } else if shell.verbosity() != Verbosity::Quiet && !diagnostics.is_empty() {
+            shell.diagnostics(&render_diagnostics(&diagnostics, shell.err_color()));
+        }
+        diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Warning)
+            .count()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl Severity {
+    fn label(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+        }
+    }
+
+    /// ANSI color code: bold red, bold yellow, bold cyan.
+    fn color(self) -> &'static str {
+        match self {
+            Severity::Error => "1;31",
+            Severity::Warning => "1;33",
+            Severity::Note => "1;36",
+        }
+    }
+}
+
+/// One `javac` diagnostic parsed from its stderr.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    /// Source path as printed by javac, after path rewriting.
+    pub file: Option<String>,
+    pub line: Option<usize>,
+    /// 1-based column of javac's caret.
+    pub column: Option<usize>,
+    /// The offending source line javac echoes back.
+    pub source_line: Option<String>,
+    /// Trailing detail lines (e.g. `symbol:` and `location:`), trimmed.
+    pub notes: Vec<String>,
+}
+
+/// Parse (already path-rewritten) `javac` stderr lines into diagnostics.
+///
+/// Recognizes `file:line: error|warning: message` headers followed by the
+/// source line, a caret line, and indented detail lines; location-less
+/// `warning:`/`error:`/`Note:` lines; and drops the trailing `N errors` count.
+/// ECJ's `-Xemacs` output and kotlinc's `file:line:column:` headers parse
+/// the same way.
+pub fn parse_diagnostics(lines: &[String]) -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+
+    for line in lines {
+        if let Some(diagnostic) = parse_header(line) {
+            diagnostics.push(diagnostic);
+            continue;
+        }
+        if is_count_line(line) {
+            continue;
+        }
+        let Some(current) = diagnostics.last_mut() else {
+            continue;
+        };
+        if current.file.is_none() {
+            continue;
+        }
+        if current.source_line.is_none() {
+            current.source_line = Some(line.clone());
+        } else if current.column.is_none()
+            && !line.trim().is_empty()
+            && line.trim().chars().all(|c| c == '^')
+        {
+            current.column = Some(line.chars().take_while(|c| *c != '^').count() + 1);
+        } else if !line.trim().is_empty() {
+            current.notes.push(line.trim().to_string());
+        }
+    }
+
+    diagnostics
+}
+
+fn parse_header(line: &str) -> Option<Diagnostic> {
+    for (marker, severity) in [
+        (": error: ", Severity::Error),
+        (": warning: ", Severity::Warning),
+    ] {
+        let Some(idx) = line.find(marker) else {
+            continue;
+        };
+        let (location, message) = (&line[..idx], &line[idx + marker.len()..]);
+        let (mut file, mut line_no) = location.rsplit_once(':')?;
+        let mut column = None;
+        if let Some((path, row)) = file
+            .rsplit_once(':')
+            .filter(|(_, row)| row.parse::<usize>().is_ok())
+        {
+            column = line_no.parse().ok();
+            (file, line_no) = (path, row);
+        }
+        if !(file.ends_with(".java") || file.ends_with(".kt")) {
+            return None;
+        }
+        return Some(Diagnostic {
+            severity,
+            message: message.to_string(),
+            file: Some(file.to_string()),
+            line: Some(line_no.parse().ok()?),
+            column,
+            source_line: None,
+            notes: Vec::new(),
+        });
+    }
+
+    for (prefix, severity) in [
+        ("error: ", Severity::Error),
+        ("warning: ", Severity::Warning),
+        ("Note: ", Severity::Note),
+    ] {
+        if let Some(message) = line.strip_prefix(prefix) {
+            return Some(Diagnostic {
+                severity,
+                message: message.to_string(),
+                file: None,
+                line: None,
+                column: None,
+                source_line: None,
+                notes: Vec::new(),
+            });
+        }
+    }
+
+    None
+}
+
+/// `1 error`, `3 errors`, `2 warnings`, and ECJ's `2 problems (1 error, 1
+/// warning)`
+fn is_count_line(line: &str) -> bool {
+    let mut parts = line.split_whitespace();
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(n), Some("error" | "errors" | "warning" | "warnings"), None) => {
+            n.parse::<usize>().is_ok()
+        }
+        (Some(n), Some("problem" | "problems"), Some(rest)) => {
+            n.parse::<usize>().is_ok() && rest.starts_with('(')
+        }
+        _ => false,
+    }
+}
+
+/// Render diagnostics Cargo-style:
+///
+/// ```text
+/// error: cannot find symbol
+///  --> src/Main.java:5:9
+///   |
+/// 5 |         foo();
+///   |         ^
+///   = symbol: method foo()
+/// ```
+pub fn render_diagnostics(diagnostics: &[Diagnostic], color: bool) -> String {
+    let mut out = String::new();
+    for d in diagnostics {
+        out.push_str(&format!(
+            "{}{}\n",
+            paint(d.severity.color(), d.severity.label(), color),
+            paint("1", &format!(": {}", d.message), color)
+        ));
+
+        let (Some(file), Some(line)) = (&d.file, d.line) else {
+            out.push('\n');
+            continue;
+        };
+
+        let gutter = " ".repeat(line.to_string().len());
+        let location = match d.column {
+            Some(column) => format!("{}:{}:{}", file, line, column),
+            None => format!("{}:{}", file, line),
+        };
+        out.push_str(&format!(
+            "{}{} {}\n",
+            gutter,
+            paint("1;34", "-->", color),
+            location
+        ));
+
+        if let Some(source) = &d.source_line {
+            let bar = paint("1;34", "|", color);
+            out.push_str(&format!("{} {}\n", gutter, bar));
+            out.push_str(&format!(
+                "{} {} {}\n",
+                paint("1;34", &line.to_string(), color),
+                bar,
+                source
+            ));
+            if let Some(column) = d.column {
+                // Reuse the source's own leading whitespace so tabs line up.
+                let pad: String = source
+                    .chars()
+                    .take(column - 1)
+                    .map(|c| if c == '\t' { '\t' } else { ' ' })
+                    .collect();
+                out.push_str(&format!(
+                    "{} {} {}{}\n",
+                    gutter,
+                    bar,
+                    pad,
+                    paint(d.severity.color(), "^", color)
+                ));
+            }
+        }
+        for note in &d.notes {
+            out.push_str(&format!(
+                "{} {} {}\n",
+                gutter,
+                paint("1;34", "=", color),
+                note
+            ));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// `" (2 warnings)"` to append to a `Finished` line, or nothing without
+/// warnings.
+pub fn warnings_suffix(count: usize) -> String {
+    match count {
+        0 => String::new(),
+        1 => " (1 warning)".to_string(),
+        n => format!(" ({} warnings)", n),
+    }
+}
+
+/// Which source tree is being compiled.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SourceSet {
+    /// `src/` → `target/classes/`, with `resources/` copied alongside.
+    Main,
+    /// `test/` → `target/test-classes/`, with `test-resources/` copied alongside.
+    Test,
+    /// `it/` → `target/it-classes/`, with `it-resources/` copied alongside.
+    IntegrationTest,
+    /// `examples/` → `target/example-classes/`. Examples read `resources/`
+    /// from the main classes.
+    Examples,
+    /// `bench/` → `target/bench-classes/`, including the JMH-generated
+    /// benchmark classes.
+    Bench,
+}
+
+impl SourceSet {
+    pub fn source_dir(self) -> &'static str {
+        match self {
+            SourceSet::Main => "src",
+            SourceSet::Test => "test",
+            SourceSet::IntegrationTest => "it",
+            SourceSet::Examples => "examples",
+            SourceSet::Bench => "bench",
+        }
+    }
+
+    fn staging_dir(self) -> &'static str {
+        match self {
+            SourceSet::Main => "src-root",
+            SourceSet::Test => "test-src-root",
+            SourceSet::IntegrationTest => "it-src-root",
+            SourceSet::Examples => "examples-src-root",
+            SourceSet::Bench => "bench-src-root",
+        }
+    }
+
+    pub fn output_dir(self) -> &'static str {
+        match self {
+            SourceSet::Main => "target/classes",
+            SourceSet::Test => "target/test-classes",
+            SourceSet::IntegrationTest => "target/it-classes",
+            SourceSet::Examples => "target/example-classes",
+            SourceSet::Bench => "target/bench-classes",
+        }
+    }
+
+    fn resources_dir(self) -> Option<&'static str> {
+        match self {
+            SourceSet::Main => Some("resources"),
+            SourceSet::Test => Some("test-resources"),
+            SourceSet::IntegrationTest => Some("it-resources"),
+            SourceSet::Examples | SourceSet::Bench => None,
+        }
+    }
+
+    fn args_file(self) -> &'static str {
+        match self {
+            SourceSet::Main => "target/.jargo/javac-args.txt",
+            SourceSet::Test => "target/.jargo/javac-test-args.txt",
+            SourceSet::IntegrationTest => "target/.jargo/javac-it-args.txt",
+            SourceSet::Examples => "target/.jargo/javac-example-args.txt",
+            SourceSet::Bench => "target/.jargo/javac-bench-args.txt",
+        }
+    }
+
+    fn batch_args_file(self) -> &'static str {
+        match self {
+            SourceSet::Main => "target/.jargo/javac-batch-args.txt",
+            SourceSet::Test => "target/.jargo/javac-test-batch-args.txt",
+            SourceSet::IntegrationTest => "target/.jargo/javac-it-batch-args.txt",
+            SourceSet::Examples => "target/.jargo/javac-example-batch-args.txt",
+            SourceSet::Bench => "target/.jargo/javac-bench-batch-args.txt",
+        }
+    }
 }
 
 /// Compile the project at the given root directory.
 ///
 /// `classpath` is a list of dependency JAR paths placed on `-classpath` for `javac`.
 pub fn compile(
-    _gctx: &GlobalContext,
+    gctx: &GlobalContext,
+    project_root: &Path,
+    manifest: &JargoToml,
+    classpath: &[PathBuf],
+) -> Result<CompileOutput> {
+    compile_source_set(
+        gctx,
+        project_root,
+        manifest,
+        classpath,
+        SourceSet::Main,
+        &[],
+    )
+}
+
+/// Like [`compile`], with extra `javac` flags (e.g. `-Xlint:deprecation`).
+pub fn compile_with_flags(
+    gctx: &GlobalContext,
+    project_root: &Path,
+    manifest: &JargoToml,
+    classpath: &[PathBuf],
+    extra_args: &[&str],
+) -> Result<CompileOutput> {
+    compile_source_set(
+        gctx,
+        project_root,
+        manifest,
+        classpath,
+        SourceSet::Main,
+        extra_args,
+    )
+}
+
+/// Like [`compile`], but when the full compile fails, compile the sources
+/// without errors again so class files are still emitted for them.
+///
+/// The sources javac reported errors in are set aside and the rest compiled
+/// in one batch; a batch that reports errors in further sources sets those
+/// aside too. When a batch fails only through sources already set aside
+/// (pulled in as dependencies), the remaining sources are compiled one at a
+/// time, so a source that depends on a broken source fails with it.
+///
+/// The result keeps the diagnostics of the full compile and reports
+/// `success: false`; `failed_sources` lists the files that could not be
+/// compiled.
+pub fn compile_keep_going(
+    gctx: &GlobalContext,
+    project_root: &Path,
+    manifest: &JargoToml,
+    classpath: &[PathBuf],
+) -> Result<CompileOutput> {
+    let set = SourceSet::Main;
+    let mut output = compile_source_set(gctx, project_root, manifest, classpath, set, &[])?;
+    if output.success {
+        return Ok(output);
+    }
+
+    let base_package = manifest.get_base_package();
+    let toolchain = toolchain::resolve(project_root, manifest)?;
+    // The batches only emit class files; Error Prone has reported already
+    let backend = backend::select(gctx, &toolchain, manifest, None)?;
+    let generated = codegen::generated_roots(project_root, manifest);
+    let source_roots: Vec<PathBuf> =
+        std::iter::once(project_root.join("target").join(set.staging_dir()))
+            .chain(generated.iter().cloned())
+            .collect();
+    let classes_dir = project_root.join(set.output_dir());
+    let args_file = project_root.join(set.batch_args_file());
+
+    let mut source_files = find_source_files(
+        &project_root.join(set.source_dir()),
+        backend::source_extensions(backend.kind()),
+    )?;
+    for root in &generated {
+        source_files.extend(find_java_files(root)?);
+    }
+    let relative = |file: &PathBuf| {
+        file.strip_prefix(project_root)
+            .unwrap_or(file)
+            .to_path_buf()
+    };
+
+    let compile = |files: &[PathBuf]| -> Result<(bool, Vec<String>)> {
+        let (success, stderr) = backend.compile(
+            gctx,
+            &Invocation {
+                project_root,
+                args_file: &args_file,
+                source_roots: &source_roots,
+                classes_dir: &classes_dir,
+                release: &manifest.package.java,
+                classpath,
+                source_files: files,
+                extra_args: backend.batch_args(),
+            },
+        )?;
+        Ok((
+            success,
+            rewrite_error_paths(&stderr, project_root, &base_package, set),
+        ))
+    };
+
+    let mut failed = error_files(&output.errors);
+    loop {
+        let batch: Vec<PathBuf> = source_files
+            .iter()
+            .filter(|file| !failed.contains(&relative(file)))
+            .cloned()
+            .collect();
+        if batch.is_empty() {
+            break;
+        }
+        let (success, lines) = compile(&batch)?;
+        if success {
+            break;
+        }
+        let newly_failed: Vec<PathBuf> = error_files(&lines)
+            .into_iter()
+            .filter(|file| batch.iter().any(|b| &relative(b) == file))
+            .collect();
+        if !newly_failed.is_empty() {
+            failed.extend(newly_failed);
+            continue;
+        }
+        // Every error is in a source already set aside: find the sources
+        // that cannot compile without it
+        for file in batch {
+            let (success, _) = compile(std::slice::from_ref(&file))?;
+            if !success {
+                failed.insert(relative(&file));
+            }
+        }
+        break;
+    }
+
+    // javac still writes a source's classes when its only errors are in a
+    // source pulled in through -sourcepath
+    let source_dir = project_root.join(set.source_dir());
+    let package_dir = PathBuf::from(base_package.replace('.', "/"));
+    for file in source_files
+        .iter()
+        .filter(|f| failed.contains(&relative(f)))
+    {
+        let class = match file.strip_prefix(&source_dir) {
+            Ok(rel) => package_dir.join(rel),
+            Err(_) => match generated
+                .iter()
+                .find_map(|root| file.strip_prefix(root).ok())
+            {
+                Some(rel) => rel.to_path_buf(),
+                None => continue,
+            },
+        };
+        remove_classes(&classes_dir, &class.with_extension(""))?;
+    }
+
+    copy_resources(project_root, set)?;
+    output.failed_sources = failed.into_iter().collect();
+    output.failed_sources.sort();
+    Ok(output)
+}
+
+/// Delete `{class}.class` and its nested `{class}$*.class` files from
+/// `classes_dir`, where `class` is the package path and simple name.
+fn remove_classes(classes_dir: &Path, class: &Path) -> Result<()> {
+    let (Some(parent), Some(name)) = (class.parent(), class.file_name()) else {
+        return Ok(());
+    };
+    let dir = classes_dir.join(parent);
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    let name = name.to_string_lossy();
+    let nested = format!("{}$", name);
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        let matches = path.extension().is_some_and(|ext| ext == "class")
+            && path.file_stem().is_some_and(|stem| {
+                let stem = stem.to_string_lossy();
+                stem == name || stem.starts_with(&nested)
+            });
+        if matches {
+            fs::remove_file(&path)
+                .with_context(|| format!("failed to remove {}", path.display()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Sources (as printed, relative to the project root) with at least one
+/// error in path-rewritten javac output.
+fn error_files(lines: &[String]) -> HashSet<PathBuf> {
+    parse_diagnostics(lines)
+        .into_iter()
+        .filter(|d| d.severity == Severity::Error)
+        .filter_map(|d| d.file.map(PathBuf::from))
+        .collect()
+}
+
+/// Compile `test/` into `target/test-classes/`.
+///
+/// `classpath` is the full test-compile classpath, including `target/classes`.
+pub fn compile_tests(
+    gctx: &GlobalContext,
+    project_root: &Path,
+    manifest: &JargoToml,
+    classpath: &[PathBuf],
+) -> Result<CompileOutput> {
+    compile_source_set(
+        gctx,
+        project_root,
+        manifest,
+        classpath,
+        SourceSet::Test,
+        &[],
+    )
+}
+
+/// Compile `it/` into `target/it-classes/`.
+///
+/// `classpath` is the integration test-compile classpath: main classes,
+/// compile deps, dev- and it-dependencies, and JUnit.
+pub fn compile_integration_tests(
+    gctx: &GlobalContext,
     project_root: &Path,
     manifest: &JargoToml,
     classpath: &[PathBuf],
+) -> Result<CompileOutput> {
+    compile_source_set(
+        gctx,
+        project_root,
+        manifest,
+        classpath,
+        SourceSet::IntegrationTest,
+        &[],
+    )
+}
+
+/// Compile `examples/` into `target/example-classes/`.
+///
+/// `classpath` includes `target/classes`, like the test-compile classpath.
+pub fn compile_examples(
+    gctx: &GlobalContext,
+    project_root: &Path,
+    manifest: &JargoToml,
+    classpath: &[PathBuf],
+) -> Result<CompileOutput> {
+    compile_source_set(
+        gctx,
+        project_root,
+        manifest,
+        classpath,
+        SourceSet::Examples,
+        &[],
+    )
+}
+
+/// Compile `bench/` into `target/bench-classes/` with the JMH annotation
+/// processor, which generates the benchmark harness classes and
+/// `META-INF/BenchmarkList` next to the compiled benchmarks.
+///
+/// `classpath` includes `target/classes` and JMH itself; `processor_path` holds
+/// the JMH annotation processor and its dependencies.
+pub fn compile_benchmarks(
+    gctx: &GlobalContext,
+    project_root: &Path,
+    manifest: &JargoToml,
+    classpath: &[PathBuf],
+    processor_path: &[PathBuf],
+) -> Result<CompileOutput> {
+    let processor_path = std::env::join_paths(processor_path)
+        .context("invalid annotation processor path entry")?
+        .to_string_lossy()
+        .into_owned();
+    compile_source_set(
+        gctx,
+        project_root,
+        manifest,
+        classpath,
+        SourceSet::Bench,
+        &["-processorpath", &processor_path],
+    )
+}
+
+/// Names of the runnable examples: the `.java` files directly under
+/// `examples/`, sorted. Subdirectories hold code shared between examples.
+pub fn example_names(project_root: &Path) -> Result<Vec<String>> {
+    let dir = project_root.join(SourceSet::Examples.source_dir());
+    let mut names = Vec::new();
+    if !dir.is_dir() {
+        return Ok(names);
+    }
+    for entry in
+        fs::read_dir(&dir).with_context(|| format!("failed to read directory {}", dir.display()))?
+    {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|ext| ext == "java") {
+            if let Some(stem) = path.file_stem() {
+                names.push(stem.to_string_lossy().into_owned());
+            }
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// Returns true when the source set's directory contains any sources the
+/// manifest's `[build] compiler` compiles.
+pub fn has_sources(project_root: &Path, manifest: &JargoToml, set: SourceSet) -> Result<bool> {
+    let extensions = backend::source_extensions(manifest.get_compiler());
+    Ok(!find_source_files(&project_root.join(set.source_dir()), extensions)?.is_empty())
+}
+
+#[tracing::instrument(
+    level = "debug",
+    skip(gctx, project_root, manifest, classpath, extra_args)
+)]
+fn compile_source_set(
+    gctx: &GlobalContext,
+    project_root: &Path,
+    manifest: &JargoToml,
+    classpath: &[PathBuf],
+    set: SourceSet,
+    extra_args: &[&str],
 ) -> Result<CompileOutput> {
     let base_package = manifest.get_base_package();
+    let toolchain = toolchain::resolve(project_root, manifest)?;
+    let error_prone = match set {
+        SourceSet::Main => error_prone::flags(gctx, &toolchain, manifest)?,
+        SourceSet::Test | SourceSet::IntegrationTest | SourceSet::Examples | SourceSet::Bench => {
+            None
+        }
+    };
+    let backend = backend::select(gctx, &toolchain, manifest, error_prone.as_ref())?;
+    let warning_args = backend.warning_args(manifest);
+    let mut extra_args: Vec<&str> = extra_args.to_vec();
+    extra_args.extend(warning_args.iter().map(String::as_str));
+    if let Some(flags) = &error_prone {
+        extra_args.extend(flags.javac_args.iter().map(String::as_str));
+    }
 
-    // 1. Create staging symlink
-    let src_root = staging::create_staging(project_root, &base_package)?;
+    // 1. Create staging symlink, and run code generators for main sources
+    let src_root = staging::create_staging_for(
+        project_root,
+        &base_package,
+        set.source_dir(),
+        set.staging_dir(),
+    )?;
+    let generated = match set {
+        SourceSet::Main => codegen::run_codegen(gctx, project_root, manifest)?,
+        SourceSet::Test | SourceSet::IntegrationTest | SourceSet::Examples | SourceSet::Bench => {
+            Vec::new()
+        }
+    };
 
-    // 2. Ensure target/classes exists
-    let classes_dir = project_root.join("target/classes");
+    // 2. Ensure the output directory exists
+    let classes_dir = project_root.join(set.output_dir());
     fs::create_dir_all(&classes_dir)
         .with_context(|| format!("failed to create {}", classes_dir.display()))?;
 
     // 3. Find all source files
-    let src_dir = project_root.join("src");
-    let source_files = find_java_files(&src_dir)?;
+    let extensions = backend::source_extensions(backend.kind());
+    let src_dir = project_root.join(set.source_dir());
+    let mut source_files = find_source_files(&src_dir, extensions)?;
+    for root in &generated {
+        source_files.extend(find_java_files(root)?);
+    }
 
     if source_files.is_empty() {
-        return Err(anyhow::anyhow!("no source files found in src/"));
+        return Err(anyhow::anyhow!(
+            "no source files found in {}/",
+            set.source_dir()
+        ));
     }
 
-    // 4. Write javac arguments to file
-    let args_file = project_root.join("target/javac-args.txt");
-    write_javac_args(
-        &args_file,
-        &src_root,
-        &classes_dir,
-        &manifest.package.java,
-        classpath,
-        &source_files,
+    // 4. Invoke the `[build] compiler`, which writes its arguments to a file
+    let args_file = project_root.join(set.args_file());
+    let source_roots: Vec<PathBuf> = std::iter::once(src_root).chain(generated).collect();
+    let (success, stderr) = backend.compile(
+        gctx,
+        &Invocation {
+            project_root,
+            args_file: &args_file,
+            source_roots: &source_roots,
+            classes_dir: &classes_dir,
+            release: &manifest.package.java,
+            classpath,
+            source_files: &source_files,
+            extra_args: &extra_args,
+        },
     )?;
 
-    // 5. Invoke javac
-    let output = Command::new("javac")
-        .arg(format!("@{}", args_file.display()))
-        .current_dir(project_root)
-        .output()
-        .map_err(|e| {
-            if e.kind() == std::io::ErrorKind::NotFound {
-                JargoError::JavacNotFound
-            } else {
-                e.into()
+    // 5. Process output and rewrite error paths. A javac too old for
+    // `--release` is reported as such rather than as its usage text
+    let lines = rewrite_error_paths(&stderr, project_root, &base_package, set);
+    if !success && backend.kind() == CompilerKind::Javac && release_unsupported(&lines) {
+        if let Some(found) = toolchain
+            .version
+            .or_else(|| toolchain::javac_version(&toolchain.javac()))
+        {
+            return Err(JargoError::JdkTooOld {
+                found,
+                required: manifest.package.java.clone(),
+                origin: toolchain.source.to_string(),
             }
-        })?;
-
-    // 6. Process output and rewrite error paths
-    let success = output.status.success();
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    let errors = if !success {
-        rewrite_error_paths(&stderr, &base_package)
+            .into());
+        }
+    }
+    let (errors, warnings) = if success {
+        (Vec::new(), lines)
     } else {
-        Vec::new()
+        (lines, Vec::new())
     };
 
-    // 7. Copy resources if present
+    // 6. Copy resources if present
     if success {
-        copy_resources(project_root)?;
+        copy_resources(project_root, set)?;
     }
 
-    Ok(CompileOutput { success, errors })
+    Ok(CompileOutput {
+        success,
+        errors,
+        failed_sources: Vec::new(),
+        warnings,
+    })
+}
+
+pub(crate) fn find_java_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    find_source_files(dir, &["java"])
 }
 
-fn find_java_files(dir: &Path) -> Result<Vec<PathBuf>> {
+/// Files under `dir` with one of the `extensions`, such as `["kt", "java"]`.
+fn find_source_files(dir: &Path, extensions: &[&str]) -> Result<Vec<PathBuf>> {
     let mut files = Vec::new();
-    find_java_files_recursive(dir, &mut files)?;
+    find_source_files_recursive(dir, extensions, &mut files)?;
     Ok(files)
 }
 
-fn find_java_files_recursive(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+fn find_source_files_recursive(
+    dir: &Path,
+    extensions: &[&str],
+    files: &mut Vec<PathBuf>,
+) -> Result<()> {
     if !dir.exists() {
         return Ok(());
     }
@@ -100,8 +841,13 @@ fn find_java_files_recursive(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()>
         let file_type = entry.file_type()?;
 
         if file_type.is_dir() {
-            find_java_files_recursive(&path, files)?;
-        } else if file_type.is_file() && path.extension().and_then(|s| s.to_str()) == Some("java") {
+            find_source_files_recursive(&path, extensions, files)?;
+        } else if file_type.is_file()
+            && path
+                .extension()
+                .and_then(|s| s.to_str())
+                .is_some_and(|ext| extensions.contains(&ext))
+        {
             files.push(path);
         }
     }
@@ -109,61 +855,47 @@ fn find_java_files_recursive(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()>
     Ok(())
 }
 
-fn write_javac_args(
-    args_file: &Path,
-    src_root: &Path,
-    classes_dir: &Path,
-    java_version: &str,
-    classpath: &[PathBuf],
-    source_files: &[PathBuf],
-) -> Result<()> {
-    let mut args = format!(
-        "--release\n{}\n-d\n{}\n-sourcepath\n{}\n",
-        java_version,
-        classes_dir.display(),
-        src_root.display()
-    );
-
-    if !classpath.is_empty() {
-        #[cfg(windows)]
-        let sep = ";";
-        #[cfg(not(windows))]
-        let sep = ":";
-
-        let cp = classpath
-            .iter()
-            .map(|p| p.display().to_string())
-            .collect::<Vec<_>>()
-            .join(sep);
-        args.push_str(&format!("-classpath\n{}\n", cp));
-    }
-
-    // Add all source files
-    for file in source_files {
-        args.push_str(&format!("{}\n", file.display()));
-    }
-
-    fs::write(args_file, args)
-        .with_context(|| format!("failed to write javac arguments to {}", args_file.display()))?;
-    Ok(())
+/// Whether javac rejected `--release` itself: `release version 21 not
+/// supported` from JDK 9 and later, `invalid flag: --release` from JDK 8.
+fn release_unsupported(lines: &[String]) -> bool {
+    lines.iter().any(|line| {
+        let line = line.trim();
+        line.contains("invalid flag: --release")
+            || line
+                .strip_prefix("error: release version ")
+                .is_some_and(|rest| rest.ends_with(" not supported"))
+    })
 }
 
-fn rewrite_error_paths(stderr: &str, base_package: &str) -> Vec<String> {
-    // Replace "target/src-root/{base-package-path}/" with "src/"
+fn rewrite_error_paths(
+    stderr: &str,
+    project_root: &Path,
+    base_package: &str,
+    set: SourceSet,
+) -> Vec<String> {
+    // Replace "target/{staging-dir}/{base-package-path}/" with "{source-dir}/",
+    // and make absolute paths under the project root relative to it.
     let package_path = base_package.replace('.', "/");
-    let staged_prefix = format!("target/src-root/{}/", package_path);
+    let staged_prefix = format!("target/{}/{}/", set.staging_dir(), package_path);
+    let source_prefix = format!("{}/", set.source_dir());
+    let root_prefix = format!("{}/", project_root.display());
 
     stderr
         .lines()
-        .map(|line| line.replace(&staged_prefix, "src/"))
+        .map(|line| {
+            line.replace(&root_prefix, "")
+                .replace(&staged_prefix, &source_prefix)
+        })
         .collect()
 }
 
-fn copy_resources(project_root: &Path) -> Result<()> {
-    let resources = project_root.join("resources");
+fn copy_resources(project_root: &Path, set: SourceSet) -> Result<()> {
+    let Some(resources) = set.resources_dir().map(|dir| project_root.join(dir)) else {
+        return Ok(());
+    };
     if resources.exists() && resources.is_dir() {
-        let classes_dir = project_root.join("target/classes");
-        // Recursively copy resources/ contents into target/classes/
+        let classes_dir = project_root.join(set.output_dir());
+        // Recursively copy resources contents into the output directory
         copy_dir_recursive(&resources, &classes_dir)?;
     }
     Ok(())
@@ -204,7 +936,8 @@ mod tests {
         let stderr = "target/src-root/myapp/Main.java:5: error: ';' expected\n\
                       target/src-root/myapp/util/Helper.java:10: warning: unused variable";
 
-        let rewritten = rewrite_error_paths(stderr, "myapp");
+        let rewritten =
+            rewrite_error_paths(stderr, Path::new("/work/myapp"), "myapp", SourceSet::Main);
 
         assert_eq!(rewritten.len(), 2);
         assert_eq!(rewritten[0], "src/Main.java:5: error: ';' expected");
@@ -218,9 +951,252 @@ mod tests {
     fn test_error_path_rewriting_nested_package() {
         let stderr = "target/src-root/com/example/app/Main.java:5: error: ';' expected";
 
-        let rewritten = rewrite_error_paths(stderr, "com.example.app");
+        let rewritten = rewrite_error_paths(
+            stderr,
+            Path::new("/work/app"),
+            "com.example.app",
+            SourceSet::Main,
+        );
 
         assert_eq!(rewritten.len(), 1);
         assert_eq!(rewritten[0], "src/Main.java:5: error: ';' expected");
     }
+
+    #[test]
+    fn test_error_path_rewriting_test_sources() {
+        let stderr = "target/test-src-root/myapp/MainTest.java:7: error: cannot find symbol";
+
+        let rewritten =
+            rewrite_error_paths(stderr, Path::new("/work/myapp"), "myapp", SourceSet::Test);
+
+        assert_eq!(
+            rewritten[0],
+            "test/MainTest.java:7: error: cannot find symbol"
+        );
+    }
+
+    #[test]
+    fn test_warnings_suffix() {
+        assert_eq!(warnings_suffix(0), "");
+        assert_eq!(warnings_suffix(1), " (1 warning)");
+        assert_eq!(warnings_suffix(3), " (3 warnings)");
+    }
+
+    #[test]
+    fn test_release_unsupported() {
+        let lines = |text: &str| text.lines().map(str::to_string).collect::<Vec<_>>();
+        assert!(release_unsupported(&lines(
+            "error: release version 21 not supported\nUsage: javac <options> <source files>"
+        )));
+        assert!(release_unsupported(&lines(
+            "javac: invalid flag: --release\nUsage: javac <options> <source files>"
+        )));
+        assert!(!release_unsupported(&lines(
+            "src/Main.java:5: error: ';' expected\n1 error"
+        )));
+    }
+
+    #[test]
+    fn test_example_names() {
+        let temp = tempfile::TempDir::new().unwrap();
+        assert!(example_names(temp.path()).unwrap().is_empty());
+
+        let examples = temp.path().join("examples");
+        fs::create_dir_all(examples.join("shared")).unwrap();
+        for file in [
+            "Quickstart.java",
+            "Advanced.java",
+            "notes.md",
+            "shared/Util.java",
+        ] {
+            fs::write(examples.join(file), "").unwrap();
+        }
+        assert_eq!(
+            example_names(temp.path()).unwrap(),
+            ["Advanced", "Quickstart"]
+        );
+    }
+
+    #[test]
+    fn test_error_path_rewriting_absolute() {
+        let stderr = "/work/myapp/src/util/Helper.java:3: error: ';' expected";
+
+        let rewritten =
+            rewrite_error_paths(stderr, Path::new("/work/myapp"), "myapp", SourceSet::Main);
+
+        assert_eq!(rewritten[0], "src/util/Helper.java:3: error: ';' expected");
+    }
+
+    fn lines(s: &str) -> Vec<String> {
+        s.lines().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn test_error_files() {
+        let stderr = lines(
+            "src/Main.java:5: error: cannot find symbol\n\
+             \x20       foo();\n\
+             \x20       ^\n\
+             src/util/Helper.java:2: warning: [removal] Integer(int) is deprecated\n\
+             \x20   Integer i = new Integer(1);\n\
+             \x20               ^\n\
+             target/generated-sources/api/Api.java:1: error: ';' expected\n\
+             src/Main.java:9: error: missing return statement\n\
+             3 errors\n\
+             1 warning",
+        );
+        let mut files: Vec<PathBuf> = error_files(&stderr).into_iter().collect();
+        files.sort();
+        assert_eq!(
+            files,
+            [
+                PathBuf::from("src/Main.java"),
+                PathBuf::from("target/generated-sources/api/Api.java")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_remove_classes() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let dir = temp.path().join("myapp");
+        fs::create_dir_all(&dir).unwrap();
+        for file in [
+            "UsesBroken.class",
+            "UsesBroken$Inner.class",
+            "UsesBrokenToo.class",
+            "Main.class",
+        ] {
+            fs::write(dir.join(file), "").unwrap();
+        }
+
+        remove_classes(temp.path(), Path::new("myapp/UsesBroken")).unwrap();
+
+        let mut left: Vec<String> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        left.sort();
+        assert_eq!(left, ["Main.class", "UsesBrokenToo.class"]);
+        remove_classes(temp.path(), Path::new("other/Missing")).unwrap();
+    }
+
+    #[test]
+    fn test_parse_diagnostics() {
+        let stderr = lines(
+            "src/Main.java:5: error: cannot find symbol\n\
+             \x20       foo();\n\
+             \x20       ^\n\
+             \x20 symbol:   method foo()\n\
+             \x20 location: class Main\n\
+             src/util/Helper.java:2: warning: [removal] Integer(int) is deprecated\n\
+             \x20   Integer i = new Integer(1);\n\
+             \x20               ^\n\
+             Note: Some input files use unchecked or unsafe operations.\n\
+             1 error\n\
+             1 warning",
+        );
+
+        let diagnostics = parse_diagnostics(&stderr);
+
+        assert_eq!(diagnostics.len(), 3);
+        assert_eq!(
+            diagnostics[0],
+            Diagnostic {
+                severity: Severity::Error,
+                message: "cannot find symbol".to_string(),
+                file: Some("src/Main.java".to_string()),
+                line: Some(5),
+                column: Some(9),
+                source_line: Some("        foo();".to_string()),
+                notes: vec![
+                    "symbol:   method foo()".to_string(),
+                    "location: class Main".to_string(),
+                ],
+            }
+        );
+        assert_eq!(diagnostics[1].severity, Severity::Warning);
+        assert_eq!(diagnostics[1].column, Some(17));
+        assert!(diagnostics[1].notes.is_empty());
+        assert_eq!(diagnostics[2].severity, Severity::Note);
+        assert_eq!(diagnostics[2].file, None);
+    }
+
+    #[test]
+    fn test_parse_error_prone_diagnostics() {
+        let stderr = lines(
+            "src/Main.java:5: error: [DeadException] Exception created but not thrown\n\
+             \x20       new RuntimeException();\n\
+             \x20       ^\n\
+             \x20   (see https://errorprone.info/bugpattern/DeadException)\n\
+             \x20 Did you mean 'throw new RuntimeException();'?\n\
+             1 error",
+        );
+
+        let diagnostics = parse_diagnostics(&stderr);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "[DeadException] Exception created but not thrown"
+        );
+        assert_eq!(diagnostics[0].column, Some(9));
+        assert_eq!(
+            diagnostics[0].notes,
+            [
+                "(see https://errorprone.info/bugpattern/DeadException)",
+                "Did you mean 'throw new RuntimeException();'?",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_ecj_and_kotlinc_diagnostics() {
+        let ecj = parse_diagnostics(&lines(
+            "src/Main.java:5: error: The method foo() is undefined for the type Main\n\
+             \tfoo();\n\
+             \t^^^^^\n\
+             1 problem (1 error)",
+        ));
+        assert_eq!(ecj.len(), 1);
+        assert_eq!(ecj[0].line, Some(5));
+        assert_eq!(ecj[0].column, Some(2));
+        assert!(ecj[0].notes.is_empty());
+
+        let kotlinc = parse_diagnostics(&lines(
+            "src/Main.kt:3:5: error: unresolved reference: foo\n\
+             \x20   foo()\n\
+             \x20   ^",
+        ));
+        assert_eq!(kotlinc.len(), 1);
+        assert_eq!(kotlinc[0].file.as_deref(), Some("src/Main.kt"));
+        assert_eq!(kotlinc[0].line, Some(3));
+        assert_eq!(kotlinc[0].column, Some(5));
+        assert_eq!(kotlinc[0].message, "unresolved reference: foo");
+    }
+
+    #[test]
+    fn test_render_diagnostics() {
+        let diagnostics = parse_diagnostics(&lines(
+            "src/Main.java:5: error: cannot find symbol\n\
+             \x20       foo();\n\
+             \x20       ^\n\
+             \x20 symbol:   method foo()\n\
+             1 error",
+        ));
+
+        assert_eq!(
+            render_diagnostics(&diagnostics, false),
+            "error: cannot find symbol\n\
+             \x20--> src/Main.java:5:9\n\
+             \x20 |\n\
+             5 |         foo();\n\
+             \x20 |         ^\n\
+             \x20 = symbol:   method foo()\n\
+             \n"
+        );
+
+        let colored = render_diagnostics(&diagnostics, true);
+        assert!(colored.starts_with("\x1b[1;31merror\x1b[0m"));
+    }
 }
@@ -0,0 +1,455 @@
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::errors::JargoError;
+use crate::manifest::JargoToml;
+
+/// The global configuration file, in the Jargo home.
+pub const CONFIG_FILE: &str = "config.toml";
+
+/// Environment variables that select a proxy; any of them set wins over the
+/// `proxy` key.
+const PROXY_VARS: [&str; 6] = [
+    "HTTPS_PROXY",
+    "https_proxy",
+    "HTTP_PROXY",
+    "http_proxy",
+    "ALL_PROXY",
+    "all_proxy",
+];
+
+/// A setting resolved through the layers, highest first: command-line flag,
+/// environment variable, Jargo.toml, `~/.jargo/config.toml`, default. Not
+/// every setting has every layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigKey {
+    /// Directory downloaded artifacts are cached in, instead of
+    /// `~/.jargo/cache`.
+    CacheDir,
+    /// Maven repository (a mirror of Maven Central) artifacts are downloaded
+    /// from.
+    Repository,
+    /// HTTP proxy for downloads and uploads.
+    Proxy,
+    /// Use only cached artifacts; anything else that needs the network
+    /// fails.
+    Offline,
+    /// JDK used when the project does not pin one.
+    JavaHome,
+    /// Where build output goes instead of `target/`. Not in the global
+    /// config, where every project would share it.
+    TargetDir,
+}
+
+impl ConfigKey {
+    pub const ALL: [ConfigKey; 6] = [
+        ConfigKey::CacheDir,
+        ConfigKey::Repository,
+        ConfigKey::Proxy,
+        ConfigKey::Offline,
+        ConfigKey::JavaHome,
+        ConfigKey::TargetDir,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ConfigKey::CacheDir => "cache-dir",
+            ConfigKey::Repository => "repository",
+            ConfigKey::Proxy => "proxy",
+            ConfigKey::Offline => "offline",
+            ConfigKey::JavaHome => "java-home",
+            ConfigKey::TargetDir => "target-dir",
+        }
+    }
+
+    /// A key of `~/.jargo/config.toml` by name.
+    pub fn parse(name: &str) -> Result<Self> {
+        let keys = ConfigKey::ALL.into_iter().filter(|k| k.in_config_file());
+        match keys.clone().find(|key| key.name() == name) {
+            Some(key) => Ok(key),
+            None => {
+                let names: Vec<&str> = keys.map(|k| k.name()).collect();
+                bail!(
+                    "unknown config key `{}`; expected one of: {}",
+                    name,
+                    names.join(", ")
+                )
+            }
+        }
+    }
+
+    fn in_config_file(self) -> bool {
+        self != ConfigKey::TargetDir
+    }
+
+    /// The environment variables that override the key, first set wins.
+    pub fn env_vars(self) -> &'static [&'static str] {
+        match self {
+            ConfigKey::CacheDir => &["JARGO_CACHE_DIR"],
+            ConfigKey::Repository => &["JARGO_MAVEN_REPO"],
+            ConfigKey::Proxy => &PROXY_VARS,
+            ConfigKey::Offline => &["JARGO_OFFLINE"],
+            ConfigKey::JavaHome => &["JARGO_JAVA_HOME"],
+            ConfigKey::TargetDir => &["JARGO_TARGET_DIR"],
+        }
+    }
+
+    /// The variables a config file value is exported as, when they are all
+    /// unset. `java-home` becomes `JAVA_HOME` rather than `JARGO_JAVA_HOME`,
+    /// so a project's `[toolchain]` still wins over it.
+    fn export_vars(self) -> &'static [&'static str] {
+        match self {
+            ConfigKey::CacheDir | ConfigKey::TargetDir => &[],
+            ConfigKey::Repository => &["JARGO_MAVEN_REPO"],
+            ConfigKey::Proxy => &["HTTPS_PROXY", "HTTP_PROXY"],
+            ConfigKey::Offline => &["JARGO_OFFLINE"],
+            ConfigKey::JavaHome => &["JAVA_HOME"],
+        }
+    }
+
+    fn validate(self, value: &str) -> Result<()> {
+        match self {
+            ConfigKey::CacheDir | ConfigKey::JavaHome if !Path::new(value).is_absolute() => {
+                bail!(
+                    "`{}` must be an absolute path, got `{}`",
+                    self.name(),
+                    value
+                )
+            }
+            ConfigKey::Repository | ConfigKey::Proxy if !value.contains("://") => {
+                bail!("`{}` must be a URL, got `{}`", self.name(), value)
+            }
+            ConfigKey::Offline if parse_bool(value).is_none() => {
+                bail!("`offline` must be `true` or `false`, got `{}`", value)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Where an effective setting comes from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Origin {
+    /// A command-line flag, such as `--offline`.
+    Cli(&'static str),
+    /// An environment variable, by name.
+    Env(&'static str),
+    /// Jargo.toml.
+    Manifest,
+    /// `~/.jargo/config.toml`.
+    Config,
+    Default,
+}
+
+impl std::fmt::Display for Origin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Origin::Cli(flag) | Origin::Env(flag) => f.write_str(flag),
+            Origin::Manifest => f.write_str("Jargo.toml"),
+            Origin::Config => f.write_str(CONFIG_FILE),
+            Origin::Default => f.write_str("default"),
+        }
+    }
+}
+
+/// `~/.jargo/config.toml`: settings that apply to every project, below
+/// environment variables and Jargo.toml and above the built-in defaults.
+///
+/// ```toml
+/// cache-dir = "/data/jargo-cache"
+/// repository = "https://nexus.example.com/repository/maven-central"
+/// proxy = "http://proxy.example.com:3128"
+/// offline = false
+/// java-home = "/opt/jdk-21"
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct GlobalConfig {
+    pub path: PathBuf,
+    doc: toml_edit::DocumentMut,
+    /// Keys exported to the environment from this file or a flag, so their
+    /// variables do not count as environment overrides.
+    exported: Vec<(ConfigKey, Origin)>,
+}
+
+impl GlobalConfig {
+    /// Read the config file in `jargo_home`; a missing file is empty.
+    pub fn load(jargo_home: &Path) -> Result<Self> {
+        let path = jargo_home.join(CONFIG_FILE);
+        let doc = match fs::read_to_string(&path) {
+            Ok(content) => content
+                .parse::<toml_edit::DocumentMut>()
+                .with_context(|| format!("failed to parse {}", path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Default::default(),
+            Err(e) => return Err(e).with_context(|| format!("failed to read {}", path.display())),
+        };
+        let config = GlobalConfig {
+            path,
+            doc,
+            exported: Vec::new(),
+        };
+        for (name, _) in config.doc.iter() {
+            let key = ConfigKey::parse(name)
+                .with_context(|| format!("invalid {}", config.path.display()))?;
+            if config.get(key).is_none() {
+                let kind = match key {
+                    ConfigKey::Offline => "boolean",
+                    _ => "string",
+                };
+                bail!(
+                    "invalid {}: `{}` must be a {}",
+                    config.path.display(),
+                    name,
+                    kind
+                );
+            }
+        }
+        Ok(config)
+    }
+
+    /// The value of `key` in the file.
+    pub fn get(&self, key: ConfigKey) -> Option<String> {
+        let value = self.doc.get(key.name())?.as_value()?;
+        match (key, value) {
+            (ConfigKey::Offline, toml_edit::Value::Boolean(b)) => Some(b.value().to_string()),
+            (ConfigKey::Offline, _) => None,
+            _ => value.as_str().map(str::to_string),
+        }
+    }
+
+    /// Set `key` in the file (keeping its comments) and save it.
+    pub fn set(&mut self, key: ConfigKey, value: &str) -> Result<()> {
+        key.validate(value)?;
+        self.doc[key.name()] = match parse_bool(value) {
+            Some(b) if key == ConfigKey::Offline => toml_edit::value(b),
+            _ => toml_edit::value(value),
+        };
+        self.save()
+    }
+
+    /// Remove `key` from the file, returning whether it was set.
+    pub fn unset(&mut self, key: ConfigKey) -> Result<bool> {
+        let removed = self.doc.remove(key.name()).is_some();
+        if removed {
+            self.save()?;
+        }
+        Ok(removed)
+    }
+
+    fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        fs::write(&self.path, self.doc.to_string())
+            .with_context(|| format!("failed to write {}", self.path.display()))
+    }
+
+    /// Apply a command-line flag, which wins over every other layer, by
+    /// setting the key's environment variable for this process and the
+    /// `jargo` and tools it starts. Call before starting threads.
+    pub fn apply_flag(&mut self, key: ConfigKey, flag: &'static str, value: &str) {
+        std::env::set_var(key.env_vars()[0], value);
+        self.exported.retain(|(k, _)| *k != key);
+        self.exported.push((key, Origin::Cli(flag)));
+    }
+
+    /// Export the file's values whose variables are all unset, so every
+    /// download, child `jargo`, and tool sees them. Call before starting
+    /// threads.
+    pub fn apply_env_defaults(&mut self) {
+        for key in ConfigKey::ALL {
+            let vars = key.export_vars();
+            let Some(value) = self.get(key) else {
+                continue;
+            };
+            if vars.is_empty() || env_override(key).is_some() || vars.iter().any(|v| is_set(v)) {
+                continue;
+            }
+            for var in vars {
+                std::env::set_var(var, &value);
+            }
+            self.exported.push((key, Origin::Config));
+        }
+    }
+
+    /// The effective value of `key` from the flag, environment, and file
+    /// layers (Jargo.toml aside), and where it comes from, or `None` when it
+    /// is unset and has no default.
+    pub fn effective(&self, key: ConfigKey, jargo_home: &Path) -> Option<(String, Origin)> {
+        match self.exported.iter().find(|(k, _)| *k == key) {
+            Some((_, Origin::Cli(flag))) => {
+                let value = std::env::var(key.env_vars()[0]).unwrap_or_default();
+                return Some((value, Origin::Cli(flag)));
+            }
+            Some(_) => {}
+            None => {
+                if let Some((var, value)) = env_override(key) {
+                    return Some((value, Origin::Env(var)));
+                }
+            }
+        }
+        if let Some(value) = self.get(key) {
+            return Some((value, Origin::Config));
+        }
+        let default = match key {
+            ConfigKey::CacheDir => jargo_home.join("cache").to_string_lossy().into_owned(),
+            ConfigKey::Repository => crate::cache::MAVEN_CENTRAL.to_string(),
+            ConfigKey::Offline => "false".to_string(),
+            ConfigKey::TargetDir => "target".to_string(),
+            ConfigKey::Proxy | ConfigKey::JavaHome => return None,
+        };
+        Some((default, Origin::Default))
+    }
+
+    /// Where downloaded artifacts are cached: `JARGO_CACHE_DIR`, else
+    /// `cache-dir`, else `~/.jargo/cache`.
+    pub fn cache_dir(&self, jargo_home: &Path) -> PathBuf {
+        if let Some((_, dir)) = env_override(ConfigKey::CacheDir) {
+            return PathBuf::from(dir);
+        }
+        match self.get(ConfigKey::CacheDir) {
+            Some(dir) => PathBuf::from(dir),
+            None => jargo_home.join("cache"),
+        }
+    }
+}
+
+/// Whether jargo runs offline: `--offline`, `JARGO_OFFLINE`, or `offline`
+/// in the global config, all of which end up in `JARGO_OFFLINE`.
+pub fn is_offline() -> bool {
+    env_override(ConfigKey::Offline).is_some_and(|(_, value)| parse_bool(&value) != Some(false))
+}
+
+/// Fail when offline, instead of requesting `url`.
+pub fn ensure_online(url: &str) -> Result<()> {
+    if is_offline() {
+        return Err(JargoError::Offline(url.to_string()).into());
+    }
+    Ok(())
+}
+
+/// Where the build output of the package at `project_root` goes when not
+/// `target/`: `{JARGO_TARGET_DIR}/{package name}` (the variable is set by
+/// `--target-dir`), so projects sharing the directory each get their own,
+/// else `[build] target-dir`. Relative paths are against the project root. A
+/// workspace root, or a Jargo.toml that does not parse, is named by its
+/// directory.
+pub fn target_dir(project_root: &Path, manifest: Option<&JargoToml>) -> Option<PathBuf> {
+    if let Some((_, dir)) = env_override(ConfigKey::TargetDir) {
+        let name = match manifest {
+            Some(manifest) => manifest.package.name.clone(),
+            None => project_root.file_name()?.to_string_lossy().into_owned(),
+        };
+        return Some(project_root.join(dir).join(name));
+    }
+    let dir = manifest?.build.as_ref()?.target_dir.as_ref()?;
+    Some(project_root.join(dir))
+}
+
+/// The first non-empty environment variable overriding `key`.
+fn env_override(key: ConfigKey) -> Option<(&'static str, String)> {
+    key.env_vars().iter().find_map(|var| {
+        let value = std::env::var(var).ok().filter(|v| !v.is_empty())?;
+        Some((*var, value))
+    })
+}
+
+fn is_set(var: &str) -> bool {
+    std::env::var_os(var).is_some_and(|v| !v.is_empty())
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value {
+        "true" | "1" => Some(true),
+        "false" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_get_unset() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let home = tmp.path().join(".jargo");
+        let mut config = GlobalConfig::load(&home).unwrap();
+        assert_eq!(config.get(ConfigKey::Proxy), None);
+        assert_eq!(config.effective(ConfigKey::Proxy, &home), None);
+
+        config
+            .set(ConfigKey::Proxy, "http://proxy.example.com:3128")
+            .unwrap();
+        config.set(ConfigKey::Offline, "true").unwrap();
+        let mut config = GlobalConfig::load(&home).unwrap();
+        assert_eq!(
+            config.get(ConfigKey::Proxy).as_deref(),
+            Some("http://proxy.example.com:3128")
+        );
+        assert_eq!(config.get(ConfigKey::Offline).as_deref(), Some("true"));
+        assert_eq!(
+            fs::read_to_string(home.join(CONFIG_FILE)).unwrap(),
+            "proxy = \"http://proxy.example.com:3128\"\noffline = true\n"
+        );
+
+        assert!(config.unset(ConfigKey::Proxy).unwrap());
+        assert!(!config.unset(ConfigKey::Proxy).unwrap());
+        assert_eq!(
+            GlobalConfig::load(&home).unwrap().get(ConfigKey::Proxy),
+            None
+        );
+    }
+
+    #[test]
+    fn test_set_keeps_comments() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join(CONFIG_FILE),
+            "# Company mirror\nrepository = \"https://old.example.com/maven\"\n",
+        )
+        .unwrap();
+        let mut config = GlobalConfig::load(tmp.path()).unwrap();
+        config
+            .set(ConfigKey::Repository, "https://new.example.com/maven")
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(tmp.path().join(CONFIG_FILE)).unwrap(),
+            "# Company mirror\nrepository = \"https://new.example.com/maven\"\n"
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(ConfigKey::parse("mirror").is_err());
+        assert!(ConfigKey::parse("target-dir").is_err());
+        assert!(ConfigKey::CacheDir.validate("cache").is_err());
+        assert!(ConfigKey::Proxy.validate("proxy:3128").is_err());
+        assert!(ConfigKey::Proxy.validate("http://proxy:3128").is_ok());
+        assert!(ConfigKey::Offline.validate("yes").is_err());
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        fs::write(tmp.path().join(CONFIG_FILE), "mirror = \"x\"\n").unwrap();
+        assert!(GlobalConfig::load(tmp.path()).is_err());
+        fs::write(tmp.path().join(CONFIG_FILE), "proxy = 3128\n").unwrap();
+        assert!(GlobalConfig::load(tmp.path()).is_err());
+        fs::write(tmp.path().join(CONFIG_FILE), "offline = \"true\"\n").unwrap();
+        assert!(GlobalConfig::load(tmp.path()).is_err());
+    }
+
+    #[test]
+    fn test_target_dir() {
+        let manifest: JargoToml = toml::from_str(
+            "[package]\nname = \"app\"\nversion = \"1.0.0\"\njava = \"21\"\n\n\
+             [build]\ntarget-dir = \"../out\"",
+        )
+        .unwrap();
+        let root = Path::new("/work/app");
+        assert_eq!(
+            target_dir(root, Some(&manifest)),
+            Some(PathBuf::from("/work/app/../out"))
+        );
+        assert_eq!(target_dir(root, None), None);
+    }
+}
@@ -0,0 +1,880 @@
+use anyhow::{bail, Context, Result};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::cache;
+use crate::cleanup::InProgress;
+use crate::context::GlobalContext;
+use crate::jar::files_under;
+use crate::manifest::{BinTarget, JargoToml};
+use crate::registry::{
+    sha256_digest, ImageRef, RegistryClient, DOCKER_MANIFEST, DOCKER_MANIFEST_LIST, OCI_INDEX,
+    OCI_MANIFEST,
+};
+
+const OCI_CONFIG: &str = "application/vnd.oci.image.config.v1+json";
+const OCI_LAYER: &str = "application/vnd.oci.image.layer.v1.tar+gzip";
+const DOCKER_CONFIG: &str = "application/vnd.docker.container.image.v1+json";
+const DOCKER_LAYER: &str = "application/vnd.docker.image.rootfs.diff.tar.gzip";
+
+/// Timestamp used for the image and every layer entry, so unchanged inputs
+/// produce the same digests.
+const EPOCH: &str = "1970-01-01T00:00:00Z";
+
+/// A content descriptor in a manifest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Descriptor {
+    #[serde(rename = "mediaType")]
+    pub media_type: String,
+    pub digest: String,
+    pub size: u64,
+}
+
+/// The image the app layers go on top of.
+pub struct BaseImage {
+    /// `None` for `scratch`.
+    pub image: Option<ImageRef>,
+    /// Whether the base uses Docker v2 media types, which the result keeps.
+    pub docker_format: bool,
+    pub config: Value,
+    pub layers: Vec<Descriptor>,
+}
+
+/// A gzipped layer built from the project.
+pub struct AppLayer {
+    pub name: &'static str,
+    pub data: Vec<u8>,
+    /// Digest of `data`.
+    pub digest: String,
+    /// Digest of the uncompressed tar, recorded in the config's `rootfs`.
+    pub diff_id: String,
+}
+
+/// An assembled image: serialized manifest and config plus the app layers.
+pub struct Image {
+    pub manifest: Vec<u8>,
+    pub manifest_media_type: &'static str,
+    pub config: Vec<u8>,
+    pub config_digest: String,
+    pub app_layers: Vec<AppLayer>,
+}
+
+/// `[container] image`, defaulting to `{name}:{version}`.
+pub fn image_name(manifest: &JargoToml) -> Result<ImageRef> {
+    let image = manifest
+        .container
+        .as_ref()
+        .and_then(|c| c.image.clone())
+        .unwrap_or_else(|| format!("{}:{}", manifest.package.name, manifest.package.version));
+    ImageRef::parse(&image)
+}
+
+/// Fetch the manifest and config of `[container] base` for the configured
+/// platform. Layers are only downloaded when they are needed.
+pub fn pull_base(gctx: &GlobalContext, manifest: &JargoToml) -> Result<BaseImage> {
+    let container = manifest.container.as_ref();
+    let base = container
+        .and_then(|c| c.base.clone())
+        .unwrap_or_else(|| format!("eclipse-temurin:{}-jre", manifest.package.java));
+    let (os, arch, variant) = platform(manifest)?;
+
+    if base == "scratch" {
+        return Ok(BaseImage {
+            image: None,
+            docker_format: false,
+            config: json!({
+                "architecture": arch,
+                "os": os,
+                "config": {},
+                "rootfs": { "type": "layers", "diff_ids": [] },
+                "history": [],
+            }),
+            layers: Vec::new(),
+        });
+    }
+
+    let image = ImageRef::parse(&base)?;
+    gctx.shell.status("Pulling", &image.to_string());
+    let mut client = RegistryClient::new(&image)?;
+
+    let (mut media_type, mut body) = client.get_manifest(&image.reference)?;
+    let mut parsed: Value =
+        serde_json::from_slice(&body).with_context(|| format!("invalid manifest for {}", image))?;
+    if media_type.is_empty() {
+        media_type = parsed["mediaType"].as_str().unwrap_or("").to_string();
+    }
+
+    if media_type == OCI_INDEX || media_type == DOCKER_MANIFEST_LIST {
+        let entry = parsed["manifests"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|m| {
+                let p = &m["platform"];
+                p["os"] == os.as_str()
+                    && p["architecture"] == arch.as_str()
+                    && variant
+                        .as_deref()
+                        .is_none_or(|v| p["variant"].as_str() == Some(v))
+            })
+            .with_context(|| format!("{} has no image for platform {}/{}", image, os, arch))?;
+        let digest = entry["digest"]
+            .as_str()
+            .with_context(|| format!("invalid index for {}", image))?
+            .to_string();
+        (media_type, body) = client.get_manifest(&digest)?;
+        parsed = serde_json::from_slice(&body)
+            .with_context(|| format!("invalid manifest for {}", image))?;
+        if media_type.is_empty() {
+            media_type = parsed["mediaType"].as_str().unwrap_or("").to_string();
+        }
+    }
+    if media_type != OCI_MANIFEST && media_type != DOCKER_MANIFEST {
+        bail!("unsupported manifest type `{}` for {}", media_type, image);
+    }
+
+    let config_descriptor: Descriptor = serde_json::from_value(parsed["config"].clone())
+        .with_context(|| format!("invalid manifest for {}", image))?;
+    let layers: Vec<Descriptor> = serde_json::from_value(parsed["layers"].clone())
+        .with_context(|| format!("invalid manifest for {}", image))?;
+    let config = serde_json::from_slice(&client.get_blob(&config_descriptor.digest)?)
+        .with_context(|| format!("invalid config for {}", image))?;
+
+    Ok(BaseImage {
+        image: Some(image),
+        docker_format: media_type == DOCKER_MANIFEST,
+        config,
+        layers,
+    })
+}
+
+/// Build the app layers, least to most frequently changing:
+///
+/// ```text
+/// dependencies   {app-root}/libs/       runtime dependency JARs
+/// resources      {app-root}/resources/  resources/
+/// classes        {app-root}/classes/    target/classes/ without copied resources
+/// ```
+///
+/// Empty layers are left out.
+pub fn app_layers(
+    project_root: &Path,
+    manifest: &JargoToml,
+    runtime_jars: &[PathBuf],
+) -> Result<Vec<AppLayer>> {
+    let root = app_root(manifest)?;
+    let root = root.trim_start_matches('/');
+    let mut layers = Vec::new();
+
+    let mut deps = TarBuilder::new(Vec::new());
+    for jar in runtime_jars {
+        let name = jar
+            .file_name()
+            .with_context(|| format!("invalid JAR path {}", jar.display()))?
+            .to_string_lossy();
+        let data = fs::read(jar).with_context(|| format!("failed to read {}", jar.display()))?;
+        deps.file(&format!("{}/libs/{}", root, name), &data)?;
+    }
+    layers.extend(finish_layer("dependencies", deps)?);
+
+    let resources_dir = project_root.join("resources");
+    let mut resource_names = BTreeSet::new();
+    let mut resources = TarBuilder::new(Vec::new());
+    if resources_dir.is_dir() {
+        for (name, path) in relative_files(&resources_dir)? {
+            let data =
+                fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+            resources.file(&format!("{}/resources/{}", root, name), &data)?;
+            resource_names.insert(name);
+        }
+    }
+    layers.extend(finish_layer("resources", resources)?);
+
+    let classes_dir = project_root.join("target/classes");
+    let mut classes = TarBuilder::new(Vec::new());
+    if classes_dir.is_dir() {
+        for (name, path) in relative_files(&classes_dir)? {
+            if resource_names.contains(&name) {
+                continue;
+            }
+            let data =
+                fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+            classes.file(&format!("{}/classes/{}", root, name), &data)?;
+        }
+    }
+    layers.extend(finish_layer("classes", classes)?);
+
+    Ok(layers)
+}
+
+/// Put the app layers on top of the base and write the config and manifest.
+pub fn assemble(
+    manifest: &JargoToml,
+    bin: &BinTarget,
+    base: &BaseImage,
+    app_layers: Vec<AppLayer>,
+) -> Result<Image> {
+    let config = serde_json::to_vec(&image_config(manifest, bin, base, &app_layers)?)?;
+    let config_digest = sha256_digest(&config);
+
+    let (manifest_media_type, config_media_type, layer_media_type) = if base.docker_format {
+        (DOCKER_MANIFEST, DOCKER_CONFIG, DOCKER_LAYER)
+    } else {
+        (OCI_MANIFEST, OCI_CONFIG, OCI_LAYER)
+    };
+    let mut layers = base.layers.clone();
+    layers.extend(app_layers.iter().map(|layer| Descriptor {
+        media_type: layer_media_type.to_string(),
+        digest: layer.digest.clone(),
+        size: layer.data.len() as u64,
+    }));
+    let image_manifest = json!({
+        "schemaVersion": 2,
+        "mediaType": manifest_media_type,
+        "config": {
+            "mediaType": config_media_type,
+            "digest": config_digest,
+            "size": config.len(),
+        },
+        "layers": layers,
+    });
+
+    Ok(Image {
+        manifest: serde_json::to_vec(&image_manifest)?,
+        manifest_media_type,
+        config,
+        config_digest,
+        app_layers,
+    })
+}
+
+/// Write the image as a tarball that both `docker load` (`manifest.json`) and
+/// OCI tools (`oci-layout`, `index.json`) read. Base layers are downloaded
+/// into `~/.jargo/containers/` once and reused.
+pub fn write_tarball(
+    gctx: &GlobalContext,
+    base: &BaseImage,
+    image: &Image,
+    target: &ImageRef,
+    path: &Path,
+) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let file =
+        File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+    let mut tar = TarBuilder::new(BufWriter::new(file));
+    tar.file("oci-layout", br#"{"imageLayoutVersion":"1.0.0"}"#)?;
+
+    let mut layer_paths = Vec::new();
+    let mut base_client = base.image.as_ref().map(RegistryClient::new).transpose()?;
+    for descriptor in &base.layers {
+        let client = base_client.as_mut().context("base image has no registry")?;
+        let cached = fetch_base_blob(gctx, client, descriptor)?;
+        let data =
+            fs::read(&cached).with_context(|| format!("failed to read {}", cached.display()))?;
+        layer_paths.push(tar_blob(&mut tar, &descriptor.digest, &data)?);
+    }
+    for layer in &image.app_layers {
+        layer_paths.push(tar_blob(&mut tar, &layer.digest, &layer.data)?);
+    }
+    let config_path = tar_blob(&mut tar, &image.config_digest, &image.config)?;
+    let manifest_digest = sha256_digest(&image.manifest);
+    tar_blob(&mut tar, &manifest_digest, &image.manifest)?;
+
+    let repo_tag = target.tag().map(|tag| {
+        let repository = match target.registry.as_str() {
+            "docker.io" => target
+                .repository
+                .strip_prefix("library/")
+                .unwrap_or(&target.repository)
+                .to_string(),
+            registry => format!("{}/{}", registry, target.repository),
+        };
+        format!("{}:{}", repository, tag)
+    });
+
+    let mut annotations = serde_json::Map::new();
+    if let (Some(tag), Some(repo_tag)) = (target.tag(), &repo_tag) {
+        annotations.insert("org.opencontainers.image.ref.name".into(), json!(tag));
+        annotations.insert("io.containerd.image.name".into(), json!(repo_tag));
+    }
+    let index = json!({
+        "schemaVersion": 2,
+        "mediaType": OCI_INDEX,
+        "manifests": [{
+            "mediaType": image.manifest_media_type,
+            "digest": manifest_digest,
+            "size": image.manifest.len(),
+            "annotations": annotations,
+        }],
+    });
+    tar.file("index.json", &serde_json::to_vec(&index)?)?;
+
+    let docker_manifest = json!([{
+        "Config": config_path,
+        "RepoTags": repo_tag.into_iter().collect::<Vec<_>>(),
+        "Layers": layer_paths,
+    }]);
+    tar.file("manifest.json", &serde_json::to_vec(&docker_manifest)?)?;
+
+    tar.finish()?
+        .flush()
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(())
+}
+
+/// Push the image to `target`. Blobs the registry already has are skipped,
+/// and base layers from the same registry are mounted rather than uploaded.
+pub fn push(
+    gctx: &GlobalContext,
+    base: &BaseImage,
+    image: &Image,
+    target: &ImageRef,
+) -> Result<()> {
+    gctx.shell.status("Pushing", &target.to_string());
+    let mut client = RegistryClient::new(target)?;
+    let mut base_client = base.image.as_ref().map(RegistryClient::new).transpose()?;
+
+    for descriptor in &base.layers {
+        if client.has_blob(&descriptor.digest)? {
+            continue;
+        }
+        if let Some(base_image) = &base.image {
+            if base_image.registry == target.registry
+                && client.mount_blob(&descriptor.digest, &base_image.repository)?
+            {
+                continue;
+            }
+        }
+        let source = base_client.as_mut().context("base image has no registry")?;
+        let cached = fetch_base_blob(gctx, source, descriptor)?;
+        let data =
+            fs::read(&cached).with_context(|| format!("failed to read {}", cached.display()))?;
+        client.upload_blob(&descriptor.digest, &data)?;
+    }
+    for layer in &image.app_layers {
+        if !client.has_blob(&layer.digest)? {
+            gctx.shell
+                .verbose(|sh| sh.print(format!("  [verbose] uploading {} layer", layer.name)));
+            client.upload_blob(&layer.digest, &layer.data)?;
+        }
+    }
+    if !client.has_blob(&image.config_digest)? {
+        client.upload_blob(&image.config_digest, &image.config)?;
+    }
+    client.put_manifest(
+        &target.reference,
+        image.manifest_media_type,
+        &image.manifest,
+    )
+}
+
+/// The image config: the base's, with an entrypoint running `bin`, and the
+/// app's environment, ports, labels, and layers added.
+fn image_config(
+    manifest: &JargoToml,
+    bin: &BinTarget,
+    base: &BaseImage,
+    app_layers: &[AppLayer],
+) -> Result<Value> {
+    let container = manifest.container.as_ref();
+    let root = app_root(manifest)?;
+    let mut config = base.config.clone();
+    if !config.is_object() {
+        bail!("base image config is not a JSON object");
+    }
+    config["created"] = json!(EPOCH);
+
+    let runtime = &mut config["config"];
+    let mut entrypoint = vec!["java".to_string()];
+    entrypoint.extend(manifest.get_jvm_args().iter().cloned());
+    entrypoint.push("-cp".to_string());
+    entrypoint.push(format!("{0}/resources:{0}/classes:{0}/libs/*", root));
+    entrypoint.push(manifest.main_class_of(bin));
+    runtime["Entrypoint"] = json!(entrypoint);
+    if let Some(runtime) = runtime.as_object_mut() {
+        runtime.remove("Cmd");
+    }
+    runtime["WorkingDir"] = json!(root);
+
+    if let Some(container) = container {
+        if let Some(user) = &container.user {
+            runtime["User"] = json!(user);
+        }
+        let mut env: Vec<String> = runtime["Env"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|e| e.as_str().map(str::to_string))
+            .collect();
+        for (key, value) in &container.env {
+            if key.is_empty() || key.contains('=') {
+                bail!(
+                    "invalid environment variable name `{}` in [container] env",
+                    key
+                );
+            }
+            env.retain(|e| e.split('=').next() != Some(key.as_str()));
+            env.push(format!("{}={}", key, value));
+        }
+        if !env.is_empty() {
+            runtime["Env"] = json!(env);
+        }
+        for port in &container.ports {
+            runtime["ExposedPorts"][format!("{}/tcp", port)] = json!({});
+        }
+        for (key, value) in &container.labels {
+            runtime["Labels"][key] = json!(value);
+        }
+    }
+
+    config["rootfs"]["type"] = json!("layers");
+    if !config["rootfs"]["diff_ids"].is_array() {
+        config["rootfs"]["diff_ids"] = json!([]);
+    }
+    if !config["history"].is_array() {
+        config["history"] = json!([]);
+    }
+    for layer in app_layers {
+        if let Some(diff_ids) = config["rootfs"]["diff_ids"].as_array_mut() {
+            diff_ids.push(json!(layer.diff_id));
+        }
+        if let Some(history) = config["history"].as_array_mut() {
+            history.push(json!({
+                "created": EPOCH,
+                "created_by": format!("jargo: {}", layer.name),
+            }));
+        }
+    }
+    Ok(config)
+}
+
+/// `[container] app-root`, defaulting to `/app`.
+fn app_root(manifest: &JargoToml) -> Result<String> {
+    let root = manifest
+        .container
+        .as_ref()
+        .and_then(|c| c.app_root.clone())
+        .unwrap_or_else(|| "/app".to_string());
+    let root = root.trim_end_matches('/');
+    if !root.starts_with('/') || root.len() < 2 || root.split('/').any(|p| p == "..") {
+        bail!(
+            "invalid [container] app-root `{}`: expected an absolute directory like `/app`",
+            root
+        );
+    }
+    Ok(root.to_string())
+}
+
+/// `[container] platform` as `(os, architecture, variant)`.
+fn platform(manifest: &JargoToml) -> Result<(String, String, Option<String>)> {
+    let platform = manifest
+        .container
+        .as_ref()
+        .and_then(|c| c.platform.as_deref())
+        .unwrap_or("linux/amd64");
+    match platform.split('/').collect::<Vec<_>>().as_slice() {
+        [os, arch] if !os.is_empty() && !arch.is_empty() => {
+            Ok((os.to_string(), arch.to_string(), None))
+        }
+        [os, arch, variant] if !os.is_empty() && !arch.is_empty() && !variant.is_empty() => {
+            Ok((os.to_string(), arch.to_string(), Some(variant.to_string())))
+        }
+        _ => bail!(
+            "invalid [container] platform `{}`: expected `os/arch` or `os/arch/variant`",
+            platform
+        ),
+    }
+}
+
+/// A base layer blob, from `~/.jargo/containers/blobs/` or downloaded there.
+fn fetch_base_blob(
+    gctx: &GlobalContext,
+    client: &mut RegistryClient,
+    descriptor: &Descriptor,
+) -> Result<PathBuf> {
+    let hex = descriptor
+        .digest
+        .strip_prefix("sha256:")
+        .with_context(|| format!("unsupported digest `{}`", descriptor.digest))?;
+    let dir = gctx.jargo_home.join("containers/blobs/sha256");
+    let path = dir.join(hex);
+    if path.exists() {
+        gctx.shell.verbose(|sh| {
+            sh.print(format!(
+                "  [verbose]   cache hit (layer): {}",
+                path.display()
+            ))
+        });
+        return Ok(path);
+    }
+
+    gctx.shell.status(
+        "Fetching",
+        &format!(
+            "layer {} ({} bytes)",
+            &hex[..12.min(hex.len())],
+            descriptor.size
+        ),
+    );
+    let data = client.get_blob(&descriptor.digest)?;
+    fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    let tmp = cache::tmp_path(&path);
+    let _in_progress = InProgress::new(&tmp);
+    fs::write(&tmp, &data)
+        .with_context(|| format!("failed to write temporary file {}", tmp.display()))?;
+    fs::rename(&tmp, &path)
+        .with_context(|| format!("failed to rename {} to {}", tmp.display(), path.display()))?;
+    Ok(path)
+}
+
+/// Add a blob under `blobs/sha256/` and return its path in the tarball.
+fn tar_blob<W: Write>(tar: &mut TarBuilder<W>, digest: &str, data: &[u8]) -> Result<String> {
+    let path = format!("blobs/{}", digest.replacen(':', "/", 1));
+    tar.file(&path, data)?;
+    Ok(path)
+}
+
+/// Files under `dir` as `(relative name with /, path)`, sorted.
+fn relative_files(dir: &Path) -> Result<Vec<(String, PathBuf)>> {
+    files_under(dir)?
+        .into_iter()
+        .map(|path| {
+            let name = path
+                .strip_prefix(dir)
+                .with_context(|| "failed to compute relative path")?
+                .to_string_lossy()
+                .replace('\\', "/");
+            Ok((name, path))
+        })
+        .collect()
+}
+
+fn finish_layer(name: &'static str, tar: TarBuilder<Vec<u8>>) -> Result<Option<AppLayer>> {
+    if tar.files == 0 {
+        return Ok(None);
+    }
+    let tar = tar.finish()?;
+    let mut gz = GzEncoder::new(Vec::new(), Compression::default());
+    gz.write_all(&tar)?;
+    let data = gz.finish()?;
+    Ok(Some(AppLayer {
+        name,
+        digest: sha256_digest(&data),
+        diff_id: sha256_digest(&tar),
+        data,
+    }))
+}
+
+/// Minimal reproducible tar writer: ustar headers owned by root with a fixed
+/// mtime, parent directories added automatically, and PAX headers for names
+/// longer than 100 bytes.
+struct TarBuilder<W: Write> {
+    out: W,
+    dirs: BTreeSet<String>,
+    files: usize,
+}
+
+impl<W: Write> TarBuilder<W> {
+    fn new(out: W) -> Self {
+        Self {
+            out,
+            dirs: BTreeSet::new(),
+            files: 0,
+        }
+    }
+
+    fn file(&mut self, name: &str, data: &[u8]) -> Result<()> {
+        let mut parent = String::new();
+        for segment in name.split('/').take(name.split('/').count() - 1) {
+            parent.push_str(segment);
+            parent.push('/');
+            if self.dirs.insert(parent.clone()) {
+                self.entry(&parent, b'5', 0o755, &[])?;
+            }
+        }
+        self.files += 1;
+        self.entry(name, b'0', 0o644, data)
+    }
+
+    fn entry(&mut self, name: &str, kind: u8, mode: u64, data: &[u8]) -> Result<()> {
+        if name.len() > 100 {
+            let record = pax_record("path", name);
+            self.write_entry("././@PaxHeader", b'x', 0o644, record.as_bytes())?;
+        }
+        self.write_entry(name, kind, mode, data)
+    }
+
+    fn write_entry(&mut self, name: &str, kind: u8, mode: u64, data: &[u8]) -> Result<()> {
+        let mut header = [0u8; 512];
+        let name = name.as_bytes();
+        header[..name.len().min(100)].copy_from_slice(&name[..name.len().min(100)]);
+        octal(&mut header[100..108], mode);
+        octal(&mut header[108..116], 0);
+        octal(&mut header[116..124], 0);
+        octal(&mut header[124..136], data.len() as u64);
+        octal(&mut header[136..148], 0);
+        header[148..156].copy_from_slice(b"        ");
+        header[156] = kind;
+        header[257..263].copy_from_slice(b"ustar\0");
+        header[263..265].copy_from_slice(b"00");
+        let checksum: u32 = header.iter().map(|&b| u32::from(b)).sum();
+        header[148..156].copy_from_slice(format!("{:06o}\0 ", checksum).as_bytes());
+
+        self.out.write_all(&header)?;
+        self.out.write_all(data)?;
+        let padding = (512 - data.len() % 512) % 512;
+        self.out.write_all(&vec![0; padding])?;
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<W> {
+        self.out.write_all(&[0; 1024])?;
+        Ok(self.out)
+    }
+}
+
+/// Write `value` as a NUL-terminated, zero-padded octal field.
+fn octal(field: &mut [u8], value: u64) {
+    let digits = format!("{:0width$o}\0", value, width = field.len() - 1);
+    field.copy_from_slice(digits.as_bytes());
+}
+
+/// A PAX extended header record: `"{len} {key}={value}\n"`, where `len`
+/// counts the whole record including itself.
+fn pax_record(key: &str, value: &str) -> String {
+    let body = format!(" {}={}\n", key, value);
+    let mut len = body.len() + 1;
+    while len.to_string().len() + body.len() > len {
+        len += 1;
+    }
+    format!("{}{}", len, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(extra: &str) -> JargoToml {
+        toml::from_str(&format!(
+            r#"
+[package]
+name = "demo"
+version = "1.0.0"
+java = "21"
+{}
+"#,
+            extra
+        ))
+        .unwrap()
+    }
+
+    fn scratch() -> BaseImage {
+        BaseImage {
+            image: None,
+            docker_format: false,
+            config: json!({
+                "architecture": "amd64",
+                "os": "linux",
+                "config": { "Env": ["PATH=/usr/bin", "MODE=base"], "Cmd": ["sh"] },
+                "rootfs": { "type": "layers", "diff_ids": ["sha256:base"] },
+                "history": [{ "created_by": "base" }],
+            }),
+            layers: vec![Descriptor {
+                media_type: OCI_LAYER.to_string(),
+                digest: "sha256:base-gz".to_string(),
+                size: 10,
+            }],
+        }
+    }
+
+    /// Entry names of an uncompressed tar.
+    fn tar_names(tar: &[u8]) -> Vec<String> {
+        let mut names = Vec::new();
+        let mut pos = 0;
+        while pos + 512 <= tar.len() && tar[pos] != 0 {
+            let header = &tar[pos..pos + 512];
+            let name_end = header[..100].iter().position(|&b| b == 0).unwrap_or(100);
+            let size_field = std::str::from_utf8(&header[124..135]).unwrap();
+            let size = u64::from_str_radix(size_field, 8).unwrap() as usize;
+            names.push(String::from_utf8_lossy(&header[..name_end]).into_owned());
+            pos += 512 + size.div_ceil(512) * 512;
+        }
+        names
+    }
+
+    #[test]
+    fn test_tar_builder() {
+        let mut tar = TarBuilder::new(Vec::new());
+        tar.file("app/libs/a.jar", b"abc").unwrap();
+        tar.file("app/libs/b.jar", b"").unwrap();
+        let long = format!("app/classes/{}.class", "x".repeat(120));
+        tar.file(&long, b"x").unwrap();
+        let out = tar.finish().unwrap();
+
+        assert_eq!(out.len() % 512, 0);
+        let names = tar_names(&out);
+        assert_eq!(
+            names[..5],
+            [
+                "app/",
+                "app/libs/",
+                "app/libs/a.jar",
+                "app/libs/b.jar",
+                "app/classes/"
+            ]
+        );
+        assert_eq!(names[5], "././@PaxHeader");
+
+        // Checksum of the first header
+        let mut header = out[..512].to_vec();
+        let stored =
+            u32::from_str_radix(std::str::from_utf8(&header[148..154]).unwrap(), 8).unwrap();
+        header[148..156].copy_from_slice(b"        ");
+        assert_eq!(stored, header.iter().map(|&b| u32::from(b)).sum::<u32>());
+    }
+
+    #[test]
+    fn test_pax_record() {
+        let record = pax_record("path", "abc");
+        assert_eq!(record, "12 path=abc\n");
+        assert_eq!(record.len(), 12);
+        let long = pax_record("path", &"x".repeat(95));
+        assert_eq!(long.len().to_string(), long.split(' ').next().unwrap());
+    }
+
+    #[test]
+    fn test_app_layers_split_resources_from_classes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("resources/config")).unwrap();
+        fs::write(root.join("resources/config/app.properties"), "a=1").unwrap();
+        fs::create_dir_all(root.join("target/classes/demo/config")).unwrap();
+        fs::write(root.join("target/classes/demo/Main.class"), "class").unwrap();
+        fs::create_dir_all(root.join("target/classes/config")).unwrap();
+        fs::write(root.join("target/classes/config/app.properties"), "a=1").unwrap();
+        let dep = root.join("dep-1.0.jar");
+        fs::write(&dep, "jar").unwrap();
+
+        let layers = app_layers(root, &manifest(""), &[dep]).unwrap();
+        let names: Vec<&str> = layers.iter().map(|l| l.name).collect();
+        assert_eq!(names, ["dependencies", "resources", "classes"]);
+
+        let mut decoder = flate2::read::GzDecoder::new(&layers[2].data[..]);
+        let mut tar = Vec::new();
+        std::io::Read::read_to_end(&mut decoder, &mut tar).unwrap();
+        assert_eq!(sha256_digest(&tar), layers[2].diff_id);
+        let files: Vec<String> = tar_names(&tar)
+            .into_iter()
+            .filter(|n| !n.ends_with('/'))
+            .collect();
+        assert_eq!(files, ["app/classes/demo/Main.class"]);
+
+        // Same inputs, same digests
+        let again = app_layers(root, &manifest(""), &[root.join("dep-1.0.jar")]).unwrap();
+        assert_eq!(again[0].digest, layers[0].digest);
+        assert!(app_layers(root, &manifest(""), &[])
+            .unwrap()
+            .iter()
+            .all(|l| l.name != "dependencies"));
+    }
+
+    #[test]
+    fn test_image_config() {
+        let m = manifest(
+            r#"
+[run]
+jvm-args = ["-Xmx512m"]
+
+[container]
+ports = [8080]
+user = "1000"
+
+[container.env]
+MODE = "prod"
+
+[container.labels]
+"org.opencontainers.image.source" = "https://example.com/demo"
+"#,
+        );
+        let layer = AppLayer {
+            name: "classes",
+            data: Vec::new(),
+            digest: "sha256:gz".to_string(),
+            diff_id: "sha256:tar".to_string(),
+        };
+        let bin = m.get_bin(None).unwrap();
+        let config = image_config(&m, &bin, &scratch(), &[layer]).unwrap();
+        let runtime = &config["config"];
+        assert_eq!(
+            runtime["Entrypoint"],
+            json!([
+                "java",
+                "-Xmx512m",
+                "-cp",
+                "/app/resources:/app/classes:/app/libs/*",
+                "demo.Main"
+            ])
+        );
+        assert!(runtime.get("Cmd").is_none());
+        assert_eq!(runtime["Env"], json!(["PATH=/usr/bin", "MODE=prod"]));
+        assert_eq!(runtime["ExposedPorts"], json!({ "8080/tcp": {} }));
+        assert_eq!(runtime["User"], "1000");
+        assert_eq!(
+            runtime["Labels"]["org.opencontainers.image.source"],
+            "https://example.com/demo"
+        );
+        assert_eq!(
+            config["rootfs"]["diff_ids"],
+            json!(["sha256:base", "sha256:tar"])
+        );
+        assert_eq!(config["history"][1]["created_by"], "jargo: classes");
+        assert_eq!(config["created"], EPOCH);
+    }
+
+    #[test]
+    fn test_assemble_manifest() {
+        let layer = AppLayer {
+            name: "classes",
+            data: vec![1, 2, 3],
+            digest: "sha256:gz".to_string(),
+            diff_id: "sha256:tar".to_string(),
+        };
+        let m = manifest("");
+        let image = assemble(&m, &m.get_bin(None).unwrap(), &scratch(), vec![layer]).unwrap();
+        let parsed: Value = serde_json::from_slice(&image.manifest).unwrap();
+        assert_eq!(parsed["mediaType"], OCI_MANIFEST);
+        assert_eq!(parsed["config"]["digest"], image.config_digest);
+        assert_eq!(parsed["config"]["size"], image.config.len());
+        assert_eq!(parsed["layers"][0]["digest"], "sha256:base-gz");
+        assert_eq!(parsed["layers"][1]["digest"], "sha256:gz");
+        assert_eq!(parsed["layers"][1]["size"], 3);
+    }
+
+    #[test]
+    fn test_settings() {
+        assert_eq!(
+            image_name(&manifest("")).unwrap().to_string(),
+            "docker.io/library/demo:1.0.0"
+        );
+        assert_eq!(app_root(&manifest("")).unwrap(), "/app");
+        assert!(app_root(&manifest("[container]\napp-root = \"app\"")).is_err());
+        assert_eq!(
+            platform(&manifest("[container]\nplatform = \"linux/arm64/v8\"")).unwrap(),
+            (
+                "linux".to_string(),
+                "arm64".to_string(),
+                Some("v8".to_string())
+            )
+        );
+        assert!(platform(&manifest("[container]\nplatform = \"linux\"")).is_err());
+    }
+}
@@ -1,30 +1,56 @@
 use anyhow::{Context, Result};
 use std::path::PathBuf;
 
+use crate::config::GlobalConfig;
 use crate::shell::{Shell, Verbosity};
 
 pub struct GlobalContext {
     pub jargo_home: PathBuf, // ~/.jargo/
     pub cwd: PathBuf,
     pub shell: Shell,
+    /// `~/.jargo/config.toml`.
+    pub config: GlobalConfig,
+    /// `--locked`: fail instead of changing an out-of-date Jargo.lock.
+    pub locked: bool,
+    /// `--features`: the `[features]` to enable.
+    pub features: Vec<String>,
 }
 
 impl GlobalContext {
-    pub fn new(verbose: bool) -> Result<Self> {
+    pub fn new(verbosity: Verbosity) -> Result<Self> {
         let cwd = std::env::current_dir().context("could not determine current directory")?;
         let home = std::env::var("HOME")
             .or_else(|_| std::env::var("USERPROFILE"))
             .context("could not determine home directory")?;
         let jargo_home = PathBuf::from(home).join(".jargo");
-        let verbosity = if verbose {
-            Verbosity::Verbose
-        } else {
-            Verbosity::Normal
-        };
+        let mut config = GlobalConfig::load(&jargo_home)?;
+        config.apply_env_defaults();
         Ok(Self {
             shell: Shell::new(verbosity),
             jargo_home,
             cwd,
+            config,
+            locked: false,
+            features: Vec::new(),
         })
     }
+
+    /// Where downloaded artifacts are cached: `cache-dir` from the global
+    /// config, else `~/.jargo/cache`.
+    pub fn cache_dir(&self) -> PathBuf {
+        self.config.cache_dir(&self.jargo_home)
+    }
+
+    /// The same context for running a command in `cwd`, such as a workspace
+    /// member.
+    pub fn with_cwd(&self, cwd: PathBuf) -> Self {
+        Self {
+            jargo_home: self.jargo_home.clone(),
+            cwd,
+            shell: self.shell.clone(),
+            config: self.config.clone(),
+            locked: self.locked,
+            features: self.features.clone(),
+        }
+    }
 }
@@ -0,0 +1,427 @@
+use anyhow::{bail, Context, Result};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::cache;
+use crate::compiler::SourceSet;
+use crate::context::GlobalContext;
+use crate::errors::JargoError;
+use crate::toolchain::Toolchain;
+
+/// JaCoCo version of the coverage agent and the report generator.
+pub const JACOCO_VERSION: &str = "0.8.12";
+
+/// Directory coverage data and reports are written to.
+pub const COVERAGE_DIR: &str = "target/coverage";
+
+/// Execution data recorded by the agent, appended to by every test JVM.
+const EXEC_FILE: &str = "jacoco.exec";
+
+/// JaCoCo counters in report order, as `(name, XML counter type, plural)`.
+const COUNTERS: [(&str, &str, &str); 4] = [
+    ("instruction", "INSTRUCTION", "instructions"),
+    ("branch", "BRANCH", "branches"),
+    ("line", "LINE", "lines"),
+    ("method", "METHOD", "methods"),
+];
+
+/// Covered and missed items of one JaCoCo counter.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Counter {
+    pub missed: u64,
+    pub covered: u64,
+}
+
+impl Counter {
+    /// Covered percentage, or 100 when there is nothing to cover.
+    pub fn percent(&self) -> f64 {
+        let total = self.missed + self.covered;
+        if total == 0 {
+            100.0
+        } else {
+            self.covered as f64 * 100.0 / total as f64
+        }
+    }
+}
+
+/// Coverage of the main classes after a `jargo test --coverage` run.
+#[derive(Debug)]
+pub struct CoverageReport {
+    /// Totals per counter, keyed by the names in `[test.coverage]`.
+    pub counters: BTreeMap<&'static str, Counter>,
+    /// Entry page of the HTML report.
+    pub html: PathBuf,
+}
+
+impl CoverageReport {
+    /// `"81.2% instructions, 66.7% branches, 80.0% lines, 90.0% methods"`
+    pub fn summary(&self) -> String {
+        COUNTERS
+            .iter()
+            .filter_map(|(name, _, plural)| {
+                let counter = self.counters.get(name)?;
+                Some(format!("{:.1}% {}", counter.percent(), plural))
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// The `minimums` from `[test.coverage]` that this run falls short of,
+    /// as `"line 72.3% < 80%"`.
+    pub fn unmet(&self, minimums: &[(&str, f64)]) -> Vec<String> {
+        minimums
+            .iter()
+            .filter_map(|&(name, minimum)| {
+                let percent = self
+                    .counters
+                    .get(name)
+                    .copied()
+                    .unwrap_or_default()
+                    .percent();
+                (percent < minimum).then(|| format!("{} {:.1}% < {}%", name, percent, minimum))
+            })
+            .collect()
+    }
+}
+
+/// Fetch the JaCoCo agent and return the JVM argument that records coverage
+/// into `target/coverage/jacoco.exec`.
+pub fn agent_jvm_arg(gctx: &GlobalContext, project_root: &Path) -> Result<String> {
+    let agent = cache::fetch_classified_jar(
+        gctx,
+        "org.jacoco",
+        "org.jacoco.agent",
+        JACOCO_VERSION,
+        "runtime",
+    )
+    .context("failed to fetch the JaCoCo agent")?;
+    let exec = project_root.join(COVERAGE_DIR).join(EXEC_FILE);
+    Ok(format!(
+        "-javaagent:{}=destfile={},append=true",
+        agent.display(),
+        exec.display()
+    ))
+}
+
+/// Remove the coverage data and reports of the previous run.
+pub fn clean(project_root: &Path) -> Result<()> {
+    let dir = project_root.join(COVERAGE_DIR);
+    if dir.exists() {
+        fs::remove_dir_all(&dir).with_context(|| format!("failed to remove {}", dir.display()))?;
+    }
+    Ok(())
+}
+
+/// Generate the HTML (`html/`), XML (`jacoco.xml`), and LCOV (`lcov.info`)
+/// reports in `target/coverage/` for the main classes.
+///
+/// Returns `None` when no test JVM recorded coverage data.
+pub fn report(
+    gctx: &GlobalContext,
+    project_root: &Path,
+    toolchain: &Toolchain,
+    name: &str,
+    base_package: &str,
+) -> Result<Option<CoverageReport>> {
+    let dir = project_root.join(COVERAGE_DIR);
+    let exec = dir.join(EXEC_FILE);
+    if !exec.exists() {
+        return Ok(None);
+    }
+
+    let cli = cache::fetch_classified_jar(
+        gctx,
+        "org.jacoco",
+        "org.jacoco.cli",
+        JACOCO_VERSION,
+        "nodeps",
+    )
+    .context("failed to fetch the JaCoCo command-line interface")?;
+    let html = dir.join("html");
+    let xml = dir.join("jacoco.xml");
+
+    let mut cmd = Command::new(toolchain.java());
+    cmd.arg("-jar")
+        .arg(&cli)
+        .arg("report")
+        .arg(&exec)
+        .arg("--classfiles")
+        .arg(project_root.join(SourceSet::Main.output_dir()))
+        .arg("--sourcefiles")
+        .arg(project_root.join("target/src-root"))
+        .arg("--encoding=UTF-8")
+        .arg("--name")
+        .arg(name)
+        .arg("--html")
+        .arg(&html)
+        .arg("--xml")
+        .arg(&xml)
+        .arg("--quiet")
+        .current_dir(project_root);
+    gctx.shell
+        .verbose(|sh| sh.print(format!("  [verbose] running {:?}", cmd)));
+
+    let output = cmd.output().map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            JargoError::JavaNotFound.into()
+        } else {
+            anyhow::Error::from(e).context("failed to run the JaCoCo report generator")
+        }
+    })?;
+    if !output.status.success() {
+        bail!(
+            "JaCoCo report generation failed:\n{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        );
+    }
+
+    let xml_text =
+        fs::read_to_string(&xml).with_context(|| format!("failed to read {}", xml.display()))?;
+    let parsed = parse_report(&xml_text)?;
+    let lcov = dir.join("lcov.info");
+    fs::write(&lcov, to_lcov(&parsed.sources, base_package))
+        .with_context(|| format!("failed to write {}", lcov.display()))?;
+
+    Ok(Some(CoverageReport {
+        counters: parsed.counters,
+        html: html.join("index.html"),
+    }))
+}
+
+/// Line coverage of one source file, as read from the XML report.
+#[derive(Debug, Default, PartialEq)]
+struct SourceFile {
+    /// Package path, e.g. `myapp/util`.
+    package: String,
+    name: String,
+    lines: Vec<Line>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Line {
+    number: u32,
+    covered_instructions: u64,
+    missed_branches: u64,
+    covered_branches: u64,
+}
+
+#[derive(Debug, Default)]
+struct ParsedReport {
+    counters: BTreeMap<&'static str, Counter>,
+    sources: Vec<SourceFile>,
+}
+
+/// Parse a JaCoCo XML report into its report-level counters and the line
+/// coverage of every source file.
+fn parse_report(xml: &str) -> Result<ParsedReport> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut parsed = ParsedReport::default();
+    // Open elements; report-level counters are direct children of <report>
+    let mut depth = 0;
+    let mut package = String::new();
+    let mut current: Option<SourceFile> = None;
+
+    loop {
+        let (e, empty) = match reader.read_event() {
+            Ok(Event::Start(e)) => (e, false),
+            Ok(Event::Empty(e)) => (e, true),
+            Ok(Event::End(e)) => {
+                depth -= 1;
+                if e.local_name().as_ref() == b"sourcefile" {
+                    parsed.sources.extend(current.take());
+                }
+                continue;
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => bail!("XML parse error: {}", e),
+            _ => continue,
+        };
+        match e.local_name().as_ref() {
+            b"package" => package = attribute(&e, "name")?,
+            b"sourcefile" => {
+                current = Some(SourceFile {
+                    package: package.clone(),
+                    name: attribute(&e, "name")?,
+                    lines: Vec::new(),
+                })
+            }
+            b"line" => {
+                if let Some(source) = current.as_mut() {
+                    source.lines.push(Line {
+                        number: number(&e, "nr")? as u32,
+                        covered_instructions: number(&e, "ci")?,
+                        missed_branches: number(&e, "mb")?,
+                        covered_branches: number(&e, "cb")?,
+                    });
+                }
+            }
+            b"counter" if depth == 1 => {
+                let kind = attribute(&e, "type")?;
+                if let Some((name, _, _)) = COUNTERS.iter().find(|(_, t, _)| *t == kind) {
+                    parsed.counters.insert(
+                        name,
+                        Counter {
+                            missed: number(&e, "missed")?,
+                            covered: number(&e, "covered")?,
+                        },
+                    );
+                }
+            }
+            _ => {}
+        }
+        if !empty {
+            depth += 1;
+        }
+    }
+
+    Ok(parsed)
+}
+
+/// Render `sources` as an LCOV tracefile. JaCoCo records whether a line ran,
+/// not how often, so hit counts are 0 or 1. Paths point at the files under
+/// `src/`, relative to the project root.
+fn to_lcov(sources: &[SourceFile], base_package: &str) -> String {
+    let base_path = base_package.replace('.', "/");
+    let mut lcov = String::new();
+    for source in sources {
+        let path = if source.package == base_path {
+            format!("src/{}", source.name)
+        } else if let Some(sub) = source
+            .package
+            .strip_prefix(&base_path)
+            .and_then(|p| p.strip_prefix('/'))
+        {
+            format!("src/{}/{}", sub, source.name)
+        } else {
+            format!("target/src-root/{}/{}", source.package, source.name)
+        };
+        let _ = writeln!(lcov, "TN:");
+        let _ = writeln!(lcov, "SF:{}", path);
+        let (mut branches, mut branches_hit) = (0, 0);
+        for line in &source.lines {
+            for index in 0..line.covered_branches + line.missed_branches {
+                let taken = if index < line.covered_branches { 1 } else { 0 };
+                let _ = writeln!(lcov, "BRDA:{},0,{},{}", line.number, index, taken);
+            }
+            branches += line.covered_branches + line.missed_branches;
+            branches_hit += line.covered_branches;
+        }
+        for line in &source.lines {
+            let hit = if line.covered_instructions > 0 { 1 } else { 0 };
+            let _ = writeln!(lcov, "DA:{},{}", line.number, hit);
+        }
+        let lines_hit = source
+            .lines
+            .iter()
+            .filter(|l| l.covered_instructions > 0)
+            .count();
+        let _ = writeln!(lcov, "BRF:{}", branches);
+        let _ = writeln!(lcov, "BRH:{}", branches_hit);
+        let _ = writeln!(lcov, "LF:{}", source.lines.len());
+        let _ = writeln!(lcov, "LH:{}", lines_hit);
+        let _ = writeln!(lcov, "end_of_record");
+    }
+    lcov
+}
+
+fn attribute(e: &BytesStart, key: &str) -> Result<String> {
+    for attr in e.attributes() {
+        let attr = attr.context("malformed attribute in coverage report")?;
+        if attr.key.local_name().as_ref() == key.as_bytes() {
+            let value = attr
+                .unescape_value()
+                .context("non-UTF8 attribute in coverage report")?;
+            return Ok(value.into_owned());
+        }
+    }
+    Ok(String::new())
+}
+
+fn number(e: &BytesStart, key: &str) -> Result<u64> {
+    let value = attribute(e, key)?;
+    value
+        .parse()
+        .with_context(|| format!("invalid `{}` value `{}` in coverage report", key, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPORT: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<!DOCTYPE report PUBLIC "-//JACOCO//DTD Report 1.1//EN" "report.dtd">
+<report name="myapp">
+  <sessioninfo id="host-1" start="1" dump="2"/>
+  <package name="myapp/util">
+    <class name="myapp/util/Calc" sourcefilename="Calc.java">
+      <method name="abs" desc="(I)I" line="3">
+        <counter type="LINE" missed="1" covered="1"/>
+      </method>
+      <counter type="LINE" missed="1" covered="1"/>
+    </class>
+    <sourcefile name="Calc.java">
+      <line nr="3" mi="0" ci="4" mb="1" cb="1"/>
+      <line nr="4" mi="2" ci="0" mb="0" cb="0"/>
+      <counter type="LINE" missed="1" covered="1"/>
+    </sourcefile>
+    <counter type="LINE" missed="1" covered="1"/>
+  </package>
+  <counter type="INSTRUCTION" missed="2" covered="4"/>
+  <counter type="BRANCH" missed="1" covered="1"/>
+  <counter type="LINE" missed="1" covered="1"/>
+  <counter type="METHOD" missed="0" covered="1"/>
+  <counter type="CLASS" missed="0" covered="1"/>
+</report>
+"#;
+
+    #[test]
+    fn test_parse_report() {
+        let parsed = parse_report(REPORT).unwrap();
+        assert_eq!(
+            parsed.counters["instruction"],
+            Counter {
+                missed: 2,
+                covered: 4
+            }
+        );
+        assert_eq!(parsed.counters["line"].percent(), 50.0);
+        assert_eq!(parsed.counters.len(), 4);
+        assert_eq!(parsed.sources.len(), 1);
+        assert_eq!(parsed.sources[0].package, "myapp/util");
+        assert_eq!(parsed.sources[0].lines.len(), 2);
+    }
+
+    #[test]
+    fn test_to_lcov() {
+        let parsed = parse_report(REPORT).unwrap();
+        assert_eq!(
+            to_lcov(&parsed.sources, "myapp"),
+            "TN:\nSF:src/util/Calc.java\nBRDA:3,0,0,1\nBRDA:3,0,1,0\nDA:3,1\nDA:4,0\n\
+             BRF:2\nBRH:1\nLF:2\nLH:1\nend_of_record\n"
+        );
+    }
+
+    #[test]
+    fn test_unmet() {
+        let report = CoverageReport {
+            counters: parse_report(REPORT).unwrap().counters,
+            html: PathBuf::new(),
+        };
+        assert_eq!(
+            report.summary(),
+            "66.7% instructions, 50.0% branches, 50.0% lines, 100.0% methods"
+        );
+        assert_eq!(
+            report.unmet(&[("line", 80.0), ("method", 100.0), ("branch", 50.0)]),
+            ["line 50.0% < 80%"]
+        );
+    }
+}
@@ -0,0 +1,293 @@
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::io::{Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use crate::context::GlobalContext;
+use crate::errors::JargoError;
+use crate::toolchain::Toolchain;
+
+/// Source of the Java helper that keeps a warm `javac` in a long-lived JVM.
+const DAEMON_SOURCE: &str = include_str!("../java/CompileDaemon.java");
+
+/// How long `start` waits for a freshly spawned daemon to publish its port.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Connection details published by a running daemon in its state file.
+#[derive(Debug, PartialEq)]
+struct DaemonState {
+    port: u16,
+    token: String,
+    /// `java.home` of the daemon JVM.
+    java_home: Option<PathBuf>,
+}
+
+/// The daemon's working directory: `~/.jargo/daemon/`.
+pub fn daemon_dir(gctx: &GlobalContext) -> PathBuf {
+    gctx.jargo_home.join("daemon")
+}
+
+fn state_file(gctx: &GlobalContext) -> PathBuf {
+    daemon_dir(gctx).join("daemon.state")
+}
+
+/// Returns true when a daemon is running and answering requests.
+pub fn is_running(gctx: &GlobalContext) -> bool {
+    matches!(request(gctx, "PING", None), Ok(Some(reply)) if reply.trim() == "PONG")
+}
+
+/// Compile via the running daemon using a javac argument file.
+///
+/// Returns `None` when no daemon is reachable or it runs on a different JDK
+/// than `toolchain`, so callers can fall back to spawning `javac`. Otherwise
+/// returns javac's exit status and output.
+pub fn compile(
+    gctx: &GlobalContext,
+    toolchain: &Toolchain,
+    args_file: &Path,
+) -> Option<(bool, String)> {
+    if !runs_on(&read_state(gctx)?, toolchain) {
+        return None;
+    }
+    let reply = match request(gctx, "COMPILE", Some(&args_file.display().to_string())) {
+        Ok(Some(reply)) => reply,
+        _ => return None,
+    };
+    let (code, output) = reply.split_once('\n').unwrap_or((reply.as_str(), ""));
+    let code: i32 = code.trim().parse().ok()?;
+    Some((code == 0, output.to_string()))
+}
+
+/// Whether the daemon runs on the JDK at `toolchain.home`. A toolchain with
+/// no known home accepts any daemon.
+fn runs_on(state: &DaemonState, toolchain: &Toolchain) -> bool {
+    let Some(home) = &toolchain.home else {
+        return true;
+    };
+    let canonical = |p: &Path| fs::canonicalize(p).unwrap_or_else(|_| p.to_path_buf());
+    state
+        .java_home
+        .as_deref()
+        .is_some_and(|daemon_home| canonical(daemon_home) == canonical(home))
+}
+
+/// Start a daemon on `toolchain` in the background unless one is already
+/// running there. A daemon on another JDK is stopped first.
+///
+/// `jvm_args` are passed to the daemon JVM (e.g. `-Xmx2g`). Returns the port
+/// the daemon listens on.
+pub fn start(gctx: &GlobalContext, toolchain: &Toolchain, jvm_args: &[String]) -> Result<u16> {
+    if is_running(gctx) {
+        if let Some(state) = read_state(gctx) {
+            if runs_on(&state, toolchain) {
+                return Ok(state.port);
+            }
+        }
+        stop(gctx)?;
+    }
+
+    let classes_dir = ensure_helper_compiled(gctx, toolchain)?;
+    let state_path = state_file(gctx);
+    let _ = fs::remove_file(&state_path);
+
+    Command::new(toolchain.java())
+        .args(jvm_args)
+        .arg("-cp")
+        .arg(&classes_dir)
+        .arg("CompileDaemon")
+        .arg(&state_path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                JargoError::JavaNotFound.into()
+            } else {
+                anyhow::Error::from(e).context("failed to start compile daemon")
+            }
+        })?;
+
+    let deadline = Instant::now() + STARTUP_TIMEOUT;
+    while Instant::now() < deadline {
+        if let Some(state) = read_state(gctx) {
+            if is_running(gctx) {
+                return Ok(state.port);
+            }
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    bail!(
+        "compile daemon did not start within {}s",
+        STARTUP_TIMEOUT.as_secs()
+    )
+}
+
+/// Ask a running daemon to exit. Returns false when none was running.
+pub fn stop(gctx: &GlobalContext) -> Result<bool> {
+    match request(gctx, "STOP", None)? {
+        Some(_) => Ok(true),
+        None => {
+            // Stale state file from a daemon that died without cleaning up.
+            let _ = fs::remove_file(state_file(gctx));
+            Ok(false)
+        }
+    }
+}
+
+/// Compile `CompileDaemon.java` into a directory per Jargo version and JDK
+/// release under the daemon dir, so class files never outrun the JVM.
+fn ensure_helper_compiled(gctx: &GlobalContext, toolchain: &Toolchain) -> Result<PathBuf> {
+    let mut name = format!("helper-{}", env!("CARGO_PKG_VERSION"));
+    if let Some(version) = toolchain.version {
+        name.push_str(&format!("-java{}", version));
+    }
+    let helper_dir = daemon_dir(gctx).join(name);
+    let class_file = helper_dir.join("CompileDaemon.class");
+    let source_file = helper_dir.join("CompileDaemon.java");
+
+    let up_to_date =
+        class_file.exists() && fs::read_to_string(&source_file).is_ok_and(|s| s == DAEMON_SOURCE);
+    if up_to_date {
+        return Ok(helper_dir);
+    }
+
+    fs::create_dir_all(&helper_dir)
+        .with_context(|| format!("failed to create {}", helper_dir.display()))?;
+    fs::write(&source_file, DAEMON_SOURCE)
+        .with_context(|| format!("failed to write {}", source_file.display()))?;
+
+    let output = Command::new(toolchain.javac())
+        .arg("-d")
+        .arg(&helper_dir)
+        .arg(&source_file)
+        .output()
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                JargoError::JavacNotFound.into()
+            } else {
+                anyhow::Error::from(e)
+            }
+        })?;
+    if !output.status.success() {
+        bail!(
+            "failed to compile daemon helper: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(helper_dir)
+}
+
+fn read_state(gctx: &GlobalContext) -> Option<DaemonState> {
+    fs::read_to_string(state_file(gctx))
+        .ok()
+        .and_then(|s| parse_state(&s))
+}
+
+/// Parse the `"{port} {token} {java.home}"` line written by the daemon.
+fn parse_state(content: &str) -> Option<DaemonState> {
+    let mut parts = content.trim().splitn(3, ' ');
+    let port = parts.next()?.parse().ok()?;
+    let token = parts.next()?.to_string();
+    Some(DaemonState {
+        port,
+        token,
+        java_home: parts.next().map(PathBuf::from),
+    })
+}
+
+/// Send one command to the daemon and read the full reply.
+///
+/// Returns `Ok(None)` when no daemon is reachable.
+fn request(gctx: &GlobalContext, command: &str, arg: Option<&str>) -> Result<Option<String>> {
+    let state = match read_state(gctx) {
+        Some(state) => state,
+        None => return Ok(None),
+    };
+
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, state.port));
+    let mut stream = match TcpStream::connect_timeout(&addr, Duration::from_secs(1)) {
+        Ok(stream) => stream,
+        Err(_) => return Ok(None),
+    };
+
+    let mut message = format!("{}\n{}\n", state.token, command);
+    if let Some(arg) = arg {
+        message.push_str(arg);
+        message.push('\n');
+    }
+    stream
+        .write_all(message.as_bytes())
+        .context("failed to send request to compile daemon")?;
+
+    let mut reply = String::new();
+    stream
+        .read_to_string(&mut reply)
+        .context("failed to read reply from compile daemon")?;
+
+    if reply.is_empty() {
+        // The daemon closes the connection without replying on a bad token.
+        return Ok(None);
+    }
+    Ok(Some(reply))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_state() {
+        assert_eq!(
+            parse_state("54321 deadbeef /opt/jdk 21\n"),
+            Some(DaemonState {
+                port: 54321,
+                token: "deadbeef".to_string(),
+                java_home: Some(PathBuf::from("/opt/jdk 21")),
+            })
+        );
+    }
+
+    #[test]
+    fn test_runs_on_matching_jdk_only() {
+        let state = parse_state("54321 deadbeef /opt/jdk-21").unwrap();
+        let toolchain = |home: Option<&str>| Toolchain {
+            home: home.map(PathBuf::from),
+            version: Some(21),
+            source: crate::toolchain::ToolchainSource::JavaHome,
+        };
+        assert!(runs_on(&state, &toolchain(Some("/opt/jdk-21"))));
+        assert!(!runs_on(&state, &toolchain(Some("/opt/jdk-17"))));
+        assert!(runs_on(&state, &toolchain(None)));
+    }
+
+    #[test]
+    fn test_parse_state_invalid() {
+        assert_eq!(parse_state(""), None);
+        assert_eq!(parse_state("notaport token"), None);
+        assert_eq!(parse_state("12345"), None);
+    }
+
+    #[test]
+    fn test_not_running_without_state_file() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let gctx = GlobalContext {
+            cwd: tmp.path().to_path_buf(),
+            jargo_home: tmp.path().join(".jargo"),
+            shell: crate::shell::Shell::new(crate::shell::Verbosity::Normal),
+            config: Default::default(),
+            locked: false,
+            features: Vec::new(),
+        };
+        assert!(!is_running(&gctx));
+        let toolchain = Toolchain {
+            home: None,
+            version: None,
+            source: crate::toolchain::ToolchainSource::Path,
+        };
+        assert!(compile(&gctx, &toolchain, Path::new("javac-args.txt")).is_none());
+    }
+}
@@ -0,0 +1,196 @@
+use anyhow::{bail, Context, Result};
+use md5::Md5;
+use reqwest::blocking::Client;
+use sha1::{Digest, Sha1};
+use std::fs;
+use std::path::Path;
+
+use crate::cache;
+use crate::config;
+use crate::manifest::JargoToml;
+
+/// A `[publish.<name>]` repository with its credentials read from the
+/// environment.
+#[derive(Debug)]
+pub struct DeployTarget {
+    pub name: String,
+    /// Base URL, without a trailing `/`.
+    pub url: String,
+    /// User name and password or token for basic auth; `None` uploads
+    /// anonymously.
+    pub credentials: Option<(String, String)>,
+}
+
+impl DeployTarget {
+    /// Look up `[publish.<name>]` in `manifest` and read its credentials.
+    pub fn from_manifest(manifest: &JargoToml, name: &str) -> Result<Self> {
+        let Some(target) = manifest.publish.get(name) else {
+            let names: Vec<&str> = manifest.publish.keys().map(|k| k.as_str()).collect();
+            if names.is_empty() {
+                bail!(
+                    "no `[publish.{}]` in Jargo.toml; add one with the repository `url`",
+                    name
+                );
+            }
+            bail!(
+                "no `[publish.{}]` in Jargo.toml; expected one of {}",
+                name,
+                names.join(", ")
+            );
+        };
+        let (username_env, password_env) = credential_vars(
+            name,
+            target.username_env.as_deref(),
+            target.password_env.as_deref(),
+        );
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let credentials = match (var(&username_env), var(&password_env)) {
+            (Some(user), Some(password)) => Some((user, password)),
+            (None, None) => None,
+            _ => bail!(
+                "`[publish.{}]` needs both {} and {}, or neither",
+                name,
+                username_env,
+                password_env
+            ),
+        };
+        Ok(Self {
+            name: name.to_string(),
+            url: target.url.trim_end_matches('/').to_string(),
+            credentials,
+        })
+    }
+
+    /// The credentials as borrowed `(user, password)`.
+    pub fn auth(&self) -> Option<(&str, &str)> {
+        self.credentials
+            .as_ref()
+            .map(|(user, password)| (user.as_str(), password.as_str()))
+    }
+}
+
+/// The environment variables holding the credentials of `[publish.<name>]`,
+/// defaulting to `JARGO_PUBLISH_{NAME}_USERNAME` and `_PASSWORD`.
+fn credential_vars(
+    name: &str,
+    username_env: Option<&str>,
+    password_env: Option<&str>,
+) -> (String, String) {
+    let prefix = format!(
+        "JARGO_PUBLISH_{}",
+        name.to_uppercase().replace(['-', '.'], "_")
+    );
+    (
+        username_env
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("{}_USERNAME", prefix)),
+        password_env
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("{}_PASSWORD", prefix)),
+    )
+}
+
+/// Upload the files of a release to `target` with HTTP `PUT`, in the Maven
+/// layout, each followed by its `.md5` and `.sha1` checksums. `files` are
+/// `(suffix, path)` pairs as for [`crate::central::bundle`]. The repository
+/// keeps `maven-metadata.xml` up to date itself.
+pub fn upload(
+    target: &DeployTarget,
+    group: &str,
+    artifact: &str,
+    version: &str,
+    files: &[(&str, &Path)],
+) -> Result<()> {
+    config::ensure_online(&target.url)?;
+    let client = Client::builder()
+        .timeout(std::time::Duration::from_secs(300))
+        .build()
+        .context("failed to create HTTP client")?;
+    let dir = format!(
+        "{}/{}/{}/{}",
+        target.url,
+        cache::group_to_path(group),
+        artifact,
+        version
+    );
+    for (suffix, path) in files {
+        let data = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+        let url = format!("{}/{}-{}{}", dir, artifact, version, suffix);
+        let md5 = format!("{:x}", Md5::digest(&data)).into_bytes();
+        let sha1 = format!("{:x}", Sha1::digest(&data)).into_bytes();
+        put(&client, target, &url, data)?;
+        put(&client, target, &format!("{}.md5", url), md5)?;
+        put(&client, target, &format!("{}.sha1", url), sha1)?;
+    }
+    Ok(())
+}
+
+fn put(client: &Client, target: &DeployTarget, url: &str, body: Vec<u8>) -> Result<()> {
+    let mut request = client.put(url).body(body);
+    if let Some((user, password)) = target.auth() {
+        request = request.basic_auth(user, Some(password));
+    }
+    let response = request
+        .send()
+        .with_context(|| format!("HTTP request failed: {}", url))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().unwrap_or_default();
+        bail!(
+            "{} rejected {} with HTTP {}: {}",
+            target.name,
+            url,
+            status,
+            body.trim()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest_with(extra: &str) -> JargoToml {
+        toml::from_str(&format!(
+            "[package]\nname = \"demo\"\nversion = \"1.0.0\"\njava = \"21\"\n{}",
+            extra
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_credential_vars() {
+        assert_eq!(
+            credential_vars("corp-snapshots", None, None),
+            (
+                "JARGO_PUBLISH_CORP_SNAPSHOTS_USERNAME".to_string(),
+                "JARGO_PUBLISH_CORP_SNAPSHOTS_PASSWORD".to_string()
+            )
+        );
+        assert_eq!(
+            credential_vars("github", Some("GITHUB_ACTOR"), Some("GITHUB_TOKEN")),
+            ("GITHUB_ACTOR".to_string(), "GITHUB_TOKEN".to_string())
+        );
+    }
+
+    #[test]
+    fn test_from_manifest() {
+        let manifest = manifest_with(
+            "\n[publish.corp]\nurl = \"https://repo.example.com/libs-release/\"\n\
+             username-env = \"JARGO_TEST_DEPLOY_UNSET_USER\"\n\
+             password-env = \"JARGO_TEST_DEPLOY_UNSET_PASSWORD\"\n",
+        );
+        let target = DeployTarget::from_manifest(&manifest, "corp").unwrap();
+        assert_eq!(target.url, "https://repo.example.com/libs-release");
+        assert!(target.credentials.is_none());
+
+        let err = DeployTarget::from_manifest(&manifest, "github")
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            err,
+            "no `[publish.github]` in Jargo.toml; expected one of corp"
+        );
+    }
+}
@@ -0,0 +1,259 @@
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::path::Path;
+
+use crate::compiler::{Diagnostic, Severity};
+
+/// Extra `javac` flags for a deprecation scan. `-simpleNames` is turned off so
+/// warnings name the fully-qualified class that declared the deprecated API.
+pub const LINT_ARGS: &[&str] = &[
+    "-Xlint:deprecation",
+    "-Xlint:removal",
+    "-Xmaxwarns",
+    "100000",
+    "-XDdiags.formatterOptions=-simpleNames",
+];
+
+/// Group label for deprecated APIs declared by the JDK (or anything else not
+/// found on the classpath or in the project).
+pub const JDK: &str = "JDK";
+
+/// One use of a deprecated API reported by `javac`.
+#[derive(Debug, PartialEq)]
+pub struct DeprecatedUse {
+    /// `file:line:column` of the use, relative to the project root.
+    pub location: String,
+    /// The deprecated API, e.g. `com.acme.Old.legacy()` or `com.acme.Old`.
+    pub api: String,
+    /// Class that declares the API, used to find the dependency providing it.
+    pub owner_class: String,
+    /// `[removal]` warning: deprecated and marked for removal.
+    pub for_removal: bool,
+}
+
+/// Extract deprecation and removal warnings from parsed `javac` diagnostics.
+///
+/// Expects messages like `[deprecation] legacy() in com.acme.Old has been
+/// deprecated` or `[deprecation] com.acme.Old in com.acme has been deprecated`
+/// (a deprecated type, reported against its package).
+pub fn parse_deprecations(diagnostics: &[Diagnostic]) -> Vec<DeprecatedUse> {
+    diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Warning)
+        .filter_map(|d| {
+            let (for_removal, rest) = if let Some(rest) = d.message.strip_prefix("[removal] ") {
+                (true, rest)
+            } else {
+                (false, d.message.strip_prefix("[deprecation] ")?)
+            };
+            let subject = rest.split(" has been deprecated").next()?;
+            let (member, owner) = subject.rsplit_once(" in ")?;
+            let owner = strip_type_args(owner);
+
+            // A deprecated type is reported "in" its package; the type itself
+            // is then both the API and the owner.
+            let is_type = member.starts_with(owner) && member[owner.len()..].starts_with('.');
+            let (api, owner_class) = if is_type {
+                let class = strip_type_args(member);
+                (class.to_string(), class.to_string())
+            } else if member.starts_with(owner) {
+                // Constructors are already qualified: `java.lang.Integer(int)`
+                (member.to_string(), owner.to_string())
+            } else {
+                (format!("{}.{}", owner, member), owner.to_string())
+            };
+
+            let location = match (&d.file, d.line, d.column) {
+                (Some(file), Some(line), Some(column)) => format!("{}:{}:{}", file, line, column),
+                (Some(file), Some(line), None) => format!("{}:{}", file, line),
+                _ => return None,
+            };
+
+            Some(DeprecatedUse {
+                location,
+                api,
+                owner_class,
+                for_removal,
+            })
+        })
+        .collect()
+}
+
+/// `java.util.List<E>` → `java.util.List`
+fn strip_type_args(name: &str) -> &str {
+    name.split('<').next().unwrap_or(name)
+}
+
+/// Maps class names to the label of the dependency JAR that contains them.
+#[derive(Default)]
+pub struct ClassIndex {
+    classes: HashMap<String, String>,
+}
+
+impl ClassIndex {
+    /// Index every class in `jar` under `label` (e.g. `group:artifact:version`).
+    pub fn add_jar(&mut self, jar: &Path, label: &str) -> Result<()> {
+        let file = File::open(jar).with_context(|| format!("failed to open {}", jar.display()))?;
+        let mut archive = zip::ZipArchive::new(file)
+            .with_context(|| format!("failed to read {}", jar.display()))?;
+        for i in 0..archive.len() {
+            let entry = archive.by_index(i)?;
+            if let Some(class) = entry.name().strip_suffix(".class") {
+                // Nested classes are named with dots in javac diagnostics.
+                let name = class.replace(['/', '$'], ".");
+                self.classes
+                    .entry(name)
+                    .or_insert_with(|| label.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    fn label_of(&self, class: &str) -> Option<&str> {
+        self.classes.get(class).map(String::as_str)
+    }
+}
+
+/// Group deprecated uses by the dependency that declared the API, then by API.
+///
+/// APIs from the project's own classes are grouped under the project name;
+/// anything not found in a dependency JAR is attributed to the [`JDK`].
+pub fn group_by_dependency<'a>(
+    uses: &'a [DeprecatedUse],
+    index: &ClassIndex,
+    base_package: &str,
+    project_name: &str,
+) -> BTreeMap<String, BTreeMap<&'a str, Vec<&'a DeprecatedUse>>> {
+    let mut groups: BTreeMap<String, BTreeMap<&str, Vec<&DeprecatedUse>>> = BTreeMap::new();
+    for u in uses {
+        let label = match index.label_of(&u.owner_class) {
+            Some(label) => label.to_string(),
+            None if u.owner_class.starts_with(&format!("{}.", base_package)) => {
+                project_name.to_string()
+            }
+            None => JDK.to_string(),
+        };
+        groups
+            .entry(label)
+            .or_default()
+            .entry(u.api.as_str())
+            .or_default()
+            .push(u);
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::parse_diagnostics;
+    use std::io::Write;
+
+    fn uses_from(stderr: &str) -> Vec<DeprecatedUse> {
+        let lines: Vec<String> = stderr.lines().map(|l| l.to_string()).collect();
+        parse_deprecations(&parse_diagnostics(&lines))
+    }
+
+    #[test]
+    fn test_parse_deprecations() {
+        let uses = uses_from(
+            "src/Use.java:2: warning: [deprecation] com.acme.Old in com.acme has been deprecated\n\
+             \x20   Old.legacy();\n\
+             \x20   ^\n\
+             src/Use.java:2: warning: [deprecation] legacy() in com.acme.Old has been deprecated\n\
+             \x20   Old.legacy();\n\
+             \x20      ^\n\
+             src/Use.java:3: warning: [removal] java.lang.Integer(int) in java.lang.Integer has been deprecated and marked for removal\n\
+             \x20   new Integer(3);\n\
+             \x20   ^\n\
+             src/Use.java:4: warning: [unchecked] unchecked call\n\
+             \x20   raw.add(1);\n\
+             \x20          ^\n\
+             4 warnings",
+        );
+
+        assert_eq!(
+            uses,
+            vec![
+                DeprecatedUse {
+                    location: "src/Use.java:2:5".to_string(),
+                    api: "com.acme.Old".to_string(),
+                    owner_class: "com.acme.Old".to_string(),
+                    for_removal: false,
+                },
+                DeprecatedUse {
+                    location: "src/Use.java:2:8".to_string(),
+                    api: "com.acme.Old.legacy()".to_string(),
+                    owner_class: "com.acme.Old".to_string(),
+                    for_removal: false,
+                },
+                DeprecatedUse {
+                    location: "src/Use.java:3:5".to_string(),
+                    api: "java.lang.Integer(int)".to_string(),
+                    owner_class: "java.lang.Integer".to_string(),
+                    for_removal: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_group_by_dependency() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let jar = temp.path().join("acme.jar");
+        let mut zip = zip::ZipWriter::new(File::create(&jar).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        zip.start_file("com/acme/Old.class", options).unwrap();
+        zip.write_all(b"\xCA\xFE\xBA\xBE").unwrap();
+        zip.start_file("com/acme/Old$Inner.class", options).unwrap();
+        zip.write_all(b"\xCA\xFE\xBA\xBE").unwrap();
+        zip.finish().unwrap();
+
+        let mut index = ClassIndex::default();
+        index.add_jar(&jar, "com.acme:acme:1.0").unwrap();
+        assert_eq!(
+            index.label_of("com.acme.Old.Inner"),
+            Some("com.acme:acme:1.0")
+        );
+
+        let uses = vec![
+            DeprecatedUse {
+                location: "src/A.java:1:1".to_string(),
+                api: "com.acme.Old.legacy()".to_string(),
+                owner_class: "com.acme.Old".to_string(),
+                for_removal: false,
+            },
+            DeprecatedUse {
+                location: "src/B.java:1:1".to_string(),
+                api: "com.acme.Old.legacy()".to_string(),
+                owner_class: "com.acme.Old".to_string(),
+                for_removal: false,
+            },
+            DeprecatedUse {
+                location: "src/A.java:2:1".to_string(),
+                api: "java.util.Date.getYear()".to_string(),
+                owner_class: "java.util.Date".to_string(),
+                for_removal: false,
+            },
+            DeprecatedUse {
+                location: "src/A.java:3:1".to_string(),
+                api: "myapp.util.Helper.old()".to_string(),
+                owner_class: "myapp.util.Helper".to_string(),
+                for_removal: false,
+            },
+        ];
+
+        let groups = group_by_dependency(&uses, &index, "myapp", "my-app");
+
+        assert_eq!(
+            groups.keys().collect::<Vec<_>>(),
+            vec!["JDK", "com.acme:acme:1.0", "my-app"]
+        );
+        assert_eq!(
+            groups["com.acme:acme:1.0"]["com.acme.Old.legacy()"].len(),
+            2
+        );
+        assert_eq!(groups["JDK"].len(), 1);
+    }
+}
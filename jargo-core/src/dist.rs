@@ -0,0 +1,407 @@
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::manifest::{BinTarget, JargoToml};
+
+const UNIX_TEMPLATE: &str = include_str!("../templates/launcher.sh");
+const WINDOWS_TEMPLATE: &str = include_str!("../templates/launcher.bat");
+
+/// Target platform of a launcher script.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Platform {
+    Unix,
+    Windows,
+}
+
+/// Lay out a runnable distribution in `target/dist/{name}/`:
+///
+/// ```text
+/// bin/{name}        POSIX launcher, one per bin (named after the package
+///                   without `[[bin]]` targets)
+/// bin/{name}.bat    Windows launcher
+/// lib/              project JAR + runtime dependency JARs
+/// agents/           `[run] agents` JARs
+/// ```
+///
+/// Returns the distribution directory. An existing one is replaced.
+pub fn write_dist(
+    project_root: &Path,
+    manifest: &JargoToml,
+    jar: &Path,
+    runtime_jars: &[PathBuf],
+    agents: &[PathBuf],
+) -> Result<PathBuf> {
+    let dist_dir = project_root
+        .join("target/dist")
+        .join(&manifest.package.name);
+    if dist_dir.exists() {
+        fs::remove_dir_all(&dist_dir)
+            .with_context(|| format!("failed to remove {}", dist_dir.display()))?;
+    }
+
+    let lib = copy_into(
+        &dist_dir.join("lib"),
+        std::iter::once(jar).chain(runtime_jars.iter().map(PathBuf::as_path)),
+    )?;
+    let agents = copy_into(
+        &dist_dir.join("agents"),
+        agents.iter().map(PathBuf::as_path),
+    )?;
+
+    let bin_dir = dist_dir.join("bin");
+    fs::create_dir_all(&bin_dir)
+        .with_context(|| format!("failed to create {}", bin_dir.display()))?;
+
+    for bin in manifest.get_bins()? {
+        for platform in [Platform::Unix, Platform::Windows] {
+            let script = render_launcher(project_root, manifest, &bin, platform, &lib, &agents)?;
+            let path = match platform {
+                Platform::Unix => bin_dir.join(&bin.name),
+                Platform::Windows => bin_dir.join(format!("{}.bat", bin.name)),
+            };
+            fs::write(&path, script)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+
+            #[cfg(unix)]
+            if platform == Platform::Unix {
+                use std::os::unix::fs::PermissionsExt;
+                fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+                    .with_context(|| format!("failed to make {} executable", path.display()))?;
+            }
+        }
+    }
+
+    Ok(dist_dir)
+}
+
+/// Copy `files` into `dir` and return their file names, in order.
+fn copy_into<'a>(dir: &Path, files: impl Iterator<Item = &'a Path>) -> Result<Vec<String>> {
+    let mut names = Vec::new();
+    for file in files {
+        if names.is_empty() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create {}", dir.display()))?;
+        }
+        let name = file
+            .file_name()
+            .with_context(|| format!("invalid JAR path {}", file.display()))?
+            .to_string_lossy()
+            .into_owned();
+        fs::copy(file, dir.join(&name))
+            .with_context(|| format!("failed to copy {} into {}", file.display(), dir.display()))?;
+        names.push(name);
+    }
+    Ok(names)
+}
+
+/// Render the launcher for `platform` from the built-in template or the
+/// configured `[dist] unix-template` / `windows-template`.
+///
+/// Placeholders: `{{name}}`, `{{java}}`, `{{main_class}}`, `{{classpath}}`,
+/// `{{jvm_args}}`, `{{env}}`, `{{prelude}}`.
+fn render_launcher(
+    project_root: &Path,
+    manifest: &JargoToml,
+    bin: &BinTarget,
+    platform: Platform,
+    lib: &[String],
+    agents: &[String],
+) -> Result<String> {
+    let dist = manifest.dist.as_ref();
+    let (template_path, prelude_path) = match platform {
+        Platform::Unix => (
+            dist.and_then(|d| d.unix_template.as_deref()),
+            dist.and_then(|d| d.unix_prelude.as_deref()),
+        ),
+        Platform::Windows => (
+            dist.and_then(|d| d.windows_template.as_deref()),
+            dist.and_then(|d| d.windows_prelude.as_deref()),
+        ),
+    };
+
+    let template = match template_path {
+        Some(path) => read_fragment(project_root, path)?,
+        None => match platform {
+            Platform::Unix => UNIX_TEMPLATE.to_string(),
+            Platform::Windows => WINDOWS_TEMPLATE.to_string(),
+        },
+    };
+    let prelude = match prelude_path {
+        Some(path) => read_fragment(project_root, path)?,
+        None => String::new(),
+    };
+
+    let mut env = String::new();
+    if let Some(dist) = dist {
+        for (key, value) in &dist.env {
+            if !is_env_name(key) {
+                bail!("invalid environment variable name `{}` in [dist] env", key);
+            }
+            env.push_str(&match platform {
+                Platform::Unix => format!("export {}={}\n", key, sh_quote(value)),
+                Platform::Windows => format!("set \"{}={}\"\n", key, value.replace('%', "%%")),
+            });
+        }
+    }
+
+    let (home, sep, path_sep) = match platform {
+        Platform::Unix => ("$APP_HOME", "/", ":"),
+        Platform::Windows => ("%APP_HOME%", "\\", ";"),
+    };
+    let classpath = lib
+        .iter()
+        .map(|jar| format!("{}{}lib{}{}", home, sep, sep, jar))
+        .collect::<Vec<_>>()
+        .join(path_sep);
+
+    let mut jvm_args: Vec<String> = agents
+        .iter()
+        .map(|jar| format!("\"-javaagent:{home}{sep}agents{sep}{jar}\""))
+        .collect();
+    jvm_args.extend(manifest.get_jvm_args().iter().map(|arg| match platform {
+        Platform::Unix => sh_quote(arg),
+        Platform::Windows => format!("\"{}\"", arg.replace('%', "%%")),
+    }));
+
+    let main_class = manifest.main_class_of(bin);
+
+    let script = render(
+        &template,
+        &[
+            ("name", &manifest.package.name),
+            ("java", &manifest.package.java),
+            ("main_class", &main_class),
+            ("classpath", &classpath),
+            ("jvm_args", &jvm_args.join(" ")),
+            ("env", env.trim_end()),
+            ("prelude", prelude.trim_end()),
+        ],
+    );
+
+    Ok(match platform {
+        Platform::Unix => script,
+        Platform::Windows => script.replace("\r\n", "\n").replace('\n', "\r\n"),
+    })
+}
+
+fn read_fragment(project_root: &Path, path: &str) -> Result<String> {
+    let full = project_root.join(path);
+    fs::read_to_string(&full).with_context(|| format!("failed to read [dist] template `{}`", path))
+}
+
+/// Replace every `{{key}}` in `template` with its value.
+fn render(template: &str, vars: &[(&str, &str)]) -> String {
+    vars.iter().fold(template.to_string(), |out, (key, value)| {
+        out.replace(&format!("{{{{{}}}}}", key), value)
+    })
+}
+
+/// Single-quote `value` for POSIX sh.
+pub(crate) fn sh_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+pub(crate) fn is_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(extra: &str) -> JargoToml {
+        toml::from_str(&format!(
+            r#"
+[package]
+name = "demo"
+version = "1.0.0"
+java = "17"
+{}
+"#,
+            extra
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            render(
+                "run {{name}} on {{java}} {{unknown}}",
+                &[("name", "demo"), ("java", "17")]
+            ),
+            "run demo on 17 {{unknown}}"
+        );
+    }
+
+    #[test]
+    fn test_sh_quote() {
+        assert_eq!(sh_quote("-Xmx1g"), "'-Xmx1g'");
+        assert_eq!(sh_quote("it's"), "'it'\\''s'");
+    }
+
+    #[test]
+    fn test_is_env_name() {
+        assert!(is_env_name("APP_MODE"));
+        assert!(is_env_name("_x1"));
+        assert!(!is_env_name("1X"));
+        assert!(!is_env_name("A-B"));
+        assert!(!is_env_name(""));
+    }
+
+    #[test]
+    fn test_render_unix_launcher() {
+        let m = manifest(
+            r#"
+[run]
+jvm-args = ["-Xmx1g", "-Dgreeting=it's"]
+
+[dist.env]
+APP_MODE = "prod"
+"#,
+        );
+        let lib = vec!["demo.jar".to_string(), "dep-1.0.jar".to_string()];
+        let agents = vec!["agent-1.0.jar".to_string()];
+        let script = render_launcher(
+            Path::new("."),
+            &m,
+            &m.get_bin(None).unwrap(),
+            Platform::Unix,
+            &lib,
+            &agents,
+        )
+        .unwrap();
+
+        assert!(script.starts_with("#!/bin/sh\n"));
+        assert!(script.contains("export APP_MODE='prod'\n"));
+        assert!(script.contains("[ \"$JAVA_VERSION\" -lt 17 ]"));
+        assert!(script.contains(
+            "exec \"$JAVACMD\" \"-javaagent:$APP_HOME/agents/agent-1.0.jar\" '-Xmx1g' '-Dgreeting=it'\\''s' \
+             -cp \"$APP_HOME/lib/demo.jar:$APP_HOME/lib/dep-1.0.jar\" demo.Main \"$@\""
+        ));
+        assert!(!script.contains("{{"));
+    }
+
+    #[test]
+    fn test_render_windows_launcher() {
+        let m = manifest("");
+        let lib = vec!["demo.jar".to_string()];
+        let script = render_launcher(
+            Path::new("."),
+            &m,
+            &m.get_bin(None).unwrap(),
+            Platform::Windows,
+            &lib,
+            &[],
+        )
+        .unwrap();
+
+        assert!(script.starts_with("@echo off\r\n"));
+        assert!(script.contains("-cp \"%APP_HOME%\\lib\\demo.jar\" demo.Main %*\r\n"));
+        assert!(!script.contains("{{"));
+    }
+
+    #[test]
+    fn test_custom_template_and_prelude() {
+        let temp = tempfile::TempDir::new().unwrap();
+        fs::write(temp.path().join("pre.sh"), "ulimit -n 4096\n").unwrap();
+        fs::write(
+            temp.path().join("launch.sh"),
+            "#!/bin/sh\n{{prelude}}\njava -cp \"{{classpath}}\" {{main_class}}\n",
+        )
+        .unwrap();
+        let m = manifest(
+            r#"
+[dist]
+unix-prelude = "pre.sh"
+unix-template = "launch.sh"
+"#,
+        );
+        let script = render_launcher(
+            temp.path(),
+            &m,
+            &m.get_bin(None).unwrap(),
+            Platform::Unix,
+            &["demo.jar".to_string()],
+            &[],
+        )
+        .unwrap();
+
+        assert_eq!(
+            script,
+            "#!/bin/sh\nulimit -n 4096\njava -cp \"$APP_HOME/lib/demo.jar\" demo.Main\n"
+        );
+
+        let m = manifest("[dist]\nwindows-prelude = \"missing.bat\"\n");
+        let bin = m.get_bin(None).unwrap();
+        assert!(render_launcher(temp.path(), &m, &bin, Platform::Windows, &[], &[]).is_err());
+    }
+
+    #[test]
+    fn test_write_dist_layout() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("target")).unwrap();
+        fs::write(root.join("target/demo.jar"), b"jar").unwrap();
+        fs::write(root.join("dep-1.0.jar"), b"dep").unwrap();
+
+        let m = manifest("");
+        let dist = write_dist(
+            root,
+            &m,
+            &root.join("target/demo.jar"),
+            &[root.join("dep-1.0.jar")],
+            &[],
+        )
+        .unwrap();
+
+        assert_eq!(dist, root.join("target/dist/demo"));
+        assert!(dist.join("lib/demo.jar").exists());
+        assert!(dist.join("lib/dep-1.0.jar").exists());
+        assert!(!dist.join("agents").exists());
+        assert!(dist.join("bin/demo").exists());
+        assert!(dist.join("bin/demo.bat").exists());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(dist.join("bin/demo"))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o755);
+        }
+    }
+
+    #[test]
+    fn test_write_dist_launcher_per_bin() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("target")).unwrap();
+        fs::write(root.join("target/demo.jar"), b"jar").unwrap();
+
+        let m = manifest(
+            r#"
+[[bin]]
+name = "server"
+main-class = "Server"
+
+[[bin]]
+name = "migrate"
+main-class = "tools.Migrate"
+"#,
+        );
+        let dist = write_dist(root, &m, &root.join("target/demo.jar"), &[], &[]).unwrap();
+
+        assert!(!dist.join("bin/demo").exists());
+        let server = fs::read_to_string(dist.join("bin/server")).unwrap();
+        assert!(server.contains(" demo.Server \"$@\""));
+        let migrate = fs::read_to_string(dist.join("bin/migrate.bat")).unwrap();
+        assert!(migrate.contains(" demo.tools.Migrate %*"));
+    }
+}
@@ -0,0 +1,323 @@
+use anyhow::{Context, Result};
+use quick_xml::escape::escape;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::manifest::JargoToml;
+
+/// Where Eclipse and jdtls write classes, apart from jargo's own output.
+const OUTPUT_DIR: &str = "target/eclipse/classes";
+const TEST_OUTPUT_DIR: &str = "target/eclipse/test-classes";
+
+/// A JAR on the Eclipse classpath.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Library {
+    pub jar: PathBuf,
+    /// The `-sources.jar` next to `jar`, when it has been downloaded.
+    pub sources: Option<PathBuf>,
+    /// Only visible to test sources.
+    pub test: bool,
+}
+
+impl Library {
+    /// `jar`, with its sources attached when they sit next to it.
+    pub fn new(jar: PathBuf, test: bool) -> Self {
+        let sources = sources_jar(&jar).filter(|path| path.is_file());
+        Library { jar, sources, test }
+    }
+}
+
+/// A source or resource folder of the project.
+#[derive(Debug, Clone, PartialEq)]
+struct Folder {
+    /// Path in the Eclipse project.
+    path: String,
+    /// For source sets, the project directory linked in at
+    /// `{path}/{base-package-as-path}`, so the flat layout gets the package
+    /// directories Eclipse expects.
+    linked: Option<&'static str>,
+    test: bool,
+}
+
+/// Source sets and resource directories Eclipse sees, as `(directory,
+/// test)`. Benchmarks are left out, since they need JMH.
+const SOURCE_SETS: [(&str, bool); 4] = [
+    ("src", false),
+    ("test", true),
+    ("it", true),
+    ("examples", true),
+];
+const RESOURCE_DIRS: [(&str, bool); 3] = [
+    ("resources", false),
+    ("test-resources", true),
+    ("it-resources", true),
+];
+
+/// Write `.project`, `.classpath`, and `.settings/org.eclipse.jdt.core.prefs`
+/// for the project, returning the paths written.
+pub fn write(
+    project_root: &Path,
+    manifest: &JargoToml,
+    libraries: &[Library],
+) -> Result<Vec<PathBuf>> {
+    let mut folders = Vec::new();
+    for (dir, test) in SOURCE_SETS {
+        if project_root.join(dir).is_dir() {
+            folders.push(Folder {
+                path: format!("jargo-{}", dir),
+                linked: Some(dir),
+                test,
+            });
+        }
+    }
+    for (dir, test) in RESOURCE_DIRS {
+        if project_root.join(dir).is_dir() {
+            folders.push(Folder {
+                path: dir.to_string(),
+                linked: None,
+                test,
+            });
+        }
+    }
+
+    let base_package = manifest.get_base_package();
+    let files = [
+        (
+            project_root.join(".project"),
+            render_project(&manifest.package.name, &base_package, &folders),
+        ),
+        (
+            project_root.join(".classpath"),
+            render_classpath(&manifest.package.java, &folders, libraries),
+        ),
+        (
+            project_root.join(".settings/org.eclipse.jdt.core.prefs"),
+            render_prefs(&manifest.package.java),
+        ),
+    ];
+    let mut written = Vec::new();
+    for (path, contents) in files {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        fs::write(&path, contents)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// `foo-1.0.jar` → `foo-1.0-sources.jar`, in the same directory.
+fn sources_jar(jar: &Path) -> Option<PathBuf> {
+    let stem = jar.file_stem()?.to_str()?;
+    Some(jar.with_file_name(format!("{}-sources.jar", stem)))
+}
+
+/// The Eclipse execution environment for a `java` release.
+fn execution_environment(java: &str) -> String {
+    match java {
+        "8" | "1.8" => "JavaSE-1.8".to_string(),
+        _ => format!("JavaSE-{}", java),
+    }
+}
+
+fn render_project(name: &str, base_package: &str, folders: &[Folder]) -> String {
+    let mut links = String::new();
+    let mut link = |name: &str, location: &str| {
+        links.push_str(&format!(
+            "\t\t<link>\n\t\t\t<name>{}</name>\n\t\t\t<type>2</type>\n\t\t\t<locationURI>{}</locationURI>\n\t\t</link>\n",
+            escape(name),
+            escape(location)
+        ));
+    };
+    for folder in folders {
+        let Some(dir) = folder.linked else {
+            continue;
+        };
+        // Virtual folders for the package path, then the source directory
+        // linked in as the innermost package.
+        let mut path = folder.path.clone();
+        link(&path, "virtual:/virtual");
+        let segments: Vec<&str> = base_package.split('.').collect();
+        for (i, segment) in segments.iter().enumerate() {
+            path = format!("{}/{}", path, segment);
+            if i + 1 < segments.len() {
+                link(&path, "virtual:/virtual");
+            } else {
+                link(&path, &format!("PROJECT_LOC/{}", dir));
+            }
+        }
+    }
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <projectDescription>\n\
+         \t<name>{}</name>\n\
+         \t<comment>Generated by jargo ide eclipse</comment>\n\
+         \t<projects>\n\
+         \t</projects>\n\
+         \t<buildSpec>\n\
+         \t\t<buildCommand>\n\
+         \t\t\t<name>org.eclipse.jdt.core.javabuilder</name>\n\
+         \t\t\t<arguments>\n\
+         \t\t\t</arguments>\n\
+         \t\t</buildCommand>\n\
+         \t</buildSpec>\n\
+         \t<natures>\n\
+         \t\t<nature>org.eclipse.jdt.core.javanature</nature>\n\
+         \t</natures>\n\
+         \t<linkedResources>\n\
+         {}\
+         \t</linkedResources>\n\
+         </projectDescription>\n",
+        escape(name),
+        links
+    )
+}
+
+const TEST_ATTRIBUTE: &str =
+    "\t\t<attributes>\n\t\t\t<attribute name=\"test\" value=\"true\"/>\n\t\t</attributes>\n";
+
+fn render_classpath(java: &str, folders: &[Folder], libraries: &[Library]) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<classpath>\n");
+    for folder in folders {
+        if folder.test {
+            out.push_str(&format!(
+                "\t<classpathentry kind=\"src\" output=\"{}\" path=\"{}\">\n{}\t</classpathentry>\n",
+                TEST_OUTPUT_DIR,
+                escape(&folder.path),
+                TEST_ATTRIBUTE
+            ));
+        } else {
+            out.push_str(&format!(
+                "\t<classpathentry kind=\"src\" path=\"{}\"/>\n",
+                escape(&folder.path)
+            ));
+        }
+    }
+    out.push_str(&format!(
+        "\t<classpathentry kind=\"con\" path=\"org.eclipse.jdt.launching.JRE_CONTAINER/org.eclipse.jdt.internal.debug.ui.launcher.StandardVMType/{}\"/>\n",
+        execution_environment(java)
+    ));
+    for library in libraries {
+        let mut entry = format!(
+            "\t<classpathentry kind=\"lib\" path=\"{}\"",
+            escape(library.jar.to_string_lossy())
+        );
+        if let Some(sources) = &library.sources {
+            entry.push_str(&format!(
+                " sourcepath=\"{}\"",
+                escape(sources.to_string_lossy())
+            ));
+        }
+        if library.test {
+            entry.push_str(&format!(">\n{}\t</classpathentry>\n", TEST_ATTRIBUTE));
+        } else {
+            entry.push_str("/>\n");
+        }
+        out.push_str(&entry);
+    }
+    out.push_str(&format!(
+        "\t<classpathentry kind=\"output\" path=\"{}\"/>\n</classpath>\n",
+        OUTPUT_DIR
+    ));
+    out
+}
+
+/// Compiler settings, so a newer JDK in the IDE still compiles at `java`.
+fn render_prefs(java: &str) -> String {
+    let level = if java == "8" { "1.8" } else { java };
+    format!(
+        "eclipse.preferences.version=1\n\
+         org.eclipse.jdt.core.compiler.codegen.targetPlatform={0}\n\
+         org.eclipse.jdt.core.compiler.compliance={0}\n\
+         org.eclipse.jdt.core.compiler.release=enabled\n\
+         org.eclipse.jdt.core.compiler.source={0}\n",
+        level
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn folders() -> Vec<Folder> {
+        vec![
+            Folder {
+                path: "jargo-src".to_string(),
+                linked: Some("src"),
+                test: false,
+            },
+            Folder {
+                path: "jargo-test".to_string(),
+                linked: Some("test"),
+                test: true,
+            },
+            Folder {
+                path: "resources".to_string(),
+                linked: None,
+                test: false,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_render_project() {
+        let project = render_project("demo", "com.example", &folders());
+        assert!(project.contains("\t<name>demo</name>\n"));
+        assert!(project.contains(
+            "\t\t\t<name>jargo-src/com</name>\n\t\t\t<type>2</type>\n\t\t\t<locationURI>virtual:/virtual</locationURI>\n"
+        ));
+        assert!(project.contains(
+            "\t\t\t<name>jargo-test/com/example</name>\n\t\t\t<type>2</type>\n\t\t\t<locationURI>PROJECT_LOC/test</locationURI>\n"
+        ));
+        assert_eq!(project.matches("<link>").count(), 6);
+    }
+
+    #[test]
+    fn test_render_classpath() {
+        let libraries = [
+            Library {
+                jar: PathBuf::from("/cache/guava-33.0.0-jre.jar"),
+                sources: Some(PathBuf::from("/cache/guava-33.0.0-jre-sources.jar")),
+                test: false,
+            },
+            Library {
+                jar: PathBuf::from("/cache/assertj-core-3.25.3.jar"),
+                sources: None,
+                test: true,
+            },
+        ];
+        assert_eq!(
+            render_classpath("17", &folders(), &libraries),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <classpath>\n\
+             \t<classpathentry kind=\"src\" path=\"jargo-src\"/>\n\
+             \t<classpathentry kind=\"src\" output=\"target/eclipse/test-classes\" path=\"jargo-test\">\n\
+             \t\t<attributes>\n\
+             \t\t\t<attribute name=\"test\" value=\"true\"/>\n\
+             \t\t</attributes>\n\
+             \t</classpathentry>\n\
+             \t<classpathentry kind=\"src\" path=\"resources\"/>\n\
+             \t<classpathentry kind=\"con\" path=\"org.eclipse.jdt.launching.JRE_CONTAINER/org.eclipse.jdt.internal.debug.ui.launcher.StandardVMType/JavaSE-17\"/>\n\
+             \t<classpathentry kind=\"lib\" path=\"/cache/guava-33.0.0-jre.jar\" sourcepath=\"/cache/guava-33.0.0-jre-sources.jar\"/>\n\
+             \t<classpathentry kind=\"lib\" path=\"/cache/assertj-core-3.25.3.jar\">\n\
+             \t\t<attributes>\n\
+             \t\t\t<attribute name=\"test\" value=\"true\"/>\n\
+             \t\t</attributes>\n\
+             \t</classpathentry>\n\
+             \t<classpathentry kind=\"output\" path=\"target/eclipse/classes\"/>\n\
+             </classpath>\n"
+        );
+        assert!(render_classpath("8", &[], &[]).contains("/JavaSE-1.8\"/>"));
+    }
+
+    #[test]
+    fn test_sources_jar() {
+        assert_eq!(
+            sources_jar(Path::new("/cache/guava-33.0.0-jre.jar")),
+            Some(PathBuf::from("/cache/guava-33.0.0-jre-sources.jar"))
+        );
+    }
+}
@@ -0,0 +1,216 @@
+use anyhow::{bail, Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::dist::is_env_name;
+use crate::manifest::{JargoToml, RunProfile};
+
+/// File read when `[run] dotenv = true`.
+pub const DOTENV_FILE: &str = ".env";
+
+/// Environment variables to set for the JVM of `jargo run` and `jargo repl`:
+/// `.env` (with `[run] dotenv`) overlaid by `[env]`, then by the `env` of the
+/// selected run profile. Variables already set in Jargo's own environment win
+/// over all of them, so a shell or CI can override them.
+pub fn program_env(
+    project_root: &Path,
+    manifest: &JargoToml,
+    profile: Option<&RunProfile>,
+) -> Result<Vec<(String, String)>> {
+    layered_env(
+        project_root,
+        manifest,
+        profile.map(|p| &p.env),
+        "a run profile",
+    )
+}
+
+/// Environment variables for the test JVM: like [`program_env`], with
+/// `[test] env` in place of a run profile.
+pub fn test_env(project_root: &Path, manifest: &JargoToml) -> Result<Vec<(String, String)>> {
+    layered_env(
+        project_root,
+        manifest,
+        manifest.test.as_ref().map(|t| &t.env),
+        "[test]",
+    )
+}
+
+fn layered_env(
+    project_root: &Path,
+    manifest: &JargoToml,
+    overlay: Option<&BTreeMap<String, String>>,
+    overlay_section: &str,
+) -> Result<Vec<(String, String)>> {
+    let mut vars = BTreeMap::new();
+    let dotenv_path = project_root.join(DOTENV_FILE);
+    let dotenv = manifest.run.as_ref().is_some_and(|run| run.dotenv);
+    if dotenv && dotenv_path.is_file() {
+        let content = fs::read_to_string(&dotenv_path)
+            .with_context(|| format!("failed to read {}", dotenv_path.display()))?;
+        vars.extend(parse_dotenv(&content).with_context(|| format!("invalid {}", DOTENV_FILE))?);
+    }
+    for (key, value) in &manifest.env {
+        if !is_env_name(key) {
+            bail!("invalid environment variable name `{}` in [env]", key);
+        }
+        vars.insert(key.clone(), value.clone());
+    }
+    for (key, value) in overlay.into_iter().flatten() {
+        if !is_env_name(key) {
+            bail!(
+                "invalid environment variable name `{}` in {}",
+                key,
+                overlay_section
+            );
+        }
+        vars.insert(key.clone(), value.clone());
+    }
+    Ok(vars
+        .into_iter()
+        .filter(|(key, _)| std::env::var_os(key).is_none())
+        .collect())
+}
+
+/// Parse a `.env` file: `KEY=value` lines with an optional `export ` prefix,
+/// `#` comments, and values that are unquoted (trimmed, ` #` starts a
+/// comment), single-quoted (literal), or double-quoted (`\n`, `\"`, `\\`
+/// escapes). Later lines win.
+fn parse_dotenv(content: &str) -> Result<Vec<(String, String)>> {
+    let mut vars = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, value)) = line.split_once('=') else {
+            bail!("line {}: expected `KEY=value`", i + 1);
+        };
+        let key = key.trim();
+        if !is_env_name(key) {
+            bail!("line {}: invalid variable name `{}`", i + 1, key);
+        }
+        let value = value.trim();
+        let value = if let Some(quoted) = value.strip_prefix('\'') {
+            match quoted.find('\'') {
+                Some(end) => quoted[..end].to_string(),
+                None => bail!("line {}: unterminated single quote", i + 1),
+            }
+        } else if let Some(quoted) = value.strip_prefix('"') {
+            unescape_double_quoted(quoted)
+                .with_context(|| format!("line {}: unterminated double quote", i + 1))?
+        } else {
+            match value.find(" #") {
+                Some(comment) => value[..comment].trim_end().to_string(),
+                None => value.to_string(),
+            }
+        };
+        vars.push((key.to_string(), value));
+    }
+    Ok(vars)
+}
+
+/// The value of a double-quoted string up to its closing quote; `None` when
+/// the quote is never closed.
+fn unescape_double_quoted(quoted: &str) -> Option<String> {
+    let mut value = String::new();
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return Some(value),
+            '\\' => match chars.next()? {
+                'n' => value.push('\n'),
+                't' => value.push('\t'),
+                other => value.push(other),
+            },
+            c => value.push(c),
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dotenv() {
+        let vars = parse_dotenv(
+            "# local settings\n\
+             DATABASE_URL=postgres://localhost/dev # dev db\n\
+             export API_KEY = 'se#cret'\n\
+             GREETING=\"hello \\\"world\\\"\\nbye\"\n\
+             \n\
+             EMPTY=\n",
+        )
+        .unwrap();
+        assert_eq!(
+            vars,
+            [
+                ("DATABASE_URL", "postgres://localhost/dev"),
+                ("API_KEY", "se#cret"),
+                ("GREETING", "hello \"world\"\nbye"),
+                ("EMPTY", ""),
+            ]
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+        );
+
+        assert!(parse_dotenv("NOT A LINE\n").is_err());
+        assert!(parse_dotenv("1X=y\n").is_err());
+        assert!(parse_dotenv("X=\"open\n").is_err());
+    }
+
+    #[test]
+    fn test_program_env() {
+        let temp = tempfile::TempDir::new().unwrap();
+        fs::write(
+            temp.path().join(DOTENV_FILE),
+            "JARGO_TEST_FROM_DOTENV=1\nJARGO_TEST_SHARED=dotenv\n",
+        )
+        .unwrap();
+        let manifest = |run: &str| -> JargoToml {
+            toml::from_str(&format!(
+                "[package]\nname = \"demo\"\nversion = \"1.0.0\"\njava = \"21\"\n\n\
+                 {}\n[env]\nJARGO_TEST_SHARED = \"manifest\"\nPATH = \"/nowhere\"\n",
+                run
+            ))
+            .unwrap()
+        };
+
+        // PATH is already set, so it is left alone
+        let env = program_env(temp.path(), &manifest(""), None).unwrap();
+        assert_eq!(
+            env,
+            [("JARGO_TEST_SHARED".to_string(), "manifest".to_string())]
+        );
+
+        let env = program_env(temp.path(), &manifest("[run]\ndotenv = true\n"), None).unwrap();
+        assert_eq!(
+            env,
+            [
+                ("JARGO_TEST_FROM_DOTENV", "1"),
+                ("JARGO_TEST_SHARED", "manifest"),
+            ]
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+        );
+
+        let m = manifest("[run.profiles.ci]\nenv = { JARGO_TEST_SHARED = \"profile\" }\n");
+        let env = program_env(temp.path(), &m, Some(m.get_run_profile("ci").unwrap())).unwrap();
+        assert_eq!(
+            env,
+            [("JARGO_TEST_SHARED".to_string(), "profile".to_string())]
+        );
+
+        let m = manifest("[test]\nenv = { JARGO_TEST_SHARED = \"test\" }\n");
+        assert_eq!(
+            test_env(temp.path(), &m).unwrap(),
+            [("JARGO_TEST_SHARED".to_string(), "test".to_string())]
+        );
+        assert_eq!(
+            program_env(temp.path(), &m, None).unwrap(),
+            [("JARGO_TEST_SHARED".to_string(), "manifest".to_string())]
+        );
+    }
+}
@@ -0,0 +1,144 @@
+use anyhow::{bail, Context, Result};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::context::GlobalContext;
+use crate::manifest::{CheckSeverity, JargoToml};
+use crate::resolver;
+use crate::toolchain::Toolchain;
+
+/// Error Prone version used unless `[error-prone] version` is set. The last
+/// line of releases that still runs on JDK 11.
+pub const ERROR_PRONE_VERSION: &str = "2.28.0";
+
+/// javac internals Error Prone uses, closed to plugins since JDK 16:
+/// exported, then opened for reflection.
+const JAVAC_EXPORTS: [&str; 8] = [
+    "api",
+    "file",
+    "main",
+    "model",
+    "parser",
+    "processing",
+    "tree",
+    "util",
+];
+const JAVAC_OPENS: [&str; 2] = ["code", "comp"];
+
+/// What running Error Prone adds to a javac invocation.
+#[derive(Debug, PartialEq)]
+pub struct ErrorProneFlags {
+    /// Written to the javac argument file.
+    pub javac_args: Vec<String>,
+    /// For the javac JVM itself, passed as `-J` flags.
+    pub jvm_args: Vec<String>,
+}
+
+/// Resolve Error Prone and work out the javac flags that run it on
+/// `toolchain`, or `None` without an `[error-prone]` section.
+pub fn flags(
+    gctx: &GlobalContext,
+    toolchain: &Toolchain,
+    manifest: &JargoToml,
+) -> Result<Option<ErrorProneFlags>> {
+    let Some(config) = manifest.error_prone.as_ref() else {
+        return Ok(None);
+    };
+    let version = config.version.as_deref().unwrap_or(ERROR_PRONE_VERSION);
+    // An unknown toolchain version is taken to be a current JDK.
+    let feature = toolchain.version.unwrap_or(u32::MAX);
+    if feature < 11 {
+        bail!(
+            "Error Prone {} needs javac from JDK 11 or newer, but the toolchain is JDK {}",
+            version,
+            feature
+        );
+    }
+    let processor_path = resolver::resolve_tool(
+        gctx,
+        &[("com.google.errorprone", "error_prone_core", version)],
+    )?;
+    Ok(Some(javac_flags(feature, &processor_path, &config.checks)?))
+}
+
+fn javac_flags(
+    feature: u32,
+    processor_path: &[PathBuf],
+    checks: &BTreeMap<String, CheckSeverity>,
+) -> Result<ErrorProneFlags> {
+    let processor_path = std::env::join_paths(processor_path)
+        .context("invalid Error Prone path entry")?
+        .to_string_lossy()
+        .into_owned();
+    let mut plugin = "-Xplugin:ErrorProne".to_string();
+    for (check, severity) in checks {
+        plugin.push_str(&format!(" -Xep:{}:{}", check, severity.flag()));
+    }
+    let javac_args = vec![
+        "-XDcompilePolicy=simple".to_string(),
+        "--should-stop=ifError=FLOW".to_string(),
+        "-processorpath".to_string(),
+        processor_path,
+        // One argument: quoted so the argument file keeps its spaces.
+        format!("\"{}\"", plugin),
+    ];
+
+    let mut jvm_args = Vec::new();
+    if feature >= 16 {
+        for package in JAVAC_EXPORTS {
+            jvm_args.push(format!(
+                "--add-exports=jdk.compiler/com.sun.tools.javac.{}=ALL-UNNAMED",
+                package
+            ));
+        }
+        for package in JAVAC_OPENS {
+            jvm_args.push(format!(
+                "--add-opens=jdk.compiler/com.sun.tools.javac.{}=ALL-UNNAMED",
+                package
+            ));
+        }
+    }
+    Ok(ErrorProneFlags {
+        javac_args,
+        jvm_args,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_javac_flags() {
+        let path = [PathBuf::from("/cache/error_prone_core.jar")];
+        let checks = BTreeMap::from([
+            ("DeadException".to_string(), CheckSeverity::Off),
+            ("MissingOverride".to_string(), CheckSeverity::Error),
+        ]);
+
+        let flags = javac_flags(21, &path, &checks).unwrap();
+        assert_eq!(
+            flags.javac_args,
+            [
+                "-XDcompilePolicy=simple",
+                "--should-stop=ifError=FLOW",
+                "-processorpath",
+                "/cache/error_prone_core.jar",
+                "\"-Xplugin:ErrorProne -Xep:DeadException:OFF -Xep:MissingOverride:ERROR\"",
+            ]
+        );
+        assert_eq!(flags.jvm_args.len(), 10);
+        assert_eq!(
+            flags.jvm_args[0],
+            "--add-exports=jdk.compiler/com.sun.tools.javac.api=ALL-UNNAMED"
+        );
+        assert_eq!(
+            flags.jvm_args[9],
+            "--add-opens=jdk.compiler/com.sun.tools.javac.comp=ALL-UNNAMED"
+        );
+
+        let flags = javac_flags(11, &path, &BTreeMap::new()).unwrap();
+        assert_eq!(flags.javac_args[4], "\"-Xplugin:ErrorProne\"");
+        assert!(flags.jvm_args.is_empty());
+    }
+}
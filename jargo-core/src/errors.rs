@@ -14,27 +14,260 @@ pub enum JargoError {
     #[error("could not determine directory name")]
     NoDirName,
 
-    #[error("Jargo.toml not found in current directory")]
+    #[error("Jargo.toml not found in the current directory or any parent directory")]
     ManifestNotFound,
 
     #[error("failed to parse Jargo.toml: {0}")]
     ManifestParse(String),
 
+    #[error("{0} problem(s) in Jargo.toml")]
+    InvalidManifest(usize),
+
+    #[error("invalid --manifest-path `{0}`: {1}")]
+    InvalidManifestPath(String, String),
+
+    #[error("{0} is a workspace root; run this command in one of its members")]
+    WorkspaceRoot(String),
+
+    #[error("codegen step `{0}` failed")]
+    CodegenFailed(String),
+
+    #[error("native-image failed")]
+    NativeImageFailed,
+
     #[error("javac compilation failed")]
     CompilationFailed,
 
+    #[error("{0} test(s) failed")]
+    TestsFailed(usize),
+
+    #[error("coverage below the [test.coverage] minimum: {0}")]
+    CoverageTooLow(String),
+
+    #[error("mutation score {0:.1}% is below the [test.mutation] threshold of {1}%")]
+    MutationScoreTooLow(f64, f64),
+
+    #[error("JAR verification failed: {0} problem(s)")]
+    JarVerificationFailed(usize),
+
+    #[error("publish check failed: {0} problem(s)")]
+    PublishCheckFailed(usize),
+
+    #[error("{0} file(s) not formatted; run `jargo fmt`")]
+    NotFormatted(usize),
+
+    #[error(
+        "{0} file(s) without the `[lint.header]` license header; run `jargo fix --license-header`"
+    )]
+    MissingLicenseHeader(usize),
+
+    #[error("{0} lint violation(s) at or above the `[lint] fail-on` severity")]
+    LintFailed(usize),
+
+    #[error("API check failed: {0}")]
+    ApiCheckFailed(String),
+
+    #[error("{0} dependency license(s) not allowed by [licenses] allow")]
+    LicensesNotAllowed(usize),
+
+    #[error("{0} dependency JAR(s) have class files newer than `java = \"{1}\"`")]
+    BytecodeTooNew(usize, String),
+
+    #[error("ci failed: {0}")]
+    CiFailed(String),
+
     #[error("javac not found in PATH")]
     JavacNotFound,
 
     #[error("java not found in PATH")]
     JavaNotFound,
 
+    #[error(
+        "project requires Java {required} but found javac {found} (from {origin}); point \
+         `[toolchain]`, `.jargo-java-version`, or JAVA_HOME at JDK {required} or newer"
+    )]
+    JdkTooOld {
+        found: u32,
+        required: String,
+        origin: String,
+    },
+
+    #[error("no JDK {version} found (pinned by {origin}); install it or set JAVA_HOME")]
+    JdkNotFound { version: String, origin: String },
+
     #[error("`jargo run` requires an app project (type = \"app\")")]
     NotAnApp,
 
+    #[error("no such command or `[tasks]` entry: `{0}`")]
+    TaskNotFound(String),
+
     #[error("dependency `{0}:{1}` version `{2}` not found on Maven Central")]
     DependencyNotFound(String, String, String),
 
+    #[error("{0} needs to be updated but --locked was passed")]
+    LockOutdated(String),
+
+    #[error("HTTP {0} fetching {1}")]
+    HttpStatus(String, String),
+
+    #[error("cannot reach `{0}`: jargo is offline (`--offline`, `JARGO_OFFLINE`, or `offline` in ~/.jargo/config.toml)")]
+    Offline(String),
+
     #[error(transparent)]
     Io(#[from] std::io::Error),
 }
+
+/// What kind of failure ended a command. Each kind has a stable exit code,
+/// so scripts can branch on it without parsing messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Anything not classified below. Exit code 1.
+    Other,
+    /// Jargo.toml, the command line, or the environment (such as a missing
+    /// JDK) is wrong. Exit code 3; clap reports bad arguments with 2.
+    Config,
+    /// The sources did not compile. Exit code 4.
+    Compile,
+    /// Tests failed, or coverage or the mutation score fell short. Exit code 5.
+    Test,
+    /// A dependency could not be resolved. Exit code 6.
+    Resolution,
+    /// A repository could not be reached, or `--offline` forbade it. Exit code 7.
+    Network,
+    /// A check such as `fmt --check`, `lint`, or `verify-jar` found problems.
+    /// Exit code 8.
+    Check,
+}
+
+impl ErrorKind {
+    /// Classify `err` by the first [`JargoError`] or HTTP client error in its
+    /// chain of causes.
+    pub fn of(err: &anyhow::Error) -> ErrorKind {
+        for cause in err.chain() {
+            if let Some(err) = cause.downcast_ref::<JargoError>() {
+                return err.kind();
+            }
+            if cause.is::<reqwest::Error>() {
+                return ErrorKind::Network;
+            }
+        }
+        ErrorKind::Other
+    }
+
+    pub fn exit_code(self) -> i32 {
+        match self {
+            ErrorKind::Other => 1,
+            ErrorKind::Config => 3,
+            ErrorKind::Compile => 4,
+            ErrorKind::Test => 5,
+            ErrorKind::Resolution => 6,
+            ErrorKind::Network => 7,
+            ErrorKind::Check => 8,
+        }
+    }
+
+    /// The name `--error-format json` reports.
+    pub fn name(self) -> &'static str {
+        match self {
+            ErrorKind::Other => "other",
+            ErrorKind::Config => "config",
+            ErrorKind::Compile => "compile",
+            ErrorKind::Test => "test",
+            ErrorKind::Resolution => "resolution",
+            ErrorKind::Network => "network",
+            ErrorKind::Check => "check",
+        }
+    }
+}
+
+impl JargoError {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            JargoError::ProjectExists(_)
+            | JargoError::InvalidName(..)
+            | JargoError::AlreadyInitialized
+            | JargoError::NoDirName
+            | JargoError::ManifestNotFound
+            | JargoError::ManifestParse(_)
+            | JargoError::InvalidManifest(_)
+            | JargoError::InvalidManifestPath(..)
+            | JargoError::WorkspaceRoot(_)
+            | JargoError::NotAnApp
+            | JargoError::TaskNotFound(_)
+            | JargoError::JavacNotFound
+            | JargoError::JavaNotFound
+            | JargoError::JdkTooOld { .. }
+            | JargoError::JdkNotFound { .. } => ErrorKind::Config,
+            JargoError::CodegenFailed(_) | JargoError::CompilationFailed => ErrorKind::Compile,
+            JargoError::TestsFailed(_)
+            | JargoError::CoverageTooLow(_)
+            | JargoError::MutationScoreTooLow(..) => ErrorKind::Test,
+            JargoError::DependencyNotFound(..) | JargoError::LockOutdated(_) => {
+                ErrorKind::Resolution
+            }
+            JargoError::HttpStatus(..) | JargoError::Offline(_) => ErrorKind::Network,
+            JargoError::JarVerificationFailed(_)
+            | JargoError::PublishCheckFailed(_)
+            | JargoError::NotFormatted(_)
+            | JargoError::MissingLicenseHeader(_)
+            | JargoError::LintFailed(_)
+            | JargoError::ApiCheckFailed(_)
+            | JargoError::LicensesNotAllowed(_)
+            | JargoError::BytecodeTooNew(..)
+            | JargoError::CiFailed(_) => ErrorKind::Check,
+            JargoError::NativeImageFailed | JargoError::Io(_) => ErrorKind::Other,
+        }
+    }
+}
+
+/// Render `err` as the single JSON line `--error-format json` prints:
+/// its kind, exit code, message, and the chain of causes below it.
+pub fn to_json(err: &anyhow::Error) -> String {
+    let kind = ErrorKind::of(err);
+    let causes: Vec<String> = err.chain().skip(1).map(|c| c.to_string()).collect();
+    serde_json::json!({
+        "kind": kind.name(),
+        "exit_code": kind.exit_code(),
+        "message": err.to_string(),
+        "causes": causes,
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_kind_of_wrapped_error() {
+        let err = Err::<(), _>(JargoError::DependencyNotFound(
+            "com.example".to_string(),
+            "demo".to_string(),
+            "1.0".to_string(),
+        ))
+        .context("failed to fetch com.example:demo:1.0")
+        .unwrap_err();
+        assert_eq!(ErrorKind::of(&err), ErrorKind::Resolution);
+        assert_eq!(ErrorKind::of(&err).exit_code(), 6);
+        assert_eq!(
+            ErrorKind::of(&anyhow::anyhow!("something else")),
+            ErrorKind::Other
+        );
+    }
+
+    #[test]
+    fn test_to_json() {
+        let err = anyhow::Error::from(JargoError::CompilationFailed).context("build failed");
+        let json: serde_json::Value = serde_json::from_str(&to_json(&err)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "kind": "compile",
+                "exit_code": 4,
+                "message": "build failed",
+                "causes": ["javac compilation failed"],
+            })
+        );
+    }
+}
@@ -0,0 +1,572 @@
+use anyhow::{bail, Context, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::cache;
+use crate::compiler::{self, SourceSet};
+use crate::context::GlobalContext;
+use crate::manifest::{FmtStyle, Formatter, JargoToml};
+use crate::toolchain::Toolchain;
+
+/// google-java-format version used by `jargo fmt`. Needs JDK 17 to run.
+pub const GJF_VERSION: &str = "1.22.0";
+
+/// palantir-java-format version used with `[fmt] formatter = "palantir-java-format"`.
+pub const PALANTIR_VERSION: &str = "2.50.0";
+
+/// palantir-java-format and its dependencies, as `(group, artifact, version)`.
+/// Pinned here rather than resolved, like PIT.
+const PALANTIR_JARS: [(&str, &str, &str); 5] = [
+    (
+        "com.palantir.javaformat",
+        "palantir-java-format",
+        PALANTIR_VERSION,
+    ),
+    (
+        "com.palantir.javaformat",
+        "palantir-java-format-spi",
+        PALANTIR_VERSION,
+    ),
+    ("com.google.guava", "guava", "33.2.1-jre"),
+    ("com.google.guava", "failureaccess", "1.0.2"),
+    ("org.functionaljava", "functionaljava", "4.8"),
+];
+
+/// The Eclipse JDT formatter and the platform bundles it needs outside of
+/// Eclipse, as `(group, artifact, version)`.
+const ECLIPSE_JARS: [(&str, &str, &str); 9] = [
+    ("org.eclipse.jdt", "org.eclipse.jdt.core", "3.36.0"),
+    ("org.eclipse.platform", "org.eclipse.text", "3.13.100"),
+    ("org.eclipse.platform", "org.eclipse.core.runtime", "3.30.0"),
+    (
+        "org.eclipse.platform",
+        "org.eclipse.equinox.common",
+        "3.18.200",
+    ),
+    ("org.eclipse.platform", "org.eclipse.core.jobs", "3.15.100"),
+    (
+        "org.eclipse.platform",
+        "org.eclipse.core.resources",
+        "3.20.0",
+    ),
+    (
+        "org.eclipse.platform",
+        "org.eclipse.core.contenttype",
+        "3.9.200",
+    ),
+    (
+        "org.eclipse.platform",
+        "org.eclipse.equinox.preferences",
+        "3.10.400",
+    ),
+    ("org.eclipse.platform", "org.eclipse.osgi", "3.18.600"),
+];
+
+/// Source of the helper that applies an Eclipse formatter profile, run in
+/// source-file mode so it needs no compile step.
+const ECLIPSE_HELPER_SOURCE: &str = include_str!("../java/EclipseFormat.java");
+
+/// Where sources are copied to be formatted, so the originals are only
+/// touched once every file has been formatted.
+const STAGING_DIR: &str = "target/fmt";
+
+/// javac internals google-java-format and palantir-java-format parse with,
+/// closed by default since JDK 16.
+const JAVAC_EXPORTS: [&str; 6] = ["api", "code", "file", "parser", "tree", "util"];
+
+/// A formatter ready to run: `java -cp {classpath} {main} {args} {files}`
+/// rewrites `files` in place.
+#[derive(Debug)]
+pub struct FormatterCommand {
+    pub name: &'static str,
+    pub classpath: Vec<PathBuf>,
+    /// Main class, or the path of a Java source file to launch.
+    pub main: String,
+    pub args: Vec<String>,
+}
+
+/// A source file whose formatting differs from the formatter's.
+#[derive(Debug, PartialEq)]
+pub struct Unformatted {
+    /// Relative to the project root.
+    pub path: PathBuf,
+    /// First and last line of the original that change, 1-based.
+    pub lines: (usize, usize),
+    pub formatted: String,
+}
+
+impl fmt::Display for Unformatted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (first, last) = self.lines;
+        if first == last {
+            write!(f, "{}: line {}", self.path.display(), first)
+        } else {
+            write!(f, "{}: lines {}-{}", self.path.display(), first, last)
+        }
+    }
+}
+
+/// Fetch the `[fmt] formatter` into the cache and work out how to run it.
+pub fn fetch_formatter(
+    gctx: &GlobalContext,
+    project_root: &Path,
+    manifest: &JargoToml,
+) -> Result<FormatterCommand> {
+    let formatter = manifest.get_formatter();
+    let (name, main, args) = formatter_args(project_root, manifest)?;
+    let classpath = match formatter {
+        Formatter::GoogleJavaFormat => vec![cache::fetch_classified_jar(
+            gctx,
+            "com.google.googlejavaformat",
+            "google-java-format",
+            GJF_VERSION,
+            "all-deps",
+        )
+        .context("failed to fetch google-java-format")?],
+        Formatter::PalantirJavaFormat => fetch_all(gctx, &PALANTIR_JARS, name)?,
+        Formatter::Eclipse => fetch_all(gctx, &ECLIPSE_JARS, name)?,
+    };
+    let main = match main {
+        Some(main) => main.to_string(),
+        None => {
+            let helper = write_eclipse_helper(gctx)?;
+            helper.to_string_lossy().into_owned()
+        }
+    };
+    Ok(FormatterCommand {
+        name,
+        classpath,
+        main,
+        args,
+    })
+}
+
+/// The `[fmt] formatter` set up to only remove unused imports and sort the
+/// rest in its own order, or `None` for the Eclipse formatter, which has no
+/// such mode.
+pub fn fetch_import_fixer(
+    gctx: &GlobalContext,
+    project_root: &Path,
+    manifest: &JargoToml,
+) -> Result<Option<FormatterCommand>> {
+    if manifest.get_formatter() == Formatter::Eclipse {
+        return Ok(None);
+    }
+    let mut formatter = fetch_formatter(gctx, project_root, manifest)?;
+    formatter.args = vec!["--fix-imports-only".to_string(), "--replace".to_string()];
+    Ok(Some(formatter))
+}
+
+fn fetch_all(
+    gctx: &GlobalContext,
+    jars: &[(&str, &str, &str)],
+    name: &str,
+) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for &(group, artifact, version) in jars {
+        let (path, _sha256) = cache::fetch_jar(gctx, group, artifact, version)
+            .with_context(|| format!("failed to fetch {}", name))?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+/// The name, main class (`None` for the Eclipse helper), and leading
+/// arguments of the `[fmt] formatter`.
+fn formatter_args(
+    project_root: &Path,
+    manifest: &JargoToml,
+) -> Result<(&'static str, Option<&'static str>, Vec<String>)> {
+    Ok(match manifest.get_formatter() {
+        Formatter::GoogleJavaFormat => {
+            let mut args = Vec::new();
+            if manifest.get_fmt_style()? == FmtStyle::Aosp {
+                args.push("--aosp".to_string());
+            }
+            args.push("--replace".to_string());
+            (
+                "google-java-format",
+                Some("com.google.googlejavaformat.java.Main"),
+                args,
+            )
+        }
+        Formatter::PalantirJavaFormat => (
+            "palantir-java-format",
+            Some("com.palantir.javaformat.java.Main"),
+            vec!["--palantir".to_string(), "--replace".to_string()],
+        ),
+        Formatter::Eclipse => {
+            let Some(config) = manifest.fmt.as_ref().and_then(|f| f.config.as_ref()) else {
+                bail!(
+                    "`[fmt] formatter = \"eclipse\"` needs `config`, the path of an \
+                     Eclipse formatter profile XML"
+                );
+            };
+            let config = project_root.join(config);
+            if !config.is_file() {
+                bail!("`[fmt] config` {} does not exist", config.display());
+            }
+            let config = config.to_string_lossy().into_owned();
+            let release = manifest.package.java.clone();
+            ("Eclipse formatter", None, vec![config, release])
+        }
+    })
+}
+
+/// Write the Eclipse formatter helper to `~/.jargo/tools/`, unless it is
+/// already there.
+fn write_eclipse_helper(gctx: &GlobalContext) -> Result<PathBuf> {
+    let dir = gctx.jargo_home.join("tools");
+    let helper = dir.join("EclipseFormat.java");
+    if fs::read_to_string(&helper).is_ok_and(|s| s == ECLIPSE_HELPER_SOURCE) {
+        return Ok(helper);
+    }
+    fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    fs::write(&helper, ECLIPSE_HELPER_SOURCE)
+        .with_context(|| format!("failed to write {}", helper.display()))?;
+    Ok(helper)
+}
+
+/// The `.java` files `jargo fmt` formats, relative to `project_root` and
+/// sorted: those matching `[fmt] include`, else every file in a source set
+/// directory or a deprecated `[format] paths` directory, less those matching
+/// `[fmt] exclude`. Globs use `.gitignore`
+/// syntax.
+pub fn source_files(project_root: &Path, manifest: &JargoToml) -> Result<Vec<PathBuf>> {
+    let config = manifest.fmt.as_ref();
+    let include = config.and_then(|c| c.include.as_deref());
+    let exclude = config.map(|c| c.exclude.as_slice()).unwrap_or_default();
+
+    let mut files = Vec::new();
+    match include {
+        Some(include) => {
+            let include = matcher(project_root, include, "include")?;
+            walk_java_files(project_root, project_root, &mut files)?;
+            files.retain(|file| matches(&include, file));
+        }
+        None => {
+            let sets = [
+                SourceSet::Main,
+                SourceSet::Test,
+                SourceSet::IntegrationTest,
+                SourceSet::Examples,
+                SourceSet::Bench,
+            ];
+            let dirs = sets
+                .iter()
+                .map(|set| set.source_dir())
+                .chain(manifest.get_format_paths().iter().map(String::as_str));
+            for dir in dirs {
+                for file in compiler::find_java_files(&project_root.join(dir))? {
+                    let rel = file.strip_prefix(project_root).unwrap_or(&file);
+                    files.push(rel.to_path_buf());
+                }
+            }
+        }
+    }
+    let exclude = matcher(project_root, exclude, "exclude")?;
+    files.retain(|file| !matches(&exclude, file));
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+fn matcher(project_root: &Path, globs: &[String], key: &str) -> Result<Gitignore> {
+    let mut builder = GitignoreBuilder::new(project_root);
+    for glob in globs {
+        builder
+            .add_line(None, glob)
+            .with_context(|| format!("invalid `[fmt] {}` glob `{}`", key, glob))?;
+    }
+    builder
+        .build()
+        .with_context(|| format!("invalid `[fmt] {}` globs", key))
+}
+
+fn matches(matcher: &Gitignore, file: &Path) -> bool {
+    matcher.matched_path_or_any_parents(file, false).is_ignore()
+}
+
+/// Every `.java` file under `dir` as a path relative to `root`, skipping
+/// `target/` and hidden directories.
+fn walk_java_files(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir).with_context(|| format!("failed to read {}", dir.display()))? {
+        let entry = entry?;
+        let path = entry.path();
+        let name = entry.file_name();
+        if entry.file_type()?.is_dir() {
+            let hidden = name.to_string_lossy().starts_with('.');
+            if !(hidden || dir == root && name == "target") {
+                walk_java_files(root, &path, files)?;
+            }
+        } else if path.extension().is_some_and(|e| e == "java") {
+            files.push(path.strip_prefix(root).unwrap_or(&path).to_path_buf());
+        }
+    }
+    Ok(())
+}
+
+/// Run `formatter` over copies of `files` in one JVM and return those whose
+/// formatting changes, in the order given. Nothing under `project_root`
+/// except `target/` is written.
+pub fn check(
+    project_root: &Path,
+    toolchain: &Toolchain,
+    formatter: &FormatterCommand,
+    files: &[PathBuf],
+) -> Result<Vec<Unformatted>> {
+    if files.is_empty() {
+        return Ok(Vec::new());
+    }
+    let staging = project_root.join(STAGING_DIR);
+    if staging.exists() {
+        fs::remove_dir_all(&staging)
+            .with_context(|| format!("failed to remove {}", staging.display()))?;
+    }
+    let mut originals = Vec::new();
+    for file in files {
+        let source = project_root.join(file);
+        let original = fs::read_to_string(&source)
+            .with_context(|| format!("failed to read {}", source.display()))?;
+        let copy = staging.join(file);
+        fs::create_dir_all(copy.parent().expect("staged file has a parent"))
+            .with_context(|| format!("failed to create {}", staging.display()))?;
+        fs::write(&copy, &original)
+            .with_context(|| format!("failed to write {}", copy.display()))?;
+        originals.push(original);
+    }
+
+    let classpath =
+        std::env::join_paths(&formatter.classpath).context("invalid classpath entry")?;
+    let mut cmd = Command::new(toolchain.java());
+    for package in JAVAC_EXPORTS {
+        cmd.arg(format!(
+            "--add-exports=jdk.compiler/com.sun.tools.javac.{}=ALL-UNNAMED",
+            package
+        ));
+    }
+    cmd.arg("-cp")
+        .arg(&classpath)
+        .arg(&formatter.main)
+        .args(&formatter.args)
+        .args(files)
+        .current_dir(&staging);
+    let output = cmd
+        .output()
+        .with_context(|| format!("failed to run {}", toolchain.java().display()))?;
+    if !output.status.success() {
+        bail!(
+            "{} failed:\n{}",
+            formatter.name,
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        );
+    }
+
+    let mut unformatted = Vec::new();
+    for (file, original) in files.iter().zip(originals) {
+        let copy = staging.join(file);
+        let formatted = fs::read_to_string(&copy)
+            .with_context(|| format!("failed to read {}", copy.display()))?;
+        if let Some(lines) = changed_lines(&original, &formatted) {
+            unformatted.push(Unformatted {
+                path: file.clone(),
+                lines,
+                formatted,
+            });
+        }
+    }
+    Ok(unformatted)
+}
+
+/// The 1-based line range of `old` that differs from `new`, between their
+/// common leading and trailing lines, or `None` when they are equal.
+fn changed_lines(old: &str, new: &str) -> Option<(usize, usize)> {
+    if old == new {
+        return None;
+    }
+    let old_lines: Vec<&str> = old.split('\n').collect();
+    let new_lines: Vec<&str> = new.split('\n').collect();
+    let prefix = old_lines
+        .iter()
+        .zip(&new_lines)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old_lines[prefix..]
+        .iter()
+        .rev()
+        .zip(new_lines[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let first = (prefix + 1).min(old_lines.len());
+    let last = (old_lines.len() - suffix).max(first);
+    Some((first, last))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest_with(extra: &str) -> JargoToml {
+        toml::from_str(&format!(
+            "[package]\nname = \"demo\"\nversion = \"1.0.0\"\njava = \"21\"\n{}",
+            extra
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_changed_lines() {
+        let old = "class A {\n  int x;\n  int y;\n}\n";
+        assert_eq!(changed_lines(old, old), None);
+        assert_eq!(
+            changed_lines(old, "class A {\n    int x;\n    int y;\n}\n"),
+            Some((2, 3))
+        );
+        assert_eq!(changed_lines(old, "class A {\n  int x;\n}\n"), Some((3, 3)));
+        assert_eq!(changed_lines("class A {}", "class A {}\n"), Some((1, 1)));
+    }
+
+    #[test]
+    fn test_source_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        for file in [
+            "src/Main.java",
+            "src/util/Strings.java",
+            "src/generated/Api.java",
+            "test/MainTest.java",
+            "tools/Gen.java",
+            "target/fmt/src/Main.java",
+        ] {
+            let path = tmp.path().join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+        fs::write(tmp.path().join("src/notes.txt"), "").unwrap();
+
+        let files = |fmt: &str| -> Vec<String> {
+            let manifest = manifest_with(&format!("\n[fmt]\n{}", fmt));
+            source_files(tmp.path(), &manifest)
+                .unwrap()
+                .iter()
+                .map(|p| p.to_string_lossy().replace('\\', "/"))
+                .collect()
+        };
+        assert_eq!(
+            files(""),
+            [
+                "src/Main.java",
+                "src/generated/Api.java",
+                "src/util/Strings.java",
+                "test/MainTest.java"
+            ]
+        );
+        assert_eq!(
+            files("exclude = [\"src/generated/\"]"),
+            [
+                "src/Main.java",
+                "src/util/Strings.java",
+                "test/MainTest.java"
+            ]
+        );
+        assert_eq!(
+            files("include = [\"src/**/*.java\", \"tools/\"]\nexclude = [\"Strings.java\"]"),
+            ["src/Main.java", "src/generated/Api.java", "tools/Gen.java"]
+        );
+
+        // Deprecated `[format] paths` add directories to the default set
+        let manifest = manifest_with("\n[format]\npaths = [\"tools\", \"src\"]\n");
+        let files = source_files(tmp.path(), &manifest).unwrap();
+        assert_eq!(files.len(), 5);
+        assert!(files.contains(&PathBuf::from("tools/Gen.java")));
+    }
+
+    #[test]
+    fn test_matcher() {
+        let root = Path::new("/work/demo");
+        let globs = |globs: &[&str]| {
+            let globs: Vec<String> = globs.iter().map(|g| g.to_string()).collect();
+            matcher(root, &globs, "include").unwrap()
+        };
+        let file = |path: &str| PathBuf::from(path);
+
+        // A directory matches every file below it
+        let dir = globs(&["src/generated/"]);
+        assert!(matches(&dir, &file("src/generated/Api.java")));
+        assert!(matches(&dir, &file("src/generated/v1/Api.java")));
+        assert!(!matches(&dir, &file("src/Main.java")));
+
+        // A name without a slash matches at any depth; a leading slash
+        // anchors it to the project root
+        let name = globs(&["Strings.java"]);
+        assert!(matches(&name, &file("src/util/Strings.java")));
+        assert!(matches(&name, &file("Strings.java")));
+        let anchored = globs(&["/Strings.java"]);
+        assert!(matches(&anchored, &file("Strings.java")));
+        assert!(!matches(&anchored, &file("src/util/Strings.java")));
+
+        // `**` crosses directories, `*` does not, and `!` re-includes
+        let deep = globs(&["src/**/*.java", "!src/**/Main.java"]);
+        assert!(matches(&deep, &file("src/util/Strings.java")));
+        assert!(matches(&deep, &file("src/Strings.java")));
+        assert!(!matches(&deep, &file("src/Main.java")));
+        assert!(!matches(&deep, &file("test/StringsTest.java")));
+        let shallow = globs(&["src/*.java"]);
+        assert!(matches(&shallow, &file("src/Main.java")));
+        assert!(!matches(&shallow, &file("src/util/Strings.java")));
+
+        assert!(!matches(&globs(&[]), &file("src/Main.java")));
+        let err = matcher(root, &["src/{a".to_string()], "exclude").unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("invalid `[fmt] exclude` glob `src/{a`"));
+    }
+
+    #[test]
+    fn test_formatter_args() {
+        let root = tempfile::tempdir().unwrap();
+        let args = |fmt: &str| formatter_args(root.path(), &manifest_with(fmt));
+
+        let (name, main, gjf) = args("").unwrap();
+        assert_eq!(name, "google-java-format");
+        assert_eq!(main, Some("com.google.googlejavaformat.java.Main"));
+        assert_eq!(gjf, ["--aosp", "--replace"]);
+        let (_, _, google) = args("\n[fmt]\nstyle = \"google\"\n").unwrap();
+        assert_eq!(google, ["--replace"]);
+        let (_, main, palantir) = args("\n[fmt]\nformatter = \"palantir-java-format\"\n").unwrap();
+        assert_eq!(main, Some("com.palantir.javaformat.java.Main"));
+        assert_eq!(palantir, ["--palantir", "--replace"]);
+        // `style` only applies to google-java-format
+        let (_, _, palantir) =
+            args("\n[fmt]\nformatter = \"palantir-java-format\"\nstyle = \"google\"\n").unwrap();
+        assert_eq!(palantir, ["--palantir", "--replace"]);
+
+        // Deprecated `[format] indent` picks the style unless `[fmt]` does
+        let (_, _, indent) = args("\n[format]\nindent = 2\n").unwrap();
+        assert_eq!(indent, ["--replace"]);
+        let (_, _, indent) = args("\n[format]\nindent = 4\n").unwrap();
+        assert_eq!(indent, ["--aosp", "--replace"]);
+        let (_, _, styled) = args("\n[fmt]\nstyle = \"aosp\"\n\n[format]\nindent = 2\n").unwrap();
+        assert_eq!(styled, ["--aosp", "--replace"]);
+        let err = args("\n[format]\nindent = 3\n").unwrap_err();
+        assert!(err.to_string().contains("`[fmt] style"), "{}", err);
+
+        let err = args("\n[fmt]\nformatter = \"eclipse\"\n").unwrap_err();
+        assert!(err.to_string().contains("needs `config`"));
+        let err = args("\n[fmt]\nformatter = \"eclipse\"\nconfig = \"missing.xml\"\n").unwrap_err();
+        assert!(err.to_string().contains("does not exist"), "{}", err);
+        fs::write(root.path().join("eclipse-format.xml"), "<profiles/>").unwrap();
+        let (_, main, eclipse) =
+            args("\n[fmt]\nformatter = \"eclipse\"\nconfig = \"eclipse-format.xml\"\n").unwrap();
+        assert_eq!(main, None);
+        assert_eq!(
+            eclipse,
+            [
+                root.path().join("eclipse-format.xml").to_string_lossy(),
+                "21".into()
+            ]
+        );
+    }
+}
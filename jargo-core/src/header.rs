@@ -0,0 +1,193 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::build_info::format_utc;
+
+/// Placeholder in a `[lint.header] template` for the copyright year.
+const YEAR: &str = "{year}";
+
+/// The current year, for headers inserted now.
+pub fn current_year() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    format_utc(secs)[..4].to_string()
+}
+
+/// The template as a block comment, one ` * ` line per template line, with
+/// `{year}` left in place.
+fn comment_lines(template: &str) -> Vec<String> {
+    let mut lines = vec!["/*".to_string()];
+    for line in template.trim_matches('\n').lines() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            lines.push(" *".to_string());
+        } else {
+            lines.push(format!(" * {}", line));
+        }
+    }
+    lines.push(" */".to_string());
+    lines
+}
+
+/// Whether `source` starts with the header of `template`. `{year}` matches
+/// any year or range of years, such as `2019-2024`.
+pub fn has_header(source: &str, template: &str) -> bool {
+    let lines: Vec<&str> = source.lines().collect();
+    let existing = &lines[..leading_comments(&lines)];
+    let expected = comment_lines(template);
+    let existing: Vec<&str> = existing
+        .iter()
+        .map(|l| l.trim_end())
+        .filter(|l| !l.is_empty())
+        .collect();
+    existing.len() == expected.len()
+        && existing
+            .iter()
+            .zip(&expected)
+            .all(|(line, pattern)| matches_line(line, pattern))
+}
+
+/// `source` with the header of `template` (`{year}` set to `year`) in place
+/// of its leading comments, or `None` when it already has the header.
+/// Javadoc comments are never replaced.
+pub fn apply(source: &str, template: &str, year: &str) -> Option<String> {
+    if has_header(source, template) {
+        return None;
+    }
+    let lines: Vec<&str> = source.lines().collect();
+    let rest = &lines[leading_comments(&lines)..];
+    let newline = if source.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let mut out: Vec<String> = comment_lines(template)
+        .iter()
+        .map(|l| l.replace(YEAR, year))
+        .collect();
+    if !rest.is_empty() {
+        out.push(String::new());
+        out.extend(rest.iter().map(|l| l.to_string()));
+    }
+    let mut fixed = out.join(newline);
+    fixed.push_str(newline);
+    Some(fixed)
+}
+
+/// Number of lines at the top of a source that are blank, `//` comments, or
+/// block comments other than Javadoc, with the blank lines after them.
+fn leading_comments(lines: &[&str]) -> usize {
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].trim();
+        if line.is_empty() || line.starts_with("//") {
+            i += 1;
+        } else if line.starts_with("/*") && !line.starts_with("/**") {
+            match lines[i..].iter().position(|l| l.contains("*/")) {
+                Some(end) => i += end + 1,
+                None => return lines.len(),
+            }
+        } else {
+            break;
+        }
+    }
+    i
+}
+
+/// Compare a header line with a template line, `{year}` matching a year or
+/// range of years.
+fn matches_line(line: &str, pattern: &str) -> bool {
+    let mut parts = pattern.split(YEAR);
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = line.strip_prefix(first) else {
+        return false;
+    };
+    for part in parts {
+        let Some(after) = skip_years(rest) else {
+            return false;
+        };
+        let Some(after) = after.strip_prefix(part) else {
+            return false;
+        };
+        rest = after;
+    }
+    rest.is_empty()
+}
+
+/// `text` after a leading year, `2024`, or range, `2019-2024` or
+/// `2019, 2024`.
+fn skip_years(text: &str) -> Option<&str> {
+    let rest = skip_year(text)?;
+    for separator in ["-", ", ", "–"] {
+        if let Some(end) = rest.strip_prefix(separator).and_then(skip_year) {
+            return Some(end);
+        }
+    }
+    Some(rest)
+}
+
+fn skip_year(text: &str) -> Option<&str> {
+    let digits = text.as_bytes().get(..4)?;
+    digits.iter().all(u8::is_ascii_digit).then(|| &text[4..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPLATE: &str =
+        "Copyright {year} Acme Corp.\n\nLicensed under the Apache License, Version 2.0.\n";
+
+    #[test]
+    fn test_apply() {
+        let source = "package demo;\n\nclass Main {}\n";
+        let fixed = apply(source, TEMPLATE, "2024").unwrap();
+        assert_eq!(
+            fixed,
+            "/*\n\
+             \x20* Copyright 2024 Acme Corp.\n\
+             \x20*\n\
+             \x20* Licensed under the Apache License, Version 2.0.\n\
+             \x20*/\n\
+             \n\
+             package demo;\n\
+             \n\
+             class Main {}\n"
+        );
+        assert!(has_header(&fixed, TEMPLATE));
+        assert_eq!(apply(&fixed, TEMPLATE, "2030"), None);
+    }
+
+    #[test]
+    fn test_apply_replaces_old_header() {
+        let source = "// Copyright 2019 Someone\n\n/* old\n * header */\npackage demo;\n";
+        assert_eq!(
+            apply(source, "Copyright {year} Acme Corp.", "2024").unwrap(),
+            "/*\n * Copyright 2024 Acme Corp.\n */\n\npackage demo;\n"
+        );
+
+        let javadoc = "/** Main class. */\nclass Main {}\n";
+        assert_eq!(
+            apply(javadoc, "Copyright {year} Acme Corp.", "2024").unwrap(),
+            "/*\n * Copyright 2024 Acme Corp.\n */\n\n/** Main class. */\nclass Main {}\n"
+        );
+    }
+
+    #[test]
+    fn test_has_header_years() {
+        let header = |years: &str| {
+            format!(
+                "/*\n * Copyright {} Acme Corp.\n */\npackage demo;\n",
+                years
+            )
+        };
+        let template = "Copyright {year} Acme Corp.";
+        assert!(has_header(&header("2019"), template));
+        assert!(has_header(&header("2019-2024"), template));
+        assert!(has_header(&header("2019, 2024"), template));
+        assert!(!has_header(&header("19"), template));
+        assert!(!has_header(&header("2019 by"), template));
+        assert!(!has_header("package demo;\n", template));
+    }
+}
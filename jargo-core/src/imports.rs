@@ -0,0 +1,306 @@
+use std::collections::HashSet;
+
+/// Import groups of the built-in organizer when `[fmt] import-order` is not
+/// set: Eclipse's default order.
+pub const DEFAULT_IMPORT_ORDER: [&str; 5] = ["java", "javax", "org", "com", "*"];
+
+/// One `import` statement.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Import {
+    is_static: bool,
+    /// Qualified name, e.g. `java.util.List` or `java.util.*`.
+    path: String,
+}
+
+impl Import {
+    /// The name the import brings into scope, or `None` for `*` imports.
+    fn simple_name(&self) -> Option<&str> {
+        match self.path.rsplit('.').next() {
+            Some("*") | None => None,
+            name => name,
+        }
+    }
+
+    fn render(&self) -> String {
+        if self.is_static {
+            format!("import static {};", self.path)
+        } else {
+            format!("import {};", self.path)
+        }
+    }
+}
+
+/// Remove the unused and duplicate imports of a Java source and sort the
+/// rest into groups: static imports first, then one group per `order` entry
+/// (a package prefix, or `*` for everything else), blank lines between.
+///
+/// Returns `None`, leaving the file to the user, when something other than
+/// imports and blank lines (such as a comment) sits between two imports.
+pub fn organize(source: &str, order: &[String]) -> Option<String> {
+    let lines: Vec<&str> = source.lines().collect();
+    let Some(first) = lines.iter().position(|l| parse_import(l).is_some()) else {
+        return Some(source.to_string());
+    };
+    let mut imports = Vec::new();
+    let mut end = first;
+    for (i, line) in lines.iter().enumerate().skip(first) {
+        if let Some(import) = parse_import(line) {
+            imports.push(import);
+            end = i + 1;
+        } else if !line.trim().is_empty() {
+            break;
+        }
+    }
+    if lines[end..].iter().any(|l| parse_import(l).is_some()) {
+        return None;
+    }
+
+    let mut prefix = &lines[..first];
+    while prefix.last().is_some_and(|l| l.trim().is_empty()) {
+        prefix = &prefix[..prefix.len() - 1];
+    }
+    let body_start = lines[end..]
+        .iter()
+        .position(|l| !l.trim().is_empty())
+        .map_or(lines.len(), |i| end + i);
+    let body = &lines[body_start..];
+
+    let body_text = body.join("\n");
+    let used = identifiers(&body_text);
+    let mut seen = HashSet::new();
+    imports.retain(|import| {
+        import.simple_name().is_none_or(|name| used.contains(name)) && seen.insert(import.clone())
+    });
+
+    let mut out: Vec<String> = prefix.iter().map(|l| l.to_string()).collect();
+    for group in groups(imports, order) {
+        if !out.is_empty() {
+            out.push(String::new());
+        }
+        out.extend(group.iter().map(Import::render));
+    }
+    if !body.is_empty() {
+        if !out.is_empty() {
+            out.push(String::new());
+        }
+        out.extend(body.iter().map(|l| l.to_string()));
+    }
+
+    let newline = if source.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let mut organized = out.join(newline);
+    if source.ends_with('\n') {
+        organized.push_str(newline);
+    }
+    Some(organized)
+}
+
+/// `import [static] a.b.C;` on a line of its own.
+fn parse_import(line: &str) -> Option<Import> {
+    let rest = line.trim().strip_prefix("import")?.strip_suffix(';')?;
+    if !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let rest = rest.trim();
+    let (is_static, path) = match rest.strip_prefix("static") {
+        Some(path) if path.starts_with(char::is_whitespace) => (true, path),
+        _ => (false, rest),
+    };
+    let path: String = path.chars().filter(|c| !c.is_whitespace()).collect();
+    let valid = !path.is_empty()
+        && path
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '$' | '.' | '*'));
+    valid.then_some(Import { is_static, path })
+}
+
+/// Split `imports` into sorted groups, dropping empty ones.
+fn groups(imports: Vec<Import>, order: &[String]) -> Vec<Vec<Import>> {
+    let mut prefixes: Vec<&str> = order.iter().map(String::as_str).collect();
+    if !prefixes.contains(&"*") {
+        prefixes.push("*");
+    }
+    let catch_all = prefixes
+        .iter()
+        .position(|p| *p == "*")
+        .expect("pushed above");
+    // Statics, then one group per prefix.
+    let mut groups: Vec<Vec<Import>> = vec![Vec::new(); prefixes.len() + 1];
+    for import in imports {
+        let index = if import.is_static {
+            0
+        } else {
+            let matches =
+                |p: &&str| import.path.starts_with(*p) && import.path[p.len()..].starts_with('.');
+            // The longest matching prefix wins, so `com.acme` can follow `com`.
+            let best = prefixes
+                .iter()
+                .enumerate()
+                .filter(|(_, p)| **p != "*" && matches(p))
+                .max_by_key(|(_, p)| p.len());
+            1 + best.map_or(catch_all, |(i, _)| i)
+        };
+        groups[index].push(import);
+    }
+    for group in &mut groups {
+        group.sort_by(|a, b| a.path.cmp(&b.path));
+    }
+    groups.retain(|g| !g.is_empty());
+    groups
+}
+
+/// Identifiers in Java source outside string and character literals and
+/// comments. Javadoc is kept, so `{@link Foo}` keeps `Foo` imported.
+fn identifiers(source: &str) -> HashSet<&str> {
+    let bytes = source.as_bytes();
+    let mut found = HashSet::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let rest = &bytes[i..];
+        if rest.starts_with(b"//") {
+            i += rest.iter().position(|&b| b == b'\n').unwrap_or(rest.len());
+        } else if rest.starts_with(b"/*") && !rest.starts_with(b"/**") {
+            i += find(rest, b"*/").map_or(rest.len(), |end| end + 2);
+        } else if rest.starts_with(b"\"\"\"") {
+            i += 3 + find(&rest[3..], b"\"\"\"").map_or(rest.len() - 3, |end| end + 3);
+        } else if rest[0] == b'"' || rest[0] == b'\'' {
+            let quote = rest[0];
+            let mut j = 1;
+            while j < rest.len() && rest[j] != quote && rest[j] != b'\n' {
+                j += if rest[j] == b'\\' { 2 } else { 1 };
+            }
+            i += j + 1;
+        } else if is_identifier_start(rest[0]) {
+            let len = rest
+                .iter()
+                .position(|&b| !is_identifier_part(b))
+                .unwrap_or(rest.len());
+            found.insert(&source[i..i + len]);
+            i += len;
+        } else {
+            i += 1;
+        }
+    }
+    found
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// ASCII identifiers only; a non-ASCII byte ends one, which at worst keeps
+/// an import that is unused.
+fn is_identifier_start(b: u8) -> bool {
+    b.is_ascii_alphabetic() || b == b'_' || b == b'$'
+}
+
+fn is_identifier_part(b: u8) -> bool {
+    is_identifier_start(b) || b.is_ascii_digit()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order() -> Vec<String> {
+        DEFAULT_IMPORT_ORDER.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_organize() {
+        let source = "package demo;\n\
+                      \n\
+                      import org.junit.Test;\n\
+                      import java.util.List;\n\
+                      import com.acme.Widget;\n\
+                      import java.util.Map;\n\
+                      \n\
+                      import static java.util.Objects.requireNonNull;\n\
+                      import java.util.List;\n\
+                      import java.io.*;\n\
+                      import javax.inject.Inject;\n\
+                      import java.util.Set;\n\
+                      \n\
+                      /** Uses {@link Set}. */\n\
+                      class Main {\n\
+                      \x20   // Map is only mentioned here\n\
+                      \x20   String s = \"Widget\";\n\
+                      \x20   @Inject List<String> names = requireNonNull(null);\n\
+                      }\n";
+        assert_eq!(
+            organize(source, &order()).unwrap(),
+            "package demo;\n\
+             \n\
+             import static java.util.Objects.requireNonNull;\n\
+             \n\
+             import java.io.*;\n\
+             import java.util.List;\n\
+             import java.util.Set;\n\
+             \n\
+             import javax.inject.Inject;\n\
+             \n\
+             /** Uses {@link Set}. */\n\
+             class Main {\n\
+             \x20   // Map is only mentioned here\n\
+             \x20   String s = \"Widget\";\n\
+             \x20   @Inject List<String> names = requireNonNull(null);\n\
+             }\n"
+        );
+    }
+
+    #[test]
+    fn test_organize_custom_order() {
+        let source = "import com.acme.Widget;\n\
+                      import com.google.Gadget;\n\
+                      import java.util.List;\n\
+                      class Main { List<Widget> w; Gadget g; }\n";
+        let order = ["com.acme".to_string(), "*".to_string()];
+        assert_eq!(
+            organize(source, &order).unwrap(),
+            "import com.acme.Widget;\n\
+             \n\
+             import com.google.Gadget;\n\
+             import java.util.List;\n\
+             \n\
+             class Main { List<Widget> w; Gadget g; }\n"
+        );
+    }
+
+    #[test]
+    fn test_organize_edge_cases() {
+        let unchanged = "package demo;\n\nclass Main {}\n";
+        assert_eq!(organize(unchanged, &order()).unwrap(), unchanged);
+
+        assert_eq!(
+            organize(
+                "package demo;\n\nimport java.util.List;\n\nclass Main {}\n",
+                &order()
+            )
+            .unwrap(),
+            "package demo;\n\nclass Main {}\n"
+        );
+
+        let commented = "import java.util.List;\n// keep\nimport java.util.Map;\nclass A {}\n";
+        assert_eq!(organize(commented, &order()), None);
+    }
+
+    #[test]
+    fn test_parse_import() {
+        assert_eq!(
+            parse_import("import  static java.lang.Math.max ;"),
+            Some(Import {
+                is_static: true,
+                path: "java.lang.Math.max".to_string()
+            })
+        );
+        assert_eq!(
+            parse_import("import staticutil.Helper;").map(|i| i.path),
+            Some("staticutil.Helper".to_string())
+        );
+        assert_eq!(parse_import("imports.add(x);"), None);
+        assert_eq!(parse_import("import java.util.List; // why"), None);
+    }
+}
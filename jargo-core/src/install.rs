@@ -0,0 +1,275 @@
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Second line of every launcher `jargo install` writes, followed by the
+/// package and version it came from. Launchers without it are never touched.
+const MARKER: &str = "Installed by jargo install: ";
+
+/// Where `jargo install` puts launchers and JARs, `~/.jargo/bin`. Add it to
+/// `PATH` to run installed tools by name.
+pub fn bin_dir(jargo_home: &Path) -> PathBuf {
+    jargo_home.join("bin")
+}
+
+/// An entry point to write a launcher for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Launcher {
+    /// File name of the launcher, `.cmd` added on Windows.
+    pub name: String,
+    /// Fully-qualified main class.
+    pub main_class: String,
+}
+
+/// The package a tool came from and how to start it.
+#[derive(Debug, Clone)]
+pub struct Tool<'a> {
+    pub package: &'a str,
+    pub version: &'a str,
+    /// `java` of the JDK the tool runs on, or the bare name for `PATH`.
+    pub java: PathBuf,
+    pub jvm_args: Vec<String>,
+    pub launchers: Vec<Launcher>,
+}
+
+/// Copy the self-contained `jar` to `{bin_dir}/{package}.jar` and write a
+/// launcher per entry point next to it, replacing the launchers of an earlier
+/// install of the package. Returns the launchers written.
+///
+/// Fails without changing anything when a launcher would replace a file that
+/// another package, or not `jargo install`, put there.
+pub fn install(bin_dir: &Path, tool: &Tool, jar: &Path) -> Result<Vec<PathBuf>> {
+    for launcher in &tool.launchers {
+        let path = launcher_path(bin_dir, &launcher.name);
+        if !path.exists() {
+            continue;
+        }
+        match owner(&path) {
+            Some(owner) if owner == tool.package => {}
+            Some(owner) => bail!(
+                "`{}` is already installed from `{}`; run `jargo uninstall {}` first",
+                launcher.name,
+                owner,
+                owner
+            ),
+            None => bail!(
+                "{} already exists and was not installed by jargo",
+                path.display()
+            ),
+        }
+    }
+
+    fs::create_dir_all(bin_dir)
+        .with_context(|| format!("failed to create {}", bin_dir.display()))?;
+    remove_launchers(bin_dir, tool.package)?;
+    let installed_jar = bin_dir.join(format!("{}.jar", tool.package));
+    fs::copy(jar, &installed_jar).with_context(|| {
+        format!(
+            "failed to copy {} to {}",
+            jar.display(),
+            installed_jar.display()
+        )
+    })?;
+
+    let mut written = Vec::new();
+    for launcher in &tool.launchers {
+        let path = launcher_path(bin_dir, &launcher.name);
+        fs::write(&path, render_launcher(tool, launcher, &installed_jar))
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        make_executable(&path)?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Remove the launchers and JAR `jargo install` put in `bin_dir` for
+/// `package`, returning the paths removed (none when it is not installed).
+pub fn uninstall(bin_dir: &Path, package: &str) -> Result<Vec<PathBuf>> {
+    let mut removed = remove_launchers(bin_dir, package)?;
+    let jar = bin_dir.join(format!("{}.jar", package));
+    if jar.exists() {
+        fs::remove_file(&jar).with_context(|| format!("failed to remove {}", jar.display()))?;
+        removed.push(jar);
+    }
+    Ok(removed)
+}
+
+/// `group:artifact:version` split into its parts.
+pub fn parse_coordinate(coordinate: &str) -> Result<(&str, &str, &str)> {
+    match coordinate.split(':').collect::<Vec<_>>().as_slice() {
+        [group, artifact, version]
+            if !group.is_empty() && !artifact.is_empty() && !version.is_empty() =>
+        {
+            Ok((group, artifact, version))
+        }
+        _ => bail!(
+            "invalid coordinate `{}`: expected GROUP:ARTIFACT:VERSION",
+            coordinate
+        ),
+    }
+}
+
+fn remove_launchers(bin_dir: &Path, package: &str) -> Result<Vec<PathBuf>> {
+    let mut removed = Vec::new();
+    let Ok(entries) = fs::read_dir(bin_dir) else {
+        return Ok(removed);
+    };
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_file() && owner(&path).as_deref() == Some(package) {
+            fs::remove_file(&path)
+                .with_context(|| format!("failed to remove {}", path.display()))?;
+            removed.push(path);
+        }
+    }
+    removed.sort();
+    Ok(removed)
+}
+
+fn launcher_path(bin_dir: &Path, name: &str) -> PathBuf {
+    if cfg!(windows) {
+        bin_dir.join(format!("{}.cmd", name))
+    } else {
+        bin_dir.join(name)
+    }
+}
+
+/// The package a launcher was installed from, read from its marker line.
+fn owner(path: &Path) -> Option<String> {
+    let content = fs::read(path).ok()?;
+    let content = String::from_utf8_lossy(&content);
+    let line = content.lines().nth(1)?;
+    let (_, rest) = line.split_once(MARKER)?;
+    rest.split_whitespace().next().map(str::to_string)
+}
+
+fn render_launcher(tool: &Tool, launcher: &Launcher, jar: &Path) -> String {
+    let marker = format!("{}{} {}", MARKER, tool.package, tool.version);
+    let java = tool.java.to_string_lossy();
+    let jar = jar.to_string_lossy();
+    if cfg!(windows) {
+        let mut line = format!("\"{}\"", cmd_escape(&java));
+        for arg in &tool.jvm_args {
+            line.push_str(&format!(" \"{}\"", cmd_escape(arg)));
+        }
+        format!(
+            "@echo off\r\nrem {}\r\n{} -cp \"{}\" {} %*\r\n",
+            marker,
+            line,
+            cmd_escape(&jar),
+            launcher.main_class
+        )
+    } else {
+        let mut line = format!("exec {}", sh_quote(&java));
+        for arg in &tool.jvm_args {
+            line.push_str(&format!(" {}", sh_quote(arg)));
+        }
+        format!(
+            "#!/bin/sh\n# {}\n{} -cp {} {} \"$@\"\n",
+            marker,
+            line,
+            sh_quote(&jar),
+            launcher.main_class
+        )
+    }
+}
+
+fn sh_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+fn cmd_escape(value: &str) -> String {
+    value.replace('%', "%%").replace('"', "\"\"")
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+        .with_context(|| format!("failed to make {} executable", path.display()))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool(package: &'static str, launchers: &[&str]) -> Tool<'static> {
+        Tool {
+            package,
+            version: "1.0.0",
+            java: PathBuf::from("/opt/jdk/bin/java"),
+            jvm_args: vec!["-Xmx1g".to_string(), "-Dname=it's".to_string()],
+            launchers: launchers
+                .iter()
+                .map(|name| Launcher {
+                    name: name.to_string(),
+                    main_class: "com.example.Main".to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_install_and_uninstall() {
+        let tmp = tempfile::tempdir().unwrap();
+        let jar = tmp.path().join("demo.jar");
+        fs::write(&jar, b"PK").unwrap();
+        let bin = tmp.path().join("bin");
+
+        let written = install(&bin, &tool("demo", &["demo", "demo-admin"]), &jar).unwrap();
+        assert_eq!(
+            written,
+            vec![
+                launcher_path(&bin, "demo"),
+                launcher_path(&bin, "demo-admin")
+            ]
+        );
+        assert_eq!(fs::read(bin.join("demo.jar")).unwrap(), b"PK");
+        assert_eq!(owner(&launcher_path(&bin, "demo")).as_deref(), Some("demo"));
+
+        // Reinstalling drops launchers of entry points that are gone.
+        install(&bin, &tool("demo", &["demo"]), &jar).unwrap();
+        assert!(!launcher_path(&bin, "demo-admin").exists());
+
+        let err = install(&bin, &tool("other", &["demo"]), &jar).unwrap_err();
+        assert!(err.to_string().contains("already installed from `demo`"));
+        fs::write(launcher_path(&bin, "mine"), "#!/bin/sh\necho mine\n").unwrap();
+        let err = install(&bin, &tool("demo", &["mine"]), &jar).unwrap_err();
+        assert!(err.to_string().contains("was not installed by jargo"));
+
+        let removed = uninstall(&bin, "demo").unwrap();
+        assert_eq!(
+            removed,
+            vec![launcher_path(&bin, "demo"), bin.join("demo.jar")]
+        );
+        assert!(launcher_path(&bin, "mine").exists());
+        assert!(uninstall(&bin, "demo").unwrap().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_render_launcher() {
+        let tool = tool("demo", &["demo"]);
+        assert_eq!(
+            render_launcher(&tool, &tool.launchers[0], Path::new("/home/me/.jargo/bin/demo.jar")),
+            "#!/bin/sh\n\
+             # Installed by jargo install: demo 1.0.0\n\
+             exec '/opt/jdk/bin/java' '-Xmx1g' '-Dname=it'\\''s' -cp '/home/me/.jargo/bin/demo.jar' com.example.Main \"$@\"\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_coordinate() {
+        assert_eq!(
+            parse_coordinate("com.example:tool:1.2.0").unwrap(),
+            ("com.example", "tool", "1.2.0")
+        );
+        assert!(parse_coordinate("com.example:tool").is_err());
+        assert!(parse_coordinate("com.example::1.0").is_err());
+    }
+}
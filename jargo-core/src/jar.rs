@@ -1,12 +1,20 @@
 use anyhow::{Context, Result};
+use std::collections::{BTreeMap, HashSet};
 use std::fs::{self, File};
-use std::io::Write;
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 use zip::write::SimpleFileOptions;
 use zip::ZipWriter;
 
+use crate::cache;
+use crate::cleanup::InProgress;
 use crate::context::GlobalContext;
 use crate::manifest::JargoToml;
+use crate::minimize;
+use crate::notices::{self, Notice};
+use crate::shade::Relocator;
+
+pub(crate) const SERVICES_DIR: &str = "META-INF/services/";
 
 /// Assemble JAR file from compiled classes and resources.
 pub fn assemble_jar(
@@ -14,51 +22,395 @@ pub fn assemble_jar(
     project_root: &Path,
     manifest: &JargoToml,
 ) -> Result<PathBuf> {
+    write_jar(project_root, manifest, &[])
+}
+
+/// Assemble a JAR that finds its runtime dependencies next to it: every
+/// runtime dependency JAR is copied into `target/lib/` (replacing what was
+/// there) and listed in the `Class-Path:` manifest header relative to the JAR,
+/// so `java -jar target/{name}.jar` runs without `-cp`.
+pub fn assemble_thin_jar(
+    _gctx: &GlobalContext,
+    project_root: &Path,
+    manifest: &JargoToml,
+    runtime_jars: &[PathBuf],
+) -> Result<PathBuf> {
+    let lib_dir = project_root.join("target/lib");
+    if lib_dir.exists() {
+        fs::remove_dir_all(&lib_dir)
+            .with_context(|| format!("failed to remove {}", lib_dir.display()))?;
+    }
+    fs::create_dir_all(&lib_dir)
+        .with_context(|| format!("failed to create {}", lib_dir.display()))?;
+
+    let mut class_path = Vec::new();
+    for jar in runtime_jars {
+        let name = jar
+            .file_name()
+            .with_context(|| format!("invalid JAR path {}", jar.display()))?
+            .to_string_lossy()
+            .into_owned();
+        fs::copy(jar, lib_dir.join(&name)).with_context(|| {
+            format!(
+                "failed to copy {} into {}",
+                jar.display(),
+                lib_dir.display()
+            )
+        })?;
+        // Class-Path entries are relative URLs
+        class_path.push(format!(
+            "lib/{}",
+            name.replace('%', "%25").replace(' ', "%20")
+        ));
+    }
+
+    write_jar(project_root, manifest, &class_path)
+}
+
+/// Assemble `target/{name}-{classifier}.jar` from the files under `dir`,
+/// e.g. the `sources` JAR from `target/src-root/` or the `javadoc` JAR from
+/// the generated HTML.
+pub fn assemble_classifier_jar(
+    project_root: &Path,
+    manifest: &JargoToml,
+    classifier: &str,
+    dir: &Path,
+) -> Result<PathBuf> {
+    let jar_name = format!("{}-{}.jar", manifest.package.name, classifier);
+    let jar_path = project_root.join("target").join(&jar_name);
+    let options = entry_options(manifest)?;
+
+    write_atomically(&jar_path, |file| {
+        let mut zip = ZipWriter::new(file);
+        // Not runnable, so none of the main JAR's headers
+        write_manifest_file(&mut zip, "Manifest-Version: 1.0\n", options)?;
+        add_directory_to_zip(&mut zip, dir, dir, options)?;
+        zip.finish()
+            .with_context(|| "failed to finish writing JAR file")
+    })?;
+
+    Ok(jar_path)
+}
+
+fn write_jar(project_root: &Path, manifest: &JargoToml, class_path: &[String]) -> Result<PathBuf> {
     let jar_name = format!("{}.jar", manifest.package.name);
     let jar_path = project_root.join("target").join(&jar_name);
+    let options = entry_options(manifest)?;
+
+    write_atomically(&jar_path, |file| {
+        let mut zip = ZipWriter::new(file);
 
-    let file = File::create(&jar_path)
-        .with_context(|| format!("failed to create JAR file at {}", jar_path.display()))?;
-    let mut zip = ZipWriter::new(file);
-    let options = SimpleFileOptions::default()
-        .compression_method(zip::CompressionMethod::Deflated)
-        .unix_permissions(0o644);
+        // 1. Write MANIFEST.MF
+        write_manifest(&mut zip, manifest, class_path, options)?;
+
+        // 2. Add all .class files from target/classes/
+        let classes_dir = project_root.join("target/classes");
+        if classes_dir.exists() {
+            add_directory_to_zip(&mut zip, &classes_dir, &classes_dir, options)?;
+        }
 
-    // 1. Write MANIFEST.MF
-    write_manifest(&mut zip, manifest, options)?;
+        zip.finish()
+            .with_context(|| "failed to finish writing JAR file")
+    })?;
+
+    Ok(jar_path)
+}
 
-    // 2. Add all .class files from target/classes/
+/// Write a JAR through `{path}.tmp`, renamed over `path` only once `write`
+/// returns the finished file and it is synced, so an interrupted or failed
+/// build never leaves a truncated JAR behind.
+fn write_atomically(path: &Path, write: impl FnOnce(File) -> Result<File>) -> Result<()> {
+    let tmp_path = cache::tmp_path(path);
+    let _in_progress = InProgress::new(&tmp_path);
+
+    File::create(&tmp_path)
+        .with_context(|| format!("failed to create JAR file at {}", tmp_path.display()))
+        .and_then(write)
+        .and_then(|file| {
+            file.sync_all()
+                .with_context(|| format!("failed to flush {}", tmp_path.display()))
+        })
+        .and_then(|()| {
+            fs::rename(&tmp_path, path).with_context(|| {
+                format!(
+                    "failed to move {} to {}",
+                    tmp_path.display(),
+                    path.display()
+                )
+            })
+        })
+}
+
+/// Options for every JAR entry. `[jar] compression-level` 0 stores entries
+/// uncompressed; 1-9 deflate them at that level (default: 6).
+fn entry_options(manifest: &JargoToml) -> Result<SimpleFileOptions> {
+    let options = SimpleFileOptions::default().unix_permissions(0o644);
+    Ok(match manifest.get_jar_compression_level()? {
+        0 => options.compression_method(zip::CompressionMethod::Stored),
+        level => options
+            .compression_method(zip::CompressionMethod::Deflated)
+            .compression_level(Some(level.into())),
+    })
+}
+
+/// Assemble a self-contained JAR: compiled classes plus the contents of every
+/// runtime dependency JAR, with `[shade.relocate]` rules applied throughout.
+///
+/// The project's entries win over dependencies' and earlier dependencies win
+/// over later ones; `META-INF/services/` files are merged instead. Dependency
+/// manifests, signatures, and `module-info.class` files are dropped, since
+/// they describe the original JARs. With `[shade] minimize`, so are dependency
+/// classes the project cannot reach.
+///
+/// `notices` become `META-INF/THIRD-PARTY.txt`, with the dependencies' license
+/// files under `META-INF/licenses/`.
+pub fn assemble_uber_jar(
+    gctx: &GlobalContext,
+    project_root: &Path,
+    manifest: &JargoToml,
+    runtime_jars: &[PathBuf],
+    notices: &[Notice],
+) -> Result<PathBuf> {
+    let relocator = Relocator::new(&manifest.get_relocations())?;
+    let jar_name = format!("{}.jar", manifest.package.name);
+    let jar_path = project_root.join("target").join(&jar_name);
+    let options = entry_options(manifest)?;
     let classes_dir = project_root.join("target/classes");
-    if classes_dir.exists() {
-        add_directory_to_zip(&mut zip, &classes_dir, &classes_dir, options)?;
-    }
+    let reachable = match manifest.shade.as_ref().filter(|shade| shade.minimize) {
+        Some(shade) => Some(minimize::reachable_classes(
+            &classes_dir,
+            runtime_jars,
+            &shade.keep,
+        )?),
+        None => None,
+    };
+    let mut dropped = 0;
 
-    zip.finish()
-        .with_context(|| "failed to finish writing JAR file")?;
+    write_atomically(&jar_path, |file| {
+        let mut uber = UberJar {
+            zip: ZipWriter::new(file),
+            options,
+            relocator,
+            written: HashSet::from(["META-INF/".to_string(), "META-INF/MANIFEST.MF".to_string()]),
+            services: BTreeMap::new(),
+        };
+        write_manifest(&mut uber.zip, manifest, &[], uber.options)?;
+
+        if !notices.is_empty() {
+            let text = notices::render(manifest, notices, "META-INF/licenses");
+            let name = format!("META-INF/{}", notices::THIRD_PARTY_FILE);
+            uber.add(gctx, &name, text.as_bytes(), "jargo")?;
+            for notice in notices {
+                for (file, bytes) in &notice.files {
+                    let name = format!("META-INF/licenses/{}/{}", notice.dir_name(), file);
+                    uber.add(gctx, &name, bytes.as_slice(), "jargo")?;
+                }
+            }
+        }
+
+        if classes_dir.exists() {
+            for path in files_under(&classes_dir)? {
+                let name = path
+                    .strip_prefix(&classes_dir)
+                    .with_context(|| "failed to compute relative path")?
+                    .to_string_lossy()
+                    .replace('\\', "/");
+                let file = File::open(&path)
+                    .with_context(|| format!("failed to open {}", path.display()))?;
+                uber.add(gctx, &name, file, &manifest.package.name)?;
+            }
+        }
+
+        for jar in runtime_jars {
+            let origin = jar
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let file =
+                File::open(jar).with_context(|| format!("failed to open {}", jar.display()))?;
+            let mut archive = zip::ZipArchive::new(file)
+                .with_context(|| format!("failed to read {}", jar.display()))?;
+            for i in 0..archive.len() {
+                let entry = archive.by_index(i)?;
+                let name = entry.name().to_string();
+                if entry.is_dir() || is_dropped_from_uber_jar(&name) {
+                    continue;
+                }
+                if let (Some(reachable), Some(class)) = (&reachable, minimize::class_name(&name)) {
+                    if !reachable.contains(class) {
+                        dropped += 1;
+                        continue;
+                    }
+                }
+                uber.add(gctx, &name, entry, &origin)?;
+            }
+        }
+
+        for (name, lines) in &uber.services {
+            uber.zip
+                .start_file(name, uber.options)
+                .with_context(|| format!("failed to start file {} in JAR", name))?;
+            uber.zip
+                .write_all(format!("{}\n", lines.join("\n")).as_bytes())
+                .with_context(|| format!("failed to write file {} to JAR", name))?;
+        }
+
+        uber.zip
+            .finish()
+            .with_context(|| "failed to finish writing JAR file")
+    })?;
+
+    if reachable.is_some() {
+        gctx.shell.status(
+            "Minimized",
+            &format!("{} unused dependency classes dropped", dropped),
+        );
+    }
 
     Ok(jar_path)
 }
 
+struct UberJar {
+    zip: ZipWriter<File>,
+    options: SimpleFileOptions,
+    relocator: Relocator,
+    /// Entry names already in the JAR.
+    written: HashSet<String>,
+    /// Merged `META-INF/services/` files: relocated name to provider lines.
+    services: BTreeMap<String, Vec<String>>,
+}
+
+impl UberJar {
+    /// Add one entry. Classes and service files are read whole to be
+    /// rewritten; everything else is streamed into the JAR.
+    fn add(
+        &mut self,
+        gctx: &GlobalContext,
+        name: &str,
+        mut contents: impl Read,
+        origin: &str,
+    ) -> Result<()> {
+        let read_all = |contents: &mut dyn Read| -> Result<Vec<u8>> {
+            let mut bytes = Vec::new();
+            contents
+                .read_to_end(&mut bytes)
+                .with_context(|| format!("failed to read {} from {}", name, origin))?;
+            Ok(bytes)
+        };
+
+        if let Some(service) = name.strip_prefix(SERVICES_DIR) {
+            let bytes = read_all(&mut contents)?;
+            let name = format!(
+                "{}{}",
+                SERVICES_DIR,
+                self.relocator.relocate_class_name(service)
+            );
+            let lines = self.services.entry(name).or_default();
+            for line in String::from_utf8_lossy(&bytes).lines() {
+                let provider = line.split('#').next().unwrap_or("").trim();
+                let provider = self.relocator.relocate_class_name(provider);
+                if !provider.is_empty() && !lines.contains(&provider) {
+                    lines.push(provider);
+                }
+            }
+            return Ok(());
+        }
+
+        let name = self.relocator.relocate_path(name);
+        if !self.written.insert(name.clone()) {
+            gctx.shell.verbose(|sh| {
+                sh.print(format!(
+                    "  [verbose] skipping duplicate {} from {}",
+                    name, origin
+                ))
+            });
+            return Ok(());
+        }
+        self.zip
+            .start_file(&name, self.options)
+            .with_context(|| format!("failed to start file {} in JAR", name))?;
+        if name.ends_with(".class") {
+            let bytes = self
+                .relocator
+                .relocate_class(&read_all(&mut contents)?)
+                .with_context(|| format!("failed to relocate {} from {}", name, origin))?;
+            self.zip
+                .write_all(&bytes)
+                .with_context(|| format!("failed to write file {} to JAR", name))?;
+        } else {
+            std::io::copy(&mut contents, &mut self.zip)
+                .with_context(|| format!("failed to copy {} from {} into JAR", name, origin))?;
+        }
+        Ok(())
+    }
+}
+
+/// Dependency entries that describe or sign the original JAR.
+fn is_dropped_from_uber_jar(name: &str) -> bool {
+    let upper = name.to_ascii_uppercase();
+    name == "META-INF/MANIFEST.MF"
+        || name.ends_with("module-info.class")
+        || (upper.starts_with("META-INF/")
+            && !upper[9..].contains('/')
+            && [".SF", ".DSA", ".RSA", ".EC"]
+                .iter()
+                .any(|ext| upper.ends_with(ext)))
+}
+
+/// Files under `dir`, recursively, in a stable order.
+pub(crate) fn files_under(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut entries = fs::read_dir(dir)
+        .with_context(|| format!("failed to read directory {}", dir.display()))?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort();
+    let mut files = Vec::new();
+    for path in entries {
+        if path.is_dir() {
+            files.extend(files_under(&path)?);
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
 fn write_manifest(
     zip: &mut ZipWriter<File>,
     manifest: &JargoToml,
+    class_path: &[String],
     options: SimpleFileOptions,
 ) -> Result<()> {
-    zip.add_directory("META-INF/", options)
-        .with_context(|| "failed to add META-INF directory")?;
-    zip.start_file("META-INF/MANIFEST.MF", options)
-        .with_context(|| "failed to start MANIFEST.MF file")?;
-
     let mut content = String::from("Manifest-Version: 1.0\n");
 
-    // For app projects, add Main-Class entry
+    // For app projects with a single entry point, add Main-Class entry
     if manifest.is_app() {
-        let base_package = manifest.get_base_package();
-        let main_class = manifest.get_main_class();
-        let main_class_fqn = format!("{}.{}", base_package, main_class);
-        content.push_str(&format!("Main-Class: {}\n", main_class_fqn));
+        if let [bin] = manifest.get_bins()?.as_slice() {
+            content.push_str(&manifest_header("Main-Class", &manifest.main_class_of(bin)));
+        }
     }
 
+    if !class_path.is_empty() {
+        content.push_str(&manifest_header("Class-Path", &class_path.join(" ")));
+    }
+
+    // User attributes from [jar.manifest]
+    for (name, value) in manifest.get_manifest_attributes()? {
+        content.push_str(&manifest_header(name, value));
+    }
+
+    write_manifest_file(zip, &content, options)
+}
+
+fn write_manifest_file(
+    zip: &mut ZipWriter<File>,
+    content: &str,
+    options: SimpleFileOptions,
+) -> Result<()> {
+    zip.add_directory("META-INF/", options)
+        .with_context(|| "failed to add META-INF directory")?;
+    zip.start_file("META-INF/MANIFEST.MF", options)
+        .with_context(|| "failed to start MANIFEST.MF file")?;
     zip.write_all(content.as_bytes())
         .with_context(|| "failed to write MANIFEST.MF content")?;
     Ok(())
@@ -87,11 +439,146 @@ fn add_directory_to_zip(
             let zip_path = relative_path.to_string_lossy().replace('\\', "/");
             zip.start_file(&zip_path, options)
                 .with_context(|| format!("failed to start file {} in JAR", zip_path))?;
-            let file_contents = fs::read(&path)
-                .with_context(|| format!("failed to read file {}", path.display()))?;
-            zip.write_all(&file_contents)
+            let mut file = File::open(&path)
+                .with_context(|| format!("failed to open file {}", path.display()))?;
+            std::io::copy(&mut file, zip)
                 .with_context(|| format!("failed to write file {} to JAR", zip_path))?;
         }
     }
     Ok(())
 }
+
+/// A `Name: value` manifest line, wrapped into continuation lines (starting
+/// with a space) so no line exceeds the 72 bytes the JAR spec allows.
+fn manifest_header(name: &str, value: &str) -> String {
+    let header = format!("{}: {}", name, value);
+    let mut out = String::new();
+    // Continuation lines spend one byte on the leading space
+    let mut limit = 72;
+    let mut line_len = 0;
+    for c in header.chars() {
+        if line_len + c.len_utf8() > limit {
+            out.push_str("\n ");
+            limit = 71;
+            line_len = 0;
+        }
+        out.push(c);
+        line_len += c.len_utf8();
+    }
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_header_wraps_at_72_bytes() {
+        let value = (1..=8)
+            .map(|i| format!("lib/dependency-number-{}.jar", i))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let header = manifest_header("Class-Path", &value);
+
+        let lines: Vec<&str> = header.lines().collect();
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|line| line.len() <= 72));
+        assert!(lines[1..].iter().all(|line| line.starts_with(' ')));
+        let unwrapped: String = lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| if i == 0 { *line } else { &line[1..] })
+            .collect();
+        assert_eq!(unwrapped, format!("Class-Path: {}", value));
+    }
+
+    #[test]
+    fn test_failed_write_keeps_previous_jar() {
+        let tmp = tempfile::tempdir().unwrap();
+        let jar = tmp.path().join("demo.jar");
+        fs::write(&jar, "previous").unwrap();
+
+        let result = write_atomically(&jar, |mut file| {
+            file.write_all(b"partial").unwrap();
+            anyhow::bail!("compile failed")
+        });
+        assert!(result.is_err());
+        assert_eq!(fs::read_to_string(&jar).unwrap(), "previous");
+        assert!(!tmp.path().join("demo.jar.tmp").exists());
+
+        write_atomically(&jar, |mut file| {
+            file.write_all(b"new").unwrap();
+            Ok(file)
+        })
+        .unwrap();
+        assert_eq!(fs::read_to_string(&jar).unwrap(), "new");
+    }
+
+    #[test]
+    fn test_compression_level() {
+        let project = |extra: &str| -> JargoToml {
+            toml::from_str(&format!(
+                "[package]\nname = \"demo\"\nversion = \"1.0.0\"\njava = \"21\"\n{}",
+                extra
+            ))
+            .unwrap()
+        };
+        let tmp = tempfile::tempdir().unwrap();
+        fs::create_dir_all(tmp.path().join("target/classes/demo")).unwrap();
+        fs::write(
+            tmp.path().join("target/classes/demo/Main.class"),
+            [0u8; 512],
+        )
+        .unwrap();
+
+        for (extra, method) in [
+            ("", zip::CompressionMethod::Deflated),
+            (
+                "[jar]\ncompression-level = 0",
+                zip::CompressionMethod::Stored,
+            ),
+        ] {
+            let jar = write_jar(tmp.path(), &project(extra), &[]).unwrap();
+            let mut archive = zip::ZipArchive::new(File::open(jar).unwrap()).unwrap();
+            let entry = archive.by_name("demo/Main.class").unwrap();
+            assert_eq!(entry.compression(), method);
+            assert_eq!(entry.size(), 512);
+        }
+
+        let bad = project("[jar]\ncompression-level = 10");
+        assert!(write_jar(tmp.path(), &bad, &[]).is_err());
+    }
+
+    #[test]
+    fn test_classifier_jar() {
+        // An app with manifest attributes, none of which belong in the sources
+        let manifest: JargoToml = toml::from_str(
+            "[package]\nname = \"demo\"\nversion = \"1.0.0\"\njava = \"21\"\n\
+             [jar.manifest]\nAutomatic-Module-Name = \"demo\"",
+        )
+        .unwrap();
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("target/src-root");
+        fs::create_dir_all(dir.join("demo")).unwrap();
+        fs::write(dir.join("demo/Main.java"), "class Main {}").unwrap();
+
+        let jar = assemble_classifier_jar(tmp.path(), &manifest, "sources", &dir).unwrap();
+        assert_eq!(jar, tmp.path().join("target/demo-sources.jar"));
+        let mut archive = zip::ZipArchive::new(File::open(jar).unwrap()).unwrap();
+        let mut mf = String::new();
+        archive
+            .by_name("META-INF/MANIFEST.MF")
+            .unwrap()
+            .read_to_string(&mut mf)
+            .unwrap();
+        assert_eq!(mf, "Manifest-Version: 1.0\n");
+        let mut source = String::new();
+        archive
+            .by_name("demo/Main.java")
+            .unwrap()
+            .read_to_string(&mut source)
+            .unwrap();
+        assert_eq!(source, "class Main {}");
+    }
+}
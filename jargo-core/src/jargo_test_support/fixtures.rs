@@ -0,0 +1,206 @@
+//! Canned repository content: POMs, Gradle `.module` files, and JARs with
+//! real (empty) class files.
+
+use std::io::{Cursor, Write};
+
+use crate::bytecode;
+
+/// A dependency listed in a fixture POM or `.module`.
+#[derive(Debug, Clone)]
+pub struct FixtureDep {
+    pub group: String,
+    pub artifact: String,
+    pub version: String,
+    /// Maven scope (`compile`, `runtime`, `test`, `provided`).
+    pub scope: String,
+}
+
+impl FixtureDep {
+    /// Parse `"group:artifact:version"` with the given Maven scope.
+    ///
+    /// Panics on a malformed coordinate.
+    pub fn new(coordinate: &str, scope: &str) -> Self {
+        let parts: Vec<&str> = coordinate.split(':').collect();
+        let [group, artifact, version] = parts[..] else {
+            panic!("expected group:artifact:version, got `{}`", coordinate);
+        };
+        Self {
+            group: group.to_string(),
+            artifact: artifact.to_string(),
+            version: version.to_string(),
+            scope: scope.to_string(),
+        }
+    }
+
+    /// Shorthand for a `compile`-scope dependency.
+    pub fn compile(coordinate: &str) -> Self {
+        Self::new(coordinate, "compile")
+    }
+
+    /// Shorthand for a `runtime`-scope dependency.
+    pub fn runtime(coordinate: &str) -> Self {
+        Self::new(coordinate, "runtime")
+    }
+}
+
+/// A minimal POM declaring `deps`.
+pub fn pom(group: &str, artifact: &str, version: &str, deps: &[FixtureDep]) -> String {
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <project xmlns=\"http://maven.apache.org/POM/4.0.0\">\n\
+         \x20 <modelVersion>4.0.0</modelVersion>\n\
+         \x20 <groupId>{}</groupId>\n\
+         \x20 <artifactId>{}</artifactId>\n\
+         \x20 <version>{}</version>\n",
+        group, artifact, version
+    );
+    if !deps.is_empty() {
+        xml.push_str("  <dependencies>\n");
+        for dep in deps {
+            xml.push_str(&format!(
+                "    <dependency>\n\
+                 \x20     <groupId>{}</groupId>\n\
+                 \x20     <artifactId>{}</artifactId>\n\
+                 \x20     <version>{}</version>\n\
+                 \x20     <scope>{}</scope>\n\
+                 \x20   </dependency>\n",
+                dep.group, dep.artifact, dep.version, dep.scope
+            ));
+        }
+        xml.push_str("  </dependencies>\n");
+    }
+    xml.push_str("</project>\n");
+    xml
+}
+
+/// A minimal Gradle `.module` file. `compile` deps go in `apiElements` and
+/// `runtimeElements`; `runtime` deps only in `runtimeElements`; other scopes
+/// are left out, as Gradle publishes them.
+pub fn gradle_module(group: &str, artifact: &str, version: &str, deps: &[FixtureDep]) -> String {
+    let entries = |scopes: &[&str]| -> Vec<serde_json::Value> {
+        deps.iter()
+            .filter(|d| scopes.contains(&d.scope.as_str()))
+            .map(|d| {
+                serde_json::json!({
+                    "group": d.group,
+                    "module": d.artifact,
+                    "version": { "requires": d.version },
+                })
+            })
+            .collect()
+    };
+    let module = serde_json::json!({
+        "formatVersion": "1.1",
+        "component": { "group": group, "module": artifact, "version": version },
+        "variants": [
+            { "name": "apiElements", "dependencies": entries(&["compile"]) },
+            { "name": "runtimeElements", "dependencies": entries(&["compile", "runtime"]) },
+        ],
+    });
+    serde_json::to_string_pretty(&module).expect("fixture JSON serializes")
+}
+
+/// Bytes of a valid, empty `public class {name}` compiled for `release`.
+///
+/// `name` is the binary name with slashes (`com/acme/Widget`). The class has
+/// no members, but `javac` can resolve it as a type and the JVM can load it.
+pub fn class_file(name: &str, release: u32) -> Vec<u8> {
+    fn utf8(out: &mut Vec<u8>, s: &str) {
+        out.push(1);
+        out.extend_from_slice(&(s.len() as u16).to_be_bytes());
+        out.extend_from_slice(s.as_bytes());
+    }
+
+    let mut out = vec![0xCA, 0xFE, 0xBA, 0xBE, 0, 0];
+    out.extend_from_slice(&bytecode::major_of(release).to_be_bytes());
+    // Constant pool: #1 name, #2 Class(#1), #3 java/lang/Object, #4 Class(#3)
+    out.extend_from_slice(&5u16.to_be_bytes());
+    utf8(&mut out, name);
+    out.extend_from_slice(&[7, 0, 1]);
+    utf8(&mut out, "java/lang/Object");
+    out.extend_from_slice(&[7, 0, 3]);
+    // ACC_PUBLIC | ACC_SUPER, this = #2, super = #4
+    out.extend_from_slice(&[0x00, 0x21, 0, 2, 0, 4]);
+    // No interfaces, fields, methods, or attributes
+    out.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 0]);
+    out
+}
+
+/// A JAR holding a `MANIFEST.MF` and an empty public class for each binary
+/// name in `classes` (`com/acme/Widget`), compiled for `release`.
+pub fn jar(classes: &[&str], release: u32) -> Vec<u8> {
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default();
+    zip.start_file("META-INF/MANIFEST.MF", options)
+        .expect("write fixture JAR");
+    zip.write_all(b"Manifest-Version: 1.0\n")
+        .expect("write fixture JAR");
+    for class in classes {
+        zip.start_file(format!("{}.class", class), options)
+            .expect("write fixture JAR");
+        zip.write_all(&class_file(class, release))
+            .expect("write fixture JAR");
+    }
+    zip.finish().expect("write fixture JAR").into_inner()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pom::parse_pom;
+
+    #[test]
+    fn test_pom_round_trips_through_parser() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("a.pom");
+        std::fs::write(
+            &path,
+            pom(
+                "com.acme",
+                "widget",
+                "1.0",
+                &[FixtureDep::compile("com.acme:core:2.0")],
+            ),
+        )
+        .unwrap();
+
+        let deps = parse_pom(&path).unwrap();
+        assert_eq!(deps.len(), 1);
+        assert_eq!(deps[0].artifact, "core");
+        assert_eq!(deps[0].version, "2.0");
+    }
+
+    #[test]
+    fn test_gradle_module_round_trips_through_parser() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("a.module");
+        std::fs::write(
+            &path,
+            gradle_module(
+                "com.acme",
+                "widget",
+                "1.0",
+                &[
+                    FixtureDep::compile("com.acme:core:2.0"),
+                    FixtureDep::runtime("com.acme:impl:2.0"),
+                ],
+            ),
+        )
+        .unwrap();
+
+        let deps = crate::gradle_module::parse_module(&path).unwrap();
+        assert_eq!(deps.len(), 2);
+    }
+
+    #[test]
+    fn test_jar_classes_have_release() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("a.jar");
+        std::fs::write(&path, jar(&["com/acme/Widget"], 21)).unwrap();
+
+        assert_eq!(
+            bytecode::classes_newer_than(&path, 17).unwrap(),
+            vec![("com/acme/Widget.class".to_string(), 65)]
+        );
+    }
+}
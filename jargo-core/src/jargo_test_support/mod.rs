@@ -0,0 +1,36 @@
+//! Helpers for integration-testing Jargo and tools built on `jargo-core`.
+//!
+//! Enabled by the `test-support` feature; add it to `[dev-dependencies]`:
+//!
+//! ```toml
+//! jargo-core = { path = "../jargo-core", features = ["test-support"] }
+//! ```
+//!
+//! A typical test serves a dependency from a [`FakeRepo`], scaffolds a
+//! project that uses it, and drives the `jargo` binary:
+//!
+//! ```no_run
+//! use jargo_core::jargo_test_support::{FakeRepo, ProjectBuilder};
+//!
+//! # fn check(jargo_bin: &str) {
+//! let repo = FakeRepo::start();
+//! repo.publish_pom("com.acme:widget:1.0", &[], &["com/acme/Widget"], 17);
+//!
+//! let project = ProjectBuilder::app("demo")
+//!     .dependency("com.acme:widget", "1.0")
+//!     .repository(&repo)
+//!     .build();
+//! let output = project.jargo(jargo_bin).arg("build").output().unwrap();
+//! assert!(output.status.success());
+//! # }
+//! ```
+//!
+//! Helpers panic on setup failures, as test code expects.
+
+mod fixtures;
+mod project;
+mod repo;
+
+pub use fixtures::{class_file, gradle_module, jar, pom, FixtureDep};
+pub use project::{ProjectBuilder, TestProject, WorkspaceBuilder};
+pub use repo::FakeRepo;
@@ -0,0 +1,331 @@
+//! Throwaway Jargo projects in temp directories.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use tempfile::TempDir;
+
+use super::repo::FakeRepo;
+use crate::context::GlobalContext;
+use crate::manifest::{self, DependencySpec, DependencyValue, JargoToml};
+use crate::shell::{Shell, Verbosity};
+
+/// Builds a [`TestProject`] laid out like `jargo new` would.
+pub struct ProjectBuilder {
+    manifest: JargoToml,
+    extra_manifest: String,
+    files: Vec<(PathBuf, String)>,
+    repository: Option<String>,
+}
+
+impl ProjectBuilder {
+    /// An app with `src/Main.java` printing `Hello, World!`.
+    pub fn app(name: &str) -> Self {
+        let base_package = manifest::derive_base_package(name);
+        let main = format!(
+            "package {base_package};\n\n\
+             public class Main {{\n\
+             \x20   public static void main(String[] args) {{\n\
+             \x20       System.out.println(\"Hello, World!\");\n\
+             \x20   }}\n\
+             }}\n"
+        );
+        Self::new(JargoToml::new_app(name)).file("src/Main.java", main)
+    }
+
+    /// A lib with `src/Lib.java`.
+    pub fn lib(name: &str) -> Self {
+        let base_package = manifest::derive_base_package(name);
+        let lib = format!(
+            "package {base_package};\n\n\
+             public class Lib {{\n\
+             \x20   public static String greeting() {{\n\
+             \x20       return \"Hello from {name}!\";\n\
+             \x20   }}\n\
+             }}\n"
+        );
+        Self::new(JargoToml::new_lib(name, &base_package)).file("src/Lib.java", lib)
+    }
+
+    fn new(manifest: JargoToml) -> Self {
+        Self {
+            manifest,
+            extra_manifest: String::new(),
+            files: Vec::new(),
+            repository: None,
+        }
+    }
+
+    /// Set `package.java`.
+    pub fn java(mut self, release: &str) -> Self {
+        self.manifest.package.java = release.to_string();
+        self
+    }
+
+    /// Add a `[dependencies]` entry (`"group:artifact"`).
+    pub fn dependency(mut self, coordinate: &str, version: &str) -> Self {
+        self.manifest.dependencies.insert(
+            coordinate.to_string(),
+            DependencyValue::Simple(version.to_string()),
+        );
+        self
+    }
+
+    /// Add a `{ workspace = true }` dependency on the workspace member `name`.
+    pub fn member_dependency(mut self, name: &str) -> Self {
+        self.manifest.dependencies.insert(
+            name.to_string(),
+            DependencyValue::Expanded(DependencySpec {
+                version: None,
+                scope: None,
+                expose: None,
+                workspace: Some(true),
+                optional: None,
+            }),
+        );
+        self
+    }
+
+    /// Append raw TOML to `Jargo.toml`, for sections without a builder method.
+    pub fn manifest(mut self, toml: &str) -> Self {
+        self.extra_manifest.push('\n');
+        self.extra_manifest.push_str(toml);
+        self
+    }
+
+    /// Write a file, relative to the project root. Replaces an earlier file
+    /// at the same path, including the scaffolded sources.
+    pub fn file(mut self, path: &str, contents: impl Into<String>) -> Self {
+        let path = PathBuf::from(path);
+        self.files.retain(|(existing, _)| *existing != path);
+        self.files.push((path, contents.into()));
+        self
+    }
+
+    /// Resolve dependencies from `repo` instead of Maven Central.
+    pub fn repository(mut self, repo: &FakeRepo) -> Self {
+        self.repository = Some(repo.url());
+        self
+    }
+
+    /// Create the project on disk.
+    pub fn build(self) -> TestProject {
+        let temp = TempDir::new().expect("create project dir");
+        let root = temp.path().join(&self.manifest.package.name);
+        self.write_to(&root);
+        fs::write(root.join(".gitignore"), "target/\n").expect("write .gitignore");
+
+        TestProject {
+            home: temp.path().join("home"),
+            root,
+            repository: self.repository,
+            _temp: temp,
+        }
+    }
+
+    /// Write the manifest and files into `root`.
+    fn write_to(&self, root: &Path) {
+        fs::create_dir_all(root.join("src")).expect("create src/");
+        fs::create_dir_all(root.join("test")).expect("create test/");
+
+        let mut toml = self
+            .manifest
+            .to_toml_string()
+            .expect("serialize Jargo.toml");
+        toml.push_str(&self.extra_manifest);
+        fs::write(root.join("Jargo.toml"), toml).expect("write Jargo.toml");
+
+        for (path, contents) in &self.files {
+            let path = root.join(path);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).expect("create parent dirs");
+            }
+            fs::write(&path, contents)
+                .unwrap_or_else(|e| panic!("write {}: {}", path.display(), e));
+        }
+    }
+}
+
+/// Builds a workspace [`TestProject`]: a root Jargo.toml with a `[workspace]`
+/// table and each member in the directory named after its package.
+#[derive(Default)]
+pub struct WorkspaceBuilder {
+    members: Vec<ProjectBuilder>,
+    repository: Option<String>,
+}
+
+impl WorkspaceBuilder {
+    /// A workspace with no members.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a member, listed in `members` in the order added.
+    pub fn member(mut self, member: ProjectBuilder) -> Self {
+        self.members.push(member);
+        self
+    }
+
+    /// Resolve dependencies from `repo` instead of Maven Central.
+    pub fn repository(mut self, repo: &FakeRepo) -> Self {
+        self.repository = Some(repo.url());
+        self
+    }
+
+    /// Create the workspace on disk, rooted at `{temp}/workspace`.
+    pub fn build(self) -> TestProject {
+        let temp = TempDir::new().expect("create workspace dir");
+        let root = temp.path().join("workspace");
+        fs::create_dir_all(&root).expect("create workspace root");
+
+        let names: Vec<String> = self
+            .members
+            .iter()
+            .map(|m| format!("\"{}\"", m.manifest.package.name))
+            .collect();
+        fs::write(
+            root.join("Jargo.toml"),
+            format!("[workspace]\nmembers = [{}]\n", names.join(", ")),
+        )
+        .expect("write Jargo.toml");
+        fs::write(root.join(".gitignore"), "target/\n").expect("write .gitignore");
+        for member in &self.members {
+            member.write_to(&root.join(&member.manifest.package.name));
+        }
+
+        TestProject {
+            home: temp.path().join("home"),
+            root,
+            repository: self.repository,
+            _temp: temp,
+        }
+    }
+}
+
+/// A project on disk with its own Jargo home, removed when dropped.
+pub struct TestProject {
+    root: PathBuf,
+    home: PathBuf,
+    repository: Option<String>,
+    _temp: TempDir,
+}
+
+impl TestProject {
+    /// Project root (the directory holding `Jargo.toml`).
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// A path relative to the project root.
+    pub fn path(&self, rel: impl AsRef<Path>) -> PathBuf {
+        self.root.join(rel)
+    }
+
+    /// The isolated home directory; the cache lives in `{home}/.jargo`.
+    pub fn home(&self) -> &Path {
+        &self.home
+    }
+
+    /// Read a project file to a string.
+    pub fn read(&self, rel: impl AsRef<Path>) -> String {
+        let path = self.path(rel);
+        fs::read_to_string(&path).unwrap_or_else(|e| panic!("read {}: {}", path.display(), e))
+    }
+
+    /// Write a project file, creating parent directories.
+    pub fn write(&self, rel: impl AsRef<Path>, contents: impl AsRef<[u8]>) {
+        let path = self.path(rel);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("create parent dirs");
+        }
+        fs::write(&path, contents).unwrap_or_else(|e| panic!("write {}: {}", path.display(), e));
+    }
+
+    /// A quiet context rooted at the project, for calling `jargo-core`
+    /// directly. Uses the same Jargo home as [`TestProject::jargo`].
+    ///
+    /// Note that downloads read `JARGO_MAVEN_REPO` from the test process
+    /// environment; only [`TestProject::jargo`] sets it for you.
+    pub fn gctx(&self) -> GlobalContext {
+        GlobalContext {
+            jargo_home: self.home.join(".jargo"),
+            cwd: self.root.clone(),
+            shell: Shell::new(Verbosity::Quiet),
+            config: Default::default(),
+            locked: false,
+            features: Vec::new(),
+        }
+    }
+
+    /// A command running the `jargo` binary at `bin` in the project root,
+    /// with an isolated home and the project's repository.
+    ///
+    /// From the `jargo` crate's own tests, pass `env!("CARGO_BIN_EXE_jargo")`.
+    pub fn jargo(&self, bin: impl AsRef<Path>) -> Command {
+        let mut cmd = Command::new(bin.as_ref());
+        cmd.current_dir(&self.root)
+            .env("HOME", &self.home)
+            .env("USERPROFILE", &self.home);
+        if let Some(url) = &self.repository {
+            cmd.env("JARGO_MAVEN_REPO", url)
+                .env("NO_PROXY", "127.0.0.1,localhost")
+                .env("no_proxy", "127.0.0.1,localhost");
+        }
+        cmd
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_writes_project() {
+        let project = ProjectBuilder::lib("my-lib")
+            .java("17")
+            .dependency("com.acme:widget", "1.0")
+            .manifest("[tasks]\nhello = \"echo hi\"\n")
+            .file("test/LibTest.java", "class LibTest {}")
+            .build();
+
+        let manifest = JargoToml::from_file(&project.path("Jargo.toml")).unwrap();
+        assert_eq!(manifest.package.name, "my-lib");
+        assert_eq!(manifest.package.java, "17");
+        assert!(manifest.dependencies.contains_key("com.acme:widget"));
+        assert_eq!(manifest.tasks["hello"], "echo hi");
+        assert!(project.read("src/Lib.java").contains("package mylib;"));
+        assert_eq!(project.read("test/LibTest.java"), "class LibTest {}");
+    }
+
+    #[test]
+    fn test_workspace_builder_writes_members() {
+        let workspace = WorkspaceBuilder::new()
+            .member(ProjectBuilder::lib("core"))
+            .member(ProjectBuilder::app("app").member_dependency("core"))
+            .build();
+
+        assert_eq!(
+            workspace.read("Jargo.toml"),
+            "[workspace]\nmembers = [\"core\", \"app\"]\n"
+        );
+        assert!(workspace
+            .read("core/src/Lib.java")
+            .contains("package core;"));
+        let app = JargoToml::from_file(&workspace.path("app/Jargo.toml")).unwrap();
+        let deps = app.get_member_dependencies().unwrap();
+        assert_eq!(deps.len(), 1);
+        assert_eq!(deps[0].name, "core");
+    }
+
+    #[test]
+    fn test_file_replaces_scaffolded_source() {
+        let project = ProjectBuilder::app("demo")
+            .file("src/Main.java", "package demo; public class Main {}")
+            .build();
+        assert_eq!(
+            project.read("src/Main.java"),
+            "package demo; public class Main {}"
+        );
+    }
+}
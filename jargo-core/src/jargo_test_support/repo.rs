@@ -0,0 +1,284 @@
+//! A Maven repository served over HTTP from a temp directory.
+
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+use tempfile::TempDir;
+
+use super::fixtures::{self, FixtureDep};
+use crate::cache;
+
+/// A local Maven repository on `127.0.0.1`, stopped when dropped.
+///
+/// Point Jargo at it with the `JARGO_MAVEN_REPO` environment variable (see
+/// [`FakeRepo::url`]); [`super::TestProject::jargo`] does this for projects
+/// built with [`super::ProjectBuilder::repository`].
+pub struct FakeRepo {
+    root: TempDir,
+    addr: SocketAddr,
+    requests: Arc<Mutex<Vec<String>>>,
+    shutdown: Arc<AtomicBool>,
+    server: Option<JoinHandle<()>>,
+}
+
+impl FakeRepo {
+    /// Start serving an empty repository. Panics if no port can be bound.
+    pub fn start() -> Self {
+        let root = TempDir::new().expect("create fake repository dir");
+        let listener =
+            TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).expect("bind fake repository port");
+        let addr = listener.local_addr().expect("fake repository address");
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let server = {
+            let dir = root.path().to_path_buf();
+            let requests = Arc::clone(&requests);
+            let shutdown = Arc::clone(&shutdown);
+            std::thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if shutdown.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    if let Ok(stream) = stream {
+                        serve(stream, &dir, &requests);
+                    }
+                }
+            })
+        };
+
+        Self {
+            root,
+            addr,
+            requests,
+            shutdown,
+            server: Some(server),
+        }
+    }
+
+    /// Base URL, suitable for `JARGO_MAVEN_REPO`.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Directory the repository is served from.
+    pub fn root(&self) -> &Path {
+        self.root.path()
+    }
+
+    /// Write a file at a repository path such as
+    /// `com/acme/widget/1.0/widget-1.0.pom`.
+    pub fn put(&self, path: &str, contents: impl AsRef<[u8]>) {
+        let full = self.root.path().join(path);
+        fs::create_dir_all(full.parent().expect("repository path has a parent"))
+            .expect("create fake repository dirs");
+        fs::write(&full, contents).expect("write fake repository file");
+    }
+
+    /// Publish `group:artifact:version` with a POM declaring `deps` and a JAR
+    /// holding `com/acme/Widget`-style `classes` compiled for `release`.
+    pub fn publish_pom(
+        &self,
+        coordinate: &str,
+        deps: &[FixtureDep],
+        classes: &[&str],
+        release: u32,
+    ) {
+        let (group, artifact, version) = split(coordinate);
+        self.put(
+            &self.path_of(group, artifact, version, "pom"),
+            fixtures::pom(group, artifact, version, deps),
+        );
+        self.put(
+            &self.path_of(group, artifact, version, "jar"),
+            fixtures::jar(classes, release),
+        );
+    }
+
+    /// Like [`FakeRepo::publish_pom`], but with Gradle `.module` metadata
+    /// (plus the POM, as Gradle publishes both).
+    pub fn publish_module(
+        &self,
+        coordinate: &str,
+        deps: &[FixtureDep],
+        classes: &[&str],
+        release: u32,
+    ) {
+        self.publish_pom(coordinate, deps, classes, release);
+        let (group, artifact, version) = split(coordinate);
+        self.put(
+            &self.path_of(group, artifact, version, "module"),
+            fixtures::gradle_module(group, artifact, version, deps),
+        );
+    }
+
+    /// Paths requested so far (including misses), in order.
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().expect("requests lock").clone()
+    }
+
+    fn path_of(&self, group: &str, artifact: &str, version: &str, ext: &str) -> String {
+        cache::artifact_url("", group, artifact, version, ext)
+            .trim_start_matches('/')
+            .to_string()
+    }
+}
+
+impl Drop for FakeRepo {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // Wake the accept loop so it sees the flag.
+        let _ = TcpStream::connect(self.addr);
+        if let Some(server) = self.server.take() {
+            let _ = server.join();
+        }
+    }
+}
+
+fn split(coordinate: &str) -> (&str, &str, &str) {
+    let parts: Vec<&str> = coordinate.split(':').collect();
+    match parts[..] {
+        [group, artifact, version] => (group, artifact, version),
+        _ => panic!("expected group:artifact:version, got `{}`", coordinate),
+    }
+}
+
+/// Answer one `GET` with the file under `dir`, or 404, and store the body of
+/// a `PUT` there, as a deploy to a repository does.
+fn serve(stream: TcpStream, dir: &Path, requests: &Mutex<Vec<String>>) {
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    // Drain headers, keeping the length of an upload
+    let mut content_length = 0;
+    let mut line = String::new();
+    while reader.read_line(&mut line).is_ok_and(|n| n > 0) && line.trim() != "" {
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+        line.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("GET").to_string();
+    let path = parts.next().unwrap_or("/").to_string();
+    requests.lock().expect("requests lock").push(path.clone());
+
+    let mut stream = &stream;
+    if method == "PUT" {
+        let mut body = vec![0; content_length];
+        let stored = reader.read_exact(&mut body).is_ok()
+            && resolve(dir, &path).is_some_and(|p| {
+                p.parent().is_some_and(|d| fs::create_dir_all(d).is_ok())
+                    && fs::write(p, body).is_ok()
+            });
+        let status = if stored {
+            "201 Created"
+        } else {
+            "400 Bad Request"
+        };
+        let _ = stream.write_all(
+            format!(
+                "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                status
+            )
+            .as_bytes(),
+        );
+        return;
+    }
+
+    let file = resolve(dir, &path).and_then(|p| fs::read(p).ok());
+    let _ = match file {
+        Some(body) => stream
+            .write_all(
+                format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                )
+                .as_bytes(),
+            )
+            .and_then(|_| stream.write_all(&body)),
+        None => stream
+            .write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"),
+    };
+}
+
+/// Map a URL path onto `dir`, refusing anything that escapes it.
+fn resolve(dir: &Path, url_path: &str) -> Option<PathBuf> {
+    let rel = Path::new(url_path.trim_start_matches('/'));
+    if rel.components().any(|c| !matches!(c, Component::Normal(_))) {
+        return None;
+    }
+    Some(dir.join(rel))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serves_published_files() {
+        let repo = FakeRepo::start();
+        repo.publish_pom("com.acme:widget:1.0", &[], &["com/acme/Widget"], 17);
+
+        let client = reqwest::blocking::Client::builder()
+            .no_proxy()
+            .build()
+            .unwrap();
+        let pom_url = cache::artifact_url(&repo.url(), "com.acme", "widget", "1.0", "pom");
+        let response = client.get(&pom_url).send().unwrap();
+        assert_eq!(response.status(), 200);
+        assert!(response
+            .text()
+            .unwrap()
+            .contains("<artifactId>widget</artifactId>"));
+
+        let module_url = cache::artifact_url(&repo.url(), "com.acme", "widget", "1.0", "module");
+        assert_eq!(client.get(&module_url).send().unwrap().status(), 404);
+        assert_eq!(
+            client
+                .get(format!("{}/../etc/passwd", repo.url()))
+                .send()
+                .unwrap()
+                .status(),
+            404
+        );
+
+        assert_eq!(
+            &repo.requests()[..2],
+            [
+                "/com/acme/widget/1.0/widget-1.0.pom",
+                "/com/acme/widget/1.0/widget-1.0.module"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stores_uploads() {
+        let repo = FakeRepo::start();
+        let client = reqwest::blocking::Client::builder()
+            .no_proxy()
+            .build()
+            .unwrap();
+        let pom_url = cache::artifact_url(&repo.url(), "com.acme", "widget", "2.0", "pom");
+        let response = client.put(&pom_url).body("<project/>").send().unwrap();
+        assert_eq!(response.status(), 201);
+        assert_eq!(
+            client.get(&pom_url).send().unwrap().text().unwrap(),
+            "<project/>"
+        );
+        assert_eq!(
+            fs::read_to_string(repo.root().join("com/acme/widget/2.0/widget-2.0.pom")).unwrap(),
+            "<project/>"
+        );
+    }
+}
@@ -0,0 +1,330 @@
+use anyhow::{bail, Context, Result};
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::cache;
+use crate::context::GlobalContext;
+use crate::errors::JargoError;
+use crate::manifest::JargoToml;
+use crate::toolchain;
+
+/// Directory the HTML API docs of a release are written to.
+pub const JAVADOC_DIR: &str = "target/javadoc";
+
+/// Directory `jargo doc` writes the HTML API docs to.
+pub const DOC_DIR: &str = "target/doc";
+
+/// Docs generated code links to: the site at `url`, whose package list
+/// (`element-list`, or `package-list` before JDK 10) is a local copy in
+/// `package_list_dir`, so javadoc never fetches it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocLink {
+    pub url: String,
+    pub package_list_dir: PathBuf,
+}
+
+/// How a javadoc run writes and checks the docs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocOptions {
+    /// Output directory, replaced on every run.
+    pub dir: PathBuf,
+    /// Other APIs that references link to.
+    pub links: Vec<DocLink>,
+    /// Value of `-Xdoclint:`, e.g. `none` or `all,-missing`.
+    pub doclint: String,
+    /// Turn every warning into an error with `-Werror`.
+    pub deny_warnings: bool,
+}
+
+/// Run `javadoc` over the main sources staged by the last build in
+/// `target/src-root/`, replacing `target/javadoc/`. Doclint is off, so
+/// missing comments never fail a release.
+pub fn generate(
+    gctx: &GlobalContext,
+    project_root: &Path,
+    manifest: &JargoToml,
+    classpath: &[PathBuf],
+) -> Result<PathBuf> {
+    let options = DocOptions {
+        dir: project_root.join(JAVADOC_DIR),
+        links: Vec::new(),
+        doclint: "none".to_string(),
+        deny_warnings: false,
+    };
+    generate_into(gctx, project_root, manifest, classpath, &options)?;
+    Ok(options.dir)
+}
+
+/// Run `javadoc` over the sources staged in `target/src-root/` with
+/// `options`. Warnings javadoc prints are passed through to stderr.
+pub fn generate_into(
+    gctx: &GlobalContext,
+    project_root: &Path,
+    manifest: &JargoToml,
+    classpath: &[PathBuf],
+    options: &DocOptions,
+) -> Result<()> {
+    let toolchain = toolchain::resolve(project_root, manifest)?;
+    let dir = &options.dir;
+    if dir.exists() {
+        fs::remove_dir_all(dir).with_context(|| format!("failed to remove {}", dir.display()))?;
+    }
+
+    let mut cmd = Command::new(toolchain.tool("javadoc"));
+    cmd.args(javadoc_args(project_root, manifest, classpath, options)?);
+    gctx.shell
+        .verbose(|sh| sh.print(format!("  [verbose] running {:?}", cmd)));
+
+    let output = cmd.output().map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            JargoError::JavaNotFound.into()
+        } else {
+            anyhow::Error::from(e).context("failed to run javadoc")
+        }
+    })?;
+    if !output.status.success() {
+        bail!(
+            "javadoc failed:\n{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        );
+    }
+    eprint!("{}", String::from_utf8_lossy(&output.stderr));
+    Ok(())
+}
+
+/// Where the JDK API docs for Java `java` are published.
+pub fn jdk_docs_url(java: &str) -> String {
+    match java {
+        "8" | "1.8" => "https://docs.oracle.com/javase/8/docs/api/".to_string(),
+        _ => format!("https://docs.oracle.com/en/java/javase/{}/docs/api/", java),
+    }
+}
+
+/// Where javadoc.io serves the docs of a Maven artifact.
+pub fn dependency_docs_url(group: &str, artifact: &str, version: &str) -> String {
+    format!("https://javadoc.io/doc/{}/{}/{}/", group, artifact, version)
+}
+
+/// A link to the JDK API docs, with the package list downloaded once into
+/// `~/.jargo/doc-links/jdk/{java}/`. `None` when it cannot be downloaded.
+pub fn jdk_link(gctx: &GlobalContext, java: &str) -> Result<Option<DocLink>> {
+    let url = jdk_docs_url(java);
+    let dir = gctx.jargo_home.join("doc-links/jdk").join(java);
+    if find_package_list(&dir).is_none() {
+        fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+        let mut found = false;
+        for name in PACKAGE_LISTS {
+            if cache::fetch_url(&format!("{}{}", url, name), &dir.join(name))? {
+                found = true;
+                break;
+            }
+        }
+        if !found {
+            return Ok(None);
+        }
+    }
+    Ok(Some(DocLink {
+        url,
+        package_list_dir: dir,
+    }))
+}
+
+/// A link to the javadoc.io docs of a dependency, with the package list
+/// taken from its `-javadoc.jar`. `None` when the artifact publishes no
+/// docs, or they have no package list.
+pub fn dependency_link(
+    gctx: &GlobalContext,
+    group: &str,
+    artifact: &str,
+    version: &str,
+) -> Result<Option<DocLink>> {
+    let dir = cache::artifact_dir(&gctx.jargo_home.join("doc-links"), group, artifact, version);
+    if find_package_list(&dir).is_none() {
+        let jar = match cache::fetch_classified_jar(gctx, group, artifact, version, "javadoc") {
+            Ok(jar) => jar,
+            Err(e)
+                if matches!(
+                    e.downcast_ref::<JargoError>(),
+                    Some(JargoError::DependencyNotFound(..))
+                ) =>
+            {
+                return Ok(None)
+            }
+            Err(e) => return Err(e),
+        };
+        if !extract_package_list(&jar, &dir)? {
+            return Ok(None);
+        }
+    }
+    Ok(Some(DocLink {
+        url: dependency_docs_url(group, artifact, version),
+        package_list_dir: dir,
+    }))
+}
+
+/// Package list files, newest format first.
+const PACKAGE_LISTS: [&str; 2] = ["element-list", "package-list"];
+
+fn find_package_list(dir: &Path) -> Option<PathBuf> {
+    PACKAGE_LISTS
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Copy the package list at the root of a javadoc JAR into `dir`. Returns
+/// whether it had one.
+fn extract_package_list(jar: &Path, dir: &Path) -> Result<bool> {
+    let file = fs::File::open(jar).with_context(|| format!("failed to open {}", jar.display()))?;
+    let mut archive =
+        zip::ZipArchive::new(file).with_context(|| format!("failed to read {}", jar.display()))?;
+    for name in PACKAGE_LISTS {
+        let Ok(mut entry) = archive.by_name(name) else {
+            continue;
+        };
+        let mut contents = Vec::new();
+        std::io::Read::read_to_end(&mut entry, &mut contents)
+            .with_context(|| format!("failed to read {} in {}", name, jar.display()))?;
+        fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+        fs::write(dir.join(name), contents)
+            .with_context(|| format!("failed to write {}", dir.join(name).display()))?;
+        return Ok(true);
+    }
+    Ok(false)
+}
+
+/// Arguments for `javadoc`, writing to `options.dir`.
+fn javadoc_args(
+    project_root: &Path,
+    manifest: &JargoToml,
+    classpath: &[PathBuf],
+    options: &DocOptions,
+) -> Result<Vec<OsString>> {
+    let mut args: Vec<OsString> = vec![
+        "-d".into(),
+        options.dir.clone().into(),
+        "-sourcepath".into(),
+        project_root.join("target/src-root").into(),
+        "-subpackages".into(),
+        manifest.get_base_package().into(),
+        "--release".into(),
+        manifest.package.java.clone().into(),
+    ];
+    args.extend(["-encoding", "UTF-8", "-quiet"].map(OsString::from));
+    args.push(format!("-Xdoclint:{}", options.doclint).into());
+    if options.deny_warnings {
+        args.push("-Werror".into());
+    }
+    if !classpath.is_empty() {
+        args.push("-classpath".into());
+        args.push(std::env::join_paths(classpath).context("invalid classpath entry")?);
+    }
+    for link in &options.links {
+        args.push("-linkoffline".into());
+        args.push(link.url.clone().into());
+        args.push(link.package_list_dir.clone().into());
+    }
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_javadoc_args() {
+        let manifest: JargoToml = toml::from_str(
+            "[package]\nname = \"demo\"\nversion = \"1.0.0\"\njava = \"17\"\n\
+             base-package = \"com.example.demo\"",
+        )
+        .unwrap();
+        let root = Path::new("/work/demo");
+        let mut options = DocOptions {
+            dir: root.join(JAVADOC_DIR),
+            links: Vec::new(),
+            doclint: "none".to_string(),
+            deny_warnings: false,
+        };
+        let args = javadoc_args(root, &manifest, &[], &options).unwrap();
+        assert_eq!(
+            args,
+            [
+                "-d",
+                "/work/demo/target/javadoc",
+                "-sourcepath",
+                "/work/demo/target/src-root",
+                "-subpackages",
+                "com.example.demo",
+                "--release",
+                "17",
+                "-encoding",
+                "UTF-8",
+                "-quiet",
+                "-Xdoclint:none",
+            ]
+        );
+
+        let jars = [PathBuf::from("/cache/a.jar"), PathBuf::from("/cache/b.jar")];
+        let args = javadoc_args(root, &manifest, &jars, &options).unwrap();
+        assert_eq!(args[args.len() - 2], "-classpath");
+        assert_eq!(args[args.len() - 1], std::env::join_paths(&jars).unwrap());
+
+        let link = DocLink {
+            url: dependency_docs_url("com.google.guava", "guava", "33.0.0-jre"),
+            package_list_dir: PathBuf::from("/home/.jargo/doc-links/guava"),
+        };
+        options = DocOptions {
+            dir: root.join(DOC_DIR),
+            links: vec![link],
+            doclint: "all,-missing".to_string(),
+            deny_warnings: true,
+        };
+        let args = javadoc_args(root, &manifest, &[], &options).unwrap();
+        assert_eq!(args[1], "/work/demo/target/doc");
+        assert_eq!(args[11..13], ["-Xdoclint:all,-missing", "-Werror"]);
+        assert_eq!(
+            args[args.len() - 3..],
+            [
+                "-linkoffline",
+                "https://javadoc.io/doc/com.google.guava/guava/33.0.0-jre/",
+                "/home/.jargo/doc-links/guava",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_jdk_docs_url() {
+        assert_eq!(
+            jdk_docs_url("21"),
+            "https://docs.oracle.com/en/java/javase/21/docs/api/"
+        );
+        assert_eq!(
+            jdk_docs_url("8"),
+            "https://docs.oracle.com/javase/8/docs/api/"
+        );
+    }
+
+    #[test]
+    fn test_extract_package_list() {
+        let tmp = tempfile::tempdir().unwrap();
+        let jar = tmp.path().join("lib-javadoc.jar");
+        let mut zip = zip::ZipWriter::new(fs::File::create(&jar).unwrap());
+        zip.start_file("index.html", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.start_file("element-list", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        std::io::Write::write_all(&mut zip, b"com.example.lib\n").unwrap();
+        zip.finish().unwrap();
+
+        let dir = tmp.path().join("links");
+        assert!(extract_package_list(&jar, &dir).unwrap());
+        assert_eq!(find_package_list(&dir), Some(dir.join("element-list")));
+        assert_eq!(
+            fs::read_to_string(dir.join("element-list")).unwrap(),
+            "com.example.lib\n"
+        );
+    }
+}
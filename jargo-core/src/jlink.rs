@@ -0,0 +1,312 @@
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::dist::sh_quote;
+use crate::manifest::{BinTarget, JargoToml};
+use crate::toolchain::Toolchain;
+
+/// Modules the app needs: what `jdeps --print-module-deps` finds in the
+/// project JAR and its runtime dependencies, plus `[jlink] add-modules`.
+pub fn required_modules(
+    toolchain: &Toolchain,
+    manifest: &JargoToml,
+    jar: &Path,
+    runtime_jars: &[PathBuf],
+) -> Result<Vec<String>> {
+    let mut cmd = Command::new(toolchain.tool("jdeps"));
+    cmd.args(["--print-module-deps", "--ignore-missing-deps"])
+        .args(["--multi-release", &manifest.package.java]);
+    if !runtime_jars.is_empty() {
+        cmd.arg("--class-path")
+            .arg(std::env::join_paths(runtime_jars).context("invalid dependency JAR path")?);
+    }
+    cmd.arg(jar).args(runtime_jars);
+
+    let output = run_tool(&mut cmd, "jdeps")?;
+    let mut modules = parse_module_deps(&output);
+    if let Some(config) = &manifest.jlink {
+        modules.extend(config.add_modules.iter().cloned());
+    }
+    modules.push("java.base".to_string());
+    modules.sort();
+    modules.dedup();
+    Ok(modules)
+}
+
+/// Build a runtime image in `target/image/` with `jlink`, then add the app:
+///
+/// ```text
+/// bin/{launcher}       POSIX launcher using the image's own `java`, one per bin
+/// bin/{launcher}.bat   Windows launcher
+/// app/                 project JAR + runtime dependency JARs
+/// ```
+///
+/// Returns the image directory. An existing one is replaced.
+pub fn write_image(
+    toolchain: &Toolchain,
+    project_root: &Path,
+    manifest: &JargoToml,
+    modules: &[String],
+    jar: &Path,
+    runtime_jars: &[PathBuf],
+) -> Result<PathBuf> {
+    let image_dir = project_root.join("target/image");
+    if image_dir.exists() {
+        fs::remove_dir_all(&image_dir)
+            .with_context(|| format!("failed to remove {}", image_dir.display()))?;
+    }
+
+    let config = manifest.jlink.as_ref();
+    let mut cmd = Command::new(toolchain.tool("jlink"));
+    cmd.args(["--add-modules", &modules.join(",")])
+        .args(["--strip-debug", "--no-header-files", "--no-man-pages"])
+        .arg("--output")
+        .arg(&image_dir);
+    if let Some(compression) = config.and_then(|c| c.compression.as_deref()) {
+        cmd.arg(format!("--compress={}", compression));
+    }
+    run_tool(&mut cmd, "jlink")?;
+
+    let app_dir = image_dir.join("app");
+    fs::create_dir_all(&app_dir)
+        .with_context(|| format!("failed to create {}", app_dir.display()))?;
+    let mut classpath = Vec::new();
+    for file in std::iter::once(jar).chain(runtime_jars.iter().map(PathBuf::as_path)) {
+        let name = file
+            .file_name()
+            .with_context(|| format!("invalid JAR path {}", file.display()))?
+            .to_string_lossy()
+            .into_owned();
+        fs::copy(file, app_dir.join(&name)).with_context(|| {
+            format!(
+                "failed to copy {} into {}",
+                file.display(),
+                app_dir.display()
+            )
+        })?;
+        classpath.push(name);
+    }
+
+    let bin_dir = image_dir.join("bin");
+    for (launcher, bin) in launchers(manifest)? {
+        let unix = bin_dir.join(&launcher);
+        let windows = bin_dir.join(format!("{}.bat", launcher));
+        for path in [&unix, &windows] {
+            if path.exists() {
+                bail!(
+                    "launcher `{}` would replace {} from the JDK",
+                    launcher,
+                    path.display()
+                );
+            }
+        }
+
+        fs::write(&unix, unix_launcher(manifest, &bin, &classpath))
+            .with_context(|| format!("failed to write {}", unix.display()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&unix, fs::Permissions::from_mode(0o755))
+                .with_context(|| format!("failed to make {} executable", unix.display()))?;
+        }
+        fs::write(&windows, windows_launcher(manifest, &bin, &classpath))
+            .with_context(|| format!("failed to write {}", windows.display()))?;
+    }
+
+    Ok(image_dir)
+}
+
+/// Launcher names and the entry points they run: `[jlink] launcher`
+/// (default: package name) for an app with a single entry point, else the
+/// `[[bin]]` names.
+fn launchers(manifest: &JargoToml) -> Result<Vec<(String, BinTarget)>> {
+    let bins = manifest.get_bins()?;
+    let configured = manifest.jlink.as_ref().and_then(|c| c.launcher.clone());
+    let [bin] = bins.as_slice() else {
+        if configured.is_some() {
+            bail!("[jlink] launcher cannot be combined with several [[bin]] targets, which name their own launchers");
+        }
+        return Ok(bins
+            .into_iter()
+            .map(|bin| (bin.name.clone(), bin))
+            .collect());
+    };
+
+    let name = configured.unwrap_or_else(|| manifest.package.name.clone());
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        bail!(
+            "invalid [jlink] launcher `{}`: expected letters, digits, `-`, `_`, or `.`",
+            name
+        );
+    }
+    Ok(vec![(name, bin.clone())])
+}
+
+fn unix_launcher(manifest: &JargoToml, bin: &BinTarget, classpath: &[String]) -> String {
+    let classpath = classpath
+        .iter()
+        .map(|jar| format!("$IMAGE/app/{}", jar))
+        .collect::<Vec<_>>()
+        .join(":");
+    let jvm_args: String = manifest
+        .get_jvm_args()
+        .iter()
+        .map(|arg| format!(" {}", sh_quote(arg)))
+        .collect();
+    format!(
+        "#!/bin/sh\n\
+         # Launcher for {name}, generated by `jargo jlink`.\n\
+         IMAGE=$(cd \"$(dirname \"$0\")/..\" && pwd -P)\n\
+         exec \"$IMAGE/bin/java\"{jvm_args} -cp \"{classpath}\" {main} \"$@\"\n",
+        name = manifest.package.name,
+        main = manifest.main_class_of(bin),
+    )
+}
+
+fn windows_launcher(manifest: &JargoToml, bin: &BinTarget, classpath: &[String]) -> String {
+    let classpath = classpath
+        .iter()
+        .map(|jar| format!("%IMAGE%\\app\\{}", jar))
+        .collect::<Vec<_>>()
+        .join(";");
+    let jvm_args: String = manifest
+        .get_jvm_args()
+        .iter()
+        .map(|arg| format!(" \"{}\"", arg.replace('%', "%%")))
+        .collect();
+    format!(
+        "@echo off\r\n\
+         rem Launcher for {name}, generated by `jargo jlink`.\r\n\
+         set \"IMAGE=%~dp0..\"\r\n\
+         \"%IMAGE%\\bin\\java.exe\"{jvm_args} -cp \"{classpath}\" {main} %*\r\n",
+        name = manifest.package.name,
+        main = manifest.main_class_of(bin),
+    )
+}
+
+/// Run a JDK tool and return its stdout.
+pub(crate) fn run_tool(cmd: &mut Command, name: &str) -> Result<String> {
+    let output = cmd.output().map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            anyhow::anyhow!("{} not found; it ships with the JDK", name)
+        } else {
+            anyhow::Error::from(e).context(format!("failed to run {}", name))
+        }
+    })?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        bail!(
+            "{} failed: {}",
+            name,
+            if stderr.trim().is_empty() {
+                stdout.trim()
+            } else {
+                stderr.trim()
+            }
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Parse `jdeps --print-module-deps` output: a comma-separated module list on
+/// the last non-empty line (warnings may come before it).
+fn parse_module_deps(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .rev()
+        .find(|line| !line.trim().is_empty())
+        .map(|line| {
+            line.split(',')
+                .map(str::trim)
+                .filter(|m| !m.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(extra: &str) -> JargoToml {
+        toml::from_str(&format!(
+            r#"
+[package]
+name = "demo"
+version = "1.0.0"
+java = "17"
+{}
+"#,
+            extra
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_module_deps() {
+        assert_eq!(
+            parse_module_deps("Warning: split package\njava.base,java.sql\n"),
+            vec!["java.base", "java.sql"]
+        );
+        assert!(parse_module_deps("").is_empty());
+    }
+
+    #[test]
+    fn test_launchers() {
+        let names = |extra: &str| -> Result<Vec<String>> {
+            Ok(launchers(&manifest(extra))?
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect())
+        };
+        assert_eq!(names("").unwrap(), ["demo"]);
+        assert_eq!(
+            names("[jlink]\nlauncher = \"demo-cli\"").unwrap(),
+            ["demo-cli"]
+        );
+        assert!(names("[jlink]\nlauncher = \"../x\"").is_err());
+
+        let bins = r#"
+[[bin]]
+name = "server"
+main-class = "Server"
+
+[[bin]]
+name = "migrate"
+main-class = "tools.Migrate"
+"#;
+        assert_eq!(names(bins).unwrap(), ["server", "migrate"]);
+        assert!(names(&format!("[jlink]\nlauncher = \"x\"\n{}", bins)).is_err());
+    }
+
+    #[test]
+    fn test_unix_launcher() {
+        let m = manifest("[run]\njvm-args = [\"-Xmx1g\"]");
+        let script = unix_launcher(
+            &m,
+            &m.get_bin(None).unwrap(),
+            &["demo.jar".to_string(), "dep-1.0.jar".to_string()],
+        );
+        assert!(script.ends_with(
+            "exec \"$IMAGE/bin/java\" '-Xmx1g' \
+             -cp \"$IMAGE/app/demo.jar:$IMAGE/app/dep-1.0.jar\" demo.Main \"$@\"\n"
+        ));
+    }
+
+    #[test]
+    fn test_windows_launcher() {
+        let m = manifest("");
+        let script = windows_launcher(&m, &m.get_bin(None).unwrap(), &["demo.jar".to_string()]);
+        assert!(script.ends_with(
+            "\"%IMAGE%\\bin\\java.exe\" -cp \"%IMAGE%\\app\\demo.jar\" demo.Main %*\r\n"
+        ));
+    }
+}
@@ -0,0 +1,253 @@
+use anyhow::{bail, Context, Result};
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::jlink::run_tool;
+use crate::manifest::{BinTarget, JargoToml};
+use crate::toolchain::Toolchain;
+
+/// Installer types `jpackage --type` accepts.
+pub const PACKAGE_TYPES: [&str; 7] = ["app-image", "deb", "rpm", "msi", "exe", "dmg", "pkg"];
+
+/// Build a native installer with `jpackage` into `target/installer/`.
+///
+/// The project JAR (`target/{name}.jar`) and runtime dependency JARs are
+/// staged in `target/jpackage-input/` (jpackage puts every JAR there on the
+/// classpath), and the bundled runtime holds only `modules`. The installed app runs `bin`, and `package_type`
+/// overrides `[jpackage] type`.
+///
+/// Returns the output directory. An existing one is replaced.
+pub fn write_installer(
+    toolchain: &Toolchain,
+    project_root: &Path,
+    manifest: &JargoToml,
+    bin: &BinTarget,
+    modules: &[String],
+    runtime_jars: &[PathBuf],
+    package_type: Option<&str>,
+) -> Result<PathBuf> {
+    let jar = project_root
+        .join("target")
+        .join(format!("{}.jar", manifest.package.name));
+    let input_dir = project_root.join("target/jpackage-input");
+    let dest_dir = project_root.join("target/installer");
+    for dir in [&input_dir, &dest_dir] {
+        if dir.exists() {
+            fs::remove_dir_all(dir)
+                .with_context(|| format!("failed to remove {}", dir.display()))?;
+        }
+    }
+    fs::create_dir_all(&input_dir)
+        .with_context(|| format!("failed to create {}", input_dir.display()))?;
+    for file in std::iter::once(jar.as_path()).chain(runtime_jars.iter().map(PathBuf::as_path)) {
+        let name = file
+            .file_name()
+            .with_context(|| format!("invalid JAR path {}", file.display()))?;
+        fs::copy(file, input_dir.join(name)).with_context(|| {
+            format!(
+                "failed to copy {} into {}",
+                file.display(),
+                input_dir.display()
+            )
+        })?;
+    }
+
+    let args = jpackage_args(
+        project_root,
+        manifest,
+        bin,
+        modules,
+        &input_dir,
+        &dest_dir,
+        package_type,
+    )?;
+    run_tool(
+        Command::new(toolchain.tool("jpackage")).args(&args),
+        "jpackage",
+    )?;
+
+    Ok(dest_dir)
+}
+
+/// Arguments for `jpackage`, with name, version, icon, vendor, and
+/// description taken from Jargo.toml.
+fn jpackage_args(
+    project_root: &Path,
+    manifest: &JargoToml,
+    bin: &BinTarget,
+    modules: &[String],
+    input_dir: &Path,
+    dest_dir: &Path,
+    package_type: Option<&str>,
+) -> Result<Vec<OsString>> {
+    let config = manifest.jpackage.as_ref();
+    let package_type = package_type.or(config.and_then(|c| c.package_type.as_deref()));
+
+    let mut args: Vec<OsString> = Vec::new();
+    let mut push = |flag: &str, value: OsString| {
+        args.push(flag.into());
+        args.push(value);
+    };
+    if let Some(package_type) = package_type {
+        if !PACKAGE_TYPES.contains(&package_type) {
+            bail!(
+                "unknown jpackage type `{}`: expected one of {}",
+                package_type,
+                PACKAGE_TYPES.join(", ")
+            );
+        }
+        push("--type", package_type.into());
+    }
+    push("--name", manifest.package.name.clone().into());
+    push(
+        "--app-version",
+        app_version(&manifest.package.version)?.into(),
+    );
+    push("--input", input_dir.into());
+    push(
+        "--main-jar",
+        format!("{}.jar", manifest.package.name).into(),
+    );
+    push("--main-class", manifest.main_class_of(bin).into());
+    push("--add-modules", modules.join(",").into());
+    push("--dest", dest_dir.into());
+    for arg in manifest.get_jvm_args() {
+        push("--java-options", arg.into());
+    }
+    if let Some(config) = config {
+        if let Some(icon) = &config.icon {
+            let icon = project_root.join(icon);
+            if !icon.is_file() {
+                bail!("[jpackage] icon `{}` does not exist", icon.display());
+            }
+            push("--icon", icon.into());
+        }
+        if let Some(vendor) = &config.vendor {
+            push("--vendor", vendor.into());
+        }
+        if let Some(description) = &config.description {
+            push("--description", description.into());
+        }
+    }
+    Ok(args)
+}
+
+/// `package.version` as jpackage accepts it: one to three numeric components,
+/// with any pre-release or build suffix dropped (`1.2.0-SNAPSHOT` → `1.2.0`).
+fn app_version(version: &str) -> Result<String> {
+    let numeric = version
+        .split(|c: char| !c.is_ascii_digit() && c != '.')
+        .next()
+        .unwrap_or("")
+        .trim_end_matches('.');
+    let components: Vec<&str> = numeric.split('.').collect();
+    if numeric.is_empty() || components.len() > 3 || components.iter().any(|c| c.is_empty()) {
+        bail!(
+            "package version `{}` cannot be used by jpackage: expected up to three numeric components",
+            version
+        );
+    }
+    Ok(numeric.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(extra: &str) -> JargoToml {
+        toml::from_str(&format!(
+            r#"
+[package]
+name = "demo"
+version = "1.2.0-SNAPSHOT"
+java = "17"
+{}
+"#,
+            extra
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_app_version() {
+        assert_eq!(app_version("1.2.0").unwrap(), "1.2.0");
+        assert_eq!(app_version("1.2.0-SNAPSHOT").unwrap(), "1.2.0");
+        assert_eq!(app_version("2").unwrap(), "2");
+        assert!(app_version("1.2.3.4").is_err());
+        assert!(app_version("beta").is_err());
+    }
+
+    #[test]
+    fn test_jpackage_args() {
+        let m = manifest(
+            r#"
+[run]
+jvm-args = ["-Xmx1g"]
+
+[jpackage]
+type = "deb"
+vendor = "Acme"
+description = "A demo"
+"#,
+        );
+        let args = jpackage_args(
+            Path::new("/p"),
+            &m,
+            &m.get_bin(None).unwrap(),
+            &["java.base".to_string(), "java.sql".to_string()],
+            Path::new("/p/target/jpackage-input"),
+            Path::new("/p/target/installer"),
+            None,
+        )
+        .unwrap();
+        let args: Vec<&str> = args.iter().map(|a| a.to_str().unwrap()).collect();
+        assert_eq!(
+            args,
+            [
+                "--type",
+                "deb",
+                "--name",
+                "demo",
+                "--app-version",
+                "1.2.0",
+                "--input",
+                "/p/target/jpackage-input",
+                "--main-jar",
+                "demo.jar",
+                "--main-class",
+                "demo.Main",
+                "--add-modules",
+                "java.base,java.sql",
+                "--dest",
+                "/p/target/installer",
+                "--java-options",
+                "-Xmx1g",
+                "--vendor",
+                "Acme",
+                "--description",
+                "A demo",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_type_override_and_validation() {
+        let m = manifest("[jpackage]\ntype = \"deb\"");
+        let bin = m.get_bin(None).unwrap();
+        let args = |package_type| {
+            jpackage_args(
+                Path::new("/p"),
+                &m,
+                &bin,
+                &[],
+                Path::new("in"),
+                Path::new("out"),
+                package_type,
+            )
+        };
+        assert_eq!(args(Some("app-image")).unwrap()[1], "app-image");
+        assert!(args(Some("zip")).is_err());
+    }
+}
@@ -1,12 +1,66 @@
+pub mod adopt;
+pub mod affected;
+pub mod api_check;
+pub mod backend;
+pub mod bench;
+pub mod build_info;
+pub mod bytecode;
 pub mod cache;
+pub mod central;
+pub mod cleanup;
+pub mod codegen;
 pub mod compiler;
+pub mod config;
+pub mod container;
 pub mod context;
+pub mod coverage;
+pub mod daemon;
+pub mod deploy;
+pub mod deprecations;
+pub mod dist;
+pub mod eclipse;
+pub mod env;
+pub mod error_prone;
 pub mod errors;
+pub mod format;
 pub mod gradle_module;
+pub mod header;
+pub mod imports;
+pub mod install;
 pub mod jar;
+#[cfg(feature = "test-support")]
+pub mod jargo_test_support;
+pub mod javadoc;
+pub mod jlink;
+pub mod jpackage;
+pub mod licenses;
+pub mod lint;
 pub mod lockfile;
+pub mod logging;
+pub mod m2;
 pub mod manifest;
+pub mod message;
+pub mod migrate;
+pub mod minimize;
+pub mod mutation;
+pub mod native;
+pub mod notices;
 pub mod pom;
+pub mod pom_export;
+pub mod profiler;
+pub mod publish_check;
+pub mod registry;
+pub mod repl;
 pub mod resolver;
+pub mod shade;
 pub mod shell;
 pub mod staging;
+pub mod suggest;
+pub mod test_filter;
+pub mod test_runner;
+pub mod toolchain;
+pub mod verify;
+pub mod version;
+pub mod vscode;
+pub mod watch;
+pub mod workspace;
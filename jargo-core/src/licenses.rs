@@ -0,0 +1,313 @@
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::context::GlobalContext;
+use crate::lockfile::LockedDependency;
+use crate::notices;
+use crate::pom::License;
+
+/// SPDX identifiers recognised in POM license names and URLs. A license
+/// matches when its words include the words of one alternative; earlier
+/// entries win.
+const KNOWN_LICENSES: &[(&str, &[&[&str]])] = &[
+    (
+        "GPL-2.0-with-classpath-exception",
+        &[&["classpath", "exception"], &["gpl", "2", "cpe"]],
+    ),
+    ("Apache-2.0", &[&["apache", "2"], &["asl", "2"]]),
+    ("MIT", &[&["mit"]]),
+    (
+        "BSD-3-Clause",
+        &[&["bsd", "3"], &["new", "bsd"], &["revised", "bsd"]],
+    ),
+    ("BSD-2-Clause", &[&["bsd", "2"], &["simplified", "bsd"]]),
+    ("EPL-2.0", &[&["eclipse", "public", "2"], &["epl", "2"]]),
+    ("EPL-1.0", &[&["eclipse", "public", "1"], &["epl", "1"]]),
+    ("LGPL-2.1", &[&["lesser", "2", "1"], &["lgpl", "2", "1"]]),
+    ("LGPL-3.0", &[&["lesser", "3"], &["lgpl", "3"]]),
+    ("MPL-2.0", &[&["mozilla", "2"], &["mpl", "2"]]),
+    (
+        "CDDL-1.1",
+        &[
+            &["cddl", "1", "1"],
+            &["common", "development", "distribution", "1", "1"],
+        ],
+    ),
+    (
+        "CDDL-1.0",
+        &[&["cddl"], &["common", "development", "distribution"]],
+    ),
+    ("CC0-1.0", &[&["cc", "0"]]),
+    ("Unlicense", &[&["unlicense"]]),
+    ("ISC", &[&["isc"]]),
+];
+
+/// The licenses of one runtime dependency, as SPDX identifiers where they
+/// are recognised and as written in the POM otherwise.
+#[derive(Debug, PartialEq)]
+pub struct DependencyLicenses {
+    pub coordinate: String,
+    /// Empty when no `<licenses>` is declared.
+    pub licenses: Vec<String>,
+}
+
+/// A dependency `[licenses] allow` does not permit.
+#[derive(Debug, PartialEq)]
+pub enum LicenseProblem {
+    /// None of the dependency's licenses is allowed.
+    NotAllowed {
+        coordinate: String,
+        licenses: Vec<String>,
+    },
+    /// The dependency's POM declares no license.
+    Undeclared(String),
+}
+
+impl fmt::Display for LicenseProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LicenseProblem::NotAllowed {
+                coordinate,
+                licenses,
+            } => write!(
+                f,
+                "{} is licensed under {}, which [licenses] allow does not include",
+                coordinate,
+                licenses.join(" OR ")
+            ),
+            LicenseProblem::Undeclared(coordinate) => {
+                write!(f, "{} declares no license in its POM", coordinate)
+            }
+        }
+    }
+}
+
+/// The SPDX identifier of a POM `<license>`, or its name (else its URL) when
+/// it is not one of the licenses jargo recognises.
+pub fn identify(license: &License) -> String {
+    for text in [&license.name, &license.url] {
+        let words = words(text);
+        for (id, alternatives) in KNOWN_LICENSES {
+            if alternatives.iter().any(|alt| contains_all(&words, alt)) {
+                return id.to_string();
+            }
+        }
+    }
+    if license.name.is_empty() {
+        license.url.clone()
+    } else {
+        license.name.clone()
+    }
+}
+
+/// Whether `words` has every one of `wanted`, as often as it is wanted, so
+/// `CDDL 1.0` is not taken for `CDDL 1.1`.
+fn contains_all(words: &[String], wanted: &[&str]) -> bool {
+    let mut left: Vec<&str> = words.iter().map(String::as_str).collect();
+    wanted
+        .iter()
+        .all(|w| match left.iter().position(|word| word == w) {
+            Some(i) => {
+                left.swap_remove(i);
+                true
+            }
+            None => false,
+        })
+}
+
+/// Lowercase words of `text`, with letters and digits split apart so `v2.0`
+/// and `GPLv2` give `2`.
+fn words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    for c in text.chars().map(|c| c.to_ascii_lowercase()) {
+        let boundary =
+            |last: char| !c.is_ascii_alphanumeric() || last.is_ascii_digit() != c.is_ascii_digit();
+        if word.chars().last().is_some_and(boundary) {
+            words.push(std::mem::take(&mut word));
+        }
+        if c.is_ascii_alphanumeric() {
+            word.push(c);
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+/// Identify the licenses of every runtime dependency in `lock_entries`, in
+/// lock file order. A POM that cannot be fetched or parsed only warns: the
+/// dependency is listed without a license.
+pub fn collect(
+    gctx: &GlobalContext,
+    lock_entries: &[LockedDependency],
+) -> Result<Vec<DependencyLicenses>> {
+    let mut deps = Vec::new();
+    for entry in lock_entries
+        .iter()
+        .filter(|e| !matches!(e.scope.as_str(), "test" | "it"))
+    {
+        let coordinate = format!("{}:{}:{}", entry.group, entry.artifact, entry.version);
+        let licenses =
+            match notices::pom_licenses(gctx, &entry.group, &entry.artifact, &entry.version) {
+                Ok(licenses) => licenses,
+                Err(e) => {
+                    gctx.shell.warn(&format!(
+                        "could not read the license of {}: {:#}",
+                        coordinate, e
+                    ));
+                    Vec::new()
+                }
+            };
+        let mut ids: Vec<String> = Vec::new();
+        for id in licenses.iter().map(identify) {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        deps.push(DependencyLicenses {
+            coordinate,
+            licenses: ids,
+        });
+    }
+    Ok(deps)
+}
+
+/// Check `deps` against `[licenses] allow`, compared case-insensitively. A
+/// dependency under several licenses may be used under any one of them.
+pub fn check(deps: &[DependencyLicenses], allow: &[String]) -> Vec<LicenseProblem> {
+    let allowed = |id: &String| allow.iter().any(|a| a.eq_ignore_ascii_case(id));
+    deps.iter()
+        .filter_map(|dep| {
+            if dep.licenses.is_empty() {
+                Some(LicenseProblem::Undeclared(dep.coordinate.clone()))
+            } else if !dep.licenses.iter().any(allowed) {
+                Some(LicenseProblem::NotAllowed {
+                    coordinate: dep.coordinate.clone(),
+                    licenses: dep.licenses.clone(),
+                })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// The `jargo licenses` report: each license with the dependencies under
+/// it, by license.
+pub fn render(deps: &[DependencyLicenses]) -> String {
+    let mut by_license: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for dep in deps {
+        if dep.licenses.is_empty() {
+            by_license
+                .entry("(not declared)")
+                .or_default()
+                .push(&dep.coordinate);
+        }
+        for license in &dep.licenses {
+            by_license.entry(license).or_default().push(&dep.coordinate);
+        }
+    }
+    let mut out = String::new();
+    for (license, coordinates) in by_license {
+        out.push_str(&format!("{} ({})\n", license, coordinates.len()));
+        for coordinate in coordinates {
+            out.push_str(&format!("    {}\n", coordinate));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn license(name: &str, url: &str) -> License {
+        License {
+            name: name.to_string(),
+            url: url.to_string(),
+        }
+    }
+
+    fn dep(coordinate: &str, licenses: &[&str]) -> DependencyLicenses {
+        DependencyLicenses {
+            coordinate: coordinate.to_string(),
+            licenses: licenses.iter().map(|l| l.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_identify() {
+        let id = |name: &str| identify(&license(name, ""));
+        assert_eq!(id("The Apache Software License, Version 2.0"), "Apache-2.0");
+        assert_eq!(id("Apache-2.0"), "Apache-2.0");
+        assert_eq!(id("The MIT License"), "MIT");
+        assert_eq!(id("Eclipse Public License - v 2.0"), "EPL-2.0");
+        assert_eq!(id("Eclipse Public License v1.0"), "EPL-1.0");
+        assert_eq!(id("BSD 3-Clause"), "BSD-3-Clause");
+        assert_eq!(id("New BSD License"), "BSD-3-Clause");
+        assert_eq!(
+            id("GNU Lesser General Public License, Version 2.1"),
+            "LGPL-2.1"
+        );
+        assert_eq!(id("GPL2 w/ CPE"), "GPL-2.0-with-classpath-exception");
+        assert_eq!(id("CDDL 1.1"), "CDDL-1.1");
+        assert_eq!(id("CDDL 1.0"), "CDDL-1.0");
+        assert_eq!(id("Acme Commercial License"), "Acme Commercial License");
+        assert_eq!(
+            identify(&license("", "https://opensource.org/licenses/MIT")),
+            "MIT"
+        );
+        assert_eq!(
+            identify(&license("", "https://example.com/terms")),
+            "https://example.com/terms"
+        );
+    }
+
+    #[test]
+    fn test_check() {
+        let deps = [
+            dep("com.acme:widget:1.0", &["Apache-2.0"]),
+            dep("com.acme:dual:1.0", &["GPL-3.0", "MIT"]),
+            dep("com.acme:gpl:1.0", &["GPL-3.0"]),
+            dep("com.acme:bare:1.0", &[]),
+        ];
+        let allow = ["apache-2.0".to_string(), "MIT".to_string()];
+        let problems = check(&deps, &allow);
+        assert_eq!(
+            problems,
+            [
+                LicenseProblem::NotAllowed {
+                    coordinate: "com.acme:gpl:1.0".to_string(),
+                    licenses: vec!["GPL-3.0".to_string()],
+                },
+                LicenseProblem::Undeclared("com.acme:bare:1.0".to_string()),
+            ]
+        );
+        assert_eq!(
+            problems[0].to_string(),
+            "com.acme:gpl:1.0 is licensed under GPL-3.0, which [licenses] allow does not include"
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let deps = [
+            dep("com.acme:widget:1.0", &["Apache-2.0"]),
+            dep("com.acme:dual:1.0", &["Apache-2.0", "MIT"]),
+            dep("com.acme:bare:1.0", &[]),
+        ];
+        assert_eq!(
+            render(&deps),
+            "(not declared) (1)\n\
+             \x20   com.acme:bare:1.0\n\
+             Apache-2.0 (2)\n\
+             \x20   com.acme:widget:1.0\n\
+             \x20   com.acme:dual:1.0\n\
+             MIT (1)\n\
+             \x20   com.acme:dual:1.0\n"
+        );
+    }
+}
@@ -0,0 +1,728 @@
+use anyhow::{bail, Context, Result};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::compiler::{self, render_diagnostics, Diagnostic, Severity, SourceSet};
+use crate::context::GlobalContext;
+use crate::manifest::{JargoToml, LintSeverity};
+use crate::resolver;
+use crate::toolchain::Toolchain;
+
+/// Checkstyle version used by `jargo lint` unless `[lint] version` is set.
+/// Checkstyle 10 needs JDK 11 to run.
+pub const CHECKSTYLE_VERSION: &str = "10.17.0";
+
+/// PMD version used with `[lint] pmd = true`.
+pub const PMD_VERSION: &str = "7.4.0";
+
+/// SpotBugs version used with `[lint] spotbugs = true`.
+pub const SPOTBUGS_VERSION: &str = "4.8.6";
+
+/// Ruleset used when the project has no Checkstyle configuration.
+const BUILTIN_RULESET: &str = include_str!("../templates/checkstyle.xml");
+
+/// Project-local configuration picked up without `[lint] config`.
+const DEFAULT_CONFIG: &str = "checkstyle.xml";
+
+/// PMD's own general-purpose Java ruleset, shipped in `pmd-java`.
+const PMD_RULESET: &str = "rulesets/java/quickstart.xml";
+
+/// Where every tool writes its XML report, and `jargo lint` the combined
+/// `report.txt`.
+pub const REPORT_DIR: &str = "target/lint";
+
+/// The Checkstyle configuration `jargo lint` runs: `[lint] config`, else
+/// `checkstyle.xml` at the project root, else the built-in ruleset written
+/// to `~/.jargo/tools/`.
+pub fn config_path(
+    gctx: &GlobalContext,
+    project_root: &Path,
+    manifest: &JargoToml,
+) -> Result<PathBuf> {
+    if let Some(config) = manifest.lint.as_ref().and_then(|l| l.config.as_ref()) {
+        let path = project_root.join(config);
+        if !path.is_file() {
+            bail!("`[lint] config` {} does not exist", path.display());
+        }
+        return Ok(path);
+    }
+    let local = project_root.join(DEFAULT_CONFIG);
+    if local.is_file() {
+        return Ok(local);
+    }
+    let dir = gctx.jargo_home.join("tools");
+    let builtin = dir.join("jargo-checkstyle.xml");
+    if fs::read_to_string(&builtin).is_ok_and(|s| s == BUILTIN_RULESET) {
+        return Ok(builtin);
+    }
+    fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    fs::write(&builtin, BUILTIN_RULESET)
+        .with_context(|| format!("failed to write {}", builtin.display()))?;
+    Ok(builtin)
+}
+
+/// Every `.java` file of every source set, relative to `project_root`.
+pub fn source_files(project_root: &Path) -> Result<Vec<PathBuf>> {
+    let sets = [
+        SourceSet::Main,
+        SourceSet::Test,
+        SourceSet::IntegrationTest,
+        SourceSet::Examples,
+        SourceSet::Bench,
+    ];
+    let mut files = Vec::new();
+    for set in sets {
+        for file in compiler::find_java_files(&project_root.join(set.source_dir()))? {
+            let rel = file.strip_prefix(project_root).unwrap_or(&file);
+            files.push(rel.to_path_buf());
+        }
+    }
+    Ok(files)
+}
+
+/// Resolve Checkstyle and run it over `files` with `config` in one JVM,
+/// returning its violations as diagnostics in report order.
+pub fn run_checkstyle(
+    gctx: &GlobalContext,
+    project_root: &Path,
+    toolchain: &Toolchain,
+    manifest: &JargoToml,
+    config: &Path,
+    files: &[PathBuf],
+) -> Result<Vec<Diagnostic>> {
+    let version = manifest
+        .lint
+        .as_ref()
+        .and_then(|l| l.version.as_deref())
+        .unwrap_or(CHECKSTYLE_VERSION);
+    let classpath =
+        resolver::resolve_tool(gctx, &[("com.puppycrawl.tools", "checkstyle", version)])?;
+    let report = report_path(project_root, "checkstyle")?;
+    let mut args: Vec<OsString> = vec![
+        "-c".into(),
+        config.into(),
+        "-f".into(),
+        "xml".into(),
+        "-o".into(),
+        report.clone().into(),
+    ];
+    args.extend(files.iter().map(Into::into));
+    let xml = run_tool(
+        project_root,
+        toolchain,
+        &classpath,
+        "Checkstyle",
+        "com.puppycrawl.tools.checkstyle.Main",
+        &args,
+        &report,
+    )?;
+    parse_checkstyle(&xml, project_root)
+}
+
+/// Resolve PMD and run its quickstart ruleset over `files`, parsing them at
+/// `package.java`.
+pub fn run_pmd(
+    gctx: &GlobalContext,
+    project_root: &Path,
+    toolchain: &Toolchain,
+    manifest: &JargoToml,
+    files: &[PathBuf],
+) -> Result<Vec<Diagnostic>> {
+    let classpath = resolver::resolve_tool(
+        gctx,
+        &[
+            ("net.sourceforge.pmd", "pmd-cli", PMD_VERSION),
+            ("net.sourceforge.pmd", "pmd-java", PMD_VERSION),
+        ],
+    )?;
+    let report = report_path(project_root, "pmd")?;
+    let file_list = project_root.join(REPORT_DIR).join("pmd-files.txt");
+    let listed: Vec<String> = files
+        .iter()
+        .map(|f| f.to_string_lossy().into_owned())
+        .collect();
+    fs::write(&file_list, listed.join("\n"))
+        .with_context(|| format!("failed to write {}", file_list.display()))?;
+    let args: [OsString; 13] = [
+        "check".into(),
+        "--no-cache".into(),
+        "--no-progress".into(),
+        "--use-version".into(),
+        format!("java-{}", manifest.package.java).into(),
+        "-R".into(),
+        PMD_RULESET.into(),
+        "-f".into(),
+        "xml".into(),
+        "-r".into(),
+        report.clone().into(),
+        "--file-list".into(),
+        file_list.into(),
+    ];
+    let xml = run_tool(
+        project_root,
+        toolchain,
+        &classpath,
+        "PMD",
+        "net.sourceforge.pmd.cli.PmdCli",
+        &args,
+        &report,
+    )?;
+    parse_pmd(&xml, project_root)
+}
+
+/// Resolve SpotBugs and run it over the compiled main classes, with
+/// `classpath` (the compile classpath) to resolve their references.
+pub fn run_spotbugs(
+    gctx: &GlobalContext,
+    project_root: &Path,
+    toolchain: &Toolchain,
+    manifest: &JargoToml,
+    classpath: &[PathBuf],
+) -> Result<Vec<Diagnostic>> {
+    let tool_classpath = resolver::resolve_tool(
+        gctx,
+        &[("com.github.spotbugs", "spotbugs", SPOTBUGS_VERSION)],
+    )?;
+    let report = report_path(project_root, "spotbugs")?;
+    let mut args: Vec<OsString> = vec![
+        "-quiet".into(),
+        "-xml:withMessages".into(),
+        "-output".into(),
+        report.clone().into(),
+    ];
+    if !classpath.is_empty() {
+        let aux = std::env::join_paths(classpath).context("invalid classpath entry")?;
+        args.push("-auxclasspath".into());
+        args.push(aux);
+    }
+    args.push(project_root.join(SourceSet::Main.output_dir()).into());
+    let xml = run_tool(
+        project_root,
+        toolchain,
+        &tool_classpath,
+        "SpotBugs",
+        "edu.umd.cs.findbugs.FindBugs2",
+        &args,
+        &report,
+    )?;
+    parse_spotbugs(&xml, project_root, &manifest.get_base_package())
+}
+
+/// Write every tool's diagnostics, rendered without color, to
+/// `target/lint/report.txt`.
+pub fn write_report(project_root: &Path, diagnostics: &[Diagnostic]) -> Result<PathBuf> {
+    let path = project_root.join(REPORT_DIR).join("report.txt");
+    fs::create_dir_all(project_root.join(REPORT_DIR))
+        .with_context(|| format!("failed to create {}", REPORT_DIR))?;
+    fs::write(&path, render_diagnostics(diagnostics, false))
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// `target/lint/{tool}.xml`, with the report of an earlier run removed.
+fn report_path(project_root: &Path, tool: &str) -> Result<PathBuf> {
+    let dir = project_root.join(REPORT_DIR);
+    fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    let report = dir.join(format!("{}.xml", tool));
+    if report.exists() {
+        fs::remove_file(&report)
+            .with_context(|| format!("failed to remove {}", report.display()))?;
+    }
+    Ok(report)
+}
+
+/// Run `java -cp {classpath} {main} {args}` in `project_root` and read the
+/// XML report it writes to `report`. The tools exit non-zero when they find
+/// violations, so only a missing report means the tool failed.
+fn run_tool(
+    project_root: &Path,
+    toolchain: &Toolchain,
+    classpath: &[PathBuf],
+    name: &str,
+    main: &str,
+    args: &[OsString],
+    report: &Path,
+) -> Result<String> {
+    let classpath = std::env::join_paths(classpath).context("invalid classpath entry")?;
+    let output = Command::new(toolchain.java())
+        .arg("-cp")
+        .arg(&classpath)
+        .arg(main)
+        .args(args)
+        .current_dir(project_root)
+        .output()
+        .with_context(|| format!("failed to run {}", toolchain.java().display()))?;
+    let Ok(xml) = fs::read_to_string(report) else {
+        bail!(
+            "{} failed:\n{}",
+            name,
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        );
+    };
+    Ok(xml)
+}
+
+/// Source lines of the files a report names, read once per file, so
+/// diagnostics can echo the offending line like javac does.
+struct SourceLines<'a> {
+    project_root: &'a Path,
+    files: HashMap<String, Vec<String>>,
+}
+
+impl<'a> SourceLines<'a> {
+    fn new(project_root: &'a Path) -> Self {
+        Self {
+            project_root,
+            files: HashMap::new(),
+        }
+    }
+
+    /// A diagnostic at `file:line:column`, relative to the project root.
+    /// Columns past the end of the line, as when a tool expands tabs to its
+    /// own width, are dropped.
+    fn diagnostic(
+        &mut self,
+        severity: Severity,
+        message: String,
+        file: Option<String>,
+        line: Option<usize>,
+        column: Option<usize>,
+    ) -> Diagnostic {
+        let source_line = match (&file, line) {
+            (Some(file), Some(line)) => {
+                let root = self.project_root;
+                let lines = self.files.entry(file.clone()).or_insert_with(|| {
+                    fs::read_to_string(root.join(file))
+                        .map(|s| s.lines().map(str::to_string).collect())
+                        .unwrap_or_default()
+                });
+                line.checked_sub(1).and_then(|i| lines.get(i)).cloned()
+            }
+            _ => None,
+        };
+        let column = column.filter(|&c| {
+            source_line
+                .as_ref()
+                .is_some_and(|s| c <= s.chars().count() + 1)
+        });
+        Diagnostic {
+            severity,
+            message,
+            file,
+            line,
+            column,
+            source_line,
+            notes: Vec::new(),
+        }
+    }
+}
+
+/// `name` relative to `project_root`, when it is under it.
+fn relative(project_root: &Path, name: &str) -> String {
+    let path = Path::new(name);
+    let rel = path.strip_prefix(project_root).unwrap_or(path);
+    rel.to_string_lossy().into_owned()
+}
+
+/// Parse a Checkstyle XML report into diagnostics. Violations of severity
+/// `ignore` are dropped.
+fn parse_checkstyle(xml: &str, project_root: &Path) -> Result<Vec<Diagnostic>> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut diagnostics = Vec::new();
+    let mut file: Option<String> = None;
+    let mut sources = SourceLines::new(project_root);
+
+    loop {
+        let (e, empty) = match reader.read_event() {
+            Ok(Event::Start(e)) => (e, false),
+            Ok(Event::Empty(e)) => (e, true),
+            Ok(Event::End(e)) => {
+                if e.local_name().as_ref() == b"file" {
+                    file = None;
+                }
+                continue;
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => bail!("XML parse error: {}", e),
+            _ => continue,
+        };
+        match e.local_name().as_ref() {
+            b"file" if !empty => file = Some(relative(project_root, &attribute(&e, "name")?)),
+            b"error" => {
+                let severity = match attribute(&e, "severity")?.as_str() {
+                    "error" => Severity::Error,
+                    "warning" => Severity::Warning,
+                    "info" => Severity::Note,
+                    _ => continue,
+                };
+                let check = attribute(&e, "source")?;
+                let check = check.rsplit('.').next().unwrap_or_default();
+                let check = check.strip_suffix("Check").unwrap_or(check);
+                diagnostics.push(sources.diagnostic(
+                    severity,
+                    format!("{} [{}]", attribute(&e, "message")?, check),
+                    file.clone(),
+                    attribute(&e, "line")?.parse().ok(),
+                    attribute(&e, "column")?.parse().ok(),
+                ));
+            }
+            _ => {}
+        }
+    }
+    Ok(diagnostics)
+}
+
+/// Parse a PMD XML report into diagnostics. Priorities 1 and 2 are errors,
+/// 3 and 4 warnings, and 5 notes.
+fn parse_pmd(xml: &str, project_root: &Path) -> Result<Vec<Diagnostic>> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut diagnostics = Vec::new();
+    let mut file: Option<String> = None;
+    let mut sources = SourceLines::new(project_root);
+    // (severity, rule, line, column) of the <violation> whose text is next
+    let mut violation: Option<(Severity, String, Option<usize>, Option<usize>)> = None;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => match e.local_name().as_ref() {
+                b"file" => file = Some(relative(project_root, &attribute(&e, "name")?)),
+                b"violation" => {
+                    let severity = match attribute(&e, "priority")?.as_str() {
+                        "1" | "2" => Severity::Error,
+                        "3" | "4" => Severity::Warning,
+                        _ => Severity::Note,
+                    };
+                    violation = Some((
+                        severity,
+                        attribute(&e, "rule")?,
+                        attribute(&e, "beginline")?.parse().ok(),
+                        attribute(&e, "begincolumn")?.parse().ok(),
+                    ));
+                }
+                _ => {}
+            },
+            Ok(Event::Text(text)) => {
+                if let Some((severity, rule, line, column)) = violation.take() {
+                    let text = text.unescape().context("malformed text in PMD report")?;
+                    diagnostics.push(sources.diagnostic(
+                        severity,
+                        format!("{} [{}]", text.trim(), rule),
+                        file.clone(),
+                        line,
+                        column,
+                    ));
+                }
+            }
+            Ok(Event::End(e)) if e.local_name().as_ref() == b"file" => file = None,
+            Ok(Event::Eof) => break,
+            Err(e) => bail!("XML parse error: {}", e),
+            _ => {}
+        }
+    }
+    Ok(diagnostics)
+}
+
+/// Parse a SpotBugs XML report into diagnostics at each bug's primary
+/// source line, else its class. SpotBugs names sources by package path, so
+/// those under `base_package` are mapped back into `src/`. Priority 1 (high)
+/// is an error, 2 a warning, and 3 a note.
+fn parse_spotbugs(xml: &str, project_root: &Path, base_package: &str) -> Result<Vec<Diagnostic>> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let base_dir = format!("{}/", base_package.replace('.', "/"));
+    let source_file = |sourcepath: String| match sourcepath.strip_prefix(&base_dir) {
+        Some(rel) => format!("{}/{}", SourceSet::Main.source_dir(), rel),
+        None => sourcepath,
+    };
+
+    struct Bug {
+        severity: Severity,
+        kind: String,
+        message: String,
+        location: Option<(String, Option<usize>)>,
+        class_location: Option<(String, Option<usize>)>,
+    }
+
+    let mut diagnostics = Vec::new();
+    let mut sources = SourceLines::new(project_root);
+    let mut stack: Vec<Vec<u8>> = Vec::new();
+    let mut bug: Option<Bug> = None;
+
+    loop {
+        let (e, empty) = match reader.read_event() {
+            Ok(Event::Start(e)) => (e, false),
+            Ok(Event::Empty(e)) => (e, true),
+            Ok(Event::Text(text)) => {
+                if let (Some(bug), Some(b"LongMessage")) =
+                    (bug.as_mut(), stack.last().map(Vec::as_slice))
+                {
+                    bug.message = text
+                        .unescape()
+                        .context("malformed text in SpotBugs report")?
+                        .into_owned();
+                }
+                continue;
+            }
+            Ok(Event::End(e)) => {
+                stack.pop();
+                if e.local_name().as_ref() == b"BugInstance" {
+                    if let Some(bug) = bug.take() {
+                        let (file, line) = match bug.location.or(bug.class_location) {
+                            Some((file, line)) => (Some(file), line),
+                            None => (None, None),
+                        };
+                        diagnostics.push(sources.diagnostic(
+                            bug.severity,
+                            format!("{} [{}]", bug.message, bug.kind),
+                            file,
+                            line,
+                            None,
+                        ));
+                    }
+                }
+                continue;
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => bail!("XML parse error: {}", e),
+            _ => continue,
+        };
+        let name = e.local_name().as_ref().to_vec();
+        match name.as_slice() {
+            b"BugInstance" => {
+                bug = Some(Bug {
+                    severity: match attribute(&e, "priority")?.as_str() {
+                        "1" => Severity::Error,
+                        "2" => Severity::Warning,
+                        _ => Severity::Note,
+                    },
+                    kind: attribute(&e, "type")?,
+                    message: String::new(),
+                    location: None,
+                    class_location: None,
+                });
+            }
+            b"SourceLine" => {
+                if let Some(bug) = bug.as_mut() {
+                    let sourcepath = attribute(&e, "sourcepath")?;
+                    let location = (
+                        source_file(sourcepath),
+                        attribute(&e, "start")?.parse().ok(),
+                    );
+                    match stack.last().map(Vec::as_slice) {
+                        Some(b"BugInstance") if bug.location.is_none() => {
+                            bug.location = Some(location)
+                        }
+                        Some(b"Class") if bug.class_location.is_none() => {
+                            bug.class_location = Some(location)
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+        if !empty {
+            stack.push(name);
+        }
+    }
+    Ok(diagnostics)
+}
+
+fn attribute(e: &BytesStart, key: &str) -> Result<String> {
+    for attr in e.attributes() {
+        let attr = attr.context("malformed attribute in lint report")?;
+        if attr.key.local_name().as_ref() == key.as_bytes() {
+            let value = attr
+                .unescape_value()
+                .context("non-UTF8 attribute in lint report")?;
+            return Ok(value.into_owned());
+        }
+    }
+    Ok(String::new())
+}
+
+/// How many of `diagnostics` are at or above `fail_on`.
+pub fn failing(diagnostics: &[Diagnostic], fail_on: LintSeverity) -> usize {
+    diagnostics
+        .iter()
+        .filter(|d| {
+            let severity = match d.severity {
+                Severity::Error => LintSeverity::Error,
+                Severity::Warning => LintSeverity::Warning,
+                Severity::Note => LintSeverity::Info,
+            };
+            severity >= fail_on
+        })
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src/demo")).unwrap();
+        fs::write(
+            dir.path().join("src/demo/Main.java"),
+            "package demo;\n\nimport java.util.*;\n\nclass Main {\n    int Bad_name;\n}\n",
+        )
+        .unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_parse_checkstyle() {
+        let dir = project();
+        let root = dir.path();
+        let xml = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<checkstyle version="10.17.0">
+<file name="{root}/src/demo/Main.java">
+<error line="3" column="17" severity="warning" message="Using the &apos;.*&apos; form of import should be avoided - java.util.*." source="com.puppycrawl.tools.checkstyle.checks.imports.AvoidStarImportCheck"/>
+<error line="6" column="9" severity="error" message="Name &apos;Bad_name&apos; must match pattern." source="com.puppycrawl.tools.checkstyle.checks.naming.MemberNameCheck"/>
+<error line="7" severity="ignore" message="ignored" source="com.puppycrawl.tools.checkstyle.checks.whitespace.FileTabCharacterCheck"/>
+</file>
+<file name="{root}/src/demo/Clean.java">
+</file>
+</checkstyle>
+"#,
+            root = root.display()
+        );
+
+        let diagnostics = parse_checkstyle(&xml, root).unwrap();
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(
+            diagnostics[0],
+            Diagnostic {
+                severity: Severity::Warning,
+                message: "Using the '.*' form of import should be avoided - java.util.*. \
+                          [AvoidStarImport]"
+                    .to_string(),
+                file: Some("src/demo/Main.java".to_string()),
+                line: Some(3),
+                column: Some(17),
+                source_line: Some("import java.util.*;".to_string()),
+                notes: Vec::new(),
+            }
+        );
+        assert_eq!(diagnostics[1].severity, Severity::Error);
+        assert_eq!(
+            diagnostics[1].source_line.as_deref(),
+            Some("    int Bad_name;")
+        );
+    }
+
+    #[test]
+    fn test_parse_pmd() {
+        let dir = project();
+        let root = dir.path();
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<pmd xmlns="http://pmd.sourceforge.net/report/2.0.0" version="7.4.0">
+<file name="src/demo/Main.java">
+<violation beginline="6" endline="6" begincolumn="9" endcolumn="17" rule="UnusedPrivateField" ruleset="Best Practices" package="demo" class="Main" priority="3">
+Avoid unused private fields such as &apos;Bad_name&apos;.
+</violation>
+<violation beginline="5" endline="7" begincolumn="1" endcolumn="1" rule="ClassWithOnlyPrivateConstructorsShouldBeFinal" ruleset="Design" priority="1">
+A class which only has private constructors should be final
+</violation>
+</file>
+</pmd>
+"#;
+        let diagnostics = parse_pmd(xml, root).unwrap();
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(
+            diagnostics[0],
+            Diagnostic {
+                severity: Severity::Warning,
+                message: "Avoid unused private fields such as 'Bad_name'. [UnusedPrivateField]"
+                    .to_string(),
+                file: Some("src/demo/Main.java".to_string()),
+                line: Some(6),
+                column: Some(9),
+                source_line: Some("    int Bad_name;".to_string()),
+                notes: Vec::new(),
+            }
+        );
+        assert_eq!(diagnostics[1].severity, Severity::Error);
+        assert_eq!(diagnostics[1].line, Some(5));
+    }
+
+    #[test]
+    fn test_parse_spotbugs() {
+        let dir = project();
+        let root = dir.path();
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<BugCollection version="4.8.6" sequence="0" timestamp="0" analysisTimestamp="0" release="">
+<Project projectName=""><Jar>target/classes</Jar></Project>
+<BugInstance type="URF_UNREAD_FIELD" priority="2" rank="18" abbrev="URF" category="PERFORMANCE">
+<ShortMessage>Unread field</ShortMessage>
+<LongMessage>Unread field: demo.Main.Bad_name</LongMessage>
+<Class classname="demo.Main" primary="true">
+<SourceLine classname="demo.Main" start="5" end="7" sourcefile="Main.java" sourcepath="demo/Main.java"/>
+</Class>
+<Field classname="demo.Main" name="Bad_name" signature="I" isStatic="false" primary="true">
+<SourceLine classname="demo.Main" sourcefile="Main.java" sourcepath="demo/Main.java"/>
+</Field>
+<SourceLine classname="demo.Main" primary="true" start="6" end="6" sourcefile="Main.java" sourcepath="demo/Main.java"/>
+</BugInstance>
+<BugInstance type="SE_BAD_FIELD" priority="1" rank="14" abbrev="Se" category="BAD_PRACTICE">
+<LongMessage>Non-transient field in serializable class demo.Main</LongMessage>
+<Class classname="demo.Main" primary="true">
+<SourceLine classname="demo.Main" start="5" end="7" sourcefile="Main.java" sourcepath="demo/Main.java"/>
+</Class>
+</BugInstance>
+<Errors errors="0" missingClasses="0"></Errors>
+</BugCollection>
+"#;
+        let diagnostics = parse_spotbugs(xml, root, "demo").unwrap();
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(
+            diagnostics[0],
+            Diagnostic {
+                severity: Severity::Warning,
+                message: "Unread field: demo.Main.Bad_name [URF_UNREAD_FIELD]".to_string(),
+                file: Some("src/Main.java".to_string()),
+                line: Some(6),
+                column: None,
+                source_line: None,
+                notes: Vec::new(),
+            }
+        );
+        assert_eq!(diagnostics[1].severity, Severity::Error);
+        assert_eq!(diagnostics[1].file.as_deref(), Some("src/Main.java"));
+        assert_eq!(diagnostics[1].line, Some(5));
+    }
+
+    #[test]
+    fn test_failing() {
+        let diagnostic = |severity| Diagnostic {
+            severity,
+            message: String::new(),
+            file: None,
+            line: None,
+            column: None,
+            source_line: None,
+            notes: Vec::new(),
+        };
+        let diagnostics = [
+            diagnostic(Severity::Error),
+            diagnostic(Severity::Warning),
+            diagnostic(Severity::Warning),
+            diagnostic(Severity::Note),
+        ];
+        assert_eq!(failing(&diagnostics, LintSeverity::Error), 1);
+        assert_eq!(failing(&diagnostics, LintSeverity::Warning), 3);
+        assert_eq!(failing(&diagnostics, LintSeverity::Info), 4);
+    }
+}
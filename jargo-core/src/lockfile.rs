@@ -1,5 +1,6 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::Path;
 
 /// A single resolved dependency entry in Jargo.lock.
@@ -8,8 +9,15 @@ pub struct LockedDependency {
     pub group: String,
     pub artifact: String,
     pub version: String,
-    /// Effective scope: `"compile"` (compile + runtime classpath) or `"runtime"` (runtime only).
+    /// Effective scope: `"compile"` (compile + runtime classpath), `"runtime"` (runtime only),
+    /// `"test"` (test classpaths only, reached through dev-dependencies), or `"it"`
+    /// (integration test classpaths only, reached through it-dependencies).
     pub scope: String,
+    /// The `[features]` that bring the entry in, when only optional
+    /// dependencies reach it. The lock holds every feature's dependencies;
+    /// builds leave out the entries of features not enabled.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
     pub sha256: String,
 }
 
@@ -17,19 +25,112 @@ pub struct LockedDependency {
 ///
 /// TOML format uses `[[dependency]]` array-of-tables:
 /// ```toml
+/// manifest-hash = "3f2a..."
+///
 /// [[dependency]]
 /// group = "com.google.guava"
 /// artifact = "guava"
 /// version = "33.0.0-jre"
 /// sha256 = "abcdef..."
 /// ```
+///
+/// The lock file of a workspace holds one `[[member.{name}.dependency]]`
+/// array per member instead.
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct LockFile {
+    /// Hash of the dependency sections of Jargo.toml the entries were
+    /// resolved from. Lock files written before it was added have none.
+    #[serde(
+        rename = "manifest-hash",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub manifest_hash: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependency: Vec<LockedDependency>,
+    /// Entries of each workspace member, keyed by member name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub member: BTreeMap<String, MemberLock>,
+}
+
+/// The locked dependencies of one workspace member.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct MemberLock {
+    #[serde(
+        rename = "manifest-hash",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub manifest_hash: Option<String>,
     #[serde(default)]
     pub dependency: Vec<LockedDependency>,
 }
 
 impl LockFile {
+    /// Entries of the workspace `member`, or of the package when `None`.
+    pub fn entries(&self, member: Option<&str>) -> &[LockedDependency] {
+        match member {
+            Some(name) => self
+                .member
+                .get(name)
+                .map(|m| &m.dependency[..])
+                .unwrap_or_default(),
+            None => &self.dependency,
+        }
+    }
+
+    /// Replace the entries returned by [`LockFile::entries`].
+    pub fn set_entries(&mut self, member: Option<&str>, entries: Vec<LockedDependency>) {
+        match member {
+            Some(name) => {
+                self.member.entry(name.to_string()).or_default().dependency = entries;
+            }
+            None => self.dependency = entries,
+        }
+    }
+
+    /// The manifest hash the entries of `member` (or of the package when
+    /// `None`) were resolved from.
+    pub fn manifest_hash(&self, member: Option<&str>) -> Option<&str> {
+        match member {
+            Some(name) => self.member.get(name)?.manifest_hash.as_deref(),
+            None => self.manifest_hash.as_deref(),
+        }
+    }
+
+    /// Replace the hash returned by [`LockFile::manifest_hash`].
+    pub fn set_manifest_hash(&mut self, member: Option<&str>, hash: String) {
+        match member {
+            Some(name) => {
+                self.member
+                    .entry(name.to_string())
+                    .or_default()
+                    .manifest_hash = Some(hash);
+            }
+            None => self.manifest_hash = Some(hash),
+        }
+    }
+
+    /// Artifacts locked at more than one version across workspace members:
+    /// `groupId:artifactId` → version → members locking it.
+    pub fn duplicates(&self) -> BTreeMap<String, BTreeMap<String, Vec<String>>> {
+        let mut versions: BTreeMap<String, BTreeMap<String, Vec<String>>> = BTreeMap::new();
+        for (name, lock) in &self.member {
+            for entry in &lock.dependency {
+                let members = versions
+                    .entry(format!("{}:{}", entry.group, entry.artifact))
+                    .or_default()
+                    .entry(entry.version.clone())
+                    .or_default();
+                if !members.contains(name) {
+                    members.push(name.clone());
+                }
+            }
+        }
+        versions.retain(|_, by_version| by_version.len() > 1);
+        versions
+    }
+
     /// Read and parse a Jargo.lock file.
     pub fn read(path: &Path) -> Result<Self> {
         let content = std::fs::read_to_string(path)
@@ -73,6 +174,7 @@ mod tests {
                     artifact: "guava".to_string(),
                     version: "33.0.0-jre".to_string(),
                     scope: "compile".to_string(),
+                    features: Vec::new(),
                     sha256: "abc123".to_string(),
                 },
                 LockedDependency {
@@ -80,9 +182,11 @@ mod tests {
                     artifact: "commons-lang3".to_string(),
                     version: "3.14.0".to_string(),
                     scope: "runtime".to_string(),
+                    features: vec!["text".to_string()],
                     sha256: "def456".to_string(),
                 },
             ],
+            ..LockFile::default()
         };
 
         lock.write(&path).unwrap();
@@ -101,8 +205,10 @@ mod tests {
                 artifact: "foo".to_string(),
                 version: "1.0.0".to_string(),
                 scope: "compile".to_string(),
+                features: Vec::new(),
                 sha256: "deadbeef".to_string(),
             }],
+            ..LockFile::default()
         };
 
         let s = toml::to_string_pretty(&lock).unwrap();
@@ -114,12 +220,84 @@ mod tests {
         assert!(s.contains("sha256 = \"deadbeef\""));
     }
 
+    #[test]
+    fn test_workspace_lockfile_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("Jargo.lock");
+        let entry = LockedDependency {
+            group: "com.example".to_string(),
+            artifact: "foo".to_string(),
+            version: "1.0.0".to_string(),
+            scope: "compile".to_string(),
+            features: Vec::new(),
+            sha256: "deadbeef".to_string(),
+        };
+
+        let mut lock = LockFile::default();
+        lock.set_entries(Some("app"), vec![entry.clone()]);
+        lock.set_manifest_hash(Some("app"), "cafe".to_string());
+        lock.set_entries(Some("core"), Vec::new());
+        lock.write(&path).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("[[member.app.dependency]]"));
+        assert!(!content.contains("[[dependency]]"));
+        assert!(content.contains("manifest-hash = \"cafe\""));
+
+        let loaded = LockFile::read(&path).unwrap();
+        assert_eq!(loaded.entries(Some("app")), [entry]);
+        assert_eq!(loaded.manifest_hash(Some("app")), Some("cafe"));
+        assert_eq!(loaded.manifest_hash(Some("core")), None);
+        assert_eq!(loaded.manifest_hash(None), None);
+        assert!(loaded.entries(Some("core")).is_empty());
+        assert!(loaded.entries(Some("missing")).is_empty());
+        assert!(loaded.entries(None).is_empty());
+    }
+
     #[test]
     fn test_read_nonexistent_file_errors() {
         let result = LockFile::read(Path::new("/nonexistent/Jargo.lock"));
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_duplicates() {
+        let toml_str = r#"
+[[member.app.dependency]]
+group = "com.google.guava"
+artifact = "guava"
+version = "33.0.0-jre"
+scope = "compile"
+sha256 = "a"
+
+[[member.app.dependency]]
+group = "org.slf4j"
+artifact = "slf4j-api"
+version = "2.0.12"
+scope = "compile"
+sha256 = "b"
+
+[[member.core.dependency]]
+group = "com.google.guava"
+artifact = "guava"
+version = "32.1.3-jre"
+scope = "compile"
+sha256 = "c"
+
+[[member.util.dependency]]
+group = "org.slf4j"
+artifact = "slf4j-api"
+version = "2.0.12"
+scope = "runtime"
+sha256 = "b"
+"#;
+        let lock: LockFile = toml::from_str(toml_str).unwrap();
+        let duplicates = lock.duplicates();
+        assert_eq!(duplicates.len(), 1);
+        let guava = &duplicates["com.google.guava:guava"];
+        assert_eq!(guava["32.1.3-jre"], ["core"]);
+        assert_eq!(guava["33.0.0-jre"], ["app"]);
+    }
+
     #[test]
     fn test_parse_lock_toml_directly() {
         let toml_str = r#"
@@ -0,0 +1,42 @@
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::fmt::format::FmtSpan;
+
+use crate::shell::Verbosity;
+
+/// The most detailed `tracing` events shown at `verbosity`: debug events
+/// (timings and other details) only with `-vv`.
+pub fn max_level(verbosity: Verbosity) -> LevelFilter {
+    match verbosity {
+        Verbosity::Quiet => LevelFilter::ERROR,
+        Verbosity::Normal => LevelFilter::WARN,
+        Verbosity::Verbose => LevelFilter::INFO,
+        Verbosity::VeryVerbose => LevelFilter::DEBUG,
+    }
+}
+
+/// Install the process-wide `tracing` subscriber, writing events to stderr
+/// so stdout stays free for command output and JSON messages. Closing a
+/// span logs how long it took, which is where `-vv` timings come from. A
+/// second call keeps the first subscriber.
+pub fn init(verbosity: Verbosity, color: bool) {
+    let _ = tracing_subscriber::fmt()
+        .with_max_level(max_level(verbosity))
+        .with_ansi(color)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(std::io::stderr)
+        .try_init();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_level() {
+        assert_eq!(max_level(Verbosity::Normal), LevelFilter::WARN);
+        assert_eq!(max_level(Verbosity::VeryVerbose), LevelFilter::DEBUG);
+        assert_eq!(Verbosity::from_flags(2, false), Verbosity::VeryVerbose);
+        assert_eq!(Verbosity::from_flags(1, true), Verbosity::Quiet);
+        assert_eq!(Verbosity::from_flags(0, false).flags(), [] as [&str; 0]);
+    }
+}
@@ -0,0 +1,82 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cache::{artifact_filename, group_to_path};
+
+/// The local Maven repository, `~/.m2/repository`, which Maven and Gradle's
+/// `mavenLocal()` resolve from before any remote repository.
+pub fn local_repository() -> Result<PathBuf> {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .context("could not determine home directory")?;
+    Ok(PathBuf::from(home).join(".m2").join("repository"))
+}
+
+/// Copy `jar` and write `pom` into `repository` under the Maven layout,
+/// `{group path}/{artifact}/{version}/{artifact}-{version}.{jar,pom}`,
+/// replacing an earlier install of the same version. Returns the version
+/// directory.
+pub fn install(
+    repository: &Path,
+    group: &str,
+    artifact: &str,
+    version: &str,
+    jar: &Path,
+    pom: &str,
+) -> Result<PathBuf> {
+    let dir = repository
+        .join(group_to_path(group))
+        .join(artifact)
+        .join(version);
+    fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+
+    let jar_dest = dir.join(artifact_filename(artifact, version, "jar"));
+    fs::copy(jar, &jar_dest)
+        .with_context(|| format!("failed to copy {} to {}", jar.display(), jar_dest.display()))?;
+    let pom_dest = dir.join(artifact_filename(artifact, version, "pom"));
+    fs::write(&pom_dest, pom).with_context(|| format!("failed to write {}", pom_dest.display()))?;
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_install() {
+        let tmp = tempfile::tempdir().unwrap();
+        let jar = tmp.path().join("parser.jar");
+        fs::write(&jar, b"PK").unwrap();
+        let repository = tmp.path().join("repository");
+
+        let dir = install(
+            &repository,
+            "com.example",
+            "parser",
+            "1.2.0",
+            &jar,
+            "<project/>",
+        )
+        .unwrap();
+        assert_eq!(dir, repository.join("com/example/parser/1.2.0"));
+        assert_eq!(fs::read(dir.join("parser-1.2.0.jar")).unwrap(), b"PK");
+        assert_eq!(
+            fs::read_to_string(dir.join("parser-1.2.0.pom")).unwrap(),
+            "<project/>"
+        );
+
+        // Reinstalling the same version replaces it
+        fs::write(&jar, b"PK2").unwrap();
+        install(
+            &repository,
+            "com.example",
+            "parser",
+            "1.2.0",
+            &jar,
+            "<project/>",
+        )
+        .unwrap();
+        assert_eq!(fs::read(dir.join("parser-1.2.0.jar")).unwrap(), b"PK2");
+    }
+}
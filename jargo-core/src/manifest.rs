@@ -1,8 +1,12 @@
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+use crate::imports::DEFAULT_IMPORT_ORDER;
+use crate::test_runner::TESTNG_VERSION;
+use crate::workspace::Workspace;
 
 /// Dependency scope: determines which classpaths a dep appears on.
 #[derive(Debug, Clone, PartialEq, Default)]
@@ -24,14 +28,30 @@ pub struct Dependency {
     pub expose: bool,
 }
 
-/// Expanded dependency form: `{ version = "x", scope = "runtime", expose = true }`
+/// A dependency on another member of the workspace, declared by name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemberDependency {
+    pub name: String,
+    /// `runtime` keeps the member's JAR off the compile classpath.
+    pub scope: Scope,
+}
+
+/// Expanded dependency form: `{ version = "x", scope = "runtime", expose = true }`,
+/// or `{ workspace = true }` to take the version from `[workspace.dependencies]`.
+/// `optional = true` leaves the dependency out unless a `[features]` entry
+/// naming it is enabled.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DependencySpec {
-    pub version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub scope: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub expose: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub optional: Option<bool>,
 }
 
 /// Raw TOML value for a dependency entry. Handles both:
@@ -49,6 +69,9 @@ pub enum DependencyValue {
 pub struct PackageManifest {
     pub name: String,
     pub version: String,
+    /// Maven groupId the package is published under; defaults to base-package.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
     #[serde(rename = "type", default = "default_type")]
     pub project_type: String,
     pub java: String,
@@ -56,6 +79,58 @@ pub struct PackageManifest {
     pub base_package: Option<String>,
     #[serde(rename = "main-class", skip_serializing_if = "Option::is_none")]
     pub main_class: Option<String>,
+    /// One-line summary for the published POM.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// SPDX license expression, e.g. `"Apache-2.0"` or `"MIT OR Apache-2.0"`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub homepage: Option<String>,
+    /// URL of the source repository.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+    /// `"Name <email>"` entries.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<String>,
+}
+
+impl PackageManifest {
+    /// The feature release `java` names: `"21"` → 21, legacy `"1.8"` → 8.
+    pub fn release(&self) -> Result<u32> {
+        let java = self.java.strip_prefix("1.").unwrap_or(&self.java);
+        match java.parse() {
+            Ok(release) => Ok(release),
+            Err(_) => bail!("invalid `java` in [package]: `{}`", self.java),
+        }
+    }
+
+    /// Check that this package may put `dep` on its classpath. Classes built
+    /// for a newer release than `--release {java}` cannot be loaded, so a
+    /// dependency's `java` must be the same or older.
+    pub fn check_dependency_release(&self, dep: &PackageManifest) -> Result<()> {
+        if dep.release()? > self.release()? {
+            bail!(
+                "`{}` (java {}) cannot depend on `{}` (java {}); members may only \
+                 depend on members with the same or an older `java`",
+                self.name,
+                self.java,
+                dep.name,
+                dep.java
+            );
+        }
+        Ok(())
+    }
+}
+
+/// One `[[bin]]` entry: an entry point of an app.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BinTarget {
+    /// Selected with `--bin`; names the launcher or binary.
+    pub name: String,
+    /// Relative to base-package, like `package.main-class`.
+    #[serde(rename = "main-class")]
+    pub main_class: String,
 }
 
 fn default_type() -> String {
@@ -67,381 +142,2812 @@ fn default_type() -> String {
 pub struct RunConfig {
     #[serde(rename = "jvm-args", default, skip_serializing_if = "Vec::is_empty")]
     pub jvm_args: Vec<String>,
+    /// Java agents as `groupId:artifactId:version`, attached with `-javaagent:`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub agents: Vec<String>,
+    /// Port the JDWP agent listens on with `jargo run --debug`.
+    #[serde(rename = "debug-port", skip_serializing_if = "Option::is_none")]
+    pub debug_port: Option<u16>,
+    /// Hold the program until a debugger attaches.
+    #[serde(rename = "debug-suspend", default)]
+    pub debug_suspend: bool,
+    /// Load `.env` from the project root for `jargo run` and `jargo test`.
+    #[serde(default)]
+    pub dotenv: bool,
+    /// Passed to `jargo run` as `-D{key}={value}`, after `jvm-args`.
+    #[serde(
+        rename = "system-properties",
+        default,
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    pub system_properties: BTreeMap<String, String>,
+    /// `[run.profiles.<name>]` launch configurations for `jargo run --profile`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, RunProfile>,
 }
 
-/// Top-level Jargo.toml structure for generation.
-#[derive(Debug, Serialize, Deserialize)]
-pub struct JargoToml {
-    pub package: PackageManifest,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub run: Option<RunConfig>,
-    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
-    pub dependencies: HashMap<String, DependencyValue>,
+/// A named `[run.profiles.<name>]` table, layered over `[run]` and `[env]`.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct RunProfile {
+    /// Appended after `[run] jvm-args`.
+    #[serde(rename = "jvm-args", default, skip_serializing_if = "Vec::is_empty")]
+    pub jvm_args: Vec<String>,
+    /// Overrides `[env]` entries of the same name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Overrides `[run] system-properties` entries of the same name.
     #[serde(
-        rename = "dev-dependencies",
+        rename = "system-properties",
         default,
-        skip_serializing_if = "HashMap::is_empty"
+        skip_serializing_if = "BTreeMap::is_empty"
     )]
-    pub dev_dependencies: HashMap<String, DependencyValue>,
+    pub system_properties: BTreeMap<String, String>,
 }
 
-impl JargoToml {
-    pub fn new_app(name: &str) -> Self {
-        Self {
-            package: PackageManifest {
-                name: name.to_string(),
-                version: "0.1.0".to_string(),
-                project_type: "app".to_string(),
-                java: "21".to_string(),
-                base_package: None,
-                main_class: None,
-            },
-            run: None,
-            dependencies: HashMap::new(),
-            dev_dependencies: HashMap::new(),
+/// Represents the optional [build] section of Jargo.toml.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct BuildConfig {
+    /// Write `META-INF/build-info.properties` into the compiled output.
+    #[serde(rename = "build-info", default)]
+    pub build_info: bool,
+    /// Heap/metaspace limits for the `javac` JVM.
+    #[serde(rename = "javac-memory", skip_serializing_if = "Option::is_none")]
+    pub javac_memory: Option<JavacMemory>,
+    /// Directory build output goes to instead of `target/`, relative to the
+    /// project root. `--target-dir` and `JARGO_TARGET_DIR` win over it.
+    #[serde(rename = "target-dir", skip_serializing_if = "Option::is_none")]
+    pub target_dir: Option<String>,
+    /// The compiler every source set is compiled with. Defaults to `javac`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compiler: Option<CompilerKind>,
+}
+
+/// A `[build] compiler` backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompilerKind {
+    /// The JDK's `javac`.
+    #[default]
+    Javac,
+    /// The Eclipse compiler, fetched from Maven Central and run on the
+    /// toolchain's `java`, so a JRE is enough.
+    Ecj,
+    /// `kotlinc` for `.kt` sources, with `javac` compiling any `.java`
+    /// sources next to them.
+    Kotlinc,
+}
+
+impl CompilerKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            CompilerKind::Javac => "javac",
+            CompilerKind::Ecj => "ecj",
+            CompilerKind::Kotlinc => "kotlinc",
         }
     }
 
-    pub fn new_lib(name: &str, base_package: &str) -> Self {
-        Self {
-            package: PackageManifest {
-                name: name.to_string(),
-                version: "0.1.0".to_string(),
-                project_type: "lib".to_string(),
-                java: "21".to_string(),
-                base_package: Some(base_package.to_string()),
-                main_class: None,
-            },
-            run: None,
-            dependencies: HashMap::new(),
-            dev_dependencies: HashMap::new(),
+    /// The tool a toolchain must provide to compile with this backend.
+    pub fn jdk_tool(self) -> &'static str {
+        match self {
+            CompilerKind::Javac => "javac",
+            CompilerKind::Ecj | CompilerKind::Kotlinc => "java",
         }
     }
+}
 
-    pub fn to_toml_string(&self) -> Result<String, toml::ser::Error> {
-        toml::to_string_pretty(self)
+/// Represents the optional [javac] section of Jargo.toml: the warnings every
+/// compile reports.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct JavacConfig {
+    /// `-Xlint` keys to enable (`"all"`, `"unchecked"`) or, prefixed with
+    /// `-`, disable (`"-serial"`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lint: Vec<String>,
+    /// Fail the compile on any warning (`-Werror`). Defaults to `false`.
+    #[serde(rename = "deny-warnings", default)]
+    pub deny_warnings: bool,
+}
+
+/// Raw TOML value for `[build] javac-memory`. Handles both:
+///   `javac-memory = "large"`  (Preset)
+///   `javac-memory = { heap = "4g", metaspace = "1g" }`  (Explicit)
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum JavacMemory {
+    Preset(String),
+    Explicit(JavacMemorySpec),
+}
+
+/// Expanded `javac-memory` form with explicit JVM sizes (e.g. `"512m"`, `"4g"`).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JavacMemorySpec {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heap: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metaspace: Option<String>,
+}
+
+/// Represents the optional [dist] section of Jargo.toml.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct DistConfig {
+    /// Environment variables exported by the launcher scripts.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Shell fragment inserted into `bin/{name}` just before `java` runs.
+    #[serde(rename = "unix-prelude", skip_serializing_if = "Option::is_none")]
+    pub unix_prelude: Option<String>,
+    /// Batch fragment inserted into `bin/{name}.bat` just before `java` runs.
+    #[serde(rename = "windows-prelude", skip_serializing_if = "Option::is_none")]
+    pub windows_prelude: Option<String>,
+    /// Replaces the built-in POSIX launcher template.
+    #[serde(rename = "unix-template", skip_serializing_if = "Option::is_none")]
+    pub unix_template: Option<String>,
+    /// Replaces the built-in Windows launcher template.
+    #[serde(rename = "windows-template", skip_serializing_if = "Option::is_none")]
+    pub windows_template: Option<String>,
+}
+
+/// One `[[codegen]]` entry: a generator run before compiling `src/`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CodegenStep {
+    /// Names the output directory `target/generated-sources/{name}`.
+    pub name: String,
+    /// Shell command; `{out}` is replaced with the output directory.
+    pub command: String,
+    /// Files or directories the generator reads. When set, the step only
+    /// reruns after one of them changes.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inputs: Vec<String>,
+}
+
+/// Represents the optional [verify] section of Jargo.toml.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct VerifyConfig {
+    /// JAR entries `jargo verify-jar` requires (e.g. `"config/app.properties"`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub resources: Vec<String>,
+}
+
+/// Represents the optional [jlink] section of Jargo.toml.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct JlinkConfig {
+    /// Name of the launcher in `target/image/bin/`. Defaults to the package name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub launcher: Option<String>,
+    /// Passed to `jlink --compress` (e.g. `"zip-6"`, or `"2"` before JDK 21).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compression: Option<String>,
+    /// Modules added to the ones jdeps finds, for code loaded reflectively.
+    #[serde(rename = "add-modules", default, skip_serializing_if = "Vec::is_empty")]
+    pub add_modules: Vec<String>,
+}
+
+/// Represents the optional [jpackage] section of Jargo.toml.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct JpackageConfig {
+    /// Installer type (`app-image`, `deb`, `rpm`, `msi`, `exe`, `dmg`, `pkg`).
+    /// Defaults to jpackage's choice for the platform.
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub package_type: Option<String>,
+    /// Icon file, relative to the project root.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vendor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// Represents the optional [native] section of Jargo.toml.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct NativeConfig {
+    /// File name of the binary in `target/native/`. Defaults to the package name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Reachability metadata directories (`reflect-config.json`, ...),
+    /// relative to the project root.
+    #[serde(rename = "config-dirs", default, skip_serializing_if = "Vec::is_empty")]
+    pub config_dirs: Vec<String>,
+    /// Extra arguments passed to `native-image`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+}
+
+/// Represents the optional [repl] section of Jargo.toml.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct ReplConfig {
+    /// Imports run when `jargo repl` starts, e.g. `"myapp.*"` or
+    /// `"static java.lang.Math.*"`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub imports: Vec<String>,
+}
+
+/// Represents the optional [test] section of Jargo.toml.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct TestConfig {
+    /// Test framework, `"junit"` (JUnit Platform) or `"testng"`. Defaults to JUnit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub framework: Option<TestFramework>,
+    /// JUnit tags or tag expressions to include, e.g. `"fast"` or `"!slow"`.
+    /// Empty runs tests regardless of tags.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// JUnit tags or tag expressions to exclude.
+    #[serde(
+        rename = "exclude-tags",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub exclude_tags: Vec<String>,
+    /// Write `target/test-reports/summary.json` after every test run.
+    #[serde(rename = "json-summary", default)]
+    pub json_summary: bool,
+    /// Run test classes and methods in a random order, printing the seed.
+    #[serde(default)]
+    pub shuffle: bool,
+    /// Number of slowest tests listed after each run; `0` turns the list off.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slowest: Option<usize>,
+    /// Extra JVM arguments for the test JVM, after `[run] agents`.
+    #[serde(rename = "jvm-args", default, skip_serializing_if = "Vec::is_empty")]
+    pub jvm_args: Vec<String>,
+    /// `-D` system properties for the test JVM.
+    #[serde(
+        rename = "system-properties",
+        default,
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    pub system_properties: BTreeMap<String, String>,
+    /// Environment variables for the test JVM; override `[env]`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Minimum coverage checked by `jargo test --coverage`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coverage: Option<CoverageConfig>,
+    /// PIT settings for `jargo test --mutation`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mutation: Option<MutationConfig>,
+}
+
+/// Represents the optional [test.mutation] section of Jargo.toml.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct MutationConfig {
+    /// Minimum mutation score (0-100); below it, `jargo test --mutation` fails.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<f64>,
+    /// PIT mutators or mutator groups, e.g. `"STRONGER"`. Empty uses PIT's defaults.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mutators: Vec<String>,
+}
+
+/// The framework `jargo test` runs tests with.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum TestFramework {
+    #[default]
+    #[serde(rename = "junit")]
+    JUnit,
+    #[serde(rename = "testng")]
+    TestNg,
+}
+
+/// Represents the optional [test.coverage] section of Jargo.toml.
+///
+/// Each field is a minimum percentage (0-100) of the JaCoCo counter of the
+/// same name; unset counters are not checked.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct CoverageConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instruction: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<f64>,
+}
+
+/// Represents the optional [bench] section of Jargo.toml.
+///
+/// Unset values fall back to the `@Fork`, `@Warmup`, and `@Measurement`
+/// annotations, then to JMH's defaults.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct BenchConfig {
+    /// Forked JVMs per benchmark; 0 runs in the harness JVM.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forks: Option<u32>,
+    /// Warmup iterations per fork.
+    #[serde(rename = "warmup-iterations", skip_serializing_if = "Option::is_none")]
+    pub warmup_iterations: Option<u32>,
+    /// Measurement iterations per fork.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iterations: Option<u32>,
+    /// Extra JVM arguments for the harness JVM, inherited by the forks.
+    #[serde(rename = "jvm-args", default, skip_serializing_if = "Vec::is_empty")]
+    pub jvm_args: Vec<String>,
+}
+
+/// Represents the optional [container] section of Jargo.toml.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct ContainerConfig {
+    /// Base image. Defaults to `eclipse-temurin:{java}-jre`; `"scratch"` for none.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    /// Image reference to write or push. Defaults to `{name}:{version}`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    /// `os/arch[/variant]` picked from multi-platform base images. Defaults to `linux/amd64`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    /// Absolute directory the app layers are placed under. Defaults to `/app`.
+    #[serde(rename = "app-root", skip_serializing_if = "Option::is_none")]
+    pub app_root: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ports: Vec<u16>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+}
+
+/// Represents the optional [jar] section of Jargo.toml.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct JarConfig {
+    /// Extra `META-INF/MANIFEST.MF` attributes (`"Automatic-Module-Name" = "com.example.app"`).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub manifest: BTreeMap<String, String>,
+    /// Deflate level for JAR entries, 0 (stored) to 9. Defaults to 6.
+    #[serde(rename = "compression-level", skip_serializing_if = "Option::is_none")]
+    pub compression_level: Option<u32>,
+}
+
+/// Represents the optional [shade] section of Jargo.toml.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct ShadeConfig {
+    /// Package relocations applied by `jargo build --uber`
+    /// (`"com.google.common" = "myapp.shaded.guava"`).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub relocate: BTreeMap<String, String>,
+    /// Drop dependency classes the project never references from the uber JAR.
+    #[serde(default)]
+    pub minimize: bool,
+    /// Classes (`com.acme.Plugin`) or packages (`com.acme.plugins.*`) kept by
+    /// `minimize` even when unreferenced, e.g. because they are loaded reflectively.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keep: Vec<String>,
+}
+
+/// Represents the optional [toolchain] section of Jargo.toml.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct ToolchainConfig {
+    /// JDK root directory, absolute or relative to the project root.
+    #[serde(rename = "java-home", skip_serializing_if = "Option::is_none")]
+    pub java_home: Option<String>,
+    /// JDK feature release to look for among installed JDKs (e.g. `"21"`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+/// Represents the optional [ci] section of Jargo.toml.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct CiConfig {
+    /// Steps run by `jargo ci`, in order.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub steps: Option<Vec<CiStep>>,
+}
+
+/// A single `jargo ci` pipeline step. Only steps backed by a working
+/// command exist, so a pipeline never reports a gate it did not run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CiStep {
+    Compile,
+    Test,
+    Fmt,
+    Lint,
+    Doc,
+    Audit,
+}
+
+impl CiStep {
+    /// Default pipeline when `[ci] steps` is not set.
+    pub const DEFAULT: [CiStep; 5] = [
+        CiStep::Fmt,
+        CiStep::Lint,
+        CiStep::Compile,
+        CiStep::Test,
+        CiStep::Audit,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CiStep::Compile => "compile",
+            CiStep::Test => "test",
+            CiStep::Fmt => "fmt",
+            CiStep::Lint => "lint",
+            CiStep::Doc => "doc",
+            CiStep::Audit => "audit",
+        }
     }
+}
 
-    /// Load and parse a Jargo.toml file.
-    pub fn from_file(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
-        let content = fs::read_to_string(path)?;
-        let manifest: JargoToml = toml::from_str(&content)?;
-        Ok(manifest)
+/// Represents the optional [licenses] section of Jargo.toml.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct LicensesConfig {
+    /// Licenses runtime dependencies may use, as SPDX identifiers (e.g.
+    /// `"Apache-2.0"`) or POM license names. Unset allows any license.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow: Option<Vec<String>>,
+}
+
+/// Represents the optional [fmt] section of Jargo.toml.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct FmtConfig {
+    /// Formatter `jargo fmt` runs. Defaults to google-java-format.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub formatter: Option<Formatter>,
+    /// google-java-format style. Defaults to AOSP (4-space indents).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub style: Option<FmtStyle>,
+    /// Eclipse formatter profile XML, relative to the project root; required
+    /// with `formatter = "eclipse"`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config: Option<String>,
+    /// Globs of the files to format, relative to the project root. Defaults
+    /// to every source set directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include: Option<Vec<String>>,
+    /// Globs of files never formatted, e.g. checked-in generated code.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+    /// Import groups, as package prefixes and `*`, of the built-in import
+    /// organizer `jargo fix --imports` uses with `formatter = "eclipse"`.
+    #[serde(rename = "import-order", skip_serializing_if = "Option::is_none")]
+    pub import_order: Option<Vec<String>>,
+}
+
+/// The `[format]` section `[fmt]` replaced, still read so existing manifests
+/// keep their formatting.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct FormatConfig {
+    /// Indentation width: 4 (AOSP style) or 2 (Google style). `[fmt] style`
+    /// takes precedence.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indent: Option<u32>,
+    /// Directories formatted besides the source set directories, unless
+    /// `[fmt] include` is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paths: Option<Vec<String>>,
+}
+
+/// A formatter backend for `jargo fmt`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Formatter {
+    GoogleJavaFormat,
+    PalantirJavaFormat,
+    Eclipse,
+}
+
+/// A google-java-format style.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FmtStyle {
+    /// Android Open Source Project style: 4-space indents.
+    Aosp,
+    /// Google style: 2-space indents.
+    Google,
+}
+
+/// Represents the optional [lint] section of Jargo.toml.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct LintConfig {
+    /// Checkstyle configuration XML, relative to the project root. Defaults
+    /// to `checkstyle.xml` when the project has one, else jargo's built-in
+    /// ruleset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config: Option<String>,
+    /// Checkstyle version. Defaults to `lint::CHECKSTYLE_VERSION`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Lowest severity that fails `jargo lint`. Defaults to `error`.
+    #[serde(rename = "fail-on", skip_serializing_if = "Option::is_none")]
+    pub fail_on: Option<LintSeverity>,
+    /// Run Checkstyle over the sources. Defaults to `true`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checkstyle: Option<bool>,
+    /// Run PMD over the sources. Defaults to `false`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pmd: Option<bool>,
+    /// Run SpotBugs over the compiled main classes. Defaults to `false`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spotbugs: Option<bool>,
+    /// `[lint.header]`: the license header every source file starts with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header: Option<HeaderConfig>,
+}
+
+/// Represents the optional [lint.header] section of Jargo.toml.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HeaderConfig {
+    /// Header text without comment markers; `{year}` stands for the
+    /// copyright year.
+    pub template: String,
+}
+
+/// A static analysis tool run by `jargo lint`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LintTool {
+    Checkstyle,
+    Pmd,
+    Spotbugs,
+}
+
+impl LintTool {
+    pub fn name(self) -> &'static str {
+        match self {
+            LintTool::Checkstyle => "checkstyle",
+            LintTool::Pmd => "pmd",
+            LintTool::Spotbugs => "spotbugs",
+        }
     }
+}
 
-    /// Get the base package, using the derived name if not explicitly set.
-    pub fn get_base_package(&self) -> String {
-        self.package
-            .base_package
-            .clone()
-            .unwrap_or_else(|| derive_base_package(&self.package.name))
+/// A lint violation severity, lowest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintSeverity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// Represents the optional [error-prone] section of Jargo.toml. Its presence
+/// runs Error Prone as a javac plugin over the main sources.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct ErrorProneConfig {
+    /// Error Prone version. Defaults to `error_prone::ERROR_PRONE_VERSION`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Severity overrides by bug pattern name, e.g. `DeadException = "off"`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub checks: BTreeMap<String, CheckSeverity>,
+}
+
+/// Severity an Error Prone check is reported at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckSeverity {
+    Off,
+    Warn,
+    Error,
+}
+
+impl CheckSeverity {
+    /// The level in an `-Xep:{check}:{level}` flag.
+    pub fn flag(self) -> &'static str {
+        match self {
+            CheckSeverity::Off => "OFF",
+            CheckSeverity::Warn => "WARN",
+            CheckSeverity::Error => "ERROR",
+        }
+    }
+}
+
+/// Represents the optional [doc] section of Jargo.toml.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct DocConfig {
+    /// Doclint categories `jargo doc` checks (`true`) or skips (`false`).
+    /// Defaults to none.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub doclint: BTreeMap<DoclintCategory, bool>,
+    /// Fail `jargo doc` on any javadoc warning. Defaults to `false`.
+    #[serde(rename = "deny-warnings", skip_serializing_if = "Option::is_none")]
+    pub deny_warnings: Option<bool>,
+}
+
+/// A group of javadoc's doclint checks, or `all` of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DoclintCategory {
+    All,
+    Accessibility,
+    Html,
+    Missing,
+    Reference,
+    Syntax,
+}
+
+impl DoclintCategory {
+    pub fn name(self) -> &'static str {
+        match self {
+            DoclintCategory::All => "all",
+            DoclintCategory::Accessibility => "accessibility",
+            DoclintCategory::Html => "html",
+            DoclintCategory::Missing => "missing",
+            DoclintCategory::Reference => "reference",
+            DoclintCategory::Syntax => "syntax",
+        }
     }
+}
+
+/// Represents the optional [api-check] section of Jargo.toml.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct ApiCheckConfig {
+    /// Make `jargo publish` refuse a version whose bump is smaller than the
+    /// API changes since the last release need.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub semver: Option<bool>,
+}
+
+/// One `[publish.<name>]` table: a Maven repository, such as GitHub
+/// Packages or Artifactory, that releases are uploaded to.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PublishTarget {
+    /// Base URL of the repository, e.g. `https://maven.pkg.github.com/OWNER/REPO`.
+    pub url: String,
+    /// Environment variable holding the user name. Defaults to
+    /// `JARGO_PUBLISH_{NAME}_USERNAME`.
+    #[serde(rename = "username-env", skip_serializing_if = "Option::is_none")]
+    pub username_env: Option<String>,
+    /// Environment variable holding the password or token. Defaults to
+    /// `JARGO_PUBLISH_{NAME}_PASSWORD`.
+    #[serde(rename = "password-env", skip_serializing_if = "Option::is_none")]
+    pub password_env: Option<String>,
+}
+
+/// Represents the optional [release] section of Jargo.toml.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct ReleaseConfig {
+    /// Steps run by `jargo release`, in order.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub steps: Option<Vec<ReleaseStep>>,
+    /// Bump from the released version to the next development version,
+    /// before `-SNAPSHOT` is added: `patch`, `minor`, `major`, or a version.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next: Option<String>,
+}
+
+/// A single `jargo release` pipeline step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReleaseStep {
+    Test,
+    Version,
+    Build,
+    Tag,
+    Publish,
+    Next,
+}
+
+impl ReleaseStep {
+    /// Default pipeline when `[release] steps` is not set.
+    pub const DEFAULT: [ReleaseStep; 6] = [
+        ReleaseStep::Test,
+        ReleaseStep::Version,
+        ReleaseStep::Build,
+        ReleaseStep::Tag,
+        ReleaseStep::Publish,
+        ReleaseStep::Next,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ReleaseStep::Test => "test",
+            ReleaseStep::Version => "version",
+            ReleaseStep::Build => "build",
+            ReleaseStep::Tag => "tag",
+            ReleaseStep::Publish => "publish",
+            ReleaseStep::Next => "next",
+        }
+    }
+}
+
+/// Top-level Jargo.toml structure for generation.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JargoToml {
+    pub package: PackageManifest,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bin: Vec<BinTarget>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build: Option<BuildConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub javac: Option<JavacConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run: Option<RunConfig>,
+    /// `[env]`: environment variables for the JVMs of `jargo run` and `jargo test`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub codegen: Vec<CodegenStep>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dist: Option<DistConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verify: Option<VerifyConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jar: Option<JarConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jlink: Option<JlinkConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jpackage: Option<JpackageConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub native: Option<NativeConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repl: Option<ReplConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test: Option<TestConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bench: Option<BenchConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<ContainerConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shade: Option<ShadeConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub toolchain: Option<ToolchainConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ci: Option<CiConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release: Option<ReleaseConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub licenses: Option<LicensesConfig>,
+    #[serde(rename = "api-check", default, skip_serializing_if = "Option::is_none")]
+    pub api_check: Option<ApiCheckConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fmt: Option<FmtConfig>,
+    /// Deprecated `[format]`, read as `[fmt]`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<FormatConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lint: Option<LintConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<DocConfig>,
+    #[serde(
+        rename = "error-prone",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub error_prone: Option<ErrorProneConfig>,
+    /// `[publish.<name>]`: Maven repositories `jargo publish --repo <name>` uploads to.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub publish: BTreeMap<String, PublishTarget>,
+    /// `[tasks]`: named shell commands run by `jargo run-task <name>` or `jargo <name>`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tasks: BTreeMap<String, String>,
+    /// `[features]`: named groups of optional [dependencies] and other
+    /// features, enabled with `--features <name>`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub features: BTreeMap<String, Vec<String>>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub dependencies: HashMap<String, DependencyValue>,
+    #[serde(
+        rename = "dev-dependencies",
+        default,
+        skip_serializing_if = "HashMap::is_empty"
+    )]
+    pub dev_dependencies: HashMap<String, DependencyValue>,
+    #[serde(
+        rename = "it-dependencies",
+        default,
+        skip_serializing_if = "HashMap::is_empty"
+    )]
+    pub it_dependencies: HashMap<String, DependencyValue>,
+}
+
+impl JargoToml {
+    pub fn new_app(name: &str) -> Self {
+        Self {
+            package: PackageManifest {
+                name: name.to_string(),
+                version: "0.1.0".to_string(),
+                group: None,
+                project_type: "app".to_string(),
+                java: "21".to_string(),
+                base_package: None,
+                main_class: None,
+                description: None,
+                license: None,
+                homepage: None,
+                repository: None,
+                authors: Vec::new(),
+            },
+            bin: Vec::new(),
+            build: None,
+            javac: None,
+            run: None,
+            env: BTreeMap::new(),
+            codegen: Vec::new(),
+            dist: None,
+            verify: None,
+            jar: None,
+            jlink: None,
+            jpackage: None,
+            native: None,
+            repl: None,
+            test: None,
+            bench: None,
+            container: None,
+            shade: None,
+            toolchain: None,
+            ci: None,
+            release: None,
+            licenses: None,
+            api_check: None,
+            fmt: None,
+            format: None,
+            lint: None,
+            doc: None,
+            error_prone: None,
+            publish: BTreeMap::new(),
+            tasks: BTreeMap::new(),
+            features: BTreeMap::new(),
+            dependencies: HashMap::new(),
+            dev_dependencies: HashMap::new(),
+            it_dependencies: HashMap::new(),
+        }
+    }
+
+    pub fn new_lib(name: &str, base_package: &str) -> Self {
+        Self {
+            package: PackageManifest {
+                name: name.to_string(),
+                version: "0.1.0".to_string(),
+                group: None,
+                project_type: "lib".to_string(),
+                java: "21".to_string(),
+                base_package: Some(base_package.to_string()),
+                main_class: None,
+                description: None,
+                license: None,
+                homepage: None,
+                repository: None,
+                authors: Vec::new(),
+            },
+            bin: Vec::new(),
+            build: None,
+            javac: None,
+            run: None,
+            env: BTreeMap::new(),
+            codegen: Vec::new(),
+            dist: None,
+            verify: None,
+            jar: None,
+            jlink: None,
+            jpackage: None,
+            native: None,
+            repl: None,
+            test: None,
+            bench: None,
+            container: None,
+            shade: None,
+            toolchain: None,
+            ci: None,
+            release: None,
+            licenses: None,
+            api_check: None,
+            fmt: None,
+            format: None,
+            lint: None,
+            doc: None,
+            error_prone: None,
+            publish: BTreeMap::new(),
+            tasks: BTreeMap::new(),
+            features: BTreeMap::new(),
+            dependencies: HashMap::new(),
+            dev_dependencies: HashMap::new(),
+            it_dependencies: HashMap::new(),
+        }
+    }
+
+    pub fn to_toml_string(&self) -> Result<String, toml::ser::Error> {
+        toml::to_string_pretty(self)
+    }
+
+    /// Load and parse a Jargo.toml file.
+    pub fn from_file(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = fs::read_to_string(path)?;
+        let mut manifest: JargoToml = toml::from_str(&content)?;
+        if manifest.inherits_workspace_dependencies() {
+            let project_root = path.parent().unwrap_or(Path::new("."));
+            let Some(workspace) = Workspace::containing(project_root)? else {
+                return Err("dependencies with `workspace = true` need a workspace, \
+                     but this package is not a workspace member"
+                    .into());
+            };
+            manifest.inherit_workspace_dependencies(&workspace.dependencies)?;
+        }
+        Ok(manifest)
+    }
+
+    fn inherits_workspace_dependencies(&self) -> bool {
+        [
+            &self.dependencies,
+            &self.dev_dependencies,
+            &self.it_dependencies,
+        ]
+        .into_iter()
+        .flat_map(|map| map.values())
+        .any(|value| matches!(value, DependencyValue::Expanded(spec) if spec.workspace == Some(true)))
+    }
+
+    /// Fill in `workspace = true` entries from the root's
+    /// `[workspace.dependencies]`. `scope` and `expose` set on the member
+    /// override the ones set on the root.
+    pub fn inherit_workspace_dependencies(
+        &mut self,
+        inherited: &HashMap<String, DependencyValue>,
+    ) -> Result<()> {
+        for map in [
+            &mut self.dependencies,
+            &mut self.dev_dependencies,
+            &mut self.it_dependencies,
+        ] {
+            for (coord, value) in map.iter_mut() {
+                if is_member_dependency(coord, value) {
+                    continue;
+                }
+                let DependencyValue::Expanded(spec) = value else {
+                    continue;
+                };
+                if spec.workspace != Some(true) {
+                    continue;
+                }
+                if spec.version.is_some() {
+                    bail!(
+                        "`{}` sets both `version` and `workspace = true`; \
+                         the version comes from [workspace.dependencies]",
+                        coord
+                    );
+                }
+                let (version, scope, expose) = match inherited.get(coord) {
+                    Some(DependencyValue::Simple(version)) => (version.clone(), None, None),
+                    Some(DependencyValue::Expanded(root)) => match &root.version {
+                        Some(version) => (version.clone(), root.scope.clone(), root.expose),
+                        None => bail!("`{}` in [workspace.dependencies] needs a `version`", coord),
+                    },
+                    None => bail!(
+                        "`{}` has `workspace = true` but is not in [workspace.dependencies]",
+                        coord
+                    ),
+                };
+                spec.version = Some(version);
+                spec.scope = spec.scope.take().or(scope);
+                spec.expose = spec.expose.or(expose);
+                spec.workspace = None;
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the base package, using the derived name if not explicitly set.
+    pub fn get_base_package(&self) -> String {
+        self.package
+            .base_package
+            .clone()
+            .unwrap_or_else(|| derive_base_package(&self.package.name))
+    }
+
+    /// Get the groupId the package is published under, defaulting to the
+    /// base package.
+    pub fn get_group(&self) -> String {
+        self.package
+            .group
+            .clone()
+            .unwrap_or_else(|| self.get_base_package())
+    }
+
+    /// Get the main class name, defaulting to "Main" if not set.
+    pub fn get_main_class(&self) -> String {
+        self.package
+            .main_class
+            .clone()
+            .unwrap_or_else(|| "Main".to_string())
+    }
+
+    /// The app's entry points: the `[[bin]]` entries, or a single one named
+    /// after the package running `main-class` when there are none.
+    pub fn get_bins(&self) -> Result<Vec<BinTarget>> {
+        if self.bin.is_empty() {
+            return Ok(vec![BinTarget {
+                name: self.package.name.clone(),
+                main_class: self.get_main_class(),
+            }]);
+        }
+        if !self.is_app() {
+            bail!("[[bin]] targets are only allowed in app projects");
+        }
+        if self.package.main_class.is_some() {
+            bail!("`main-class` in [package] cannot be combined with [[bin]]; set it per bin");
+        }
+        let mut names = HashSet::new();
+        for bin in &self.bin {
+            let valid_name = !bin.name.is_empty()
+                && bin
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+            if !valid_name {
+                bail!(
+                    "invalid [[bin]] name `{}`: expected letters, digits, `-`, `_`, or `.`",
+                    bin.name
+                );
+            }
+            if !names.insert(bin.name.as_str()) {
+                bail!("duplicate [[bin]] name `{}`", bin.name);
+            }
+            if bin.main_class.is_empty() {
+                bail!("[[bin]] `{}` has an empty `main-class`", bin.name);
+            }
+        }
+        Ok(self.bin.clone())
+    }
+
+    /// The entry point named `name`, or the only one when `name` is `None`.
+    pub fn get_bin(&self, name: Option<&str>) -> Result<BinTarget> {
+        let mut bins = self.get_bins()?;
+        let names = || {
+            bins.iter()
+                .map(|b| b.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match name {
+            Some(name) => match bins.iter().position(|b| b.name == name) {
+                Some(i) => Ok(bins.swap_remove(i)),
+                None => bail!("no bin target named `{}`; available: {}", name, names()),
+            },
+            None if bins.len() == 1 => Ok(bins.remove(0)),
+            None => bail!(
+                "`{}` has several [[bin]] targets; pass `--bin` with one of: {}",
+                self.package.name,
+                names()
+            ),
+        }
+    }
+
+    /// Fully-qualified main class of an entry point.
+    pub fn main_class_of(&self, bin: &BinTarget) -> String {
+        format!("{}.{}", self.get_base_package(), bin.main_class)
+    }
+
+    /// Check if this is an app project.
+    pub fn is_app(&self) -> bool {
+        self.package.project_type == "app"
+    }
+
+    /// Get the `jargo ci` pipeline, defaulting to every step.
+    pub fn get_ci_steps(&self) -> Vec<CiStep> {
+        self.ci
+            .as_ref()
+            .and_then(|c| c.steps.clone())
+            .unwrap_or_else(|| CiStep::DEFAULT.to_vec())
+    }
+
+    /// Get `[licenses] allow`, if the licenses of dependencies are restricted.
+    pub fn get_license_allowlist(&self) -> Option<&[String]> {
+        self.licenses.as_ref().and_then(|l| l.allow.as_deref())
+    }
+
+    /// Get `[fmt] formatter`, defaulting to google-java-format.
+    pub fn get_formatter(&self) -> Formatter {
+        self.fmt
+            .as_ref()
+            .and_then(|f| f.formatter)
+            .unwrap_or(Formatter::GoogleJavaFormat)
+    }
+
+    /// Get `[fmt] style`, else the style of the deprecated `[format] indent`,
+    /// defaulting to AOSP.
+    pub fn get_fmt_style(&self) -> Result<FmtStyle> {
+        if let Some(style) = self.fmt.as_ref().and_then(|f| f.style) {
+            return Ok(style);
+        }
+        match self.format.as_ref().and_then(|f| f.indent) {
+            None | Some(4) => Ok(FmtStyle::Aosp),
+            Some(2) => Ok(FmtStyle::Google),
+            Some(indent) => bail!(
+                "invalid `[format] indent = {}`: expected 4 or 2; `[format]` is replaced by \
+                 `[fmt] style = \"aosp\"` (4) or `\"google\"` (2)",
+                indent
+            ),
+        }
+    }
+
+    /// Get the deprecated `[format] paths`, the extra directories `jargo fmt`
+    /// formats.
+    pub fn get_format_paths(&self) -> &[String] {
+        self.format
+            .as_ref()
+            .and_then(|f| f.paths.as_deref())
+            .unwrap_or_default()
+    }
+
+    /// Get `[fmt] import-order`, defaulting to Eclipse's order.
+    pub fn get_import_order(&self) -> Vec<String> {
+        self.fmt
+            .as_ref()
+            .and_then(|f| f.import_order.clone())
+            .unwrap_or_else(|| DEFAULT_IMPORT_ORDER.iter().map(|s| s.to_string()).collect())
+    }
+
+    /// Get `[lint] fail-on`, defaulting to `error`.
+    pub fn get_lint_fail_on(&self) -> LintSeverity {
+        self.lint
+            .as_ref()
+            .and_then(|l| l.fail_on)
+            .unwrap_or(LintSeverity::Error)
+    }
+
+    /// Get the `[lint.header] template`, if any.
+    pub fn get_license_header(&self) -> Option<&str> {
+        self.lint
+            .as_ref()
+            .and_then(|l| l.header.as_ref())
+            .map(|h| h.template.as_str())
+    }
+
+    /// Get the tools `jargo lint` runs, in order: Checkstyle unless disabled,
+    /// then PMD and SpotBugs when enabled.
+    pub fn get_lint_tools(&self) -> Vec<LintTool> {
+        let lint = self.lint.as_ref();
+        let enabled = |flag: Option<bool>, default| flag.unwrap_or(default);
+        let mut tools = Vec::new();
+        if enabled(lint.and_then(|l| l.checkstyle), true) {
+            tools.push(LintTool::Checkstyle);
+        }
+        if enabled(lint.and_then(|l| l.pmd), false) {
+            tools.push(LintTool::Pmd);
+        }
+        if enabled(lint.and_then(|l| l.spotbugs), false) {
+            tools.push(LintTool::Spotbugs);
+        }
+        tools
+    }
+
+    /// Get the value of javadoc's `-Xdoclint:` flag for `[doc] doclint`:
+    /// `all` first, then the enabled categories, then the disabled ones
+    /// (`all,-missing`), or `none` when nothing is enabled.
+    pub fn get_doclint(&self) -> String {
+        let Some(doclint) = self.doc.as_ref().map(|d| &d.doclint) else {
+            return "none".to_string();
+        };
+        if !doclint.values().any(|&on| on) {
+            return "none".to_string();
+        }
+        let enabled = doclint
+            .iter()
+            .filter(|(_, &on)| on)
+            .map(|(c, _)| c.name().to_string());
+        let disabled = doclint
+            .iter()
+            .filter(|(c, &on)| !on && **c != DoclintCategory::All)
+            .map(|(c, _)| format!("-{}", c.name()));
+        enabled.chain(disabled).collect::<Vec<_>>().join(",")
+    }
+
+    /// Whether `[doc] deny-warnings` fails `jargo doc` on javadoc warnings.
+    pub fn get_doc_deny_warnings(&self) -> bool {
+        self.doc
+            .as_ref()
+            .and_then(|d| d.deny_warnings)
+            .unwrap_or(false)
+    }
+
+    /// Whether `[api-check] semver` makes publishing enforce semver.
+    pub fn enforces_semver(&self) -> bool {
+        self.api_check
+            .as_ref()
+            .and_then(|c| c.semver)
+            .unwrap_or(false)
+    }
+
+    /// Get the `jargo release` pipeline, defaulting to every step.
+    pub fn get_release_steps(&self) -> Vec<ReleaseStep> {
+        self.release
+            .as_ref()
+            .and_then(|r| r.steps.clone())
+            .unwrap_or_else(|| ReleaseStep::DEFAULT.to_vec())
+    }
+
+    /// Get the bump to the next development version, defaulting to `patch`.
+    pub fn get_release_next(&self) -> &str {
+        self.release
+            .as_ref()
+            .and_then(|r| r.next.as_deref())
+            .unwrap_or("patch")
+    }
+
+    /// The `[build] compiler`, defaulting to `javac`.
+    pub fn get_compiler(&self) -> CompilerKind {
+        self.build
+            .as_ref()
+            .and_then(|b| b.compiler)
+            .unwrap_or_default()
+    }
+
+    /// Whether `[build] build-info` is enabled.
+    pub fn build_info_enabled(&self) -> bool {
+        self.build.as_ref().is_some_and(|b| b.build_info)
+    }
+
+    /// JVM flags for the `javac` process from `[build] javac-memory`
+    /// (e.g. `["-Xmx2g", "-XX:MaxMetaspaceSize=1g"]`), defaulting to empty.
+    pub fn get_javac_jvm_args(&self) -> Result<Vec<String>> {
+        let memory = match self.build.as_ref().and_then(|b| b.javac_memory.as_ref()) {
+            Some(memory) => memory,
+            None => return Ok(Vec::new()),
+        };
+
+        let (heap, metaspace) = match memory {
+            JavacMemory::Preset(name) => match name.as_str() {
+                "small" => (Some("512m"), Some("256m")),
+                "medium" => (Some("1g"), Some("512m")),
+                "large" => (Some("2g"), Some("1g")),
+                "xlarge" => (Some("4g"), Some("2g")),
+                other => bail!(
+                    "unknown javac-memory preset `{}`: expected `small`, `medium`, `large`, or `xlarge`",
+                    other
+                ),
+            },
+            JavacMemory::Explicit(spec) => (spec.heap.as_deref(), spec.metaspace.as_deref()),
+        };
+
+        let mut args = Vec::new();
+        if let Some(heap) = heap {
+            validate_memory_size(heap)?;
+            args.push(format!("-Xmx{}", heap));
+        }
+        if let Some(metaspace) = metaspace {
+            validate_memory_size(metaspace)?;
+            args.push(format!("-XX:MaxMetaspaceSize={}", metaspace));
+        }
+        Ok(args)
+    }
+
+    /// javac flags from `[javac]`: `-Xlint:{keys}` and `-Werror`.
+    pub fn get_javac_args(&self) -> Vec<String> {
+        let Some(javac) = &self.javac else {
+            return Vec::new();
+        };
+        let mut args = Vec::new();
+        if !javac.lint.is_empty() {
+            args.push(format!("-Xlint:{}", javac.lint.join(",")));
+        }
+        if javac.deny_warnings {
+            args.push("-Werror".to_string());
+        }
+        args
+    }
+
+    /// Get JVM args from the [run] section, defaulting to empty.
+    pub fn get_jvm_args(&self) -> &[String] {
+        match &self.run {
+            Some(run_config) => &run_config.jvm_args,
+            None => &[],
+        }
+    }
+
+    /// Get the `[run] system-properties`, defaulting to empty. Keys must be
+    /// non-empty and free of `=` and whitespace.
+    pub fn get_system_properties(&self) -> Result<BTreeMap<String, String>> {
+        let properties = match &self.run {
+            Some(run_config) => run_config.system_properties.clone(),
+            None => return Ok(BTreeMap::new()),
+        };
+        check_system_property_names(&properties, "[run]")?;
+        Ok(properties)
+    }
+
+    /// Get the `[test] framework`, defaulting to JUnit.
+    pub fn get_test_framework(&self) -> TestFramework {
+        self.test
+            .as_ref()
+            .and_then(|t| t.framework)
+            .unwrap_or_default()
+    }
+
+    /// Get the `[test] slowest` count, defaulting to 5.
+    pub fn get_slowest_count(&self) -> usize {
+        self.test.as_ref().and_then(|t| t.slowest).unwrap_or(5)
+    }
+
+    /// Get the `[test] jvm-args`, defaulting to empty.
+    pub fn get_test_jvm_args(&self) -> &[String] {
+        match &self.test {
+            Some(test_config) => &test_config.jvm_args,
+            None => &[],
+        }
+    }
+
+    /// Get the `[test] system-properties`, defaulting to empty, checked like
+    /// `[run] system-properties`.
+    pub fn get_test_system_properties(&self) -> Result<BTreeMap<String, String>> {
+        let properties = match &self.test {
+            Some(test_config) => test_config.system_properties.clone(),
+            None => return Ok(BTreeMap::new()),
+        };
+        check_system_property_names(&properties, "[test]")?;
+        Ok(properties)
+    }
+
+    /// Get the `[test.coverage]` minimums as `(counter, percent)` pairs, with
+    /// counters named like JaCoCo's (`"line"` for `LINE`). Defaults to empty.
+    pub fn get_coverage_minimums(&self) -> Result<Vec<(&'static str, f64)>> {
+        let Some(coverage) = self.test.as_ref().and_then(|t| t.coverage.as_ref()) else {
+            return Ok(Vec::new());
+        };
+        let minimums: Vec<(&'static str, f64)> = [
+            ("instruction", coverage.instruction),
+            ("branch", coverage.branch),
+            ("line", coverage.line),
+            ("method", coverage.method),
+        ]
+        .into_iter()
+        .filter_map(|(counter, minimum)| minimum.map(|m| (counter, m)))
+        .collect();
+        for (counter, minimum) in &minimums {
+            if !(0.0..=100.0).contains(minimum) {
+                bail!(
+                    "invalid [test.coverage] `{}` = {}: expected a percentage from 0 to 100",
+                    counter,
+                    minimum
+                );
+            }
+        }
+        Ok(minimums)
+    }
+
+    /// Get the `[test.mutation] threshold`, a percentage from 0 to 100.
+    pub fn get_mutation_threshold(&self) -> Result<Option<f64>> {
+        let threshold = self
+            .test
+            .as_ref()
+            .and_then(|t| t.mutation.as_ref())
+            .and_then(|m| m.threshold);
+        if let Some(threshold) = threshold {
+            if !(0.0..=100.0).contains(&threshold) {
+                bail!(
+                    "invalid [test.mutation] `threshold` = {}: expected a percentage from 0 to 100",
+                    threshold
+                );
+            }
+        }
+        Ok(threshold)
+    }
+
+    /// Get the `[test.mutation] mutators`, defaulting to empty.
+    pub fn get_mutators(&self) -> &[String] {
+        match self.test.as_ref().and_then(|t| t.mutation.as_ref()) {
+            Some(mutation) => &mutation.mutators,
+            None => &[],
+        }
+    }
+
+    /// The `[run.profiles.<name>]` table selected with `jargo run --profile`.
+    pub fn get_run_profile(&self, name: &str) -> Result<&RunProfile> {
+        let profiles = self.run.as_ref().map(|run_config| &run_config.profiles);
+        let Some(profile) = profiles.and_then(|profiles| profiles.get(name)) else {
+            match profiles.filter(|profiles| !profiles.is_empty()) {
+                Some(profiles) => bail!(
+                    "no run profile named `{}`; available: {}",
+                    name,
+                    profiles.keys().cloned().collect::<Vec<_>>().join(", ")
+                ),
+                None => bail!(
+                    "no run profile named `{}`; define it in [run.profiles.{}]",
+                    name,
+                    name
+                ),
+            }
+        };
+        check_system_property_names(
+            &profile.system_properties,
+            &format!("[run.profiles.{}]", name),
+        )?;
+        Ok(profile)
+    }
+
+    /// Get the `[run] debug-port`, defaulting to 5005.
+    pub fn get_debug_port(&self) -> u16 {
+        self.run
+            .as_ref()
+            .and_then(|run_config| run_config.debug_port)
+            .unwrap_or(5005)
+    }
+
+    /// Whether `jargo run --debug` waits for a debugger by default.
+    pub fn get_debug_suspend(&self) -> bool {
+        self.run
+            .as_ref()
+            .is_some_and(|run_config| run_config.debug_suspend)
+    }
+
+    /// Get the JAR entries required by `[verify] resources`, defaulting to empty.
+    pub fn get_verify_resources(&self) -> &[String] {
+        match &self.verify {
+            Some(verify_config) => &verify_config.resources,
+            None => &[],
+        }
+    }
+
+    /// Get the `[jar.manifest]` attributes, sorted by name. Names must be valid
+    /// manifest header names, and the headers Jargo writes itself are rejected.
+    pub fn get_manifest_attributes(&self) -> Result<Vec<(&str, &str)>> {
+        let attributes = match &self.jar {
+            Some(jar_config) => &jar_config.manifest,
+            None => return Ok(Vec::new()),
+        };
+        let mut out = Vec::with_capacity(attributes.len());
+        for (name, value) in attributes {
+            let valid_name = !name.is_empty()
+                && name.len() <= 70
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if !valid_name {
+                bail!(
+                    "invalid [jar.manifest] attribute `{}`: expected letters, digits, `-`, or `_` (at most 70)",
+                    name
+                );
+            }
+            if ["Manifest-Version", "Main-Class", "Class-Path"]
+                .iter()
+                .any(|reserved| name.eq_ignore_ascii_case(reserved))
+            {
+                bail!("[jar.manifest] cannot set `{}`: Jargo writes it", name);
+            }
+            if value.contains(['\r', '\n', '\0']) {
+                bail!(
+                    "invalid [jar.manifest] value for `{}`: line breaks are not allowed",
+                    name
+                );
+            }
+            out.push((name.as_str(), value.as_str()));
+        }
+        Ok(out)
+    }
+
+    /// Get `[jar] compression-level`, defaulting to 6.
+    pub fn get_jar_compression_level(&self) -> Result<u32> {
+        let level = self
+            .jar
+            .as_ref()
+            .and_then(|jar_config| jar_config.compression_level)
+            .unwrap_or(6);
+        if level > 9 {
+            bail!(
+                "invalid [jar] compression-level {}: expected 0 (stored) to 9",
+                level
+            );
+        }
+        Ok(level)
+    }
+
+    /// Get the `[shade.relocate]` rules, defaulting to none.
+    pub fn get_relocations(&self) -> BTreeMap<String, String> {
+        self.shade
+            .as_ref()
+            .map(|shade| shade.relocate.clone())
+            .unwrap_or_default()
+    }
+
+    /// Parse `[run] agents` in declaration order (agents load in that order).
+    pub fn get_agents(&self) -> Result<Vec<Dependency>> {
+        let agents = match &self.run {
+            Some(run_config) => &run_config.agents,
+            None => return Ok(Vec::new()),
+        };
+        agents
+            .iter()
+            .map(
+                |coord| match coord.split(':').collect::<Vec<_>>().as_slice() {
+                    [g, a, v] if !g.is_empty() && !a.is_empty() && !v.is_empty() => {
+                        Ok(Dependency {
+                            group: g.to_string(),
+                            artifact: a.to_string(),
+                            version: v.to_string(),
+                            scope: Scope::Runtime,
+                            expose: false,
+                        })
+                    }
+                    _ => bail!(
+                        "invalid agent `{}`: expected `groupId:artifactId:version`",
+                        coord
+                    ),
+                },
+            )
+            .collect()
+    }
+
+    /// Parse and return the [dependencies] section as a normalized, sorted list.
+    /// Workspace members are left out; see `get_member_dependencies`.
+    pub fn get_dependencies(&self) -> Result<Vec<Dependency>> {
+        parse_dependency_map(
+            self.dependencies
+                .iter()
+                .filter(|(coord, value)| !is_member_dependency(coord, value)),
+        )
+    }
+
+    /// The workspace members in [dependencies], sorted by name. A key
+    /// without a `:` names a member: `"my-core" = { workspace = true }`.
+    pub fn get_member_dependencies(&self) -> Result<Vec<MemberDependency>> {
+        let mut deps = Vec::new();
+        for (name, value) in &self.dependencies {
+            if !is_member_dependency(name, value) {
+                continue;
+            }
+            let DependencyValue::Expanded(spec) = value else {
+                continue;
+            };
+            if spec.version.is_some() {
+                bail!("`{}` is a workspace member and takes no `version`", name);
+            }
+            deps.push(MemberDependency {
+                name: name.clone(),
+                scope: parse_scope(spec, name)?,
+            });
+        }
+        deps.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(deps)
+    }
+
+    /// The `"group:artifact"` keys of the `optional = true` [dependencies].
+    pub fn optional_dependencies(&self) -> BTreeSet<String> {
+        self.dependencies
+            .iter()
+            .filter(|(_, value)| {
+                matches!(value, DependencyValue::Expanded(spec) if spec.optional == Some(true))
+            })
+            .map(|(coord, _)| coord.clone())
+            .collect()
+    }
+
+    /// The `[features]`, each with the `"group:artifact"` optional
+    /// [dependencies] it enables, itself or through the features it includes.
+    ///
+    /// Entries with a `:` name dependencies, which must be `optional = true`;
+    /// the others name features. An optional dependency no feature enables
+    /// could never be used, and is an error too.
+    pub fn get_features(&self) -> Result<BTreeMap<String, BTreeSet<String>>> {
+        let optional = self.optional_dependencies();
+        let mut features = BTreeMap::new();
+        for name in self.features.keys() {
+            let mut deps = BTreeSet::new();
+            let mut seen = BTreeSet::new();
+            let mut stack = vec![name.as_str()];
+            while let Some(feature) = stack.pop() {
+                if !seen.insert(feature) {
+                    continue;
+                }
+                for entry in &self.features[feature] {
+                    if entry.contains(':') {
+                        if !optional.contains(entry) {
+                            bail!(
+                                "feature `{}` enables `{}`, which is not an `optional = true` \
+                                 entry in [dependencies]",
+                                feature,
+                                entry
+                            );
+                        }
+                        deps.insert(entry.clone());
+                    } else if self.features.contains_key(entry) {
+                        stack.push(entry);
+                    } else {
+                        bail!(
+                            "feature `{}` includes `{}`, which is not in [features]",
+                            feature,
+                            entry
+                        );
+                    }
+                }
+            }
+            features.insert(name.clone(), deps);
+        }
+        if let Some(unused) = optional
+            .iter()
+            .find(|coord| !features.values().any(|deps| deps.contains(*coord)))
+        {
+            bail!(
+                "`{}` is optional, but no entry in [features] enables it",
+                unused
+            );
+        }
+        Ok(features)
+    }
+
+    /// Parse and return the [dev-dependencies] section as a normalized, sorted list.
+    ///
+    /// With `[test] framework = "testng"`, TestNG is an implicit dev-dependency
+    /// unless listed with another version.
+    pub fn get_dev_dependencies(&self) -> Result<Vec<Dependency>> {
+        let mut deps = parse_dependency_map(&self.dev_dependencies)?;
+        let listed = deps
+            .iter()
+            .any(|d| d.group == "org.testng" && d.artifact == "testng");
+        if self.get_test_framework() == TestFramework::TestNg && !listed {
+            deps.push(Dependency {
+                group: "org.testng".to_string(),
+                artifact: "testng".to_string(),
+                version: TESTNG_VERSION.to_string(),
+                scope: Scope::Compile,
+                expose: false,
+            });
+            deps.sort_by(|a, b| (&a.group, &a.artifact).cmp(&(&b.group, &b.artifact)));
+        }
+        Ok(deps)
+    }
+
+    /// Parse and return the [it-dependencies] section as a normalized, sorted list.
+    pub fn get_it_dependencies(&self) -> Result<Vec<Dependency>> {
+        parse_dependency_map(&self.it_dependencies)
+    }
+
+    /// Check up front what commands otherwise reject only when they get to
+    /// it: the `java` release, every dependency's coordinate, version, and
+    /// scope, and the settings read through fallible getters. Returns every
+    /// problem found rather than the first.
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if crate::bytecode::parse_release(&self.package.java).is_none() {
+            problems.push(format!(
+                "invalid `java = \"{}\"`: expected a feature release such as 17 or 21",
+                self.package.java
+            ));
+        }
+        for (table, entries) in [
+            ("dependencies", &self.dependencies),
+            ("dev-dependencies", &self.dev_dependencies),
+            ("it-dependencies", &self.it_dependencies),
+        ] {
+            let mut entries: Vec<_> = entries.iter().collect();
+            entries.sort_by_key(|(coord, _)| *coord);
+            for (coord, value) in entries {
+                if is_member_dependency(coord, value) {
+                    continue;
+                }
+                if let Err(e) = parse_dependency_map([(coord, value)]) {
+                    problems.push(format!("[{}] {}", table, e));
+                }
+            }
+        }
+        let getters = [
+            self.get_member_dependencies().map(drop),
+            self.get_bins().map(drop),
+            self.get_javac_jvm_args().map(drop),
+            self.get_system_properties().map(drop),
+            self.get_test_system_properties().map(drop),
+            self.get_coverage_minimums().map(drop),
+            self.get_mutation_threshold().map(drop),
+            self.get_manifest_attributes().map(drop),
+            self.get_jar_compression_level().map(drop),
+            self.get_agents().map(drop),
+            self.get_features().map(drop),
+            self.get_fmt_style().map(drop),
+        ];
+        problems.extend(
+            getters
+                .into_iter()
+                .filter_map(|r| r.err().map(|e| e.to_string())),
+        );
+        problems
+    }
+}
+
+/// The keys in the Jargo.toml `content` that jargo does not know, as dotted
+/// paths such as `package.autor`, so a typo does not silently do nothing.
+pub fn unknown_keys(content: &str) -> Result<Vec<String>> {
+    #[derive(Deserialize)]
+    struct WorkspaceRoot {
+        #[serde(rename = "workspace")]
+        _workspace: crate::workspace::WorkspaceConfig,
+    }
+
+    let table: toml::Table = toml::from_str(content)?;
+    let mut keys = Vec::new();
+    // `?.` marks an `Option` along the path and is not a key
+    let record = |path: serde_ignored::Path| keys.push(path.to_string().replace("?.", ""));
+    if table.contains_key("workspace") {
+        let _: WorkspaceRoot =
+            serde_ignored::deserialize(toml::Deserializer::new(content), record)?;
+    } else {
+        let _: JargoToml = serde_ignored::deserialize(toml::Deserializer::new(content), record)?;
+    }
+    Ok(keys)
+}
+
+/// Add `"{coordinate}" = "{version}"` to `[dependencies]` of the Jargo.toml
+/// at `manifest_path`, creating the table if needed and keeping the rest of
+/// the file as written.
+pub fn add_dependency(manifest_path: &Path, coordinate: &str, version: &str) -> Result<()> {
+    let content = fs::read_to_string(manifest_path)
+        .with_context(|| format!("failed to read {}", manifest_path.display()))?;
+    let mut doc: toml_edit::DocumentMut = content
+        .parse()
+        .with_context(|| format!("failed to parse {}", manifest_path.display()))?;
+    if doc.get("dependencies").is_none() {
+        doc.insert("dependencies", toml_edit::table());
+    }
+    let dependencies = doc["dependencies"]
+        .as_table_like_mut()
+        .context("`[dependencies]` must be a table")?;
+    dependencies.insert(coordinate, toml_edit::value(version));
+    fs::write(manifest_path, doc.to_string())
+        .with_context(|| format!("failed to write {}", manifest_path.display()))
+}
+
+/// Whether a [dependencies] entry names a workspace member rather than a
+/// `groupId:artifactId` coordinate.
+fn is_member_dependency(key: &str, value: &DependencyValue) -> bool {
+    !key.contains(':')
+        && matches!(value, DependencyValue::Expanded(spec) if spec.workspace == Some(true))
+}
+
+/// Parse raw dependency entries (from TOML) into a sorted, normalized list.
+fn parse_dependency_map<'a>(
+    entries: impl IntoIterator<Item = (&'a String, &'a DependencyValue)>,
+) -> Result<Vec<Dependency>> {
+    let mut deps = Vec::new();
+
+    for (coord, value) in entries {
+        let (group, artifact) = parse_coordinate(coord)?;
+        let (version, scope, expose) = match value {
+            DependencyValue::Simple(v) => (v.clone(), Scope::Compile, false),
+            DependencyValue::Expanded(spec) => {
+                let scope = parse_scope(spec, coord)?;
+                let version = match &spec.version {
+                    Some(version) => version.clone(),
+                    None if spec.workspace == Some(true) => bail!(
+                        "`{}` has `workspace = true`, but no workspace provided its version",
+                        coord
+                    ),
+                    None => bail!("missing `version` for `{}`", coord),
+                };
+                (version, scope, spec.expose.unwrap_or(false))
+            }
+        };
+        if version.is_empty() || version.contains(char::is_whitespace) {
+            bail!("invalid version `{}` for `{}`", version, coord);
+        }
+        deps.push(Dependency {
+            group,
+            artifact,
+            version,
+            scope,
+            expose,
+        });
+    }
+
+    // Sort for determinism — HashMap iteration order is unspecified.
+    deps.sort_by(|a, b| (&a.group, &a.artifact).cmp(&(&b.group, &b.artifact)));
+    Ok(deps)
+}
+
+fn parse_scope(spec: &DependencySpec, coord: &str) -> Result<Scope> {
+    match spec.scope.as_deref() {
+        None | Some("compile") => Ok(Scope::Compile),
+        Some("runtime") => Ok(Scope::Runtime),
+        Some(other) => bail!("unknown scope `{}` for `{}`", other, coord),
+    }
+}
+
+/// Split `"groupId:artifactId"` into its two parts.
+pub fn parse_coordinate(coord: &str) -> Result<(String, String)> {
+    let is_id = |id: &str| {
+        !id.is_empty()
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+    };
+    match coord.splitn(2, ':').collect::<Vec<_>>().as_slice() {
+        [g, a] if is_id(g) && is_id(a) => Ok((g.to_string(), a.to_string())),
+        _ => bail!(
+            "invalid dependency coordinate `{}`: expected `groupId:artifactId`",
+            coord
+        ),
+    }
+}
+
+/// Check that `size` is a JVM memory size: digits with an optional `k`/`m`/`g` suffix.
+fn validate_memory_size(size: &str) -> Result<()> {
+    let digits = size.trim_end_matches(['k', 'K', 'm', 'M', 'g', 'G']);
+    let suffix_len = size.len() - digits.len();
+    if digits.is_empty() || suffix_len > 1 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        bail!(
+            "invalid memory size `{}`: expected a number with an optional k, m, or g suffix",
+            size
+        );
+    }
+    Ok(())
+}
+
+fn check_system_property_names(properties: &BTreeMap<String, String>, section: &str) -> Result<()> {
+    for key in properties.keys() {
+        if key.is_empty() || key.contains(|c: char| c == '=' || c.is_whitespace()) {
+            bail!("invalid system property name `{}` in {}", key, section);
+        }
+    }
+    Ok(())
+}
+
+/// Derive base-package name from project name by stripping hyphens.
+pub fn derive_base_package(name: &str) -> String {
+    name.replace('-', "")
+}
+
+/// The nearest directory at or above `dir` holding a Jargo.toml: the root of
+/// the package (or workspace) `dir` is in.
+pub fn find_project_root(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .find(|dir| dir.join("Jargo.toml").is_file())
+        .map(Path::to_path_buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest_with(extra: &str) -> JargoToml {
+        toml::from_str(&format!(
+            "[package]\nname = \"demo\"\nversion = \"1.0.0\"\njava = \"21\"\n{}",
+            extra
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_add_dependency() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("Jargo.toml");
+        fs::write(
+            &path,
+            "[package]\nname = \"demo\"  # the app\nversion = \"1.0.0\"\njava = \"21\"\n",
+        )
+        .unwrap();
+        add_dependency(&path, "org.slf4j:slf4j-api", "2.0.12").unwrap();
+        add_dependency(&path, "com.google.guava:guava", "33.0.0-jre").unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.contains("name = \"demo\"  # the app\n"));
+        assert!(content.ends_with(
+            "[dependencies]\n\"org.slf4j:slf4j-api\" = \"2.0.12\"\n\
+             \"com.google.guava:guava\" = \"33.0.0-jre\"\n"
+        ));
+        let manifest = JargoToml::from_file(&path).unwrap();
+        assert_eq!(manifest.get_dependencies().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_unknown_keys() {
+        let keys = unknown_keys(
+            "[package]\nname = \"demo\"\nversion = \"1.0.0\"\njava = \"21\"\nautor = \"me\"\n\n\
+             [run]\njvm-arg = [\"-Xmx1g\"]\n\n[dependencys]\n",
+        )
+        .unwrap();
+        assert_eq!(keys, ["package.autor", "run.jvm-arg", "dependencys"]);
+        let keys = unknown_keys("[workspace]\nmembers = [\"app\"]\nmember = [\"lib\"]\n").unwrap();
+        assert_eq!(keys, ["workspace.member"]);
+    }
+
+    #[test]
+    fn test_validate_lists_every_problem() {
+        let manifest: JargoToml = toml::from_str(
+            "[package]\nname = \"demo\"\nversion = \"1.0.0\"\njava = \"twenty\"\n\n\
+             [run]\njvm-args = []\nsystem-properties = { \"bad name\" = \"x\" }\n\n\
+             [dependencies]\n\"com.example:a:1.0\" = \"1.0\"\n\
+             \"com.example:b\" = { version = \"1.0\", scope = \"test\" }\n\
+             \"com.example:c\" = \"\"\n\
+             \"com.example:d\" = \"1.0\"\n",
+        )
+        .unwrap();
+        let problems = manifest.validate();
+        assert_eq!(problems.len(), 5, "{:?}", problems);
+        assert!(problems[0].contains("invalid `java = \"twenty\"`"));
+        assert!(problems[1].contains("invalid dependency coordinate `com.example:a:1.0`"));
+        assert!(problems[2].contains("unknown scope `test`"));
+        assert!(problems[3].contains("invalid version `` for `com.example:c`"));
+        assert!(problems[4].contains("invalid system property name `bad name`"));
+        assert!(manifest_with("").validate().is_empty());
+    }
+
+    #[test]
+    fn test_deprecated_format_section() {
+        let manifest = manifest_with("\n[format]\nindent = 2\npaths = [\"tools\"]\n");
+        assert_eq!(manifest.get_fmt_style().unwrap(), FmtStyle::Google);
+        assert_eq!(manifest.get_format_paths(), ["tools"]);
+        assert!(manifest.validate().is_empty());
+        assert_eq!(manifest_with("").get_fmt_style().unwrap(), FmtStyle::Aosp);
+        assert!(manifest_with("").get_format_paths().is_empty());
+
+        let manifest = manifest_with("\n[format]\nindent = 8\n");
+        let problems = manifest.validate();
+        assert_eq!(problems.len(), 1, "{:?}", problems);
+        assert!(problems[0].contains("invalid `[format] indent = 8`"));
+    }
+
+    #[test]
+    fn test_find_project_root() {
+        let tmp = tempfile::tempdir().unwrap();
+        let nested = tmp.path().join("app/src/util");
+        fs::create_dir_all(&nested).unwrap();
+        assert_eq!(find_project_root(&nested), None);
+        fs::write(tmp.path().join("Jargo.toml"), "").unwrap();
+        fs::write(tmp.path().join("app/Jargo.toml"), "").unwrap();
+        assert_eq!(find_project_root(&nested), Some(tmp.path().join("app")));
+        assert_eq!(
+            find_project_root(tmp.path()),
+            Some(tmp.path().to_path_buf())
+        );
+    }
+
+    #[test]
+    fn test_derive_base_package() {
+        assert_eq!(derive_base_package("my-app"), "myapp");
+        assert_eq!(derive_base_package("hello"), "hello");
+        assert_eq!(derive_base_package("my-cool-lib"), "mycoollib");
+    }
+
+    #[test]
+    fn test_app_toml_generation() {
+        let toml = JargoToml::new_app("my-app");
+        let s = toml.to_toml_string().unwrap();
+        assert!(s.contains("name = \"my-app\""));
+        assert!(s.contains("type = \"app\""));
+        assert!(s.contains("java = \"21\""));
+        assert!(!s.contains("base-package"));
+    }
+
+    #[test]
+    fn test_lib_toml_generation() {
+        let toml = JargoToml::new_lib("my-lib", "mylib");
+        let s = toml.to_toml_string().unwrap();
+        assert!(s.contains("name = \"my-lib\""));
+        assert!(s.contains("type = \"lib\""));
+        assert!(s.contains("base-package = \"mylib\""));
+    }
+
+    #[test]
+    fn test_toml_parsing() {
+        let toml_str = r#"
+[package]
+name = "test-app"
+version = "1.0.0"
+type = "app"
+java = "17"
+"#;
+        let manifest: JargoToml = toml::from_str(toml_str).unwrap();
+        assert_eq!(manifest.package.name, "test-app");
+        assert_eq!(manifest.package.version, "1.0.0");
+        assert_eq!(manifest.package.project_type, "app");
+        assert_eq!(manifest.package.java, "17");
+        assert!(manifest.package.base_package.is_none());
+    }
+
+    #[test]
+    fn test_get_base_package() {
+        let toml = JargoToml::new_app("my-app");
+        assert_eq!(toml.get_base_package(), "myapp");
+
+        let toml = JargoToml::new_lib("my-lib", "com.example.mylib");
+        assert_eq!(toml.get_base_package(), "com.example.mylib");
+    }
+
+    #[test]
+    fn test_get_main_class() {
+        let toml = JargoToml::new_app("my-app");
+        assert_eq!(toml.get_main_class(), "Main");
+    }
+
+    #[test]
+    fn test_dependency_release() {
+        let package = |name: &str, java: &str| -> PackageManifest {
+            let toml_str = format!(
+                "[package]\nname = \"{}\"\nversion = \"0.1.0\"\njava = \"{}\"\n",
+                name, java
+            );
+            toml::from_str::<JargoToml>(&toml_str).unwrap().package
+        };
+        assert_eq!(package("app", "21").release().unwrap(), 21);
+        assert_eq!(package("app", "1.8").release().unwrap(), 8);
+        assert!(package("app", "latest").release().is_err());
+
+        let client = package("client", "11");
+        let service = package("service", "21");
+        service.check_dependency_release(&client).unwrap();
+        service.check_dependency_release(&service).unwrap();
+        let err = client.check_dependency_release(&service).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`client` (java 11) cannot depend on `service` (java 21); members may only \
+             depend on members with the same or an older `java`"
+        );
+    }
+
+    #[test]
+    fn test_is_app() {
+        let toml = JargoToml::new_app("my-app");
+        assert!(toml.is_app());
+
+        let toml = JargoToml::new_lib("my-lib", "mylib");
+        assert!(!toml.is_app());
+    }
+
+    #[test]
+    fn test_default_type() {
+        let toml_str = r#"
+[package]
+name = "test-app"
+version = "1.0.0"
+java = "21"
+"#;
+        let manifest: JargoToml = toml::from_str(toml_str).unwrap();
+        assert_eq!(manifest.package.project_type, "app");
+    }
+
+    #[test]
+    fn test_no_deps_when_section_absent() {
+        let toml_str = r#"
+[package]
+name = "test-app"
+version = "1.0.0"
+java = "21"
+"#;
+        let manifest: JargoToml = toml::from_str(toml_str).unwrap();
+        assert!(manifest.get_dependencies().unwrap().is_empty());
+        assert!(manifest.get_dev_dependencies().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_simple_dependency() {
+        let toml_str = r#"
+[package]
+name = "test-app"
+version = "1.0.0"
+java = "21"
+
+[dependencies]
+"org.apache.commons:commons-lang3" = "3.14.0"
+"#;
+        let manifest: JargoToml = toml::from_str(toml_str).unwrap();
+        let deps = manifest.get_dependencies().unwrap();
+        assert_eq!(deps.len(), 1);
+        assert_eq!(deps[0].group, "org.apache.commons");
+        assert_eq!(deps[0].artifact, "commons-lang3");
+        assert_eq!(deps[0].version, "3.14.0");
+        assert_eq!(deps[0].scope, Scope::Compile);
+        assert!(!deps[0].expose);
+    }
+
+    #[test]
+    fn test_expanded_dependency_runtime_scope() {
+        let toml_str = r#"
+[package]
+name = "test-app"
+version = "1.0.0"
+java = "21"
+
+[dependencies]
+"org.postgresql:postgresql" = { version = "42.7.1", scope = "runtime" }
+"#;
+        let manifest: JargoToml = toml::from_str(toml_str).unwrap();
+        let deps = manifest.get_dependencies().unwrap();
+        assert_eq!(deps.len(), 1);
+        assert_eq!(deps[0].version, "42.7.1");
+        assert_eq!(deps[0].scope, Scope::Runtime);
+        assert!(!deps[0].expose);
+    }
+
+    #[test]
+    fn test_expanded_dependency_expose() {
+        let toml_str = r#"
+[package]
+name = "my-lib"
+version = "0.1.0"
+type = "lib"
+java = "21"
+
+[dependencies]
+"com.google.guava:guava" = { version = "33.0.0-jre", expose = true }
+"#;
+        let manifest: JargoToml = toml::from_str(toml_str).unwrap();
+        let deps = manifest.get_dependencies().unwrap();
+        assert_eq!(deps.len(), 1);
+        assert_eq!(deps[0].artifact, "guava");
+        assert_eq!(deps[0].scope, Scope::Compile);
+        assert!(deps[0].expose);
+    }
+
+    #[test]
+    fn test_dev_dependencies() {
+        let toml_str = r#"
+[package]
+name = "test-app"
+version = "1.0.0"
+java = "21"
+
+[dev-dependencies]
+"org.assertj:assertj-core" = "3.25.1"
+"#;
+        let manifest: JargoToml = toml::from_str(toml_str).unwrap();
+        assert!(manifest.get_dependencies().unwrap().is_empty());
+        let dev_deps = manifest.get_dev_dependencies().unwrap();
+        assert_eq!(dev_deps.len(), 1);
+        assert_eq!(dev_deps[0].group, "org.assertj");
+        assert_eq!(dev_deps[0].artifact, "assertj-core");
+        assert!(manifest.get_it_dependencies().unwrap().is_empty());
+
+        let with_it: JargoToml = toml::from_str(&format!(
+            "{}\n[it-dependencies]\n\"org.testcontainers:postgresql\" = \"1.19.7\"\n",
+            toml_str
+        ))
+        .unwrap();
+        let it_deps = with_it.get_it_dependencies().unwrap();
+        assert_eq!(it_deps.len(), 1);
+        assert_eq!(it_deps[0].artifact, "postgresql");
+    }
+
+    #[test]
+    fn test_testng_framework() {
+        let toml_str = r#"
+[package]
+name = "test-app"
+version = "1.0.0"
+java = "21"
+
+[test]
+framework = "testng"
+
+[dev-dependencies]
+"org.assertj:assertj-core" = "3.25.1"
+"#;
+        let manifest: JargoToml = toml::from_str(toml_str).unwrap();
+        assert_eq!(manifest.get_test_framework(), TestFramework::TestNg);
+        let dev_deps = manifest.get_dev_dependencies().unwrap();
+        assert_eq!(dev_deps.len(), 2);
+        assert_eq!(dev_deps[1].artifact, "testng");
+        assert_eq!(dev_deps[1].version, TESTNG_VERSION);
+
+        let pinned: JargoToml =
+            toml::from_str(&format!("{}\"org.testng:testng\" = \"7.8.0\"\n", toml_str)).unwrap();
+        let dev_deps = pinned.get_dev_dependencies().unwrap();
+        assert_eq!(dev_deps.len(), 2);
+        assert_eq!(dev_deps[1].version, "7.8.0");
+
+        let junit: JargoToml =
+            toml::from_str(&toml_str.replace("framework = \"testng\"", "")).unwrap();
+        assert_eq!(junit.get_test_framework(), TestFramework::JUnit);
+        assert_eq!(junit.get_dev_dependencies().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_dependencies_sorted() {
+        let toml_str = r#"
+[package]
+name = "test-app"
+version = "1.0.0"
+java = "21"
+
+[dependencies]
+"org.postgresql:postgresql" = "42.7.1"
+"com.google.guava:guava" = "33.0.0-jre"
+"org.apache.commons:commons-lang3" = "3.14.0"
+"#;
+        let manifest: JargoToml = toml::from_str(toml_str).unwrap();
+        let deps = manifest.get_dependencies().unwrap();
+        assert_eq!(deps.len(), 3);
+        // Should be sorted by group then artifact
+        assert_eq!(deps[0].group, "com.google.guava");
+        assert_eq!(deps[1].group, "org.apache.commons");
+        assert_eq!(deps[2].group, "org.postgresql");
+    }
+
+    #[test]
+    fn test_invalid_coordinate_missing_colon() {
+        let toml_str = r#"
+[package]
+name = "test-app"
+version = "1.0.0"
+java = "21"
+
+[dependencies]
+"badcoordinate" = "1.0.0"
+"#;
+        let manifest: JargoToml = toml::from_str(toml_str).unwrap();
+        assert!(manifest.get_dependencies().is_err());
+    }
+
+    #[test]
+    fn test_invalid_scope() {
+        let toml_str = r#"
+[package]
+name = "test-app"
+version = "1.0.0"
+java = "21"
+
+[dependencies]
+"com.example:foo" = { version = "1.0.0", scope = "provided" }
+"#;
+        let manifest: JargoToml = toml::from_str(toml_str).unwrap();
+        assert!(manifest.get_dependencies().is_err());
+    }
+
+    #[test]
+    fn test_inherit_workspace_dependencies() {
+        let toml_str = r#"
+[package]
+name = "test-app"
+version = "1.0.0"
+java = "21"
+
+[dependencies]
+"com.google.guava:guava" = { workspace = true }
+"org.postgresql:postgresql" = { workspace = true, scope = "compile" }
+
+[dev-dependencies]
+"org.junit.jupiter:junit-jupiter" = { workspace = true }
+"#;
+        let inherited: HashMap<String, DependencyValue> = toml::from_str(
+            r#"
+"com.google.guava:guava" = "33.0.0-jre"
+"org.postgresql:postgresql" = { version = "42.7.1", scope = "runtime" }
+"org.junit.jupiter:junit-jupiter" = "5.10.2"
+"#,
+        )
+        .unwrap();
+        let mut manifest: JargoToml = toml::from_str(toml_str).unwrap();
+        let err = manifest.get_dependencies().unwrap_err().to_string();
+        assert!(err.contains("no workspace provided its version"));
+
+        manifest.inherit_workspace_dependencies(&inherited).unwrap();
+        let deps = manifest.get_dependencies().unwrap();
+        assert_eq!(deps[0].version, "33.0.0-jre");
+        assert_eq!(deps[1].version, "42.7.1");
+        assert_eq!(deps[1].scope, Scope::Compile);
+        let dev_deps = manifest.get_dev_dependencies().unwrap();
+        assert_eq!(dev_deps[0].version, "5.10.2");
+
+        let mut manifest: JargoToml = toml::from_str(toml_str).unwrap();
+        let err = manifest
+            .inherit_workspace_dependencies(&HashMap::new())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("not in [workspace.dependencies]"));
+    }
+
+    #[test]
+    fn test_release_steps() {
+        let toml = JargoToml::new_lib("my-lib", "com.example.mylib");
+        assert_eq!(toml.get_release_steps(), ReleaseStep::DEFAULT.to_vec());
+        assert_eq!(toml.get_release_next(), "patch");
+
+        let manifest = manifest_with(
+            "\n[release]\nsteps = [\"version\", \"tag\", \"next\"]\nnext = \"minor\"",
+        );
+        assert_eq!(
+            manifest.get_release_steps(),
+            vec![ReleaseStep::Version, ReleaseStep::Tag, ReleaseStep::Next]
+        );
+        assert_eq!(manifest.get_release_next(), "minor");
+    }
+
+    #[test]
+    fn test_ci_steps() {
+        let toml = JargoToml::new_app("my-app");
+        assert_eq!(toml.get_ci_steps(), CiStep::DEFAULT.to_vec());
+
+        let toml_str = r#"
+[package]
+name = "test-app"
+version = "1.0.0"
+java = "21"
+
+[ci]
+steps = ["fmt", "compile", "test"]
+"#;
+        let manifest: JargoToml = toml::from_str(toml_str).unwrap();
+        assert_eq!(
+            manifest.get_ci_steps(),
+            vec![CiStep::Fmt, CiStep::Compile, CiStep::Test]
+        );
+
+        let toml_str = r#"
+[package]
+name = "test-app"
+version = "1.0.0"
+java = "21"
+
+[ci]
+steps = ["deploy"]
+"#;
+        assert!(toml::from_str::<JargoToml>(toml_str).is_err());
+
+        let toml_str = r#"
+[package]
+name = "test-app"
+version = "1.0.0"
+java = "21"
+
+[ci]
+steps = ["audit", "compile"]
+"#;
+        let manifest: JargoToml = toml::from_str(toml_str).unwrap();
+        assert_eq!(
+            manifest.get_ci_steps(),
+            vec![CiStep::Audit, CiStep::Compile]
+        );
+    }
+
+    #[test]
+    fn test_run_system_properties() {
+        assert!(JargoToml::new_app("my-app")
+            .get_system_properties()
+            .unwrap()
+            .is_empty());
+
+        let toml_str = r#"
+[package]
+name = "test-app"
+version = "1.0.0"
+java = "21"
 
-    /// Get the main class name, defaulting to "Main" if not set.
-    pub fn get_main_class(&self) -> String {
-        self.package
-            .main_class
-            .clone()
-            .unwrap_or_else(|| "Main".to_string())
-    }
+[run]
+system-properties = { "config.path" = "conf/dev.yaml", "app.mode" = "dev" }
+"#;
+        let manifest: JargoToml = toml::from_str(toml_str).unwrap();
+        let properties = manifest.get_system_properties().unwrap();
+        assert_eq!(properties["config.path"], "conf/dev.yaml");
+        assert_eq!(properties.len(), 2);
 
-    /// Check if this is an app project.
-    pub fn is_app(&self) -> bool {
-        self.package.project_type == "app"
+        let invalid: JargoToml =
+            toml::from_str(&toml_str.replace("\"app.mode\"", "\"app mode\"")).unwrap();
+        assert!(invalid.get_system_properties().is_err());
     }
 
-    /// Get JVM args from the [run] section, defaulting to empty.
-    pub fn get_jvm_args(&self) -> &[String] {
-        match &self.run {
-            Some(run_config) => &run_config.jvm_args,
-            None => &[],
-        }
-    }
+    #[test]
+    fn test_test_config() {
+        let toml_str = r#"
+[package]
+name = "test-app"
+version = "1.0.0"
+java = "21"
 
-    /// Parse and return the [dependencies] section as a normalized, sorted list.
-    pub fn get_dependencies(&self) -> Result<Vec<Dependency>> {
-        parse_dependency_map(&self.dependencies)
-    }
+[run]
+jvm-args = ["-Xmx512m"]
 
-    /// Parse and return the [dev-dependencies] section as a normalized, sorted list.
-    #[allow(dead_code)] // used by the test runner (not yet implemented)
-    pub fn get_dev_dependencies(&self) -> Result<Vec<Dependency>> {
-        parse_dependency_map(&self.dev_dependencies)
+[test]
+jvm-args = ["-ea", "-Djava.awt.headless=true"]
+system-properties = { "db.url" = "jdbc:h2:mem:test" }
+env = { APP_ENV = "test" }
+"#;
+        let manifest: JargoToml = toml::from_str(toml_str).unwrap();
+        assert_eq!(manifest.get_jvm_args(), ["-Xmx512m"]);
+        assert_eq!(
+            manifest.get_test_jvm_args(),
+            ["-ea", "-Djava.awt.headless=true"]
+        );
+        let properties = manifest.get_test_system_properties().unwrap();
+        assert_eq!(properties["db.url"], "jdbc:h2:mem:test");
+        assert!(manifest.get_system_properties().unwrap().is_empty());
+        assert_eq!(manifest.test.unwrap().env["APP_ENV"], "test");
+
+        let invalid: JargoToml =
+            toml::from_str(&toml_str.replace("\"db.url\"", "\"db url\"")).unwrap();
+        assert!(invalid.get_test_system_properties().is_err());
     }
-}
 
-/// Parse a raw dependency map (from TOML) into a sorted, normalized list.
-fn parse_dependency_map(map: &HashMap<String, DependencyValue>) -> Result<Vec<Dependency>> {
-    let mut deps = Vec::with_capacity(map.len());
+    #[test]
+    fn test_coverage_minimums() {
+        let toml_str = r#"
+[package]
+name = "test-app"
+version = "1.0.0"
+java = "21"
 
-    for (coord, value) in map {
-        let (group, artifact) = parse_coordinate(coord)?;
-        let (version, scope, expose) = match value {
-            DependencyValue::Simple(v) => (v.clone(), Scope::Compile, false),
-            DependencyValue::Expanded(spec) => {
-                let scope = match spec.scope.as_deref() {
-                    None | Some("compile") => Scope::Compile,
-                    Some("runtime") => Scope::Runtime,
-                    Some(other) => bail!("unknown scope `{}` for `{}`", other, coord),
-                };
-                (spec.version.clone(), scope, spec.expose.unwrap_or(false))
-            }
-        };
-        deps.push(Dependency {
-            group,
-            artifact,
-            version,
-            scope,
-            expose,
-        });
+[test.coverage]
+line = 80
+branch = 62.5
+"#;
+        let manifest: JargoToml = toml::from_str(toml_str).unwrap();
+        assert_eq!(
+            manifest.get_coverage_minimums().unwrap(),
+            [("branch", 62.5), ("line", 80.0)]
+        );
+
+        let invalid: JargoToml = toml::from_str(&toml_str.replace("80", "180")).unwrap();
+        assert!(invalid.get_coverage_minimums().is_err());
     }
 
-    // Sort for determinism — HashMap iteration order is unspecified.
-    deps.sort_by(|a, b| (&a.group, &a.artifact).cmp(&(&b.group, &b.artifact)));
-    Ok(deps)
-}
+    #[test]
+    fn test_mutation_config() {
+        let toml_str = r#"
+[package]
+name = "test-app"
+version = "1.0.0"
+java = "21"
 
-/// Split `"groupId:artifactId"` into its two parts.
-fn parse_coordinate(coord: &str) -> Result<(String, String)> {
-    match coord.splitn(2, ':').collect::<Vec<_>>().as_slice() {
-        [g, a] if !g.is_empty() && !a.is_empty() => Ok((g.to_string(), a.to_string())),
-        _ => bail!(
-            "invalid dependency coordinate `{}`: expected `groupId:artifactId`",
-            coord
-        ),
+[test.mutation]
+threshold = 75
+mutators = ["STRONGER"]
+"#;
+        let manifest: JargoToml = toml::from_str(toml_str).unwrap();
+        assert_eq!(manifest.get_mutation_threshold().unwrap(), Some(75.0));
+        assert_eq!(manifest.get_mutators(), ["STRONGER"]);
+
+        let invalid: JargoToml = toml::from_str(&toml_str.replace("75", "-1")).unwrap();
+        assert!(invalid.get_mutation_threshold().is_err());
     }
-}
 
-/// Derive base-package name from project name by stripping hyphens.
-pub fn derive_base_package(name: &str) -> String {
-    name.replace('-', "")
-}
+    #[test]
+    fn test_lint_fail_on() {
+        let toml = JargoToml::new_app("my-app");
+        assert_eq!(toml.get_lint_fail_on(), LintSeverity::Error);
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let toml_str = r#"
+[package]
+name = "test-app"
+version = "1.0.0"
+java = "21"
 
-    #[test]
-    fn test_derive_base_package() {
-        assert_eq!(derive_base_package("my-app"), "myapp");
-        assert_eq!(derive_base_package("hello"), "hello");
-        assert_eq!(derive_base_package("my-cool-lib"), "mycoollib");
+[lint]
+fail-on = "warning"
+"#;
+        let manifest: JargoToml = toml::from_str(toml_str).unwrap();
+        assert_eq!(manifest.get_lint_fail_on(), LintSeverity::Warning);
+        assert!(LintSeverity::Info < LintSeverity::Warning);
+
+        assert!(toml::from_str::<JargoToml>(&toml_str.replace("warning", "fatal")).is_err());
     }
 
     #[test]
-    fn test_app_toml_generation() {
+    fn test_lint_tools() {
         let toml = JargoToml::new_app("my-app");
-        let s = toml.to_toml_string().unwrap();
-        assert!(s.contains("name = \"my-app\""));
-        assert!(s.contains("type = \"app\""));
-        assert!(s.contains("java = \"21\""));
-        assert!(!s.contains("base-package"));
+        assert_eq!(toml.get_lint_tools(), [LintTool::Checkstyle]);
+
+        let manifest = manifest_with("[lint]\ncheckstyle = false\nspotbugs = true\npmd = true\n");
+        assert_eq!(
+            manifest.get_lint_tools(),
+            [LintTool::Pmd, LintTool::Spotbugs]
+        );
+        assert_eq!(manifest.get_license_header(), None);
+
+        let manifest = manifest_with("[lint.header]\ntemplate = \"Copyright {year} Acme\"\n");
+        assert_eq!(manifest.get_license_header(), Some("Copyright {year} Acme"));
+        assert_eq!(manifest.get_lint_tools(), [LintTool::Checkstyle]);
     }
 
     #[test]
-    fn test_lib_toml_generation() {
-        let toml = JargoToml::new_lib("my-lib", "mylib");
-        let s = toml.to_toml_string().unwrap();
-        assert!(s.contains("name = \"my-lib\""));
-        assert!(s.contains("type = \"lib\""));
-        assert!(s.contains("base-package = \"mylib\""));
+    fn test_doclint() {
+        let toml = JargoToml::new_lib("my-lib", "com.example.lib");
+        assert_eq!(toml.get_doclint(), "none");
+        assert!(!toml.get_doc_deny_warnings());
+
+        let manifest = manifest_with(
+            "[doc]\ndeny-warnings = true\n[doc.doclint]\nmissing = false\nall = true\naccessibility = false\n",
+        );
+        assert_eq!(manifest.get_doclint(), "all,-accessibility,-missing");
+        assert!(manifest.get_doc_deny_warnings());
+
+        let manifest =
+            manifest_with("[doc.doclint]\nsyntax = true\nhtml = true\nmissing = false\n");
+        assert_eq!(manifest.get_doclint(), "html,syntax,-missing");
+
+        let manifest = manifest_with("[doc.doclint]\nmissing = false\n");
+        assert_eq!(manifest.get_doclint(), "none");
     }
 
     #[test]
-    fn test_toml_parsing() {
+    fn test_run_profiles() {
         let toml_str = r#"
 [package]
 name = "test-app"
 version = "1.0.0"
-type = "app"
-java = "17"
+java = "21"
+
+[run.profiles.low-mem]
+jvm-args = ["-Xmx64m"]
+system-properties = { "cache.size" = "16" }
+
+[run.profiles.debug-log]
+env = { LOG_LEVEL = "debug" }
 "#;
         let manifest: JargoToml = toml::from_str(toml_str).unwrap();
-        assert_eq!(manifest.package.name, "test-app");
-        assert_eq!(manifest.package.version, "1.0.0");
-        assert_eq!(manifest.package.project_type, "app");
-        assert_eq!(manifest.package.java, "17");
-        assert!(manifest.package.base_package.is_none());
+        let low_mem = manifest.get_run_profile("low-mem").unwrap();
+        assert_eq!(low_mem.jvm_args, ["-Xmx64m"]);
+        assert_eq!(low_mem.system_properties["cache.size"], "16");
+        assert_eq!(
+            manifest.get_run_profile("debug-log").unwrap().env["LOG_LEVEL"],
+            "debug"
+        );
+
+        let err = manifest.get_run_profile("fast").unwrap_err().to_string();
+        assert!(err.contains("available: debug-log, low-mem"), "{}", err);
+        let err = JargoToml::new_app("my-app")
+            .get_run_profile("fast")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("[run.profiles.fast]"), "{}", err);
     }
 
     #[test]
-    fn test_get_base_package() {
+    fn test_run_debug() {
         let toml = JargoToml::new_app("my-app");
-        assert_eq!(toml.get_base_package(), "myapp");
+        assert_eq!(toml.get_debug_port(), 5005);
+        assert!(!toml.get_debug_suspend());
 
-        let toml = JargoToml::new_lib("my-lib", "com.example.mylib");
-        assert_eq!(toml.get_base_package(), "com.example.mylib");
-    }
+        let toml_str = r#"
+[package]
+name = "test-app"
+version = "1.0.0"
+java = "21"
 
-    #[test]
-    fn test_get_main_class() {
-        let toml = JargoToml::new_app("my-app");
-        assert_eq!(toml.get_main_class(), "Main");
+[run]
+debug-port = 8000
+debug-suspend = true
+"#;
+        let manifest: JargoToml = toml::from_str(toml_str).unwrap();
+        assert_eq!(manifest.get_debug_port(), 8000);
+        assert!(manifest.get_debug_suspend());
     }
 
     #[test]
-    fn test_is_app() {
+    fn test_run_agents() {
         let toml = JargoToml::new_app("my-app");
-        assert!(toml.is_app());
+        assert!(toml.get_agents().unwrap().is_empty());
 
-        let toml = JargoToml::new_lib("my-lib", "mylib");
-        assert!(!toml.is_app());
-    }
+        let toml_str = r#"
+[package]
+name = "test-app"
+version = "1.0.0"
+java = "21"
+
+[run]
+agents = [
+    "io.opentelemetry.javaagent:opentelemetry-javaagent:2.10.0",
+    "org.example:agent:1.0",
+]
+"#;
+        let manifest: JargoToml = toml::from_str(toml_str).unwrap();
+        let agents = manifest.get_agents().unwrap();
+        assert_eq!(agents.len(), 2);
+        assert_eq!(agents[0].group, "io.opentelemetry.javaagent");
+        assert_eq!(agents[0].artifact, "opentelemetry-javaagent");
+        assert_eq!(agents[0].version, "2.10.0");
+        assert_eq!(agents[1].artifact, "agent");
 
-    #[test]
-    fn test_default_type() {
         let toml_str = r#"
 [package]
 name = "test-app"
 version = "1.0.0"
 java = "21"
+
+[run]
+agents = ["org.example:agent"]
 "#;
         let manifest: JargoToml = toml::from_str(toml_str).unwrap();
-        assert_eq!(manifest.package.project_type, "app");
+        assert!(manifest.get_agents().is_err());
     }
 
     #[test]
-    fn test_no_deps_when_section_absent() {
+    fn test_build_info_toggle() {
+        let toml = JargoToml::new_app("my-app");
+        assert!(!toml.build_info_enabled());
+
         let toml_str = r#"
 [package]
 name = "test-app"
 version = "1.0.0"
 java = "21"
+
+[build]
+build-info = true
 "#;
         let manifest: JargoToml = toml::from_str(toml_str).unwrap();
-        assert!(manifest.get_dependencies().unwrap().is_empty());
-        assert!(manifest.get_dev_dependencies().unwrap().is_empty());
+        assert!(manifest.build_info_enabled());
     }
 
     #[test]
-    fn test_simple_dependency() {
+    fn test_javac_args() {
+        assert!(manifest_with("").get_javac_args().is_empty());
+        let m = manifest_with("[javac]\nlint = [\"all\", \"-serial\"]\ndeny-warnings = true\n");
+        assert_eq!(m.get_javac_args(), ["-Xlint:all,-serial", "-Werror"]);
+    }
+
+    #[test]
+    fn test_javac_memory_absent() {
+        let toml = JargoToml::new_app("my-app");
+        assert!(toml.get_javac_jvm_args().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_javac_memory_preset() {
         let toml_str = r#"
 [package]
 name = "test-app"
 version = "1.0.0"
 java = "21"
 
-[dependencies]
-"org.apache.commons:commons-lang3" = "3.14.0"
+[build]
+javac-memory = "large"
 "#;
         let manifest: JargoToml = toml::from_str(toml_str).unwrap();
-        let deps = manifest.get_dependencies().unwrap();
-        assert_eq!(deps.len(), 1);
-        assert_eq!(deps[0].group, "org.apache.commons");
-        assert_eq!(deps[0].artifact, "commons-lang3");
-        assert_eq!(deps[0].version, "3.14.0");
-        assert_eq!(deps[0].scope, Scope::Compile);
-        assert!(!deps[0].expose);
+        assert_eq!(
+            manifest.get_javac_jvm_args().unwrap(),
+            vec!["-Xmx2g", "-XX:MaxMetaspaceSize=1g"]
+        );
     }
 
     #[test]
-    fn test_expanded_dependency_runtime_scope() {
+    fn test_javac_memory_explicit() {
         let toml_str = r#"
 [package]
 name = "test-app"
 version = "1.0.0"
 java = "21"
 
-[dependencies]
-"org.postgresql:postgresql" = { version = "42.7.1", scope = "runtime" }
+[build]
+javac-memory = { heap = "3g" }
 "#;
         let manifest: JargoToml = toml::from_str(toml_str).unwrap();
-        let deps = manifest.get_dependencies().unwrap();
-        assert_eq!(deps.len(), 1);
-        assert_eq!(deps[0].version, "42.7.1");
-        assert_eq!(deps[0].scope, Scope::Runtime);
-        assert!(!deps[0].expose);
+        assert_eq!(manifest.get_javac_jvm_args().unwrap(), vec!["-Xmx3g"]);
     }
 
     #[test]
-    fn test_expanded_dependency_expose() {
+    fn test_javac_memory_invalid() {
         let toml_str = r#"
 [package]
-name = "my-lib"
-version = "0.1.0"
-type = "lib"
+name = "test-app"
+version = "1.0.0"
 java = "21"
 
-[dependencies]
-"com.google.guava:guava" = { version = "33.0.0-jre", expose = true }
+[build]
+javac-memory = "huge"
 "#;
         let manifest: JargoToml = toml::from_str(toml_str).unwrap();
-        let deps = manifest.get_dependencies().unwrap();
-        assert_eq!(deps.len(), 1);
-        assert_eq!(deps[0].artifact, "guava");
-        assert_eq!(deps[0].scope, Scope::Compile);
-        assert!(deps[0].expose);
+        assert!(manifest.get_javac_jvm_args().is_err());
+
+        assert!(validate_memory_size("512m").is_ok());
+        assert!(validate_memory_size("1024").is_ok());
+        assert!(validate_memory_size("2gb").is_err());
+        assert!(validate_memory_size("g").is_err());
+        assert!(validate_memory_size("-1g").is_err());
     }
 
     #[test]
-    fn test_dev_dependencies() {
+    fn test_tasks() {
         let toml_str = r#"
 [package]
 name = "test-app"
 version = "1.0.0"
 java = "21"
 
-[dev-dependencies]
-"org.assertj:assertj-core" = "3.25.1"
+[tasks]
+lint = "jargo check --fmt"
+e2e = "java -cp target/test-app.jar e2e.Main"
 "#;
         let manifest: JargoToml = toml::from_str(toml_str).unwrap();
-        assert!(manifest.get_dependencies().unwrap().is_empty());
-        let dev_deps = manifest.get_dev_dependencies().unwrap();
-        assert_eq!(dev_deps.len(), 1);
-        assert_eq!(dev_deps[0].group, "org.assertj");
-        assert_eq!(dev_deps[0].artifact, "assertj-core");
+        assert_eq!(
+            manifest.tasks.keys().collect::<Vec<_>>(),
+            vec!["e2e", "lint"]
+        );
+        assert_eq!(manifest.tasks["lint"], "jargo check --fmt");
+
+        let s = JargoToml::new_app("my-app").to_toml_string().unwrap();
+        assert!(!s.contains("[tasks]"));
     }
 
     #[test]
-    fn test_dependencies_sorted() {
-        let toml_str = r#"
+    fn test_features() {
+        let base = r#"
 [package]
 name = "test-app"
 version = "1.0.0"
 java = "21"
 
 [dependencies]
-"org.postgresql:postgresql" = "42.7.1"
-"com.google.guava:guava" = "33.0.0-jre"
-"org.apache.commons:commons-lang3" = "3.14.0"
+"org.slf4j:slf4j-api" = "2.0.12"
+"org.postgresql:postgresql" = { version = "42.7.1", optional = true }
+"io.micrometer:micrometer-core" = { version = "1.12.2", optional = true }
 "#;
-        let manifest: JargoToml = toml::from_str(toml_str).unwrap();
-        let deps = manifest.get_dependencies().unwrap();
-        assert_eq!(deps.len(), 3);
-        // Should be sorted by group then artifact
-        assert_eq!(deps[0].group, "com.google.guava");
-        assert_eq!(deps[1].group, "org.apache.commons");
-        assert_eq!(deps[2].group, "org.postgresql");
+        let parse = |features: &str| -> JargoToml {
+            toml::from_str(&format!("{}\n[features]\n{}", base, features)).unwrap()
+        };
+
+        let manifest = parse(
+            r#"
+postgres = ["org.postgresql:postgresql"]
+metrics = ["io.micrometer:micrometer-core"]
+full = ["postgres", "metrics"]
+"#,
+        );
+        let features = manifest.get_features().unwrap();
+        assert_eq!(
+            features["full"].iter().collect::<Vec<_>>(),
+            vec!["io.micrometer:micrometer-core", "org.postgresql:postgresql"]
+        );
+        assert_eq!(features["postgres"].len(), 1);
+        assert_eq!(manifest.optional_dependencies().len(), 2);
+
+        let err = parse(
+            r#"
+postgres = ["org.postgresql:postgresql", "org.slf4j:slf4j-api"]
+metrics = ["io.micrometer:micrometer-core"]
+"#,
+        )
+        .get_features()
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("`org.slf4j:slf4j-api`, which is not an `optional = true`"));
+
+        let err = parse(
+            r#"
+postgres = ["org.postgresql:postgresql", "tracing"]
+metrics = ["io.micrometer:micrometer-core"]
+"#,
+        )
+        .get_features()
+        .unwrap_err();
+        assert!(err.to_string().contains("includes `tracing`"));
+
+        let err = parse(r#"postgres = ["org.postgresql:postgresql"]"#)
+            .get_features()
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("`io.micrometer:micrometer-core` is optional"));
     }
 
     #[test]
-    fn test_invalid_coordinate_missing_colon() {
+    fn test_jar_manifest_attributes() {
         let toml_str = r#"
 [package]
 name = "test-app"
 version = "1.0.0"
 java = "21"
 
-[dependencies]
-"badcoordinate" = "1.0.0"
+[jar.manifest]
+"Automatic-Module-Name" = "com.example.app"
+"Add-Opens" = "java.base/java.lang"
 "#;
         let manifest: JargoToml = toml::from_str(toml_str).unwrap();
-        assert!(manifest.get_dependencies().is_err());
+        assert_eq!(
+            manifest.get_manifest_attributes().unwrap(),
+            vec![
+                ("Add-Opens", "java.base/java.lang"),
+                ("Automatic-Module-Name", "com.example.app"),
+            ]
+        );
+        assert!(JargoToml::new_app("my-app")
+            .get_manifest_attributes()
+            .unwrap()
+            .is_empty());
+
+        for bad in ["\"main-class\" = \"x.Main\"", "\"Bad Name\" = \"x\""] {
+            let toml_str = format!(
+                "[package]\nname = \"a\"\nversion = \"1.0.0\"\njava = \"21\"\n[jar.manifest]\n{}\n",
+                bad
+            );
+            let manifest: JargoToml = toml::from_str(&toml_str).unwrap();
+            assert!(manifest.get_manifest_attributes().is_err(), "{}", bad);
+        }
     }
 
     #[test]
-    fn test_invalid_scope() {
+    fn test_shade_relocations() {
         let toml_str = r#"
 [package]
 name = "test-app"
 version = "1.0.0"
 java = "21"
 
-[dependencies]
-"com.example:foo" = { version = "1.0.0", scope = "provided" }
+[shade.relocate]
+"com.google.common" = "testapp.shaded.guava"
 "#;
         let manifest: JargoToml = toml::from_str(toml_str).unwrap();
-        assert!(manifest.get_dependencies().is_err());
+        assert_eq!(
+            manifest.get_relocations()["com.google.common"],
+            "testapp.shaded.guava"
+        );
+        assert!(JargoToml::new_app("my-app").get_relocations().is_empty());
     }
 
     #[test]
@@ -452,4 +2958,43 @@ java = "21"
         assert!(!s.contains("[dependencies]"));
         assert!(!s.contains("[dev-dependencies]"));
     }
+
+    #[test]
+    fn test_bins() {
+        let single = JargoToml::new_app("my-app");
+        let bin = single.get_bin(None).unwrap();
+        assert_eq!(bin.name, "my-app");
+        assert_eq!(single.main_class_of(&bin), "myapp.Main");
+
+        let toml_str = r#"
+[package]
+name = "my-app"
+version = "1.0.0"
+java = "21"
+
+[[bin]]
+name = "server"
+main-class = "Main"
+
+[[bin]]
+name = "migrate"
+main-class = "tools.Migrate"
+"#;
+        let manifest: JargoToml = toml::from_str(toml_str).unwrap();
+        assert_eq!(manifest.get_bins().unwrap().len(), 2);
+        let migrate = manifest.get_bin(Some("migrate")).unwrap();
+        assert_eq!(manifest.main_class_of(&migrate), "myapp.tools.Migrate");
+        let err = manifest.get_bin(None).unwrap_err().to_string();
+        assert!(err.contains("pass `--bin` with one of: server, migrate"));
+        let err = manifest.get_bin(Some("nope")).unwrap_err().to_string();
+        assert!(err.contains("no bin target named `nope`"));
+
+        let duplicate: JargoToml = toml::from_str(&toml_str.replace("migrate", "server")).unwrap();
+        assert!(duplicate.get_bins().is_err());
+        let with_main: JargoToml = toml::from_str(
+            &toml_str.replace("java = \"21\"", "java = \"21\"\nmain-class = \"App\""),
+        )
+        .unwrap();
+        assert!(with_main.get_bins().is_err());
+    }
 }
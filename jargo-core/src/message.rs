@@ -0,0 +1,88 @@
+use serde::Serialize;
+use std::path::Path;
+
+use crate::compiler::{render_diagnostics, Diagnostic};
+
+/// One line of `--message-format json` output, tagged by `reason` like Cargo's
+/// messages: `{"reason":"compiler-artifact","package":"demo",...}`.
+#[derive(Debug, Serialize)]
+#[serde(tag = "reason", rename_all = "kebab-case")]
+pub enum Message<'a> {
+    /// A `javac` error or warning.
+    CompilerMessage {
+        package: &'a str,
+        message: &'a Diagnostic,
+        /// The diagnostic as it would be printed in human mode, without color.
+        rendered: String,
+    },
+    /// A build output: the `"classes"` directory or the `"jar"`.
+    CompilerArtifact {
+        package: &'a str,
+        kind: &'a str,
+        path: &'a Path,
+    },
+    /// One finished test case.
+    TestResult {
+        package: &'a str,
+        class: &'a str,
+        name: &'a str,
+        /// `"passed"`, `"failed"`, or `"skipped"`.
+        outcome: &'a str,
+        time: f64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<&'a str>,
+    },
+    /// Last message of a command.
+    BuildFinished { success: bool },
+}
+
+impl<'a> Message<'a> {
+    pub fn compiler_message(package: &'a str, diagnostic: &'a Diagnostic) -> Self {
+        Message::CompilerMessage {
+            package,
+            message: diagnostic,
+            rendered: render_diagnostics(std::slice::from_ref(diagnostic), false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::parse_diagnostics;
+
+    #[test]
+    fn test_message_json() {
+        let diagnostics = parse_diagnostics(&[
+            "src/Main.java:5: error: cannot find symbol".to_string(),
+            "        foo();".to_string(),
+            "        ^".to_string(),
+        ]);
+        let json =
+            serde_json::to_value(Message::compiler_message("demo", &diagnostics[0])).unwrap();
+        assert_eq!(json["reason"], "compiler-message");
+        assert_eq!(json["package"], "demo");
+        assert_eq!(json["message"]["severity"], "error");
+        assert_eq!(json["message"]["file"], "src/Main.java");
+        assert_eq!(json["message"]["line"], 5);
+        assert_eq!(json["message"]["column"], 9);
+        assert!(json["rendered"]
+            .as_str()
+            .unwrap()
+            .starts_with("error: cannot find symbol\n"));
+
+        let json = serde_json::to_string(&Message::CompilerArtifact {
+            package: "demo",
+            kind: "jar",
+            path: Path::new("target/demo.jar"),
+        })
+        .unwrap();
+        assert_eq!(
+            json,
+            r#"{"reason":"compiler-artifact","package":"demo","kind":"jar","path":"target/demo.jar"}"#
+        );
+
+        let json = serde_json::to_string(&Message::BuildFinished { success: true }).unwrap();
+        assert_eq!(json, r#"{"reason":"build-finished","success":true}"#);
+    }
+}
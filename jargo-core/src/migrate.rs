@@ -0,0 +1,1017 @@
+use anyhow::{bail, Context, Result};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+use crate::bytecode;
+use crate::manifest::JargoToml;
+use crate::pom::RawDep;
+use crate::resolver::substitute_props;
+
+/// Build files `jargo migrate` reads, in the order it looks for them.
+pub const BUILD_FILES: [&str; 3] = ["pom.xml", "build.gradle.kts", "build.gradle"];
+
+/// The classpaths an imported dependency belongs on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImportedScope {
+    /// `[dependencies]`; `expose` for Gradle's `api` configuration.
+    Compile { expose: bool },
+    /// `[dependencies]` with `scope = "runtime"`.
+    Runtime,
+    /// `[dev-dependencies]`.
+    Test,
+}
+
+/// A dependency read from a build file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedDep {
+    pub group: String,
+    pub artifact: String,
+    pub version: String,
+    pub scope: ImportedScope,
+}
+
+/// What `jargo migrate` read from a Maven or Gradle build.
+#[derive(Debug, Default)]
+pub struct Imported {
+    /// The build file read, one of [`BUILD_FILES`].
+    pub build_file: &'static str,
+    pub name: Option<String>,
+    pub group: Option<String>,
+    pub version: Option<String>,
+    /// Feature release, with `1.8` already read as `8`.
+    pub java: Option<String>,
+    /// Fully-qualified main class.
+    pub main_class: Option<String>,
+    pub dependencies: Vec<ImportedDep>,
+    /// What could not be carried over as is, one sentence each.
+    pub notes: Vec<String>,
+}
+
+/// Read the Maven or Gradle build in `project_root`. `None` when it has
+/// none of [`BUILD_FILES`].
+///
+/// Fails for multi-module builds, which map to a workspace rather than a
+/// single package.
+pub fn read(project_root: &Path) -> Result<Option<Imported>> {
+    let Some(build_file) = BUILD_FILES
+        .into_iter()
+        .find(|file| project_root.join(file).is_file())
+    else {
+        return Ok(None);
+    };
+    let path = project_root.join(build_file);
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    let read_optional =
+        |file: &str| fs::read_to_string(project_root.join(file)).unwrap_or_default();
+
+    let mut imported = if build_file == "pom.xml" {
+        parse_pom(&content)
+    } else {
+        let settings = if project_root.join("settings.gradle.kts").is_file() {
+            read_optional("settings.gradle.kts")
+        } else {
+            read_optional("settings.gradle")
+        };
+        parse_gradle(
+            build_file,
+            &content,
+            &settings,
+            &read_optional("gradle.properties"),
+            &read_optional("gradle/libs.versions.toml"),
+        )
+    }
+    .with_context(|| format!("failed to read {}", path.display()))?;
+    imported.build_file = build_file;
+    if imported.java.is_none() {
+        imported.notes.push(format!(
+            "{} sets no Java version; Jargo.toml uses jargo's default",
+            build_file
+        ));
+    }
+    Ok(Some(imported))
+}
+
+impl Imported {
+    /// Render a Jargo.toml for the build: an app running `main_class`
+    /// (relative to `base_package`), or a lib when it is `None`.
+    pub fn manifest(
+        &self,
+        name: &str,
+        base_package: &str,
+        main_class: Option<&str>,
+    ) -> Result<String> {
+        let mut toml = match main_class {
+            Some(main_class) => {
+                let mut toml = JargoToml::new_app(name);
+                toml.package.base_package = Some(base_package.to_string());
+                toml.package.main_class = Some(main_class.to_string()).filter(|c| c != "Main");
+                toml
+            }
+            None => JargoToml::new_lib(name, base_package),
+        };
+        if let Some(version) = &self.version {
+            toml.package.version = version.clone();
+        }
+        toml.package.group = self.group.clone().filter(|g| g != base_package);
+        if let Some(java) = &self.java {
+            toml.package.java = java.clone();
+        }
+        let mut out = toml
+            .to_toml_string()
+            .context("failed to serialize Jargo.toml")?;
+
+        let mut dependencies = BTreeMap::new();
+        let mut dev_dependencies = BTreeMap::new();
+        for dep in &self.dependencies {
+            let version = toml::Value::String(dep.version.clone()).to_string();
+            let value = match dep.scope {
+                ImportedScope::Compile { expose: true } if main_class.is_none() => {
+                    format!("{{ version = {}, expose = true }}", version)
+                }
+                ImportedScope::Runtime => {
+                    format!("{{ version = {}, scope = \"runtime\" }}", version)
+                }
+                _ => version,
+            };
+            let section = if dep.scope == ImportedScope::Test {
+                &mut dev_dependencies
+            } else {
+                &mut dependencies
+            };
+            section
+                .entry(format!("{}:{}", dep.group, dep.artifact))
+                .or_insert(value);
+        }
+        dev_dependencies.retain(|coord, _| !dependencies.contains_key(coord));
+
+        if dev_dependencies.contains_key("org.testng:testng") {
+            out.push_str("\n[test]\nframework = \"testng\"\n");
+        }
+        for (table, section) in [
+            ("dependencies", &dependencies),
+            ("dev-dependencies", &dev_dependencies),
+        ] {
+            if section.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("\n[{}]\n", table));
+            for (coord, value) in section {
+                out.push_str(&format!("\"{}\" = {}\n", coord, value));
+            }
+        }
+        Ok(out)
+    }
+
+    /// Add `group:artifact:version` under `scope`, noting when it names a
+    /// classifier jargo cannot express or has no version to pin.
+    fn add_coordinate(&mut self, coordinate: &str, scope: ImportedScope) {
+        match coordinate.split(':').collect::<Vec<_>>().as_slice() {
+            [group, artifact, version] if !version.is_empty() => {
+                self.dependencies.push(ImportedDep {
+                    group: group.to_string(),
+                    artifact: artifact.to_string(),
+                    version: version.to_string(),
+                    scope,
+                })
+            }
+            [group, artifact] | [group, artifact, _] => self.notes.push(format!(
+                "`{}:{}` has no version in {} (a parent, platform or BOM manages it); add it to Jargo.toml by hand",
+                group, artifact, self.build_file
+            )),
+            _ => self.notes.push(format!(
+                "`{}` names a classifier or type, which jargo does not support; add it to Jargo.toml by hand",
+                coordinate
+            )),
+        }
+    }
+}
+
+/// Normalize a Java version as build files write it (`17`, `1.8`,
+/// `VERSION_1_8`) to a feature release.
+fn java_release(value: &str) -> Option<String> {
+    let value = match value.find("VERSION_") {
+        Some(i) => value[i + "VERSION_".len()..].replace('_', "."),
+        None => value.to_string(),
+    };
+    let start = value.find(|c: char| c.is_ascii_digit())?;
+    let digits: String = value[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    bytecode::parse_release(digits.trim_end_matches('.')).map(|release| release.to_string())
+}
+
+// ---------------------------------------------------------------------------
+// Maven
+// ---------------------------------------------------------------------------
+
+fn parse_pom(xml: &str) -> Result<Imported> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+    let mut stack: Vec<String> = Vec::new();
+
+    let mut project: HashMap<String, String> = HashMap::new();
+    let mut parent: HashMap<String, String> = HashMap::new();
+    let mut properties: HashMap<String, String> = HashMap::new();
+    let mut managed: HashMap<(String, String), String> = HashMap::new();
+    // Direct dependencies with their `<type>` or `<classifier>`, if any
+    let mut direct_deps: Vec<(RawDep, String)> = Vec::new();
+    let mut modules = false;
+    let mut main_class: Option<String> = None;
+    let mut compiler_release: Option<String> = None;
+    let mut compiler_source: Option<String> = None;
+
+    let mut cur = RawDep {
+        group: String::new(),
+        artifact: String::new(),
+        version: String::new(),
+        scope: String::new(),
+        optional: false,
+    };
+    let mut cur_type = String::new();
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                let name = String::from_utf8_lossy(e.name().local_name().as_ref()).into_owned();
+                if name == "dependency" {
+                    cur.group.clear();
+                    cur.artifact.clear();
+                    cur.version.clear();
+                    cur.scope.clear();
+                    cur_type.clear();
+                }
+                stack.push(name);
+            }
+            Ok(Event::Text(e)) => {
+                let text = e.unescape().context("non-UTF8 text in POM")?.into_owned();
+                let path: Vec<&str> = stack.iter().map(String::as_str).collect();
+                match path.as_slice() {
+                    [_, tag @ ("groupId" | "artifactId" | "version")] => {
+                        project.insert(tag.to_string(), text);
+                    }
+                    [_, "parent", tag @ ("groupId" | "version")] => {
+                        parent.insert(tag.to_string(), text);
+                    }
+                    [_, "properties", key] => {
+                        properties.insert(key.to_string(), text);
+                    }
+                    [_, "modules", "module"] => modules = true,
+                    [_, "dependencies", "dependency", tag]
+                    | [_, "dependencyManagement", "dependencies", "dependency", tag] => {
+                        match *tag {
+                            "groupId" => cur.group = text,
+                            "artifactId" => cur.artifact = text,
+                            "version" => cur.version = text,
+                            "scope" => cur.scope = text,
+                            "type" | "classifier" => cur_type = text,
+                            _ => {}
+                        }
+                    }
+                    [_, "build", .., tag] if path.contains(&"plugin") => match *tag {
+                        "mainClass" => {
+                            main_class.get_or_insert(text);
+                        }
+                        "release" if path.contains(&"configuration") => {
+                            compiler_release.get_or_insert(text);
+                        }
+                        "source" if path.contains(&"configuration") => {
+                            compiler_source.get_or_insert(text);
+                        }
+                        _ => {}
+                    },
+                    _ => {}
+                }
+            }
+            Ok(Event::End(_)) => {
+                let path: Vec<&str> = stack.iter().map(String::as_str).collect();
+                match path.as_slice() {
+                    [_, "dependencies", "dependency"] => {
+                        direct_deps.push((cur.clone(), cur_type.clone()));
+                    }
+                    [_, "dependencyManagement", "dependencies", "dependency"] => {
+                        managed.insert(
+                            (cur.group.clone(), cur.artifact.clone()),
+                            cur.version.clone(),
+                        );
+                    }
+                    _ => {}
+                }
+                stack.pop();
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => bail!("XML parse error: {}", e),
+            _ => {}
+        }
+    }
+
+    if modules {
+        bail!("it is a multi-module build; run `jargo migrate` in each module instead");
+    }
+
+    let mut imported = Imported {
+        build_file: "pom.xml",
+        ..Imported::default()
+    };
+    let group = project
+        .get("groupId")
+        .or(parent.get("groupId"))
+        .cloned()
+        .unwrap_or_default();
+    let version = project
+        .get("version")
+        .or(parent.get("version"))
+        .cloned()
+        .unwrap_or_default();
+    let artifact = project.get("artifactId").cloned().unwrap_or_default();
+    for (key, value) in [
+        ("project.groupId", &group),
+        ("project.artifactId", &artifact),
+        ("project.version", &version),
+    ] {
+        properties.insert(key.to_string(), value.clone());
+    }
+    // A value still holding `${...}` refers to a parent's properties
+    let resolve = |value: &str| {
+        Some(substitute_props(value, &properties)).filter(|v| !v.is_empty() && !v.contains("${"))
+    };
+
+    imported.name = resolve(&artifact);
+    imported.group = resolve(&group);
+    imported.version = resolve(&version);
+    imported.java = [
+        compiler_release.as_deref(),
+        properties.get("maven.compiler.release").map(String::as_str),
+        compiler_source.as_deref(),
+        properties.get("maven.compiler.source").map(String::as_str),
+        properties.get("java.version").map(String::as_str),
+    ]
+    .into_iter()
+    .flatten()
+    .find_map(|value| java_release(&resolve(value)?));
+    imported.main_class = main_class.as_deref().and_then(resolve);
+
+    for (dep, kind) in direct_deps {
+        let coord = format!("{}:{}", dep.group, dep.artifact);
+        if !kind.is_empty() && kind != "jar" {
+            imported.notes.push(format!(
+                "`{}` names a classifier or type, which jargo does not support; add it to Jargo.toml by hand",
+                coord
+            ));
+            continue;
+        }
+        let scope = match dep.scope.as_str() {
+            "" | "compile" => ImportedScope::Compile { expose: false },
+            "runtime" => ImportedScope::Runtime,
+            "test" => ImportedScope::Test,
+            "provided" => {
+                imported.notes.push(format!(
+                    "`{}` has provided scope in pom.xml; jargo has no such scope, so it is a compile dependency",
+                    coord
+                ));
+                ImportedScope::Compile { expose: false }
+            }
+            scope => {
+                imported.notes.push(format!(
+                    "`{}` has {} scope in pom.xml, which jargo does not support; it was left out",
+                    coord, scope
+                ));
+                continue;
+            }
+        };
+        let version = if dep.version.is_empty() {
+            managed
+                .get(&(dep.group.clone(), dep.artifact.clone()))
+                .cloned()
+                .unwrap_or_default()
+        } else {
+            dep.version
+        };
+        match resolve(&version) {
+            Some(version) => imported.add_coordinate(&format!("{}:{}", coord, version), scope),
+            None => imported.add_coordinate(&coord, scope),
+        }
+    }
+    Ok(imported)
+}
+
+// ---------------------------------------------------------------------------
+// Gradle
+// ---------------------------------------------------------------------------
+
+/// Read a Gradle build script line by line. Only the common shapes are
+/// understood: string and map dependency notations, version catalog
+/// accessors, and plain `name = "value"` variables; anything else in a
+/// `dependencies` block is noted for the user.
+fn parse_gradle(
+    build_file: &'static str,
+    script: &str,
+    settings: &str,
+    properties: &str,
+    catalog: &str,
+) -> Result<Imported> {
+    let script = strip_comments(script);
+    let settings = strip_comments(settings);
+    let mut imported = Imported {
+        build_file,
+        ..Imported::default()
+    };
+
+    let mut vars: HashMap<String, String> = HashMap::new();
+    for line in properties.lines() {
+        if let Some((key, value)) = line.split_once('=') {
+            if !line.trim_start().starts_with('#') {
+                vars.insert(key.trim().to_string(), value.trim().to_string());
+            }
+        }
+    }
+    for line in script.lines().chain(settings.lines()) {
+        if let Some((name, value)) = parse_assignment(line.trim()) {
+            if let Some(value) = interpolate(value, &vars) {
+                vars.insert(name.to_string(), value);
+            }
+        }
+    }
+    let catalog = parse_catalog(catalog).context("failed to read gradle/libs.versions.toml")?;
+
+    for line in settings.lines().map(str::trim) {
+        let included = line
+            .strip_prefix("include")
+            .is_some_and(|rest| rest.starts_with([' ', '(', '"', '\'']));
+        if included {
+            bail!("it is a multi-project build; run `jargo migrate` in each project instead");
+        }
+    }
+    imported.name = vars.get("rootProject.name").cloned();
+    imported.group = vars.get("group").cloned();
+    imported.version = vars.get("version").cloned().filter(|v| v != "unspecified");
+
+    let mut toolchain = None;
+    let mut release = None;
+    let mut source = None;
+    for line in script.lines().map(str::trim) {
+        if let Some(i) = line.find("JavaLanguageVersion.of(") {
+            toolchain = toolchain.or_else(|| java_release(&line[i..]));
+        } else if is_setting(line.trim_start_matches("options."), "release") {
+            release = release.or_else(|| java_release(line));
+        } else if is_setting(line.trim_start_matches("java."), "sourceCompatibility") {
+            source = source.or_else(|| {
+                let value = line.split_once('=').map_or(line, |(_, value)| value);
+                java_release(&interpolate(value.trim(), &vars)?)
+            });
+        } else if is_setting(line, "mainClass") || is_setting(line, "mainClassName") {
+            if imported.main_class.is_none() {
+                imported.main_class = first_quoted(line).and_then(|v| interpolate(v, &vars));
+            }
+        } else if let Some(i) = line.find("Main-Class") {
+            if imported.main_class.is_none() {
+                imported.main_class = line[i + "Main-Class".len()..]
+                    .get(1..)
+                    .and_then(first_quoted)
+                    .and_then(|v| interpolate(v, &vars));
+            }
+        } else if let Some((configuration, notation)) = parse_dependency(line) {
+            let scope = match configuration {
+                "implementation" | "compile" => ImportedScope::Compile { expose: false },
+                "api" => ImportedScope::Compile { expose: true },
+                "runtimeOnly" | "runtime" => ImportedScope::Runtime,
+                "testImplementation" | "testCompileOnly" | "testRuntimeOnly" | "testCompile"
+                | "testRuntime" => ImportedScope::Test,
+                "compileOnly" | "annotationProcessor" => ImportedScope::Compile { expose: false },
+                _ => continue,
+            };
+            let Some(coordinate) = dependency_coordinate(notation, &vars, &catalog) else {
+                imported.notes.push(format!(
+                    "`{}` in {} could not be read; add it to Jargo.toml by hand",
+                    line, build_file
+                ));
+                continue;
+            };
+            if matches!(configuration, "compileOnly" | "annotationProcessor") {
+                imported.notes.push(format!(
+                    "`{}` is {} in {}; jargo has no such configuration, so it is a compile dependency",
+                    coordinate, configuration, build_file
+                ));
+            }
+            imported.add_coordinate(&coordinate, scope);
+        }
+    }
+    imported.java = toolchain.or(release).or(source);
+    Ok(imported)
+}
+
+/// Drop `//` and `/* */` comments outside string literals.
+fn strip_comments(script: &str) -> String {
+    let mut out = String::with_capacity(script.len());
+    let mut chars = script.chars().peekable();
+    let mut quote: Option<char> = None;
+    let mut in_block = false;
+    while let Some(c) = chars.next() {
+        if in_block {
+            if c == '*' && chars.peek() == Some(&'/') {
+                chars.next();
+                in_block = false;
+            } else if c == '\n' {
+                out.push(c);
+            }
+            continue;
+        }
+        match (quote, c) {
+            (Some(_), '\\') => {
+                out.push(c);
+                out.extend(chars.next());
+            }
+            (Some(q), _) => {
+                out.push(c);
+                if c == q || c == '\n' {
+                    quote = None;
+                }
+            }
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                out.push(c);
+            }
+            (None, '/') if chars.peek() == Some(&'/') => {
+                while chars.peek().is_some_and(|&n| n != '\n') {
+                    chars.next();
+                }
+            }
+            (None, '/') if chars.peek() == Some(&'*') => {
+                chars.next();
+                in_block = true;
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Whether `line` sets `name`: `name = ...`, `name.set(...)` or Groovy's
+/// `name ...`.
+fn is_setting(line: &str, name: &str) -> bool {
+    line.strip_prefix(name)
+        .is_some_and(|rest| rest.starts_with([' ', '=', '.', '(']))
+}
+
+/// `name = "value"`, optionally declared with `val`, `var`, `def` or
+/// `ext.`, as `(name, value)`. The value is still to be interpolated.
+fn parse_assignment(line: &str) -> Option<(&str, &str)> {
+    let line = ["val ", "var ", "def ", "ext."]
+        .iter()
+        .find_map(|prefix| line.strip_prefix(prefix))
+        .unwrap_or(line);
+    let (name, value) = line.split_once('=')?;
+    // Kotlin may declare the type: `val junitVersion: String = "5.10.0"`
+    let name = name.split(':').next()?.trim();
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+    {
+        return None;
+    }
+    Some((name, unquote(value.trim())?))
+}
+
+/// The contents of a single- or double-quoted string literal.
+fn unquote(value: &str) -> Option<&str> {
+    let quote = value.chars().next().filter(|c| matches!(c, '"' | '\''))?;
+    value
+        .strip_prefix(quote)?
+        .strip_suffix(quote)
+        .filter(|inner| !inner.contains(quote))
+}
+
+/// The contents of the first string literal in `line`.
+fn first_quoted(line: &str) -> Option<&str> {
+    let start = line.find(['"', '\''])?;
+    let quote = line[start..].chars().next()?;
+    let rest = &line[start + 1..];
+    Some(&rest[..rest.find(quote)?])
+}
+
+/// Replace `$name` and `${name}` with Gradle variables; `None` when one is
+/// not known.
+fn interpolate(value: &str, vars: &HashMap<String, String>) -> Option<String> {
+    let mut out = String::new();
+    let mut rest = value;
+    while let Some(i) = rest.find('$') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i + 1..];
+        let (name, after) = match rest.strip_prefix('{') {
+            Some(inner) => {
+                let end = inner.find('}')?;
+                (&inner[..end], &inner[end + 1..])
+            }
+            None => {
+                let end = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                (&rest[..end], &rest[end..])
+            }
+        };
+        let name = name.trim_start_matches("project.");
+        out.push_str(vars.get(name)?);
+        rest = after;
+    }
+    out.push_str(rest);
+    Some(out)
+}
+
+/// Split `implementation("g:a:v")`, `implementation 'g:a:v'` and the like
+/// into the configuration and the dependency notation.
+fn parse_dependency(line: &str) -> Option<(&str, &str)> {
+    let end = line.find(|c: char| !c.is_ascii_alphanumeric())?;
+    let (configuration, rest) = line.split_at(end);
+    let rest = rest.trim_start();
+    let notation = match rest.strip_prefix('(') {
+        Some(inner) => {
+            let mut depth = 1;
+            let end = inner.find(|c: char| {
+                match c {
+                    '(' => depth += 1,
+                    ')' => depth -= 1,
+                    _ => {}
+                }
+                depth == 0
+            })?;
+            &inner[..end]
+        }
+        None if rest.starts_with(['"', '\'']) || rest.starts_with("group") => {
+            rest.split('{').next()?.trim_end()
+        }
+        None => return None,
+    };
+    Some((configuration, notation.trim()))
+}
+
+/// The `group:artifact[:version]` a dependency notation names.
+fn dependency_coordinate(
+    notation: &str,
+    vars: &HashMap<String, String>,
+    catalog: &HashMap<String, String>,
+) -> Option<String> {
+    if let Some(value) = unquote(notation) {
+        return interpolate(value, vars);
+    }
+    if let Some(alias) = notation.strip_prefix("libs.") {
+        return catalog.get(alias).cloned();
+    }
+    let mut parts: HashMap<&str, String> = HashMap::new();
+    for entry in notation.split(',') {
+        let (key, value) = entry.split_once([':', '='])?;
+        let value = value.trim();
+        let value = match unquote(value) {
+            Some(value) => interpolate(value, vars)?,
+            // Groovy may name a variable directly: `version: junitVersion`
+            None => vars.get(value.trim_start_matches("project."))?.clone(),
+        };
+        parts.insert(key.trim(), value);
+    }
+    let group = parts.get("group")?;
+    let name = parts.get("name")?;
+    Some(match parts.get("version") {
+        Some(version) => format!("{}:{}:{}", group, name, version),
+        None => format!("{}:{}", group, name),
+    })
+}
+
+/// The libraries of a version catalog as `group:artifact[:version]`, keyed
+/// by their `libs.` accessor (`junit-jupiter` is `libs.junit.jupiter`).
+fn parse_catalog(catalog: &str) -> Result<HashMap<String, String>> {
+    let table: toml::Table = toml::from_str(catalog)?;
+    let empty = toml::Table::new();
+    let versions = table
+        .get("versions")
+        .and_then(|v| v.as_table())
+        .unwrap_or(&empty);
+    let libraries = table
+        .get("libraries")
+        .and_then(|v| v.as_table())
+        .unwrap_or(&empty);
+
+    let mut coordinates = HashMap::new();
+    for (alias, library) in libraries {
+        let coordinate = match library {
+            toml::Value::String(coordinate) => Some(coordinate.clone()),
+            toml::Value::Table(library) => {
+                let module = match library.get("module").and_then(|m| m.as_str()) {
+                    Some(module) => Some(module.to_string()),
+                    None => library
+                        .get("group")
+                        .and_then(|g| g.as_str())
+                        .zip(library.get("name").and_then(|n| n.as_str()))
+                        .map(|(group, name)| format!("{}:{}", group, name)),
+                };
+                module.map(|module| {
+                    match library
+                        .get("version")
+                        .and_then(|v| catalog_version(v, versions))
+                    {
+                        Some(version) => format!("{}:{}", module, version),
+                        None => module,
+                    }
+                })
+            }
+            _ => None,
+        };
+        if let Some(coordinate) = coordinate {
+            coordinates.insert(alias.replace(['-', '_'], "."), coordinate);
+        }
+    }
+    Ok(coordinates)
+}
+
+/// A catalog version: a string, a `{ ref = "..." }` into `[versions]`, or
+/// rich version constraints, of which the strictest is taken.
+fn catalog_version(value: &toml::Value, versions: &toml::Table) -> Option<String> {
+    match value {
+        toml::Value::String(version) => Some(version.clone()),
+        toml::Value::Table(table) => match table.get("ref").and_then(|r| r.as_str()) {
+            Some(name) => catalog_version(versions.get(name)?, &toml::Table::new()),
+            None => ["strictly", "require", "prefer"]
+                .iter()
+                .find_map(|key| table.get(*key)?.as_str().map(str::to_string)),
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pom() {
+        let imported = parse_pom(
+            r#"<project xmlns="http://maven.apache.org/POM/4.0.0">
+  <groupId>com.example</groupId>
+  <artifactId>demo</artifactId>
+  <version>1.2.0</version>
+  <properties>
+    <maven.compiler.source>1.8</maven.compiler.source>
+    <guava.version>33.0.0-jre</guava.version>
+  </properties>
+  <dependencyManagement>
+    <dependencies>
+      <dependency>
+        <groupId>org.slf4j</groupId>
+        <artifactId>slf4j-api</artifactId>
+        <version>2.0.9</version>
+      </dependency>
+    </dependencies>
+  </dependencyManagement>
+  <dependencies>
+    <dependency>
+      <groupId>com.google.guava</groupId>
+      <artifactId>guava</artifactId>
+      <version>${guava.version}</version>
+    </dependency>
+    <dependency>
+      <groupId>org.slf4j</groupId>
+      <artifactId>slf4j-api</artifactId>
+    </dependency>
+    <dependency>
+      <groupId>org.postgresql</groupId>
+      <artifactId>postgresql</artifactId>
+      <version>42.7.1</version>
+      <scope>runtime</scope>
+    </dependency>
+    <dependency>
+      <groupId>org.junit.jupiter</groupId>
+      <artifactId>junit-jupiter</artifactId>
+      <version>5.10.0</version>
+      <scope>test</scope>
+    </dependency>
+    <dependency>
+      <groupId>com.example</groupId>
+      <artifactId>managed-elsewhere</artifactId>
+    </dependency>
+  </dependencies>
+  <build>
+    <plugins>
+      <plugin>
+        <artifactId>maven-jar-plugin</artifactId>
+        <configuration>
+          <archive><manifest><mainClass>com.example.demo.App</mainClass></manifest></archive>
+        </configuration>
+      </plugin>
+    </plugins>
+  </build>
+</project>"#,
+        )
+        .unwrap();
+        assert_eq!(imported.name.as_deref(), Some("demo"));
+        assert_eq!(imported.group.as_deref(), Some("com.example"));
+        assert_eq!(imported.version.as_deref(), Some("1.2.0"));
+        assert_eq!(imported.java.as_deref(), Some("8"));
+        assert_eq!(imported.main_class.as_deref(), Some("com.example.demo.App"));
+        let coords: Vec<(String, ImportedScope)> = imported
+            .dependencies
+            .iter()
+            .map(|d| (format!("{}:{}:{}", d.group, d.artifact, d.version), d.scope))
+            .collect();
+        let compile = ImportedScope::Compile { expose: false };
+        assert_eq!(
+            coords,
+            vec![
+                ("com.google.guava:guava:33.0.0-jre".to_string(), compile),
+                ("org.slf4j:slf4j-api:2.0.9".to_string(), compile),
+                (
+                    "org.postgresql:postgresql:42.7.1".to_string(),
+                    ImportedScope::Runtime
+                ),
+                (
+                    "org.junit.jupiter:junit-jupiter:5.10.0".to_string(),
+                    ImportedScope::Test
+                ),
+            ]
+        );
+        assert_eq!(imported.notes.len(), 1);
+        assert!(imported.notes[0].contains("`com.example:managed-elsewhere` has no version"));
+    }
+
+    #[test]
+    fn test_parse_pom_rejects_modules() {
+        let err =
+            parse_pom("<project><modules><module>core</module></modules></project>").unwrap_err();
+        assert!(err.to_string().contains("multi-module"));
+    }
+
+    #[test]
+    fn test_parse_gradle_kotlin_dsl() {
+        let script = r#"
+plugins {
+    application
+}
+
+group = "com.example"
+version = "0.3.0"
+val junitVersion = "5.10.0"
+
+java {
+    toolchain {
+        languageVersion = JavaLanguageVersion.of(17) // LTS
+    }
+}
+
+dependencies {
+    implementation(libs.guava)
+    api("org.slf4j:slf4j-api:2.0.9")
+    runtimeOnly("org.postgresql:postgresql:42.7.1")
+    compileOnly("org.projectlombok:lombok:1.18.30")
+    testImplementation("org.junit.jupiter:junit-jupiter:$junitVersion")
+    testImplementation(platform("org.junit:junit-bom:5.10.0"))
+    implementation(project(":core"))
+}
+
+application {
+    mainClass.set("com.example.demo.App")
+}
+"#;
+        let catalog = r#"
+[versions]
+guava = "33.0.0-jre"
+
+[libraries]
+guava = { module = "com.google.guava:guava", version.ref = "guava" }
+"#;
+        let imported = parse_gradle(
+            "build.gradle.kts",
+            script,
+            "rootProject.name = \"demo\"\n",
+            "",
+            catalog,
+        )
+        .unwrap();
+        assert_eq!(imported.name.as_deref(), Some("demo"));
+        assert_eq!(imported.group.as_deref(), Some("com.example"));
+        assert_eq!(imported.version.as_deref(), Some("0.3.0"));
+        assert_eq!(imported.java.as_deref(), Some("17"));
+        assert_eq!(imported.main_class.as_deref(), Some("com.example.demo.App"));
+        let coords: Vec<String> = imported
+            .dependencies
+            .iter()
+            .map(|d| format!("{}:{}:{}", d.group, d.artifact, d.version))
+            .collect();
+        assert_eq!(
+            coords,
+            [
+                "com.google.guava:guava:33.0.0-jre",
+                "org.slf4j:slf4j-api:2.0.9",
+                "org.postgresql:postgresql:42.7.1",
+                "org.projectlombok:lombok:1.18.30",
+                "org.junit.jupiter:junit-jupiter:5.10.0",
+            ]
+        );
+        assert_eq!(
+            imported.dependencies[1].scope,
+            ImportedScope::Compile { expose: true }
+        );
+        assert_eq!(imported.notes.len(), 3, "{:?}", imported.notes);
+        assert!(imported.notes[1].contains("platform("));
+        assert!(imported.notes[2].contains("project(\":core\")"));
+    }
+
+    #[test]
+    fn test_parse_gradle_groovy_dsl() {
+        let script = r#"
+apply plugin: 'java'
+sourceCompatibility = JavaVersion.VERSION_1_8
+/* dependencies { implementation 'com.example:commented-out:1.0' } */
+dependencies {
+    implementation group: 'commons-io', name: 'commons-io', version: commonsIoVersion
+    testImplementation 'org.testng:testng:7.5.1'
+}
+jar {
+    manifest {
+        attributes 'Main-Class': 'com.example.Tool'
+    }
+}
+"#;
+        let imported =
+            parse_gradle("build.gradle", script, "", "commonsIoVersion=2.15.1\n", "").unwrap();
+        assert_eq!(imported.java.as_deref(), Some("8"));
+        assert_eq!(imported.main_class.as_deref(), Some("com.example.Tool"));
+        assert_eq!(imported.dependencies.len(), 1 + 1, "{:?}", imported.notes);
+        assert_eq!(imported.dependencies[0].version, "2.15.1");
+        assert_eq!(imported.dependencies[1].scope, ImportedScope::Test);
+        assert!(imported.notes.is_empty(), "{:?}", imported.notes);
+
+        let err = parse_gradle("build.gradle", "", "include 'core', 'app'\n", "", "").unwrap_err();
+        assert!(err.to_string().contains("multi-project"));
+    }
+
+    #[test]
+    fn test_manifest() {
+        let imported = Imported {
+            build_file: "pom.xml",
+            name: Some("demo".to_string()),
+            group: Some("com.example".to_string()),
+            version: Some("1.2.0".to_string()),
+            java: Some("17".to_string()),
+            main_class: None,
+            dependencies: vec![
+                ImportedDep {
+                    group: "org.slf4j".to_string(),
+                    artifact: "slf4j-api".to_string(),
+                    version: "2.0.9".to_string(),
+                    scope: ImportedScope::Compile { expose: true },
+                },
+                ImportedDep {
+                    group: "org.testng".to_string(),
+                    artifact: "testng".to_string(),
+                    version: "7.10.2".to_string(),
+                    scope: ImportedScope::Test,
+                },
+                ImportedDep {
+                    group: "org.postgresql".to_string(),
+                    artifact: "postgresql".to_string(),
+                    version: "42.7.1".to_string(),
+                    scope: ImportedScope::Runtime,
+                },
+            ],
+            notes: Vec::new(),
+        };
+        let lib = imported.manifest("demo", "com.example.demo", None).unwrap();
+        let toml: JargoToml = toml::from_str(&lib).unwrap();
+        assert!(!toml.is_app());
+        assert_eq!(toml.package.group.as_deref(), Some("com.example"));
+        assert_eq!(toml.package.version, "1.2.0");
+        assert_eq!(toml.package.java, "17");
+        assert!(lib.contains(
+            "[dependencies]\n\
+             \"org.postgresql:postgresql\" = { version = \"42.7.1\", scope = \"runtime\" }\n\
+             \"org.slf4j:slf4j-api\" = { version = \"2.0.9\", expose = true }\n"
+        ));
+        assert!(lib.contains("[test]\nframework = \"testng\"\n"));
+        assert!(lib.contains("[dev-dependencies]\n\"org.testng:testng\" = \"7.10.2\"\n"));
+
+        let app = imported
+            .manifest("demo", "com.example.demo", Some("cli.Tool"))
+            .unwrap();
+        let toml: JargoToml = toml::from_str(&app).unwrap();
+        assert!(toml.is_app());
+        assert_eq!(toml.package.main_class.as_deref(), Some("cli.Tool"));
+        assert!(app.contains("\"org.slf4j:slf4j-api\" = \"2.0.9\"\n"));
+    }
+
+    #[test]
+    fn test_java_release() {
+        assert_eq!(java_release("17").as_deref(), Some("17"));
+        assert_eq!(java_release("'1.8'").as_deref(), Some("8"));
+        assert_eq!(
+            java_release("JavaVersion.VERSION_11").as_deref(),
+            Some("11")
+        );
+        assert_eq!(
+            java_release("options.release.set(21)").as_deref(),
+            Some("21")
+        );
+        assert_eq!(java_release("${java.version}"), None);
+    }
+}
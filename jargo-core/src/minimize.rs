@@ -0,0 +1,289 @@
+use anyhow::{Context, Result};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::bytecode;
+use crate::jar::{files_under, SERVICES_DIR};
+
+/// Dependency classes an uber JAR built with `[shade] minimize` keeps, as
+/// internal names (`com/google/common/base/Strings`).
+///
+/// Every project class is a root, since the main class and anything loaded
+/// reflectively live there, along with providers listed in
+/// `META-INF/services/` files and dependency classes matching `[shade] keep`.
+/// Class references are followed through constant pools: class constants,
+/// descriptors and signatures, and string constants that name a class.
+pub fn reachable_classes(
+    classes_dir: &Path,
+    runtime_jars: &[PathBuf],
+    keep: &[String],
+) -> Result<HashSet<String>> {
+    let mut roots = Vec::new();
+    if classes_dir.exists() {
+        for path in files_under(classes_dir)? {
+            let name = path
+                .strip_prefix(classes_dir)
+                .with_context(|| "failed to compute relative path")?
+                .to_string_lossy()
+                .replace('\\', "/");
+            if class_name(&name).is_none() && !name.starts_with(SERVICES_DIR) {
+                continue;
+            }
+            let bytes =
+                fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+            roots.extend(references(&name, &bytes).with_context(|| {
+                format!("failed to read class references in {}", path.display())
+            })?);
+        }
+    }
+
+    // Dependency class to the classes it references
+    let mut graph: HashMap<String, Vec<String>> = HashMap::new();
+    for jar in runtime_jars {
+        let file = File::open(jar).with_context(|| format!("failed to open {}", jar.display()))?;
+        let mut archive = zip::ZipArchive::new(file)
+            .with_context(|| format!("failed to read {}", jar.display()))?;
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i)?;
+            let name = entry.name().to_string();
+            let class = class_name(&name);
+            if entry.is_dir() || (class.is_none() && !name.starts_with(SERVICES_DIR)) {
+                continue;
+            }
+            let mut bytes = Vec::with_capacity(entry.size() as usize);
+            entry
+                .read_to_end(&mut bytes)
+                .with_context(|| format!("failed to read {} in {}", name, jar.display()))?;
+            let refs = references(&name, &bytes)
+                .with_context(|| format!("failed to read {} in {}", name, jar.display()))?;
+            match class {
+                Some(class) => {
+                    if is_kept(class, keep) {
+                        roots.push(class.to_string());
+                    }
+                    // Multi-release variants share the base class's references
+                    graph.entry(class.to_string()).or_default().extend(refs);
+                }
+                None => roots.extend(refs),
+            }
+        }
+    }
+
+    let mut reachable = HashSet::new();
+    while let Some(class) = roots.pop() {
+        if let Some(refs) = graph.get(&class) {
+            if reachable.insert(class) {
+                roots.extend(refs.iter().cloned());
+            }
+        }
+    }
+    Ok(reachable)
+}
+
+/// Internal class name of a JAR entry (`com/acme/Foo.class` → `com/acme/Foo`),
+/// looking through `META-INF/versions/{N}/`. `None` for other entries and
+/// `module-info.class`.
+pub(crate) fn class_name(entry: &str) -> Option<&str> {
+    let entry = match entry.strip_prefix("META-INF/versions/") {
+        Some(versioned) => &versioned[versioned.find('/')? + 1..],
+        None => entry,
+    };
+    entry
+        .strip_suffix(".class")
+        .filter(|name| !name.is_empty() && !name.ends_with("module-info"))
+}
+
+/// Classes a class file or `META-INF/services/` file refers to.
+fn references(name: &str, bytes: &[u8]) -> Result<Vec<String>> {
+    if name.starts_with(SERVICES_DIR) {
+        return Ok(String::from_utf8_lossy(bytes)
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or("").trim())
+            .filter(|provider| !provider.is_empty())
+            .map(|provider| provider.replace('.', "/"))
+            .collect());
+    }
+    class_references(bytes)
+}
+
+/// Internal names referenced from a class file's constant pool. Extra names
+/// that are not classes are harmless: they match nothing.
+fn class_references(class: &[u8]) -> Result<Vec<String>> {
+    if bytecode::class_major_version(class).is_none() {
+        return Ok(Vec::new());
+    }
+    let (constants, _) = bytecode::constant_pool(class)?;
+    let utf8: HashMap<u16, &[u8]> = constants
+        .iter()
+        .filter(|&&(_, tag, _, _)| tag == 1)
+        .map(|&(index, _, start, end)| (index, &class[start + 3..end]))
+        .collect();
+
+    let mut refs = Vec::new();
+    for &(_, tag, start, end) in &constants {
+        let value = match tag {
+            1 => {
+                descriptor_classes(&class[start + 3..end], &mut refs);
+                continue;
+            }
+            7 | 8 => bytecode::u16_at(class, start + 1)?,
+            _ => continue,
+        };
+        let Some(value) = utf8.get(&value) else {
+            continue;
+        };
+        let value = String::from_utf8_lossy(value);
+        if tag == 7 && !value.starts_with('[') {
+            refs.push(value.into_owned());
+        } else if tag == 8 && !value.contains(['/', ' ']) {
+            // `Class.forName("com.acme.Plugin")`
+            refs.push(value.replace('.', "/"));
+        }
+    }
+    Ok(refs)
+}
+
+/// Class names in descriptor types (`Lcom/acme/Foo;`) anywhere in a constant,
+/// including array class constants and generic signatures.
+fn descriptor_classes(value: &[u8], refs: &mut Vec<String>) {
+    for (i, &byte) in value.iter().enumerate() {
+        let opens_type = byte == b'L' && (i == 0 || b"[(;<):^+-".contains(&value[i - 1]));
+        if !opens_type {
+            continue;
+        }
+        let rest = &value[i + 1..];
+        if let Some(end) = rest.iter().position(|&b| matches!(b, b';' | b'<')) {
+            if end > 0 {
+                refs.push(String::from_utf8_lossy(&rest[..end]).into_owned());
+            }
+        }
+    }
+}
+
+/// Whether `[shade] keep` names the class (`com.acme.Plugin`, which keeps its
+/// nested classes too) or a package it is under (`com.acme.plugins.*`).
+fn is_kept(class: &str, keep: &[String]) -> bool {
+    let dotted = class.replace('/', ".");
+    keep.iter().any(|pattern| match pattern.strip_suffix(".*") {
+        Some(package) => dotted
+            .strip_prefix(package)
+            .is_some_and(|rest| rest.starts_with('.')),
+        None => dotted
+            .strip_prefix(pattern.as_str())
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('$')),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn utf8(out: &mut Vec<u8>, s: &str) {
+        out.push(1);
+        out.extend_from_slice(&(s.len() as u16).to_be_bytes());
+        out.extend_from_slice(s.as_bytes());
+    }
+
+    /// A class file whose constant pool holds a class constant per `classes`
+    /// entry and a string constant per `strings` entry.
+    fn class_file(classes: &[&str], strings: &[&str]) -> Vec<u8> {
+        let mut class = vec![0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 61];
+        let count = 1 + 2 * (classes.len() + strings.len());
+        class.extend_from_slice(&(count as u16).to_be_bytes());
+        let mut index = 1u16;
+        for (tag, value) in classes
+            .iter()
+            .map(|c| (7, c))
+            .chain(strings.iter().map(|s| (8, s)))
+        {
+            utf8(&mut class, value);
+            class.push(tag);
+            class.extend_from_slice(&index.to_be_bytes());
+            index += 2;
+        }
+        class.extend_from_slice(&[0, 0x21]);
+        class
+    }
+
+    #[test]
+    fn test_class_references() {
+        let class = class_file(
+            &["com/acme/Foo", "[Lcom/acme/Bar;"],
+            &["com.acme.Plugin", "not a class"],
+        );
+        let mut refs = class_references(&class).unwrap();
+        refs.sort();
+        refs.dedup();
+        assert_eq!(refs, ["com/acme/Bar", "com/acme/Foo", "com/acme/Plugin"]);
+
+        let mut refs = Vec::new();
+        descriptor_classes(
+            b"(Ljava/util/List<Lcom/acme/Item;>;I)Lcom/acme/Result;",
+            &mut refs,
+        );
+        assert_eq!(refs, ["java/util/List", "com/acme/Item", "com/acme/Result"]);
+    }
+
+    #[test]
+    fn test_class_name_and_keep() {
+        assert_eq!(class_name("com/acme/Foo.class"), Some("com/acme/Foo"));
+        assert_eq!(
+            class_name("META-INF/versions/11/com/acme/Foo.class"),
+            Some("com/acme/Foo")
+        );
+        assert_eq!(class_name("module-info.class"), None);
+        assert_eq!(class_name("com/acme/foo.properties"), None);
+
+        let keep = ["com.acme.Plugin".to_string(), "org.ext.*".to_string()];
+        assert!(is_kept("com/acme/Plugin", &keep));
+        assert!(is_kept("com/acme/Plugin$Inner", &keep));
+        assert!(!is_kept("com/acme/PluginFactory", &keep));
+        assert!(is_kept("org/ext/deep/Thing", &keep));
+        assert!(!is_kept("org/extra/Thing", &keep));
+    }
+
+    #[test]
+    fn test_reachable_classes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let classes_dir = tmp.path().join("classes");
+        fs::create_dir_all(classes_dir.join("app")).unwrap();
+        fs::write(
+            classes_dir.join("app/Main.class"),
+            class_file(&["app/Main", "lib/Used"], &[]),
+        )
+        .unwrap();
+
+        let jar = tmp.path().join("lib.jar");
+        let mut zip = zip::ZipWriter::new(File::create(&jar).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        for (name, contents) in [
+            (
+                "lib/Used.class",
+                class_file(&["lib/Used", "lib/Helper"], &[]),
+            ),
+            ("lib/Helper.class", class_file(&["lib/Helper"], &[])),
+            ("lib/Unused.class", class_file(&["lib/Unused"], &[])),
+            ("lib/Provider.class", class_file(&["lib/Provider"], &[])),
+            ("lib/Kept.class", class_file(&["lib/Kept"], &[])),
+            (
+                "META-INF/services/lib.Spi",
+                b"# providers\nlib.Provider\n".to_vec(),
+            ),
+        ] {
+            zip.start_file(name, options).unwrap();
+            zip.write_all(&contents).unwrap();
+        }
+        zip.finish().unwrap();
+
+        let reachable = reachable_classes(&classes_dir, &[jar], &["lib.Kept".to_string()]).unwrap();
+        let mut reachable: Vec<&str> = reachable.iter().map(String::as_str).collect();
+        reachable.sort();
+        assert_eq!(
+            reachable,
+            ["lib/Helper", "lib/Kept", "lib/Provider", "lib/Used"]
+        );
+    }
+}
@@ -0,0 +1,296 @@
+use anyhow::{bail, Context, Result};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::cache;
+use crate::context::GlobalContext;
+use crate::errors::JargoError;
+use crate::manifest::TestFramework;
+use crate::toolchain::Toolchain;
+
+/// PIT version used for mutation testing.
+pub const PIT_VERSION: &str = "1.15.8";
+
+/// Directory the PIT reports are written to.
+pub const MUTATION_DIR: &str = "target/mutation";
+
+/// PIT itself, as `(group, artifact, version)`.
+const PIT_JARS: [(&str, &str, &str); 3] = [
+    ("org.pitest", "pitest", PIT_VERSION),
+    ("org.pitest", "pitest-entry", PIT_VERSION),
+    ("org.pitest", "pitest-command-line", PIT_VERSION),
+];
+
+/// Fetch PIT and the plugin for `framework` into the local cache. Like the
+/// JUnit console launcher, these are pinned here rather than resolved.
+pub fn fetch_pit(gctx: &GlobalContext, framework: TestFramework) -> Result<Vec<PathBuf>> {
+    let plugin = match framework {
+        TestFramework::JUnit => ("org.pitest", "pitest-junit5-plugin", "1.2.1"),
+        TestFramework::TestNg => ("org.pitest", "pitest-testng-plugin", "1.0.0"),
+    };
+    let mut jars = Vec::new();
+    for (group, artifact, version) in PIT_JARS.into_iter().chain([plugin]) {
+        let (path, _sha256) =
+            cache::fetch_jar(gctx, group, artifact, version).context("failed to fetch PIT")?;
+        jars.push(path);
+    }
+    Ok(jars)
+}
+
+/// Inputs of a PIT run.
+#[derive(Debug, Default)]
+pub struct MutationOptions {
+    /// PIT, the compiled tests and main classes, and their dependencies.
+    pub classpath: Vec<PathBuf>,
+    /// Package whose classes are mutated and whose tests are run, e.g. `myapp`.
+    pub base_package: String,
+    /// `[test.mutation] mutators`; empty uses PIT's defaults.
+    pub mutators: Vec<String>,
+    /// JVM arguments for the JVMs PIT runs the tests in.
+    pub jvm_args: Vec<String>,
+    /// Environment variables for those JVMs.
+    pub env: Vec<(String, String)>,
+}
+
+/// One mutation from PIT's `mutations.xml`.
+#[derive(Debug, Default, PartialEq)]
+pub struct Mutation {
+    pub detected: bool,
+    /// `KILLED`, `SURVIVED`, `NO_COVERAGE`, `TIMED_OUT`, ...
+    pub status: String,
+    pub source_file: String,
+    pub class: String,
+    pub method: String,
+    pub line: u32,
+    /// What was changed, e.g. `Replaced integer addition with subtraction`.
+    pub description: String,
+}
+
+/// Results of a `jargo test --mutation` run.
+#[derive(Debug)]
+pub struct MutationReport {
+    pub mutations: Vec<Mutation>,
+    /// Entry page of the HTML report.
+    pub html: PathBuf,
+}
+
+impl MutationReport {
+    pub fn detected(&self) -> usize {
+        self.mutations.iter().filter(|m| m.detected).count()
+    }
+
+    /// Percentage of mutations the tests detected, or 100 when PIT generated
+    /// none.
+    pub fn score(&self) -> f64 {
+        if self.mutations.is_empty() {
+            100.0
+        } else {
+            self.detected() as f64 * 100.0 / self.mutations.len() as f64
+        }
+    }
+
+    /// Mutations no test detected, by class and line.
+    pub fn survivors(&self) -> Vec<&Mutation> {
+        let mut survivors: Vec<&Mutation> = self.mutations.iter().filter(|m| !m.detected).collect();
+        survivors.sort_by(|a, b| (&a.class, a.line).cmp(&(&b.class, b.line)));
+        survivors
+    }
+}
+
+/// Run PIT over the main classes of `base_package` with the compiled tests,
+/// writing XML and HTML reports to `target/mutation/`.
+pub fn run(
+    gctx: &GlobalContext,
+    project_root: &Path,
+    toolchain: &Toolchain,
+    opts: &MutationOptions,
+) -> Result<MutationReport> {
+    let dir = project_root.join(MUTATION_DIR);
+    if dir.exists() {
+        fs::remove_dir_all(&dir).with_context(|| format!("failed to remove {}", dir.display()))?;
+    }
+    let classpath = std::env::join_paths(&opts.classpath).context("invalid classpath entry")?;
+    let targets = format!("{}.*", opts.base_package);
+
+    let mut cmd = Command::new(toolchain.java());
+    cmd.arg("-cp")
+        .arg(&classpath)
+        .arg("org.pitest.mutationtest.commandline.MutationCoverageReport")
+        .arg("--reportDir")
+        .arg(&dir)
+        .arg("--targetClasses")
+        .arg(&targets)
+        .arg("--targetTests")
+        .arg(&targets)
+        .arg("--sourceDirs")
+        .arg(project_root.join("target/src-root"))
+        .arg("--outputFormats")
+        .arg("XML,HTML")
+        .arg("--timestampedReports=false")
+        .arg("--failWhenNoMutations=false");
+    if !opts.mutators.is_empty() {
+        cmd.arg("--mutators").arg(opts.mutators.join(","));
+    }
+    // PIT splits `--jvmArgs` on commas, so pass each argument separately
+    for arg in &opts.jvm_args {
+        cmd.arg(format!("--jvmArgs={}", arg));
+    }
+    cmd.envs(opts.env.iter().map(|(k, v)| (k, v)))
+        .current_dir(project_root);
+    gctx.shell
+        .verbose(|sh| sh.print(format!("  [verbose] running {:?}", cmd)));
+
+    let output = cmd.output().map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            JargoError::JavaNotFound.into()
+        } else {
+            anyhow::Error::from(e).context("failed to run PIT")
+        }
+    })?;
+    if !output.status.success() {
+        bail!(
+            "PIT mutation analysis failed:\n{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        );
+    }
+    gctx.shell.verbose(|sh| {
+        sh.print(String::from_utf8_lossy(&output.stdout).trim_end());
+        sh.print(String::from_utf8_lossy(&output.stderr).trim_end());
+    });
+
+    let xml = dir.join("mutations.xml");
+    let mutations = if xml.exists() {
+        let text = fs::read_to_string(&xml)
+            .with_context(|| format!("failed to read {}", xml.display()))?;
+        parse_mutations(&text).with_context(|| format!("failed to parse {}", xml.display()))?
+    } else {
+        Vec::new()
+    };
+    Ok(MutationReport {
+        mutations,
+        html: dir.join("index.html"),
+    })
+}
+
+/// Parse PIT's `mutations.xml`.
+fn parse_mutations(xml: &str) -> Result<Vec<Mutation>> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut mutations = Vec::new();
+    let mut current: Option<Mutation> = None;
+    // Child element of <mutation> whose text is being read
+    let mut field = Vec::new();
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) if e.local_name().as_ref() == b"mutation" => {
+                current = Some(Mutation {
+                    detected: attribute(&e, "detected")? == "true",
+                    status: attribute(&e, "status")?,
+                    ..Mutation::default()
+                });
+            }
+            Ok(Event::Start(e)) => field = e.local_name().as_ref().to_vec(),
+            Ok(Event::Text(text)) => {
+                let Some(mutation) = current.as_mut() else {
+                    continue;
+                };
+                let text = text
+                    .unescape()
+                    .context("malformed text in mutation report")?;
+                match field.as_slice() {
+                    b"sourceFile" => mutation.source_file = text.into_owned(),
+                    b"mutatedClass" => mutation.class = text.into_owned(),
+                    b"mutatedMethod" => mutation.method = text.into_owned(),
+                    b"lineNumber" => {
+                        mutation.line = text
+                            .parse()
+                            .with_context(|| format!("invalid line number `{}`", text))?
+                    }
+                    b"description" => mutation.description = text.into_owned(),
+                    _ => {}
+                }
+            }
+            Ok(Event::End(e)) => {
+                if e.local_name().as_ref() == b"mutation" {
+                    mutations.extend(current.take());
+                }
+                field.clear();
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => bail!("XML parse error: {}", e),
+            _ => {}
+        }
+    }
+
+    Ok(mutations)
+}
+
+fn attribute(e: &BytesStart, key: &str) -> Result<String> {
+    for attr in e.attributes() {
+        let attr = attr.context("malformed attribute in mutation report")?;
+        if attr.key.local_name().as_ref() == key.as_bytes() {
+            let value = attr
+                .unescape_value()
+                .context("non-UTF8 attribute in mutation report")?;
+            return Ok(value.into_owned());
+        }
+    }
+    Ok(String::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPORT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<mutations partial="false">
+<mutation detected='true' status='KILLED' numberOfTestsRun='1'><sourceFile>Calc.java</sourceFile><mutatedClass>myapp.Calc</mutatedClass><mutatedMethod>add</mutatedMethod><methodDescription>(II)I</methodDescription><lineNumber>5</lineNumber><mutator>org.pitest.mutationtest.engine.gregor.mutators.MathMutator</mutator><indexes><index>2</index></indexes><blocks><block>0</block></blocks><killingTest>myapp.CalcTest.[engine:junit-jupiter]/[class:myapp.CalcTest]/[method:adds()]</killingTest><description>Replaced integer addition with subtraction</description></mutation>
+<mutation detected='false' status='SURVIVED' numberOfTestsRun='2'><sourceFile>Calc.java</sourceFile><mutatedClass>myapp.Calc</mutatedClass><mutatedMethod>abs</mutatedMethod><methodDescription>(I)I</methodDescription><lineNumber>9</lineNumber><mutator>org.pitest.mutationtest.engine.gregor.mutators.ConditionalsBoundaryMutator</mutator><indexes><index>1</index></indexes><blocks><block>0</block></blocks><killingTest/><description>changed conditional boundary</description></mutation>
+<mutation detected='false' status='NO_COVERAGE' numberOfTestsRun='0'><sourceFile>Calc.java</sourceFile><mutatedClass>myapp.Calc</mutatedClass><mutatedMethod>neg</mutatedMethod><methodDescription>(I)I</methodDescription><lineNumber>3</lineNumber><mutator>org.pitest.mutationtest.engine.gregor.mutators.returns.PrimitiveReturnsMutator</mutator><indexes><index>3</index></indexes><blocks><block>0</block></blocks><killingTest/><description>replaced int return with 0 for myapp/Calc::neg</description></mutation>
+<mutation detected='true' status='TIMED_OUT' numberOfTestsRun='1'><sourceFile>Calc.java</sourceFile><mutatedClass>myapp.Calc</mutatedClass><mutatedMethod>loop</mutatedMethod><methodDescription>()V</methodDescription><lineNumber>12</lineNumber><mutator>org.pitest.mutationtest.engine.gregor.mutators.IncrementsMutator</mutator><indexes><index>4</index></indexes><blocks><block>1</block></blocks><killingTest/><description>Changed increment from 1 to -1</description></mutation>
+</mutations>
+"#;
+
+    #[test]
+    fn test_parse_mutations() {
+        let mutations = parse_mutations(REPORT).unwrap();
+        assert_eq!(mutations.len(), 4);
+        assert_eq!(
+            mutations[1],
+            Mutation {
+                detected: false,
+                status: "SURVIVED".to_string(),
+                source_file: "Calc.java".to_string(),
+                class: "myapp.Calc".to_string(),
+                method: "abs".to_string(),
+                line: 9,
+                description: "changed conditional boundary".to_string(),
+            }
+        );
+        assert!(mutations[3].detected);
+    }
+
+    #[test]
+    fn test_score_and_survivors() {
+        let report = MutationReport {
+            mutations: parse_mutations(REPORT).unwrap(),
+            html: PathBuf::new(),
+        };
+        assert_eq!(report.detected(), 2);
+        assert_eq!(report.score(), 50.0);
+        let lines: Vec<u32> = report.survivors().iter().map(|m| m.line).collect();
+        assert_eq!(lines, [3, 9]);
+
+        let empty = MutationReport {
+            mutations: Vec::new(),
+            html: PathBuf::new(),
+        };
+        assert_eq!(empty.score(), 100.0);
+    }
+}
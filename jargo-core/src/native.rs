@@ -0,0 +1,217 @@
+use anyhow::{bail, Context, Result};
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::errors::JargoError;
+use crate::manifest::{BinTarget, JargoToml};
+use crate::toolchain::Toolchain;
+
+/// Compile the app ahead of time with GraalVM `native-image` into
+/// `target/native/{name}`, with `bin` as the entry point. Build output
+/// streams to the terminal.
+///
+/// Returns the path of the binary.
+pub fn build_native_image(
+    toolchain: &Toolchain,
+    project_root: &Path,
+    manifest: &JargoToml,
+    bin: &BinTarget,
+    jar: &Path,
+    runtime_jars: &[PathBuf],
+) -> Result<PathBuf> {
+    let out_dir = project_root.join("target/native");
+    fs::create_dir_all(&out_dir)
+        .with_context(|| format!("failed to create {}", out_dir.display()))?;
+    let binary = out_dir.join(binary_name(manifest, bin)?);
+
+    let args = native_image_args(project_root, manifest, bin, jar, runtime_jars, &binary)?;
+    let status = Command::new(native_image(toolchain))
+        .args(&args)
+        .current_dir(project_root)
+        .status()
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                anyhow::anyhow!(
+                    "native-image not found; select a GraalVM JDK with [toolchain], \
+                     .jargo-java-version, or JAVA_HOME"
+                )
+            } else {
+                anyhow::Error::from(e).context("failed to run native-image")
+            }
+        })?;
+    if !status.success() {
+        return Err(JargoError::NativeImageFailed.into());
+    }
+
+    // native-image appends `.exe` on Windows
+    Ok(if cfg!(windows) {
+        binary.with_extension("exe")
+    } else {
+        binary
+    })
+}
+
+/// `native-image` from the toolchain; GraalVM ships it as a `.cmd` on Windows.
+fn native_image(toolchain: &Toolchain) -> PathBuf {
+    let tool = toolchain.tool("native-image");
+    if cfg!(windows) && tool.is_absolute() && !tool.exists() {
+        tool.with_extension("cmd")
+    } else {
+        tool
+    }
+}
+
+/// `[native] name`, defaulting to the bin name (the package name without
+/// `[[bin]]` targets).
+fn binary_name(manifest: &JargoToml, bin: &BinTarget) -> Result<String> {
+    let name = manifest
+        .native
+        .as_ref()
+        .and_then(|c| c.name.clone())
+        .unwrap_or_else(|| bin.name.clone());
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        bail!(
+            "invalid [native] name `{}`: expected letters, digits, `-`, `_`, or `.`",
+            name
+        );
+    }
+    Ok(name)
+}
+
+/// Arguments for `native-image`: `--no-fallback`, metadata directories, the
+/// runtime classpath, `[native] args`, the output path, and the main class.
+fn native_image_args(
+    project_root: &Path,
+    manifest: &JargoToml,
+    bin: &BinTarget,
+    jar: &Path,
+    runtime_jars: &[PathBuf],
+    binary: &Path,
+) -> Result<Vec<OsString>> {
+    let config = manifest.native.as_ref();
+    let mut args: Vec<OsString> = vec!["--no-fallback".into()];
+
+    let config_dirs = config.map(|c| c.config_dirs.as_slice()).unwrap_or(&[]);
+    if !config_dirs.is_empty() {
+        let mut dirs = Vec::new();
+        for dir in config_dirs {
+            let path = project_root.join(dir);
+            if !path.is_dir() {
+                bail!("[native] config-dirs entry `{}` is not a directory", dir);
+            }
+            dirs.push(path.display().to_string());
+        }
+        args.push(format!("-H:ConfigurationFileDirectories={}", dirs.join(",")).into());
+    }
+
+    args.push("-cp".into());
+    args.push(
+        std::env::join_paths(std::iter::once(jar).chain(runtime_jars.iter().map(PathBuf::as_path)))
+            .context("invalid classpath entry")?,
+    );
+    if let Some(config) = config {
+        args.extend(config.args.iter().map(OsString::from));
+    }
+    args.push("-o".into());
+    args.push(binary.into());
+    args.push(manifest.main_class_of(bin).into());
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(extra: &str) -> JargoToml {
+        toml::from_str(&format!(
+            r#"
+[package]
+name = "demo"
+version = "1.0.0"
+java = "21"
+{}
+"#,
+            extra
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_binary_name() {
+        let name = |extra: &str| {
+            let m = manifest(extra);
+            binary_name(&m, &m.get_bin(None).unwrap())
+        };
+        assert_eq!(name("").unwrap(), "demo");
+        assert_eq!(name("[native]\nname = \"demo-cli\"").unwrap(), "demo-cli");
+        assert!(name("[native]\nname = \"a/b\"").is_err());
+        assert_eq!(
+            name("[[bin]]\nname = \"server\"\nmain-class = \"Server\"").unwrap(),
+            "server"
+        );
+    }
+
+    #[test]
+    fn test_native_image_args() {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::create_dir_all(tmp.path().join("native-config")).unwrap();
+        let m = manifest(
+            r#"
+[native]
+config-dirs = ["native-config"]
+args = ["-O2"]
+"#,
+        );
+        let args = native_image_args(
+            tmp.path(),
+            &m,
+            &m.get_bin(None).unwrap(),
+            Path::new("target/demo.jar"),
+            &[PathBuf::from("dep.jar")],
+            Path::new("target/native/demo"),
+        )
+        .unwrap();
+        let args: Vec<String> = args
+            .iter()
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect();
+        let sep = if cfg!(windows) { ";" } else { ":" };
+        assert_eq!(
+            args,
+            [
+                "--no-fallback".to_string(),
+                format!(
+                    "-H:ConfigurationFileDirectories={}",
+                    tmp.path().join("native-config").display()
+                ),
+                "-cp".to_string(),
+                format!("target/demo.jar{}dep.jar", sep),
+                "-O2".to_string(),
+                "-o".to_string(),
+                "target/native/demo".to_string(),
+                "demo.Main".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_config_dir() {
+        let tmp = tempfile::tempdir().unwrap();
+        let m = manifest("[native]\nconfig-dirs = [\"missing\"]");
+        assert!(native_image_args(
+            tmp.path(),
+            &m,
+            &m.get_bin(None).unwrap(),
+            Path::new("demo.jar"),
+            &[],
+            Path::new("demo")
+        )
+        .is_err());
+    }
+}
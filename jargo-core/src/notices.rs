@@ -0,0 +1,265 @@
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+
+use crate::cache;
+use crate::context::GlobalContext;
+use crate::lockfile::LockedDependency;
+use crate::manifest::JargoToml;
+use crate::pom::{self, License};
+
+/// File name of the generated notices file.
+pub const THIRD_PARTY_FILE: &str = "THIRD-PARTY.txt";
+
+/// Parent POMs followed when looking for inherited `<licenses>`.
+const MAX_PARENT_DEPTH: usize = 10;
+
+/// License information for one runtime dependency.
+pub struct Notice {
+    pub group: String,
+    pub artifact: String,
+    pub version: String,
+    /// `<licenses>` from the POM or the nearest parent declaring them.
+    pub licenses: Vec<License>,
+    /// `LICENSE`, `NOTICE`, and `COPYING` files found at the root or in
+    /// `META-INF/` of the dependency JAR, as `(file name, contents)`.
+    pub files: Vec<(String, Vec<u8>)>,
+}
+
+impl Notice {
+    /// Directory the dependency's license files are bundled under.
+    pub fn dir_name(&self) -> String {
+        format!("{}-{}", self.artifact, self.version)
+    }
+}
+
+/// Collect notices for every runtime dependency in `lock_entries`, in lock
+/// file order. A POM that cannot be fetched or parsed only warns: the
+/// dependency is listed without a license.
+pub fn collect(gctx: &GlobalContext, lock_entries: &[LockedDependency]) -> Result<Vec<Notice>> {
+    let mut notices = Vec::new();
+    for entry in lock_entries
+        .iter()
+        .filter(|e| !matches!(e.scope.as_str(), "test" | "it"))
+    {
+        let coordinate = format!("{}:{}:{}", entry.group, entry.artifact, entry.version);
+        let licenses = match pom_licenses(gctx, &entry.group, &entry.artifact, &entry.version) {
+            Ok(licenses) => licenses,
+            Err(e) => {
+                gctx.shell.warn(&format!(
+                    "could not read the license of {}: {:#}",
+                    coordinate, e
+                ));
+                Vec::new()
+            }
+        };
+        let (jar, _sha256) = cache::fetch_jar(gctx, &entry.group, &entry.artifact, &entry.version)
+            .with_context(|| format!("failed to fetch JAR for {}", coordinate))?;
+        notices.push(Notice {
+            group: entry.group.clone(),
+            artifact: entry.artifact.clone(),
+            version: entry.version.clone(),
+            licenses,
+            files: license_files(&jar)?,
+        });
+    }
+    Ok(notices)
+}
+
+/// `<licenses>` of an artifact's POM, inherited from parents like Maven does.
+pub(crate) fn pom_licenses(
+    gctx: &GlobalContext,
+    group: &str,
+    artifact: &str,
+    version: &str,
+) -> Result<Vec<License>> {
+    let (mut group, mut artifact, mut version) =
+        (group.to_string(), artifact.to_string(), version.to_string());
+    for _ in 0..MAX_PARENT_DEPTH {
+        let path = cache::fetch_pom(gctx, &group, &artifact, &version)?;
+        let parsed = pom::parse_pom_raw(&path)?;
+        if !parsed.licenses.is_empty() {
+            return Ok(parsed.licenses);
+        }
+        match parsed.parent {
+            Some(parent) => {
+                (group, artifact, version) = (parent.group, parent.artifact, parent.version)
+            }
+            None => break,
+        }
+    }
+    Ok(Vec::new())
+}
+
+/// License and notice files at the root or in `META-INF/` of a JAR. When both
+/// hold a file with the same name, the `META-INF/` one wins.
+fn license_files(jar: &Path) -> Result<Vec<(String, Vec<u8>)>> {
+    let file = File::open(jar).with_context(|| format!("failed to open {}", jar.display()))?;
+    let mut archive =
+        zip::ZipArchive::new(file).with_context(|| format!("failed to read {}", jar.display()))?;
+    let mut names: Vec<String> = archive
+        .file_names()
+        .filter(|name| is_license_file(name))
+        .map(String::from)
+        .collect();
+    names.sort_by_key(|name| !name.starts_with("META-INF/"));
+
+    let mut seen = HashSet::new();
+    let mut files = Vec::new();
+    for name in names {
+        let base = name.trim_start_matches("META-INF/").to_string();
+        if !seen.insert(base.clone()) {
+            continue;
+        }
+        let mut entry = archive.by_name(&name)?;
+        let mut bytes = Vec::with_capacity(entry.size() as usize);
+        entry
+            .read_to_end(&mut bytes)
+            .with_context(|| format!("failed to read {} in {}", name, jar.display()))?;
+        files.push((base, bytes));
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn is_license_file(name: &str) -> bool {
+    let base = name.strip_prefix("META-INF/").unwrap_or(name);
+    let stem = base.split('.').next().unwrap_or("").to_ascii_uppercase();
+    !base.contains('/')
+        && ["LICENSE", "LICENCE", "NOTICE", "COPYING"]
+            .iter()
+            .any(|prefix| stem.starts_with(prefix))
+}
+
+/// The `THIRD-PARTY.txt` text: one block per dependency with its licenses
+/// and the bundled files under `licenses_dir`.
+pub fn render(manifest: &JargoToml, notices: &[Notice], licenses_dir: &str) -> String {
+    let mut out = format!(
+        "Third-party software bundled with {} {}\n",
+        manifest.package.name, manifest.package.version
+    );
+    for notice in notices {
+        out.push_str(&format!(
+            "\n{}:{}:{}\n",
+            notice.group, notice.artifact, notice.version
+        ));
+        if notice.licenses.is_empty() {
+            out.push_str("    License: not declared in the POM\n");
+        }
+        for license in &notice.licenses {
+            let text = match (license.name.is_empty(), license.url.is_empty()) {
+                (false, false) => format!("{} <{}>", license.name, license.url),
+                (false, true) => license.name.clone(),
+                (true, _) => license.url.clone(),
+            };
+            out.push_str(&format!("    License: {}\n", text));
+        }
+        for (name, _) in &notice.files {
+            out.push_str(&format!(
+                "    File: {}/{}/{}\n",
+                licenses_dir,
+                notice.dir_name(),
+                name
+            ));
+        }
+    }
+    out
+}
+
+/// Write `THIRD-PARTY.txt` and `licenses/{artifact}-{version}/` into `dir`.
+pub fn write_to_dir(dir: &Path, manifest: &JargoToml, notices: &[Notice]) -> Result<()> {
+    let path = dir.join(THIRD_PARTY_FILE);
+    fs::write(&path, render(manifest, notices, "licenses"))
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    for notice in notices {
+        if notice.files.is_empty() {
+            continue;
+        }
+        let notice_dir = dir.join("licenses").join(notice.dir_name());
+        fs::create_dir_all(&notice_dir)
+            .with_context(|| format!("failed to create {}", notice_dir.display()))?;
+        for (name, bytes) in &notice.files {
+            let path = notice_dir.join(name);
+            fs::write(&path, bytes)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn notice(licenses: Vec<License>, files: &[&str]) -> Notice {
+        Notice {
+            group: "com.acme".to_string(),
+            artifact: "widget".to_string(),
+            version: "1.0".to_string(),
+            licenses,
+            files: files
+                .iter()
+                .map(|name| (name.to_string(), b"text".to_vec()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_license_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let jar = tmp.path().join("dep.jar");
+        let mut zip = zip::ZipWriter::new(File::create(&jar).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        for (name, contents) in [
+            ("LICENSE", "root license"),
+            ("META-INF/LICENSE", "meta-inf license"),
+            ("META-INF/NOTICE.txt", "notice"),
+            ("META-INF/MANIFEST.MF", "Manifest-Version: 1.0\n"),
+            ("com/acme/LICENSE", "not at the root"),
+            ("com/acme/Widget.class", ""),
+        ] {
+            zip.start_file(name, options).unwrap();
+            zip.write_all(contents.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+
+        assert_eq!(
+            license_files(&jar).unwrap(),
+            vec![
+                ("LICENSE".to_string(), b"meta-inf license".to_vec()),
+                ("NOTICE.txt".to_string(), b"notice".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let manifest: JargoToml =
+            toml::from_str("[package]\nname = \"demo\"\nversion = \"2.0.0\"\njava = \"21\"\n")
+                .unwrap();
+        let notices = [
+            notice(
+                vec![License {
+                    name: "Apache License, Version 2.0".to_string(),
+                    url: "https://www.apache.org/licenses/LICENSE-2.0.txt".to_string(),
+                }],
+                &["LICENSE"],
+            ),
+            notice(Vec::new(), &[]),
+        ];
+        assert_eq!(
+            render(&manifest, &notices, "META-INF/licenses"),
+            "Third-party software bundled with demo 2.0.0\n\
+             \n\
+             com.acme:widget:1.0\n\
+             \x20   License: Apache License, Version 2.0 <https://www.apache.org/licenses/LICENSE-2.0.txt>\n\
+             \x20   File: META-INF/licenses/widget-1.0/LICENSE\n\
+             \n\
+             com.acme:widget:1.0\n\
+             \x20   License: not declared in the POM\n"
+        );
+    }
+}
@@ -25,6 +25,11 @@ pub enum TransitiveScope {
     Compile,
     /// Appears on the runtime classpath only.
     Runtime,
+    /// Appears on the test classpaths only (reached solely through dev-dependencies).
+    Test,
+    /// Appears on the integration test classpaths only (reached solely through
+    /// it-dependencies).
+    IntegrationTest,
 }
 
 // ---------------------------------------------------------------------------
@@ -55,6 +60,15 @@ pub struct ManagedEntry {
     pub scope: String,
 }
 
+/// A `<license>` entry from a POM's `<licenses>` section.
+#[derive(Debug, Clone, PartialEq)]
+pub struct License {
+    /// `<name>`, e.g. `Apache License, Version 2.0`. May be empty.
+    pub name: String,
+    /// `<url>`. May be empty.
+    pub url: String,
+}
+
 /// Coordinates of a parent POM.
 #[derive(Debug, Clone)]
 pub struct ParentRef {
@@ -81,6 +95,9 @@ pub struct ParsedPom {
     /// Direct `<dependencies>` (raw; may have empty versions / `${...}` placeholders).
     /// Optional and excluded-scope entries are already filtered out.
     pub direct_deps: Vec<RawDep>,
+    /// Project `<licenses>` (empty if none are declared; Maven inherits them
+    /// from the parent then).
+    pub licenses: Vec<License>,
 }
 
 // ---------------------------------------------------------------------------
@@ -168,6 +185,7 @@ fn parse_pom_raw_str(xml: &str) -> Result<ParsedPom> {
     let mut properties: HashMap<String, String> = HashMap::new();
     let mut managed: HashMap<(String, String), ManagedEntry> = HashMap::new();
     let mut direct_deps: Vec<RawDep> = Vec::new();
+    let mut licenses: Vec<License> = Vec::new();
 
     // Current dependency being parsed (shared for direct and managed)
     let mut cur_group = String::new();
@@ -176,6 +194,10 @@ fn parse_pom_raw_str(xml: &str) -> Result<ParsedPom> {
     let mut cur_scope = String::new();
     let mut cur_optional = String::new();
 
+    // Current license being parsed
+    let mut cur_license_name = String::new();
+    let mut cur_license_url = String::new();
+
     loop {
         match reader.read_event() {
             Ok(Event::Start(e)) => {
@@ -189,6 +211,10 @@ fn parse_pom_raw_str(xml: &str) -> Result<ParsedPom> {
                     cur_scope.clear();
                     cur_optional.clear();
                 }
+                if name == "license" && in_licenses_element(&stack) {
+                    cur_license_name.clear();
+                    cur_license_url.clear();
+                }
 
                 stack.push(name);
             }
@@ -215,6 +241,13 @@ fn parse_pom_raw_str(xml: &str) -> Result<ParsedPom> {
                             "version" => parent_version = text,
                             _ => {}
                         }
+                    } else if stack.len() == 4 && in_licenses_element(&stack[..2]) {
+                        // Inside <licenses><license>
+                        match tag.as_str() {
+                            "name" => cur_license_name = text,
+                            "url" => cur_license_url = text,
+                            _ => {}
+                        }
                     } else if in_properties_element(&stack) && tag != "properties" {
                         // Inside <properties> — tag name is the property key
                         properties.insert(tag, text);
@@ -266,6 +299,12 @@ fn parse_pom_raw_str(xml: &str) -> Result<ParsedPom> {
                 }
 
                 stack.pop();
+                if name == "license" && in_licenses_element(&stack) {
+                    licenses.push(License {
+                        name: cur_license_name.clone(),
+                        url: cur_license_url.clone(),
+                    });
+                }
             }
 
             Ok(Event::Eof) => break,
@@ -292,6 +331,7 @@ fn parse_pom_raw_str(xml: &str) -> Result<ParsedPom> {
         properties,
         managed,
         direct_deps,
+        licenses,
     })
 }
 
@@ -319,6 +359,11 @@ fn in_properties_element(stack: &[String]) -> bool {
     has_tag(stack, "properties") && !has_tag(stack, "dependency")
 }
 
+/// True when the stack is `<project><licenses>`.
+fn in_licenses_element(stack: &[String]) -> bool {
+    stack.len() == 2 && stack[1] == "licenses"
+}
+
 /// True when the stack has exactly two elements (the project root and its direct child).
 ///
 /// This identifies project-level fields like `<groupId>`, `<version>`, etc. that
@@ -720,4 +765,43 @@ mod tests {
         assert_eq!(parsed.group, "com.example.child");
         assert_eq!(parsed.parent.unwrap().group, "com.example");
     }
+
+    #[test]
+    fn test_raw_licenses() {
+        let xml = r#"<?xml version="1.0"?>
+<project>
+  <licenses>
+    <license>
+      <name>Apache License, Version 2.0</name>
+      <url>https://www.apache.org/licenses/LICENSE-2.0.txt</url>
+      <distribution>repo</distribution>
+    </license>
+    <license>
+      <name>MIT</name>
+    </license>
+  </licenses>
+  <dependencies>
+    <dependency>
+      <groupId>x</groupId>
+      <artifactId>y</artifactId>
+      <version>1</version>
+    </dependency>
+  </dependencies>
+</project>"#;
+        let parsed = parse_pom_raw_str(xml).unwrap();
+        assert_eq!(
+            parsed.licenses,
+            vec![
+                License {
+                    name: "Apache License, Version 2.0".to_string(),
+                    url: "https://www.apache.org/licenses/LICENSE-2.0.txt".to_string(),
+                },
+                License {
+                    name: "MIT".to_string(),
+                    url: String::new(),
+                },
+            ]
+        );
+        assert_eq!(parsed.direct_deps.len(), 1);
+    }
 }
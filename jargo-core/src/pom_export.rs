@@ -0,0 +1,263 @@
+use anyhow::{bail, Result};
+use quick_xml::escape::escape;
+
+use crate::manifest::{Dependency, JargoToml, Scope};
+
+/// Where `jargo pom` writes the POM.
+pub const POM_PATH: &str = "target/pom.xml";
+
+/// Render the `pom.xml` a Maven consumer sees for `manifest`.
+/// `member_deps` are the workspace members the package depends on, as
+/// published coordinates.
+///
+/// Scope mapping: compile dependencies of an app, and compile dependencies
+/// of a lib with `expose = true`, stay `compile`; the other compile and all
+/// runtime dependencies become `runtime`; dev- and it-dependencies become
+/// `test`.
+pub fn render(manifest: &JargoToml, member_deps: &[Dependency]) -> Result<String> {
+    let package = &manifest.package;
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(
+        "<project xmlns=\"http://maven.apache.org/POM/4.0.0\" \
+         xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+         xsi:schemaLocation=\"http://maven.apache.org/POM/4.0.0 \
+         https://maven.apache.org/xsd/maven-4.0.0.xsd\">\n",
+    );
+    element(&mut out, 1, "modelVersion", "4.0.0");
+    element(&mut out, 1, "groupId", &manifest.get_group());
+    element(&mut out, 1, "artifactId", &package.name);
+    element(&mut out, 1, "version", &package.version);
+    element(&mut out, 1, "packaging", "jar");
+    element(&mut out, 1, "name", &package.name);
+    if let Some(description) = &package.description {
+        element(&mut out, 1, "description", description);
+    }
+    if let Some(url) = package.homepage.as_ref().or(package.repository.as_ref()) {
+        element(&mut out, 1, "url", url);
+    }
+
+    if let Some(license) = &package.license {
+        out.push_str("  <licenses>\n");
+        for id in license_ids(license)? {
+            out.push_str("    <license>\n");
+            element(&mut out, 3, "name", id);
+            element(
+                &mut out,
+                3,
+                "url",
+                &format!("https://spdx.org/licenses/{}.html", id),
+            );
+            out.push_str("    </license>\n");
+        }
+        out.push_str("  </licenses>\n");
+    }
+
+    if !package.authors.is_empty() {
+        out.push_str("  <developers>\n");
+        for author in &package.authors {
+            let (name, email) = parse_author(author);
+            out.push_str("    <developer>\n");
+            element(&mut out, 3, "name", name);
+            if let Some(email) = email {
+                element(&mut out, 3, "email", email);
+            }
+            out.push_str("    </developer>\n");
+        }
+        out.push_str("  </developers>\n");
+    }
+
+    if let Some(repository) = &package.repository {
+        out.push_str("  <scm>\n");
+        element(&mut out, 2, "url", repository);
+        let git = format!("scm:git:{}", repository);
+        element(&mut out, 2, "connection", &git);
+        element(&mut out, 2, "developerConnection", &git);
+        out.push_str("  </scm>\n");
+    }
+
+    out.push_str("  <properties>\n");
+    element(&mut out, 2, "maven.compiler.release", &package.java);
+    element(&mut out, 2, "project.build.sourceEncoding", "UTF-8");
+    out.push_str("  </properties>\n");
+
+    let is_lib = !manifest.is_app();
+    let mut deps: Vec<(Dependency, &str)> = Vec::new();
+    for dep in manifest
+        .get_dependencies()?
+        .into_iter()
+        .chain(member_deps.iter().cloned())
+    {
+        let scope = match dep.scope {
+            Scope::Compile if !is_lib || dep.expose => "compile",
+            _ => "runtime",
+        };
+        deps.push((dep, scope));
+    }
+    for dep in manifest
+        .get_dev_dependencies()?
+        .into_iter()
+        .chain(manifest.get_it_dependencies()?)
+    {
+        // A main dependency is already on the test classpath
+        if !deps
+            .iter()
+            .any(|(d, _)| d.group == dep.group && d.artifact == dep.artifact)
+        {
+            deps.push((dep, "test"));
+        }
+    }
+    // `<optional>` keeps feature dependencies off consumers' classpaths
+    let optional = manifest.optional_dependencies();
+    if !deps.is_empty() {
+        out.push_str("  <dependencies>\n");
+        for (dep, scope) in &deps {
+            out.push_str("    <dependency>\n");
+            element(&mut out, 3, "groupId", &dep.group);
+            element(&mut out, 3, "artifactId", &dep.artifact);
+            element(&mut out, 3, "version", &dep.version);
+            element(&mut out, 3, "scope", scope);
+            if optional.contains(&format!("{}:{}", dep.group, dep.artifact)) {
+                element(&mut out, 3, "optional", "true");
+            }
+            out.push_str("    </dependency>\n");
+        }
+        out.push_str("  </dependencies>\n");
+    }
+
+    out.push_str("</project>\n");
+    Ok(out)
+}
+
+fn element(out: &mut String, depth: usize, name: &str, text: &str) {
+    out.push_str(&format!(
+        "{}<{}>{}</{}>\n",
+        "  ".repeat(depth),
+        name,
+        escape(text),
+        name
+    ));
+}
+
+/// The license identifiers of an SPDX expression: `"MIT OR Apache-2.0"` →
+/// `["MIT", "Apache-2.0"]`.
+fn license_ids(expression: &str) -> Result<Vec<&str>> {
+    let ids: Vec<&str> = expression
+        .split_whitespace()
+        .filter(|word| !matches!(*word, "OR" | "AND" | "WITH"))
+        .map(|word| word.trim_matches(|c| c == '(' || c == ')'))
+        .filter(|id| !id.is_empty())
+        .collect();
+    if ids.is_empty() {
+        bail!(
+            "invalid `license = \"{}\"`: expected an SPDX expression",
+            expression
+        );
+    }
+    Ok(ids)
+}
+
+/// `"Jane Doe <jane@example.com>"` → name and email.
+fn parse_author(author: &str) -> (&str, Option<&str>) {
+    match author.split_once('<') {
+        Some((name, rest)) => (name.trim(), Some(rest.trim_end_matches('>').trim())),
+        None => (author.trim(), None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let manifest: JargoToml = toml::from_str(
+            r#"
+[package]
+name = "parser"
+version = "1.2.0"
+type = "lib"
+java = "17"
+group = "com.example"
+description = "Parses <things> & more"
+license = "MIT OR Apache-2.0"
+repository = "https://github.com/example/parser"
+authors = ["Jane Doe <jane@example.com>", "Build Bot"]
+
+[dependencies]
+"com.google.guava:guava" = { version = "33.0.0-jre", expose = true }
+"org.slf4j:slf4j-api" = "2.0.12"
+
+[dev-dependencies]
+"org.assertj:assertj-core" = "3.25.1"
+"#,
+        )
+        .unwrap();
+        let member = Dependency {
+            group: "com.example".to_string(),
+            artifact: "core".to_string(),
+            version: "1.0.0".to_string(),
+            scope: Scope::Compile,
+            expose: false,
+        };
+        let pom = render(&manifest, &[member]).unwrap();
+
+        assert!(pom.contains("  <groupId>com.example</groupId>\n  <artifactId>parser</artifactId>\n  <version>1.2.0</version>"));
+        assert!(pom.contains("<description>Parses &lt;things&gt; &amp; more</description>"));
+        assert!(pom.contains("<url>https://github.com/example/parser</url>"));
+        assert!(pom.contains(
+            "<name>Apache-2.0</name>\n      <url>https://spdx.org/licenses/Apache-2.0.html</url>"
+        ));
+        assert!(pom.contains("<name>Jane Doe</name>\n      <email>jane@example.com</email>"));
+        assert!(pom.contains("<name>Build Bot</name>\n    </developer>"));
+        assert!(pom.contains("<maven.compiler.release>17</maven.compiler.release>"));
+        let scope_of = |artifact: &str| {
+            let start = pom
+                .find(&format!("<artifactId>{}</artifactId>", artifact))
+                .unwrap();
+            let scope = &pom[start..][pom[start..].find("<scope>").unwrap() + 7..];
+            scope[..scope.find('<').unwrap()].to_string()
+        };
+        assert_eq!(scope_of("guava"), "compile");
+        assert_eq!(scope_of("slf4j-api"), "runtime");
+        assert_eq!(scope_of("core"), "runtime");
+        assert_eq!(scope_of("assertj-core"), "test");
+    }
+
+    #[test]
+    fn test_app_scopes_and_minimal_pom() {
+        let manifest: JargoToml = toml::from_str(
+            r#"
+[package]
+name = "my-app"
+version = "0.1.0"
+java = "21"
+
+[features]
+metrics = ["io.micrometer:micrometer-core"]
+
+[dependencies]
+"org.slf4j:slf4j-api" = "2.0.12"
+"io.micrometer:micrometer-core" = { version = "1.12.2", optional = true }
+"#,
+        )
+        .unwrap();
+        let pom = render(&manifest, &[]).unwrap();
+        assert!(pom.contains("<groupId>myapp</groupId>"));
+        assert!(pom.contains("<scope>compile</scope>"));
+        assert!(pom.contains("<scope>compile</scope>\n      <optional>true</optional>"));
+        assert_eq!(pom.matches("<optional>").count(), 1);
+        assert!(!pom.contains("<licenses>"));
+        assert!(!pom.contains("<scm>"));
+    }
+
+    #[test]
+    fn test_license_ids() {
+        assert_eq!(license_ids("MIT").unwrap(), ["MIT"]);
+        assert_eq!(
+            license_ids("(MIT OR Apache-2.0) AND BSD-3-Clause").unwrap(),
+            ["MIT", "Apache-2.0", "BSD-3-Clause"]
+        );
+        assert!(license_ids(" ").is_err());
+    }
+}
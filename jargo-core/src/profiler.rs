@@ -0,0 +1,77 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A Java Flight Recorder session for a single JVM launch.
+///
+/// The recording is written to `target/profiles/{name}-{timestamp}.jfr` when
+/// the JVM exits.
+pub struct FlightRecording {
+    pub path: PathBuf,
+}
+
+impl FlightRecording {
+    /// Prepare a recording for `name`, creating `target/profiles/` if needed.
+    pub fn new(project_root: &Path, name: &str) -> Result<Self> {
+        let profiles_dir = project_root.join("target/profiles");
+        fs::create_dir_all(&profiles_dir)
+            .with_context(|| format!("failed to create {}", profiles_dir.display()))?;
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        Ok(Self {
+            path: profiles_dir.join(recording_filename(name, timestamp)),
+        })
+    }
+
+    /// The JVM argument that starts the recording and dumps it on exit.
+    pub fn jvm_arg(&self) -> String {
+        format!(
+            "-XX:StartFlightRecording=filename={},settings=profile,dumponexit=true",
+            self.path.display()
+        )
+    }
+}
+
+fn recording_filename(name: &str, timestamp: u64) -> String {
+    format!("{}-{}.jfr", name, timestamp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_recording_filename() {
+        assert_eq!(
+            recording_filename("my-app", 1700000000),
+            "my-app-1700000000.jfr"
+        );
+    }
+
+    #[test]
+    fn test_recording_under_target_profiles() {
+        let tmp = TempDir::new().unwrap();
+        let rec = FlightRecording::new(tmp.path(), "my-app").unwrap();
+
+        assert!(tmp.path().join("target/profiles").is_dir());
+        assert!(rec.path.starts_with(tmp.path().join("target/profiles")));
+        assert_eq!(rec.path.extension().unwrap(), "jfr");
+    }
+
+    #[test]
+    fn test_jvm_arg() {
+        let rec = FlightRecording {
+            path: PathBuf::from("target/profiles/app-1.jfr"),
+        };
+        assert_eq!(
+            rec.jvm_arg(),
+            "-XX:StartFlightRecording=filename=target/profiles/app-1.jfr,settings=profile,dumponexit=true"
+        );
+    }
+}
@@ -0,0 +1,233 @@
+use anyhow::{Context, Result};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs::File;
+use std::path::Path;
+
+use crate::cache;
+use crate::manifest::{Dependency, JargoToml};
+
+/// A reason a release would be rejected, found before anything is uploaded.
+#[derive(Debug, PartialEq)]
+pub enum PublishProblem {
+    /// `package.version` is a `-SNAPSHOT`.
+    SnapshotVersion(String),
+    /// `[package]` fields the repository requires are not set.
+    MissingMetadata(Vec<&'static str>),
+    /// A dependency is on a `-SNAPSHOT` version, which releases may not use.
+    SnapshotDependency(String),
+    /// A workspace member this package depends on is not published at the
+    /// version its POM will name.
+    UnpublishedMember { name: String, coordinate: String },
+    /// This version is already in the repository.
+    AlreadyPublished(String),
+    /// A class compiled into `target/classes/` is not in the JAR.
+    MissingClass(String),
+    /// The version bump is smaller than the API changes since the last
+    /// release need, with `[api-check] semver` set.
+    SemverViolation(String),
+}
+
+impl fmt::Display for PublishProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PublishProblem::SnapshotVersion(version) => {
+                write!(f, "version `{}` is a snapshot, not a release", version)
+            }
+            PublishProblem::MissingMetadata(fields) => {
+                write!(f, "[package] has no {}", fields.join(", "))
+            }
+            PublishProblem::SnapshotDependency(coordinate) => {
+                write!(f, "dependency `{}` is a snapshot", coordinate)
+            }
+            PublishProblem::UnpublishedMember { name, coordinate } => write!(
+                f,
+                "workspace member `{}` is not published as `{}`; publish it first",
+                name, coordinate
+            ),
+            PublishProblem::AlreadyPublished(coordinate) => {
+                write!(f, "`{}` is already published", coordinate)
+            }
+            PublishProblem::MissingClass(entry) => {
+                write!(f, "`{}` was compiled but is not in the JAR", entry)
+            }
+            PublishProblem::SemverViolation(reason) => f.write_str(reason),
+        }
+    }
+}
+
+/// Check the release itself: `manifest` is not a snapshot, has every
+/// `required` `[package]` field, and depends on no snapshots.
+pub fn check_manifest(
+    manifest: &JargoToml,
+    required: &[&'static str],
+) -> Result<Vec<PublishProblem>> {
+    let package = &manifest.package;
+    let mut problems = Vec::new();
+    if package.version.ends_with("-SNAPSHOT") {
+        problems.push(PublishProblem::SnapshotVersion(package.version.clone()));
+    }
+    let missing: Vec<&'static str> = required
+        .iter()
+        .copied()
+        .filter(|field| !has_field(manifest, field))
+        .collect();
+    if !missing.is_empty() {
+        problems.push(PublishProblem::MissingMetadata(missing));
+    }
+    for dep in manifest.get_dependencies()? {
+        if dep.version.ends_with("-SNAPSHOT") {
+            problems.push(PublishProblem::SnapshotDependency(format!(
+                "{}:{}:{}",
+                dep.group, dep.artifact, dep.version
+            )));
+        }
+    }
+    Ok(problems)
+}
+
+fn has_field(manifest: &JargoToml, field: &str) -> bool {
+    let package = &manifest.package;
+    match field {
+        "description" => package.description.is_some(),
+        "license" => package.license.is_some(),
+        "repository" => package.repository.is_some(),
+        "authors" => !package.authors.is_empty(),
+        _ => true,
+    }
+}
+
+/// Check `repository` (read with `auth`, if set) for the published state of
+/// the release: `coordinate` (`group:artifact:version`) must be new there,
+/// and the workspace members it depends on, as `members` are published, must
+/// already be there.
+pub fn check_repository(
+    repository: &str,
+    auth: Option<(&str, &str)>,
+    coordinate: (&str, &str, &str),
+    members: &[Dependency],
+) -> Result<Vec<PublishProblem>> {
+    let mut problems = Vec::new();
+    let (group, artifact, version) = coordinate;
+    if cache::artifact_exists(repository, auth, group, artifact, version)? {
+        problems.push(PublishProblem::AlreadyPublished(format!(
+            "{}:{}:{}",
+            group, artifact, version
+        )));
+    }
+    for dep in members {
+        if !cache::artifact_exists(repository, auth, &dep.group, &dep.artifact, &dep.version)? {
+            problems.push(PublishProblem::UnpublishedMember {
+                name: dep.artifact.clone(),
+                coordinate: format!("{}:{}:{}", dep.group, dep.artifact, dep.version),
+            });
+        }
+    }
+    Ok(problems)
+}
+
+/// Check that every class file under `classes_dir` made it into `jar`.
+pub fn check_jar(classes_dir: &Path, jar: &Path) -> Result<Vec<PublishProblem>> {
+    let file = File::open(jar).with_context(|| format!("failed to open {}", jar.display()))?;
+    let archive =
+        zip::ZipArchive::new(file).with_context(|| format!("failed to read {}", jar.display()))?;
+    let entries: BTreeSet<&str> = archive.file_names().collect();
+
+    let mut classes = Vec::new();
+    if classes_dir.is_dir() {
+        collect_classes(classes_dir, classes_dir, &mut classes)?;
+    }
+    classes.sort();
+    Ok(classes
+        .into_iter()
+        .filter(|class| !entries.contains(class.as_str()))
+        .map(PublishProblem::MissingClass)
+        .collect())
+}
+
+fn collect_classes(root: &Path, dir: &Path, classes: &mut Vec<String>) -> Result<()> {
+    for entry in
+        std::fs::read_dir(dir).with_context(|| format!("failed to read {}", dir.display()))?
+    {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_classes(root, &path, classes)?;
+        } else if path.extension().is_some_and(|e| e == "class") {
+            let rel = path.strip_prefix(root).expect("walked from root");
+            let name: Vec<String> = rel
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect();
+            classes.push(name.join("/"));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::central::REQUIRED_METADATA;
+    use std::fs;
+    use zip::write::SimpleFileOptions;
+
+    fn manifest_with(extra: &str) -> JargoToml {
+        toml::from_str(&format!(
+            "[package]\nname = \"demo\"\nversion = \"1.0.0\"\njava = \"21\"\n{}",
+            extra
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_check_manifest() {
+        let mut manifest = manifest_with(
+            "license = \"MIT\"\n\n[dependencies]\n\
+             \"com.acme:widget\" = \"2.0-SNAPSHOT\"\n\"com.acme:core\" = \"1.0\"\n",
+        );
+        manifest.package.version = "1.0.0-SNAPSHOT".to_string();
+        let problems = check_manifest(&manifest, REQUIRED_METADATA).unwrap();
+        assert_eq!(
+            problems,
+            [
+                PublishProblem::SnapshotVersion("1.0.0-SNAPSHOT".to_string()),
+                PublishProblem::MissingMetadata(vec!["description", "repository", "authors"]),
+                PublishProblem::SnapshotDependency("com.acme:widget:2.0-SNAPSHOT".to_string()),
+            ]
+        );
+        assert_eq!(
+            problems[1].to_string(),
+            "[package] has no description, repository, authors"
+        );
+
+        let release = manifest_with(
+            "description = \"Parses things\"\nlicense = \"MIT\"\n\
+             repository = \"https://github.com/example/parser\"\n\
+             authors = [\"Jane Doe <jane@example.com>\"]",
+        );
+        assert!(check_manifest(&release, REQUIRED_METADATA)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_check_jar() {
+        let tmp = tempfile::tempdir().unwrap();
+        let classes = tmp.path().join("classes");
+        fs::create_dir_all(classes.join("demo/util")).unwrap();
+        fs::write(classes.join("demo/Main.class"), "").unwrap();
+        fs::write(classes.join("demo/util/Helper.class"), "").unwrap();
+        let jar_path = tmp.path().join("demo.jar");
+        let mut zip = zip::ZipWriter::new(File::create(&jar_path).unwrap());
+        zip.start_file("demo/Main.class", SimpleFileOptions::default())
+            .unwrap();
+        zip.finish().unwrap();
+
+        assert_eq!(
+            check_jar(&classes, &jar_path).unwrap(),
+            [PublishProblem::MissingClass(
+                "demo/util/Helper.class".to_string()
+            )]
+        );
+    }
+}
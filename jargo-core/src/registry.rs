@@ -0,0 +1,436 @@
+use anyhow::{bail, Context, Result};
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::header::{ACCEPT, CONTENT_TYPE, LOCATION, WWW_AUTHENTICATE};
+use reqwest::StatusCode;
+use sha2::{Digest, Sha256};
+
+use crate::config;
+
+pub const OCI_INDEX: &str = "application/vnd.oci.image.index.v1+json";
+pub const OCI_MANIFEST: &str = "application/vnd.oci.image.manifest.v1+json";
+pub const DOCKER_MANIFEST_LIST: &str = "application/vnd.docker.distribution.manifest.list.v2+json";
+pub const DOCKER_MANIFEST: &str = "application/vnd.docker.distribution.manifest.v2+json";
+
+/// A parsed image reference: `[registry/]repository[:tag|@digest]`.
+///
+/// `eclipse-temurin:21-jre` → `docker.io`, `library/eclipse-temurin`, `21-jre`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageRef {
+    pub registry: String,
+    pub repository: String,
+    /// Tag or `sha256:` digest; `latest` when neither is given.
+    pub reference: String,
+}
+
+impl ImageRef {
+    pub fn parse(image: &str) -> Result<Self> {
+        let (name, reference) = match image.split_once('@') {
+            Some((name, digest)) => (name, digest.to_string()),
+            None => match image.rfind(':') {
+                // A colon followed by a `/` belongs to a registry port
+                Some(i) if !image[i + 1..].contains('/') => {
+                    (&image[..i], image[i + 1..].to_string())
+                }
+                _ => (image, "latest".to_string()),
+            },
+        };
+        let (registry, repository) = match name.split_once('/') {
+            Some((first, rest)) if first.contains(['.', ':']) || first == "localhost" => {
+                (first.to_string(), rest.to_string())
+            }
+            _ => ("docker.io".to_string(), name.to_string()),
+        };
+        let repository = if registry == "docker.io" && !repository.contains('/') {
+            format!("library/{}", repository)
+        } else {
+            repository
+        };
+
+        let valid_repository = !repository.is_empty()
+            && repository.split('/').all(|part| {
+                !part.is_empty()
+                    && part.chars().all(|c| {
+                        c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '.' | '_' | '-')
+                    })
+            });
+        if !valid_repository || reference.is_empty() {
+            bail!(
+                "invalid image reference `{}`: expected `[registry/]name[:tag]` with a lowercase name",
+                image
+            );
+        }
+        Ok(Self {
+            registry,
+            repository,
+            reference,
+        })
+    }
+
+    /// The tag, unless the reference is a digest.
+    pub fn tag(&self) -> Option<&str> {
+        (!self.reference.starts_with("sha256:")).then_some(self.reference.as_str())
+    }
+
+    /// Base URL of the repository's registry API.
+    fn api_url(&self) -> String {
+        let host = match self.registry.as_str() {
+            "docker.io" => "registry-1.docker.io",
+            other => other,
+        };
+        // Local registries usually serve plain HTTP
+        let scheme = if host.starts_with("localhost") || host.starts_with("127.0.0.1") {
+            "http"
+        } else {
+            "https"
+        };
+        format!("{}://{}/v2/{}", scheme, host, self.repository)
+    }
+}
+
+impl std::fmt::Display for ImageRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let separator = if self.tag().is_some() { ':' } else { '@' };
+        write!(
+            f,
+            "{}/{}{}{}",
+            self.registry, self.repository, separator, self.reference
+        )
+    }
+}
+
+enum Auth {
+    None,
+    Basic,
+    Bearer(String),
+}
+
+/// Client for one repository of an OCI distribution registry.
+///
+/// Authentication follows the registry's `WWW-Authenticate` challenge:
+/// anonymous or `JARGO_REGISTRY_USERNAME`/`JARGO_REGISTRY_PASSWORD` bearer
+/// tokens, or basic auth with those credentials.
+pub struct RegistryClient {
+    client: Client,
+    image: ImageRef,
+    credentials: Option<(String, String)>,
+    auth: Auth,
+}
+
+impl RegistryClient {
+    pub fn new(image: &ImageRef) -> Result<Self> {
+        config::ensure_online(&image.registry)?;
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(300))
+            .build()
+            .context("failed to create HTTP client")?;
+        let credentials = match (
+            std::env::var("JARGO_REGISTRY_USERNAME"),
+            std::env::var("JARGO_REGISTRY_PASSWORD"),
+        ) {
+            (Ok(user), Ok(password)) if !user.is_empty() => Some((user, password)),
+            _ => None,
+        };
+        Ok(Self {
+            client,
+            image: image.clone(),
+            credentials,
+            auth: Auth::None,
+        })
+    }
+
+    /// Fetch a manifest or index by tag or digest. Returns its media type and body.
+    pub fn get_manifest(&mut self, reference: &str) -> Result<(String, Vec<u8>)> {
+        let url = format!("{}/manifests/{}", self.image.api_url(), reference);
+        let accept = [
+            OCI_INDEX,
+            OCI_MANIFEST,
+            DOCKER_MANIFEST_LIST,
+            DOCKER_MANIFEST,
+        ]
+        .join(", ");
+        let response = self.send(|client| client.get(&url).header(ACCEPT, &accept))?;
+        let response = check(response, &url)?;
+        let media_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("")
+            .split(';')
+            .next()
+            .unwrap_or("")
+            .trim()
+            .to_string();
+        let body = response
+            .bytes()
+            .with_context(|| format!("failed to read response body from {}", url))?;
+        Ok((media_type, body.to_vec()))
+    }
+
+    /// Download a blob and check it against its digest.
+    pub fn get_blob(&mut self, digest: &str) -> Result<Vec<u8>> {
+        let url = format!("{}/blobs/{}", self.image.api_url(), digest);
+        let response = check(self.send(|client| client.get(&url))?, &url)?;
+        let body = response
+            .bytes()
+            .with_context(|| format!("failed to read response body from {}", url))?;
+        if sha256_digest(&body) != digest {
+            bail!(
+                "blob {} from {} does not match its digest",
+                digest,
+                self.image
+            );
+        }
+        Ok(body.to_vec())
+    }
+
+    pub fn has_blob(&mut self, digest: &str) -> Result<bool> {
+        let url = format!("{}/blobs/{}", self.image.api_url(), digest);
+        let response = self.send(|client| client.head(&url))?;
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(false),
+            status if status.is_success() => Ok(true),
+            status => bail!("HTTP {} checking {}", status, url),
+        }
+    }
+
+    /// Ask the registry to link a blob from another repository it hosts.
+    /// Returns `false` when it did not, in which case the blob must be uploaded.
+    pub fn mount_blob(&mut self, digest: &str, from: &str) -> Result<bool> {
+        let url = format!(
+            "{}/blobs/uploads/?mount={}&from={}",
+            self.image.api_url(),
+            digest,
+            from
+        );
+        let response = self.send(|client| client.post(&url))?;
+        Ok(response.status() == StatusCode::CREATED)
+    }
+
+    /// Upload a blob in one request.
+    pub fn upload_blob(&mut self, digest: &str, data: &[u8]) -> Result<()> {
+        let url = format!("{}/blobs/uploads/", self.image.api_url());
+        let response = check(self.send(|client| client.post(&url))?, &url)?;
+        let location = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|v| v.to_str().ok())
+            .with_context(|| format!("registry returned no upload location for {}", url))?;
+        let mut upload_url = response
+            .url()
+            .join(location)
+            .with_context(|| format!("invalid upload location `{}`", location))?;
+        upload_url.query_pairs_mut().append_pair("digest", digest);
+
+        let response = self.send(|client| {
+            client
+                .put(upload_url.clone())
+                .header(CONTENT_TYPE, "application/octet-stream")
+                .body(data.to_vec())
+        })?;
+        check(response, upload_url.as_str())?;
+        Ok(())
+    }
+
+    pub fn put_manifest(&mut self, reference: &str, media_type: &str, body: &[u8]) -> Result<()> {
+        let url = format!("{}/manifests/{}", self.image.api_url(), reference);
+        let response = self.send(|client| {
+            client
+                .put(&url)
+                .header(CONTENT_TYPE, media_type)
+                .body(body.to_vec())
+        })?;
+        check(response, &url)?;
+        Ok(())
+    }
+
+    /// Send a request, authenticating and retrying once when challenged.
+    fn send(&mut self, request: impl Fn(&Client) -> RequestBuilder) -> Result<Response> {
+        let response = self.authorize(request(&self.client)).send();
+        let response = response.context("HTTP request to registry failed")?;
+        if response.status() != StatusCode::UNAUTHORIZED {
+            return Ok(response);
+        }
+        let challenge = response
+            .headers()
+            .get(WWW_AUTHENTICATE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("")
+            .to_string();
+        self.authenticate(&challenge)?;
+        self.authorize(request(&self.client))
+            .send()
+            .context("HTTP request to registry failed")
+    }
+
+    fn authorize(&self, request: RequestBuilder) -> RequestBuilder {
+        match (&self.auth, &self.credentials) {
+            (Auth::Bearer(token), _) => request.bearer_auth(token),
+            (Auth::Basic, Some((user, password))) => request.basic_auth(user, Some(password)),
+            _ => request,
+        }
+    }
+
+    fn authenticate(&mut self, challenge: &str) -> Result<()> {
+        let (scheme, params) = challenge.split_once(' ').unwrap_or((challenge, ""));
+        if scheme.eq_ignore_ascii_case("basic") {
+            if self.credentials.is_none() {
+                bail!(
+                    "{} requires credentials: set JARGO_REGISTRY_USERNAME and JARGO_REGISTRY_PASSWORD",
+                    self.image.registry
+                );
+            }
+            self.auth = Auth::Basic;
+            return Ok(());
+        }
+        if !scheme.eq_ignore_ascii_case("bearer") {
+            bail!("{} refused the request (HTTP 401)", self.image.registry);
+        }
+
+        let params = parse_challenge(params);
+        let realm = params
+            .iter()
+            .find(|(k, _)| k == "realm")
+            .map(|(_, v)| v.as_str())
+            .with_context(|| {
+                format!(
+                    "{} sent a bearer challenge without a realm",
+                    self.image.registry
+                )
+            })?;
+        let query: Vec<(&str, &str)> = params
+            .iter()
+            .filter(|(k, _)| k == "service" || k == "scope")
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        let mut request = self.client.get(realm).query(&query);
+        if let Some((user, password)) = &self.credentials {
+            request = request.basic_auth(user, Some(password));
+        }
+        let response = request
+            .send()
+            .with_context(|| format!("failed to request a token from {}", realm))?;
+        if !response.status().is_success() {
+            bail!(
+                "HTTP {} requesting a token from {}{}",
+                response.status(),
+                realm,
+                if self.credentials.is_none() {
+                    " (set JARGO_REGISTRY_USERNAME and JARGO_REGISTRY_PASSWORD for private images)"
+                } else {
+                    ""
+                }
+            );
+        }
+        let body = response
+            .bytes()
+            .with_context(|| format!("failed to read token response from {}", realm))?;
+        let body: serde_json::Value = serde_json::from_slice(&body)
+            .with_context(|| format!("invalid token response from {}", realm))?;
+        let token = body
+            .get("token")
+            .or_else(|| body.get("access_token"))
+            .and_then(|t| t.as_str())
+            .with_context(|| format!("token response from {} has no token", realm))?;
+        self.auth = Auth::Bearer(token.to_string());
+        Ok(())
+    }
+}
+
+fn check(response: Response, url: &str) -> Result<Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().unwrap_or_default();
+    bail!("HTTP {} from {}: {}", status, url, body.trim())
+}
+
+/// Parse `key="value",key2="value2"` challenge parameters.
+fn parse_challenge(params: &str) -> Vec<(String, String)> {
+    let mut out = Vec::new();
+    let mut rest = params.trim();
+    while let Some(eq) = rest.find('=') {
+        let key = rest[..eq].trim().trim_start_matches(',').trim().to_string();
+        rest = &rest[eq + 1..];
+        let value = if let Some(quoted) = rest.strip_prefix('"') {
+            let end = quoted.find('"').unwrap_or(quoted.len());
+            let value = &quoted[..end];
+            rest = quoted.get(end + 1..).unwrap_or("");
+            value
+        } else {
+            let end = rest.find(',').unwrap_or(rest.len());
+            let value = &rest[..end];
+            rest = &rest[end..];
+            value.trim()
+        };
+        out.push((key, value.to_string()));
+    }
+    out
+}
+
+/// `sha256:{hex}` digest of `data`.
+pub fn sha256_digest(data: &[u8]) -> String {
+    let hash = Sha256::digest(data);
+    format!(
+        "sha256:{}",
+        hash.iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_image_ref() {
+        let r = ImageRef::parse("eclipse-temurin:21-jre").unwrap();
+        assert_eq!(r.registry, "docker.io");
+        assert_eq!(r.repository, "library/eclipse-temurin");
+        assert_eq!(r.reference, "21-jre");
+
+        let r = ImageRef::parse("localhost:5000/team/app").unwrap();
+        assert_eq!(r.registry, "localhost:5000");
+        assert_eq!(r.repository, "team/app");
+        assert_eq!(r.reference, "latest");
+        assert_eq!(r.api_url(), "http://localhost:5000/v2/team/app");
+
+        let r = ImageRef::parse("ghcr.io/acme/app@sha256:abc").unwrap();
+        assert_eq!(r.registry, "ghcr.io");
+        assert_eq!(r.tag(), None);
+        assert_eq!(r.to_string(), "ghcr.io/acme/app@sha256:abc");
+
+        assert_eq!(
+            ImageRef::parse("acme/app:1.0").unwrap().api_url(),
+            "https://registry-1.docker.io/v2/acme/app"
+        );
+        assert!(ImageRef::parse("Acme/App").is_err());
+        assert!(ImageRef::parse("app:").is_err());
+    }
+
+    #[test]
+    fn test_parse_challenge() {
+        assert_eq!(
+            parse_challenge(
+                r#"realm="https://auth.docker.io/token",service="registry.docker.io",scope="repository:library/a:pull""#
+            ),
+            vec![
+                (
+                    "realm".to_string(),
+                    "https://auth.docker.io/token".to_string()
+                ),
+                ("service".to_string(), "registry.docker.io".to_string()),
+                ("scope".to_string(), "repository:library/a:pull".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sha256_digest() {
+        assert_eq!(
+            sha256_digest(b""),
+            "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+}
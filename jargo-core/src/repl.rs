@@ -0,0 +1,160 @@
+use anyhow::{bail, Context, Result};
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus};
+
+use crate::env;
+use crate::manifest::JargoToml;
+use crate::toolchain::Toolchain;
+
+/// Startup script holding the `[repl] imports`.
+const STARTUP_FILE: &str = "target/repl-startup.jsh";
+
+/// Start `jshell` on the terminal with `classpath`, `[run] jvm-args` and
+/// `system-properties` for the execution JVM, `[env]`, and the `[repl]
+/// imports` run after jshell's default startup.
+pub fn run_jshell(
+    toolchain: &Toolchain,
+    project_root: &Path,
+    manifest: &JargoToml,
+    classpath: &[PathBuf],
+) -> Result<ExitStatus> {
+    let startup = write_startup(project_root, manifest)?;
+    let args = jshell_args(manifest, classpath, startup.as_deref())?;
+    Command::new(toolchain.tool("jshell"))
+        .args(&args)
+        .envs(env::program_env(project_root, manifest, None)?)
+        .current_dir(project_root)
+        .status()
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                anyhow::anyhow!("jshell not found; it ships with JDK 9 and later")
+            } else {
+                anyhow::Error::from(e).context("failed to run jshell")
+            }
+        })
+}
+
+/// Write the `[repl] imports` as a startup script. `None` without imports.
+fn write_startup(project_root: &Path, manifest: &JargoToml) -> Result<Option<PathBuf>> {
+    let imports = manifest
+        .repl
+        .as_ref()
+        .map(|c| c.imports.as_slice())
+        .unwrap_or(&[]);
+    if imports.is_empty() {
+        return Ok(None);
+    }
+    let mut script = String::new();
+    for import in imports {
+        if !is_import(import) {
+            bail!("invalid [repl] import `{}`", import);
+        }
+        script.push_str(&format!("import {};\n", import));
+    }
+    let path = project_root.join(STARTUP_FILE);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    fs::write(&path, script).with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(Some(path))
+}
+
+/// `com.acme.Widget`, `com.acme.*`, or either with a `static ` prefix.
+fn is_import(import: &str) -> bool {
+    let name = import.strip_prefix("static ").unwrap_or(import);
+    let name = name.strip_suffix(".*").unwrap_or(name);
+    !name.is_empty()
+        && name.split('.').all(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+                && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+        })
+}
+
+/// Arguments for `jshell`.
+fn jshell_args(
+    manifest: &JargoToml,
+    classpath: &[PathBuf],
+    startup: Option<&Path>,
+) -> Result<Vec<OsString>> {
+    let mut args: Vec<OsString> = vec![
+        "--class-path".into(),
+        std::env::join_paths(classpath).context("invalid classpath entry")?,
+    ];
+    for arg in manifest.get_jvm_args() {
+        args.push(format!("-R{}", arg).into());
+    }
+    for (key, value) in manifest.get_system_properties()? {
+        args.push(format!("-R-D{}={}", key, value).into());
+    }
+    if let Some(startup) = startup {
+        // A second --startup replaces the default script unless it is named too
+        args.push("--startup".into());
+        args.push("DEFAULT".into());
+        args.push("--startup".into());
+        args.push(startup.into());
+    }
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(extra: &str) -> JargoToml {
+        toml::from_str(&format!(
+            "[package]\nname = \"demo\"\nversion = \"1.0.0\"\njava = \"21\"\n{}",
+            extra
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_is_import() {
+        assert!(is_import("demo.*"));
+        assert!(is_import("com.acme.Widget"));
+        assert!(is_import("static java.lang.Math.*"));
+        assert!(!is_import("*"));
+        assert!(!is_import("com.acme.Widget;"));
+        assert!(!is_import("com..acme"));
+    }
+
+    #[test]
+    fn test_jshell_args() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let m = manifest(
+            "[run]\njvm-args = [\"-Xmx256m\"]\nsystem-properties = { \"app.mode\" = \"dev\" }\n\n\
+             [repl]\nimports = [\"demo.*\", \"static java.lang.Math.max\"]\n",
+        );
+        let startup = write_startup(temp.path(), &m).unwrap().unwrap();
+        assert_eq!(
+            fs::read_to_string(&startup).unwrap(),
+            "import demo.*;\nimport static java.lang.Math.max;\n"
+        );
+
+        let classpath = [PathBuf::from("target/classes"), PathBuf::from("dep.jar")];
+        let args = jshell_args(&m, &classpath, Some(&startup)).unwrap();
+        let joined = std::env::join_paths(&classpath).unwrap();
+        assert_eq!(
+            args,
+            [
+                OsString::from("--class-path"),
+                joined,
+                "-R-Xmx256m".into(),
+                "-R-Dapp.mode=dev".into(),
+                "--startup".into(),
+                "DEFAULT".into(),
+                "--startup".into(),
+                startup.into_os_string(),
+            ]
+        );
+
+        assert!(write_startup(temp.path(), &manifest("")).unwrap().is_none());
+        assert!(write_startup(temp.path(), &manifest("[repl]\nimports = [\"a b\"]\n")).is_err());
+    }
+}
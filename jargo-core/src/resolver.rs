@@ -1,13 +1,17 @@
-use anyhow::{Context, Result};
-use std::collections::{HashMap, HashSet, VecDeque};
+use anyhow::{bail, Context, Result};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 use crate::cache::{self, MetadataFormat};
 use crate::context::GlobalContext;
+use crate::errors::JargoError;
 use crate::gradle_module;
 use crate::lockfile::{LockFile, LockedDependency};
 use crate::manifest::{Dependency, JargoToml, Scope};
 use crate::pom::{ParsedPom, TransitiveDep, TransitiveScope};
+use crate::workspace::Workspace;
 
 /// The output of dependency resolution: classpath JAR lists and lock file entries.
 pub struct ResolvedDeps {
@@ -15,6 +19,11 @@ pub struct ResolvedDeps {
     pub compile_jars: Vec<PathBuf>,
     /// JARs on the runtime classpath (compile + runtime scope deps).
     pub runtime_jars: Vec<PathBuf>,
+    /// JARs reached only through dev-dependencies. Added to both test classpaths.
+    pub test_jars: Vec<PathBuf>,
+    /// JARs reached only through it-dependencies. Added to both integration
+    /// test classpaths.
+    pub it_jars: Vec<PathBuf>,
     /// Entries written to / read from Jargo.lock.
     pub lock_entries: Vec<LockedDependency>,
 }
@@ -24,70 +33,278 @@ impl ResolvedDeps {
         Self {
             compile_jars: Vec::new(),
             runtime_jars: Vec::new(),
+            test_jars: Vec::new(),
+            it_jars: Vec::new(),
             lock_entries: Vec::new(),
         }
     }
 }
 
+/// Held while reading and writing a lock file.
+static LOCK_FILE: Mutex<()> = Mutex::new(());
+
 /// Resolve dependencies for the project at `project_root`.
 ///
-/// - If `Jargo.lock` exists: uses pinned versions from the lock file,
+/// - If `Jargo.lock` exists and was written for the current dependency
+///   sections of the manifest: uses pinned versions from the lock file,
 ///   fetches any JARs not yet in the local cache, and builds classpaths.
-/// - If `Jargo.lock` is absent: runs BFS resolution from Maven Central,
-///   writes a new `Jargo.lock`, and returns the resulting classpaths.
+/// - If `Jargo.lock` is absent or the manifest changed since: runs BFS
+///   resolution from Maven Central, writes a new `Jargo.lock`, and returns
+///   the resulting classpaths. With `--locked` this is an error instead.
+///
+/// Workspace members use their section of the `Jargo.lock` at the
+/// workspace root.
 ///
 /// Returns empty classpaths immediately when there are no dependencies.
+/// Workspace members the project depends on add their built JARs and
+/// their own dependencies.
+#[tracing::instrument(level = "debug", skip_all)]
 pub fn resolve(
     gctx: &GlobalContext,
     project_root: &Path,
     manifest: &JargoToml,
 ) -> Result<ResolvedDeps> {
-    let direct_deps = manifest.get_dependencies()?;
+    let workspace = Workspace::containing(project_root)?;
+    let mut direct_deps = manifest.get_dependencies()?;
+    let mut member_jars = Vec::new();
+    if let Some(workspace) = &workspace {
+        let member = workspace
+            .member(project_root)
+            .expect("the workspace containing the project");
+        let own = direct_deps.len();
+        for path_dep in workspace.path_dependencies(member)? {
+            let dep = path_dep.member;
+            let dep_manifest = workspace.manifest(dep)?;
+            manifest
+                .package
+                .check_dependency_release(&dep_manifest.package)?;
+            let jar = workspace.jar_path(dep);
+            if !jar.exists() {
+                anyhow::bail!(
+                    "workspace member `{}` has not been built; run `jargo build -p {}`",
+                    dep.name,
+                    dep.name
+                );
+            }
+            member_jars.push((jar, path_dep.compile));
+
+            // The member's own dependencies come along: the ones it exposes on
+            // the compile classpath, the rest at runtime only. Its optional
+            // ones stay behind, as its features are not enabled here
+            let optional = dep_manifest.optional_dependencies();
+            for inherited in dep_manifest.get_dependencies()? {
+                if optional.contains(&format!("{}:{}", inherited.group, inherited.artifact)) {
+                    continue;
+                }
+                let scope =
+                    if path_dep.compile && inherited.expose && inherited.scope == Scope::Compile {
+                        Scope::Compile
+                    } else {
+                        Scope::Runtime
+                    };
+                let existing = direct_deps
+                    .iter()
+                    .position(|d| d.group == inherited.group && d.artifact == inherited.artifact);
+                match existing {
+                    None => direct_deps.push(Dependency {
+                        scope,
+                        expose: false,
+                        ..inherited
+                    }),
+                    Some(i) if i >= own && scope == Scope::Compile => {
+                        direct_deps[i].scope = Scope::Compile
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        direct_deps.sort_by(|a, b| (&a.group, &a.artifact).cmp(&(&b.group, &b.artifact)));
+    }
 
-    if direct_deps.is_empty() {
+    // Workspace members are used as built, from their JARs
+    let mut resolved = resolve_external(
+        gctx,
+        project_root,
+        manifest,
+        direct_deps,
+        workspace.as_ref(),
+    )?;
+    for (jar, compile) in member_jars {
+        if compile {
+            resolved.compile_jars.push(jar.clone());
+        }
+        resolved.runtime_jars.push(jar);
+    }
+    Ok(resolved)
+}
+
+/// Resolve the Maven dependencies of `manifest`, with `direct_deps` in
+/// place of its [dependencies], through the lock file of the project or of
+/// its workspace.
+///
+/// The lock file holds the dependencies of every feature; the classpaths
+/// only those of the features enabled with `--features`.
+fn resolve_external(
+    gctx: &GlobalContext,
+    project_root: &Path,
+    manifest: &JargoToml,
+    direct_deps: Vec<Dependency>,
+    workspace: Option<&Workspace>,
+) -> Result<ResolvedDeps> {
+    let features = manifest.get_features()?;
+    let active = active_features(&gctx.features, &features, workspace.is_some())?;
+    let dev_deps = manifest.get_dev_dependencies()?;
+    let it_deps = manifest.get_it_dependencies()?;
+
+    if direct_deps.is_empty() && dev_deps.is_empty() && it_deps.is_empty() {
         gctx.shell
             .verbose(|sh| sh.print("  [verbose] no dependencies declared"));
         return Ok(ResolvedDeps::empty());
     }
 
-    let lock_path = project_root.join("Jargo.lock");
+    let (lock_path, member) = match workspace {
+        Some(workspace) => (workspace.lock_path(), Some(manifest.package.name.as_str())),
+        None => (project_root.join("Jargo.lock"), None),
+    };
+    // Members of a workspace may resolve in parallel; each rewrites its own
+    // section of the shared lock file
+    let _guard = LOCK_FILE.lock().unwrap_or_else(|e| e.into_inner());
 
+    let hash = manifest_hash(&direct_deps, &dev_deps, &it_deps, &features);
+    let mut lock = LockFile::default();
     if lock_path.exists() {
-        let lock = LockFile::read(&lock_path)?;
-        if lock_is_fresh(&direct_deps, &lock) {
+        lock = LockFile::read(&lock_path)?;
+        let entries = lock.entries(member);
+        let fresh = match lock.manifest_hash(member) {
+            Some(locked) => locked == hash,
+            // Written before lock files recorded the hash
+            None => {
+                let all_direct: Vec<Dependency> = direct_deps
+                    .iter()
+                    .chain(&dev_deps)
+                    .chain(&it_deps)
+                    .cloned()
+                    .collect();
+                lock_is_fresh(&all_direct, entries)
+            }
+        };
+        if fresh {
             gctx.shell.verbose(|sh| {
                 sh.print(format!(
                     "  [verbose] lock file is up to date: {}",
                     lock_path.display()
                 ))
             });
-            return resolve_from_lock(gctx, &lock);
+            return resolve_from_lock(gctx, &enabled_entries(entries, &active));
         }
-        gctx.shell
-            .verbose(|sh| sh.print("  [verbose] lock file is out of date, re-resolving"));
+        gctx.shell.verbose(|sh| {
+            sh.print("  [verbose] dependencies changed since Jargo.lock was written, re-resolving")
+        });
+    }
+    if gctx.locked {
+        return Err(JargoError::LockOutdated(lock_path.display().to_string()).into());
     }
 
     gctx.shell.status("Resolving", "dependencies");
-    let resolved = resolve_fresh(gctx, &direct_deps)?;
+    let resolved = resolve_fresh(gctx, &direct_deps, &dev_deps, &it_deps, &features)?;
 
-    let lock = LockFile {
-        dependency: resolved.lock_entries.clone(),
-    };
+    lock.set_entries(member, resolved.lock_entries.clone());
+    lock.set_manifest_hash(member, hash);
     gctx.shell
         .verbose(|sh| sh.print("  [verbose] writing Jargo.lock"));
     lock.write(&lock_path)
         .context("failed to write Jargo.lock")?;
     gctx.shell.status("Locking", "dependencies");
 
-    Ok(resolved)
+    if features.is_empty() {
+        return Ok(resolved);
+    }
+    resolve_from_lock(gctx, &enabled_entries(&resolved.lock_entries, &active))
+}
+
+/// The `requested` features `features` declares. Outside a workspace, a
+/// feature the package does not declare is an error; members leave out the
+/// features of other members.
+fn active_features(
+    requested: &[String],
+    features: &BTreeMap<String, BTreeSet<String>>,
+    in_workspace: bool,
+) -> Result<BTreeSet<String>> {
+    let mut active = BTreeSet::new();
+    for name in requested {
+        if features.contains_key(name) {
+            active.insert(name.clone());
+        } else if !in_workspace {
+            let declared: Vec<&str> = features.keys().map(String::as_str).collect();
+            if declared.is_empty() {
+                bail!("unknown feature `{}`: Jargo.toml has no [features]", name);
+            }
+            bail!(
+                "unknown feature `{}`; [features] has: {}",
+                name,
+                declared.join(", ")
+            );
+        }
+    }
+    Ok(active)
+}
+
+/// The lock entries a build with the `active` features uses: the ones
+/// without features, and the ones of an active feature.
+fn enabled_entries(
+    entries: &[LockedDependency],
+    active: &BTreeSet<String>,
+) -> Vec<LockedDependency> {
+    entries
+        .iter()
+        .filter(|entry| {
+            entry.features.is_empty() || entry.features.iter().any(|f| active.contains(f))
+        })
+        .cloned()
+        .collect()
+}
+
+/// Hash of the declared dependencies (workspace members' inherited ones
+/// included) and `[features]` that a lock file is resolved from. Any change
+/// to a coordinate, version, scope, or `expose`, an added or removed entry,
+/// or a changed feature, changes it.
+fn manifest_hash(
+    direct_deps: &[Dependency],
+    dev_deps: &[Dependency],
+    it_deps: &[Dependency],
+    features: &BTreeMap<String, BTreeSet<String>>,
+) -> String {
+    let mut hasher = Sha256::new();
+    for (section, deps) in [
+        ("dependencies", direct_deps),
+        ("dev-dependencies", dev_deps),
+        ("it-dependencies", it_deps),
+    ] {
+        hasher.update(format!("[{}]\n", section));
+        for dep in deps {
+            hasher.update(format!(
+                "{}:{}:{} {:?} {}\n",
+                dep.group, dep.artifact, dep.version, dep.scope, dep.expose
+            ));
+        }
+    }
+    // Left out without features, keeping the hashes of earlier lock files
+    if !features.is_empty() {
+        hasher.update("[features]\n");
+        for (name, deps) in features {
+            let deps: Vec<&str> = deps.iter().map(String::as_str).collect();
+            hasher.update(format!("{} = {}\n", name, deps.join(",")));
+        }
+    }
+    format!("{:x}", hasher.finalize())
 }
 
 /// Returns true when every direct dep in the manifest has an entry in the lock
-/// file with the exact same version. If any dep is missing or has changed
-/// version, the lock is considered stale and must be regenerated.
-fn lock_is_fresh(direct_deps: &[Dependency], lock: &LockFile) -> bool {
+/// file with the exact same version. Used for lock files without a manifest
+/// hash; a dep that is missing or has changed version makes them stale.
+fn lock_is_fresh(direct_deps: &[Dependency], entries: &[LockedDependency]) -> bool {
     direct_deps.iter().all(|dep| {
-        lock.dependency.iter().any(|entry| {
+        entries.iter().any(|entry| {
             entry.group == dep.group
                 && entry.artifact == dep.artifact
                 && entry.version == dep.version
@@ -95,27 +312,81 @@ fn lock_is_fresh(direct_deps: &[Dependency], lock: &LockFile) -> bool {
     })
 }
 
+/// Return one `-javaagent:` flag per `[run] agents` entry, in declaration order.
+pub fn agent_jvm_args(gctx: &GlobalContext, manifest: &JargoToml) -> Result<Vec<String>> {
+    Ok(fetch_agents(gctx, manifest)?
+        .iter()
+        .map(|jar| format!("-javaagent:{}", jar.display()))
+        .collect())
+}
+
+/// Fetch the `[run] agents` JARs in declaration order. Agents are
+/// self-contained, so no transitive resolution happens.
+pub fn fetch_agents(gctx: &GlobalContext, manifest: &JargoToml) -> Result<Vec<PathBuf>> {
+    let mut jars = Vec::new();
+    for agent in manifest.get_agents()? {
+        gctx.shell.verbose(|sh| {
+            sh.print(format!(
+                "  [verbose] fetching agent {}:{}:{}",
+                agent.group, agent.artifact, agent.version
+            ))
+        });
+        let (jar_path, _sha256) =
+            cache::fetch_jar(gctx, &agent.group, &agent.artifact, &agent.version).with_context(
+                || {
+                    format!(
+                        "failed to fetch agent {}:{}:{}",
+                        agent.group, agent.artifact, agent.version
+                    )
+                },
+            )?;
+        jars.push(jar_path);
+    }
+    Ok(jars)
+}
+
+/// Resolve a tool such as Checkstyle from its `(group, artifact, version)`
+/// coordinates with their transitive dependencies, returning its runtime
+/// classpath. Tools are not locked: the versions are pinned by the caller.
+pub fn resolve_tool(
+    gctx: &GlobalContext,
+    coordinates: &[(&str, &str, &str)],
+) -> Result<Vec<PathBuf>> {
+    let deps: Vec<Dependency> = coordinates
+        .iter()
+        .map(|&(group, artifact, version)| Dependency {
+            group: group.to_string(),
+            artifact: artifact.to_string(),
+            version: version.to_string(),
+            scope: Scope::Compile,
+            expose: false,
+        })
+        .collect();
+    let (group, artifact, version) = coordinates[0];
+    let resolved = resolve_fresh(gctx, &deps, &[], &[], &BTreeMap::new())
+        .with_context(|| format!("failed to resolve {}:{}:{}", group, artifact, version))?;
+    Ok(resolved.runtime_jars)
+}
+
 // --- Lock-file path ---
 
 /// Build classpaths from an existing `Jargo.lock` without re-resolving.
 /// Fetches JARs from the local cache (downloading if absent).
-fn resolve_from_lock(gctx: &GlobalContext, lock: &LockFile) -> Result<ResolvedDeps> {
+fn resolve_from_lock(gctx: &GlobalContext, entries: &[LockedDependency]) -> Result<ResolvedDeps> {
     gctx.shell.verbose(|sh| {
         sh.print(format!(
             "  [verbose] lock file has {} entr{}",
-            lock.dependency.len(),
-            if lock.dependency.len() == 1 {
-                "y"
-            } else {
-                "ies"
-            }
+            entries.len(),
+            if entries.len() == 1 { "y" } else { "ies" }
         ))
     });
 
     let mut compile_jars = Vec::new();
     let mut runtime_jars = Vec::new();
+    let mut test_jars = Vec::new();
+    let mut it_jars = Vec::new();
 
-    for entry in &lock.dependency {
+    for entry in entries {
         gctx.shell.verbose(|sh| {
             sh.print(format!(
                 "  [verbose] fetching {}:{}:{} ({})",
@@ -137,6 +408,8 @@ fn resolve_from_lock(gctx: &GlobalContext, lock: &LockFile) -> Result<ResolvedDe
                 compile_jars.push(jar_path.clone());
                 runtime_jars.push(jar_path);
             }
+            "test" => test_jars.push(jar_path),
+            "it" => it_jars.push(jar_path),
             _ => {
                 // "runtime" or any unknown scope → runtime only
                 runtime_jars.push(jar_path);
@@ -155,7 +428,9 @@ fn resolve_from_lock(gctx: &GlobalContext, lock: &LockFile) -> Result<ResolvedDe
     Ok(ResolvedDeps {
         compile_jars,
         runtime_jars,
-        lock_entries: lock.dependency.clone(),
+        test_jars,
+        it_jars,
+        lock_entries: entries.to_vec(),
     })
 }
 
@@ -173,18 +448,167 @@ fn resolve_from_lock(gctx: &GlobalContext, lock: &LockFile) -> Result<ResolvedDe
 /// 5. For each transitive dep, apply scope mediation; if it's new or its
 ///    version is higher, update the resolved map and enqueue for fetching.
 /// 6. After BFS, fetch all JARs and assemble classpaths and lock entries.
-fn resolve_fresh(gctx: &GlobalContext, direct_deps: &[Dependency]) -> Result<ResolvedDeps> {
+///
+/// Dev-dependencies are seeded with `Test` scope only after the main graph is
+/// complete, so an artifact shared with the main graph keeps its main scope.
+/// It-dependencies follow last with `IntegrationTest` scope.
+///
+/// `direct_deps` include the optional dependencies of every feature in
+/// `features`; lock entries only they reach name the features reaching them.
+fn resolve_fresh(
+    gctx: &GlobalContext,
+    direct_deps: &[Dependency],
+    dev_deps: &[Dependency],
+    it_deps: &[Dependency],
+    features: &BTreeMap<String, BTreeSet<String>>,
+) -> Result<ResolvedDeps> {
     // (group, artifact) → (highest_version, effective_scope)
     let mut resolved: HashMap<(String, String), (String, TransitiveScope)> = HashMap::new();
     // Guards against fetching the same (group, artifact, version) twice.
     let mut fetched: HashSet<(String, String, String)> = HashSet::new();
+
+    let main_seeds = direct_deps
+        .iter()
+        .map(|dep| (dep, from_manifest_scope(&dep.scope)));
+    resolve_graph(gctx, &mut resolved, &mut fetched, main_seeds)?;
+
+    let dev_seeds = dev_deps.iter().map(|dep| (dep, TransitiveScope::Test));
+    resolve_graph(gctx, &mut resolved, &mut fetched, dev_seeds)?;
+
+    let it_seeds = it_deps
+        .iter()
+        .map(|dep| (dep, TransitiveScope::IntegrationTest));
+    resolve_graph(gctx, &mut resolved, &mut fetched, it_seeds)?;
+
+    let mut feature_tags = feature_tags(gctx, direct_deps, dev_deps, it_deps, features)?;
+
+    // Collect, sort for determinism, fetch JARs, build output.
+    let mut entries: Vec<_> = resolved.into_iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut compile_jars = Vec::new();
+    let mut runtime_jars = Vec::new();
+    let mut test_jars = Vec::new();
+    let mut it_jars = Vec::new();
+    let mut lock_entries = Vec::new();
+
+    gctx.shell.verbose(|sh| {
+        sh.print(format!(
+            "  [verbose] BFS complete: {} dep(s) resolved",
+            entries.len()
+        ))
+    });
+
+    for ((group, artifact), (version, scope)) in entries {
+        gctx.shell.verbose(|sh| {
+            sh.print(format!(
+                "  [verbose] fetching JAR: {}:{}:{}",
+                group, artifact, version
+            ))
+        });
+        let (jar_path, sha256) =
+            cache::fetch_jar(gctx, &group, &artifact, &version).with_context(|| {
+                format!("failed to fetch JAR for {}:{}:{}", group, artifact, version)
+            })?;
+
+        match scope {
+            TransitiveScope::Compile => {
+                compile_jars.push(jar_path.clone());
+                runtime_jars.push(jar_path);
+            }
+            TransitiveScope::Runtime => {
+                runtime_jars.push(jar_path);
+            }
+            TransitiveScope::Test => {
+                test_jars.push(jar_path);
+            }
+            TransitiveScope::IntegrationTest => {
+                it_jars.push(jar_path);
+            }
+        }
+
+        let features = feature_tags
+            .remove(&(group.clone(), artifact.clone()))
+            .unwrap_or_default();
+        lock_entries.push(LockedDependency {
+            group,
+            artifact,
+            version,
+            scope: scope_str(scope),
+            features,
+            sha256,
+        });
+    }
+
+    Ok(ResolvedDeps {
+        compile_jars,
+        runtime_jars,
+        test_jars,
+        it_jars,
+        lock_entries,
+    })
+}
+
+/// For each artifact only optional dependencies reach, the features (in
+/// name order) whose dependencies reach it. Each feature's graph is walked
+/// on its own, next to the dependencies every build has.
+fn feature_tags(
+    gctx: &GlobalContext,
+    direct_deps: &[Dependency],
+    dev_deps: &[Dependency],
+    it_deps: &[Dependency],
+    features: &BTreeMap<String, BTreeSet<String>>,
+) -> Result<HashMap<(String, String), Vec<String>>> {
+    let mut tags: HashMap<(String, String), Vec<String>> = HashMap::new();
+    if features.is_empty() {
+        return Ok(tags);
+    }
+    let coordinate = |dep: &Dependency| format!("{}:{}", dep.group, dep.artifact);
+    let optional: HashSet<String> = features.values().flatten().cloned().collect();
+    let reached = |enabled: &BTreeSet<String>| -> Result<HashSet<(String, String)>> {
+        let mut resolved = HashMap::new();
+        let mut fetched = HashSet::new();
+        let main_seeds = direct_deps
+            .iter()
+            .filter(|dep| {
+                let coordinate = coordinate(dep);
+                !optional.contains(&coordinate) || enabled.contains(&coordinate)
+            })
+            .map(|dep| (dep, from_manifest_scope(&dep.scope)));
+        resolve_graph(gctx, &mut resolved, &mut fetched, main_seeds)?;
+        let dev_seeds = dev_deps.iter().map(|dep| (dep, TransitiveScope::Test));
+        resolve_graph(gctx, &mut resolved, &mut fetched, dev_seeds)?;
+        let it_seeds = it_deps
+            .iter()
+            .map(|dep| (dep, TransitiveScope::IntegrationTest));
+        resolve_graph(gctx, &mut resolved, &mut fetched, it_seeds)?;
+        Ok(resolved.into_keys().collect())
+    };
+
+    let always = reached(&BTreeSet::new())?;
+    for (name, enabled) in features {
+        for key in reached(enabled)? {
+            if !always.contains(&key) {
+                tags.entry(key).or_default().push(name.clone());
+            }
+        }
+    }
+    Ok(tags)
+}
+
+/// Run the BFS from `seeds`, updating `resolved` in place.
+fn resolve_graph<'a>(
+    gctx: &GlobalContext,
+    resolved: &mut HashMap<(String, String), (String, TransitiveScope)>,
+    fetched: &mut HashSet<(String, String, String)>,
+    seeds: impl Iterator<Item = (&'a Dependency, TransitiveScope)>,
+) -> Result<()> {
     let mut queue: VecDeque<(String, String, String, TransitiveScope)> = VecDeque::new();
 
     // Seed from direct dependencies.
-    for dep in direct_deps {
-        let scope = from_manifest_scope(&dep.scope);
+    for (dep, scope) in seeds {
         let key = (dep.group.clone(), dep.artifact.clone());
-        update_resolved(&mut resolved, key, dep.version.clone(), scope);
+        update_resolved(resolved, key, dep.version.clone(), scope);
         queue.push_back((
             dep.group.clone(),
             dep.artifact.clone(),
@@ -237,7 +661,7 @@ fn resolve_fresh(gctx: &GlobalContext, direct_deps: &[Dependency]) -> Result<Res
 
             let trans_key = (trans.group.clone(), trans.artifact.clone());
             let needs_fetch =
-                update_resolved(&mut resolved, trans_key, trans.version.clone(), child_scope);
+                update_resolved(resolved, trans_key, trans.version.clone(), child_scope);
 
             if needs_fetch {
                 queue.push_back((
@@ -250,57 +674,7 @@ fn resolve_fresh(gctx: &GlobalContext, direct_deps: &[Dependency]) -> Result<Res
         }
     }
 
-    // Collect, sort for determinism, fetch JARs, build output.
-    let mut entries: Vec<_> = resolved.into_iter().collect();
-    entries.sort_by(|a, b| a.0.cmp(&b.0));
-
-    let mut compile_jars = Vec::new();
-    let mut runtime_jars = Vec::new();
-    let mut lock_entries = Vec::new();
-
-    gctx.shell.verbose(|sh| {
-        sh.print(format!(
-            "  [verbose] BFS complete: {} dep(s) resolved",
-            entries.len()
-        ))
-    });
-
-    for ((group, artifact), (version, scope)) in entries {
-        gctx.shell.verbose(|sh| {
-            sh.print(format!(
-                "  [verbose] fetching JAR: {}:{}:{}",
-                group, artifact, version
-            ))
-        });
-        let (jar_path, sha256) =
-            cache::fetch_jar(gctx, &group, &artifact, &version).with_context(|| {
-                format!("failed to fetch JAR for {}:{}:{}", group, artifact, version)
-            })?;
-
-        match scope {
-            TransitiveScope::Compile => {
-                compile_jars.push(jar_path.clone());
-                runtime_jars.push(jar_path);
-            }
-            TransitiveScope::Runtime => {
-                runtime_jars.push(jar_path);
-            }
-        }
-
-        lock_entries.push(LockedDependency {
-            group,
-            artifact,
-            version,
-            scope: scope_str(scope),
-            sha256,
-        });
-    }
-
-    Ok(ResolvedDeps {
-        compile_jars,
-        runtime_jars,
-        lock_entries,
-    })
+    Ok(())
 }
 
 // --- Phase 2 POM resolution ---
@@ -478,7 +852,7 @@ fn build_effective_pom(gctx: &GlobalContext, pom: &ParsedPom, depth: u8) -> Resu
 /// Applies substitution in a loop to handle chained references (e.g., a property
 /// value that itself contains `${other}`). Stops after 20 iterations to guard
 /// against circular references.
-fn substitute_props(s: &str, props: &HashMap<String, String>) -> String {
+pub(crate) fn substitute_props(s: &str, props: &HashMap<String, String>) -> String {
     let mut result = s.to_string();
     for _ in 0..20 {
         match result.find("${") {
@@ -551,20 +925,28 @@ fn update_resolved(
 /// | runtime     | compile     | runtime         |
 /// | runtime     | runtime     | runtime         |
 ///
+/// | test        | any         | test            |
+///
 /// `provided` / `test` transitives were already filtered by the POM parser.
 fn mediate_scope(parent: TransitiveScope, child: &TransitiveScope) -> TransitiveScope {
     match (parent, child) {
+        (TransitiveScope::Test, _) => TransitiveScope::Test,
+        (TransitiveScope::IntegrationTest, _) => TransitiveScope::IntegrationTest,
         (TransitiveScope::Compile, TransitiveScope::Compile) => TransitiveScope::Compile,
         _ => TransitiveScope::Runtime,
     }
 }
 
-/// Return the higher-priority scope (Compile > Runtime).
+/// Return the higher-priority scope (Compile > Runtime > Test).
 fn higher_scope(a: TransitiveScope, b: TransitiveScope) -> TransitiveScope {
     if a == TransitiveScope::Compile || b == TransitiveScope::Compile {
         TransitiveScope::Compile
-    } else {
+    } else if a == TransitiveScope::Runtime || b == TransitiveScope::Runtime {
         TransitiveScope::Runtime
+    } else if a == TransitiveScope::Test || b == TransitiveScope::Test {
+        TransitiveScope::Test
+    } else {
+        TransitiveScope::IntegrationTest
     }
 }
 
@@ -579,6 +961,8 @@ fn scope_str(scope: TransitiveScope) -> String {
     match scope {
         TransitiveScope::Compile => "compile".to_string(),
         TransitiveScope::Runtime => "runtime".to_string(),
+        TransitiveScope::Test => "test".to_string(),
+        TransitiveScope::IntegrationTest => "it".to_string(),
     }
 }
 
@@ -686,6 +1070,9 @@ mod tests {
         assert_eq!(mediate_scope(Compile, &Runtime), Runtime);
         assert_eq!(mediate_scope(Runtime, &Compile), Runtime);
         assert_eq!(mediate_scope(Runtime, &Runtime), Runtime);
+        assert_eq!(mediate_scope(Test, &Compile), Test);
+        assert_eq!(mediate_scope(Test, &Runtime), Test);
+        assert_eq!(mediate_scope(IntegrationTest, &Compile), IntegrationTest);
     }
 
     #[test]
@@ -695,6 +1082,15 @@ mod tests {
         assert_eq!(higher_scope(Runtime, Compile), Compile);
         assert_eq!(higher_scope(Compile, Compile), Compile);
         assert_eq!(higher_scope(Runtime, Runtime), Runtime);
+        assert_eq!(higher_scope(Test, Runtime), Runtime);
+        assert_eq!(higher_scope(Compile, Test), Compile);
+        assert_eq!(higher_scope(Test, Test), Test);
+        assert_eq!(higher_scope(IntegrationTest, Test), Test);
+        assert_eq!(higher_scope(Runtime, IntegrationTest), Runtime);
+        assert_eq!(
+            higher_scope(IntegrationTest, IntegrationTest),
+            IntegrationTest
+        );
     }
 
     // --- update_resolved ---
@@ -793,6 +1189,7 @@ mod tests {
             artifact: artifact.to_string(),
             version: version.to_string(),
             scope: "compile".to_string(),
+            features: Vec::new(),
             sha256: "abc123".to_string(),
         }
     }
@@ -802,8 +1199,9 @@ mod tests {
         let deps = vec![make_dep("com.example", "foo", "1.0.0")];
         let lock = LockFile {
             dependency: vec![make_lock_entry("com.example", "foo", "1.0.0")],
+            ..LockFile::default()
         };
-        assert!(lock_is_fresh(&deps, &lock));
+        assert!(lock_is_fresh(&deps, &lock.dependency));
     }
 
     #[test]
@@ -814,8 +1212,9 @@ mod tests {
         ];
         let lock = LockFile {
             dependency: vec![make_lock_entry("com.example", "foo", "1.0.0")],
+            ..LockFile::default()
         };
-        assert!(!lock_is_fresh(&deps, &lock));
+        assert!(!lock_is_fresh(&deps, &lock.dependency));
     }
 
     #[test]
@@ -823,8 +1222,9 @@ mod tests {
         let deps = vec![make_dep("com.example", "foo", "2.0.0")];
         let lock = LockFile {
             dependency: vec![make_lock_entry("com.example", "foo", "1.0.0")],
+            ..LockFile::default()
         };
-        assert!(!lock_is_fresh(&deps, &lock));
+        assert!(!lock_is_fresh(&deps, &lock.dependency));
     }
 
     #[test]
@@ -836,16 +1236,80 @@ mod tests {
                 make_lock_entry("com.example", "foo", "1.0.0"),
                 make_lock_entry("org.other", "transitive", "3.0.0"),
             ],
+            ..LockFile::default()
         };
-        assert!(lock_is_fresh(&deps, &lock));
+        assert!(lock_is_fresh(&deps, &lock.dependency));
     }
 
     #[test]
     fn test_lock_is_fresh_empty_deps() {
         let lock = LockFile {
             dependency: vec![make_lock_entry("com.example", "foo", "1.0.0")],
+            ..LockFile::default()
+        };
+        assert!(lock_is_fresh(&[], &lock.dependency));
+    }
+
+    #[test]
+    fn test_manifest_hash() {
+        let deps = vec![make_dep("com.example", "foo", "1.0.0")];
+        let none = BTreeMap::new();
+        let hash = manifest_hash(&deps, &[], &[], &none);
+        assert_eq!(hash, manifest_hash(&deps, &[], &[], &none));
+        assert_eq!(hash.len(), 64);
+
+        let mut runtime = deps.clone();
+        runtime[0].scope = Scope::Runtime;
+        assert_ne!(hash, manifest_hash(&runtime, &[], &[], &none));
+        // The same dependency in another section is a different manifest
+        assert_ne!(hash, manifest_hash(&[], &deps, &[], &none));
+        assert_ne!(hash, manifest_hash(&[], &[], &[], &none));
+
+        // A feature, or a change to one
+        let features = BTreeMap::from([(
+            "foo".to_string(),
+            BTreeSet::from(["com.example:foo".to_string()]),
+        )]);
+        let with_feature = manifest_hash(&deps, &[], &[], &features);
+        assert_ne!(hash, with_feature);
+        let renamed = BTreeMap::from([("bar".to_string(), features["foo"].clone())]);
+        assert_ne!(with_feature, manifest_hash(&deps, &[], &[], &renamed));
+    }
+
+    #[test]
+    fn test_active_features_and_enabled_entries() {
+        let features = BTreeMap::from([
+            ("metrics".to_string(), BTreeSet::new()),
+            ("postgres".to_string(), BTreeSet::new()),
+        ]);
+        let requested = vec!["postgres".to_string(), "tracing".to_string()];
+        let err = active_features(&requested, &features, false).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("unknown feature `tracing`; [features] has: metrics, postgres"));
+        let err = active_features(&requested, &BTreeMap::new(), false).unwrap_err();
+        assert!(err.to_string().contains("Jargo.toml has no [features]"));
+        // A workspace member skips the features of other members
+        let active = active_features(&requested, &features, true).unwrap();
+        assert_eq!(active, BTreeSet::from(["postgres".to_string()]));
+
+        let mut driver = make_lock_entry("org.postgresql", "postgresql", "42.7.1");
+        driver.features = vec!["postgres".to_string()];
+        let mut gauge = make_lock_entry("io.micrometer", "micrometer-core", "1.12.2");
+        gauge.features = vec!["metrics".to_string()];
+        let entries = vec![
+            make_lock_entry("com.example", "foo", "1.0.0"),
+            driver,
+            gauge,
+        ];
+        let artifacts = |active: &BTreeSet<String>| -> Vec<String> {
+            enabled_entries(&entries, active)
+                .into_iter()
+                .map(|e| e.artifact)
+                .collect()
         };
-        assert!(lock_is_fresh(&[], &lock));
+        assert_eq!(artifacts(&BTreeSet::new()), vec!["foo"]);
+        assert_eq!(artifacts(&active), vec!["foo", "postgresql"]);
     }
 
     // --- substitute_props ---
@@ -910,6 +1374,9 @@ mod tests {
             cwd: tmp.path().to_path_buf(),
             jargo_home: tmp.path().join(".jargo"),
             shell: crate::shell::Shell::new(crate::shell::Verbosity::Normal),
+            config: Default::default(),
+            locked: false,
+            features: Vec::new(),
         }
     }
 
@@ -1049,4 +1516,56 @@ mod tests {
         assert_eq!(deps.len(), 1);
         assert_eq!(deps[0].artifact, "has-version");
     }
+
+    #[test]
+    fn test_resolve_member_dependencies() {
+        use std::fs;
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::write(
+            root.join("Jargo.toml"),
+            "[workspace]\nmembers = [\"app\", \"core\", \"util\"]\n",
+        )
+        .unwrap();
+        let package = |name: &str, java: &str, deps: &str| {
+            fs::create_dir_all(root.join(name)).unwrap();
+            fs::create_dir_all(root.join("target").join(name)).unwrap();
+            fs::write(root.join(format!("target/{}/{}.jar", name, name)), "").unwrap();
+            fs::write(
+                root.join(name).join("Jargo.toml"),
+                format!(
+                    "[package]\nname = \"{}\"\nversion = \"0.1.0\"\njava = \"{}\"\n\n[dependencies]\n{}",
+                    name, java, deps
+                ),
+            )
+            .unwrap();
+        };
+        package("app", "21", "\"core\" = { workspace = true }\n");
+        package(
+            "core",
+            "17",
+            "\"util\" = { workspace = true, scope = \"runtime\" }\n",
+        );
+        package("util", "17", "");
+
+        let gctx = make_test_gctx(&tmp);
+        let app = root.join("app");
+        let manifest = JargoToml::from_file(&app.join("Jargo.toml")).unwrap();
+        let resolved = resolve(&gctx, &app, &manifest).unwrap();
+        let names = |jars: &[PathBuf]| -> Vec<String> {
+            jars.iter()
+                .map(|j| j.file_name().unwrap().to_string_lossy().into_owned())
+                .collect()
+        };
+        assert_eq!(names(&resolved.compile_jars), ["core.jar"]);
+        assert_eq!(names(&resolved.runtime_jars), ["core.jar", "util.jar"]);
+
+        package("core", "25", "");
+        let err = match resolve(&gctx, &app, &manifest) {
+            Ok(_) => panic!("a java 21 member depends on a java 25 member"),
+            Err(e) => e.to_string(),
+        };
+        assert!(err.contains("`app` (java 21) cannot depend on `core` (java 25)"));
+    }
 }
@@ -0,0 +1,263 @@
+use anyhow::{bail, Result};
+use std::collections::{BTreeMap, HashSet};
+
+use crate::bytecode;
+
+/// Bytecode-level package relocation for `[shade.relocate]` rules.
+///
+/// A rule `"com.google.common" = "myapp.shaded.guava"` moves every class and
+/// resource under `com/google/common/` and rewrites references to them: class
+/// names, descriptors, and generic signatures in the constant pool, plus
+/// string constants naming a class (`"com.google.common.Foo"`), as used by
+/// reflection and `ServiceLoader`.
+pub struct Relocator {
+    /// `(from, to)` in internal form with a trailing `/`, longest `from` first
+    /// so nested packages win over their parents.
+    rules: Vec<(String, String)>,
+}
+
+impl Relocator {
+    pub fn new(relocate: &BTreeMap<String, String>) -> Result<Self> {
+        let mut rules = Vec::new();
+        for (from, to) in relocate {
+            for package in [from, to] {
+                if !is_package_name(package) {
+                    bail!(
+                        "invalid [shade.relocate] package `{}`: expected a dotted Java package name",
+                        package
+                    );
+                }
+            }
+            rules.push((
+                format!("{}/", from.replace('.', "/")),
+                format!("{}/", to.replace('.', "/")),
+            ));
+        }
+        rules.sort_by_key(|(from, _)| std::cmp::Reverse(from.len()));
+        Ok(Self { rules })
+    }
+
+    /// Relocated JAR entry name. Multi-release entries keep their
+    /// `META-INF/versions/{N}/` prefix.
+    pub fn relocate_path(&self, name: &str) -> String {
+        let (prefix, rest) = match name.strip_prefix("META-INF/versions/") {
+            Some(versioned) => match versioned.find('/') {
+                Some(slash) => name.split_at("META-INF/versions/".len() + slash + 1),
+                None => ("", name),
+            },
+            None => ("", name),
+        };
+        match self.rule_at_start(rest.as_bytes(), b'/') {
+            Some((from, to)) => format!("{}{}{}", prefix, to, &rest[from.len()..]),
+            None => name.to_string(),
+        }
+    }
+
+    /// Relocated binary class name in dotted form (`com.google.common.Foo`),
+    /// as found in `META-INF/services/` files.
+    pub fn relocate_class_name(&self, name: &str) -> String {
+        String::from_utf8(self.relocate_dotted(name.as_bytes()))
+            .unwrap_or_else(|_| name.to_string())
+    }
+
+    /// Rewrite a `.class` file. Only the constant pool changes; everything
+    /// after it is copied as is.
+    pub fn relocate_class(&self, class: &[u8]) -> Result<Vec<u8>> {
+        if self.rules.is_empty() || bytecode::class_major_version(class).is_none() {
+            return Ok(class.to_vec());
+        }
+        let (constants, pos) = bytecode::constant_pool(class)?;
+
+        let mut strings = HashSet::new();
+        for &(_, tag, start, _) in &constants {
+            if tag == 8 {
+                strings.insert(bytecode::u16_at(class, start + 1)?);
+            }
+        }
+
+        let mut out = class[..10].to_vec();
+        for &(index, tag, start, end) in &constants {
+            if tag != 1 {
+                out.extend_from_slice(&class[start..end]);
+                continue;
+            }
+            let mut value = self.relocate_internal(&class[start + 3..end]);
+            if strings.contains(&index) {
+                value = self.relocate_dotted(&value);
+            }
+            let Ok(len) = u16::try_from(value.len()) else {
+                bail!("relocated constant exceeds 65535 bytes");
+            };
+            out.push(1);
+            out.extend_from_slice(&len.to_be_bytes());
+            out.extend_from_slice(&value);
+        }
+        out.extend_from_slice(&class[pos..]);
+        Ok(out)
+    }
+
+    /// Rewrite internal names wherever one can start in a constant: at the
+    /// beginning (class names) or after an `L` that opens a descriptor type.
+    fn relocate_internal(&self, value: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(value.len());
+        let mut i = 0;
+        while i < value.len() {
+            let starts_name = i == 0
+                || (value[i - 1] == b'L' && (i == 1 || b"[(;<):^+-".contains(&value[i - 2])));
+            if starts_name {
+                if let Some((from, to)) = self.rule_at_start(&value[i..], b'/') {
+                    out.extend_from_slice(to.as_bytes());
+                    i += from.len();
+                    continue;
+                }
+            }
+            out.push(value[i]);
+            i += 1;
+        }
+        out
+    }
+
+    /// Rewrite a dotted class name (`com.google.common.Foo`).
+    fn relocate_dotted(&self, value: &[u8]) -> Vec<u8> {
+        match self.rule_at_start(value, b'.') {
+            Some((from, to)) => {
+                let mut out = to.replace('/', ".").into_bytes();
+                out.extend_from_slice(&value[from.len()..]);
+                out
+            }
+            None => value.to_vec(),
+        }
+    }
+
+    /// The rule whose package prefix `value` starts with, using `separator`
+    /// between package segments.
+    fn rule_at_start(&self, value: &[u8], separator: u8) -> Option<(&str, &str)> {
+        self.rules.iter().find_map(|(from, to)| {
+            let matches = value.len() >= from.len()
+                && from
+                    .bytes()
+                    .zip(value)
+                    .all(|(f, &v)| if f == b'/' { v == separator } else { f == v });
+            matches.then_some((from.as_str(), to.as_str()))
+        })
+    }
+}
+
+fn is_package_name(name: &str) -> bool {
+    name.split('.').all(|segment| {
+        let mut chars = segment.chars();
+        chars
+            .next()
+            .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+            && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relocator() -> Relocator {
+        Relocator::new(&BTreeMap::from([
+            (
+                "com.google.common".to_string(),
+                "myapp.shaded.guava".to_string(),
+            ),
+            (
+                "com.google.common.base".to_string(),
+                "myapp.shaded.base".to_string(),
+            ),
+        ]))
+        .unwrap()
+    }
+
+    fn utf8(out: &mut Vec<u8>, s: &str) {
+        out.push(1);
+        out.extend_from_slice(&(s.len() as u16).to_be_bytes());
+        out.extend_from_slice(s.as_bytes());
+    }
+
+    /// Constant pool strings of a class written by `relocate_class`.
+    fn utf8_constants(class: &[u8]) -> Vec<String> {
+        let mut values = Vec::new();
+        let mut pos = 10;
+        let count = u16::from_be_bytes([class[8], class[9]]);
+        let mut index = 1;
+        while index < count {
+            let tag = class[pos];
+            let len = match tag {
+                1 => 3 + usize::from(u16::from_be_bytes([class[pos + 1], class[pos + 2]])),
+                7 | 8 => 3,
+                5 => 9,
+                _ => panic!("unexpected tag {}", tag),
+            };
+            if tag == 1 {
+                values.push(String::from_utf8(class[pos + 3..pos + len].to_vec()).unwrap());
+            }
+            pos += len;
+            index += if tag == 5 { 2 } else { 1 };
+        }
+        values
+    }
+
+    #[test]
+    fn test_relocate_path() {
+        let r = relocator();
+        assert_eq!(
+            r.relocate_path("com/google/common/collect/Lists.class"),
+            "myapp/shaded/guava/collect/Lists.class"
+        );
+        assert_eq!(
+            r.relocate_path("com/google/common/base/Strings.class"),
+            "myapp/shaded/base/Strings.class"
+        );
+        assert_eq!(
+            r.relocate_path("META-INF/versions/11/com/google/common/A.class"),
+            "META-INF/versions/11/myapp/shaded/guava/A.class"
+        );
+        assert_eq!(
+            r.relocate_path("com/google/commonx/A.class"),
+            "com/google/commonx/A.class"
+        );
+        assert_eq!(
+            r.relocate_class_name("com.google.common.Foo"),
+            "myapp.shaded.guava.Foo"
+        );
+    }
+
+    #[test]
+    fn test_relocate_class_constant_pool() {
+        let mut class = vec![0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 61];
+        class.extend_from_slice(&9u16.to_be_bytes());
+        utf8(&mut class, "com/google/common/Foo"); // #1
+        class.extend_from_slice(&[7, 0, 1]); // #2 Class(#1)
+        utf8(
+            &mut class,
+            "(Lcom/google/common/Foo;[Lorg/x/Lcom/google/common/Y;)V",
+        ); // #3
+        class.extend_from_slice(&[5, 0, 0, 0, 0, 0, 0, 0, 42]); // #4-5 Long
+        utf8(&mut class, "com.google.common.base.Joiner"); // #6
+        class.extend_from_slice(&[8, 0, 6]); // #7 String(#6)
+        utf8(&mut class, "com.google.common.NotAString"); // #8
+        class.extend_from_slice(&[0, 0x21, 0, 2]);
+
+        let out = relocator().relocate_class(&class).unwrap();
+
+        assert_eq!(
+            utf8_constants(&out),
+            vec![
+                "myapp/shaded/guava/Foo",
+                "(Lmyapp/shaded/guava/Foo;[Lorg/x/Lcom/google/common/Y;)V",
+                "myapp.shaded.base.Joiner",
+                "com.google.common.NotAString",
+            ]
+        );
+        assert!(out.ends_with(&[0, 0x21, 0, 2]));
+    }
+
+    #[test]
+    fn test_invalid_rule() {
+        let rules = BTreeMap::from([("com..x".to_string(), "y".to_string())]);
+        assert!(Relocator::new(&rules).is_err());
+    }
+}
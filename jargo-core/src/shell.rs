@@ -1,29 +1,180 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+use std::io::IsTerminal;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Verbosity {
-    Verbose,
-    Normal,
+    /// `--quiet`: no status lines or warnings.
     Quiet,
+    Normal,
+    /// `-v`: adds per-artifact fetch lines and tool command lines.
+    Verbose,
+    /// `-vv`: adds debug events and timings from the `tracing` log.
+    VeryVerbose,
+}
+
+impl Verbosity {
+    /// From the global `-v` count and `--quiet` flag.
+    pub fn from_flags(verbose: u8, quiet: bool) -> Self {
+        match (quiet, verbose) {
+            (true, _) => Verbosity::Quiet,
+            (false, 0) => Verbosity::Normal,
+            (false, 1) => Verbosity::Verbose,
+            (false, _) => Verbosity::VeryVerbose,
+        }
+    }
+
+    /// The global flags selecting this verbosity, for child `jargo`
+    /// processes.
+    pub fn flags(self) -> &'static [&'static str] {
+        match self {
+            Verbosity::Quiet => &["--quiet"],
+            Verbosity::Normal => &[],
+            Verbosity::Verbose => &["-v"],
+            Verbosity::VeryVerbose => &["-vv"],
+        }
+    }
 }
 
+/// When output is colored (`--color`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorChoice {
+    /// Color terminals, unless `NO_COLOR` is set or `TERM` is `dumb`.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "auto" => Some(ColorChoice::Auto),
+            "always" => Some(ColorChoice::Always),
+            "never" => Some(ColorChoice::Never),
+            _ => None,
+        }
+    }
+
+    /// The global flags selecting this choice, for child `jargo` processes.
+    pub fn flags(self) -> &'static [&'static str] {
+        match self {
+            ColorChoice::Auto => &[],
+            ColorChoice::Always => &["--color", "always"],
+            ColorChoice::Never => &["--color", "never"],
+        }
+    }
+
+    /// Whether to color a stream, given whether it is a terminal and the
+    /// `NO_COLOR` and `TERM` variables.
+    fn enabled(self, is_terminal: bool, no_color: Option<&str>, term: Option<&str>) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                is_terminal && no_color.is_none_or(str::is_empty) && term != Some("dumb")
+            }
+        }
+    }
+}
+
+/// Wrap `text` in the ANSI escape `code` (such as `1;32`, bold green) when
+/// `color` is set.
+pub fn paint(code: &str, text: &str, color: bool) -> String {
+    if color {
+        format!("\x1b[{}m{}\x1b[0m", code, text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// How command results are reported (`--message-format`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MessageFormat {
+    #[default]
+    Human,
+    /// Newline-delimited JSON messages on stdout; human output moves to stderr.
+    Json,
+}
+
+#[derive(Clone)]
 pub struct Shell {
     verbosity: Verbosity,
+    message_format: MessageFormat,
+    color: ColorChoice,
 }
 
 impl Shell {
     pub fn new(verbosity: Verbosity) -> Self {
-        Shell { verbosity }
+        Shell {
+            verbosity,
+            message_format: MessageFormat::Human,
+            color: ColorChoice::Auto,
+        }
+    }
+
+    pub fn verbosity(&self) -> Verbosity {
+        self.verbosity
+    }
+
+    pub fn color_choice(&self) -> ColorChoice {
+        self.color
+    }
+
+    pub fn set_color_choice(&mut self, color: ColorChoice) {
+        self.color = color;
+    }
+
+    /// Whether output written to stdout is colored.
+    pub fn out_color(&self) -> bool {
+        self.stream_color(std::io::stdout().is_terminal())
+    }
+
+    /// Whether output written to stderr (diagnostics, warnings, log events)
+    /// is colored.
+    pub fn err_color(&self) -> bool {
+        self.stream_color(std::io::stderr().is_terminal())
+    }
+
+    fn stream_color(&self, is_terminal: bool) -> bool {
+        let no_color = std::env::var("NO_COLOR").ok();
+        let term = std::env::var("TERM").ok();
+        self.color
+            .enabled(is_terminal, no_color.as_deref(), term.as_deref())
+    }
+
+    pub fn set_message_format(&mut self, format: MessageFormat) {
+        self.message_format = format;
+    }
+
+    pub fn is_json(&self) -> bool {
+        self.message_format == MessageFormat::Json
+    }
+
+    /// Write one JSON message line to stdout. No-op unless the message format
+    /// is JSON.
+    pub fn message(&self, message: &impl serde::Serialize) {
+        if self.is_json() {
+            match serde_json::to_string(message) {
+                Ok(line) => println!("{}", line),
+                Err(e) => eprintln!("warning: failed to serialize message: {}", e),
+            }
+        }
     }
 
     /// Cargo-style right-aligned status line: "{:>12} {message}"
     /// e.g. status("Compiling", "foo v1.0") → "   Compiling foo v1.0"
-    /// Silent in Quiet mode.
+    /// The verb is bold green when the stream is colored. Silent in Quiet
+    /// mode; goes to stderr in JSON mode.
     pub fn status(&self, verb: &str, message: &str) {
         if self.verbosity != Verbosity::Quiet {
-            println!("{:>12} {}", verb, message);
+            if self.is_json() {
+                eprintln!("{}", status_line(verb, message, self.err_color()));
+            } else {
+                println!("{}", status_line(verb, message, self.out_color()));
+            }
         }
     }
 
-    /// Execute a closure only in Verbose mode. The closure is never called
+    /// Execute a closure only in Verbose (or VeryVerbose) mode. The closure is never called
     /// (and no formatting happens) on the non-verbose path. Mirrors Cargo's pattern:
     ///
     ///   gctx.shell.verbose(|sh| sh.status("Fetching", "group:artifact:1.0"));
@@ -36,20 +187,80 @@ impl Shell {
     /// - Inside the closure, `sh.status()` and other Shell methods are available,
     ///   letting verbose messages reuse the same structured formatting as normal output
     pub fn verbose<F: FnOnce(&Shell)>(&self, f: F) {
-        if self.verbosity == Verbosity::Verbose {
+        if self.verbosity >= Verbosity::Verbose {
             f(self);
         }
     }
 
     /// Print an unformatted line. Primarily used inside verbose() closures for
     /// diagnostic messages that don't fit the verb/message status pattern.
+    /// Goes to stderr in JSON mode so stdout stays machine-readable.
     pub fn print(&self, message: impl std::fmt::Display) {
-        println!("{}", message);
+        if self.is_json() {
+            eprintln!("{}", message);
+        } else {
+            println!("{}", message);
+        }
     }
 
     pub fn warn(&self, message: &str) {
         if self.verbosity != Verbosity::Quiet {
-            eprintln!("warning: {}", message);
+            eprintln!(
+                "{} {}",
+                paint("1;33", "warning:", self.err_color()),
+                message
+            );
         }
     }
+
+    /// Write rendered compiler diagnostics (newline-terminated) to stderr,
+    /// shown even in Quiet mode.
+    pub fn diagnostics(&self, text: &str) {
+        eprint!("{}", text);
+    }
+
+    /// Print an error to stderr, shown even in Quiet mode.
+    pub fn error(&self, message: impl std::fmt::Display) {
+        eprintln!("{} {}", paint("1;31", "error:", self.err_color()), message);
+    }
+}
+
+/// The verb is padded before painting, so escape codes do not upset the
+/// alignment.
+fn status_line(verb: &str, message: &str, color: bool) -> String {
+    format!(
+        "{} {}",
+        paint("1;32", &format!("{:>12}", verb), color),
+        message
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_choice() {
+        assert!(ColorChoice::Auto.enabled(true, None, Some("xterm")));
+        assert!(!ColorChoice::Auto.enabled(false, None, None));
+        assert!(!ColorChoice::Auto.enabled(true, Some("1"), None));
+        assert!(ColorChoice::Auto.enabled(true, Some(""), None));
+        assert!(!ColorChoice::Auto.enabled(true, None, Some("dumb")));
+        assert!(ColorChoice::Always.enabled(false, Some("1"), None));
+        assert!(!ColorChoice::Never.enabled(true, None, None));
+        assert_eq!(ColorChoice::parse("never"), Some(ColorChoice::Never));
+        assert_eq!(ColorChoice::parse("yes"), None);
+    }
+
+    #[test]
+    fn test_status_line() {
+        assert_eq!(
+            status_line("Compiling", "demo v0.1.0", false),
+            "   Compiling demo v0.1.0"
+        );
+        assert_eq!(
+            status_line("Finished", "ok", true),
+            "\x1b[1;32m    Finished\x1b[0m ok"
+        );
+    }
 }
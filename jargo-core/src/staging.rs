@@ -5,8 +5,20 @@ use std::path::{Path, PathBuf};
 /// Create staging symlink structure for compilation.
 /// Returns the path to target/src-root.
 pub fn create_staging(project_root: &Path, base_package: &str) -> Result<PathBuf> {
+    create_staging_for(project_root, base_package, "src", "src-root")
+}
+
+/// Create a staging symlink `target/{staging_dir}/{base-package-as-path}` pointing
+/// at `{source_dir}/` (e.g. `test/` staged under `target/test-src-root`).
+/// Returns the path to `target/{staging_dir}`.
+pub fn create_staging_for(
+    project_root: &Path,
+    base_package: &str,
+    source_dir: &str,
+    staging_dir: &str,
+) -> Result<PathBuf> {
     let target = project_root.join("target");
-    let src_root = target.join("src-root");
+    let src_root = target.join(staging_dir);
 
     // Clean and recreate src-root
     if src_root.exists() {
@@ -31,14 +43,23 @@ pub fn create_staging(project_root: &Path, base_package: &str) -> Result<PathBuf
     let segments: Vec<&str> = package_path.split('/').collect();
     let depth = segments.len();
 
-    // Build relative path: depth+1 levels up, then "src"
+    // Build relative path: depth+1 levels up, then the source dir
     // For "myapp" (depth=1): ../../src
     // For "com/example/app" (depth=3): ../../../../src
     let mut relative_path = PathBuf::new();
     for _ in 0..=depth {
         relative_path.push("..");
     }
-    relative_path.push("src");
+    relative_path.push(source_dir);
+
+    // In a workspace member, target/ links into the workspace's target/, where
+    // the relative path would point elsewhere
+    if fs::symlink_metadata(&target).is_ok_and(|m| m.file_type().is_symlink()) {
+        relative_path = project_root
+            .canonicalize()
+            .with_context(|| format!("failed to resolve {}", project_root.display()))?
+            .join(source_dir);
+    }
 
     // Create symlink (Unix) or copy directory (Windows)
     create_symlink_or_copy(&relative_path, &symlink_location)?;
@@ -0,0 +1,133 @@
+/// Packages of well-known libraries and the `groupId:artifactId` providing
+/// them. A package matches the entry it equals or is nested in.
+const KNOWN_PACKAGES: &[(&str, &str)] = &[
+    (
+        "org.apache.commons.lang3",
+        "org.apache.commons:commons-lang3",
+    ),
+    ("org.apache.commons.text", "org.apache.commons:commons-text"),
+    (
+        "org.apache.commons.collections4",
+        "org.apache.commons:commons-collections4",
+    ),
+    ("org.apache.commons.io", "commons-io:commons-io"),
+    ("org.apache.commons.codec", "commons-codec:commons-codec"),
+    ("org.apache.commons.csv", "org.apache.commons:commons-csv"),
+    (
+        "org.apache.hc.client5",
+        "org.apache.httpcomponents.client5:httpclient5",
+    ),
+    (
+        "org.apache.logging.log4j",
+        "org.apache.logging.log4j:log4j-api",
+    ),
+    ("com.google.common", "com.google.guava:guava"),
+    ("com.google.gson", "com.google.code.gson:gson"),
+    ("com.google.inject", "com.google.inject:guice"),
+    ("com.google.protobuf", "com.google.protobuf:protobuf-java"),
+    (
+        "com.fasterxml.jackson.databind",
+        "com.fasterxml.jackson.core:jackson-databind",
+    ),
+    (
+        "com.fasterxml.jackson.annotation",
+        "com.fasterxml.jackson.core:jackson-annotations",
+    ),
+    (
+        "com.fasterxml.jackson.core",
+        "com.fasterxml.jackson.core:jackson-core",
+    ),
+    (
+        "com.fasterxml.jackson.dataformat.yaml",
+        "com.fasterxml.jackson.dataformat:jackson-dataformat-yaml",
+    ),
+    (
+        "com.github.benmanes.caffeine",
+        "com.github.ben-manes.caffeine:caffeine",
+    ),
+    ("com.squareup.moshi", "com.squareup.moshi:moshi"),
+    ("com.zaxxer.hikari", "com.zaxxer:HikariCP"),
+    ("okhttp3", "com.squareup.okhttp3:okhttp"),
+    ("org.slf4j", "org.slf4j:slf4j-api"),
+    ("ch.qos.logback.classic", "ch.qos.logback:logback-classic"),
+    ("io.micrometer.core", "io.micrometer:micrometer-core"),
+    ("reactor.core", "io.projectreactor:reactor-core"),
+    ("picocli", "info.picocli:picocli"),
+    ("org.jetbrains.annotations", "org.jetbrains:annotations"),
+    ("org.jspecify.annotations", "org.jspecify:jspecify"),
+    ("org.jsoup", "org.jsoup:jsoup"),
+    ("org.yaml.snakeyaml", "org.yaml:snakeyaml"),
+    ("org.postgresql", "org.postgresql:postgresql"),
+    ("org.joda.time", "joda-time:joda-time"),
+    ("jakarta.inject", "jakarta.inject:jakarta.inject-api"),
+    (
+        "jakarta.annotation",
+        "jakarta.annotation:jakarta.annotation-api",
+    ),
+];
+
+/// The `groupId:artifactId` to add for a javac `package ... does not exist`
+/// error `message`, when the package belongs to a well-known library.
+pub fn missing_package_coordinate(message: &str) -> Option<&'static str> {
+    let package = message
+        .strip_prefix("package ")?
+        .strip_suffix(" does not exist")?;
+    KNOWN_PACKAGES
+        .iter()
+        .find(|(prefix, _)| {
+            package
+                .strip_prefix(prefix)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+        })
+        .map(|(_, coordinate)| *coordinate)
+}
+
+/// A `help:` note suggesting the `jargo add` command that fixes the javac
+/// error `message`, if it is a missing well-known package.
+pub fn missing_dependency_help(message: &str) -> Option<String> {
+    let coordinate = missing_package_coordinate(message)?;
+    Some(format!(
+        "help: `{}` provides this package; add it with `jargo add {}`",
+        coordinate, coordinate
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_package_coordinate() {
+        assert_eq!(
+            missing_package_coordinate("package org.apache.commons.lang3 does not exist"),
+            Some("org.apache.commons:commons-lang3")
+        );
+        assert_eq!(
+            missing_package_coordinate("package com.google.common.collect does not exist"),
+            Some("com.google.guava:guava")
+        );
+        assert_eq!(
+            missing_package_coordinate("package com.fasterxml.jackson.core.type does not exist"),
+            Some("com.fasterxml.jackson.core:jackson-core")
+        );
+        // A package sharing only a name prefix is not the library's
+        assert_eq!(
+            missing_package_coordinate("package org.slf4jx does not exist"),
+            None
+        );
+        assert_eq!(
+            missing_package_coordinate("package com.example.util does not exist"),
+            None
+        );
+        assert_eq!(missing_package_coordinate("cannot find symbol"), None);
+    }
+
+    #[test]
+    fn test_missing_dependency_help() {
+        assert_eq!(
+            missing_dependency_help("package org.slf4j does not exist").unwrap(),
+            "help: `org.slf4j:slf4j-api` provides this package; add it with \
+             `jargo add org.slf4j:slf4j-api`"
+        );
+    }
+}
@@ -0,0 +1,335 @@
+use anyhow::{bail, Context, Result};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::bytecode;
+use crate::jar::files_under;
+use crate::manifest::TestConfig;
+
+/// A `jargo test` filter: `FooTest`, `com.example.*.FooTest`, or
+/// `FooTest#shouldWork`.
+///
+/// Both parts are globs where `*` matches any run of characters and `?` a
+/// single one. A class pattern without a `.` matches the simple class name,
+/// otherwise the fully-qualified one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestFilter {
+    pub class: String,
+    pub method: Option<String>,
+}
+
+impl TestFilter {
+    pub fn parse(filter: &str) -> Result<Self> {
+        let (class, method) = match filter.split_once('#') {
+            Some((class, method)) => (class, Some(method)),
+            None => (filter, None),
+        };
+        if class.is_empty() || method.is_some_and(|m| m.is_empty() || m.contains('#')) {
+            bail!(
+                "invalid test filter `{}`: expected CLASS or CLASS#METHOD",
+                filter
+            );
+        }
+        Ok(Self {
+            class: class.to_string(),
+            method: method.map(String::from),
+        })
+    }
+
+    fn matches_class(&self, class: &str) -> bool {
+        if self.class.contains('.') {
+            glob_match(&self.class, class)
+        } else {
+            glob_match(&self.class, class.rsplit('.').next().unwrap_or(class))
+        }
+    }
+}
+
+impl fmt::Display for TestFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.method {
+            Some(method) => write!(f, "`{}#{}`", self.class, method),
+            None => write!(f, "`{}`", self.class),
+        }
+    }
+}
+
+/// JUnit Platform selectors for the tests matched by filters, as passed to
+/// `--select-class` and `--select-method`.
+#[derive(Debug, Default, PartialEq)]
+pub struct TestSelection {
+    /// Fully-qualified top-level classes to run entirely.
+    pub classes: Vec<String>,
+    /// Single test methods, as `com.example.FooTest#shouldWork`.
+    pub methods: Vec<String>,
+}
+
+impl TestSelection {
+    pub fn is_empty(&self) -> bool {
+        self.classes.is_empty() && self.methods.is_empty()
+    }
+}
+
+/// Resolve `filters` against the compiled classes in `test_classes_dir`.
+///
+/// Class filters select matching top-level classes. Method filters select the
+/// matching methods declared by matching classes and their nested classes.
+/// A non-empty `only` (the `--affected` test classes) limits which top-level
+/// classes are considered.
+pub fn select(
+    test_classes_dir: &Path,
+    filters: &[TestFilter],
+    only: &[String],
+) -> Result<TestSelection> {
+    let mut selection = TestSelection::default();
+    if !test_classes_dir.is_dir() {
+        return Ok(selection);
+    }
+    for path in files_under(test_classes_dir)? {
+        let Some(class) = path
+            .strip_prefix(test_classes_dir)
+            .with_context(|| "failed to compute relative path")?
+            .to_string_lossy()
+            .strip_suffix(".class")
+            .map(|name| name.replace(['/', '\\'], "."))
+        else {
+            continue;
+        };
+        let top_level = class.split('$').next().unwrap_or(&class);
+        if !only.is_empty() && !only.iter().any(|c| c == top_level) {
+            continue;
+        }
+        let matching: Vec<&TestFilter> = filters
+            .iter()
+            .filter(|f| f.matches_class(top_level))
+            .collect();
+        if matching.is_empty() {
+            continue;
+        }
+        if top_level == class && matching.iter().any(|f| f.method.is_none()) {
+            selection.classes.push(class.clone());
+        }
+        let patterns: Vec<&str> = matching
+            .iter()
+            .filter_map(|f| f.method.as_deref())
+            .collect();
+        if patterns.is_empty() {
+            continue;
+        }
+        let bytes =
+            fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+        let methods = bytecode::method_names(&bytes)
+            .with_context(|| format!("failed to read methods of {}", path.display()))?;
+        for method in methods {
+            // Constructors, initializers, and compiler-generated methods
+            if method.starts_with('<') || method.contains('$') {
+                continue;
+            }
+            if patterns.iter().any(|p| glob_match(p, &method)) {
+                selection.methods.push(format!("{}#{}", class, method));
+            }
+        }
+    }
+    selection.classes.sort();
+    selection.methods.sort();
+    selection.methods.dedup();
+    // Methods of a class that runs entirely would run twice
+    selection.methods.retain(|m| {
+        let top_level = m.split(['$', '#']).next().unwrap_or(m);
+        !selection.classes.iter().any(|c| c == top_level)
+    });
+    Ok(selection)
+}
+
+/// Tags to include and exclude in a test run, as passed to `--include-tag` and
+/// `--exclude-tag`. `--tag` replaces `[test] tags` and lifts those tags out of
+/// `[test] exclude-tags`, so `jargo test --tag slow` runs the slow tests even
+/// when they are excluded by default; `--exclude-tag` adds to the exclusions.
+pub fn tag_filters(
+    config: Option<&TestConfig>,
+    tags: &[String],
+    exclude_tags: &[String],
+) -> (Vec<String>, Vec<String>) {
+    let (config_tags, config_excludes) = match config {
+        Some(config) => (&config.tags[..], &config.exclude_tags[..]),
+        None => (&[][..], &[][..]),
+    };
+    let include = if tags.is_empty() { config_tags } else { tags };
+    let exclude: Vec<String> = config_excludes
+        .iter()
+        .filter(|tag| !tags.contains(tag))
+        .chain(exclude_tags)
+        .cloned()
+        .collect();
+    (include.to_vec(), exclude)
+}
+
+/// Match `text` against a glob where `*` is any run of characters and `?` is
+/// a single character.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position after the last `*` and the text position it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p + 1, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((after_star, tried)) => {
+                    p = after_star;
+                    t = tried + 1;
+                    backtrack = Some((after_star, tried + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A class file declaring `methods`, with no fields and no code.
+    fn class_file(methods: &[&str]) -> Vec<u8> {
+        let mut class = vec![0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 61];
+        class.extend_from_slice(&(methods.len() as u16 + 1).to_be_bytes());
+        for name in methods {
+            class.push(1);
+            class.extend_from_slice(&(name.len() as u16).to_be_bytes());
+            class.extend_from_slice(name.as_bytes());
+        }
+        class.extend_from_slice(&[0, 0x21, 0, 0, 0, 0, 0, 0, 0, 0]);
+        class.extend_from_slice(&(methods.len() as u16).to_be_bytes());
+        for index in 1..=methods.len() as u16 {
+            class.extend_from_slice(&[0, 1]);
+            class.extend_from_slice(&index.to_be_bytes());
+            class.extend_from_slice(&[0, 1, 0, 0]);
+        }
+        class
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("FooTest", "FooTest"));
+        assert!(!glob_match("FooTest", "FooTests"));
+        assert!(glob_match("*Test", "FooTest"));
+        assert!(glob_match(
+            "com.example.*.FooTest",
+            "com.example.a.b.FooTest"
+        ));
+        assert!(!glob_match("com.example.*.FooTest", "com.example.FooTest"));
+        assert!(glob_match("should*Work?", "shouldAlwaysWork1"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("?", ""));
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            TestFilter::parse("com.example.*.FooTest#shouldWork").unwrap(),
+            TestFilter {
+                class: "com.example.*.FooTest".to_string(),
+                method: Some("shouldWork".to_string()),
+            }
+        );
+        assert_eq!(TestFilter::parse("FooTest").unwrap().method, None);
+        assert!(TestFilter::parse("#shouldWork").is_err());
+        assert!(TestFilter::parse("FooTest#").is_err());
+        assert!(TestFilter::parse("FooTest#a#b").is_err());
+    }
+
+    #[test]
+    fn test_matches_class() {
+        let filter = TestFilter::parse("FooTest").unwrap();
+        assert!(filter.matches_class("com.example.FooTest"));
+        assert!(filter.matches_class("FooTest"));
+        assert!(!filter.matches_class("com.example.BarTest"));
+        let filter = TestFilter::parse("com.example.*Test").unwrap();
+        assert!(filter.matches_class("com.example.FooTest"));
+        assert!(!filter.matches_class("org.example.FooTest"));
+    }
+
+    #[test]
+    fn test_select() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("com/example");
+        fs::create_dir_all(&dir).unwrap();
+        for (name, methods) in [
+            (
+                "FooTest",
+                &["<init>", "shouldWork", "shouldFail", "lambda$shouldWork$0"][..],
+            ),
+            ("FooTest$Nested", &["shouldWorkNested"][..]),
+            ("BarTest", &["shouldWork"][..]),
+            ("Helper", &["help"][..]),
+        ] {
+            fs::write(dir.join(format!("{}.class", name)), class_file(methods)).unwrap();
+        }
+
+        let filters = |filters: &[&str]| -> Vec<TestFilter> {
+            filters
+                .iter()
+                .map(|f| TestFilter::parse(f).unwrap())
+                .collect()
+        };
+        let selection = select(tmp.path(), &filters(&["FooTest#shouldWork*"]), &[]).unwrap();
+        assert_eq!(selection.classes, Vec::<String>::new());
+        assert_eq!(
+            selection.methods,
+            [
+                "com.example.FooTest#shouldWork",
+                "com.example.FooTest$Nested#shouldWorkNested"
+            ]
+        );
+
+        let selection =
+            select(tmp.path(), &filters(&["*Test", "FooTest#shouldFail"]), &[]).unwrap();
+        assert_eq!(
+            selection.classes,
+            ["com.example.BarTest", "com.example.FooTest"]
+        );
+        assert!(selection.methods.is_empty());
+
+        let only = ["com.example.BarTest".to_string()];
+        let selection = select(tmp.path(), &filters(&["*Test"]), &only).unwrap();
+        assert_eq!(selection.classes, ["com.example.BarTest"]);
+
+        assert!(select(tmp.path(), &filters(&["Missing"]), &[])
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_tag_filters() {
+        let strings =
+            |tags: &[&str]| -> Vec<String> { tags.iter().map(|t| t.to_string()).collect() };
+        let config = TestConfig {
+            tags: strings(&["unit"]),
+            exclude_tags: strings(&["slow", "flaky"]),
+            ..TestConfig::default()
+        };
+        assert_eq!(
+            tag_filters(Some(&config), &[], &[]),
+            (strings(&["unit"]), strings(&["slow", "flaky"]))
+        );
+        assert_eq!(
+            tag_filters(Some(&config), &strings(&["slow"]), &strings(&["db"])),
+            (strings(&["slow"]), strings(&["flaky", "db"]))
+        );
+        assert_eq!(
+            tag_filters(None, &[], &strings(&["db"])),
+            (Vec::new(), strings(&["db"]))
+        );
+    }
+}
@@ -0,0 +1,1188 @@
+use anyhow::{bail, Context, Result};
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::affected;
+use crate::cache;
+use crate::compiler::SourceSet;
+use crate::context::GlobalContext;
+use crate::errors::JargoError;
+use crate::manifest::TestFramework;
+use crate::test_filter::TestSelection;
+use crate::toolchain::Toolchain;
+
+/// JUnit Platform version of the bundled console launcher. The standalone JAR
+/// includes the Jupiter API and engine, so it doubles as the implicit JUnit 5
+/// dependency on the test-compile classpath.
+pub const JUNIT_PLATFORM_VERSION: &str = "1.10.2";
+
+/// TestNG version added as an implicit dev-dependency with
+/// `[test] framework = "testng"`.
+pub const TESTNG_VERSION: &str = "7.10.2";
+
+/// Directory the console launcher writes its JUnit XML reports to.
+pub const REPORTS_DIR: &str = "target/test-reports";
+
+/// Like [`REPORTS_DIR`], for integration tests.
+pub const IT_REPORTS_DIR: &str = "target/it-reports";
+
+/// File name of the JSON summary written next to the XML reports.
+pub const SUMMARY_FILE: &str = "summary.json";
+
+/// Selectors of the tests that failed in the last run, one per line, for
+/// `jargo test --rerun-failed`.
+pub const FAILED_TESTS_FILE: &str = "target/failed-tests.txt";
+
+/// Wall times of each test over recent runs, for the slowest-tests report.
+pub const TIMINGS_FILE: &str = "target/test-timings.json";
+
+/// Runs of history kept per test in [`TIMINGS_FILE`].
+const TIMING_HISTORY: usize = 10;
+
+/// Options for a single test run.
+#[derive(Debug, Default)]
+pub struct TestOptions {
+    /// Extra JVM arguments for the test JVM.
+    pub jvm_args: Vec<String>,
+    /// Fully-qualified test classes to run. Empty runs every discovered test class.
+    pub select_classes: Vec<String>,
+    /// Test methods to run, as `com.example.FooTest#shouldWork`.
+    pub select_methods: Vec<String>,
+    /// JUnit tag expressions a test must match to run.
+    pub include_tags: Vec<String>,
+    /// JUnit tag expressions that skip a test.
+    pub exclude_tags: Vec<String>,
+    /// Environment variables set for the test JVM.
+    pub env: Vec<(String, String)>,
+    /// Run each top-level test class in its own JVM and stop after the first
+    /// class with failures.
+    pub fail_fast: bool,
+    /// Run the integration tests in `target/it-classes/` instead of the unit
+    /// tests, reporting to `target/it-reports/`.
+    pub integration: bool,
+    /// Run test classes and methods in the random order derived from this
+    /// seed. JUnit only; classes with their own orderer keep it.
+    pub seed: Option<u64>,
+}
+
+impl TestOptions {
+    /// Compiled test classes to run, relative to the project root.
+    pub fn classes_dir(&self) -> &'static str {
+        if self.integration {
+            SourceSet::IntegrationTest.output_dir()
+        } else {
+            SourceSet::Test.output_dir()
+        }
+    }
+
+    /// Where the XML reports and the JSON summary go, relative to the project root.
+    pub fn reports_dir(&self) -> &'static str {
+        if self.integration {
+            IT_REPORTS_DIR
+        } else {
+            REPORTS_DIR
+        }
+    }
+}
+
+/// The result of a single test method.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TestOutcome {
+    Passed,
+    /// Assertion failure or unexpected exception.
+    Failed {
+        message: String,
+        details: String,
+    },
+    Skipped(Option<String>),
+}
+
+impl TestOutcome {
+    /// `"passed"`, `"failed"`, or `"skipped"`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TestOutcome::Passed => "passed",
+            TestOutcome::Failed { .. } => "failed",
+            TestOutcome::Skipped(_) => "skipped",
+        }
+    }
+
+    /// Failure message or skip reason.
+    pub fn message(&self) -> Option<&str> {
+        match self {
+            TestOutcome::Passed => None,
+            TestOutcome::Failed { message, .. } => Some(message),
+            TestOutcome::Skipped(reason) => reason.as_deref(),
+        }
+    }
+}
+
+/// One executed test, as reported by the JUnit Platform or TestNG.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestCase {
+    /// Fully-qualified class name, e.g. `myapp.MainTest`.
+    pub class_name: String,
+    /// Method display name, e.g. `testMain()`.
+    pub name: String,
+    /// Duration in seconds.
+    pub time: f64,
+    pub outcome: TestOutcome,
+    /// Captured stdout/stderr of the test, if any.
+    pub output: String,
+    /// JUnit Platform unique ID, e.g.
+    /// `[engine:junit-jupiter]/[class:myapp.MainTest]/[method:testMain()]`,
+    /// or an `[engine:testng]` one of the same shape for TestNG.
+    pub unique_id: String,
+}
+
+impl TestCase {
+    /// Console launcher selector that runs this test again: the method
+    /// (`myapp.MainTest#testMain()`) from the unique ID, or the whole class
+    /// when the ID names no method.
+    pub fn selector(&self) -> String {
+        let mut class = self.class_name.clone();
+        for segment in self.unique_id.split("]/[") {
+            let (kind, value) = segment
+                .trim_matches(['[', ']'])
+                .split_once(':')
+                .unwrap_or_default();
+            match kind {
+                "class" => class = value.to_string(),
+                "nested-class" => class = format!("{}${}", class, value),
+                "method" | "test-template" | "test-factory" => {
+                    return format!("{}#{}", class, value.replace(", ", ","));
+                }
+                _ => {}
+            }
+        }
+        class
+    }
+}
+
+/// The parsed outcome of a test run.
+pub struct TestRun {
+    pub cases: Vec<TestCase>,
+    /// Raw console launcher output, for diagnostics.
+    pub launcher_output: String,
+}
+
+impl TestRun {
+    pub fn passed(&self) -> usize {
+        self.count(|o| matches!(o, TestOutcome::Passed))
+    }
+
+    pub fn failed(&self) -> usize {
+        self.count(|o| matches!(o, TestOutcome::Failed { .. }))
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.count(|o| matches!(o, TestOutcome::Skipped(_)))
+    }
+
+    pub fn success(&self) -> bool {
+        self.failed() == 0
+    }
+
+    /// Total time across all tests, in seconds.
+    pub fn total_time(&self) -> f64 {
+        self.cases.iter().map(|c| c.time).sum()
+    }
+
+    fn count(&self, pred: impl Fn(&TestOutcome) -> bool) -> usize {
+        self.cases.iter().filter(|c| pred(&c.outcome)).count()
+    }
+}
+
+/// How test JVMs are launched for the `[test] framework`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TestEngine {
+    /// `java -jar <console launcher> execute`. The standalone launcher JAR
+    /// also provides JUnit on the test-compile classpath.
+    JUnitPlatform { launcher: PathBuf },
+    /// `java org.testng.TestNG` with a generated suite file. TestNG comes
+    /// from the test runtime classpath as a dev-dependency.
+    TestNg,
+}
+
+impl TestEngine {
+    /// The engine for `framework`, fetching the console launcher for JUnit.
+    pub fn for_framework(gctx: &GlobalContext, framework: TestFramework) -> Result<Self> {
+        Ok(match framework {
+            TestFramework::JUnit => TestEngine::JUnitPlatform {
+                launcher: fetch_console_launcher(gctx)?,
+            },
+            TestFramework::TestNg => TestEngine::TestNg,
+        })
+    }
+
+    /// JAR the engine adds to the test-compile classpath, if any.
+    pub fn compile_jar(&self) -> Option<&Path> {
+        match self {
+            TestEngine::JUnitPlatform { launcher } => Some(launcher),
+            TestEngine::TestNg => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            TestEngine::JUnitPlatform { .. } => "JUnit console launcher",
+            TestEngine::TestNg => "TestNG",
+        }
+    }
+
+    /// Where the JUnit XML reports of a run writing to `out_dir` end up.
+    /// TestNG's `JUnitReportReporter` puts them in a `junitreports/` subdirectory.
+    fn xml_dir(&self, out_dir: &Path) -> PathBuf {
+        match self {
+            TestEngine::JUnitPlatform { .. } => out_dir.to_path_buf(),
+            TestEngine::TestNg => out_dir.join("junitreports"),
+        }
+    }
+}
+
+/// Fetch the JUnit Platform console launcher JAR into the local cache.
+pub fn fetch_console_launcher(gctx: &GlobalContext) -> Result<PathBuf> {
+    let (path, _sha256) = cache::fetch_jar(
+        gctx,
+        "org.junit.platform",
+        "junit-platform-console-standalone",
+        JUNIT_PLATFORM_VERSION,
+    )
+    .context("failed to fetch the JUnit Platform console launcher")?;
+    Ok(path)
+}
+
+/// Run the compiled tests in `target/test-classes/` (or `target/it-classes/`)
+/// with `engine`.
+///
+/// `classpath` is the test runtime classpath (test classes, main classes, and
+/// dependency JARs). Results are read back from the JUnit XML reports in
+/// `target/test-reports/` (or `target/it-reports/`). With `fail_fast`, each
+/// top-level test class runs in its own JVM, in name order, and no further
+/// classes run after one fails.
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn run_tests(
+    gctx: &GlobalContext,
+    project_root: &Path,
+    toolchain: &Toolchain,
+    engine: &TestEngine,
+    classpath: &[PathBuf],
+    opts: &TestOptions,
+) -> Result<TestRun> {
+    let reports_dir = project_root.join(opts.reports_dir());
+    if reports_dir.exists() {
+        fs::remove_dir_all(&reports_dir)
+            .with_context(|| format!("failed to remove {}", reports_dir.display()))?;
+    }
+    if *engine == TestEngine::TestNg {
+        check_groups(opts)?;
+        if opts.seed.is_some() {
+            bail!("shuffling test order is only supported by JUnit");
+        }
+    }
+
+    let launcher = Launcher {
+        gctx,
+        project_root,
+        toolchain,
+        engine,
+        classpath,
+        opts,
+    };
+    if !opts.fail_fast {
+        let selection = TestSelection {
+            classes: opts.select_classes.clone(),
+            methods: opts.select_methods.clone(),
+        };
+        let (cases, launcher_output) = match engine {
+            TestEngine::JUnitPlatform { .. } => launcher.run(&selection, &reports_dir)?,
+            // TestNG writes more than the XML reports; keep only those
+            TestEngine::TestNg => {
+                let scratch = reports_dir.join(".testng");
+                let result = launcher.run(&selection, &scratch)?;
+                move_reports(&engine.xml_dir(&scratch), &reports_dir, None)?;
+                remove_scratch(&scratch)?;
+                result
+            }
+        };
+        return Ok(TestRun {
+            cases,
+            launcher_output,
+        });
+    }
+
+    let groups = class_groups(project_root, opts)?;
+    let mut run = TestRun {
+        cases: Vec::new(),
+        launcher_output: String::new(),
+    };
+    for (i, (class, selection)) in groups.iter().enumerate() {
+        // Each JVM writes the same report names, so they get a prefix
+        let scratch = reports_dir.join(format!(".{}", class));
+        let (cases, output) = launcher.run(selection, &scratch)?;
+        run.cases.extend(cases);
+        run.launcher_output.push_str(&output);
+        move_reports(&engine.xml_dir(&scratch), &reports_dir, Some(class))?;
+        remove_scratch(&scratch)?;
+
+        let remaining = groups.len() - i - 1;
+        if !run.success() && remaining > 0 {
+            gctx.shell.status(
+                "Stopped",
+                &format!(
+                    "after failures in {} (--fail-fast); {} test class{} not run",
+                    class,
+                    remaining,
+                    if remaining == 1 { "" } else { "es" }
+                ),
+            );
+            break;
+        }
+    }
+    Ok(run)
+}
+
+/// TestNG groups are plain names, so `[test] tags` and `--tag` values must be
+/// too when running TestNG.
+fn check_groups(opts: &TestOptions) -> Result<()> {
+    for tag in opts.include_tags.iter().chain(&opts.exclude_tags) {
+        if tag.is_empty()
+            || tag
+                .chars()
+                .any(|c| c.is_whitespace() || "!&|()".contains(c))
+        {
+            bail!(
+                "invalid TestNG group `{}`: tag expressions are only supported by JUnit",
+                tag
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Test selection per top-level test class, in class name order. Without
+/// selected classes or methods, every class in the test classes directory
+/// named like a test class gets one.
+fn class_groups(project_root: &Path, opts: &TestOptions) -> Result<Vec<(String, TestSelection)>> {
+    let top_level = |selector: &str| -> String {
+        selector
+            .split(['$', '#'])
+            .next()
+            .unwrap_or(selector)
+            .to_string()
+    };
+    let mut classes: BTreeSet<String> = BTreeSet::new();
+    if opts.select_classes.is_empty() && opts.select_methods.is_empty() {
+        classes.extend(affected::list_test_classes(
+            &project_root.join(opts.classes_dir()),
+        )?);
+        return Ok(classes
+            .into_iter()
+            .map(|class| {
+                let selection = TestSelection {
+                    classes: vec![class.clone()],
+                    methods: Vec::new(),
+                };
+                (class, selection)
+            })
+            .collect());
+    }
+    classes.extend(opts.select_classes.iter().map(|c| top_level(c)));
+    classes.extend(opts.select_methods.iter().map(|m| top_level(m)));
+    Ok(classes
+        .into_iter()
+        .map(|class| {
+            let in_class = |selectors: &[String]| -> Vec<String> {
+                selectors
+                    .iter()
+                    .filter(|s| top_level(s) == class)
+                    .cloned()
+                    .collect()
+            };
+            let selection = TestSelection {
+                classes: in_class(&opts.select_classes),
+                methods: in_class(&opts.select_methods),
+            };
+            (class, selection)
+        })
+        .collect())
+}
+
+/// Everything needed to start a test JVM, apart from what it runs.
+struct Launcher<'a> {
+    gctx: &'a GlobalContext,
+    project_root: &'a Path,
+    toolchain: &'a Toolchain,
+    engine: &'a TestEngine,
+    classpath: &'a [PathBuf],
+    opts: &'a TestOptions,
+}
+
+impl Launcher<'_> {
+    /// Run the tests in `selection` (all of them when empty), writing reports
+    /// to `out_dir`, and return the test cases with the JVM's output.
+    fn run(&self, selection: &TestSelection, out_dir: &Path) -> Result<(Vec<TestCase>, String)> {
+        let cmd = match self.engine {
+            TestEngine::JUnitPlatform { launcher } => {
+                self.junit_command(launcher, selection, out_dir)
+            }
+            TestEngine::TestNg => self.testng_command(selection, out_dir)?,
+        };
+        let output = self.launch(cmd)?;
+        let mut cases = read_reports(&self.engine.xml_dir(out_dir), self.engine, &output)?;
+        if *self.engine == TestEngine::TestNg {
+            // TestNG reports carry no unique IDs; give them JUnit-style ones
+            for case in &mut cases {
+                case.unique_id = format!(
+                    "[engine:testng]/[class:{}]/[method:{}]",
+                    case.class_name, case.name
+                );
+            }
+        }
+        Ok((cases, output))
+    }
+
+    /// Console launcher command, selecting with `--select-class` and
+    /// `--select-method` or scanning the test classes directory.
+    fn junit_command(&self, launcher: &Path, selection: &TestSelection, out_dir: &Path) -> Command {
+        let opts = self.opts;
+        let mut cmd = Command::new(self.toolchain.java());
+        cmd.args(&opts.jvm_args)
+            .envs(opts.env.iter().map(|(key, value)| (key, value)))
+            .arg("-jar")
+            .arg(launcher)
+            .arg("execute")
+            .arg("--disable-banner")
+            .arg("--disable-ansi-colors")
+            .arg("--details=none")
+            .arg("--config=junit.platform.output.capture.stdout=true")
+            .arg("--config=junit.platform.output.capture.stderr=true")
+            .arg("--reports-dir")
+            .arg(out_dir)
+            .arg("--class-path")
+            .arg(join_classpath(self.classpath));
+        if selection.is_empty() {
+            cmd.arg("--scan-class-path")
+                .arg(self.project_root.join(opts.classes_dir()));
+        }
+        for class in &selection.classes {
+            cmd.arg("--select-class").arg(class);
+        }
+        for method in &selection.methods {
+            cmd.arg("--select-method").arg(method);
+        }
+        for tag in &opts.include_tags {
+            cmd.arg("--include-tag").arg(tag);
+        }
+        for tag in &opts.exclude_tags {
+            cmd.arg("--exclude-tag").arg(tag);
+        }
+        cmd.args(order_args(opts.seed));
+        cmd
+    }
+
+    /// `org.testng.TestNG` command running a suite file written to `out_dir`,
+    /// with only the JUnit XML reporter enabled.
+    fn testng_command(&self, selection: &TestSelection, out_dir: &Path) -> Result<Command> {
+        let opts = self.opts;
+        let all;
+        let selection = if selection.is_empty() {
+            all = TestSelection {
+                classes: affected::list_test_classes(&self.project_root.join(opts.classes_dir()))?,
+                methods: Vec::new(),
+            };
+            &all
+        } else {
+            selection
+        };
+        fs::create_dir_all(out_dir)
+            .with_context(|| format!("failed to create {}", out_dir.display()))?;
+        let suite = out_dir.join("testng.xml");
+        let xml = testng_suite(selection, &opts.include_tags, &opts.exclude_tags);
+        fs::write(&suite, xml).with_context(|| format!("failed to write {}", suite.display()))?;
+
+        let mut cmd = Command::new(self.toolchain.java());
+        cmd.args(&opts.jvm_args)
+            .envs(opts.env.iter().map(|(key, value)| (key, value)))
+            .arg("-cp")
+            .arg(join_classpath(self.classpath))
+            .arg("org.testng.TestNG")
+            .arg("-d")
+            .arg(out_dir)
+            .arg("-usedefaultlisteners")
+            .arg("false")
+            .arg("-listener")
+            .arg("org.testng.reporters.JUnitReportReporter")
+            .arg(&suite);
+        Ok(cmd)
+    }
+
+    /// Run a test JVM and return its combined stdout and stderr.
+    fn launch(&self, mut cmd: Command) -> Result<String> {
+        self.gctx
+            .shell
+            .verbose(|sh| sh.print(format!("  [verbose] running {:?}", cmd)));
+
+        let output = cmd.current_dir(self.project_root).output().map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                JargoError::JavaNotFound.into()
+            } else {
+                anyhow::Error::from(e)
+            }
+        })?;
+
+        let mut launcher_output = String::from_utf8_lossy(&output.stdout).into_owned();
+        launcher_output.push_str(&String::from_utf8_lossy(&output.stderr));
+        Ok(launcher_output)
+    }
+}
+
+/// A TestNG suite running `selection`, where methods are given as
+/// `com.example.FooTest#shouldWork` (an optional `(...)` parameter list is
+/// ignored).
+fn testng_suite(selection: &TestSelection, groups: &[String], exclude_groups: &[String]) -> String {
+    let mut methods: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for method in &selection.methods {
+        let (class, name) = method.split_once('#').unwrap_or((method, ""));
+        let name = name.split('(').next().unwrap_or(name);
+        methods.entry(class).or_default().push(name);
+    }
+
+    let mut xml = String::from(
+        "<!DOCTYPE suite SYSTEM \"https://testng.org/testng-1.0.dtd\">\n\
+         <suite name=\"jargo\">\n  <test name=\"tests\">\n",
+    );
+    if !groups.is_empty() || !exclude_groups.is_empty() {
+        xml.push_str("    <groups>\n      <run>\n");
+        for group in groups {
+            xml.push_str(&format!("        <include name=\"{}\"/>\n", escape(group)));
+        }
+        for group in exclude_groups {
+            xml.push_str(&format!("        <exclude name=\"{}\"/>\n", escape(group)));
+        }
+        xml.push_str("      </run>\n    </groups>\n");
+    }
+    xml.push_str("    <classes>\n");
+    for class in &selection.classes {
+        xml.push_str(&format!("      <class name=\"{}\"/>\n", escape(class)));
+    }
+    for (class, names) in &methods {
+        xml.push_str(&format!(
+            "      <class name=\"{}\">\n        <methods>\n",
+            escape(*class)
+        ));
+        for name in names {
+            xml.push_str(&format!(
+                "          <include name=\"{}\"/>\n",
+                escape(*name)
+            ));
+        }
+        xml.push_str("        </methods>\n      </class>\n");
+    }
+    xml.push_str("    </classes>\n  </test>\n</suite>\n");
+    xml
+}
+
+/// Move the XML reports in `from` into `reports_dir`, prefixed with `TEST-{class}-`
+/// when `class` is given.
+fn move_reports(from: &Path, reports_dir: &Path, class: Option<&str>) -> Result<()> {
+    for report in xml_files(from)? {
+        let name = report.file_name().unwrap_or_default().to_string_lossy();
+        let name = match class {
+            Some(class) => format!("TEST-{}-{}", class, name.trim_start_matches("TEST-")),
+            None => name.into_owned(),
+        };
+        fs::rename(&report, reports_dir.join(&name))
+            .with_context(|| format!("failed to move {}", report.display()))?;
+    }
+    Ok(())
+}
+
+fn remove_scratch(scratch: &Path) -> Result<()> {
+    fs::remove_dir_all(scratch).with_context(|| format!("failed to remove {}", scratch.display()))
+}
+
+/// XML reports in `reports_dir`, sorted by name.
+fn xml_files(reports_dir: &Path) -> Result<Vec<PathBuf>> {
+    if !reports_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut reports: Vec<PathBuf> = fs::read_dir(reports_dir)
+        .with_context(|| format!("failed to read {}", reports_dir.display()))?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "xml"))
+        .collect();
+    reports.sort();
+    Ok(reports)
+}
+
+/// Test cases from the XML reports in `reports_dir`. An engine that wrote
+/// none failed before running anything; its output explains why.
+fn read_reports(
+    reports_dir: &Path,
+    engine: &TestEngine,
+    launcher_output: &str,
+) -> Result<Vec<TestCase>> {
+    let reports = xml_files(reports_dir)?;
+    if reports.is_empty() {
+        bail!(
+            "{} produced no test report:\n{}",
+            engine.name(),
+            launcher_output.trim_end()
+        );
+    }
+    let mut cases = Vec::new();
+    for report in reports {
+        let xml = fs::read_to_string(&report)
+            .with_context(|| format!("failed to read {}", report.display()))?;
+        cases.extend(
+            parse_report(&xml).with_context(|| format!("failed to parse {}", report.display()))?,
+        );
+    }
+    Ok(cases)
+}
+
+/// Parse a JUnit legacy XML report (`TEST-*.xml`) into test cases.
+fn parse_report(xml: &str) -> Result<Vec<TestCase>> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut cases = Vec::new();
+    let mut current: Option<TestCase> = None;
+    // Element whose text is being collected: "failure", "error", or "system-out"/"system-err".
+    let mut text_target: Option<String> = None;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).into_owned();
+                match name.as_str() {
+                    "testcase" => current = Some(start_case(&e)?),
+                    "failure" | "error" | "skipped" => {
+                        if let Some(case) = current.as_mut() {
+                            case.outcome = outcome_for(&name, &e)?;
+                        }
+                        text_target = Some(name);
+                    }
+                    "system-out" | "system-err" => text_target = Some(name),
+                    _ => {}
+                }
+            }
+            Ok(Event::Empty(e)) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).into_owned();
+                match name.as_str() {
+                    "testcase" => cases.push(start_case(&e)?),
+                    "failure" | "error" | "skipped" => {
+                        if let Some(case) = current.as_mut() {
+                            case.outcome = outcome_for(&name, &e)?;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Ok(Event::Text(e)) => {
+                let text = e.unescape().context("non-UTF8 text in test report")?;
+                append_text(current.as_mut(), text_target.as_deref(), &text);
+            }
+            Ok(Event::CData(e)) => {
+                let text = String::from_utf8_lossy(&e.into_inner()).into_owned();
+                append_text(current.as_mut(), text_target.as_deref(), &text);
+            }
+            Ok(Event::End(e)) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).into_owned();
+                if name == "testcase" {
+                    if let Some(case) = current.take() {
+                        cases.push(case);
+                    }
+                } else if text_target.as_deref() == Some(name.as_str()) {
+                    text_target = None;
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => bail!("XML parse error: {}", e),
+            _ => {}
+        }
+    }
+
+    Ok(cases)
+}
+
+fn start_case(e: &BytesStart) -> Result<TestCase> {
+    Ok(TestCase {
+        class_name: attribute(e, "classname")?.unwrap_or_default(),
+        name: attribute(e, "name")?.unwrap_or_default(),
+        time: attribute(e, "time")?
+            .and_then(|t| t.parse().ok())
+            .unwrap_or(0.0),
+        outcome: TestOutcome::Passed,
+        output: String::new(),
+        unique_id: String::new(),
+    })
+}
+
+fn outcome_for(element: &str, e: &BytesStart) -> Result<TestOutcome> {
+    let message = attribute(e, "message")?;
+    Ok(match element {
+        "skipped" => TestOutcome::Skipped(message),
+        _ => TestOutcome::Failed {
+            message: message.unwrap_or_default(),
+            details: String::new(),
+        },
+    })
+}
+
+fn append_text(case: Option<&mut TestCase>, target: Option<&str>, text: &str) {
+    let case = match case {
+        Some(case) => case,
+        None => return,
+    };
+    match target {
+        Some("failure") | Some("error") => {
+            if let TestOutcome::Failed { details, .. } = &mut case.outcome {
+                details.push_str(text);
+            }
+        }
+        // The first system-out block only carries JUnit's own metadata.
+        Some("system-out") | Some("system-err") => {
+            match text.trim_start().strip_prefix("unique-id:") {
+                Some(metadata) => {
+                    case.unique_id = metadata.lines().next().unwrap_or("").trim().to_string();
+                }
+                None => case.output.push_str(text),
+            }
+        }
+        _ => {}
+    }
+}
+
+fn attribute(e: &BytesStart, key: &str) -> Result<Option<String>> {
+    for attr in e.attributes() {
+        let attr = attr.context("malformed attribute in test report")?;
+        if attr.key.local_name().as_ref() == key.as_bytes() {
+            let value = attr
+                .unescape_value()
+                .context("non-UTF8 attribute in test report")?;
+            return Ok(Some(value.into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+/// Record the failed tests of `run` in `target/failed-tests.txt`, removing
+/// the file when none failed.
+pub fn save_failed(project_root: &Path, run: &TestRun) -> Result<()> {
+    let path = project_root.join(FAILED_TESTS_FILE);
+    let failed: BTreeSet<String> = run
+        .cases
+        .iter()
+        .filter(|case| matches!(case.outcome, TestOutcome::Failed { .. }))
+        .map(TestCase::selector)
+        .collect();
+    if failed.is_empty() {
+        if path.exists() {
+            fs::remove_file(&path)
+                .with_context(|| format!("failed to remove {}", path.display()))?;
+        }
+        return Ok(());
+    }
+    let contents: String = failed.into_iter().map(|s| s + "\n").collect();
+    fs::write(&path, contents).with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// Selectors saved by [`save_failed`]; empty when the last run passed.
+pub fn load_failed(project_root: &Path) -> Result<Vec<String>> {
+    let path = project_root.join(FAILED_TESTS_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents =
+        fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+/// Per-test wall times of recent runs, as stored in [`TIMINGS_FILE`].
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TestTimings {
+    /// Durations in seconds, oldest first, keyed by JUnit Platform unique ID
+    /// (or `class#name` when a report carries none).
+    pub tests: BTreeMap<String, Vec<f64>>,
+}
+
+impl TestTimings {
+    /// Read the history; a missing or unreadable file starts a new one.
+    pub fn load(project_root: &Path) -> Self {
+        fs::read_to_string(project_root.join(TIMINGS_FILE))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, project_root: &Path) -> Result<()> {
+        let path = project_root.join(TIMINGS_FILE);
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(&path, json + "\n").with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// Add the times of the tests that ran in `run`, keeping the last
+    /// [`TIMING_HISTORY`] runs of each.
+    pub fn record(&mut self, run: &TestRun) {
+        for case in &run.cases {
+            if matches!(case.outcome, TestOutcome::Skipped(_)) {
+                continue;
+            }
+            let times = self.tests.entry(timing_key(case)).or_default();
+            times.push(case.time);
+            if times.len() > TIMING_HISTORY {
+                times.drain(..times.len() - TIMING_HISTORY);
+            }
+        }
+    }
+
+    /// Average time of `case` over the recorded runs, with their count.
+    pub fn average(&self, case: &TestCase) -> Option<(f64, usize)> {
+        let times = self.tests.get(&timing_key(case))?;
+        (!times.is_empty()).then(|| (times.iter().sum::<f64>() / times.len() as f64, times.len()))
+    }
+}
+
+fn timing_key(case: &TestCase) -> String {
+    if case.unique_id.is_empty() {
+        format!("{}#{}", case.class_name, case.name)
+    } else {
+        case.unique_id.clone()
+    }
+}
+
+/// The `n` tests of `run` that took longest, slowest first. Skipped tests
+/// are left out.
+pub fn slowest(run: &TestRun, n: usize) -> Vec<&TestCase> {
+    let mut cases: Vec<&TestCase> = run
+        .cases
+        .iter()
+        .filter(|case| !matches!(case.outcome, TestOutcome::Skipped(_)))
+        .collect();
+    cases.sort_by(|a, b| b.time.total_cmp(&a.time));
+    cases.truncate(n);
+    cases
+}
+
+/// Console launcher arguments that shuffle test classes and methods in the
+/// order derived from `seed`. JUnit's random orderers give the same order for
+/// the same seed, so a recorded seed reproduces a run.
+fn order_args(seed: Option<u64>) -> Vec<String> {
+    let Some(seed) = seed else {
+        return Vec::new();
+    };
+    vec![
+        "--config=junit.jupiter.testclass.order.default=org.junit.jupiter.api.ClassOrderer$Random"
+            .to_string(),
+        "--config=junit.jupiter.testmethod.order.default=org.junit.jupiter.api.MethodOrderer$Random"
+            .to_string(),
+        format!("--config=junit.jupiter.execution.order.random.seed={}", seed),
+    ]
+}
+
+#[derive(Serialize)]
+struct Summary<'a> {
+    package: &'a str,
+    tests: usize,
+    passed: usize,
+    failed: usize,
+    skipped: usize,
+    time: f64,
+    /// Seed of a shuffled run, for `--seed`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+    cases: Vec<SummaryCase<'a>>,
+}
+
+#[derive(Serialize)]
+struct SummaryCase<'a> {
+    class: &'a str,
+    name: &'a str,
+    outcome: &'a str,
+    time: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<&'a str>,
+}
+
+/// Write the JSON summary of `run` to `summary.json` in `reports_dir`,
+/// returning its path. A shuffled run records its `seed`.
+pub fn write_summary(
+    reports_dir: &Path,
+    package: &str,
+    run: &TestRun,
+    seed: Option<u64>,
+) -> Result<PathBuf> {
+    let summary = Summary {
+        package,
+        tests: run.cases.len(),
+        passed: run.passed(),
+        failed: run.failed(),
+        skipped: run.skipped(),
+        time: run.total_time(),
+        seed,
+        cases: run
+            .cases
+            .iter()
+            .map(|case| SummaryCase {
+                class: &case.class_name,
+                name: &case.name,
+                outcome: case.outcome.as_str(),
+                time: case.time,
+                message: case.outcome.message(),
+            })
+            .collect(),
+    };
+    fs::create_dir_all(reports_dir)
+        .with_context(|| format!("failed to create {}", reports_dir.display()))?;
+    let path = reports_dir.join(SUMMARY_FILE);
+    let json = serde_json::to_string_pretty(&summary)?;
+    fs::write(&path, json + "\n").with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+fn join_classpath(paths: &[PathBuf]) -> String {
+    #[cfg(windows)]
+    let sep = ";";
+    #[cfg(not(windows))]
+    let sep = ":";
+
+    paths
+        .iter()
+        .map(|p| p.display().to_string())
+        .collect::<Vec<_>>()
+        .join(sep)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPORT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuite name="JUnit Jupiter" tests="3" skipped="1" failures="1" errors="0" time="0.05">
+  <testcase name="testAdd()" classname="myapp.CalcTest" time="0.012">
+    <system-out><![CDATA[
+unique-id: [engine:junit-jupiter]/[class:myapp.CalcTest]/[method:testAdd()]
+display-name: testAdd()
+]]></system-out>
+  </testcase>
+  <testcase name="testDivide()" classname="myapp.CalcTest" time="0.003">
+    <failure message="expected: &lt;2&gt; but was: &lt;3&gt;" type="org.opentest4j.AssertionFailedError"><![CDATA[org.opentest4j.AssertionFailedError: expected: <2> but was: <3>
+	at myapp.CalcTest.testDivide(CalcTest.java:14)
+]]></failure>
+    <system-out><![CDATA[dividing
+]]></system-out>
+  </testcase>
+  <testcase name="testLater()" classname="myapp.CalcTest" time="0">
+    <skipped message="not yet"/>
+  </testcase>
+</testsuite>
+"#;
+
+    #[test]
+    fn test_parse_report() {
+        let cases = parse_report(REPORT).unwrap();
+        assert_eq!(cases.len(), 3);
+
+        assert_eq!(cases[0].class_name, "myapp.CalcTest");
+        assert_eq!(cases[0].name, "testAdd()");
+        assert_eq!(cases[0].outcome, TestOutcome::Passed);
+        assert!(cases[0].output.is_empty());
+
+        match &cases[1].outcome {
+            TestOutcome::Failed { message, details } => {
+                assert_eq!(message, "expected: <2> but was: <3>");
+                assert!(details.contains("CalcTest.java:14"));
+            }
+            other => panic!("expected failure, got {:?}", other),
+        }
+        assert_eq!(cases[1].output.trim(), "dividing");
+
+        assert_eq!(
+            cases[2].outcome,
+            TestOutcome::Skipped(Some("not yet".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_run_counts() {
+        let run = TestRun {
+            cases: parse_report(REPORT).unwrap(),
+            launcher_output: String::new(),
+        };
+        assert_eq!(run.passed(), 1);
+        assert_eq!(run.failed(), 1);
+        assert_eq!(run.skipped(), 1);
+        assert!(!run.success());
+        assert!((run.total_time() - 0.015).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_selector() {
+        let cases = parse_report(REPORT).unwrap();
+        assert_eq!(cases[0].selector(), "myapp.CalcTest#testAdd()");
+        // No metadata in the report
+        assert_eq!(cases[1].selector(), "myapp.CalcTest");
+
+        let case = |unique_id: &str| TestCase {
+            unique_id: unique_id.to_string(),
+            ..cases[0].clone()
+        };
+        assert_eq!(
+            case(
+                "[engine:junit-jupiter]/[class:myapp.CalcTest]/[nested-class:Edge]/[method:zero()]"
+            )
+            .selector(),
+            "myapp.CalcTest$Edge#zero()"
+        );
+        assert_eq!(
+            case("[engine:junit-jupiter]/[class:myapp.CalcTest]/[test-template:add(int, int)]/[test-template-invocation:#2]")
+                .selector(),
+            "myapp.CalcTest#add(int,int)"
+        );
+    }
+
+    #[test]
+    fn test_save_and_load_failed() {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::create_dir_all(tmp.path().join("target")).unwrap();
+        let mut run = TestRun {
+            cases: parse_report(REPORT).unwrap(),
+            launcher_output: String::new(),
+        };
+        save_failed(tmp.path(), &run).unwrap();
+        assert_eq!(load_failed(tmp.path()).unwrap(), ["myapp.CalcTest"]);
+
+        run.cases.retain(|case| case.outcome == TestOutcome::Passed);
+        save_failed(tmp.path(), &run).unwrap();
+        assert!(!tmp.path().join(FAILED_TESTS_FILE).exists());
+        assert!(load_failed(tmp.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_write_summary() {
+        let tmp = tempfile::tempdir().unwrap();
+        let run = TestRun {
+            cases: parse_report(REPORT).unwrap(),
+            launcher_output: String::new(),
+        };
+        let reports_dir = tmp.path().join(REPORTS_DIR);
+        let path = write_summary(&reports_dir, "demo", &run, None).unwrap();
+        assert_eq!(path, tmp.path().join("target/test-reports/summary.json"));
+
+        let summary: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(summary["package"], "demo");
+        assert_eq!(summary["tests"], 3);
+        assert_eq!(summary["failed"], 1);
+        assert_eq!(summary["cases"][0]["outcome"], "passed");
+        assert!(summary["cases"][0].get("message").is_none());
+        assert_eq!(summary["cases"][1]["message"], "expected: <2> but was: <3>");
+        assert_eq!(summary["cases"][2]["outcome"], "skipped");
+        assert!(summary.get("seed").is_none());
+    }
+
+    #[test]
+    fn test_order_args() {
+        assert!(order_args(None).is_empty());
+
+        // The same seed always gives the launcher the same order
+        let args = order_args(Some(42));
+        assert_eq!(args, order_args(Some(42)));
+        assert_eq!(
+            args.last().unwrap(),
+            "--config=junit.jupiter.execution.order.random.seed=42"
+        );
+        let other = order_args(Some(7));
+        assert_eq!(args[..2], other[..2]);
+        assert_ne!(args[2], other[2]);
+
+        // A seed recorded in the summary reproduces the run
+        let tmp = tempfile::tempdir().unwrap();
+        let run = TestRun {
+            cases: parse_report(REPORT).unwrap(),
+            launcher_output: String::new(),
+        };
+        let path = write_summary(tmp.path(), "demo", &run, Some(42)).unwrap();
+        let summary: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(summary["seed"], 42);
+        assert_eq!(order_args(summary["seed"].as_u64()), args);
+    }
+
+    #[test]
+    fn test_timings() {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::create_dir_all(tmp.path().join("target")).unwrap();
+        let mut run = TestRun {
+            cases: parse_report(REPORT).unwrap(),
+            launcher_output: String::new(),
+        };
+        let slow: Vec<&str> = slowest(&run, 5).iter().map(|c| &c.name[..]).collect();
+        assert_eq!(slow, ["testAdd()", "testDivide()"]);
+
+        let mut timings = TestTimings::load(tmp.path());
+        assert_eq!(timings.average(&run.cases[0]), None);
+        for _ in 0..12 {
+            timings.record(&run);
+        }
+        run.cases[0].time = 0.024;
+        timings.record(&run);
+        timings.save(tmp.path()).unwrap();
+
+        let timings = TestTimings::load(tmp.path());
+        assert_eq!(timings.tests.len(), 2);
+        let (average, runs) = timings.average(&run.cases[0]).unwrap();
+        assert_eq!(runs, 10);
+        assert!((average - 0.0132).abs() < 1e-9);
+        assert!(timings.tests.contains_key("myapp.CalcTest#testDivide()"));
+    }
+
+    #[test]
+    fn test_parse_empty_suite() {
+        let xml = r#"<testsuite name="JUnit Jupiter" tests="0"></testsuite>"#;
+        assert!(parse_report(xml).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_testng_suite() {
+        let selection = TestSelection {
+            classes: vec!["myapp.CalcTest".to_string()],
+            methods: vec![
+                "myapp.UtilTest#trims".to_string(),
+                "myapp.UtilTest#splits()".to_string(),
+            ],
+        };
+        let xml = testng_suite(&selection, &["fast".to_string()], &["db".to_string()]);
+        assert!(xml.contains("<include name=\"fast\"/>\n        <exclude name=\"db\"/>"));
+        assert!(xml.contains("<class name=\"myapp.CalcTest\"/>"));
+        assert!(xml.contains(
+            "<class name=\"myapp.UtilTest\">\n        <methods>\n          \
+             <include name=\"trims\"/>\n          <include name=\"splits\"/>"
+        ));
+        assert!(!testng_suite(&selection, &[], &[]).contains("<groups>"));
+    }
+
+    #[test]
+    fn test_check_groups() {
+        let opts = |tag: &str| TestOptions {
+            include_tags: vec![tag.to_string()],
+            ..TestOptions::default()
+        };
+        assert!(check_groups(&opts("integration-db")).is_ok());
+        assert!(check_groups(&opts("!slow")).is_err());
+        assert!(check_groups(&opts("db & fast")).is_err());
+    }
+}
@@ -0,0 +1,397 @@
+use anyhow::{bail, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::errors::JargoError;
+use crate::manifest::JargoToml;
+
+/// Project file pinning the JDK feature release, e.g. `21`.
+pub const VERSION_FILE: &str = ".jargo-java-version";
+
+/// Where a [`Toolchain`] was found.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ToolchainSource {
+    /// The `JARGO_JAVA_HOME` environment variable, which wins over any pin.
+    JargoJavaHome,
+    /// `[toolchain]` in Jargo.toml.
+    Manifest,
+    /// `.jargo-java-version` in the project root.
+    VersionFile,
+    /// The `JAVA_HOME` environment variable.
+    JavaHome,
+    /// `javac` on `PATH`.
+    Path,
+}
+
+impl std::fmt::Display for ToolchainSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ToolchainSource::JargoJavaHome => "JARGO_JAVA_HOME",
+            ToolchainSource::Manifest => "[toolchain] in Jargo.toml",
+            ToolchainSource::VersionFile => VERSION_FILE,
+            ToolchainSource::JavaHome => "JAVA_HOME",
+            ToolchainSource::Path => "PATH",
+        })
+    }
+}
+
+/// The JDK used to run `javac`, `java`, and the other JDK tools.
+#[derive(Debug, Clone)]
+pub struct Toolchain {
+    /// JDK root directory. `None` when tools come from `PATH` and their JDK
+    /// could not be located.
+    pub home: Option<PathBuf>,
+    /// Feature release (e.g. `21`), when it could be determined.
+    pub version: Option<u32>,
+    pub source: ToolchainSource,
+}
+
+impl Toolchain {
+    /// Path of a JDK tool (`java`, `javac`, `jdeps`, ...), or the bare name
+    /// to be looked up on `PATH`.
+    pub fn tool(&self, name: &str) -> PathBuf {
+        match &self.home {
+            Some(home) if self.source != ToolchainSource::Path => {
+                home.join("bin").join(exe_name(name))
+            }
+            _ => PathBuf::from(name),
+        }
+    }
+
+    pub fn java(&self) -> PathBuf {
+        self.tool("java")
+    }
+
+    pub fn javac(&self) -> PathBuf {
+        self.tool("javac")
+    }
+}
+
+/// Find the JDK for a project and check that it can compile `package.java`.
+///
+/// Lookup order: `JARGO_JAVA_HOME`, `[toolchain] java-home`, `[toolchain]
+/// version`, the `.jargo-java-version` file, `JAVA_HOME` (which `java-home`
+/// in the global config sets when unset), then `javac` on `PATH`. A pinned
+/// version must match the JDK's feature release exactly. With a `[build]
+/// compiler` that runs on the JVM, such as ECJ, a JRE will do.
+pub fn resolve(project_root: &Path, manifest: &JargoToml) -> Result<Toolchain> {
+    let toolchain = find(project_root, manifest)?;
+
+    if let (Some(found), Ok(required)) =
+        (toolchain.version, feature_release(&manifest.package.java))
+    {
+        if found < required {
+            return Err(JargoError::JdkTooOld {
+                found,
+                required: manifest.package.java.clone(),
+                origin: toolchain.source.to_string(),
+            }
+            .into());
+        }
+    }
+
+    Ok(toolchain)
+}
+
+/// The JDK from `JARGO_JAVA_HOME` or `JAVA_HOME`, else `PATH`, ignoring any
+/// project pin. Used outside of projects.
+pub fn from_environment() -> Result<Toolchain> {
+    environment_with("javac")
+}
+
+/// [`from_environment`], for a JDK (or JRE) providing the `compiler` tool.
+fn environment_with(compiler: &str) -> Result<Toolchain> {
+    if let Some(toolchain) = from_jargo_java_home(compiler) {
+        return toolchain;
+    }
+    match std::env::var_os("JAVA_HOME").filter(|h| !h.is_empty()) {
+        Some(home) => from_home(PathBuf::from(home), ToolchainSource::JavaHome, compiler),
+        None => Ok(from_path()),
+    }
+}
+
+fn from_jargo_java_home(compiler: &str) -> Option<Result<Toolchain>> {
+    let home = std::env::var_os("JARGO_JAVA_HOME").filter(|h| !h.is_empty())?;
+    Some(from_home(
+        PathBuf::from(home),
+        ToolchainSource::JargoJavaHome,
+        compiler,
+    ))
+}
+
+fn find(project_root: &Path, manifest: &JargoToml) -> Result<Toolchain> {
+    let compiler = manifest.get_compiler().jdk_tool();
+    if let Some(toolchain) = from_jargo_java_home(compiler) {
+        return toolchain;
+    }
+    if let Some(config) = &manifest.toolchain {
+        if let Some(home) = &config.java_home {
+            return from_home(project_root.join(home), ToolchainSource::Manifest, compiler);
+        }
+        if let Some(version) = &config.version {
+            return find_installed(version, ToolchainSource::Manifest, compiler);
+        }
+    }
+
+    let version_file = project_root.join(VERSION_FILE);
+    if let Ok(content) = fs::read_to_string(&version_file) {
+        let version = content.trim();
+        if !version.is_empty() {
+            return find_installed(version, ToolchainSource::VersionFile, compiler);
+        }
+    }
+
+    environment_with(compiler)
+}
+
+/// The JDK at `home`, which must have `bin/{compiler}`.
+fn from_home(home: PathBuf, source: ToolchainSource, compiler: &str) -> Result<Toolchain> {
+    if !home.join("bin").join(exe_name(compiler)).is_file() {
+        bail!(
+            "no JDK found at `{}` (from {}): bin/{} is missing",
+            home.display(),
+            source,
+            compiler
+        );
+    }
+    Ok(Toolchain {
+        version: release_version(&home)
+            .or_else(|| javac_version(&home.join("bin").join(exe_name("javac")))),
+        home: Some(home),
+        source,
+    })
+}
+
+fn from_path() -> Toolchain {
+    let javac = find_on_path(&exe_name("javac"));
+    // bin/javac → JDK root, following symlinks such as /usr/bin/javac
+    let home = javac
+        .as_ref()
+        .and_then(|p| fs::canonicalize(p).ok())
+        .and_then(|p| Some(p.parent()?.parent()?.to_path_buf()));
+    let version = home
+        .as_deref()
+        .and_then(release_version)
+        .or_else(|| javac.as_ref().and_then(|p| javac_version(p)));
+    Toolchain {
+        home,
+        version,
+        source: ToolchainSource::Path,
+    }
+}
+
+/// Search standard install locations for a JDK with the given feature release.
+fn find_installed(version: &str, source: ToolchainSource, compiler: &str) -> Result<Toolchain> {
+    let wanted = feature_release(version)
+        .map_err(|_| anyhow::anyhow!("invalid JDK version `{}` in {}", version, source))?;
+
+    let mut candidates = Vec::new();
+    if let Some(home) = std::env::var_os("JAVA_HOME").filter(|h| !h.is_empty()) {
+        candidates.push(PathBuf::from(home));
+    }
+    for dir in install_dirs() {
+        if let Ok(entries) = fs::read_dir(&dir) {
+            let mut homes: Vec<PathBuf> = entries
+                .filter_map(|e| e.ok())
+                .map(|e| {
+                    let path = e.path();
+                    // macOS bundles keep the JDK under Contents/Home
+                    let mac_home = path.join("Contents/Home");
+                    if mac_home.is_dir() {
+                        mac_home
+                    } else {
+                        path
+                    }
+                })
+                .collect();
+            homes.sort();
+            candidates.extend(homes);
+        }
+    }
+
+    for home in candidates {
+        if release_version(&home) == Some(wanted)
+            && home.join("bin").join(exe_name(compiler)).is_file()
+        {
+            return Ok(Toolchain {
+                home: Some(home),
+                version: Some(wanted),
+                source,
+            });
+        }
+    }
+
+    Err(JargoError::JdkNotFound {
+        version: version.to_string(),
+        origin: source.to_string(),
+    }
+    .into())
+}
+
+/// Directories whose children are JDK installs.
+fn install_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![
+        PathBuf::from("/usr/lib/jvm"),
+        PathBuf::from("/usr/java"),
+        PathBuf::from("/opt/java"),
+        PathBuf::from("/Library/Java/JavaVirtualMachines"),
+        PathBuf::from("C:\\Program Files\\Java"),
+        PathBuf::from("C:\\Program Files\\Eclipse Adoptium"),
+    ];
+    if let Some(home) = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE")) {
+        let home = PathBuf::from(home);
+        dirs.push(home.join(".sdkman/candidates/java"));
+        dirs.push(home.join(".jdks"));
+    }
+    dirs
+}
+
+/// Read the feature release from a JDK's `release` file
+/// (`JAVA_VERSION="21.0.2"`).
+fn release_version(home: &Path) -> Option<u32> {
+    let content = fs::read_to_string(home.join("release")).ok()?;
+    parse_release_file(&content)
+}
+
+fn parse_release_file(content: &str) -> Option<u32> {
+    content.lines().find_map(|line| {
+        let value = line.strip_prefix("JAVA_VERSION=")?;
+        feature_release(value.trim_matches('"')).ok()
+    })
+}
+
+/// Ask `javac -version` (`javac 17.0.9`) when no `release` file is available.
+pub(crate) fn javac_version(javac: &Path) -> Option<u32> {
+    let output = Command::new(javac).arg("-version").output().ok()?;
+    let text = String::from_utf8_lossy(&output.stdout).into_owned()
+        + &String::from_utf8_lossy(&output.stderr);
+    let version = text.split_whitespace().nth(1)?;
+    feature_release(version).ok()
+}
+
+/// `"21"`, `"21.0.2"`, `"17-ea"` → 17/21; legacy `"1.8.0_392"` → 8.
+pub(crate) fn feature_release(version: &str) -> Result<u32> {
+    let version = version.strip_prefix("1.").unwrap_or(version);
+    let major: String = version.chars().take_while(|c| c.is_ascii_digit()).collect();
+    major
+        .parse()
+        .map_err(|_| anyhow::anyhow!("invalid Java version `{}`", version))
+}
+
+fn find_on_path(name: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(name))
+        .find(|candidate| candidate.is_file())
+}
+
+fn exe_name(name: &str) -> String {
+    if cfg!(windows) {
+        format!("{}.exe", name)
+    } else {
+        name.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fake_jdk(dir: &Path, version: &str) -> PathBuf {
+        fs::create_dir_all(dir.join("bin")).unwrap();
+        fs::write(dir.join("bin").join(exe_name("javac")), "").unwrap();
+        fs::write(
+            dir.join("release"),
+            format!("IMPLEMENTOR=\"Test\"\nJAVA_VERSION=\"{}\"\n", version),
+        )
+        .unwrap();
+        dir.to_path_buf()
+    }
+
+    fn manifest(extra: &str) -> JargoToml {
+        toml::from_str(&format!(
+            "[package]\nname = \"demo\"\nversion = \"1.0.0\"\njava = \"17\"\n{}",
+            extra
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_feature_release() {
+        assert_eq!(feature_release("21").unwrap(), 21);
+        assert_eq!(feature_release("21.0.2").unwrap(), 21);
+        assert_eq!(feature_release("17-ea").unwrap(), 17);
+        assert_eq!(feature_release("1.8.0_392").unwrap(), 8);
+        assert!(feature_release("latest").is_err());
+    }
+
+    #[test]
+    fn test_parse_release_file() {
+        assert_eq!(
+            parse_release_file("IMPLEMENTOR=\"Eclipse Adoptium\"\nJAVA_VERSION=\"21.0.2\"\n"),
+            Some(21)
+        );
+        assert_eq!(parse_release_file("IMPLEMENTOR=\"x\"\n"), None);
+    }
+
+    #[test]
+    fn test_manifest_java_home() {
+        let temp = tempfile::TempDir::new().unwrap();
+        fake_jdk(&temp.path().join("jdk"), "21.0.1");
+
+        let m = manifest("[toolchain]\njava-home = \"jdk\"\n");
+        let toolchain = resolve(temp.path(), &m).unwrap();
+
+        assert_eq!(toolchain.source, ToolchainSource::Manifest);
+        assert_eq!(toolchain.version, Some(21));
+        assert_eq!(
+            toolchain.javac(),
+            temp.path().join("jdk/bin").join(exe_name("javac"))
+        );
+    }
+
+    #[test]
+    fn test_jdk_older_than_release_fails() {
+        let temp = tempfile::TempDir::new().unwrap();
+        fake_jdk(&temp.path().join("jdk"), "11.0.20");
+
+        let m = manifest("[toolchain]\njava-home = \"jdk\"\n");
+        let err = resolve(temp.path(), &m).unwrap_err().to_string();
+
+        assert!(
+            err.contains("project requires Java 17 but found javac 11"),
+            "{}",
+            err
+        );
+        assert!(err.contains("`[toolchain]`"), "{}", err);
+    }
+
+    #[test]
+    fn test_missing_java_home_fails() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let m = manifest("[toolchain]\njava-home = \"nope\"\n");
+        assert!(resolve(temp.path(), &m).is_err());
+    }
+
+    #[test]
+    fn test_unknown_pinned_version_fails() {
+        let temp = tempfile::TempDir::new().unwrap();
+        fs::write(temp.path().join(VERSION_FILE), "4\n").unwrap();
+
+        let err = resolve(temp.path(), &manifest("")).unwrap_err().to_string();
+
+        assert!(err.contains("no JDK 4 found"), "{}", err);
+        assert!(err.contains(VERSION_FILE), "{}", err);
+    }
+
+    #[test]
+    fn test_path_toolchain_uses_bare_names() {
+        let toolchain = Toolchain {
+            home: Some(PathBuf::from("/usr/lib/jvm/jdk-21")),
+            version: Some(21),
+            source: ToolchainSource::Path,
+        };
+        assert_eq!(toolchain.java(), PathBuf::from("java"));
+    }
+}
@@ -0,0 +1,433 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+
+use crate::bytecode;
+use crate::manifest::JargoToml;
+
+/// A packaging problem found by [`verify_jar`].
+#[derive(Debug, PartialEq)]
+pub enum JarProblem {
+    /// App JAR without `META-INF/MANIFEST.MF` or without a `Main-Class` entry.
+    MissingMainClassAttribute,
+    /// `Main-Class` names a different class than the manifest declares.
+    MainClassMismatch { expected: String, found: String },
+    /// `Main-Class` points at a class file that is not in the JAR.
+    MainClassNotFound(String),
+    /// A `[[bin]]` main class is not in the JAR.
+    BinClassNotFound { bin: String, class: String },
+    /// A `[verify] resources` entry is missing.
+    MissingResource(String),
+    /// The same entry name appears more than once.
+    DuplicateEntry { name: String, count: usize },
+    /// A class file targets a different release than `package.java`.
+    BytecodeVersion {
+        class: String,
+        found: u32,
+        expected: u32,
+    },
+}
+
+impl fmt::Display for JarProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JarProblem::MissingMainClassAttribute => {
+                write!(f, "META-INF/MANIFEST.MF has no Main-Class entry")
+            }
+            JarProblem::MainClassMismatch { expected, found } => {
+                write!(f, "Main-Class is `{}`, expected `{}`", found, expected)
+            }
+            JarProblem::MainClassNotFound(class) => {
+                write!(f, "Main-Class `{}` is not in the JAR", class)
+            }
+            JarProblem::BinClassNotFound { bin, class } => {
+                write!(
+                    f,
+                    "main class `{}` of bin `{}` is not in the JAR",
+                    class, bin
+                )
+            }
+            JarProblem::MissingResource(path) => {
+                write!(f, "required resource `{}` is missing", path)
+            }
+            JarProblem::DuplicateEntry { name, count } => {
+                write!(f, "`{}` appears {} times", name, count)
+            }
+            JarProblem::BytecodeVersion {
+                class,
+                found,
+                expected,
+            } => write!(
+                f,
+                "`{}` targets Java {}, expected Java {}",
+                class, found, expected
+            ),
+        }
+    }
+}
+
+/// Check a built JAR against what the manifest declares:
+///
+/// - app JARs name the configured main class in `Main-Class`, and that class
+///   is in the JAR; with several `[[bin]]` targets, each main class is in the
+///   JAR instead
+/// - every `[verify] resources` path is present
+/// - no entry name is duplicated
+/// - every class file targets exactly `package.java`
+///
+/// Returns the problems found, in that order; an empty list means the JAR
+/// passed.
+pub fn verify_jar(jar: &Path, manifest: &JargoToml) -> Result<Vec<JarProblem>> {
+    // The zip reader keeps one entry per name, so count names from the
+    // central directory itself.
+    let bytes = fs::read(jar).with_context(|| format!("failed to read {}", jar.display()))?;
+    let names = central_directory_names(&bytes)
+        .with_context(|| format!("{} is not a valid JAR", jar.display()))?;
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    let mut order = Vec::new();
+    for name in &names {
+        let count = counts.entry(name.as_str()).or_insert(0);
+        *count += 1;
+        if *count == 1 {
+            order.push(name.as_str());
+        }
+    }
+
+    let file = File::open(jar).with_context(|| format!("failed to open {}", jar.display()))?;
+    let mut archive =
+        zip::ZipArchive::new(file).with_context(|| format!("failed to read {}", jar.display()))?;
+    let mut manifest_mf = None;
+    let mut versions = Vec::new();
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        let name = entry.name().to_string();
+        if name == "META-INF/MANIFEST.MF" {
+            let mut content = String::new();
+            entry
+                .read_to_string(&mut content)
+                .with_context(|| format!("failed to read {} in {}", name, jar.display()))?;
+            manifest_mf = Some(content);
+        } else if name.ends_with(".class") && !bytecode::is_versioned_entry(&name) {
+            let mut header = [0u8; 8];
+            if entry.read_exact(&mut header).is_ok() {
+                if let Some(major) = bytecode::class_major_version(&header) {
+                    versions.push((name, major));
+                }
+            }
+        }
+    }
+
+    let mut problems = Vec::new();
+
+    let bins = if manifest.is_app() {
+        manifest.get_bins()?
+    } else {
+        Vec::new()
+    };
+    if let [bin] = bins.as_slice() {
+        let expected = manifest.main_class_of(bin);
+        match manifest_mf.as_deref().and_then(main_class_attribute) {
+            None => problems.push(JarProblem::MissingMainClassAttribute),
+            Some(found) if found != expected => {
+                problems.push(JarProblem::MainClassMismatch { expected, found })
+            }
+            Some(found) => {
+                let entry = format!("{}.class", found.replace('.', "/"));
+                if !counts.contains_key(entry.as_str()) {
+                    problems.push(JarProblem::MainClassNotFound(found));
+                }
+            }
+        }
+    } else {
+        for bin in &bins {
+            let class = manifest.main_class_of(bin);
+            let entry = format!("{}.class", class.replace('.', "/"));
+            if !counts.contains_key(entry.as_str()) {
+                problems.push(JarProblem::BinClassNotFound {
+                    bin: bin.name.clone(),
+                    class,
+                });
+            }
+        }
+    }
+
+    for resource in manifest.get_verify_resources() {
+        let entry = resource.trim_start_matches('/');
+        if !counts.contains_key(entry) {
+            problems.push(JarProblem::MissingResource(resource.clone()));
+        }
+    }
+
+    for name in order {
+        let count = counts[name];
+        if count > 1 {
+            problems.push(JarProblem::DuplicateEntry {
+                name: name.to_string(),
+                count,
+            });
+        }
+    }
+
+    if let Some(expected) = bytecode::parse_release(&manifest.package.java) {
+        for (name, major) in versions {
+            if major != bytecode::major_of(expected) {
+                problems.push(JarProblem::BytecodeVersion {
+                    class: name,
+                    found: bytecode::release_of(major),
+                    expected,
+                });
+            }
+        }
+    }
+
+    Ok(problems)
+}
+
+/// Entry names in central directory order, duplicates included.
+///
+/// Reads the end-of-central-directory record, then walks the central file
+/// headers. ZIP64 archives are not supported.
+fn central_directory_names(bytes: &[u8]) -> Option<Vec<String>> {
+    const EOCD_SIG: [u8; 4] = [0x50, 0x4b, 0x05, 0x06];
+    const CENTRAL_SIG: [u8; 4] = [0x50, 0x4b, 0x01, 0x02];
+    let u16_at = |at: usize| Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?));
+    let u32_at = |at: usize| Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?));
+
+    // The record is 22 bytes plus a comment of up to 64 KiB.
+    let eocd = (0..=bytes.len().checked_sub(22)?)
+        .rev()
+        .take(22 + usize::from(u16::MAX))
+        .find(|&at| bytes[at..at + 4] == EOCD_SIG)?;
+    let total = usize::from(u16_at(eocd + 10)?);
+    let mut at = usize::try_from(u32_at(eocd + 16)?).ok()?;
+
+    let mut names = Vec::with_capacity(total);
+    for _ in 0..total {
+        if bytes.get(at..at + 4)? != CENTRAL_SIG {
+            return None;
+        }
+        let name_len = usize::from(u16_at(at + 28)?);
+        let extra_len = usize::from(u16_at(at + 30)?);
+        let comment_len = usize::from(u16_at(at + 32)?);
+        let name = bytes.get(at + 46..at + 46 + name_len)?;
+        names.push(String::from_utf8_lossy(name).into_owned());
+        at += 46 + name_len + extra_len + comment_len;
+    }
+    Some(names)
+}
+
+/// The `Main-Class` of a JAR's manifest, if it has one.
+pub fn jar_main_class(jar: &Path) -> Result<Option<String>> {
+    let file = File::open(jar).with_context(|| format!("failed to open {}", jar.display()))?;
+    let mut archive =
+        zip::ZipArchive::new(file).with_context(|| format!("failed to read {}", jar.display()))?;
+    let Ok(mut entry) = archive.by_name("META-INF/MANIFEST.MF") else {
+        return Ok(None);
+    };
+    let mut content = String::new();
+    entry
+        .read_to_string(&mut content)
+        .with_context(|| format!("failed to read the manifest of {}", jar.display()))?;
+    Ok(main_class_attribute(&content))
+}
+
+/// Read `Main-Class` from MANIFEST.MF, joining 72-byte continuation lines.
+fn main_class_attribute(content: &str) -> Option<String> {
+    let mut value: Option<String> = None;
+    for line in content.lines() {
+        let line = line.trim_end_matches('\r');
+        match &mut value {
+            Some(v) if line.starts_with(' ') => v.push_str(&line[1..]),
+            Some(_) => break,
+            None => {
+                if let Some(rest) = line.strip_prefix("Main-Class:") {
+                    value = Some(rest.trim_start().to_string());
+                }
+            }
+        }
+    }
+    value.map(|v| v.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn manifest(extra: &str) -> JargoToml {
+        toml::from_str(&format!(
+            "[package]\nname = \"demo\"\nversion = \"1.0.0\"\njava = \"21\"\n{}",
+            extra
+        ))
+        .unwrap()
+    }
+
+    fn class_bytes(release: u16) -> Vec<u8> {
+        let mut bytes = vec![0xCA, 0xFE, 0xBA, 0xBE, 0, 0];
+        bytes.extend_from_slice(&(release + 44).to_be_bytes());
+        bytes
+    }
+
+    fn write_jar(path: &Path, entries: &[(&str, &[u8])]) {
+        let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        for (name, content) in entries {
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(content).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn test_main_class_attribute() {
+        assert_eq!(
+            main_class_attribute("Manifest-Version: 1.0\r\nMain-Class: demo.Main\r\n"),
+            Some("demo.Main".to_string())
+        );
+        assert_eq!(
+            main_class_attribute("Main-Class: com.example.very.long\n .Main\nX: y\n"),
+            Some("com.example.very.long.Main".to_string())
+        );
+        assert_eq!(main_class_attribute("Manifest-Version: 1.0\n"), None);
+    }
+
+    #[test]
+    fn test_valid_jar_passes() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let jar = temp.path().join("demo.jar");
+        write_jar(
+            &jar,
+            &[
+                (
+                    "META-INF/MANIFEST.MF",
+                    b"Manifest-Version: 1.0\nMain-Class: demo.Main\n",
+                ),
+                ("demo/Main.class", &class_bytes(21)),
+                ("META-INF/versions/22/demo/Fast.class", &class_bytes(22)),
+                ("config/app.properties", b"x=1"),
+            ],
+        );
+
+        let m = manifest("[verify]\nresources = [\"config/app.properties\"]\n");
+        assert_eq!(verify_jar(&jar, &m).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_reports_every_problem() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let jar = temp.path().join("demo.jar");
+        write_jar(
+            &jar,
+            &[
+                (
+                    "META-INF/MANIFEST.MF",
+                    b"Manifest-Version: 1.0\nMain-Class: demo.Main\n",
+                ),
+                ("demo/Util.class", &class_bytes(17)),
+            ],
+        );
+
+        let m = manifest("[verify]\nresources = [\"logback.xml\"]\n");
+        assert_eq!(
+            verify_jar(&jar, &m).unwrap(),
+            vec![
+                JarProblem::MainClassNotFound("demo.Main".to_string()),
+                JarProblem::MissingResource("logback.xml".to_string()),
+                JarProblem::BytecodeVersion {
+                    class: "demo/Util.class".to_string(),
+                    found: 17,
+                    expected: 21,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_entries() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let jar = temp.path().join("demo.jar");
+        write_jar(
+            &jar,
+            &[
+                (
+                    "META-INF/MANIFEST.MF",
+                    b"Manifest-Version: 1.0\nMain-Class: demo.Main\n",
+                ),
+                ("demo/Main.class", &class_bytes(21)),
+                ("demo/Mbin.class", &class_bytes(21)),
+            ],
+        );
+        // ZipWriter refuses duplicate names; rename the second entry in place
+        // (local and central headers).
+        let mut bytes = fs::read(&jar).unwrap();
+        let needle = b"demo/Mbin.class";
+        for i in 0..bytes.len() - needle.len() {
+            if &bytes[i..i + needle.len()] == needle {
+                bytes[i + 6] = b'a';
+            }
+        }
+        fs::write(&jar, bytes).unwrap();
+
+        assert_eq!(
+            verify_jar(&jar, &manifest("")).unwrap(),
+            vec![JarProblem::DuplicateEntry {
+                name: "demo/Main.class".to_string(),
+                count: 2,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_lib_without_main_class() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let jar = temp.path().join("demo.jar");
+        write_jar(
+            &jar,
+            &[("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\n")],
+        );
+
+        assert_eq!(
+            verify_jar(&jar, &manifest("")).unwrap(),
+            vec![JarProblem::MissingMainClassAttribute]
+        );
+
+        let m = manifest("").package;
+        let lib = JargoToml {
+            package: crate::manifest::PackageManifest {
+                project_type: "lib".to_string(),
+                ..m
+            },
+            ..JargoToml::new_lib("demo", "demo")
+        };
+        assert_eq!(verify_jar(&jar, &lib).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_several_bins() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let jar = temp.path().join("demo.jar");
+        write_jar(
+            &jar,
+            &[
+                ("META-INF/MANIFEST.MF", b"Manifest-Version: 1.0\n"),
+                ("demo/Main.class", &class_bytes(21)),
+            ],
+        );
+
+        let m = manifest(
+            "[[bin]]\nname = \"demo\"\nmain-class = \"Main\"\n\n\
+             [[bin]]\nname = \"tool\"\nmain-class = \"Tool\"\n",
+        );
+        assert_eq!(
+            verify_jar(&jar, &m).unwrap(),
+            vec![JarProblem::BinClassNotFound {
+                bin: "tool".to_string(),
+                class: "demo.Tool".to_string(),
+            }]
+        );
+    }
+}
@@ -0,0 +1,206 @@
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::affected::git;
+
+/// How `jargo version` changes `package.version`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Bump {
+    Patch,
+    Minor,
+    Major,
+    /// Set this version as written.
+    Exact(String),
+}
+
+impl FromStr for Bump {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "patch" => Bump::Patch,
+            "minor" => Bump::Minor,
+            "major" => Bump::Major,
+            _ if s.is_empty() || s.contains(char::is_whitespace) => {
+                bail!("`{}` is not a version or one of patch, minor, major", s)
+            }
+            _ => Bump::Exact(s.to_string()),
+        })
+    }
+}
+
+/// The version after applying `bump` to `current`.
+///
+/// `current` is `MAJOR[.MINOR[.PATCH]]` with an optional `-pre-release` or
+/// `+build` suffix. A patch bump of a pre-release releases it
+/// (`1.2.0-SNAPSHOT` → `1.2.0`); minor and major bumps reset the lower
+/// components and drop the suffix.
+pub fn next_version(current: &str, bump: &Bump) -> Result<String> {
+    if let Bump::Exact(version) = bump {
+        return Ok(version.clone());
+    }
+    let numeric_end = current.find(['-', '+']).unwrap_or(current.len());
+    let (numeric, suffix) = current.split_at(numeric_end);
+    let parts = numeric
+        .split('.')
+        .map(|p| p.parse::<u64>().ok())
+        .collect::<Option<Vec<_>>>()
+        .filter(|parts| parts.len() <= 3);
+    let Some(mut parts) = parts else {
+        bail!(
+            "cannot bump version `{}`: expected up to three numeric components",
+            current
+        );
+    };
+    parts.resize(3, 0);
+    match bump {
+        Bump::Patch if suffix.is_empty() => parts[2] += 1,
+        Bump::Patch => {}
+        Bump::Minor => parts = vec![parts[0], parts[1] + 1, 0],
+        Bump::Major => parts = vec![parts[0] + 1, 0, 0],
+        Bump::Exact(_) => unreachable!(),
+    }
+    Ok(format!("{}.{}.{}", parts[0], parts[1], parts[2]))
+}
+
+/// Replace `package.version` in the Jargo.toml at `manifest_path`, keeping
+/// the rest of the file as written.
+pub fn set_version(manifest_path: &Path, version: &str) -> Result<()> {
+    let content = fs::read_to_string(manifest_path)
+        .with_context(|| format!("failed to read {}", manifest_path.display()))?;
+    let mut doc: toml_edit::DocumentMut = content
+        .parse()
+        .with_context(|| format!("failed to parse {}", manifest_path.display()))?;
+    let package = doc["package"]
+        .as_table_like_mut()
+        .with_context(|| format!("{} has no [package] table", manifest_path.display()))?;
+    // Keep the comments and spacing around the old value
+    let decor = package
+        .get("version")
+        .and_then(|v| v.as_value())
+        .map(|v| v.decor().clone());
+    let mut value = toml_edit::Value::from(version);
+    if let Some(decor) = decor {
+        *value.decor_mut() = decor;
+    }
+    package.insert("version", toml_edit::Item::Value(value));
+    fs::write(manifest_path, doc.to_string())
+        .with_context(|| format!("failed to write {}", manifest_path.display()))
+}
+
+/// The git tag of `version` of package `name`: `v{version}`, or
+/// `{name}-v{version}` in a workspace, where members are tagged apart.
+pub fn tag_name(name: &str, version: &str, in_workspace: bool) -> String {
+    if in_workspace {
+        format!("{}-v{}", name, version)
+    } else {
+        format!("v{}", version)
+    }
+}
+
+/// Fail when one of `tags` already exists in the git repository at `repo`.
+pub fn check_tags_unused(repo: &Path, tags: &[String]) -> Result<()> {
+    for tag in tags {
+        if git(repo, &["tag", "--list", tag])?.trim() == tag {
+            bail!("git tag `{}` already exists", tag);
+        }
+    }
+    Ok(())
+}
+
+/// Fail when tracked files in the git repository at `repo` have uncommitted
+/// changes, which a release commit would leave out.
+pub fn check_clean(repo: &Path) -> Result<()> {
+    let status = git(repo, &["status", "--porcelain", "--untracked-files=no"])?;
+    if !status.trim().is_empty() {
+        bail!(
+            "the git working tree has uncommitted changes:\n{}",
+            status.trim_end()
+        );
+    }
+    Ok(())
+}
+
+/// Commit the bumped `manifests` in the git repository at `repo` with
+/// `message`, leaving other changes alone, then create an annotated tag for
+/// each of `tags`. With no `manifests`, only the tags are created.
+pub fn commit_and_tag(
+    repo: &Path,
+    manifests: &[PathBuf],
+    message: &str,
+    tags: &[String],
+) -> Result<()> {
+    let paths: Vec<&str> = manifests
+        .iter()
+        .map(|p| p.to_str().context("manifest path is not valid UTF-8"))
+        .collect::<Result<_>>()?;
+    if !paths.is_empty() {
+        let mut add = vec!["add", "--"];
+        add.extend(&paths);
+        git(repo, &add)?;
+        let mut commit = vec!["commit", "-m", message, "--"];
+        commit.extend(&paths);
+        git(repo, &commit)?;
+    }
+    for tag in tags {
+        git(repo, &["tag", "-a", tag, "-m", message])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bump() {
+        assert_eq!("patch".parse::<Bump>().unwrap(), Bump::Patch);
+        assert_eq!("major".parse::<Bump>().unwrap(), Bump::Major);
+        assert_eq!(
+            "2.0.0-rc.1".parse::<Bump>().unwrap(),
+            Bump::Exact("2.0.0-rc.1".to_string())
+        );
+        assert!("1 2".parse::<Bump>().is_err());
+    }
+
+    #[test]
+    fn test_next_version() {
+        let next = |current: &str, bump: Bump| next_version(current, &bump).unwrap();
+        assert_eq!(next("1.2.3", Bump::Patch), "1.2.4");
+        assert_eq!(next("1.2.3", Bump::Minor), "1.3.0");
+        assert_eq!(next("1.2.3", Bump::Major), "2.0.0");
+        assert_eq!(next("1.2.0-SNAPSHOT", Bump::Patch), "1.2.0");
+        assert_eq!(next("1.2.0-SNAPSHOT", Bump::Minor), "1.3.0");
+        assert_eq!(next("0.1", Bump::Patch), "0.1.1");
+        assert_eq!(
+            next("1.0.0", Bump::Exact("1.1.0-beta".into())),
+            "1.1.0-beta"
+        );
+        assert!(next_version("1.x", &Bump::Patch).is_err());
+        assert!(next_version("1.2.3.4", &Bump::Patch).is_err());
+    }
+
+    #[test]
+    fn test_tag_name() {
+        assert_eq!(tag_name("demo", "1.2.0", false), "v1.2.0");
+        assert_eq!(tag_name("core", "1.2.0", true), "core-v1.2.0");
+    }
+
+    #[test]
+    fn test_set_version() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("Jargo.toml");
+        fs::write(
+            &path,
+            "[package]\nname = \"demo\"\nversion = \"0.1.0\" # bumped by jargo version\njava = \"21\"\n",
+        )
+        .unwrap();
+        set_version(&path, "0.2.0").unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "[package]\nname = \"demo\"\nversion = \"0.2.0\" # bumped by jargo version\njava = \"21\"\n"
+        );
+    }
+}
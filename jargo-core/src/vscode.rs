@@ -0,0 +1,248 @@
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::eclipse::Library;
+use crate::manifest::JargoToml;
+
+/// Name prefix of the launch configurations jargo owns. Regenerating
+/// replaces these and keeps every other configuration.
+pub const LAUNCH_PREFIX: &str = "jargo: ";
+
+/// Where the Java extension writes classes, apart from jargo's own output.
+const OUTPUT_PATH: &str = "target/vscode/classes";
+
+/// Source sets the Java extension sees, with the staging directory that
+/// gives each the package directories of its base package. Benchmarks are
+/// left out, since they need JMH.
+pub const SOURCE_SETS: [(&str, &str); 4] = [
+    ("src", "src-root"),
+    ("test", "test-src-root"),
+    ("it", "it-src-root"),
+    ("examples", "examples-src-root"),
+];
+
+/// Merge jargo's entries into `.vscode/settings.json` and
+/// `.vscode/launch.json`, creating them when missing, and return their
+/// paths. `source_roots` are the staged source directories, relative to
+/// `project_root`.
+pub fn write(
+    project_root: &Path,
+    manifest: &JargoToml,
+    source_roots: &[String],
+    libraries: &[Library],
+) -> Result<Vec<PathBuf>> {
+    let dir = project_root.join(".vscode");
+    fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+
+    let settings_path = dir.join("settings.json");
+    let settings = update_settings(read_json(&settings_path)?, source_roots, libraries);
+    write_json(&settings_path, &settings)?;
+
+    let launch_path = dir.join("launch.json");
+    let launch = update_launch(read_json(&launch_path)?, launch_configurations(manifest)?)?;
+    write_json(&launch_path, &launch)?;
+    Ok(vec![settings_path, launch_path])
+}
+
+/// The object in a JSON file, or `None` when it does not exist.
+fn read_json(path: &Path) -> Result<Option<Map<String, Value>>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let text =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    match serde_json::from_str(&text) {
+        Ok(Value::Object(object)) => Ok(Some(object)),
+        Ok(_) => bail!("{} does not hold a JSON object", path.display()),
+        Err(e) => bail!(
+            "failed to parse {} (comments and trailing commas are not supported): {}",
+            path.display(),
+            e
+        ),
+    }
+}
+
+fn write_json(path: &Path, object: &Map<String, Value>) -> Result<()> {
+    let mut text = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b"    ");
+    let mut serializer = serde_json::Serializer::with_formatter(&mut text, formatter);
+    serde::Serialize::serialize(object, &mut serializer)?;
+    text.push(b'\n');
+    fs::write(path, text).with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// `settings` with the Java extension's source paths, output path, and
+/// referenced libraries (with their sources) set; other keys are kept.
+fn update_settings(
+    settings: Option<Map<String, Value>>,
+    source_roots: &[String],
+    libraries: &[Library],
+) -> Map<String, Value> {
+    let mut settings = settings.unwrap_or_default();
+    let include: Vec<String> = libraries
+        .iter()
+        .map(|l| l.jar.to_string_lossy().into_owned())
+        .collect();
+    let sources: BTreeMap<String, String> = libraries
+        .iter()
+        .filter_map(|l| {
+            let sources = l.sources.as_ref()?;
+            Some((
+                l.jar.to_string_lossy().into_owned(),
+                sources.to_string_lossy().into_owned(),
+            ))
+        })
+        .collect();
+    settings.insert("java.project.sourcePaths".into(), json!(source_roots));
+    settings.insert("java.project.outputPath".into(), json!(OUTPUT_PATH));
+    settings.insert(
+        "java.project.referencedLibraries".into(),
+        json!({ "include": include, "sources": sources }),
+    );
+    settings
+}
+
+/// A debug launch configuration per entry point of an app, running with the
+/// `[run]` JVM arguments and system properties. Libraries have none.
+fn launch_configurations(manifest: &JargoToml) -> Result<Vec<Value>> {
+    if !manifest.is_app() {
+        return Ok(Vec::new());
+    }
+    let mut vm_args: Vec<String> = manifest.get_jvm_args().to_vec();
+    for (key, value) in manifest.get_system_properties()? {
+        vm_args.push(format!("-D{}={}", key, value));
+    }
+    Ok(manifest
+        .get_bins()?
+        .iter()
+        .map(|bin| {
+            json!({
+                "type": "java",
+                "name": format!("{}{}", LAUNCH_PREFIX, bin.name),
+                "request": "launch",
+                "mainClass": manifest.main_class_of(bin),
+                "vmArgs": vm_args,
+                "cwd": "${workspaceFolder}",
+            })
+        })
+        .collect())
+}
+
+/// `launch` with its jargo configurations replaced by `configurations`.
+fn update_launch(
+    launch: Option<Map<String, Value>>,
+    configurations: Vec<Value>,
+) -> Result<Map<String, Value>> {
+    let mut launch = launch.unwrap_or_default();
+    launch.entry("version").or_insert_with(|| json!("0.2.0"));
+    let mut kept = match launch.remove("configurations") {
+        Some(Value::Array(existing)) => existing,
+        Some(_) => bail!("`configurations` in .vscode/launch.json is not an array"),
+        None => Vec::new(),
+    };
+    kept.retain(|c| {
+        !c.get("name")
+            .and_then(Value::as_str)
+            .is_some_and(|name| name.starts_with(LAUNCH_PREFIX))
+    });
+    kept.extend(configurations);
+    launch.insert("configurations".into(), Value::Array(kept));
+    Ok(launch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest_with(extra: &str) -> JargoToml {
+        toml::from_str(&format!(
+            "[package]\nname = \"demo\"\nversion = \"1.0.0\"\njava = \"21\"\n{}",
+            extra
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_update_settings() {
+        let existing = json!({
+            "editor.tabSize": 4,
+            "java.project.sourcePaths": ["src"],
+        });
+        let libraries = [
+            Library {
+                jar: PathBuf::from("/cache/guava.jar"),
+                sources: Some(PathBuf::from("/cache/guava-sources.jar")),
+                test: false,
+            },
+            Library {
+                jar: PathBuf::from("/cache/assertj.jar"),
+                sources: None,
+                test: true,
+            },
+        ];
+        let settings = update_settings(
+            existing.as_object().cloned(),
+            &["target/src-root".to_string()],
+            &libraries,
+        );
+        assert_eq!(
+            Value::Object(settings),
+            json!({
+                "editor.tabSize": 4,
+                "java.project.sourcePaths": ["target/src-root"],
+                "java.project.outputPath": "target/vscode/classes",
+                "java.project.referencedLibraries": {
+                    "include": ["/cache/guava.jar", "/cache/assertj.jar"],
+                    "sources": { "/cache/guava.jar": "/cache/guava-sources.jar" },
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_update_launch() {
+        let manifest = manifest_with(
+            "base-package = \"com.example\"\n[run]\njvm-args = [\"-Xmx1g\"]\nsystem-properties = { mode = \"dev\" }",
+        );
+        let existing = json!({
+            "version": "0.2.0",
+            "configurations": [
+                { "type": "java", "name": "Attach", "request": "attach" },
+                { "type": "java", "name": "jargo: old-name", "request": "launch" },
+            ],
+        });
+        let launch = update_launch(
+            existing.as_object().cloned(),
+            launch_configurations(&manifest).unwrap(),
+        )
+        .unwrap();
+        let configurations = launch["configurations"].as_array().unwrap();
+        assert_eq!(configurations.len(), 2);
+        assert_eq!(configurations[0]["name"], "Attach");
+        assert_eq!(
+            configurations[1],
+            json!({
+                "type": "java",
+                "name": format!("jargo: {}", manifest.package.name),
+                "request": "launch",
+                "mainClass": "com.example.Main",
+                "vmArgs": ["-Xmx1g", "-Dmode=dev"],
+                "cwd": "${workspaceFolder}",
+            })
+        );
+
+        let library = manifest_with("type = \"lib\"");
+        assert!(launch_configurations(&library).unwrap().is_empty());
+        let launch = update_launch(None, Vec::new()).unwrap();
+        assert_eq!(
+            Value::Object(launch),
+            json!({ "version": "0.2.0", "configurations": [] })
+        );
+    }
+}
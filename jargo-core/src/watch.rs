@@ -0,0 +1,175 @@
+use anyhow::{Context, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher as _};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+/// Set on commands spawned by `jargo watch`, so they can behave as a restartable
+/// child (e.g. `run` replaces itself with the JVM).
+pub const WATCH_ENV: &str = "JARGO_WATCH";
+
+/// Quiet period after the last change before a batch of changes is reported.
+pub const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Decides which paths under a project are worth re-running a command for.
+///
+/// `target/`, `.git/` and `Jargo.lock` (written by builds) never count, nor
+/// does anything the project's root `.gitignore` ignores.
+pub struct ChangeFilter {
+    root: PathBuf,
+    gitignore: Gitignore,
+}
+
+impl ChangeFilter {
+    pub fn new(project_root: &Path) -> Self {
+        let mut builder = GitignoreBuilder::new(project_root);
+        // A missing .gitignore is fine; a malformed line is skipped.
+        let _ = builder.add(project_root.join(".gitignore"));
+        let gitignore = builder.build().unwrap_or_else(|_| Gitignore::empty());
+        Self {
+            root: project_root.to_path_buf(),
+            gitignore,
+        }
+    }
+
+    pub fn is_relevant(&self, path: &Path) -> bool {
+        let Ok(rel) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+        let first = rel.components().next().map(|c| c.as_os_str());
+        if first.is_none_or(|c| c == "target" || c == ".git") || rel == Path::new("Jargo.lock") {
+            return false;
+        }
+        !self
+            .gitignore
+            .matched_path_or_any_parents(rel, path.is_dir())
+            .is_ignore()
+    }
+}
+
+/// Recursive filesystem watcher over a project root.
+pub struct Watcher {
+    // Dropping the watcher stops the events.
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+    filter: ChangeFilter,
+}
+
+impl Watcher {
+    pub fn new(project_root: &Path) -> Result<Self> {
+        // Event paths are reported under the canonical root on some platforms.
+        let root = project_root
+            .canonicalize()
+            .with_context(|| format!("failed to resolve {}", project_root.display()))?;
+        let (tx, events) = mpsc::channel();
+        let mut watcher =
+            notify::recommended_watcher(tx).context("failed to start the filesystem watcher")?;
+        watcher
+            .watch(&root, RecursiveMode::Recursive)
+            .with_context(|| format!("failed to watch {}", root.display()))?;
+        Ok(Self {
+            _watcher: watcher,
+            filter: ChangeFilter::new(&root),
+            events,
+        })
+    }
+
+    /// Wait up to `timeout` (forever when `None`) for a relevant change, then
+    /// keep collecting until no change arrives for [`DEBOUNCE`].
+    ///
+    /// Returns the changed paths relative to the project root, sorted, or
+    /// `None` when the timeout elapsed first.
+    pub fn next_change(&self, timeout: Option<Duration>) -> Result<Option<Vec<PathBuf>>> {
+        let deadline = timeout.map(|t| Instant::now() + t);
+        let mut changed = Vec::new();
+
+        while changed.is_empty() {
+            let event = match deadline {
+                Some(deadline) => {
+                    match self
+                        .events
+                        .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                    {
+                        Ok(event) => event,
+                        Err(RecvTimeoutError::Timeout) => return Ok(None),
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    }
+                }
+                None => match self.events.recv() {
+                    Ok(event) => event,
+                    Err(_) => break,
+                },
+            };
+            self.collect(event?, &mut changed);
+        }
+
+        while let Ok(event) = self.events.recv_timeout(DEBOUNCE) {
+            self.collect(event?, &mut changed);
+        }
+
+        changed.sort();
+        changed.dedup();
+        Ok(Some(changed))
+    }
+
+    fn collect(&self, event: Event, changed: &mut Vec<PathBuf>) {
+        // Reads (and opens) are not changes; builds read every source.
+        if matches!(event.kind, EventKind::Access(_)) {
+            return;
+        }
+        for path in event.paths {
+            if self.filter.is_relevant(&path) {
+                let rel = path.strip_prefix(&self.filter.root).unwrap_or(&path);
+                changed.push(rel.to_path_buf());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_filter_skips_outputs_and_gitignored() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path();
+        fs::write(root.join(".gitignore"), "target/\n*.log\nscratch/\n").unwrap();
+        fs::create_dir_all(root.join("scratch")).unwrap();
+        let filter = ChangeFilter::new(root);
+
+        assert!(filter.is_relevant(&root.join("src/Main.java")));
+        assert!(filter.is_relevant(&root.join("Jargo.toml")));
+        assert!(!filter.is_relevant(&root.join("target/classes/Main.class")));
+        assert!(!filter.is_relevant(&root.join(".git/index")));
+        assert!(!filter.is_relevant(&root.join("Jargo.lock")));
+        assert!(!filter.is_relevant(&root.join("debug.log")));
+        assert!(!filter.is_relevant(&root.join("scratch/notes.txt")));
+        assert!(!filter.is_relevant(Path::new("/elsewhere/Main.java")));
+    }
+
+    #[test]
+    fn test_watcher_reports_changed_source() {
+        let temp = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join("src")).unwrap();
+        fs::create_dir_all(temp.path().join("target")).unwrap();
+        let watcher = Watcher::new(temp.path()).unwrap();
+
+        assert_eq!(
+            watcher
+                .next_change(Some(Duration::from_millis(50)))
+                .unwrap(),
+            None
+        );
+
+        fs::write(temp.path().join("target/Ignored.class"), "x").unwrap();
+        fs::write(temp.path().join("src/Main.java"), "class Main {}").unwrap();
+        let changed = watcher
+            .next_change(Some(Duration::from_secs(5)))
+            .unwrap()
+            .unwrap();
+        assert_eq!(changed, vec![PathBuf::from("src/Main.java")]);
+    }
+}
@@ -0,0 +1,1009 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+
+use crate::manifest::{Dependency, DependencyValue, JargoToml, Scope};
+
+/// Where a member keeps the fingerprints of its last successful runs.
+pub const FINGERPRINT_FILE: &str = "target/fingerprint.json";
+
+/// Represents the [workspace] section of a workspace root's Jargo.toml.
+#[derive(Debug, Deserialize, Default)]
+pub struct WorkspaceConfig {
+    /// Member directories relative to the root. A last segment of `*`
+    /// (`"libs/*"`) matches every subdirectory with a Jargo.toml.
+    #[serde(default)]
+    pub members: Vec<String>,
+    /// Versions members take with `"group:artifact" = { workspace = true }`.
+    #[serde(default)]
+    pub dependencies: HashMap<String, DependencyValue>,
+}
+
+/// The top-level tables of a workspace root's Jargo.toml.
+#[derive(Deserialize)]
+struct RootToml {
+    workspace: WorkspaceConfig,
+    package: Option<toml::Value>,
+}
+
+/// Just enough of a member's Jargo.toml to name it. Reading the full
+/// manifest would look up the workspace again for inherited dependencies.
+#[derive(Deserialize)]
+struct MemberToml {
+    package: MemberPackage,
+}
+
+#[derive(Deserialize)]
+struct MemberPackage {
+    name: String,
+}
+
+/// A package listed in `[workspace] members`.
+#[derive(Debug)]
+pub struct Member {
+    /// `[package] name` of the member.
+    pub name: String,
+    /// Absolute path of the member directory.
+    pub dir: PathBuf,
+}
+
+/// A member reached from another member's [dependencies].
+#[derive(Debug)]
+pub struct PathDependency<'a> {
+    pub member: &'a Member,
+    /// Whether the member's JAR goes on the compile classpath as well as the
+    /// runtime one: false when only reached through `scope = "runtime"`.
+    pub compile: bool,
+}
+
+/// A set of packages sharing one `Jargo.lock` and one `target/` at the root.
+#[derive(Debug)]
+pub struct Workspace {
+    pub root: PathBuf,
+    /// Members in `[workspace] members` order.
+    pub members: Vec<Member>,
+    /// `[workspace.dependencies]`.
+    pub dependencies: HashMap<String, DependencyValue>,
+}
+
+impl Workspace {
+    /// Load the workspace rooted at `dir`, or `None` when `dir` has no
+    /// Jargo.toml with a `[workspace]` table.
+    ///
+    /// A Jargo.toml that is not valid TOML is not treated as a workspace, so
+    /// the command that reads it reports the parse error.
+    pub fn load(dir: &Path) -> Result<Option<Self>> {
+        let manifest_path = dir.join("Jargo.toml");
+        let Ok(content) = fs::read_to_string(&manifest_path) else {
+            return Ok(None);
+        };
+        let Ok(table) = content.parse::<toml::Table>() else {
+            return Ok(None);
+        };
+        if !table.contains_key("workspace") {
+            return Ok(None);
+        }
+        let root_toml: RootToml = toml::from_str(&content)
+            .with_context(|| format!("failed to parse {}", manifest_path.display()))?;
+        if root_toml.package.is_some() {
+            bail!(
+                "{} has both [workspace] and [package]; move the package into a member directory",
+                manifest_path.display()
+            );
+        }
+
+        let root = dir
+            .canonicalize()
+            .with_context(|| format!("failed to resolve {}", dir.display()))?;
+        let mut members: Vec<Member> = Vec::new();
+        for dir in expand_members(&root, &root_toml.workspace.members)? {
+            let manifest_path = dir.join("Jargo.toml");
+            if !manifest_path.exists() {
+                bail!(
+                    "workspace member `{}` has no Jargo.toml",
+                    dir.strip_prefix(&root).unwrap_or(&dir).display()
+                );
+            }
+            let content = fs::read_to_string(&manifest_path)
+                .with_context(|| format!("failed to read {}", manifest_path.display()))?;
+            let manifest: MemberToml = toml::from_str(&content)
+                .with_context(|| format!("failed to parse {}", manifest_path.display()))?;
+            let name = manifest.package.name;
+            if members.iter().any(|m| m.name == name) {
+                bail!("two workspace members are named `{}`", name);
+            }
+            members.push(Member { name, dir });
+        }
+        Ok(Some(Self {
+            root,
+            members,
+            dependencies: root_toml.workspace.dependencies,
+        }))
+    }
+
+    /// The workspace `project_root` is a member of: the nearest ancestor
+    /// directory with a `[workspace]` Jargo.toml, when it lists `project_root`.
+    pub fn containing(project_root: &Path) -> Result<Option<Self>> {
+        let Ok(project_root) = project_root.canonicalize() else {
+            return Ok(None);
+        };
+        for dir in project_root.ancestors().skip(1) {
+            if let Some(workspace) = Self::load(dir)? {
+                let is_member = workspace.members.iter().any(|m| m.dir == project_root);
+                return Ok(is_member.then_some(workspace));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The member at `dir`.
+    pub fn member(&self, dir: &Path) -> Option<&Member> {
+        let dir = dir.canonicalize().ok()?;
+        self.members.iter().find(|m| m.dir == dir)
+    }
+
+    /// The lock file shared by every member.
+    pub fn lock_path(&self) -> PathBuf {
+        self.root.join("Jargo.lock")
+    }
+
+    /// Where the build output of `member` goes: `target/{name}` under the root.
+    pub fn target_dir(&self, member: &Member) -> PathBuf {
+        self.root.join("target").join(&member.name)
+    }
+
+    /// The JAR `jargo build` writes for `member`, which downstream members
+    /// compile and run against: `target/{name}/{name}.jar` under the root.
+    pub fn jar_path(&self, member: &Member) -> PathBuf {
+        self.target_dir(member).join(format!("{}.jar", member.name))
+    }
+
+    /// The current fingerprint of every member, by name: a hash of the
+    /// member's files outside `target/`, the root Jargo.toml and Jargo.lock,
+    /// the enabled `features`, and the fingerprints of the members it
+    /// depends on.
+    pub fn fingerprints(&self, features: &[String]) -> Result<HashMap<String, String>> {
+        let graph = self.dependency_graph()?;
+        let mut shared = Sha256::new();
+        if !features.is_empty() {
+            let mut features = features.to_vec();
+            features.sort();
+            features.dedup();
+            shared.update(format!("features = {}\n", features.join(",")));
+        }
+        for name in ["Jargo.toml", "Jargo.lock"] {
+            if let Ok(bytes) = fs::read(self.root.join(name)) {
+                shared.update(name.as_bytes());
+                shared.update(&bytes);
+            }
+        }
+        let own: Vec<Sha256> = self
+            .members
+            .iter()
+            .map(|member| {
+                let mut hasher = shared.clone();
+                hash_dir(&mut hasher, &member.dir, &member.dir)?;
+                Ok(hasher)
+            })
+            .collect::<Result<_>>()?;
+
+        fn combine(
+            node: usize,
+            graph: &[Vec<usize>],
+            own: &[Sha256],
+            done: &mut [Option<String>],
+        ) -> String {
+            if let Some(fingerprint) = &done[node] {
+                return fingerprint.clone();
+            }
+            let mut hasher = own[node].clone();
+            for &dep in &graph[node] {
+                hasher.update(combine(dep, graph, own, done).as_bytes());
+            }
+            let fingerprint: String = hasher
+                .finalize()
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect();
+            done[node] = Some(fingerprint.clone());
+            fingerprint
+        }
+        let mut done = vec![None; graph.len()];
+        Ok(self
+            .members
+            .iter()
+            .enumerate()
+            .map(|(i, m)| (m.name.clone(), combine(i, &graph, &own, &mut done)))
+            .collect())
+    }
+
+    /// The Jargo.toml of `member`.
+    pub fn manifest(&self, member: &Member) -> Result<JargoToml> {
+        let manifest_path = member.dir.join("Jargo.toml");
+        JargoToml::from_file(&manifest_path)
+            .map_err(|e| anyhow::anyhow!("failed to parse {}: {}", manifest_path.display(), e))
+    }
+
+    /// The members `member` lists in its [dependencies], with their scope.
+    pub fn member_dependencies(&self, member: &Member) -> Result<Vec<(&Member, Scope)>> {
+        let mut deps = Vec::new();
+        for dep in self.manifest(member)?.get_member_dependencies()? {
+            match self.members.iter().find(|m| m.name == dep.name) {
+                Some(found) => deps.push((found, dep.scope)),
+                None => bail!(
+                    "`{}` depends on `{}`, which is not a member of the workspace",
+                    member.name,
+                    dep.name
+                ),
+            }
+        }
+        Ok(deps)
+    }
+
+    /// Every member `member` depends on, directly or through other members,
+    /// in `[workspace] members` order. Inside the workspace these are path
+    /// dependencies on the members' built JARs.
+    pub fn path_dependencies(&self, member: &Member) -> Result<Vec<PathDependency<'_>>> {
+        // Rejects cycles before walking the graph
+        self.dependency_graph()?;
+        let mut compile: Vec<Option<bool>> = vec![None; self.members.len()];
+        let mut stack = vec![(self.index(member), true)];
+        while let Some((node, on_compile)) = stack.pop() {
+            for (dep, scope) in self.member_dependencies(&self.members[node])? {
+                let dep_compile = on_compile && scope == Scope::Compile;
+                let dep = self.index(dep);
+                // Already reached, at least as much on the compile classpath
+                if compile[dep].is_some_and(|c| c || !dep_compile) {
+                    continue;
+                }
+                compile[dep] = Some(dep_compile);
+                stack.push((dep, dep_compile));
+            }
+        }
+        Ok(self
+            .members
+            .iter()
+            .zip(compile)
+            .filter_map(|(member, compile)| {
+                compile.map(|compile| PathDependency { member, compile })
+            })
+            .collect())
+    }
+
+    /// The members `member` depends on directly, as the Maven coordinates
+    /// they are published under (`{group}:{name}:{version}`), for consumers
+    /// outside the workspace.
+    pub fn published_dependencies(&self, member: &Member) -> Result<Vec<Dependency>> {
+        let mut deps = Vec::new();
+        for (dep, scope) in self.member_dependencies(member)? {
+            let manifest = self.manifest(dep)?;
+            deps.push(Dependency {
+                group: manifest.get_group(),
+                artifact: manifest.package.name,
+                version: manifest.package.version,
+                scope,
+                expose: false,
+            });
+        }
+        Ok(deps)
+    }
+
+    /// For every member, the indices in `members` of the members it depends
+    /// on directly. Fails when the dependencies form a cycle.
+    fn dependency_graph(&self) -> Result<Vec<Vec<usize>>> {
+        let mut graph = Vec::with_capacity(self.members.len());
+        for member in &self.members {
+            let deps = self.member_dependencies(member)?;
+            graph.push(
+                deps.iter()
+                    .map(|(dep, _)| self.index(dep))
+                    .collect::<Vec<usize>>(),
+            );
+        }
+
+        // Depth-first search; `path` is the chain of members being visited
+        fn visit(
+            graph: &[Vec<usize>],
+            node: usize,
+            path: &mut Vec<usize>,
+            done: &mut [bool],
+        ) -> std::result::Result<(), Vec<usize>> {
+            if done[node] {
+                return Ok(());
+            }
+            if let Some(start) = path.iter().position(|&n| n == node) {
+                let mut cycle = path[start..].to_vec();
+                cycle.push(node);
+                return Err(cycle);
+            }
+            path.push(node);
+            for &dep in &graph[node] {
+                visit(graph, dep, path, done)?;
+            }
+            path.pop();
+            done[node] = true;
+            Ok(())
+        }
+        let mut done = vec![false; graph.len()];
+        for node in 0..graph.len() {
+            if let Err(cycle) = visit(&graph, node, &mut Vec::new(), &mut done) {
+                let names: Vec<&str> = cycle.iter().map(|&i| &self.members[i].name[..]).collect();
+                bail!(
+                    "workspace members depend on each other in a cycle: {}",
+                    names.join(" -> ")
+                );
+            }
+        }
+        Ok(graph)
+    }
+
+    fn index(&self, member: &Member) -> usize {
+        self.members
+            .iter()
+            .position(|m| m.dir == member.dir)
+            .expect("member of this workspace")
+    }
+
+    /// Every member that `members` depend on, directly or through other
+    /// members, in `[workspace] members` order.
+    pub fn upstream(&self, members: &[&Member]) -> Result<Vec<&Member>> {
+        let graph = self.dependency_graph()?;
+        let mut reached = vec![false; graph.len()];
+        let mut stack: Vec<usize> = members.iter().map(|m| self.index(m)).collect();
+        while let Some(node) = stack.pop() {
+            for &dep in &graph[node] {
+                if !reached[dep] {
+                    reached[dep] = true;
+                    stack.push(dep);
+                }
+            }
+        }
+        Ok(self
+            .members
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| reached[*i])
+            .map(|(_, m)| m)
+            .collect())
+    }
+
+    /// Run `f` in each of `members`, each after the members among them it
+    /// depends on (directly or through other members), running up to `jobs`
+    /// at once. Ready members start in `[workspace] members` order. After a
+    /// failure no more members start; the first error is returned once the
+    /// running ones finish.
+    pub fn run_in_order<F>(&self, members: &[&Member], jobs: usize, f: F) -> Result<()>
+    where
+        F: Fn(&Member) -> Result<()> + Sync,
+    {
+        let graph = self.dependency_graph()?;
+        let selected: Vec<usize> = members.iter().map(|m| self.index(m)).collect();
+        // For each selected member, the selected members it waits for
+        let mut waiting: Vec<BTreeSet<usize>> = selected
+            .iter()
+            .map(|&node| {
+                let mut reached = BTreeSet::new();
+                let mut stack = vec![node];
+                while let Some(n) = stack.pop() {
+                    for &dep in &graph[n] {
+                        if reached.insert(dep) {
+                            stack.push(dep);
+                        }
+                    }
+                }
+                reached.retain(|n| selected.contains(n));
+                reached
+            })
+            .collect();
+
+        let jobs = jobs.max(1);
+        let mut started = vec![false; selected.len()];
+        let mut running = 0;
+        let mut error = None;
+        thread::scope(|scope| {
+            let (tx, rx) = mpsc::channel();
+            loop {
+                if error.is_none() {
+                    for (i, &node) in selected.iter().enumerate() {
+                        if running < jobs && !started[i] && waiting[i].is_empty() {
+                            started[i] = true;
+                            running += 1;
+                            let (tx, f, member) = (tx.clone(), &f, &self.members[node]);
+                            scope.spawn(move || tx.send((node, f(member))));
+                        }
+                    }
+                }
+                if running == 0 {
+                    break;
+                }
+                let (node, result) = rx.recv().expect("a member is running");
+                running -= 1;
+                match result {
+                    Ok(()) => waiting.iter_mut().for_each(|w| {
+                        w.remove(&node);
+                    }),
+                    Err(e) => {
+                        error.get_or_insert(e);
+                    }
+                }
+            }
+        });
+        match error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    /// Make `{member}/target` a link to the member's directory in the shared
+    /// `target/`, replacing a `target/` directory left over from building the
+    /// member on its own.
+    pub fn link_target(&self, member: &Member) -> Result<()> {
+        link_target(&member.dir, &self.target_dir(member))
+    }
+}
+
+/// Make `{dir}/target` a link to `target`, creating `target` and replacing
+/// a `target/` directory already there. Used for workspace members and for
+/// `--target-dir`. A `target` that is `dir`, contains it, or is inside its
+/// `target/` is rejected, since `jargo clean` would remove the project with
+/// it or replacing `target/` would remove the directory itself.
+pub fn link_target(dir: &Path, target: &Path) -> Result<()> {
+    let targets = [normalize(target), resolve(target)];
+    for project in [normalize(dir), resolve(dir)] {
+        for target in &targets {
+            if project.starts_with(target) {
+                bail!(
+                    "target directory {} contains the project at {}",
+                    target.display(),
+                    dir.display()
+                );
+            }
+            if target.starts_with(project.join("target")) {
+                bail!(
+                    "target directory {} is inside the project's own target/",
+                    target.display()
+                );
+            }
+        }
+    }
+    fs::create_dir_all(target).with_context(|| format!("failed to create {}", target.display()))?;
+    let link = dir.join("target");
+    match fs::symlink_metadata(&link) {
+        Ok(meta) if meta.file_type().is_symlink() => {
+            if fs::read_link(&link).is_ok_and(|t| t == target) {
+                return Ok(());
+            }
+            remove_link(&link)?;
+        }
+        Ok(_) => fs::remove_dir_all(&link)
+            .with_context(|| format!("failed to remove {}", link.display()))?,
+        Err(_) => {}
+    }
+    create_link(target, &link)
+}
+
+/// `path` without `.` and with each `..` removing the component before it,
+/// without looking at the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normal = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normal.pop();
+            }
+            component => normal.push(component),
+        }
+    }
+    normal
+}
+
+/// `path` normalized, with symlinks resolved in the part of it that exists.
+fn resolve(path: &Path) -> PathBuf {
+    let normal = normalize(path);
+    for existing in normal.ancestors() {
+        if let Ok(resolved) = existing.canonicalize() {
+            return resolved.join(normal.strip_prefix(existing).unwrap_or(&normal));
+        }
+    }
+    normal
+}
+
+#[cfg(unix)]
+fn create_link(target: &Path, link: &Path) -> Result<()> {
+    std::os::unix::fs::symlink(target, link)
+        .with_context(|| format!("failed to create symlink at {}", link.display()))
+}
+
+#[cfg(unix)]
+fn remove_link(link: &Path) -> Result<()> {
+    fs::remove_file(link).with_context(|| format!("failed to remove {}", link.display()))
+}
+
+/// Directory symlinks need extra privileges on Windows, so the link is a
+/// junction, which does not.
+#[cfg(windows)]
+fn create_link(target: &Path, link: &Path) -> Result<()> {
+    let output = std::process::Command::new("cmd")
+        .args(["/C", "mklink", "/J"])
+        .arg(link)
+        .arg(target)
+        .output()
+        .context("failed to run mklink")?;
+    if !output.status.success() {
+        bail!(
+            "failed to create junction at {}: {}",
+            link.display(),
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn remove_link(link: &Path) -> Result<()> {
+    fs::remove_dir(link).with_context(|| format!("failed to remove {}", link.display()))
+}
+
+/// Jargo.toml of a new workspace root with no members.
+pub fn new_root_manifest() -> String {
+    "[workspace]\nmembers = []\n".to_string()
+}
+
+/// Add `path` (relative to the root, `/`-separated) to `[workspace] members`
+/// of the root manifest at `root`, keeping the rest of the file as written.
+/// Does nothing when a `*` entry already matches it or it is listed.
+pub fn add_member(root: &Path, path: &str) -> Result<()> {
+    let manifest_path = root.join("Jargo.toml");
+    let content = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("failed to read {}", manifest_path.display()))?;
+    let mut doc: toml_edit::DocumentMut = content
+        .parse()
+        .with_context(|| format!("failed to parse {}", manifest_path.display()))?;
+    let workspace = doc["workspace"]
+        .as_table_like_mut()
+        .with_context(|| format!("{} has no [workspace] table", manifest_path.display()))?;
+    if workspace.get("members").is_none() {
+        workspace.insert("members", toml_edit::value(toml_edit::Array::new()));
+    }
+    let members = workspace
+        .get_mut("members")
+        .and_then(|m| m.as_array_mut())
+        .context("`[workspace] members` must be an array")?;
+
+    let parent = path.rsplit_once('/').map_or("", |(parent, _)| parent);
+    let listed = members.iter().filter_map(|m| m.as_str()).any(|m| {
+        m == path
+            || m.strip_suffix('*').is_some_and(|p| {
+                p.trim_end_matches('/') == parent && (p.is_empty() || p.ends_with('/'))
+            })
+    });
+    if listed {
+        return Ok(());
+    }
+    members.push(path);
+    fs::write(&manifest_path, doc.to_string())
+        .with_context(|| format!("failed to write {}", manifest_path.display()))
+}
+
+/// Fingerprints of the last successful runs in a member, as stored in
+/// [`FINGERPRINT_FILE`] inside the member.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct MemberFingerprints {
+    /// Member fingerprint at the last successful run, keyed by kind of run
+    /// (`build`, `test`, ...).
+    pub runs: BTreeMap<String, String>,
+}
+
+impl MemberFingerprints {
+    /// Read the fingerprints; a missing or unreadable file has none.
+    pub fn load(member: &Member) -> Self {
+        fs::read_to_string(member.dir.join(FINGERPRINT_FILE))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, member: &Member) -> Result<()> {
+        let path = member.dir.join(FINGERPRINT_FILE);
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(&path, json + "\n").with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// Whether the last successful run of `kind` saw `fingerprint`.
+    pub fn is_fresh(&self, kind: &str, fingerprint: &str) -> bool {
+        self.runs.get(kind).is_some_and(|f| f == fingerprint)
+    }
+}
+
+/// Feed the files under `dir` to `hasher` in path order, skipping
+/// `target/`, hidden entries, and packages nested in the member.
+fn hash_dir(hasher: &mut Sha256, base: &Path, dir: &Path) -> Result<()> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|| format!("failed to read {}", dir.display()))?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .collect();
+    entries.sort();
+    for path in entries {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if name.starts_with('.') || (dir == base && name == "target") {
+            continue;
+        }
+        if path.is_dir() {
+            if !path.join("Jargo.toml").exists() {
+                hash_dir(hasher, base, &path)?;
+            }
+            continue;
+        }
+        let bytes =
+            fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+        let relative = path.strip_prefix(base).unwrap_or(&path);
+        hasher.update(relative.to_string_lossy().as_bytes());
+        hasher.update((bytes.len() as u64).to_le_bytes());
+        hasher.update(&bytes);
+    }
+    Ok(())
+}
+
+/// Resolve `[workspace] members` entries to member directories, in order and
+/// without duplicates.
+fn expand_members(root: &Path, patterns: &[String]) -> Result<Vec<PathBuf>> {
+    let mut dirs = Vec::new();
+    let mut seen = BTreeSet::new();
+    for pattern in patterns {
+        let matched = match pattern.strip_suffix('*') {
+            Some(parent) if parent.is_empty() || parent.ends_with('/') => {
+                let parent = root.join(parent);
+                let mut found: Vec<PathBuf> = fs::read_dir(&parent)
+                    .with_context(|| format!("failed to read {}", parent.display()))?
+                    .filter_map(|e| e.ok().map(|e| e.path()))
+                    .filter(|p| p.join("Jargo.toml").is_file())
+                    .collect();
+                found.sort();
+                found
+            }
+            _ if pattern.contains('*') => {
+                bail!(
+                    "invalid workspace member `{}`: `*` is only supported as the last path segment",
+                    pattern
+                )
+            }
+            _ => vec![root.join(pattern)],
+        };
+        for dir in matched {
+            let dir = dir
+                .canonicalize()
+                .with_context(|| format!("workspace member `{}` does not exist", pattern))?;
+            if seen.insert(dir.clone()) {
+                dirs.push(dir);
+            }
+        }
+    }
+    Ok(dirs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_package(dir: &Path, name: &str) {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(
+            dir.join("Jargo.toml"),
+            format!(
+                "[package]\nname = \"{}\"\nversion = \"0.1.0\"\njava = \"21\"\n",
+                name
+            ),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_load_and_containing() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        fs::write(
+            root.join("Jargo.toml"),
+            "[workspace]\nmembers = [\"app\", \"libs/*\"]\n",
+        )
+        .unwrap();
+        write_package(&root.join("app"), "app");
+        write_package(&root.join("libs/util"), "util");
+        write_package(&root.join("libs/core"), "core");
+        fs::create_dir_all(root.join("libs/notes")).unwrap();
+        write_package(&root.join("other"), "other");
+
+        let workspace = Workspace::load(root).unwrap().unwrap();
+        let names: Vec<&str> = workspace.members.iter().map(|m| &m.name[..]).collect();
+        assert_eq!(names, ["app", "core", "util"]);
+        assert_eq!(
+            workspace.member(&root.join("libs/util")).unwrap().name,
+            "util"
+        );
+
+        let containing = Workspace::containing(&root.join("libs/core")).unwrap();
+        assert_eq!(containing.unwrap().root, root.canonicalize().unwrap());
+        assert!(Workspace::containing(&root.join("other"))
+            .unwrap()
+            .is_none());
+        assert!(Workspace::load(&root.join("app")).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        fs::write(
+            root.join("Jargo.toml"),
+            "[workspace]\nmembers = [\"a\", \"b\"]\n",
+        )
+        .unwrap();
+        write_package(&root.join("a"), "same");
+        write_package(&root.join("b"), "same");
+        let err = Workspace::load(root).unwrap_err().to_string();
+        assert!(err.contains("two workspace members are named `same`"));
+
+        fs::write(
+            root.join("Jargo.toml"),
+            "[workspace]\nmembers = [\"missing\"]\n",
+        )
+        .unwrap();
+        assert!(Workspace::load(root).is_err());
+
+        fs::write(
+            root.join("Jargo.toml"),
+            "[workspace]\nmembers = []\n\n[package]\nname = \"root\"\n",
+        )
+        .unwrap();
+        assert!(Workspace::load(root).is_err());
+    }
+
+    #[test]
+    fn test_inherited_dependencies() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        fs::write(
+            root.join("Jargo.toml"),
+            "[workspace]\nmembers = [\"app\"]\n\n[workspace.dependencies]\n\"com.google.guava:guava\" = \"33.0.0-jre\"\n",
+        )
+        .unwrap();
+        write_package(&root.join("app"), "app");
+        let manifest_path = root.join("app/Jargo.toml");
+        let mut content = fs::read_to_string(&manifest_path).unwrap();
+        content.push_str("\n[dependencies]\n\"com.google.guava:guava\" = { workspace = true }\n");
+        fs::write(&manifest_path, &content).unwrap();
+
+        let manifest = JargoToml::from_file(&manifest_path).unwrap();
+        let deps = manifest.get_dependencies().unwrap();
+        assert_eq!(deps[0].version, "33.0.0-jre");
+
+        write_package(&root.join("other"), "other");
+        fs::write(root.join("other/Jargo.toml"), &content).unwrap();
+        assert!(JargoToml::from_file(&root.join("other/Jargo.toml")).is_err());
+    }
+
+    fn add_member_dependency(dir: &Path, name: &str) {
+        let manifest_path = dir.join("Jargo.toml");
+        let mut content = fs::read_to_string(&manifest_path).unwrap();
+        if !content.contains("[dependencies]") {
+            content.push_str("\n[dependencies]\n");
+        }
+        content.push_str(&format!("\"{}\" = {{ workspace = true }}\n", name));
+        fs::write(&manifest_path, content).unwrap();
+    }
+
+    #[test]
+    fn test_dependency_order() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        fs::write(
+            root.join("Jargo.toml"),
+            "[workspace]\nmembers = [\"app\", \"core\", \"util\", \"tool\"]\n",
+        )
+        .unwrap();
+        for name in ["app", "core", "util", "tool"] {
+            write_package(&root.join(name), name);
+        }
+        add_member_dependency(&root.join("app"), "core");
+        add_member_dependency(&root.join("core"), "util");
+
+        let workspace = Workspace::load(root).unwrap().unwrap();
+        let app = &workspace.members[0];
+        let upstream: Vec<&str> = workspace
+            .upstream(&[app])
+            .unwrap()
+            .iter()
+            .map(|m| &m.name[..])
+            .collect();
+        assert_eq!(upstream, ["core", "util"]);
+
+        let all: Vec<&Member> = workspace.members.iter().collect();
+        let order = std::sync::Mutex::new(Vec::new());
+        workspace
+            .run_in_order(&all, 4, |member| {
+                order.lock().unwrap().push(member.name.clone());
+                Ok(())
+            })
+            .unwrap();
+        let order = order.into_inner().unwrap();
+        let position = |name: &str| order.iter().position(|n| n == name).unwrap();
+        assert_eq!(order.len(), 4);
+        assert!(position("util") < position("core"));
+        assert!(position("core") < position("app"));
+
+        let err = workspace
+            .run_in_order(&all, 1, |member| {
+                if member.name == "util" {
+                    anyhow::bail!("util failed");
+                }
+                assert_ne!(member.name, "core");
+                Ok(())
+            })
+            .unwrap_err();
+        assert_eq!(err.to_string(), "util failed");
+
+        add_member_dependency(&root.join("util"), "app");
+        let err = workspace.upstream(&[app]).unwrap_err().to_string();
+        assert!(err.contains("cycle: app -> core -> util -> app"));
+    }
+
+    #[test]
+    fn test_published_dependencies() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        fs::write(
+            root.join("Jargo.toml"),
+            "[workspace]\nmembers = [\"app\", \"core\"]\n",
+        )
+        .unwrap();
+        write_package(&root.join("app"), "app");
+        fs::create_dir_all(root.join("core")).unwrap();
+        fs::write(
+            root.join("core/Jargo.toml"),
+            "[package]\nname = \"core\"\nversion = \"1.2.0\"\ngroup = \"com.example\"\njava = \"21\"\n",
+        )
+        .unwrap();
+        add_member_dependency(&root.join("app"), "core");
+
+        let workspace = Workspace::load(root).unwrap().unwrap();
+        let deps = workspace
+            .published_dependencies(&workspace.members[0])
+            .unwrap();
+        assert_eq!(deps.len(), 1);
+        assert_eq!(
+            (
+                &deps[0].group[..],
+                &deps[0].artifact[..],
+                &deps[0].version[..]
+            ),
+            ("com.example", "core", "1.2.0")
+        );
+    }
+
+    #[test]
+    fn test_fingerprints() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        fs::write(
+            root.join("Jargo.toml"),
+            "[workspace]\nmembers = [\"app\", \"core\", \"tool\"]\n",
+        )
+        .unwrap();
+        for name in ["app", "core", "tool"] {
+            write_package(&root.join(name), name);
+            fs::create_dir_all(root.join(name).join("src")).unwrap();
+        }
+        add_member_dependency(&root.join("app"), "core");
+        let workspace = Workspace::load(root).unwrap().unwrap();
+        let before = workspace.fingerprints(&[]).unwrap();
+
+        fs::create_dir_all(root.join("core/target")).unwrap();
+        fs::write(root.join("core/target/core.jar"), "").unwrap();
+        assert_eq!(workspace.fingerprints(&[]).unwrap(), before);
+
+        fs::write(root.join("core/src/Lib.java"), "class Lib {}").unwrap();
+        let after = workspace.fingerprints(&[]).unwrap();
+        assert_ne!(after["core"], before["core"]);
+        assert_ne!(after["app"], before["app"]);
+        assert_eq!(after["tool"], before["tool"]);
+
+        // The enabled features, in any order
+        let features = ["postgres".to_string(), "metrics".to_string()];
+        let with_features = workspace.fingerprints(&features).unwrap();
+        assert_ne!(with_features["tool"], after["tool"]);
+        let reversed = [features[1].clone(), features[0].clone()];
+        assert_eq!(workspace.fingerprints(&reversed).unwrap(), with_features);
+
+        let core = &workspace.members[1];
+        let mut recorded = MemberFingerprints::load(core);
+        assert!(!recorded.is_fresh("build", &after["core"]));
+        recorded
+            .runs
+            .insert("build".to_string(), after["core"].clone());
+        recorded.save(core).unwrap();
+        let recorded = MemberFingerprints::load(core);
+        assert!(recorded.is_fresh("build", &after["core"]));
+        assert!(!recorded.is_fresh("test", &after["core"]));
+    }
+
+    #[test]
+    fn test_add_member() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        fs::write(
+            root.join("Jargo.toml"),
+            "# shared build\n[workspace]\nmembers = [\"app\", \"libs/*\"]\n",
+        )
+        .unwrap();
+        add_member(root, "core").unwrap();
+        add_member(root, "app").unwrap();
+        add_member(root, "libs/util").unwrap();
+        assert_eq!(
+            fs::read_to_string(root.join("Jargo.toml")).unwrap(),
+            "# shared build\n[workspace]\nmembers = [\"app\", \"libs/*\", \"core\"]\n"
+        );
+
+        fs::write(root.join("Jargo.toml"), new_root_manifest()).unwrap();
+        add_member(root, "app").unwrap();
+        let table: toml::Table = fs::read_to_string(root.join("Jargo.toml"))
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(table["workspace"]["members"][0].as_str(), Some("app"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_link_target() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        fs::write(
+            root.join("Jargo.toml"),
+            "[workspace]\nmembers = [\"app\"]\n",
+        )
+        .unwrap();
+        write_package(&root.join("app"), "app");
+        fs::create_dir_all(root.join("app/target/classes")).unwrap();
+
+        let workspace = Workspace::load(root).unwrap().unwrap();
+        let member = &workspace.members[0];
+        workspace.link_target(member).unwrap();
+        workspace.link_target(member).unwrap();
+
+        let link = member.dir.join("target");
+        assert!(fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        fs::write(link.join("marker"), "").unwrap();
+        assert!(workspace.root.join("target/app/marker").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_link_target_rejects_the_project() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("work/app");
+        fs::create_dir_all(dir.join("target/classes")).unwrap();
+
+        let err = link_target(&dir, &dir).unwrap_err();
+        assert!(err.to_string().contains("contains the project"), "{}", err);
+        let err = link_target(&dir, &dir.join("..")).unwrap_err();
+        assert!(err.to_string().contains("contains the project"), "{}", err);
+        let err = link_target(&dir, &tmp.path().join("work/app/src/../..")).unwrap_err();
+        assert!(err.to_string().contains("contains the project"), "{}", err);
+        let err = link_target(&dir, &dir.join("target/out")).unwrap_err();
+        assert!(err.to_string().contains("own target/"), "{}", err);
+        assert!(dir.join("target/classes").is_dir());
+
+        // A directory beside the project, or inside it but not in target/, is fine
+        link_target(&dir, &tmp.path().join("work/out")).unwrap();
+        link_target(&dir, &dir.join("build")).unwrap();
+        assert!(dir.join("build").is_dir());
+    }
+}
@@ -0,0 +1,21 @@
+@echo off
+rem Launcher for {{name}}, generated by `jargo dist`.
+setlocal
+
+set "APP_HOME=%~dp0.."
+
+if defined JAVA_HOME (
+    set "JAVACMD=%JAVA_HOME%\bin\java.exe"
+) else (
+    set "JAVACMD=java.exe"
+)
+
+"%JAVACMD%" -version >nul 2>&1
+if errorlevel 1 (
+    echo error: java not found; set JAVA_HOME or add java to PATH 1>&2
+    exit /b 1
+)
+{{env}}
+{{prelude}}
+"%JAVACMD%" {{jvm_args}} -cp "{{classpath}}" {{main_class}} %*
+exit /b %ERRORLEVEL%
@@ -0,0 +1,24 @@
+#!/bin/sh
+# Launcher for {{name}}, generated by `jargo dist`.
+
+APP_HOME=$(cd "$(dirname "$0")/.." && pwd -P)
+
+if [ -n "$JAVA_HOME" ]; then
+    JAVACMD="$JAVA_HOME/bin/java"
+else
+    JAVACMD=java
+fi
+
+if ! command -v "$JAVACMD" >/dev/null 2>&1; then
+    echo "error: java not found; set JAVA_HOME or add java to PATH" >&2
+    exit 1
+fi
+
+JAVA_VERSION=$("$JAVACMD" -version 2>&1 | sed -n 's/.* version "\([0-9]*\).*/\1/p' | head -n 1)
+if [ -n "$JAVA_VERSION" ] && [ "$JAVA_VERSION" -lt {{java}} ]; then
+    echo "error: {{name}} requires Java {{java}} or newer (found $JAVA_VERSION)" >&2
+    exit 1
+fi
+{{env}}
+{{prelude}}
+exec "$JAVACMD" {{jvm_args}} -cp "{{classpath}}" {{main_class}} "$@"
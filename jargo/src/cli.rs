@@ -1,17 +1,51 @@
-use clap::{Parser, Subcommand};
+use clap::{ArgAction, Args, Parser, Subcommand};
+use jargo_core::test_filter::TestFilter;
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(name = "jargo", about = "A Cargo-inspired build tool for Java")]
 pub struct Cli {
-    /// Use verbose output
-    #[arg(short = 'v', long, global = true)]
-    pub verbose: bool,
+    /// Use verbose output (-vv also shows debug details and timings)
+    #[arg(short = 'v', long, action = ArgAction::Count, global = true)]
+    pub verbose: u8,
+
+    /// Print no status lines or warnings
+    #[arg(short = 'q', long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Use only cached artifacts; fail instead of going to the network
+    #[arg(long, global = true)]
+    pub offline: bool,
+
+    /// Fail instead of updating a Jargo.lock that is missing or out of date with Jargo.toml
+    #[arg(long, global = true)]
+    pub locked: bool,
+
+    /// Enable these `[features]` of Jargo.toml (comma-separated or repeated)
+    #[arg(long, value_name = "FEATURES", value_delimiter = ',', global = true)]
+    pub features: Vec<String>,
+
+    /// Run in the package of this Jargo.toml instead of the one around the current directory
+    #[arg(long, value_name = "PATH", global = true)]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Put build output in DIR instead of target/
+    #[arg(long, value_name = "DIR", global = true)]
+    pub target_dir: Option<PathBuf>,
+
+    /// Coloring: `auto` (terminals, unless `NO_COLOR` is set), `always`, or `never`
+    #[arg(long, value_name = "WHEN", default_value = "auto", value_parser = ["auto", "always", "never"], global = true)]
+    pub color: String,
+
+    /// How to report a failure: `human`, or `json` for one JSON object on stderr
+    #[arg(long, value_name = "FMT", default_value = "human", value_parser = ["human", "json"], global = true)]
+    pub error_format: String,
 
     #[command(subcommand)]
     pub command: Command,
 }
 
-#[derive(Subcommand)]
+#[derive(Subcommand, Clone)]
 pub enum Command {
     /// Create a new Jargo project
     New {
@@ -20,47 +54,514 @@ pub enum Command {
         /// Create a library project instead of an application
         #[arg(long)]
         lib: bool,
+        /// Create a workspace root with no members
+        #[arg(long, conflicts_with_all = ["lib", "member", "java", "base_package", "main_class", "test_framework"])]
+        workspace: bool,
+        /// Create the package in the current workspace and add it to `[workspace] members`
+        #[arg(long)]
+        member: bool,
+        /// Workspace member the new member depends on (repeatable)
+        #[arg(long = "dep", value_name = "MEMBER", requires = "member")]
+        deps: Vec<String>,
+        /// Do not initialize a git repository
+        #[arg(long)]
+        no_git: bool,
+        #[command(flatten)]
+        options: NewOptions,
     },
     /// Initialize a Jargo project in the current directory
     Init {
         /// Create a library project instead of an application
         #[arg(long)]
         lib: bool,
+        #[command(flatten)]
+        options: NewOptions,
     },
+    /// Generate a Jargo.toml from the Maven or Gradle build in the current directory
+    Migrate,
     /// Compile the project and assemble a JAR
-    Build,
-    /// Compile and run the project (app only)
+    Build {
+        /// On compile errors, still emit class files for sources that compile
+        #[arg(long)]
+        keep_going: bool,
+        /// Bundle runtime dependencies into the JAR, applying `[shade.relocate]`
+        #[arg(long)]
+        uber: bool,
+        /// Copy runtime dependencies to target/lib/ and reference them from the JAR's `Class-Path`
+        #[arg(long, conflicts_with = "uber")]
+        thin: bool,
+        /// Output format: `human` or `json` (newline-delimited messages on stdout)
+        #[arg(long, value_name = "FMT", default_value = "human", value_parser = ["human", "json"])]
+        message_format: String,
+        /// Re-run on file changes
+        #[arg(long)]
+        watch: bool,
+        #[command(flatten)]
+        selection: PackageSelection,
+    },
+    /// Build a runnable distribution with launcher scripts in target/dist/ (app only)
+    Dist,
+    /// Build a trimmed runtime image with only the modules the app needs in target/image/ (app only)
+    Jlink,
+    /// Build a native installer with jpackage in target/installer/ (app only)
+    Package {
+        /// Installer type, overriding `[jpackage] type`
+        #[arg(long = "type", value_name = "TYPE", value_parser = jargo_core::jpackage::PACKAGE_TYPES)]
+        package_type: Option<String>,
+        /// Entry point to use, from the `[[bin]]` targets
+        #[arg(long, value_name = "NAME")]
+        bin: Option<String>,
+    },
+    /// Compile the app to a native binary in target/native/ with GraalVM native-image (app only)
+    Native {
+        /// Entry point to use, from the `[[bin]]` targets
+        #[arg(long, value_name = "NAME")]
+        bin: Option<String>,
+    },
+    /// Build a layered OCI image from `[container]` into target/container/ (app only)
+    Image {
+        /// Push to the registry named by `[container] image` instead of writing a tarball
+        #[arg(long)]
+        push: bool,
+        /// Entry point to use, from the `[[bin]]` targets
+        #[arg(long, value_name = "NAME")]
+        bin: Option<String>,
+    },
+    /// Build the JAR and check it against what Jargo.toml declares
+    VerifyJar,
+    /// Compile and run the project (app only) or one of its examples
     Run {
+        /// Record a Java Flight Recorder profile into target/profiles/
+        #[arg(long)]
+        profile_jvm: bool,
+        /// Re-run on file changes
+        #[arg(long)]
+        watch: bool,
+        /// Entry point to use, from the `[[bin]]` targets
+        #[arg(long, value_name = "NAME")]
+        bin: Option<String>,
+        /// Run `examples/{NAME}.java` instead (also allowed in lib projects)
+        #[arg(long, value_name = "NAME", conflicts_with = "bin")]
+        example: Option<String>,
+        /// Listen for a debugger with JDWP (port defaults to `[run] debug-port` or 5005)
+        #[arg(long, value_name = "PORT", num_args = 0..=1, require_equals = true)]
+        debug: Option<Option<u16>>,
+        /// With `--debug`, wait for the debugger to attach before starting
+        #[arg(long, requires = "debug")]
+        suspend: bool,
+        /// Launch configuration from `[run.profiles.<NAME>]`
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+        /// Set a system property, overriding `[run] system-properties` (repeatable)
+        #[arg(long = "sysprop", value_name = "KEY=VALUE", value_parser = parse_sysprop)]
+        sysprops: Vec<(String, String)>,
         /// Arguments to pass to the Java program
         #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
         args: Vec<String>,
+        #[command(flatten)]
+        selection: PackageSelection,
     },
+    /// Run any main class from the project or its dependencies on the runtime classpath
+    Exec {
+        /// Fully-qualified main class, e.g. `org.flywaydb.commandline.Main`
+        #[arg(value_name = "CLASS")]
+        main_class: String,
+        /// Arguments to pass to the Java program
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Start jshell with the compiled classes and runtime dependencies on its classpath
+    Repl,
     /// Run tests
-    Test,
+    Test {
+        /// Only run tests matching these filters: `FooTest`, `com.example.*Test`,
+        /// or `FooTest#shouldWork` (`*` and `?` are wildcards)
+        #[arg(value_name = "FILTER", value_parser = parse_test_filter)]
+        filters: Vec<TestFilter>,
+        /// Only run tests with this JUnit tag or tag expression (repeatable),
+        /// replacing `[test] tags`
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+        /// Skip tests with this JUnit tag or tag expression (repeatable)
+        #[arg(long = "exclude-tag", value_name = "TAG")]
+        exclude_tags: Vec<String>,
+        /// Also write a JSON summary to target/test-reports/summary.json
+        #[arg(long)]
+        json_summary: bool,
+        /// Run each test class in its own JVM, in name order, and stop after the
+        /// first class with failures
+        #[arg(long, conflicts_with_all = ["shuffle", "seed"])]
+        fail_fast: bool,
+        /// Number of slowest tests to list after the run, overriding `[test] slowest`
+        /// (0 to turn the list off)
+        #[arg(long, value_name = "N")]
+        slowest: Option<usize>,
+        /// Run test classes and methods in a random order and print the seed
+        #[arg(long)]
+        shuffle: bool,
+        /// Shuffle with this seed to reproduce an earlier order (implies `--shuffle`)
+        #[arg(long, value_name = "SEED")]
+        seed: Option<u64>,
+        /// Only run the tests that failed in the last run
+        #[arg(long, conflicts_with_all = ["filters", "affected"])]
+        rerun_failed: bool,
+        /// After the unit tests pass, compile and run the integration tests in it/
+        #[arg(long, conflicts_with_all = ["affected", "rerun_failed"])]
+        integration: bool,
+        /// Record coverage with JaCoCo, write reports to target/coverage/, and
+        /// check the `[test.coverage]` minimums
+        #[arg(long)]
+        coverage: bool,
+        /// After the unit tests pass, run PIT mutation analysis, write reports to
+        /// target/mutation/, and check the `[test.mutation]` threshold
+        #[arg(long, conflicts_with_all = ["filters", "affected", "rerun_failed"])]
+        mutation: bool,
+        /// Record a Java Flight Recorder profile into target/profiles/
+        #[arg(long)]
+        profile_jvm: bool,
+        /// Only run tests affected by changes since `--since`
+        #[arg(long)]
+        affected: bool,
+        /// Git ref to compare against with `--affected`
+        #[arg(
+            long,
+            value_name = "REF",
+            default_value = "HEAD",
+            requires = "affected"
+        )]
+        since: String,
+        /// Output format: `human` or `json` (newline-delimited messages on stdout)
+        #[arg(long, value_name = "FMT", default_value = "human", value_parser = ["human", "json"])]
+        message_format: String,
+        /// Re-run on file changes
+        #[arg(long)]
+        watch: bool,
+        #[command(flatten)]
+        selection: PackageSelection,
+    },
+    /// Compile and run the JMH benchmarks in bench/, saving results to target/bench/
+    Bench {
+        /// Only run benchmarks matching these regular expressions
+        #[arg(value_name = "FILTER")]
+        filters: Vec<String>,
+        /// Forked JVMs per benchmark, overriding `[bench] forks` (0 runs in-process)
+        #[arg(long, value_name = "N")]
+        forks: Option<u32>,
+        /// Warmup iterations per fork, overriding `[bench] warmup-iterations`
+        #[arg(long, value_name = "N")]
+        warmup_iterations: Option<u32>,
+        /// Measurement iterations per fork, overriding `[bench] iterations`
+        #[arg(long, value_name = "N")]
+        iterations: Option<u32>,
+    },
     /// Check the project for errors without producing a JAR
     Check {
+        /// Only validate Jargo.toml, listing every problem, without compiling
+        #[arg(long, conflicts_with_all = ["fmt", "license_header", "watch"])]
+        manifest: bool,
         /// Also check formatting
         #[arg(long)]
         fmt: bool,
+        /// Also check every source file starts with the `[lint.header]`
+        /// license header
+        #[arg(long)]
+        license_header: bool,
+        /// Output format: `human` or `json` (newline-delimited messages on stdout)
+        #[arg(long, value_name = "FMT", default_value = "human", value_parser = ["human", "json"])]
+        message_format: String,
+        /// Re-run on file changes
+        #[arg(long)]
+        watch: bool,
+        #[command(flatten)]
+        selection: PackageSelection,
+    },
+    /// Re-run `build` (default), `check`, `test`, or `run` whenever project files change
+    Watch {
+        /// Command to re-run, with its arguments
+        #[arg(
+            value_name = "COMMAND",
+            trailing_var_arg = true,
+            allow_hyphen_values = true
+        )]
+        command: Vec<String>,
     },
+    /// Run the `[ci]` pipeline (fmt, lint, compile, test, audit) with one report
+    Ci,
     /// Remove the target directory
-    Clean,
+    Clean {
+        #[command(flatten)]
+        selection: PackageSelection,
+    },
     /// Add a dependency
     Add {
         /// Maven coordinate (groupId:artifactId)
         coordinate: String,
-        /// Specific version (otherwise queries Maven Central for latest)
+        /// Specific version (otherwise the latest release in the repository)
         #[arg(long)]
         version: Option<String>,
     },
     /// Update dependencies to latest versions and regenerate lock file
     Update,
     /// Display the dependency tree
-    Tree,
-    /// Format source files
-    Fmt,
-    /// Auto-fix package declarations
-    Fix,
-    /// Generate Javadoc
-    Doc,
+    Tree {
+        /// List artifacts that workspace members lock at different versions
+        #[arg(long)]
+        duplicates: bool,
+        #[command(flatten)]
+        selection: PackageSelection,
+    },
+    /// Format source files with the `[fmt]` formatter
+    Fmt {
+        /// List the files that are not formatted, without changing them
+        #[arg(long)]
+        check: bool,
+    },
+    /// Check the sources with Checkstyle
+    Lint,
+    /// Auto-fix package declarations, or with `--imports` and
+    /// `--license-header`, organize imports and insert license headers
+    Fix {
+        /// Remove unused imports and sort the rest
+        #[arg(long)]
+        imports: bool,
+        /// Insert the `[lint.header]` license header where it is missing
+        #[arg(long)]
+        license_header: bool,
+    },
+    /// Generate Javadoc into target/doc, linked to the JDK and dependency docs
+    Doc {
+        /// Open the generated docs in a browser
+        #[arg(long)]
+        open: bool,
+        /// Fail on any javadoc warning, like `[doc] deny-warnings`
+        #[arg(long)]
+        deny_warnings: bool,
+    },
+    /// Report uses of deprecated APIs, grouped by dependency
+    Deprecations,
+    /// List the licenses of runtime dependencies and check `[licenses] allow`
+    Licenses,
+    /// Compare the public API with the last release and check the version
+    /// bump is big enough for the changes
+    ApiCheck {
+        /// Version to compare with, instead of the newest release before
+        /// this one
+        #[arg(long, value_name = "VERSION")]
+        baseline: Option<String>,
+    },
+    /// Write a Maven pom.xml for the package to target/
+    Pom,
+    /// Build an app and install a launcher for it in ~/.jargo/bin, or install
+    /// the package into a local repository
+    Install {
+        /// Published app to install instead of the current package
+        #[arg(value_name = "GROUP:ARTIFACT:VERSION", conflicts_with_all = ["path", "to_m2"])]
+        coordinate: Option<String>,
+        /// Install the app in this directory instead of the current package
+        #[arg(long, value_name = "DIR")]
+        path: Option<PathBuf>,
+        /// Install the JAR and POM into the local Maven repository
+        /// (~/.m2/repository) instead
+        #[arg(long)]
+        to_m2: bool,
+        #[command(flatten)]
+        selection: PackageSelection,
+    },
+    /// Remove an app installed with `jargo install` from ~/.jargo/bin
+    Uninstall {
+        /// Package name of the app
+        name: String,
+    },
+    /// Release the package to Maven Central
+    Publish {
+        /// Sign and upload the release through the Central Portal
+        #[arg(long, required_unless_present_any = ["check", "repo"], conflicts_with = "repo")]
+        central: bool,
+        /// Upload the release to the `[publish.<NAME>]` repository in Jargo.toml
+        #[arg(long, value_name = "NAME")]
+        repo: Option<String>,
+        /// Check the release without uploading it: no snapshot versions,
+        /// required metadata, members published, version not yet
+        /// published, and a complete JAR
+        #[arg(long)]
+        check: bool,
+        /// Publish as soon as the deployment validates, instead of waiting
+        /// to be published from the portal
+        #[arg(long, requires = "central")]
+        auto_release: bool,
+        #[command(flatten)]
+        selection: PackageSelection,
+    },
+    /// Release the package: test, set the release version, build, commit and
+    /// tag, publish, and start the next snapshot (`[release] steps`)
+    Release {
+        /// `patch`, `minor`, `major`, or an explicit version; `patch` releases
+        /// a `-SNAPSHOT` as its own version
+        #[arg(value_name = "BUMP", default_value = "patch")]
+        bump: String,
+        /// Leave out a step of the pipeline (repeatable)
+        #[arg(long, value_name = "STEP")]
+        skip: Vec<String>,
+    },
+    /// Bump `package.version` in Jargo.toml and print the new version
+    Version {
+        /// `patch`, `minor`, `major`, or an explicit version
+        #[arg(value_name = "BUMP")]
+        bump: String,
+        /// Commit the changed Jargo.toml files
+        #[arg(long)]
+        commit: bool,
+        /// Commit and tag the commit `v{version}` (`{name}-v{version}` for
+        /// workspace members)
+        #[arg(long)]
+        tag: bool,
+        #[command(flatten)]
+        selection: PackageSelection,
+    },
+    /// Inspect resolved dependencies
+    Deps {
+        #[command(subcommand)]
+        action: DepsAction,
+    },
+    /// Analyze the project's class graph
+    Graph {
+        #[command(subcommand)]
+        action: GraphAction,
+    },
+    /// Generate IDE project files
+    Ide {
+        #[command(subcommand)]
+        action: IdeAction,
+    },
+    /// Manage the background compile daemon
+    Daemon {
+        #[command(subcommand)]
+        action: DaemonAction,
+    },
+    /// Print the effective configuration: cache, repositories, proxy, and
+    /// toolchain, with where each comes from
+    Env,
+    /// Read and change the global configuration in ~/.jargo/config.toml
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Run a `[tasks]` entry from Jargo.toml, or list them without a name
+    RunTask {
+        /// Task name
+        name: Option<String>,
+        /// Arguments appended to the task command
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Any other name runs the `[tasks]` entry of that name
+    #[command(external_subcommand)]
+    Task(Vec<String>),
+}
+
+/// What `new` and `init` generate, beyond the name and kind of package.
+#[derive(Args, Clone, Debug)]
+pub struct NewOptions {
+    /// Java feature release to compile for [default: 21]
+    #[arg(long, value_name = "VERSION")]
+    pub java: Option<String>,
+    /// Base package of the sources, instead of the name without hyphens
+    #[arg(long = "package", value_name = "PACKAGE")]
+    pub base_package: Option<String>,
+    /// Main class of the app, in the base package [default: Main]
+    #[arg(long, value_name = "CLASS", conflicts_with = "lib")]
+    pub main_class: Option<String>,
+    /// Framework of the generated test: `junit`, `testng`, or `none` for no test
+    #[arg(long, value_name = "FRAMEWORK", default_value = "junit", value_parser = ["junit", "testng", "none"])]
+    pub test_framework: String,
+}
+
+/// Workspace members a command runs in. Without either flag, that is the
+/// member in the current directory, or every member at the workspace root.
+#[derive(Args, Clone, Debug, Default)]
+pub struct PackageSelection {
+    /// Run in this workspace member (repeatable)
+    #[arg(short = 'p', long = "package", value_name = "MEMBER")]
+    pub packages: Vec<String>,
+    /// Run in every workspace member
+    #[arg(long, conflicts_with = "packages")]
+    pub workspace: bool,
+}
+
+impl Command {
+    /// The `-p`/`--workspace` flags of commands that can run in several
+    /// workspace members; `None` for the others.
+    pub fn package_selection(&self) -> Option<&PackageSelection> {
+        match self {
+            Command::Build { selection, .. }
+            | Command::Run { selection, .. }
+            | Command::Test { selection, .. }
+            | Command::Check { selection, .. }
+            | Command::Clean { selection }
+            | Command::Install { selection, .. }
+            | Command::Publish { selection, .. }
+            | Command::Version { selection, .. }
+            | Command::Tree { selection, .. } => Some(selection),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Subcommand, Clone)]
+pub enum GraphAction {
+    /// List test classes affected by changes since a git ref
+    AffectedTests {
+        /// Git ref to compare against
+        #[arg(long, value_name = "REF", default_value = "HEAD")]
+        since: String,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+pub enum ConfigAction {
+    /// Print a key's value (`cache-dir`, `repository`, or `proxy`)
+    Get { key: String },
+    /// Set a key
+    Set { key: String, value: String },
+    /// Remove a key, restoring its default
+    Unset { key: String },
+}
+
+#[derive(Subcommand, Clone)]
+pub enum IdeAction {
+    /// Write Eclipse .project and .classpath files, also read by VS Code's
+    /// Java extension (jdtls)
+    Eclipse,
+    /// Write .vscode/settings.json and launch.json for VS Code's Java
+    /// extension, keeping entries jargo did not write
+    Vscode,
+}
+
+#[derive(Subcommand, Clone)]
+pub enum DepsAction {
+    /// Fail if a dependency has class files newer than the project's `java`
+    BytecodeCheck,
+}
+
+#[derive(Subcommand, Clone)]
+pub enum DaemonAction {
+    /// Start the compile daemon (builds reuse it while it runs)
+    Start,
+    /// Stop the compile daemon
+    Stop,
+    /// Report whether the compile daemon is running
+    Status,
+}
+
+/// Parse a `jargo test` filter.
+fn parse_test_filter(arg: &str) -> Result<TestFilter, String> {
+    TestFilter::parse(arg).map_err(|e| e.to_string())
+}
+
+/// Parse `--sysprop KEY=VALUE`. The value may be empty or contain `=`.
+fn parse_sysprop(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("expected KEY=VALUE, got `{}`", arg)),
+    }
 }
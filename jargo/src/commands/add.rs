@@ -0,0 +1,52 @@
+use anyhow::{bail, Result};
+
+use jargo_core::cache;
+use jargo_core::context::GlobalContext;
+use jargo_core::errors::JargoError;
+use jargo_core::manifest::{self, JargoToml};
+
+/// Execute `jargo add <group:artifact>`: add the dependency to
+/// `[dependencies]` at `version`, or at the latest release in the
+/// repository. The next build resolves it into Jargo.lock.
+pub fn exec(gctx: &GlobalContext, coordinate: &str, version: Option<&str>) -> Result<()> {
+    let manifest_path = gctx.cwd.join("Jargo.toml");
+
+    if !manifest_path.exists() {
+        return Err(JargoError::ManifestNotFound.into());
+    }
+
+    let manifest = JargoToml::from_file(&manifest_path)
+        .map_err(|e| JargoError::ManifestParse(e.to_string()))?;
+    let (group, artifact) = manifest::parse_coordinate(coordinate)?;
+    for (table, entries) in [
+        ("dependencies", &manifest.dependencies),
+        ("dev-dependencies", &manifest.dev_dependencies),
+        ("it-dependencies", &manifest.it_dependencies),
+    ] {
+        if entries.contains_key(coordinate) {
+            bail!("`{}` is already in [{}]", coordinate, table);
+        }
+    }
+
+    let version = match version {
+        Some(version) => version.to_string(),
+        None => {
+            let versions = cache::published_versions(&group, &artifact)?;
+            match versions
+                .into_iter()
+                .rev()
+                .find(|v| !v.ends_with("-SNAPSHOT"))
+            {
+                Some(version) => version,
+                None => bail!("no release of `{}` found in the repository", coordinate),
+            }
+        }
+    };
+
+    manifest::add_dependency(&manifest_path, coordinate, &version)?;
+    gctx.shell.status(
+        "Adding",
+        &format!("{} v{} to dependencies", coordinate, version),
+    );
+    Ok(())
+}
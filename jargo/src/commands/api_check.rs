@@ -0,0 +1,93 @@
+use anyhow::Result;
+use std::path::Path;
+
+use jargo_core::api_check::{self, ApiChange};
+use jargo_core::cache;
+use jargo_core::context::GlobalContext;
+use jargo_core::errors::JargoError;
+use jargo_core::manifest::JargoToml;
+
+use crate::commands::build;
+
+/// Execute `jargo api-check`: build the JAR and compare its public API with
+/// `baseline`, or else the newest release before this version, and fail
+/// when the version bump is smaller than the changes need.
+pub fn exec(gctx: &GlobalContext, baseline: Option<&str>) -> Result<()> {
+    let manifest_path = gctx.cwd.join("Jargo.toml");
+
+    if !manifest_path.exists() {
+        return Err(JargoError::ManifestNotFound.into());
+    }
+
+    let manifest = JargoToml::from_file(&manifest_path)
+        .map_err(|e| JargoError::ManifestParse(e.to_string()))?;
+
+    build::exec(gctx, false, false, false)?;
+
+    let package = &manifest.package;
+    let jar_path = gctx
+        .cwd
+        .join("target")
+        .join(format!("{}.jar", package.name));
+    let Some((baseline, changes)) = compare(gctx, &manifest, &jar_path, baseline)? else {
+        gctx.shell.status(
+            "Finished",
+            &format!("no release of {} before {}", package.name, package.version),
+        );
+        return Ok(());
+    };
+
+    let (breaking, added): (Vec<&ApiChange>, Vec<&ApiChange>) =
+        changes.iter().partition(|c| c.is_breaking());
+    for (heading, changes) in [("Breaking changes:", &breaking), ("Additions:", &added)] {
+        if !changes.is_empty() {
+            gctx.shell.print(heading);
+            for change in changes {
+                gctx.shell.print(format!("  {}", change));
+            }
+        }
+    }
+    gctx.shell.status(
+        "Compared",
+        &format!(
+            "{} {} with {}: {} breaking change(s), {} addition(s)",
+            package.name,
+            package.version,
+            baseline,
+            breaking.len(),
+            added.len()
+        ),
+    );
+
+    if let Some(reason) = api_check::semver_violation(&baseline, &package.version, &changes) {
+        return Err(JargoError::ApiCheckFailed(reason).into());
+    }
+    Ok(())
+}
+
+/// Compare the public API of `jar` with `baseline`, or else the newest
+/// release of the package before its version, fetched from the configured
+/// repository. Returns the baseline version and the changes, or `None` when
+/// there is no earlier release.
+pub fn compare(
+    gctx: &GlobalContext,
+    manifest: &JargoToml,
+    jar: &Path,
+    baseline: Option<&str>,
+) -> Result<Option<(String, Vec<ApiChange>)>> {
+    let group = manifest.get_group();
+    let package = &manifest.package;
+    let baseline = match baseline {
+        Some(version) => version.to_string(),
+        None => {
+            let versions = cache::published_versions(&group, &package.name)?;
+            match api_check::baseline(&versions, &package.version) {
+                Some(version) => version,
+                None => return Ok(None),
+            }
+        }
+    };
+    let (old_jar, _sha256) = cache::fetch_jar(gctx, &group, &package.name, &baseline)?;
+    let changes = api_check::compare(&api_check::read_jar(&old_jar)?, &api_check::read_jar(jar)?);
+    Ok(Some((baseline, changes)))
+}
@@ -0,0 +1,143 @@
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::process;
+use jargo_core::bench::{self, BenchOptions};
+use jargo_core::compiler::{self, SourceSet};
+use jargo_core::context::GlobalContext;
+use jargo_core::env;
+use jargo_core::errors::JargoError;
+use jargo_core::manifest::JargoToml;
+use jargo_core::resolver;
+use jargo_core::toolchain;
+
+/// Execute `jargo bench`: compile `bench/` with JMH, run the harness, and
+/// compare the results with the previous run.
+pub fn exec(gctx: &GlobalContext, opts: BenchOptions) -> Result<()> {
+    let manifest_path = gctx.cwd.join("Jargo.toml");
+
+    if !manifest_path.exists() {
+        return Err(JargoError::ManifestNotFound.into());
+    }
+
+    let manifest = JargoToml::from_file(&manifest_path)
+        .map_err(|e| JargoError::ManifestParse(e.to_string()))?;
+
+    if !compiler::has_sources(&gctx.cwd, &manifest, SourceSet::Bench)? {
+        gctx.shell.warn("no benchmark sources found in bench/");
+        return Ok(());
+    }
+
+    let resolved = resolver::resolve(gctx, &gctx.cwd, &manifest)?;
+
+    gctx.shell.status(
+        "Compiling",
+        &format!(
+            "{} v{} (java {})",
+            manifest.package.name, manifest.package.version, manifest.package.java
+        ),
+    );
+
+    let compile_output = compiler::compile(gctx, &gctx.cwd, &manifest, &resolved.compile_jars)?;
+    if !compile_output.success {
+        compile_output.print_errors(&gctx.shell, &manifest.package.name);
+        return Err(JargoError::CompilationFailed.into());
+    }
+    compile_output.print_warnings(&gctx.shell, &manifest.package.name);
+
+    // Benchmarks compile against main classes, compile deps, dev deps, and
+    // JMH, and run with the runtime deps instead of the compile deps.
+    let jmh = bench::fetch_jmh(gctx)?;
+    let classes_dir = gctx.cwd.join(SourceSet::Main.output_dir());
+    let mut bench_compile_cp = vec![classes_dir.clone()];
+    bench_compile_cp.extend(resolved.compile_jars.iter().cloned());
+    bench_compile_cp.extend(resolved.test_jars.iter().cloned());
+    bench_compile_cp.extend(jmh.runtime.iter().cloned());
+
+    let bench_output = compiler::compile_benchmarks(
+        gctx,
+        &gctx.cwd,
+        &manifest,
+        &bench_compile_cp,
+        &jmh.processor,
+    )?;
+    if !bench_output.success {
+        bench_output.print_errors(&gctx.shell, &manifest.package.name);
+        return Err(JargoError::CompilationFailed.into());
+    }
+    bench_output.print_warnings(&gctx.shell, &manifest.package.name);
+
+    let mut runtime_classpath: Vec<PathBuf> =
+        vec![gctx.cwd.join(SourceSet::Bench.output_dir()), classes_dir];
+    runtime_classpath.extend(resolved.runtime_jars.iter().cloned());
+    runtime_classpath.extend(resolved.test_jars.iter().cloned());
+    runtime_classpath.extend(jmh.runtime);
+    let classpath = std::env::join_paths(&runtime_classpath).context("invalid classpath entry")?;
+
+    let (result_file, previous) = bench::result_paths(&gctx.cwd)?;
+    let config = manifest.bench.as_ref();
+    let jvm_args = config.map(|c| &c.jvm_args[..]).unwrap_or_default();
+
+    gctx.shell.status(
+        "Running",
+        &format!("benchmarks ({})", SourceSet::Bench.output_dir()),
+    );
+    let toolchain = toolchain::resolve(&gctx.cwd, &manifest)?;
+    let mut java = Command::new(toolchain.java());
+    java.arg("-cp")
+        .arg(&classpath)
+        .args(jvm_args)
+        .arg("org.openjdk.jmh.Main")
+        .args(bench::jmh_args(&opts, config, &result_file))
+        .envs(env::program_env(&gctx.cwd, &manifest, None)?)
+        .current_dir(&gctx.cwd);
+    let status = process::run_foreground(&mut java).map_err(|e| -> anyhow::Error {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            JargoError::JavaNotFound.into()
+        } else {
+            e.into()
+        }
+    })?;
+    if !status.success() {
+        std::process::exit(process::exit_code(status));
+    }
+
+    if !result_file.exists() {
+        gctx.shell
+            .warn("no benchmarks matched; nothing was recorded");
+        return Ok(());
+    }
+    let results = bench::read_results(&result_file)?;
+    // A previous run that was interrupted may have left a partial file
+    let previous = previous
+        .and_then(|path| bench::read_results(&path).ok())
+        .unwrap_or_default();
+
+    gctx.shell.print("");
+    for result in &results {
+        let change = match result.change_from(&previous) {
+            Some(change) => format!(" ({:+.1}% vs previous run)", change),
+            None => String::new(),
+        };
+        let error = if result.error.is_nan() {
+            String::new()
+        } else {
+            format!(" ± {:.3}", result.error)
+        };
+        gctx.shell.print(format!(
+            "bench {} [{}] ... {:.3}{} {}{}",
+            result.name(),
+            result.mode,
+            result.score,
+            error,
+            result.unit,
+            change
+        ));
+    }
+    gctx.shell.print("");
+
+    let display = result_file.strip_prefix(&gctx.cwd).unwrap_or(&result_file);
+    gctx.shell.status("Saved", &display.display().to_string());
+    Ok(())
+}
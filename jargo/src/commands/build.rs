@@ -1,13 +1,17 @@
 use anyhow::Result;
 
-use jargo_core::compiler;
+use jargo_core::build_info;
+use jargo_core::compiler::{self, SourceSet};
 use jargo_core::context::GlobalContext;
 use jargo_core::errors::JargoError;
 use jargo_core::jar;
+use jargo_core::licenses;
 use jargo_core::manifest::JargoToml;
+use jargo_core::message::Message;
+use jargo_core::notices;
 use jargo_core::resolver;
 
-pub fn exec(gctx: &GlobalContext) -> Result<()> {
+pub fn exec(gctx: &GlobalContext, keep_going: bool, uber: bool, thin: bool) -> Result<()> {
     let manifest_path = gctx.cwd.join("Jargo.toml");
 
     if !manifest_path.exists() {
@@ -21,6 +25,12 @@ pub fn exec(gctx: &GlobalContext) -> Result<()> {
     // Resolve dependencies (uses lock file if present, else resolves + writes lock)
     let resolved = resolver::resolve(gctx, &gctx.cwd, &manifest)?;
 
+    // Fail before compiling when a dependency's license is not allowed
+    if let Some(allow) = manifest.get_license_allowlist() {
+        let deps = licenses::collect(gctx, &resolved.lock_entries)?;
+        crate::commands::licenses::enforce(gctx, &deps, allow)?;
+    }
+
     // Print Cargo-style compilation status
     gctx.shell.status(
         "Compiling",
@@ -31,28 +41,72 @@ pub fn exec(gctx: &GlobalContext) -> Result<()> {
     );
 
     // Compile with dependency classpath
-    let compile_output = compiler::compile(gctx, &gctx.cwd, &manifest, &resolved.compile_jars)?;
+    let compile_output = if keep_going {
+        compiler::compile_keep_going(gctx, &gctx.cwd, &manifest, &resolved.compile_jars)?
+    } else {
+        compiler::compile(gctx, &gctx.cwd, &manifest, &resolved.compile_jars)?
+    };
 
     if !compile_output.success {
-        for error in compile_output.errors {
-            eprintln!("{}", error);
+        compile_output.print_errors(&gctx.shell, &manifest.package.name);
+        if keep_going {
+            for source in &compile_output.failed_sources {
+                gctx.shell
+                    .warn(&format!("{} was not compiled", source.display()));
+            }
+            gctx.shell.status(
+                "Partial",
+                &format!(
+                    "{} source(s) failed, other classes written to target/classes",
+                    compile_output.failed_sources.len()
+                ),
+            );
         }
+        gctx.shell
+            .message(&Message::BuildFinished { success: false });
         return Err(JargoError::CompilationFailed.into());
     }
 
+    let package = manifest.package.name.as_str();
+    let warnings = compile_output.print_warnings(&gctx.shell, package);
+    gctx.shell.message(&Message::CompilerArtifact {
+        package,
+        kind: "classes",
+        path: &gctx.cwd.join(SourceSet::Main.output_dir()),
+    });
+
+    if manifest.build_info_enabled() {
+        build_info::write_build_info(&gctx.cwd, &manifest)?;
+    }
+
     // Assemble JAR
-    let jar_path = jar::assemble_jar(gctx, &gctx.cwd, &manifest)?;
+    let jar_path = if uber {
+        let notices = notices::collect(gctx, &resolved.lock_entries)?;
+        jar::assemble_uber_jar(gctx, &gctx.cwd, &manifest, &resolved.runtime_jars, &notices)?
+    } else if thin {
+        jar::assemble_thin_jar(gctx, &gctx.cwd, &manifest, &resolved.runtime_jars)?
+    } else {
+        jar::assemble_jar(gctx, &gctx.cwd, &manifest)?
+    };
 
     gctx.shell.status(
         "Finished",
         &format!(
-            "JAR at {}",
+            "JAR at {}{}",
             jar_path
                 .strip_prefix(&gctx.cwd)
                 .unwrap_or(&jar_path)
-                .display()
+                .display(),
+            compiler::warnings_suffix(warnings)
         ),
     );
+    gctx.shell.message(&Message::CompilerArtifact {
+        package,
+        kind: "jar",
+        path: &jar_path,
+    });
+    gctx.shell
+        .message(&Message::BuildFinished { success: true });
 
     Ok(())
 }
@@ -0,0 +1,146 @@
+use anyhow::{anyhow, Context, Result};
+use std::fs;
+
+use jargo_core::compiler::SourceSet;
+use jargo_core::context::GlobalContext;
+use jargo_core::errors::JargoError;
+use jargo_core::manifest::{self, JargoToml};
+use jargo_core::message::Message;
+use jargo_core::{compiler, format, header, resolver};
+
+use crate::commands::{fmt, task};
+
+/// Execute `jargo check`: compile the main sources without assembling a
+/// JAR, then with `--fmt`, check their formatting like `jargo fmt --check`,
+/// and with `--license-header`, their `[lint.header]` license headers.
+pub fn exec(gctx: &GlobalContext, check_fmt: bool, check_license_header: bool) -> Result<()> {
+    let manifest_path = gctx.cwd.join("Jargo.toml");
+
+    if !manifest_path.exists() {
+        return Err(JargoError::ManifestNotFound.into());
+    }
+
+    let manifest = JargoToml::from_file(&manifest_path)
+        .map_err(|e| JargoError::ManifestParse(e.to_string()))?;
+    let template = if check_license_header {
+        Some(manifest.get_license_header().ok_or_else(|| {
+            anyhow!("`--license-header` needs a `[lint.header] template` in Jargo.toml")
+        })?)
+    } else {
+        None
+    };
+
+    let resolved = resolver::resolve(gctx, &gctx.cwd, &manifest)?;
+
+    gctx.shell.status(
+        "Checking",
+        &format!(
+            "{} v{} (java {})",
+            manifest.package.name, manifest.package.version, manifest.package.java
+        ),
+    );
+    let compile_output = compiler::compile(gctx, &gctx.cwd, &manifest, &resolved.compile_jars)?;
+    if !compile_output.success {
+        compile_output.print_errors(&gctx.shell, &manifest.package.name);
+        gctx.shell
+            .message(&Message::BuildFinished { success: false });
+        return Err(JargoError::CompilationFailed.into());
+    }
+    let warnings = compile_output.print_warnings(&gctx.shell, &manifest.package.name);
+    gctx.shell.message(&Message::CompilerArtifact {
+        package: &manifest.package.name,
+        kind: "classes",
+        path: &gctx.cwd.join(SourceSet::Main.output_dir()),
+    });
+
+    if check_fmt {
+        fmt::verify(gctx, &manifest)?;
+    }
+    if let Some(template) = template {
+        verify_license_headers(gctx, &manifest, template)?;
+    }
+    gctx.shell.status(
+        "Finished",
+        &format!(
+            "checked {} v{}{}",
+            manifest.package.name,
+            manifest.package.version,
+            compiler::warnings_suffix(warnings)
+        ),
+    );
+    gctx.shell
+        .message(&Message::BuildFinished { success: true });
+    Ok(())
+}
+
+/// Execute `jargo check --manifest`: report unknown keys in Jargo.toml and
+/// every problem commands would otherwise find one at a time, without
+/// resolving or compiling anything.
+pub fn exec_manifest(gctx: &GlobalContext) -> Result<()> {
+    let manifest_path = gctx.cwd.join("Jargo.toml");
+
+    if !manifest_path.exists() {
+        return Err(JargoError::ManifestNotFound.into());
+    }
+
+    warn_unknown_keys(gctx);
+    let manifest = JargoToml::from_file(&manifest_path)
+        .map_err(|e| JargoError::ManifestParse(e.to_string()))?;
+    for name in task::shadowed(&manifest) {
+        gctx.shell.warn(&format!(
+            "task `{0}` in [tasks] has the name of a built-in command; `jargo {0}` runs the built-in, `jargo run-task {0}` the task",
+            name
+        ));
+    }
+    let problems = manifest.validate();
+    for problem in &problems {
+        gctx.shell.error(problem);
+    }
+    if !problems.is_empty() {
+        return Err(JargoError::InvalidManifest(problems.len()).into());
+    }
+    gctx.shell
+        .status("Checked", &format!("{}", manifest_path.display()));
+    Ok(())
+}
+
+/// Warn about each key of the Jargo.toml in the current directory that
+/// jargo does not know. A manifest that does not parse is left for the
+/// command to report.
+pub fn warn_unknown_keys(gctx: &GlobalContext) {
+    let Ok(content) = fs::read_to_string(gctx.cwd.join("Jargo.toml")) else {
+        return;
+    };
+    for key in manifest::unknown_keys(&content).unwrap_or_default() {
+        gctx.shell
+            .warn(&format!("unknown key `{}` in Jargo.toml is ignored", key));
+    }
+}
+
+/// List the files `jargo fmt` formats that do not start with the license
+/// header, and fail if there are any.
+fn verify_license_headers(
+    gctx: &GlobalContext,
+    manifest: &JargoToml,
+    template: &str,
+) -> Result<()> {
+    let files = format::source_files(&gctx.cwd, manifest)?;
+    let mut missing = 0;
+    for file in &files {
+        let path = gctx.cwd.join(file);
+        let source = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        if !header::has_header(&source, template) {
+            gctx.shell.print(format!("  {}", file.display()));
+            missing += 1;
+        }
+    }
+    if missing > 0 {
+        return Err(JargoError::MissingLicenseHeader(missing).into());
+    }
+    gctx.shell.status(
+        "Finished",
+        &format!("{} file(s) have the license header", files.len()),
+    );
+    Ok(())
+}
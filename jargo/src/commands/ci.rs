@@ -0,0 +1,125 @@
+use anyhow::Result;
+use std::time::Instant;
+
+use crate::commands::test::{self, PreparedTests, TestArgs};
+use crate::commands::{doc, fmt, licenses, lint};
+use jargo_core::context::GlobalContext;
+use jargo_core::errors::JargoError;
+use jargo_core::manifest::{CiStep, JargoToml};
+
+enum StepStatus {
+    Ok,
+    Failed(String),
+    Skipped(&'static str),
+}
+
+/// Execute `jargo ci`: run every `[ci] steps` entry in order, then print one
+/// aggregated report. Later steps still run after a failure so a single run
+/// surfaces every problem; `test` is skipped when `compile` failed.
+pub fn exec(gctx: &GlobalContext) -> Result<()> {
+    let manifest_path = gctx.cwd.join("Jargo.toml");
+
+    if !manifest_path.exists() {
+        return Err(JargoError::ManifestNotFound.into());
+    }
+
+    let manifest = JargoToml::from_file(&manifest_path)
+        .map_err(|e| JargoError::ManifestParse(e.to_string()))?;
+
+    // Compiled test inputs from the `compile` step, reused by `test`.
+    let mut prepared: Option<Option<PreparedTests>> = None;
+    let mut compile_failed = false;
+    let mut results = Vec::new();
+
+    for step in manifest.get_ci_steps() {
+        let start = Instant::now();
+        let status = match step {
+            CiStep::Compile => match test::prepare(gctx, &manifest) {
+                Ok(p) => {
+                    prepared = Some(p);
+                    StepStatus::Ok
+                }
+                Err(e) => {
+                    compile_failed = true;
+                    StepStatus::Failed(e.to_string())
+                }
+            },
+            CiStep::Test if compile_failed => StepStatus::Skipped("compile failed"),
+            CiStep::Test => run_tests(gctx, &manifest, prepared.take()),
+            CiStep::Fmt => match fmt::verify(gctx, &manifest) {
+                Ok(()) => StepStatus::Ok,
+                Err(e) => StepStatus::Failed(e.to_string()),
+            },
+            CiStep::Lint => match lint::verify(gctx, &manifest) {
+                Ok(()) => StepStatus::Ok,
+                Err(e) => StepStatus::Failed(e.to_string()),
+            },
+            CiStep::Doc => match doc::generate(gctx, &manifest, false) {
+                Ok(_) => StepStatus::Ok,
+                Err(e) => StepStatus::Failed(e.to_string()),
+            },
+            CiStep::Audit => match licenses::verify(gctx, &manifest) {
+                Ok(()) => StepStatus::Ok,
+                Err(e) => StepStatus::Failed(e.to_string()),
+            },
+        };
+        results.push((step, status, start.elapsed().as_secs_f64()));
+    }
+
+    let failed: Vec<&str> = results
+        .iter()
+        .filter(|(_, status, _)| matches!(status, StepStatus::Failed(_)))
+        .map(|(step, _, _)| step.name())
+        .collect();
+    let skipped = results
+        .iter()
+        .filter(|(_, status, _)| matches!(status, StepStatus::Skipped(_)))
+        .count();
+
+    gctx.shell.print("");
+    gctx.shell.print("ci summary:");
+    for (step, status, secs) in &results {
+        let line = match status {
+            StepStatus::Ok => format!("ok ({:.2}s)", secs),
+            StepStatus::Failed(msg) => format!("FAILED ({})", msg),
+            StepStatus::Skipped(reason) => format!("skipped ({})", reason),
+        };
+        gctx.shell.print(format!("    {:<8} {}", step.name(), line));
+    }
+    gctx.shell.print("");
+    gctx.shell.print(format!(
+        "ci result: {}. {} passed; {} failed; {} skipped",
+        if failed.is_empty() { "ok" } else { "FAILED" },
+        results.len() - failed.len() - skipped,
+        failed.len(),
+        skipped
+    ));
+    gctx.shell.print("");
+
+    if !failed.is_empty() {
+        return Err(JargoError::CiFailed(failed.join(", ")).into());
+    }
+
+    Ok(())
+}
+
+fn run_tests(
+    gctx: &GlobalContext,
+    manifest: &JargoToml,
+    prepared: Option<Option<PreparedTests>>,
+) -> StepStatus {
+    let prepared = match prepared {
+        Some(p) => p,
+        None => match test::prepare(gctx, manifest) {
+            Ok(p) => p,
+            Err(e) => return StepStatus::Failed(e.to_string()),
+        },
+    };
+    match prepared {
+        None => StepStatus::Skipped("no test sources"),
+        Some(p) => match test::run(gctx, manifest, &p, &TestArgs::default()) {
+            Ok(()) => StepStatus::Ok,
+            Err(e) => StepStatus::Failed(e.to_string()),
+        },
+    }
+}
@@ -1,10 +1,21 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use std::fs;
 
 use jargo_core::context::GlobalContext;
 
 pub fn exec(gctx: &GlobalContext) -> Result<()> {
+    // In a workspace member, target/ links to the member's part of the
+    // workspace's target/
     let target = gctx.cwd.join("target");
+    let target = target.canonicalize().unwrap_or(target);
+    // A target directory containing the project would take the sources with it
+    let project = gctx.cwd.canonicalize().unwrap_or_else(|_| gctx.cwd.clone());
+    if project.starts_with(&target) {
+        bail!(
+            "refusing to remove {}: it contains the project",
+            target.display()
+        );
+    }
 
     if target.exists() {
         fs::remove_dir_all(&target)?;
@@ -0,0 +1,41 @@
+use anyhow::{bail, Result};
+
+use crate::cli::ConfigAction;
+use jargo_core::config::{ConfigKey, GlobalConfig};
+use jargo_core::context::GlobalContext;
+
+/// Execute `jargo config <action>` on `~/.jargo/config.toml`.
+pub fn exec(gctx: &GlobalContext, action: ConfigAction) -> Result<()> {
+    // Read afresh: the loaded config may have been exported to the
+    // environment
+    let mut config = GlobalConfig::load(&gctx.jargo_home)?;
+    match action {
+        ConfigAction::Get { key } => {
+            let key = ConfigKey::parse(&key)?;
+            match config.get(key) {
+                Some(value) => gctx.shell.print(value),
+                None => bail!("`{}` is not set in {}", key.name(), config.path.display()),
+            }
+        }
+        ConfigAction::Set { key, value } => {
+            let key = ConfigKey::parse(&key)?;
+            config.set(key, &value)?;
+            gctx.shell.status(
+                "Updated",
+                &format!("`{}` in {}", key.name(), config.path.display()),
+            );
+        }
+        ConfigAction::Unset { key } => {
+            let key = ConfigKey::parse(&key)?;
+            if config.unset(key)? {
+                gctx.shell.status(
+                    "Removed",
+                    &format!("`{}` from {}", key.name(), config.path.display()),
+                );
+            } else {
+                gctx.shell.status("Nothing", "to remove");
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use anyhow::Result;
+
+use crate::cli::DaemonAction;
+use jargo_core::context::GlobalContext;
+use jargo_core::daemon;
+use jargo_core::errors::JargoError;
+use jargo_core::manifest::JargoToml;
+use jargo_core::toolchain;
+
+/// Execute `jargo daemon <start|stop|status>`.
+pub fn exec(gctx: &GlobalContext, action: DaemonAction) -> Result<()> {
+    match action {
+        DaemonAction::Start => {
+            // Apply the project's JDK and `[build] javac-memory` when started
+            // inside one.
+            let manifest_path = gctx.cwd.join("Jargo.toml");
+            let (toolchain, jvm_args) = if manifest_path.exists() {
+                let manifest = JargoToml::from_file(&manifest_path)
+                    .map_err(|e| JargoError::ManifestParse(e.to_string()))?;
+                (
+                    toolchain::resolve(&gctx.cwd, &manifest)?,
+                    manifest.get_javac_jvm_args()?,
+                )
+            } else {
+                (toolchain::from_environment()?, Vec::new())
+            };
+
+            let port = daemon::start(gctx, &toolchain, &jvm_args)?;
+            gctx.shell
+                .status("Started", &format!("compile daemon on port {}", port));
+        }
+        DaemonAction::Stop => {
+            if daemon::stop(gctx)? {
+                gctx.shell.status("Stopped", "compile daemon");
+            } else {
+                gctx.shell.status("Nothing", "to stop");
+            }
+        }
+        DaemonAction::Status => {
+            if daemon::is_running(gctx) {
+                gctx.shell.status("Running", "compile daemon");
+            } else {
+                gctx.shell.status("Stopped", "compile daemon");
+            }
+        }
+    }
+    Ok(())
+}
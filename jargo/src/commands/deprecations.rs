@@ -0,0 +1,108 @@
+use anyhow::Result;
+
+use jargo_core::cache;
+use jargo_core::compiler::{self, parse_diagnostics};
+use jargo_core::context::GlobalContext;
+use jargo_core::deprecations::{self, ClassIndex};
+use jargo_core::errors::JargoError;
+use jargo_core::manifest::JargoToml;
+use jargo_core::resolver;
+
+/// Execute `jargo deprecations`: compile with deprecation lints and report
+/// every deprecated API use, grouped by the dependency that declared it.
+pub fn exec(gctx: &GlobalContext) -> Result<()> {
+    let manifest_path = gctx.cwd.join("Jargo.toml");
+
+    if !manifest_path.exists() {
+        return Err(JargoError::ManifestNotFound.into());
+    }
+
+    let manifest = JargoToml::from_file(&manifest_path)
+        .map_err(|e| JargoError::ManifestParse(e.to_string()))?;
+
+    let resolved = resolver::resolve(gctx, &gctx.cwd, &manifest)?;
+
+    gctx.shell.status(
+        "Compiling",
+        &format!(
+            "{} v{} (java {})",
+            manifest.package.name, manifest.package.version, manifest.package.java
+        ),
+    );
+
+    let compile_output = compiler::compile_with_flags(
+        gctx,
+        &gctx.cwd,
+        &manifest,
+        &resolved.compile_jars,
+        deprecations::LINT_ARGS,
+    )?;
+
+    if !compile_output.success {
+        compile_output.print_errors(&gctx.shell, &manifest.package.name);
+        return Err(JargoError::CompilationFailed.into());
+    }
+
+    let uses = deprecations::parse_deprecations(&parse_diagnostics(&compile_output.warnings));
+    if uses.is_empty() {
+        gctx.shell
+            .status("Finished", "no deprecated API uses found");
+        return Ok(());
+    }
+
+    let mut index = ClassIndex::default();
+    for entry in resolved
+        .lock_entries
+        .iter()
+        .filter(|e| e.scope == "compile")
+    {
+        let (jar, _) = cache::fetch_jar(gctx, &entry.group, &entry.artifact, &entry.version)?;
+        index.add_jar(
+            &jar,
+            &format!("{}:{}:{}", entry.group, entry.artifact, entry.version),
+        )?;
+    }
+
+    let groups = deprecations::group_by_dependency(
+        &uses,
+        &index,
+        &manifest.get_base_package(),
+        &manifest.package.name,
+    );
+
+    gctx.shell.status(
+        "Found",
+        &format!(
+            "{} deprecated API use{} from {} source{}",
+            uses.len(),
+            if uses.len() == 1 { "" } else { "s" },
+            groups.len(),
+            if groups.len() == 1 { "" } else { "s" }
+        ),
+    );
+
+    for (label, apis) in &groups {
+        gctx.shell.print("");
+        gctx.shell.print(label);
+        for (api, sites) in apis {
+            let removal = if sites[0].for_removal {
+                " [for removal]"
+            } else {
+                ""
+            };
+            gctx.shell.print(format!(
+                "    {}{} ({} use{})",
+                api,
+                removal,
+                sites.len(),
+                if sites.len() == 1 { "" } else { "s" }
+            ));
+            for site in sites {
+                gctx.shell.print(format!("        {}", site.location));
+            }
+        }
+    }
+    gctx.shell.print("");
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use anyhow::{bail, Result};
+
+use crate::cli::DepsAction;
+use jargo_core::bytecode;
+use jargo_core::cache;
+use jargo_core::context::GlobalContext;
+use jargo_core::errors::JargoError;
+use jargo_core::manifest::JargoToml;
+use jargo_core::resolver;
+
+/// Execute `jargo deps <action>`.
+pub fn exec(gctx: &GlobalContext, action: DepsAction) -> Result<()> {
+    let manifest_path = gctx.cwd.join("Jargo.toml");
+
+    if !manifest_path.exists() {
+        return Err(JargoError::ManifestNotFound.into());
+    }
+
+    let manifest = JargoToml::from_file(&manifest_path)
+        .map_err(|e| JargoError::ManifestParse(e.to_string()))?;
+
+    match action {
+        DepsAction::BytecodeCheck => bytecode_check(gctx, &manifest),
+    }
+}
+
+/// Scan every resolved JAR (all scopes) for class files the project's `java`
+/// release cannot load.
+fn bytecode_check(gctx: &GlobalContext, manifest: &JargoToml) -> Result<()> {
+    let Some(release) = bytecode::parse_release(&manifest.package.java) else {
+        bail!(
+            "invalid `java = \"{}\"` in Jargo.toml",
+            manifest.package.java
+        );
+    };
+
+    let resolved = resolver::resolve(gctx, &gctx.cwd, manifest)?;
+
+    let mut offenders = 0;
+    for entry in &resolved.lock_entries {
+        let coordinate = format!("{}:{}:{}", entry.group, entry.artifact, entry.version);
+        let (jar, _) = cache::fetch_jar(gctx, &entry.group, &entry.artifact, &entry.version)?;
+        gctx.shell
+            .verbose(|sh| sh.print(format!("  [verbose] scanning {}", jar.display())));
+
+        let newer = bytecode::classes_newer_than(&jar, release)?;
+        let Some(max_major) = newer.iter().map(|(_, major)| *major).max() else {
+            continue;
+        };
+        offenders += 1;
+
+        let (example, _) = newer
+            .iter()
+            .find(|(_, major)| *major == max_major)
+            .expect("max comes from this list");
+        gctx.shell.print(format!(
+            "  {} ({} scope) requires Java {}: {} class file{}, e.g. {}",
+            coordinate,
+            entry.scope,
+            bytecode::release_of(max_major),
+            newer.len(),
+            if newer.len() == 1 { "" } else { "s" },
+            example
+        ));
+    }
+
+    if offenders > 0 {
+        return Err(JargoError::BytecodeTooNew(offenders, manifest.package.java.clone()).into());
+    }
+
+    gctx.shell.status(
+        "Checked",
+        &format!(
+            "{} dependenc{} for Java {}",
+            resolved.lock_entries.len(),
+            if resolved.lock_entries.len() == 1 {
+                "y"
+            } else {
+                "ies"
+            },
+            release
+        ),
+    );
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use anyhow::Result;
+
+use crate::commands::build;
+use jargo_core::context::GlobalContext;
+use jargo_core::dist;
+use jargo_core::errors::JargoError;
+use jargo_core::manifest::JargoToml;
+use jargo_core::notices;
+use jargo_core::resolver;
+
+/// Execute `jargo dist`: build the JAR, then lay out `target/dist/{name}/`
+/// with launcher scripts, the runtime classpath, and third-party notices.
+pub fn exec(gctx: &GlobalContext) -> Result<()> {
+    let manifest_path = gctx.cwd.join("Jargo.toml");
+
+    if !manifest_path.exists() {
+        return Err(JargoError::ManifestNotFound.into());
+    }
+
+    let manifest = JargoToml::from_file(&manifest_path)
+        .map_err(|e| JargoError::ManifestParse(e.to_string()))?;
+
+    // Launchers need a main class
+    if !manifest.is_app() {
+        return Err(JargoError::NotAnApp.into());
+    }
+
+    build::exec(gctx, false, false, false)?;
+
+    let resolved = resolver::resolve(gctx, &gctx.cwd, &manifest)?;
+    let agents = resolver::fetch_agents(gctx, &manifest)?;
+    let jar_path = gctx
+        .cwd
+        .join("target")
+        .join(format!("{}.jar", manifest.package.name));
+
+    let dist_dir = dist::write_dist(
+        &gctx.cwd,
+        &manifest,
+        &jar_path,
+        &resolved.runtime_jars,
+        &agents,
+    )?;
+    let notices = notices::collect(gctx, &resolved.lock_entries)?;
+    if !notices.is_empty() {
+        notices::write_to_dir(&dist_dir, &manifest, &notices)?;
+    }
+
+    gctx.shell.status(
+        "Packaged",
+        &dist_dir
+            .strip_prefix(&gctx.cwd)
+            .unwrap_or(&dist_dir)
+            .display()
+            .to_string(),
+    );
+
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use jargo_core::context::GlobalContext;
+use jargo_core::errors::JargoError;
+use jargo_core::javadoc::{self, DocLink, DocOptions};
+use jargo_core::manifest::JargoToml;
+use jargo_core::{resolver, staging};
+
+/// Execute `jargo doc`: generate the API docs of the main sources into
+/// `target/doc/`, linked to the JDK and dependency docs, and with `--open`,
+/// show them in the browser. `--deny-warnings` fails on any javadoc warning,
+/// like `[doc] deny-warnings`.
+pub fn exec(gctx: &GlobalContext, open: bool, deny_warnings: bool) -> Result<()> {
+    let manifest_path = gctx.cwd.join("Jargo.toml");
+
+    if !manifest_path.exists() {
+        return Err(JargoError::ManifestNotFound.into());
+    }
+
+    let manifest = JargoToml::from_file(&manifest_path)
+        .map_err(|e| JargoError::ManifestParse(e.to_string()))?;
+
+    let index = generate(gctx, &manifest, deny_warnings)?;
+    if open {
+        gctx.shell.status("Opening", &index.display().to_string());
+        open_in_browser(&index)?;
+    }
+    Ok(())
+}
+
+/// Generate `target/doc/` with the `[doc]` doclint checks, failing on
+/// warnings when `deny_warnings` or `[doc] deny-warnings` is set. Returns
+/// the path of `index.html`. Used by `jargo doc` and the `doc` CI step.
+pub fn generate(
+    gctx: &GlobalContext,
+    manifest: &JargoToml,
+    deny_warnings: bool,
+) -> Result<PathBuf> {
+    let resolved = resolver::resolve(gctx, &gctx.cwd, manifest)?;
+    staging::create_staging(&gctx.cwd, &manifest.get_base_package())?;
+    gctx.shell.status(
+        "Documenting",
+        &format!("{} v{}", manifest.package.name, manifest.package.version),
+    );
+
+    let mut links = Vec::new();
+    match javadoc::jdk_link(gctx, &manifest.package.java) {
+        Ok(Some(link)) => links.push(link),
+        Ok(None) => gctx
+            .shell
+            .warn("the JDK docs have no package list; JDK types are not linked"),
+        Err(e) => gctx
+            .shell
+            .warn(&format!("JDK types are not linked: {:#}", e)),
+    }
+    for entry in resolved
+        .lock_entries
+        .iter()
+        .filter(|e| e.scope == "compile")
+    {
+        let coordinates = format!("{}:{}:{}", entry.group, entry.artifact, entry.version);
+        match javadoc::dependency_link(gctx, &entry.group, &entry.artifact, &entry.version) {
+            Ok(Some(link)) => links.push(link),
+            Ok(None) => gctx.shell.verbose(|sh| {
+                sh.print(format!(
+                    "  [verbose] {} publishes no javadoc; not linked",
+                    coordinates
+                ))
+            }),
+            Err(e) => gctx
+                .shell
+                .warn(&format!("{} is not linked: {:#}", coordinates, e)),
+        }
+    }
+    gctx.shell.verbose(|sh| {
+        for DocLink { url, .. } in &links {
+            sh.print(format!("  [verbose] linking {}", url));
+        }
+    });
+
+    let options = DocOptions {
+        dir: gctx.cwd.join(javadoc::DOC_DIR),
+        links,
+        doclint: manifest.get_doclint(),
+        deny_warnings: deny_warnings || manifest.get_doc_deny_warnings(),
+    };
+    gctx.shell
+        .verbose(|sh| sh.print(format!("  [verbose] doclint: {}", options.doclint)));
+    javadoc::generate_into(gctx, &gctx.cwd, manifest, &resolved.compile_jars, &options)?;
+    gctx.shell.status(
+        "Finished",
+        &format!("generated {}/index.html", javadoc::DOC_DIR),
+    );
+    Ok(options.dir.join("index.html"))
+}
+
+/// Hand `path` to the platform's default opener.
+fn open_in_browser(path: &Path) -> Result<()> {
+    let mut cmd = if cfg!(target_os = "windows") {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", "start", ""]);
+        cmd
+    } else if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else {
+        Command::new("xdg-open")
+    };
+    let status = cmd
+        .arg(path)
+        .status()
+        .with_context(|| format!("failed to open {} in a browser", path.display()))?;
+    if !status.success() {
+        anyhow::bail!("failed to open {} in a browser", path.display());
+    }
+    Ok(())
+}
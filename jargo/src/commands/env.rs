@@ -0,0 +1,97 @@
+use anyhow::Result;
+
+use jargo_core::config::{ConfigKey, Origin, CONFIG_FILE};
+use jargo_core::context::GlobalContext;
+use jargo_core::errors::JargoError;
+use jargo_core::manifest::JargoToml;
+use jargo_core::toolchain;
+use jargo_core::workspace::Workspace;
+
+/// Execute `jargo env`: print the effective configuration as TOML, with
+/// where each value comes from. Works outside of projects too.
+pub fn exec(gctx: &GlobalContext) -> Result<()> {
+    let manifest_path = gctx.cwd.join("Jargo.toml");
+    // A workspace root has no project settings of its own
+    let manifest = if manifest_path.exists() && Workspace::load(&gctx.cwd)?.is_none() {
+        Some(
+            JargoToml::from_file(&manifest_path)
+                .map_err(|e| JargoError::ManifestParse(e.to_string()))?,
+        )
+    } else {
+        None
+    };
+
+    let sh = &gctx.shell;
+    sh.print(format!(
+        "jargo-home = {}",
+        quote(&gctx.jargo_home.to_string_lossy())
+    ));
+    sh.print(format!(
+        "config = {}",
+        quote(&gctx.jargo_home.join(CONFIG_FILE).to_string_lossy())
+    ));
+    for key in ConfigKey::ALL {
+        let mut effective = gctx.config.effective(key, &gctx.jargo_home);
+        if let Some((value, origin)) = &mut effective {
+            let project_target_dir = manifest
+                .as_ref()
+                .and_then(|m| m.build.as_ref()?.target_dir.clone());
+            if let (ConfigKey::TargetDir, Origin::Default, Some(dir)) =
+                (key, &origin, project_target_dir)
+            {
+                *value = dir;
+                *origin = Origin::Manifest;
+            }
+        }
+        match effective {
+            Some((value, origin)) => {
+                sh.print(format!("{} = {}  # {}", key.name(), quote(&value), origin))
+            }
+            None => sh.print(format!("# {} is not set", key.name())),
+        }
+    }
+
+    let toolchain = match &manifest {
+        Some(manifest) => toolchain::resolve(&gctx.cwd, manifest),
+        None => toolchain::from_environment(),
+    };
+    match toolchain {
+        Ok(toolchain) => {
+            match &toolchain.home {
+                Some(home) => sh.print(format!(
+                    "toolchain = {}  # {}",
+                    quote(&home.to_string_lossy()),
+                    toolchain.source
+                )),
+                None => sh.print(format!(
+                    "# toolchain home is unknown ({})",
+                    toolchain.source
+                )),
+            }
+            if let Some(version) = toolchain.version {
+                sh.print(format!("toolchain-version = {}", version));
+            }
+        }
+        Err(e) => sh.print(format!("# toolchain: {}", e)),
+    }
+
+    if let Some(manifest) = &manifest {
+        sh.print(format!(
+            "java = {}  # Jargo.toml",
+            quote(&manifest.package.java)
+        ));
+        for (name, target) in &manifest.publish {
+            sh.print(format!(
+                "publish.{} = {}  # Jargo.toml",
+                name,
+                quote(&target.url)
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// `value` as a TOML basic string.
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
@@ -0,0 +1,17 @@
+use anyhow::Result;
+
+use crate::commands::run::{self, RunArgs};
+use jargo_core::context::GlobalContext;
+
+/// Execute `jargo exec`: compile, then run `main_class` (from the project or
+/// any runtime dependency) like `jargo run` would, in app and lib projects.
+pub fn exec(gctx: &GlobalContext, main_class: String, args: Vec<String>) -> Result<()> {
+    run::exec(
+        gctx,
+        RunArgs {
+            main_class: Some(main_class),
+            args,
+            ..RunArgs::default()
+        },
+    )
+}
@@ -0,0 +1,137 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::PathBuf;
+
+use jargo_core::context::GlobalContext;
+use jargo_core::errors::JargoError;
+use jargo_core::format;
+use jargo_core::header;
+use jargo_core::imports;
+use jargo_core::manifest::JargoToml;
+use jargo_core::toolchain;
+
+/// Execute `jargo fix` with `--imports`, `--license-header`, or both, over
+/// the files `jargo fmt` formats.
+pub fn exec(gctx: &GlobalContext, fix_imports: bool, fix_license_header: bool) -> Result<()> {
+    let manifest_path = gctx.cwd.join("Jargo.toml");
+
+    if !manifest_path.exists() {
+        return Err(JargoError::ManifestNotFound.into());
+    }
+
+    let manifest = JargoToml::from_file(&manifest_path)
+        .map_err(|e| JargoError::ManifestParse(e.to_string()))?;
+
+    let template = if fix_license_header {
+        Some(manifest.get_license_header().ok_or_else(|| {
+            anyhow::anyhow!("`--license-header` needs a `[lint.header] template` in Jargo.toml")
+        })?)
+    } else {
+        None
+    };
+    let files = format::source_files(&gctx.cwd, &manifest)?;
+    if files.is_empty() {
+        gctx.shell.status("Finished", "no source files to fix");
+        return Ok(());
+    }
+    if let Some(template) = template {
+        license_headers(gctx, &manifest, &files, template)?;
+    }
+    if fix_imports {
+        organize_imports(gctx, &manifest, &files)?;
+    }
+    Ok(())
+}
+
+/// Insert the `[lint.header]` license header, with the current year, into
+/// each file that does not start with it, replacing any other leading
+/// comment.
+fn license_headers(
+    gctx: &GlobalContext,
+    manifest: &JargoToml,
+    files: &[PathBuf],
+    template: &str,
+) -> Result<()> {
+    gctx.shell.status(
+        "Fixing",
+        &format!(
+            "license headers of {} v{}",
+            manifest.package.name, manifest.package.version
+        ),
+    );
+    let year = header::current_year();
+    let mut fixed = 0;
+    for file in files {
+        let path = gctx.cwd.join(file);
+        let source = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        if let Some(with_header) = header::apply(&source, template, &year) {
+            write(gctx, file, &with_header)?;
+            fixed += 1;
+        }
+    }
+    gctx.shell.status(
+        "Finished",
+        &format!(
+            "fixed license headers in {} of {} file(s)",
+            fixed,
+            files.len()
+        ),
+    );
+    Ok(())
+}
+
+/// Remove unused imports and sort the rest, with the `[fmt] formatter`'s
+/// import ordering, or the built-in organizer for the Eclipse formatter.
+fn organize_imports(gctx: &GlobalContext, manifest: &JargoToml, files: &[PathBuf]) -> Result<()> {
+    gctx.shell.status(
+        "Fixing",
+        &format!(
+            "imports of {} v{}",
+            manifest.package.name, manifest.package.version
+        ),
+    );
+
+    let mut fixed = 0;
+    match format::fetch_import_fixer(gctx, &gctx.cwd, manifest)? {
+        Some(fixer) => {
+            let toolchain = toolchain::resolve(&gctx.cwd, manifest)?;
+            for file in format::check(&gctx.cwd, &toolchain, &fixer, files)? {
+                write(gctx, &file.path, &file.formatted)?;
+                fixed += 1;
+            }
+        }
+        None => {
+            let order = manifest.get_import_order();
+            for file in files {
+                let path = gctx.cwd.join(file);
+                let source = fs::read_to_string(&path)
+                    .with_context(|| format!("failed to read {}", path.display()))?;
+                match imports::organize(&source, &order) {
+                    Some(organized) if organized != source => {
+                        write(gctx, file, &organized)?;
+                        fixed += 1;
+                    }
+                    Some(_) => {}
+                    None => gctx.shell.warn(&format!(
+                        "{}: left as is, since its imports are interleaved with comments",
+                        file.display()
+                    )),
+                }
+            }
+        }
+    }
+    gctx.shell.status(
+        "Finished",
+        &format!("fixed imports in {} of {} file(s)", fixed, files.len()),
+    );
+    Ok(())
+}
+
+fn write(gctx: &GlobalContext, file: &std::path::Path, contents: &str) -> Result<()> {
+    let path = gctx.cwd.join(file);
+    fs::write(&path, contents).with_context(|| format!("failed to write {}", path.display()))?;
+    gctx.shell
+        .verbose(|sh| sh.print(format!("  [verbose] fixed {}", file.display())));
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use anyhow::{Context, Result};
+use std::fs;
+
+use jargo_core::context::GlobalContext;
+use jargo_core::errors::JargoError;
+use jargo_core::format;
+use jargo_core::manifest::JargoToml;
+use jargo_core::toolchain;
+
+/// Execute `jargo fmt`: reformat every source file with the `[fmt]
+/// formatter`, or with `check`, list the files that would change and fail.
+pub fn exec(gctx: &GlobalContext, check: bool) -> Result<()> {
+    let manifest_path = gctx.cwd.join("Jargo.toml");
+
+    if !manifest_path.exists() {
+        return Err(JargoError::ManifestNotFound.into());
+    }
+
+    let manifest = JargoToml::from_file(&manifest_path)
+        .map_err(|e| JargoError::ManifestParse(e.to_string()))?;
+
+    if check {
+        return verify(gctx, &manifest);
+    }
+    let (checked, unformatted) = run(gctx, &manifest)?;
+    for file in &unformatted {
+        let path = gctx.cwd.join(&file.path);
+        fs::write(&path, &file.formatted)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        gctx.shell
+            .verbose(|sh| sh.print(format!("  [verbose] {}", file)));
+    }
+    gctx.shell.status(
+        "Finished",
+        &format!("formatted {} of {} file(s)", unformatted.len(), checked),
+    );
+    Ok(())
+}
+
+/// Fail listing every source file of `manifest` that is not formatted, with
+/// the lines that would change. Used by `jargo fmt --check` and `jargo check
+/// --fmt`.
+pub fn verify(gctx: &GlobalContext, manifest: &JargoToml) -> Result<()> {
+    let (checked, unformatted) = run(gctx, manifest)?;
+    if !unformatted.is_empty() {
+        for file in &unformatted {
+            gctx.shell.print(format!("  {}", file));
+        }
+        return Err(JargoError::NotFormatted(unformatted.len()).into());
+    }
+    gctx.shell
+        .status("Finished", &format!("{} file(s) formatted", checked));
+    Ok(())
+}
+
+/// Format copies of the sources, returning how many there are and the ones
+/// that change.
+fn run(gctx: &GlobalContext, manifest: &JargoToml) -> Result<(usize, Vec<format::Unformatted>)> {
+    if manifest.format.is_some() {
+        gctx.shell.warn(
+            "`[format]` in Jargo.toml is deprecated; use `[fmt] style` (`\"aosp\"` for 4-space \
+             or `\"google\"` for 2-space indents) and `[fmt] include`",
+        );
+    }
+    let files = format::source_files(&gctx.cwd, manifest)?;
+    if files.is_empty() {
+        return Ok((0, Vec::new()));
+    }
+    let toolchain = toolchain::resolve(&gctx.cwd, manifest)?;
+    let formatter = format::fetch_formatter(gctx, &gctx.cwd, manifest)?;
+    gctx.shell.status(
+        "Formatting",
+        &format!("{} v{}", manifest.package.name, manifest.package.version),
+    );
+    let unformatted = format::check(&gctx.cwd, &toolchain, &formatter, &files)?;
+    Ok((files.len(), unformatted))
+}
@@ -0,0 +1,55 @@
+use anyhow::Result;
+
+use crate::cli::GraphAction;
+use crate::commands::test;
+use jargo_core::affected::{self, AffectedTests};
+use jargo_core::context::GlobalContext;
+use jargo_core::errors::JargoError;
+use jargo_core::manifest::JargoToml;
+use jargo_core::toolchain;
+
+/// Execute `jargo graph <action>`.
+pub fn exec(gctx: &GlobalContext, action: GraphAction) -> Result<()> {
+    let manifest_path = gctx.cwd.join("Jargo.toml");
+
+    if !manifest_path.exists() {
+        return Err(JargoError::ManifestNotFound.into());
+    }
+
+    let manifest = JargoToml::from_file(&manifest_path)
+        .map_err(|e| JargoError::ManifestParse(e.to_string()))?;
+
+    match action {
+        GraphAction::AffectedTests { since } => {
+            // Class dependencies are read from compiled output.
+            if test::prepare(gctx, &manifest)?.is_none() {
+                gctx.shell.warn("no test sources found in test/");
+                return Ok(());
+            }
+
+            let toolchain = toolchain::resolve(&gctx.cwd, &manifest)?;
+            match affected::affected_tests(
+                &gctx.cwd,
+                &toolchain,
+                &manifest.get_base_package(),
+                &since,
+            )? {
+                AffectedTests::All(trigger) => {
+                    gctx.shell
+                        .status("Affected", &format!("all tests ({} changed)", trigger));
+                }
+                AffectedTests::Classes(classes) => {
+                    gctx.shell.status(
+                        "Affected",
+                        &format!("{} test class(es) since {}", classes.len(), since),
+                    );
+                    for class in classes {
+                        gctx.shell.print(class);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use anyhow::Result;
+use std::path::PathBuf;
+
+use crate::cli::IdeAction;
+use jargo_core::compiler::{self, SourceSet};
+use jargo_core::context::GlobalContext;
+use jargo_core::eclipse::{self, Library};
+use jargo_core::errors::JargoError;
+use jargo_core::manifest::JargoToml;
+use jargo_core::test_runner::TestEngine;
+use jargo_core::{resolver, staging, vscode};
+
+/// Execute `jargo ide <action>`.
+pub fn exec(gctx: &GlobalContext, action: IdeAction) -> Result<()> {
+    let manifest_path = gctx.cwd.join("Jargo.toml");
+
+    if !manifest_path.exists() {
+        return Err(JargoError::ManifestNotFound.into());
+    }
+
+    let manifest = JargoToml::from_file(&manifest_path)
+        .map_err(|e| JargoError::ManifestParse(e.to_string()))?;
+
+    match action {
+        IdeAction::Eclipse => {
+            let libraries = libraries(gctx, &manifest)?;
+            gctx.shell.status(
+                "Generating",
+                &format!(
+                    "Eclipse project for {} v{}",
+                    manifest.package.name, manifest.package.version
+                ),
+            );
+            let attached = libraries.iter().filter(|l| l.sources.is_some()).count();
+            for path in eclipse::write(&gctx.cwd, &manifest, &libraries)? {
+                gctx.shell
+                    .verbose(|sh| sh.print(format!("  [verbose] wrote {}", path.display())));
+            }
+            gctx.shell.status(
+                "Finished",
+                &format!(
+                    "wrote .project and .classpath with {} JAR(s), {} with sources",
+                    libraries.len(),
+                    attached
+                ),
+            );
+        }
+        IdeAction::Vscode => {
+            let libraries = libraries(gctx, &manifest)?;
+            gctx.shell.status(
+                "Generating",
+                &format!(
+                    "VS Code settings for {} v{}",
+                    manifest.package.name, manifest.package.version
+                ),
+            );
+            // The Java extension expects package directories, so it reads
+            // the staged source sets.
+            let base_package = manifest.get_base_package();
+            let mut source_roots = Vec::new();
+            for (dir, staging_dir) in vscode::SOURCE_SETS {
+                if gctx.cwd.join(dir).is_dir() {
+                    staging::create_staging_for(&gctx.cwd, &base_package, dir, staging_dir)?;
+                    source_roots.push(format!("target/{}", staging_dir));
+                }
+            }
+            for path in vscode::write(&gctx.cwd, &manifest, &source_roots, &libraries)? {
+                gctx.shell
+                    .verbose(|sh| sh.print(format!("  [verbose] wrote {}", path.display())));
+            }
+            gctx.shell.status(
+                "Finished",
+                &format!(
+                    "wrote .vscode/settings.json and .vscode/launch.json with {} JAR(s)",
+                    libraries.len()
+                ),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The runtime dependencies, then those only tests see, including the test
+/// engine's JAR when there are tests.
+fn libraries(gctx: &GlobalContext, manifest: &JargoToml) -> Result<Vec<Library>> {
+    let resolved = resolver::resolve(gctx, &gctx.cwd, manifest)?;
+    let mut test_jars: Vec<PathBuf> = resolved
+        .test_jars
+        .iter()
+        .chain(&resolved.it_jars)
+        .cloned()
+        .collect();
+    if compiler::has_sources(&gctx.cwd, manifest, SourceSet::Test)?
+        || compiler::has_sources(&gctx.cwd, manifest, SourceSet::IntegrationTest)?
+    {
+        let engine = TestEngine::for_framework(gctx, manifest.get_test_framework())?;
+        test_jars.extend(engine.compile_jar().map(PathBuf::from));
+    }
+
+    let mut libraries: Vec<Library> = Vec::new();
+    let jars = resolved
+        .runtime_jars
+        .into_iter()
+        .map(|jar| (jar, false))
+        .chain(test_jars.into_iter().map(|jar| (jar, true)));
+    for (jar, test) in jars {
+        if !libraries.iter().any(|l| l.jar == jar) {
+            libraries.push(Library::new(jar, test));
+        }
+    }
+    Ok(libraries)
+}
@@ -0,0 +1,61 @@
+use anyhow::Result;
+
+use crate::commands::build;
+use jargo_core::container;
+use jargo_core::context::GlobalContext;
+use jargo_core::errors::JargoError;
+use jargo_core::manifest::JargoToml;
+use jargo_core::resolver;
+
+/// Execute `jargo image`: build, then assemble a layered OCI image and write
+/// it to `target/container/{name}.tar` or push it to its registry.
+pub fn exec(gctx: &GlobalContext, push: bool, bin: Option<&str>) -> Result<()> {
+    let manifest_path = gctx.cwd.join("Jargo.toml");
+
+    if !manifest_path.exists() {
+        return Err(JargoError::ManifestNotFound.into());
+    }
+
+    let manifest = JargoToml::from_file(&manifest_path)
+        .map_err(|e| JargoError::ManifestParse(e.to_string()))?;
+
+    // The entrypoint runs the main class
+    if !manifest.is_app() {
+        return Err(JargoError::NotAnApp.into());
+    }
+
+    // Check the settings before building
+    let bin = manifest.get_bin(bin)?;
+    let target = container::image_name(&manifest)?;
+
+    build::exec(gctx, false, false, false)?;
+
+    let resolved = resolver::resolve(gctx, &gctx.cwd, &manifest)?;
+    let base = container::pull_base(gctx, &manifest)?;
+    let layers = container::app_layers(&gctx.cwd, &manifest, &resolved.runtime_jars)?;
+    let image = container::assemble(&manifest, &bin, &base, layers)?;
+
+    if push {
+        container::push(gctx, &base, &image, &target)?;
+        gctx.shell.status("Pushed", &target.to_string());
+    } else {
+        let tarball = gctx
+            .cwd
+            .join("target/container")
+            .join(format!("{}.tar", manifest.package.name));
+        container::write_tarball(gctx, &base, &image, &target, &tarball)?;
+        gctx.shell.status(
+            "Finished",
+            &format!(
+                "image {} at {}",
+                target,
+                tarball
+                    .strip_prefix(&gctx.cwd)
+                    .unwrap_or(&tarball)
+                    .display()
+            ),
+        );
+    }
+
+    Ok(())
+}
@@ -1,21 +1,30 @@
+use std::fs;
 use std::path::Path;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 
-use crate::commands::new::{scaffold, validate_name};
+use crate::cli::NewOptions;
+use crate::commands::new::{manifest_content, scaffold, validate_name, validate_options};
+use jargo_core::adopt::{self, Adoption, LayoutKind};
 use jargo_core::context::GlobalContext;
 use jargo_core::errors::JargoError;
 
-/// Execute `jargo init`.
-pub fn exec(gctx: &GlobalContext, is_lib: bool) -> Result<()> {
+/// Execute `jargo init`: adopt the Java sources already in the current
+/// directory, or scaffold a new package when there are none.
+pub fn exec(gctx: &GlobalContext, is_lib: bool, options: &NewOptions) -> Result<()> {
     if gctx.cwd.join("Jargo.toml").exists() {
         return Err(JargoError::AlreadyInitialized.into());
     }
 
     let name = dir_name(&gctx.cwd)?;
     validate_name(&name)?;
+    validate_options(options)?;
 
-    scaffold(&gctx.cwd, &name, is_lib)?;
+    if let Some(adoption) = adopt::detect(&gctx.cwd)? {
+        return exec_adopt(gctx, &name, is_lib, options, &adoption);
+    }
+
+    scaffold(&gctx.cwd, &name, is_lib, options)?;
 
     let kind = if is_lib { "lib" } else { "app" };
     gctx.shell
@@ -24,7 +33,96 @@ pub fn exec(gctx: &GlobalContext, is_lib: bool) -> Result<()> {
     Ok(())
 }
 
-fn dir_name(path: &Path) -> Result<String> {
+/// Move existing sources into jargo's layout and write a Jargo.toml for
+/// them. The package is a lib unless a class has a `main` method.
+fn exec_adopt(
+    gctx: &GlobalContext,
+    name: &str,
+    is_lib: bool,
+    options: &NewOptions,
+    adoption: &Adoption,
+) -> Result<()> {
+    if let Some(package) = &options.base_package {
+        if *package != adoption.base_package {
+            bail!(
+                "the sources are in package `{}`, not `{}`",
+                adoption.base_package,
+                package
+            );
+        }
+    }
+    let is_lib = is_lib || (options.main_class.is_none() && adoption.main_class.is_none());
+    let mut options = options.clone();
+    options.base_package = Some(adoption.base_package.clone());
+    if !is_lib && options.main_class.is_none() {
+        options.main_class = adoption.main_class.clone().filter(|c| c != "Main");
+    }
+    if adoption.testng {
+        options.test_framework = "testng".to_string();
+    }
+    let manifest = manifest_content(name, is_lib, &options)?;
+
+    move_sources(gctx, adoption)?;
+    fs::write(gctx.cwd.join("Jargo.toml"), manifest)?;
+    add_to_gitignore(&gctx.cwd)?;
+
+    let kind = if is_lib { "lib" } else { "app" };
+    gctx.shell.status(
+        "Created",
+        &format!(
+            "{} `{}` package (base package {})",
+            kind, name, adoption.base_package
+        ),
+    );
+    Ok(())
+}
+
+/// Move the sources `adoption` found into jargo's layout.
+pub(crate) fn move_sources(gctx: &GlobalContext, adoption: &Adoption) -> Result<()> {
+    let leftover = adopt::apply(&gctx.cwd, adoption)?;
+    for (from, to) in &adoption.moves {
+        gctx.shell.verbose(|sh| {
+            sh.print(format!(
+                "  [verbose] moved {} to {}",
+                from.display(),
+                to.display()
+            ))
+        });
+    }
+    if adoption.kind != LayoutKind::Flat {
+        gctx.shell.status(
+            "Moved",
+            &format!("{} layout sources into src/ and test/", adoption.kind),
+        );
+    }
+    if leftover {
+        gctx.shell.warn(&format!(
+            "{}/ holds files jargo does not use; move resources into resources/",
+            adopt::LEFTOVER_DIR
+        ));
+    }
+    Ok(())
+}
+
+/// Add `target/` to `.gitignore`, creating it when missing.
+pub(crate) fn add_to_gitignore(dir: &Path) -> Result<()> {
+    let path = dir.join(".gitignore");
+    let mut content = fs::read_to_string(&path).unwrap_or_default();
+    if content
+        .lines()
+        .any(|l| matches!(l.trim(), "target/" | "target" | "/target" | "/target/"))
+    {
+        return Ok(());
+    }
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str("target/\n");
+    fs::write(&path, content)?;
+    Ok(())
+}
+
+pub(crate) fn dir_name(path: &Path) -> Result<String> {
     path.file_name()
         .and_then(|n| n.to_str())
         .map(|s| s.to_string())
@@ -0,0 +1,205 @@
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::Path;
+
+use jargo_core::cache::artifact_filename;
+use jargo_core::cleanup::InProgress;
+use jargo_core::context::GlobalContext;
+use jargo_core::errors::JargoError;
+use jargo_core::install::{self, Launcher, Tool};
+use jargo_core::jar;
+use jargo_core::m2;
+use jargo_core::manifest::{DependencyValue, JargoToml};
+use jargo_core::resolver;
+use jargo_core::toolchain;
+use jargo_core::verify;
+
+use crate::commands::{build, pom};
+
+/// Execute `jargo install`: build the app into a self-contained JAR and
+/// install a launcher per entry point in `~/.jargo/bin`, or do the same for
+/// the published app at `coordinate`. With `--to-m2`, install the package's
+/// JAR and POM into the local Maven repository instead.
+pub fn exec(gctx: &GlobalContext, coordinate: Option<&str>, to_m2: bool) -> Result<()> {
+    if let Some(coordinate) = coordinate {
+        return install_published(gctx, coordinate);
+    }
+
+    let manifest_path = gctx.cwd.join("Jargo.toml");
+
+    if !manifest_path.exists() {
+        return Err(JargoError::ManifestNotFound.into());
+    }
+
+    let manifest = JargoToml::from_file(&manifest_path)
+        .map_err(|e| JargoError::ManifestParse(e.to_string()))?;
+
+    if to_m2 {
+        install_to_m2(gctx, &manifest)
+    } else {
+        install_app(gctx, &manifest)
+    }
+}
+
+fn install_to_m2(gctx: &GlobalContext, manifest: &JargoToml) -> Result<()> {
+    build::exec(gctx, false, false, false)?;
+    let pom = pom::render(gctx, manifest)?;
+
+    let package = &manifest.package;
+    let jar = gctx
+        .cwd
+        .join("target")
+        .join(format!("{}.jar", package.name));
+    let group = manifest.get_group();
+    let dir = m2::install(
+        &m2::local_repository()?,
+        &group,
+        &package.name,
+        &package.version,
+        &jar,
+        &pom,
+    )?;
+
+    gctx.shell.status(
+        "Installed",
+        &format!(
+            "{}:{}:{} to {}",
+            group,
+            package.name,
+            package.version,
+            dir.display()
+        ),
+    );
+    Ok(())
+}
+
+/// Install the app in the current directory, run with its JDK and the
+/// `[run]` JVM arguments and system properties.
+fn install_app(gctx: &GlobalContext, manifest: &JargoToml) -> Result<()> {
+    if !manifest.is_app() {
+        bail!("only apps can be installed as tools; use `jargo install --to-m2` for a library");
+    }
+    build::exec(gctx, false, true, false)?;
+
+    let toolchain = toolchain::resolve(&gctx.cwd, manifest)?;
+    let mut jvm_args = manifest.get_jvm_args().to_vec();
+    for (key, value) in manifest.get_system_properties()? {
+        jvm_args.push(format!("-D{}={}", key, value));
+    }
+    let launchers = manifest
+        .get_bins()?
+        .iter()
+        .map(|bin| Launcher {
+            name: bin.name.clone(),
+            main_class: manifest.main_class_of(bin),
+        })
+        .collect();
+    let package = &manifest.package;
+    let tool = Tool {
+        package: &package.name,
+        version: &package.version,
+        java: toolchain.java(),
+        jvm_args,
+        launchers,
+    };
+    let jar = gctx
+        .cwd
+        .join("target")
+        .join(format!("{}.jar", package.name));
+    finish(gctx, &tool, &jar)
+}
+
+/// Install `group:artifact:version` from the repository, run with the JDK
+/// of the environment. Its JAR must name a `Main-Class`.
+fn install_published(gctx: &GlobalContext, coordinate: &str) -> Result<()> {
+    let (group, artifact, version) = install::parse_coordinate(coordinate)?;
+    gctx.shell.status("Installing", coordinate);
+
+    // Resolve and bundle in a scratch project holding only the dependency
+    let scratch = gctx
+        .jargo_home
+        .join("tmp")
+        .join(format!("install-{}", artifact));
+    if scratch.exists() {
+        fs::remove_dir_all(&scratch)
+            .with_context(|| format!("failed to remove {}", scratch.display()))?;
+    }
+    let scratch = InProgress::new(scratch);
+    fs::create_dir_all(scratch.path().join("target"))
+        .with_context(|| format!("failed to create {}", scratch.path().display()))?;
+    install_published_in(gctx, scratch.path(), group, artifact, version)
+}
+
+fn install_published_in(
+    gctx: &GlobalContext,
+    scratch: &Path,
+    group: &str,
+    artifact: &str,
+    version: &str,
+) -> Result<()> {
+    let toolchain = toolchain::from_environment()?;
+    let mut manifest = JargoToml::new_lib(artifact, group);
+    manifest.package.version = version.to_string();
+    if let Some(release) = toolchain.version {
+        manifest.package.java = release.to_string();
+    }
+    manifest.dependencies.insert(
+        format!("{}:{}", group, artifact),
+        DependencyValue::Simple(version.to_string()),
+    );
+    let resolved = resolver::resolve(gctx, scratch, &manifest)?;
+
+    let file_name = artifact_filename(artifact, version, "jar");
+    let app_jar = resolved
+        .runtime_jars
+        .iter()
+        .find(|jar| jar.file_name().is_some_and(|name| *name == *file_name))
+        .with_context(|| format!("{}:{}:{} has no JAR", group, artifact, version))?;
+    let Some(main_class) = verify::jar_main_class(app_jar)? else {
+        bail!(
+            "{}:{}:{} is not an app: its JAR has no Main-Class",
+            group,
+            artifact,
+            version
+        );
+    };
+
+    let jar = jar::assemble_uber_jar(gctx, scratch, &manifest, &resolved.runtime_jars, &[])?;
+    let tool = Tool {
+        package: artifact,
+        version,
+        java: toolchain.java(),
+        jvm_args: Vec::new(),
+        launchers: vec![Launcher {
+            name: artifact.to_string(),
+            main_class,
+        }],
+    };
+    finish(gctx, &tool, &jar)
+}
+
+fn finish(gctx: &GlobalContext, tool: &Tool, jar: &Path) -> Result<()> {
+    let bin_dir = install::bin_dir(&gctx.jargo_home);
+    install::install(&bin_dir, tool, jar)?;
+    let names: Vec<&str> = tool.launchers.iter().map(|l| l.name.as_str()).collect();
+    gctx.shell.status(
+        "Installed",
+        &format!(
+            "{} v{} ({} in {})",
+            tool.package,
+            tool.version,
+            names.join(", "),
+            bin_dir.display()
+        ),
+    );
+
+    let on_path = std::env::var_os("PATH")
+        .is_some_and(|path| std::env::split_paths(&path).any(|dir| dir == bin_dir));
+    if !on_path {
+        gctx.shell.warn(&format!(
+            "{} is not on your PATH; add it to run installed tools by name",
+            bin_dir.display()
+        ));
+    }
+    Ok(())
+}
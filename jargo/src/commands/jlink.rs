@@ -0,0 +1,60 @@
+use anyhow::Result;
+
+use crate::commands::build;
+use jargo_core::context::GlobalContext;
+use jargo_core::errors::JargoError;
+use jargo_core::jlink;
+use jargo_core::manifest::JargoToml;
+use jargo_core::resolver;
+use jargo_core::toolchain;
+
+/// Execute `jargo jlink`: build the JAR, then link a runtime image with only
+/// the modules the app needs into `target/image/`.
+pub fn exec(gctx: &GlobalContext) -> Result<()> {
+    let manifest_path = gctx.cwd.join("Jargo.toml");
+
+    if !manifest_path.exists() {
+        return Err(JargoError::ManifestNotFound.into());
+    }
+
+    let manifest = JargoToml::from_file(&manifest_path)
+        .map_err(|e| JargoError::ManifestParse(e.to_string()))?;
+
+    // The launcher needs a main class
+    if !manifest.is_app() {
+        return Err(JargoError::NotAnApp.into());
+    }
+
+    build::exec(gctx, false, false, false)?;
+
+    let resolved = resolver::resolve(gctx, &gctx.cwd, &manifest)?;
+    let toolchain = toolchain::resolve(&gctx.cwd, &manifest)?;
+    let jar_path = gctx
+        .cwd
+        .join("target")
+        .join(format!("{}.jar", manifest.package.name));
+
+    let modules =
+        jlink::required_modules(&toolchain, &manifest, &jar_path, &resolved.runtime_jars)?;
+    gctx.shell.status("Linking", &modules.join(", "));
+
+    let image_dir = jlink::write_image(
+        &toolchain,
+        &gctx.cwd,
+        &manifest,
+        &modules,
+        &jar_path,
+        &resolved.runtime_jars,
+    )?;
+
+    gctx.shell.status(
+        "Packaged",
+        &image_dir
+            .strip_prefix(&gctx.cwd)
+            .unwrap_or(&image_dir)
+            .display()
+            .to_string(),
+    );
+
+    Ok(())
+}
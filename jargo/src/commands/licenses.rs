@@ -0,0 +1,56 @@
+use anyhow::Result;
+
+use jargo_core::context::GlobalContext;
+use jargo_core::errors::JargoError;
+use jargo_core::licenses::{self, DependencyLicenses};
+use jargo_core::manifest::JargoToml;
+use jargo_core::resolver;
+
+/// Execute `jargo licenses`: list the licenses of the runtime dependencies,
+/// then check them against `[licenses] allow`, if set.
+pub fn exec(gctx: &GlobalContext) -> Result<()> {
+    let manifest_path = gctx.cwd.join("Jargo.toml");
+
+    if !manifest_path.exists() {
+        return Err(JargoError::ManifestNotFound.into());
+    }
+
+    let manifest = JargoToml::from_file(&manifest_path)
+        .map_err(|e| JargoError::ManifestParse(e.to_string()))?;
+
+    verify(gctx, &manifest)
+}
+
+/// List the licenses of the runtime dependencies and check them against
+/// `[licenses] allow`. Used by `jargo licenses` and the `audit` step of
+/// `jargo ci`.
+pub fn verify(gctx: &GlobalContext, manifest: &JargoToml) -> Result<()> {
+    let resolved = resolver::resolve(gctx, &gctx.cwd, manifest)?;
+    let deps = licenses::collect(gctx, &resolved.lock_entries)?;
+    if deps.is_empty() {
+        gctx.shell.status("Finished", "no runtime dependencies");
+        return Ok(());
+    }
+    gctx.shell.print(licenses::render(&deps).trim_end());
+
+    if let Some(allow) = manifest.get_license_allowlist() {
+        enforce(gctx, &deps, allow)?;
+        gctx.shell.status(
+            "Allowed",
+            &format!("{} dependencies by [licenses] allow", deps.len()),
+        );
+    }
+    Ok(())
+}
+
+/// Fail listing every dependency in `deps` that `allow` does not permit.
+pub fn enforce(gctx: &GlobalContext, deps: &[DependencyLicenses], allow: &[String]) -> Result<()> {
+    let problems = licenses::check(deps, allow);
+    if !problems.is_empty() {
+        for problem in &problems {
+            gctx.shell.print(format!("  {}", problem));
+        }
+        return Err(JargoError::LicensesNotAllowed(problems.len()).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,124 @@
+use anyhow::Result;
+
+use jargo_core::compiler::{self, render_diagnostics, Severity};
+use jargo_core::context::GlobalContext;
+use jargo_core::errors::JargoError;
+use jargo_core::lint;
+use jargo_core::manifest::{JargoToml, LintTool};
+use jargo_core::message::Message;
+use jargo_core::{resolver, toolchain};
+
+/// Execute `jargo lint`: run Checkstyle, and PMD and SpotBugs when enabled,
+/// over every source set.
+pub fn exec(gctx: &GlobalContext) -> Result<()> {
+    let manifest_path = gctx.cwd.join("Jargo.toml");
+
+    if !manifest_path.exists() {
+        return Err(JargoError::ManifestNotFound.into());
+    }
+
+    let manifest = JargoToml::from_file(&manifest_path)
+        .map_err(|e| JargoError::ManifestParse(e.to_string()))?;
+
+    verify(gctx, &manifest)
+}
+
+/// Run the `[lint]` tools, print their violations like compiler diagnostics,
+/// and fail when any is at or above `[lint] fail-on`. Used by `jargo lint`
+/// and the `lint` CI step.
+pub fn verify(gctx: &GlobalContext, manifest: &JargoToml) -> Result<()> {
+    let files = lint::source_files(&gctx.cwd)?;
+    let tools = manifest.get_lint_tools();
+    if files.is_empty() || tools.is_empty() {
+        gctx.shell.status("Finished", "nothing to lint");
+        return Ok(());
+    }
+    let toolchain = toolchain::resolve(&gctx.cwd, manifest)?;
+    let names: Vec<&str> = tools.iter().map(|t| t.name()).collect();
+    gctx.shell.status(
+        "Linting",
+        &format!(
+            "{} v{} ({})",
+            manifest.package.name,
+            manifest.package.version,
+            names.join(", ")
+        ),
+    );
+
+    let mut diagnostics = Vec::new();
+    for tool in tools {
+        match tool {
+            LintTool::Checkstyle => {
+                let config = lint::config_path(gctx, &gctx.cwd, manifest)?;
+                gctx.shell.verbose(|sh| {
+                    sh.print(format!(
+                        "  [verbose] checkstyle config: {}",
+                        config.display()
+                    ))
+                });
+                diagnostics.extend(lint::run_checkstyle(
+                    gctx, &gctx.cwd, &toolchain, manifest, &config, &files,
+                )?);
+            }
+            LintTool::Pmd => {
+                diagnostics.extend(lint::run_pmd(
+                    gctx, &gctx.cwd, &toolchain, manifest, &files,
+                )?);
+            }
+            LintTool::Spotbugs => {
+                // SpotBugs reads bytecode, so the main sources are compiled first.
+                let resolved = resolver::resolve(gctx, &gctx.cwd, manifest)?;
+                let compile_output =
+                    compiler::compile(gctx, &gctx.cwd, manifest, &resolved.compile_jars)?;
+                if !compile_output.success {
+                    compile_output.print_errors(&gctx.shell, &manifest.package.name);
+                    return Err(JargoError::CompilationFailed.into());
+                }
+                diagnostics.extend(lint::run_spotbugs(
+                    gctx,
+                    &gctx.cwd,
+                    &toolchain,
+                    manifest,
+                    &resolved.compile_jars,
+                )?);
+            }
+        }
+    }
+    let report = lint::write_report(&gctx.cwd, &diagnostics)?;
+    gctx.shell
+        .verbose(|sh| sh.print(format!("  [verbose] report: {}", report.display())));
+
+    if gctx.shell.is_json() {
+        for diagnostic in &diagnostics {
+            gctx.shell.message(&Message::compiler_message(
+                &manifest.package.name,
+                diagnostic,
+            ));
+        }
+    } else {
+        gctx.shell
+            .diagnostics(&render_diagnostics(&diagnostics, gctx.shell.err_color()));
+    }
+
+    let failing = lint::failing(&diagnostics, manifest.get_lint_fail_on());
+    if failing > 0 {
+        return Err(JargoError::LintFailed(failing).into());
+    }
+    let count = |severity| {
+        diagnostics
+            .iter()
+            .filter(|d| d.severity == severity)
+            .count()
+    };
+    gctx.shell.status(
+        "Finished",
+        &format!(
+            "linted {} file(s): {} error(s), {} warning(s), {} info",
+            files.len(),
+            count(Severity::Error),
+            count(Severity::Warning),
+            count(Severity::Note)
+        ),
+    );
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use std::fs;
+
+use anyhow::{bail, Result};
+
+use crate::commands::init::{add_to_gitignore, dir_name, move_sources};
+use crate::commands::new::{is_java_identifier, validate_name};
+use jargo_core::adopt;
+use jargo_core::context::GlobalContext;
+use jargo_core::errors::JargoError;
+use jargo_core::manifest;
+use jargo_core::migrate::{self, BUILD_FILES};
+
+/// Execute `jargo migrate`: write a Jargo.toml equivalent to the Maven or
+/// Gradle build in the current directory and move its sources into
+/// jargo's layout. The build file itself is left in place.
+pub fn exec(gctx: &GlobalContext) -> Result<()> {
+    if gctx.cwd.join("Jargo.toml").exists() {
+        return Err(JargoError::AlreadyInitialized.into());
+    }
+    let Some(imported) = migrate::read(&gctx.cwd)? else {
+        bail!(
+            "no Maven or Gradle build to migrate: none of {} in {}",
+            BUILD_FILES.join(", "),
+            gctx.cwd.display()
+        );
+    };
+
+    let name = match &imported.name {
+        Some(name) => name.clone(),
+        None => dir_name(&gctx.cwd)?,
+    };
+    validate_name(&name)?;
+
+    // The sources decide the base package; without any, the group does
+    let adoption = adopt::detect(&gctx.cwd)?;
+    let base_package = match &adoption {
+        Some(adoption) => adoption.base_package.clone(),
+        None => imported
+            .group
+            .clone()
+            .filter(|group| group.split('.').all(is_java_identifier))
+            .unwrap_or_else(|| manifest::derive_base_package(&name)),
+    };
+    let found_main_class = adoption.as_ref().and_then(|a| a.main_class.clone());
+    let main_class = match &imported.main_class {
+        Some(main_class) => match main_class.strip_prefix(&format!("{}.", base_package)) {
+            Some(relative) => Some(relative.to_string()),
+            None => {
+                gctx.shell.warn(&format!(
+                    "main class {} is outside base package {}; set `main-class` by hand",
+                    main_class, base_package
+                ));
+                found_main_class
+            }
+        },
+        None => found_main_class,
+    };
+    let content = imported.manifest(&name, &base_package, main_class.as_deref())?;
+
+    if let Some(adoption) = &adoption {
+        move_sources(gctx, adoption)?;
+    }
+    fs::write(gctx.cwd.join("Jargo.toml"), content)?;
+    add_to_gitignore(&gctx.cwd)?;
+
+    for note in &imported.notes {
+        gctx.shell.warn(note);
+    }
+    let kind = if main_class.is_some() { "app" } else { "lib" };
+    gctx.shell.status(
+        "Created",
+        &format!(
+            "{} `{}` package from {} (base package {})",
+            kind, name, imported.build_file, base_package
+        ),
+    );
+    Ok(())
+}
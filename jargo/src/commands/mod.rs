@@ -1,5 +1,41 @@
+pub mod add;
+pub mod api_check;
+pub mod bench;
 pub mod build;
+pub mod check;
+pub mod ci;
 pub mod clean;
+pub mod config;
+pub mod daemon;
+pub mod deprecations;
+pub mod deps;
+pub mod dist;
+pub mod doc;
+pub mod env;
+pub mod exec;
+pub mod fix;
+pub mod fmt;
+pub mod graph;
+pub mod ide;
+pub mod image;
 pub mod init;
+pub mod install;
+pub mod jlink;
+pub mod licenses;
+pub mod lint;
+pub mod migrate;
+pub mod native;
 pub mod new;
+pub mod package;
+pub mod pom;
+pub mod publish;
+pub mod release;
+pub mod repl;
 pub mod run;
+pub mod task;
+pub mod test;
+pub mod tree;
+pub mod uninstall;
+pub mod verify_jar;
+pub mod version;
+pub mod watch;
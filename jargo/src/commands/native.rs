@@ -0,0 +1,64 @@
+use anyhow::Result;
+
+use crate::commands::build;
+use jargo_core::context::GlobalContext;
+use jargo_core::errors::JargoError;
+use jargo_core::manifest::JargoToml;
+use jargo_core::native;
+use jargo_core::resolver;
+use jargo_core::toolchain;
+
+/// Execute `jargo native`: build the JAR, then compile it and its runtime
+/// classpath to a native binary with GraalVM `native-image`, entering at
+/// `bin` (required when there are several `[[bin]]` targets).
+pub fn exec(gctx: &GlobalContext, bin: Option<&str>) -> Result<()> {
+    let manifest_path = gctx.cwd.join("Jargo.toml");
+
+    if !manifest_path.exists() {
+        return Err(JargoError::ManifestNotFound.into());
+    }
+
+    let manifest = JargoToml::from_file(&manifest_path)
+        .map_err(|e| JargoError::ManifestParse(e.to_string()))?;
+
+    // The binary's entry point is the main class
+    if !manifest.is_app() {
+        return Err(JargoError::NotAnApp.into());
+    }
+    let bin = manifest.get_bin(bin)?;
+
+    build::exec(gctx, false, false, false)?;
+
+    let resolved = resolver::resolve(gctx, &gctx.cwd, &manifest)?;
+    let toolchain = toolchain::resolve(&gctx.cwd, &manifest)?;
+    let jar_path = gctx
+        .cwd
+        .join("target")
+        .join(format!("{}.jar", manifest.package.name));
+
+    gctx.shell.status(
+        "Compiling",
+        &format!(
+            "{} v{} (native-image)",
+            manifest.package.name, manifest.package.version
+        ),
+    );
+    let binary = native::build_native_image(
+        &toolchain,
+        &gctx.cwd,
+        &manifest,
+        &bin,
+        &jar_path,
+        &resolved.runtime_jars,
+    )?;
+
+    gctx.shell.status(
+        "Finished",
+        &format!(
+            "native binary at {}",
+            binary.strip_prefix(&gctx.cwd).unwrap_or(&binary).display()
+        ),
+    );
+
+    Ok(())
+}
@@ -1,12 +1,21 @@
-use std::fs;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
 use std::path::Path;
 use std::process::Command;
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 
+use crate::cli::NewOptions;
+use jargo_core::bytecode;
 use jargo_core::context::GlobalContext;
 use jargo_core::errors::JargoError;
 use jargo_core::manifest::{self, JargoToml};
+use jargo_core::workspace::{self, Workspace};
+
+/// TestNG version added by `--test-framework testng`; 7.5 is the last line
+/// that runs on Java 8.
+const TESTNG_VERSION: &str = "7.10.2";
+const TESTNG_JAVA_8_VERSION: &str = "7.5.1";
 
 /// Validate a project name: must be non-empty, start with a letter,
 /// and contain only ASCII lowercase letters, digits, and hyphens.
@@ -46,9 +55,63 @@ pub fn validate_name(name: &str) -> Result<(), JargoError> {
     Ok(())
 }
 
+/// Check `--java`, `--package`, and `--main-class` before anything is
+/// created.
+pub fn validate_options(options: &NewOptions) -> Result<()> {
+    if let Some(java) = &options.java {
+        if bytecode::parse_release(java).is_none() {
+            bail!(
+                "invalid Java version `{}`: expected a feature release such as 17 or 21",
+                java
+            );
+        }
+    }
+    if let Some(package) = &options.base_package {
+        if !package.split('.').all(is_java_identifier) {
+            bail!(
+                "invalid package `{}`: expected dot-separated Java identifiers, e.g. com.example.app",
+                package
+            );
+        }
+    }
+    if let Some(class) = &options.main_class {
+        if !is_java_identifier(class) {
+            bail!(
+                "invalid main class `{}`: expected a class name in the base package, e.g. App",
+                class
+            );
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn is_java_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+fn git_init(path: &Path) {
+    let _ = Command::new("git")
+        .arg("init")
+        .current_dir(path)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status();
+}
+
 /// Execute `jargo new <name>`.
-pub fn exec(gctx: &GlobalContext, name: &str, is_lib: bool) -> Result<()> {
+pub fn exec(
+    gctx: &GlobalContext,
+    name: &str,
+    is_lib: bool,
+    no_git: bool,
+    options: &NewOptions,
+) -> Result<()> {
     validate_name(name)?;
+    validate_options(options)?;
 
     let path = Path::new(name);
     if path.exists() {
@@ -57,15 +120,11 @@ pub fn exec(gctx: &GlobalContext, name: &str, is_lib: bool) -> Result<()> {
 
     fs::create_dir(path).with_context(|| format!("failed to create directory `{name}`"))?;
 
-    scaffold(path, name, is_lib)?;
+    scaffold(path, name, is_lib, options)?;
 
-    // Initialize git repository
-    let _ = Command::new("git")
-        .arg("init")
-        .current_dir(path)
-        .stdout(std::process::Stdio::null())
-        .stderr(std::process::Stdio::null())
-        .status();
+    if !no_git {
+        git_init(path);
+    }
 
     let kind = if is_lib { "lib" } else { "app" };
     gctx.shell
@@ -74,44 +133,119 @@ pub fn exec(gctx: &GlobalContext, name: &str, is_lib: bool) -> Result<()> {
     Ok(())
 }
 
-/// Shared scaffolding logic used by both `new` and `init`.
-pub fn scaffold(project_dir: &Path, name: &str, is_lib: bool) -> Result<()> {
-    let base_package = manifest::derive_base_package(name);
+/// Execute `jargo new --workspace <name>`: a root with no members.
+pub fn exec_workspace(gctx: &GlobalContext, name: &str, no_git: bool) -> Result<()> {
+    validate_name(name)?;
 
-    // Generate Jargo.toml
-    let toml = if is_lib {
-        JargoToml::new_lib(name, &base_package)
-    } else {
-        JargoToml::new_app(name)
+    let path = Path::new(name);
+    if path.exists() {
+        return Err(JargoError::ProjectExists(name.to_string()).into());
+    }
+
+    fs::create_dir(path).with_context(|| format!("failed to create directory `{}`", name))?;
+    fs::write(path.join("Jargo.toml"), workspace::new_root_manifest())?;
+    fs::write(path.join(".gitignore"), "target/\n")?;
+
+    if !no_git {
+        git_init(path);
+    }
+
+    gctx.shell
+        .status("Created", &format!("workspace `{}`", name));
+
+    Ok(())
+}
+
+/// Execute `jargo new --member <name>`: a package in the workspace root,
+/// listed in `[workspace] members` and depending on the `deps` members.
+pub fn exec_member(
+    gctx: &GlobalContext,
+    name: &str,
+    is_lib: bool,
+    deps: &[String],
+    options: &NewOptions,
+) -> Result<()> {
+    validate_name(name)?;
+    validate_options(options)?;
+
+    let workspace = match Workspace::load(&gctx.cwd)? {
+        Some(workspace) => workspace,
+        None => Workspace::containing(&gctx.cwd)?.context(
+            "`--member` needs a workspace; run it in the workspace root or one of its members",
+        )?,
     };
-    let toml_content = toml
-        .to_toml_string()
-        .context("failed to serialize Jargo.toml")?;
-    fs::write(project_dir.join("Jargo.toml"), toml_content)?;
+    if workspace.members.iter().any(|m| m.name == name) {
+        return Err(JargoError::ProjectExists(name.to_string()).into());
+    }
+    for dep in deps {
+        if !workspace.members.iter().any(|m| &m.name == dep) {
+            bail!(
+                "`{}` is not a member of the workspace at {}",
+                dep,
+                workspace.root.display()
+            );
+        }
+    }
 
-    // Create directories
-    fs::create_dir(project_dir.join("src"))?;
-    fs::create_dir(project_dir.join("test"))?;
+    let path = workspace.root.join(name);
+    if path.exists() {
+        return Err(JargoError::ProjectExists(name.to_string()).into());
+    }
+    fs::create_dir(&path).with_context(|| format!("failed to create directory `{}`", name))?;
+    scaffold(&path, name, is_lib, options)?;
+
+    if !deps.is_empty() {
+        let mut manifest = OpenOptions::new()
+            .append(true)
+            .open(path.join("Jargo.toml"))?;
+        writeln!(manifest, "\n[dependencies]")?;
+        for dep in deps {
+            writeln!(manifest, "\"{}\" = {{ workspace = true }}", dep)?;
+        }
+    }
+    workspace::add_member(&workspace.root, name)?;
+
+    let kind = if is_lib { "lib" } else { "app" };
+    gctx.shell
+        .status("Created", &format!("{} `{}` workspace member", kind, name));
+
+    Ok(())
+}
+
+/// Shared scaffolding logic used by both `new` and `init`. `options` must
+/// have passed [`validate_options`].
+pub fn scaffold(project_dir: &Path, name: &str, is_lib: bool, options: &NewOptions) -> Result<()> {
+    let base_package = options
+        .base_package
+        .clone()
+        .unwrap_or_else(|| manifest::derive_base_package(name));
+    let main_class = options.main_class.as_deref().unwrap_or("Main");
+
+    fs::write(
+        project_dir.join("Jargo.toml"),
+        manifest_content(name, is_lib, options)?,
+    )?;
 
     // Generate source files
-    if is_lib {
-        fs::write(
-            project_dir.join("src/Lib.java"),
-            generate_lib_java(&base_package, name),
-        )?;
-        fs::write(
-            project_dir.join("test/LibTest.java"),
-            generate_lib_test_java(&base_package, name),
-        )?;
+    fs::create_dir(project_dir.join("src"))?;
+    let (class, source) = if is_lib {
+        ("Lib", generate_lib_java(&base_package, name))
     } else {
-        fs::write(
-            project_dir.join("src/Main.java"),
-            generate_main_java(&base_package),
-        )?;
-        fs::write(
-            project_dir.join("test/MainTest.java"),
-            generate_main_test_java(&base_package),
-        )?;
+        (main_class, generate_main_java(&base_package, main_class))
+    };
+    fs::write(project_dir.join(format!("src/{}.java", class)), source)?;
+
+    // Generate the test, unless `--test-framework none`
+    let test = match (options.test_framework.as_str(), is_lib) {
+        ("none", _) => None,
+        ("testng", true) => Some(generate_lib_testng_java(&base_package, name)),
+        ("testng", false) => Some(generate_main_testng_java(&base_package, main_class)),
+        (_, true) => Some(generate_lib_test_java(&base_package, name)),
+        (_, false) => Some(generate_main_test_java(&base_package, main_class)),
+    };
+    if let Some(test) = test {
+        fs::create_dir(project_dir.join("test"))?;
+        fs::write(project_dir.join(format!("test/{}Test.java", class)), test)?;
     }
 
     // Generate .gitignore
@@ -120,11 +254,48 @@ pub fn scaffold(project_dir: &Path, name: &str, is_lib: bool) -> Result<()> {
     Ok(())
 }
 
-fn generate_main_java(base_package: &str) -> String {
+/// The Jargo.toml `new` and `init` write for `options`, which must have
+/// passed [`validate_options`].
+pub fn manifest_content(name: &str, is_lib: bool, options: &NewOptions) -> Result<String> {
+    let base_package = options
+        .base_package
+        .clone()
+        .unwrap_or_else(|| manifest::derive_base_package(name));
+
+    let mut toml = if is_lib {
+        JargoToml::new_lib(name, &base_package)
+    } else {
+        let mut toml = JargoToml::new_app(name);
+        if options.base_package.is_some() {
+            toml.package.base_package = Some(base_package.clone());
+        }
+        toml.package.main_class = options.main_class.clone();
+        toml
+    };
+    if let Some(java) = &options.java {
+        toml.package.java = java.clone();
+    }
+    let mut toml_content = toml
+        .to_toml_string()
+        .context("failed to serialize Jargo.toml")?;
+    if options.test_framework == "testng" {
+        let version = match bytecode::parse_release(&toml.package.java) {
+            Some(release) if release < 11 => TESTNG_JAVA_8_VERSION,
+            _ => TESTNG_VERSION,
+        };
+        toml_content.push_str(&format!(
+            "\n[test]\nframework = \"testng\"\n\n[dev-dependencies]\n\"org.testng:testng\" = \"{}\"\n",
+            version
+        ));
+    }
+    Ok(toml_content)
+}
+
+fn generate_main_java(base_package: &str, class: &str) -> String {
     format!(
         r#"package {base_package};
 
-public class Main {{
+public class {class} {{
     public static void main(String[] args) {{
         System.out.println("Hello, World!");
     }}
@@ -133,14 +304,14 @@ public class Main {{
     )
 }
 
-fn generate_main_test_java(base_package: &str) -> String {
+fn generate_main_test_java(base_package: &str, class: &str) -> String {
     format!(
         r#"package {base_package};
 
 import org.junit.jupiter.api.Test;
 import static org.junit.jupiter.api.Assertions.*;
 
-class MainTest {{
+class {class}Test {{
     @Test
     void testMain() {{
         // TODO: add tests
@@ -151,6 +322,24 @@ class MainTest {{
     )
 }
 
+fn generate_main_testng_java(base_package: &str, class: &str) -> String {
+    format!(
+        r#"package {base_package};
+
+import org.testng.annotations.Test;
+import static org.testng.Assert.*;
+
+public class {class}Test {{
+    @Test
+    public void testMain() {{
+        // TODO: add tests
+        assertTrue(true);
+    }}
+}}
+"#
+    )
+}
+
 fn generate_lib_java(base_package: &str, name: &str) -> String {
     format!(
         r#"package {base_package};
@@ -181,6 +370,23 @@ class LibTest {{
     )
 }
 
+fn generate_lib_testng_java(base_package: &str, name: &str) -> String {
+    format!(
+        r#"package {base_package};
+
+import org.testng.annotations.Test;
+import static org.testng.Assert.*;
+
+public class LibTest {{
+    @Test
+    public void testGreeting() {{
+        assertEquals(Lib.greeting(), "Hello from {name}!");
+    }}
+}}
+"#
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -203,4 +409,20 @@ mod tests {
         assert!(validate_name("my app").is_err());
         assert!(validate_name("app-").is_err());
     }
+
+    #[test]
+    fn test_validate_options() {
+        let options = |java: &str, package: &str, class: &str| NewOptions {
+            java: Some(java.to_string()),
+            base_package: Some(package.to_string()),
+            main_class: Some(class.to_string()),
+            test_framework: "junit".to_string(),
+        };
+        assert!(validate_options(&options("17", "com.example.app", "App")).is_ok());
+        assert!(validate_options(&options("1.8", "_internal.v2", "$Main")).is_ok());
+        assert!(validate_options(&options("latest", "com.example", "App")).is_err());
+        assert!(validate_options(&options("17", "com..example", "App")).is_err());
+        assert!(validate_options(&options("17", "com.2example", "App")).is_err());
+        assert!(validate_options(&options("17", "com.example", "cli.App")).is_err());
+    }
 }
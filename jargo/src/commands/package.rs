@@ -0,0 +1,63 @@
+use anyhow::Result;
+
+use crate::commands::build;
+use jargo_core::context::GlobalContext;
+use jargo_core::errors::JargoError;
+use jargo_core::jlink;
+use jargo_core::jpackage;
+use jargo_core::manifest::JargoToml;
+use jargo_core::resolver;
+use jargo_core::toolchain;
+
+/// Execute `jargo package`: build the JAR, then wrap it and a trimmed runtime
+/// into a native installer in `target/installer/` that launches `bin`.
+pub fn exec(gctx: &GlobalContext, package_type: Option<&str>, bin: Option<&str>) -> Result<()> {
+    let manifest_path = gctx.cwd.join("Jargo.toml");
+
+    if !manifest_path.exists() {
+        return Err(JargoError::ManifestNotFound.into());
+    }
+
+    let manifest = JargoToml::from_file(&manifest_path)
+        .map_err(|e| JargoError::ManifestParse(e.to_string()))?;
+
+    // Installers launch a main class
+    if !manifest.is_app() {
+        return Err(JargoError::NotAnApp.into());
+    }
+    let bin = manifest.get_bin(bin)?;
+
+    build::exec(gctx, false, false, false)?;
+
+    let resolved = resolver::resolve(gctx, &gctx.cwd, &manifest)?;
+    let toolchain = toolchain::resolve(&gctx.cwd, &manifest)?;
+    let jar_path = gctx
+        .cwd
+        .join("target")
+        .join(format!("{}.jar", manifest.package.name));
+
+    let modules =
+        jlink::required_modules(&toolchain, &manifest, &jar_path, &resolved.runtime_jars)?;
+    gctx.shell.status("Packaging", &modules.join(", "));
+
+    let dest_dir = jpackage::write_installer(
+        &toolchain,
+        &gctx.cwd,
+        &manifest,
+        &bin,
+        &modules,
+        &resolved.runtime_jars,
+        package_type,
+    )?;
+
+    gctx.shell.status(
+        "Packaged",
+        &dest_dir
+            .strip_prefix(&gctx.cwd)
+            .unwrap_or(&dest_dir)
+            .display()
+            .to_string(),
+    );
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use anyhow::{Context, Result};
+use std::fs;
+
+use jargo_core::context::GlobalContext;
+use jargo_core::errors::JargoError;
+use jargo_core::manifest::{Dependency, JargoToml};
+use jargo_core::pom_export::{self, POM_PATH};
+use jargo_core::workspace::Workspace;
+
+/// Execute `jargo pom`: write the package's Maven POM to target/pom.xml.
+pub fn exec(gctx: &GlobalContext) -> Result<()> {
+    let manifest_path = gctx.cwd.join("Jargo.toml");
+
+    if !manifest_path.exists() {
+        return Err(JargoError::ManifestNotFound.into());
+    }
+
+    let manifest = JargoToml::from_file(&manifest_path)
+        .map_err(|e| JargoError::ManifestParse(e.to_string()))?;
+
+    let pom = render(gctx, &manifest)?;
+    let path = gctx.cwd.join(POM_PATH);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    fs::write(&path, pom).with_context(|| format!("failed to write {}", path.display()))?;
+
+    gctx.shell
+        .status("Finished", &format!("POM at {}", POM_PATH));
+    Ok(())
+}
+
+/// Render the POM of the package in the current directory.
+pub fn render(gctx: &GlobalContext, manifest: &JargoToml) -> Result<String> {
+    pom_export::render(manifest, &member_dependencies(gctx)?)
+}
+
+/// The workspace members the package in the current directory depends on,
+/// as the published dependencies Maven consumers see.
+pub fn member_dependencies(gctx: &GlobalContext) -> Result<Vec<Dependency>> {
+    Ok(match Workspace::containing(&gctx.cwd)? {
+        Some(workspace) => match workspace.member(&gctx.cwd) {
+            Some(member) => workspace.published_dependencies(member)?,
+            None => Vec::new(),
+        },
+        None => Vec::new(),
+    })
+}
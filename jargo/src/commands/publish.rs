@@ -0,0 +1,204 @@
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use jargo_core::cache;
+use jargo_core::central::{
+    self, CentralClient, DeploymentState, BUNDLE_PATH, CENTRAL_API, DEPLOYMENTS_URL,
+};
+use jargo_core::context::GlobalContext;
+use jargo_core::deploy::{self, DeployTarget};
+use jargo_core::errors::JargoError;
+use jargo_core::jar;
+use jargo_core::javadoc;
+use jargo_core::manifest::JargoToml;
+use jargo_core::pom_export::POM_PATH;
+use jargo_core::publish_check::{self, PublishProblem};
+use jargo_core::resolver;
+
+use crate::commands::{api_check, build, pom};
+
+/// How often the deployment status is polled.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long to wait for a deployment to validate or publish.
+const POLL_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+/// Execute `jargo publish --central`: build the JAR, sources, Javadoc, and
+/// POM, check the release, sign and bundle it, upload the bundle to the
+/// Central Portal, and wait until it is validated, or published with
+/// `--auto-release`. With `repo`, upload the files to that
+/// `[publish.<name>]` repository instead. With `--check`, stop after the
+/// checks.
+pub fn exec(
+    gctx: &GlobalContext,
+    repo: Option<&str>,
+    check_only: bool,
+    auto_release: bool,
+) -> Result<()> {
+    let manifest_path = gctx.cwd.join("Jargo.toml");
+
+    if !manifest_path.exists() {
+        return Err(JargoError::ManifestNotFound.into());
+    }
+
+    let manifest = JargoToml::from_file(&manifest_path)
+        .map_err(|e| JargoError::ManifestParse(e.to_string()))?;
+
+    // Fail before building when there is nothing to upload with
+    let target = repo
+        .map(|name| DeployTarget::from_manifest(&manifest, name))
+        .transpose()?;
+    let client = if check_only || target.is_some() {
+        None
+    } else {
+        Some(CentralClient::new(CENTRAL_API)?)
+    };
+
+    build::exec(gctx, false, false, false)?;
+    let resolved = resolver::resolve(gctx, &gctx.cwd, &manifest)?;
+
+    let package = &manifest.package;
+    let group = manifest.get_group();
+    let coordinates = format!("{}:{}:{}", group, package.name, package.version);
+    let jar_path = gctx
+        .cwd
+        .join("target")
+        .join(format!("{}.jar", package.name));
+
+    // Only Maven Central requires metadata beyond the coordinates
+    let (repository, auth, required) = match &target {
+        Some(target) => (target.url.clone(), target.auth(), &[][..]),
+        None => (cache::repository_url(), None, central::REQUIRED_METADATA),
+    };
+    gctx.shell.status("Checking", &coordinates);
+    let mut problems = publish_check::check_manifest(&manifest, required)?;
+    problems.extend(publish_check::check_repository(
+        &repository,
+        auth,
+        (&group, &package.name, &package.version),
+        &pom::member_dependencies(gctx)?,
+    )?);
+    problems.extend(publish_check::check_jar(
+        &gctx.cwd.join("target/classes"),
+        &jar_path,
+    )?);
+    if manifest.enforces_semver() {
+        if let Some((baseline, changes)) = api_check::compare(gctx, &manifest, &jar_path, None)? {
+            problems.extend(
+                jargo_core::api_check::semver_violation(&baseline, &package.version, &changes)
+                    .map(PublishProblem::SemverViolation),
+            );
+        }
+    }
+    if !problems.is_empty() {
+        for problem in &problems {
+            gctx.shell.print(format!("  {}", problem));
+        }
+        return Err(JargoError::PublishCheckFailed(problems.len()).into());
+    }
+
+    let pom_path = gctx.cwd.join(POM_PATH);
+    fs::write(&pom_path, pom::render(gctx, &manifest)?)
+        .with_context(|| format!("failed to write {}", pom_path.display()))?;
+    let sources = jar::assemble_classifier_jar(
+        &gctx.cwd,
+        &manifest,
+        "sources",
+        &gctx.cwd.join("target/src-root"),
+    )?;
+    gctx.shell.status(
+        "Documenting",
+        &format!("{} v{}", package.name, package.version),
+    );
+    let docs = javadoc::generate(gctx, &gctx.cwd, &manifest, &resolved.compile_jars)?;
+    let javadoc = jar::assemble_classifier_jar(&gctx.cwd, &manifest, "javadoc", &docs)?;
+
+    if check_only {
+        gctx.shell
+            .status("Checked", &format!("{} is ready to publish", coordinates));
+        return Ok(());
+    }
+    let files: [(&str, &Path); 4] = [
+        (".jar", &jar_path),
+        (".pom", &pom_path),
+        ("-sources.jar", &sources),
+        ("-javadoc.jar", &javadoc),
+    ];
+
+    if let Some(target) = &target {
+        gctx.shell
+            .status("Uploading", &format!("{} to {}", coordinates, target.name));
+        deploy::upload(target, &group, &package.name, &package.version, &files)?;
+        gctx.shell.status(
+            "Published",
+            &format!("{} to {} ({})", coordinates, target.name, target.url),
+        );
+        return Ok(());
+    }
+    let Some(client) = client else {
+        unreachable!("the Central Portal client exists unless checking or using --repo");
+    };
+
+    gctx.shell.status("Signing", &coordinates);
+    let bundle = central::bundle(
+        &group,
+        &package.name,
+        &package.version,
+        &files,
+        central::gpg_sign,
+    )?;
+    let bundle_path = gctx.cwd.join(BUNDLE_PATH);
+    fs::write(&bundle_path, &bundle)
+        .with_context(|| format!("failed to write {}", bundle_path.display()))?;
+
+    gctx.shell
+        .status("Uploading", &format!("{} to Maven Central", coordinates));
+    let id = client.upload(&coordinates, bundle, auto_release)?;
+    gctx.shell.status("Uploaded", &format!("deployment {}", id));
+
+    let started = Instant::now();
+    let mut last = None;
+    loop {
+        let status = client.status(&id)?;
+        match status.state {
+            DeploymentState::Failed => bail!(
+                "Maven Central rejected {}:\n  {}",
+                coordinates,
+                status.errors.join("\n  ")
+            ),
+            DeploymentState::Validated if !auto_release => {
+                gctx.shell.status(
+                    "Validated",
+                    &format!(
+                        "{}; publish deployment {} at {}",
+                        coordinates, id, DEPLOYMENTS_URL
+                    ),
+                );
+                return Ok(());
+            }
+            DeploymentState::Published => {
+                gctx.shell.status("Published", &coordinates);
+                return Ok(());
+            }
+            state => {
+                if last != Some(state) {
+                    gctx.shell
+                        .status("Waiting", &format!("deployment {} is {}", id, state));
+                    last = Some(state);
+                }
+                if started.elapsed() > POLL_TIMEOUT {
+                    bail!(
+                        "deployment {} is still {} after {} minutes; check {}",
+                        id,
+                        state,
+                        POLL_TIMEOUT.as_secs() / 60,
+                        DEPLOYMENTS_URL
+                    );
+                }
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use anyhow::{bail, Result};
+
+use jargo_core::context::GlobalContext;
+use jargo_core::errors::JargoError;
+use jargo_core::manifest::{JargoToml, ReleaseStep};
+use jargo_core::version::{self, Bump};
+use jargo_core::workspace::Workspace;
+
+use crate::commands::test::TestArgs;
+use crate::commands::{build, publish, test};
+
+/// Execute `jargo release <bump>`: run the `[release] steps` not in `skip`,
+/// in order, stopping at the first failure.
+pub fn exec(gctx: &GlobalContext, bump: &str, skip: &[String]) -> Result<()> {
+    let manifest_path = gctx.cwd.join("Jargo.toml");
+
+    if !manifest_path.exists() {
+        return Err(JargoError::ManifestNotFound.into());
+    }
+
+    let manifest = JargoToml::from_file(&manifest_path)
+        .map_err(|e| JargoError::ManifestParse(e.to_string()))?;
+
+    let steps = manifest.get_release_steps();
+    for name in skip {
+        if !ReleaseStep::DEFAULT.iter().any(|s| s.name() == name) {
+            let names: Vec<&str> = ReleaseStep::DEFAULT.iter().map(|s| s.name()).collect();
+            bail!(
+                "unknown release step `{}`; expected one of {}",
+                name,
+                names.join(", ")
+            );
+        }
+    }
+    let steps: Vec<ReleaseStep> = steps
+        .into_iter()
+        .filter(|s| !skip.iter().any(|name| name == s.name()))
+        .collect();
+
+    // Work out both versions and check git before changing anything
+    let package = &manifest.package;
+    let release = version::next_version(&package.version, &bump.parse::<Bump>()?)?;
+    let next_bump: Bump = manifest.get_release_next().parse()?;
+    let next = format!("{}-SNAPSHOT", version::next_version(&release, &next_bump)?);
+    let in_workspace = Workspace::containing(&gctx.cwd)?.is_some();
+    let tags = [version::tag_name(&package.name, &release, in_workspace)];
+    let tagged = steps.contains(&ReleaseStep::Tag);
+    if tagged {
+        version::check_clean(&gctx.cwd)?;
+        version::check_tags_unused(&gctx.cwd, &tags)?;
+    }
+
+    let mut bumped = false;
+    for step in steps {
+        match step {
+            ReleaseStep::Test => test::exec(gctx, TestArgs::default())?,
+            ReleaseStep::Version => {
+                version::set_version(&manifest_path, &release)?;
+                bumped = true;
+                gctx.shell.status(
+                    "Bumping",
+                    &format!("{} from {} to {}", package.name, package.version, release),
+                );
+            }
+            ReleaseStep::Build => build::exec(gctx, false, false, false)?,
+            ReleaseStep::Tag => {
+                let message = format!("Release {} {}", package.name, release);
+                let manifests = if bumped {
+                    vec![manifest_path.clone()]
+                } else {
+                    Vec::new()
+                };
+                version::commit_and_tag(&gctx.cwd, &manifests, &message, &tags)?;
+                gctx.shell.status("Tagged", &tags[0]);
+            }
+            ReleaseStep::Publish => publish::exec(gctx, None, false, false)?,
+            ReleaseStep::Next => {
+                version::set_version(&manifest_path, &next)?;
+                gctx.shell.status(
+                    "Bumping",
+                    &format!("{} from {} to {}", package.name, release, next),
+                );
+                if tagged {
+                    let message = format!("Start {} {}", package.name, next);
+                    version::commit_and_tag(
+                        &gctx.cwd,
+                        std::slice::from_ref(&manifest_path),
+                        &message,
+                        &[],
+                    )?;
+                    gctx.shell.status("Committed", &message);
+                }
+            }
+        }
+    }
+
+    gctx.shell
+        .status("Released", &format!("{} v{}", package.name, release));
+    Ok(())
+}
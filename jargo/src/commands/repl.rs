@@ -0,0 +1,54 @@
+use anyhow::Result;
+use std::path::PathBuf;
+
+use jargo_core::compiler::{self, SourceSet};
+use jargo_core::context::GlobalContext;
+use jargo_core::errors::JargoError;
+use jargo_core::manifest::JargoToml;
+use jargo_core::repl;
+use jargo_core::resolver;
+use jargo_core::toolchain;
+
+/// Execute `jargo repl`: compile, then start `jshell` with the compiled
+/// classes and runtime dependency JARs on its classpath.
+pub fn exec(gctx: &GlobalContext) -> Result<()> {
+    let manifest_path = gctx.cwd.join("Jargo.toml");
+
+    if !manifest_path.exists() {
+        return Err(JargoError::ManifestNotFound.into());
+    }
+
+    let manifest = JargoToml::from_file(&manifest_path)
+        .map_err(|e| JargoError::ManifestParse(e.to_string()))?;
+
+    let resolved = resolver::resolve(gctx, &gctx.cwd, &manifest)?;
+
+    gctx.shell.status(
+        "Compiling",
+        &format!(
+            "{} v{} (java {})",
+            manifest.package.name, manifest.package.version, manifest.package.java
+        ),
+    );
+
+    let compile_output = compiler::compile(gctx, &gctx.cwd, &manifest, &resolved.compile_jars)?;
+    if !compile_output.success {
+        compile_output.print_errors(&gctx.shell, &manifest.package.name);
+        return Err(JargoError::CompilationFailed.into());
+    }
+    compile_output.print_warnings(&gctx.shell, &manifest.package.name);
+
+    let mut classpath: Vec<PathBuf> = vec![gctx.cwd.join(SourceSet::Main.output_dir())];
+    classpath.extend(resolved.runtime_jars.iter().cloned());
+
+    let toolchain = toolchain::resolve(&gctx.cwd, &manifest)?;
+    gctx.shell
+        .status("Starting", "jshell (`/exit` or Ctrl-D to quit)");
+    let status = repl::run_jshell(&toolchain, &gctx.cwd, &manifest, &classpath)?;
+
+    if !status.success() {
+        std::process::exit(status.code().unwrap_or(1));
+    }
+
+    Ok(())
+}
@@ -1,13 +1,53 @@
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
+use std::path::PathBuf;
 use std::process::Command;
 
-use jargo_core::compiler;
+use crate::process;
+use jargo_core::compiler::{self, SourceSet};
 use jargo_core::context::GlobalContext;
+use jargo_core::env;
 use jargo_core::errors::JargoError;
 use jargo_core::manifest::JargoToml;
+use jargo_core::profiler::FlightRecording;
 use jargo_core::resolver;
+use jargo_core::toolchain;
+use jargo_core::watch::WATCH_ENV;
+
+#[derive(Default)]
+pub struct RunArgs {
+    /// `[[bin]]` target to run.
+    pub bin: Option<String>,
+    /// Example under `examples/` to run instead.
+    pub example: Option<String>,
+    /// Fully-qualified class to run instead of a bin (`jargo exec`).
+    pub main_class: Option<String>,
+    /// Attach the JDWP agent, on the given port or `[run] debug-port`.
+    pub debug: Option<Option<u16>>,
+    /// Suspend until a debugger attaches, regardless of `[run] debug-suspend`.
+    pub suspend: bool,
+    /// `[run.profiles.<name>]` to launch with.
+    pub profile: Option<String>,
+    /// `--sysprop` overrides of `[run] system-properties`.
+    pub sysprops: Vec<(String, String)>,
+    pub profile_jvm: bool,
+    /// Arguments passed to the program.
+    pub args: Vec<String>,
+}
 
-pub fn exec(gctx: &GlobalContext, args: Vec<String>) -> Result<()> {
+/// Execute `jargo run`.
+pub fn exec(gctx: &GlobalContext, args: RunArgs) -> Result<()> {
+    let RunArgs {
+        bin,
+        example,
+        main_class,
+        debug,
+        suspend,
+        profile,
+        sysprops,
+        profile_jvm,
+        args,
+    } = args;
+    let (bin, example) = (bin.as_deref(), example.as_deref());
     let manifest_path = gctx.cwd.join("Jargo.toml");
 
     if !manifest_path.exists() {
@@ -17,10 +57,38 @@ pub fn exec(gctx: &GlobalContext, args: Vec<String>) -> Result<()> {
     let manifest = JargoToml::from_file(&manifest_path)
         .map_err(|e| JargoError::ManifestParse(e.to_string()))?;
 
-    // run is app-only
-    if !manifest.is_app() {
-        return Err(JargoError::NotAnApp.into());
-    }
+    let profile = match &profile {
+        Some(name) => Some(manifest.get_run_profile(name)?),
+        None => None,
+    };
+
+    // run is app-only, except for examples and `jargo exec`
+    let (name, fq_main_class) = match (main_class, example) {
+        (Some(main_class), _) => (main_class.clone(), main_class),
+        (None, Some(example)) => {
+            let names = compiler::example_names(&gctx.cwd)?;
+            if names.is_empty() {
+                bail!("no examples found in examples/");
+            }
+            if !names.iter().any(|name| name == example) {
+                bail!(
+                    "no example named `{}`; available: {}",
+                    example,
+                    names.join(", ")
+                );
+            }
+            (
+                example.to_string(),
+                format!("{}.{}", manifest.get_base_package(), example),
+            )
+        }
+        (None, None) if !manifest.is_app() => return Err(JargoError::NotAnApp.into()),
+        (None, None) => {
+            let bin = manifest.get_bin(bin)?;
+            let fq_main_class = manifest.main_class_of(&bin);
+            (bin.name, fq_main_class)
+        }
+    };
 
     // Resolve dependencies (uses lock file if present, else resolves + writes lock)
     let resolved = resolver::resolve(gctx, &gctx.cwd, &manifest)?;
@@ -37,55 +105,138 @@ pub fn exec(gctx: &GlobalContext, args: Vec<String>) -> Result<()> {
     let compile_output = compiler::compile(gctx, &gctx.cwd, &manifest, &resolved.compile_jars)?;
 
     if !compile_output.success {
-        for error in compile_output.errors {
-            eprintln!("{}", error);
-        }
+        compile_output.print_errors(&gctx.shell, &manifest.package.name);
         return Err(JargoError::CompilationFailed.into());
     }
+    compile_output.print_warnings(&gctx.shell, &manifest.package.name);
+
+    // Assemble the runtime classpath: compiled classes + runtime-scope
+    // dependency JARs (compile and runtime scope, transitives included).
+    let classes_dir = gctx.cwd.join(SourceSet::Main.output_dir());
+    let mut runtime_classpath: Vec<PathBuf> = vec![classes_dir.clone()];
+    runtime_classpath.extend(resolved.runtime_jars.iter().cloned());
+
+    if example.is_some() {
+        // Examples compile against main classes, compile deps, and dev deps,
+        // and run with dev deps on the classpath too.
+        let mut example_compile_cp = vec![classes_dir];
+        example_compile_cp.extend(resolved.compile_jars.iter().cloned());
+        example_compile_cp.extend(resolved.test_jars.iter().cloned());
+
+        let example_output =
+            compiler::compile_examples(gctx, &gctx.cwd, &manifest, &example_compile_cp)?;
+        if !example_output.success {
+            example_output.print_errors(&gctx.shell, &manifest.package.name);
+            return Err(JargoError::CompilationFailed.into());
+        }
+        example_output.print_warnings(&gctx.shell, &manifest.package.name);
 
-    // Assemble the runtime classpath: compiled classes + dependency JARs.
-    let classes_dir = gctx.cwd.join("target/classes");
+        runtime_classpath.insert(0, gctx.cwd.join(SourceSet::Examples.output_dir()));
+        runtime_classpath.extend(resolved.test_jars.iter().cloned());
+    }
+    let classpath = std::env::join_paths(&runtime_classpath).context("invalid classpath entry")?;
 
-    #[cfg(windows)]
-    let sep = ";";
-    #[cfg(not(windows))]
-    let sep = ":";
+    // Invoke java
+    gctx.shell.status("Running", &name);
 
-    let mut cp_parts = vec![classes_dir.to_string_lossy().into_owned()];
-    for jar in &resolved.runtime_jars {
-        cp_parts.push(jar.to_string_lossy().into_owned());
+    let mut jvm_args = resolver::agent_jvm_args(gctx, &manifest)?;
+    jvm_args.extend(manifest.get_jvm_args().iter().cloned());
+    if let Some(profile) = profile {
+        jvm_args.extend(profile.jvm_args.iter().cloned());
     }
-    let classpath = cp_parts.join(sep);
-
-    // Build the fully-qualified main class name
-    let base_package = manifest.get_base_package();
-    let main_class = manifest.get_main_class();
-    let fq_main_class = format!("{}.{}", base_package, main_class);
 
-    // Invoke java
-    gctx.shell.status("Running", &manifest.package.name);
+    // System properties come after jvm-args so they win over `-D` flags there
+    let mut system_properties = manifest.get_system_properties()?;
+    if let Some(profile) = profile {
+        system_properties.extend(profile.system_properties.clone());
+    }
+    system_properties.extend(sysprops);
+    jvm_args.extend(
+        system_properties
+            .iter()
+            .map(|(key, value)| format!("-D{}={}", key, value)),
+    );
 
-    let jvm_args = manifest.get_jvm_args();
+    if let Some(port) = debug {
+        let port = port.unwrap_or_else(|| manifest.get_debug_port());
+        let suspend = suspend || manifest.get_debug_suspend();
+        jvm_args.push(jdwp_arg(port, suspend));
+        gctx.shell.status(
+            "Debugging",
+            &format!(
+                "JDWP listening on 127.0.0.1:{}{}",
+                port,
+                if suspend {
+                    ", waiting for a debugger to attach"
+                } else {
+                    ""
+                }
+            ),
+        );
+    }
 
-    let status = Command::new("java")
-        .arg("-cp")
+    let recording = if profile_jvm {
+        let rec = FlightRecording::new(&gctx.cwd, &manifest.package.name)?;
+        jvm_args.push(rec.jvm_arg());
+        Some(rec)
+    } else {
+        None
+    };
+
+    let toolchain = toolchain::resolve(&gctx.cwd, &manifest)?;
+    let mut java = Command::new(toolchain.java());
+    java.arg("-cp")
         .arg(&classpath)
-        .args(jvm_args)
+        .args(&jvm_args)
         .arg(&fq_main_class)
         .args(&args)
-        .current_dir(&gctx.cwd)
-        .status()
-        .map_err(|e| {
-            if e.kind() == std::io::ErrorKind::NotFound {
-                JargoError::JavaNotFound
-            } else {
-                e.into()
-            }
-        })?;
+        .envs(env::program_env(&gctx.cwd, &manifest, profile)?)
+        .current_dir(&gctx.cwd);
+    let java_error = |e: std::io::Error| -> anyhow::Error {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            JargoError::JavaNotFound.into()
+        } else {
+            e.into()
+        }
+    };
+
+    // Under `jargo watch`, become the JVM so that stopping this process on a
+    // change stops the program too.
+    #[cfg(unix)]
+    if recording.is_none() && std::env::var_os(WATCH_ENV).is_some() {
+        use std::os::unix::process::CommandExt;
+        return Err(java_error(java.exec()));
+    }
+
+    let status = process::run_foreground(&mut java).map_err(java_error)?;
+
+    if let Some(rec) = recording {
+        if rec.path.exists() {
+            let display = rec.path.strip_prefix(&gctx.cwd).unwrap_or(&rec.path);
+            gctx.shell
+                .status("Profiled", &display.display().to_string());
+            gctx.shell.print(format!(
+                "             open with JDK Mission Control, or run `jfr summary {}`",
+                display.display()
+            ));
+        } else {
+            gctx.shell
+                .warn("JVM exited without writing a flight recording");
+        }
+    }
 
     if !status.success() {
-        std::process::exit(status.code().unwrap_or(1));
+        std::process::exit(process::exit_code(status));
     }
 
     Ok(())
 }
+
+/// `-agentlib:jdwp` option for a socket server on the loopback interface.
+fn jdwp_arg(port: u16, suspend: bool) -> String {
+    format!(
+        "-agentlib:jdwp=transport=dt_socket,server=y,suspend={},address=127.0.0.1:{}",
+        if suspend { "y" } else { "n" },
+        port
+    )
+}
@@ -0,0 +1,118 @@
+use anyhow::{Context, Result};
+use clap::CommandFactory;
+use std::process::Command;
+
+use jargo_core::context::GlobalContext;
+use jargo_core::errors::JargoError;
+use jargo_core::manifest::JargoToml;
+
+use crate::cli::Cli;
+
+/// Execute `jargo run-task [name] [args...]` or `jargo <name> [args...]`.
+///
+/// Without a name, lists the `[tasks]` table. A task runs through the
+/// platform shell in the project root with `args` appended, and `jargo` on
+/// `PATH` resolves to this executable.
+pub fn exec(gctx: &GlobalContext, name: Option<&str>, args: &[String]) -> Result<()> {
+    let manifest_path = gctx.cwd.join("Jargo.toml");
+
+    if !manifest_path.exists() {
+        return Err(match name {
+            Some(name) => JargoError::TaskNotFound(name.to_string()),
+            None => JargoError::ManifestNotFound,
+        }
+        .into());
+    }
+
+    let manifest = JargoToml::from_file(&manifest_path)
+        .map_err(|e| JargoError::ManifestParse(e.to_string()))?;
+
+    let Some(name) = name else {
+        if manifest.tasks.is_empty() {
+            gctx.shell.print("no tasks defined in [tasks]");
+        }
+        let width = manifest.tasks.keys().map(|k| k.len()).max().unwrap_or(0);
+        for (task, command) in &manifest.tasks {
+            gctx.shell
+                .print(format!("  {:width$}  {}", task, command, width = width));
+        }
+        return Ok(());
+    };
+
+    let command = manifest
+        .tasks
+        .get(name)
+        .ok_or_else(|| JargoError::TaskNotFound(name.to_string()))?;
+
+    gctx.shell.status("Running", &format!("`{}`", command));
+
+    let mut cmd = shell_command(command, name, args);
+    if let Some(path) = path_with_current_exe()? {
+        cmd.env("PATH", path);
+    }
+
+    let status = cmd
+        .current_dir(&gctx.cwd)
+        .status()
+        .with_context(|| format!("failed to run task `{}`", name))?;
+
+    if !status.success() {
+        std::process::exit(status.code().unwrap_or(1));
+    }
+
+    Ok(())
+}
+
+/// `[tasks]` names that are also built-in commands. Built-ins always win, so
+/// these only run through `jargo run-task <name>`.
+pub fn shadowed(manifest: &JargoToml) -> Vec<&str> {
+    let mut cli = Cli::command();
+    cli.build();
+    manifest
+        .tasks
+        .keys()
+        .filter(|name| cli.find_subcommand(name).is_some())
+        .map(String::as_str)
+        .collect()
+}
+
+#[cfg(not(windows))]
+fn shell_command(command: &str, name: &str, args: &[String]) -> Command {
+    // `"$@"` forwards args without re-splitting; `name` becomes `$0`.
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c")
+        .arg(format!("{} \"$@\"", command))
+        .arg(name)
+        .args(args);
+    cmd
+}
+
+#[cfg(windows)]
+fn shell_command(command: &str, _name: &str, args: &[String]) -> Command {
+    let mut line = command.to_string();
+    for arg in args {
+        line.push(' ');
+        line.push_str(arg);
+    }
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(line);
+    cmd
+}
+
+/// `PATH` with this executable's directory first, so tasks that call `jargo`
+/// run the same build.
+fn path_with_current_exe() -> Result<Option<std::ffi::OsString>> {
+    let Some(dir) = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|p| p.to_path_buf()))
+    else {
+        return Ok(None);
+    };
+    let mut paths = vec![dir];
+    if let Some(path) = std::env::var_os("PATH") {
+        paths.extend(std::env::split_paths(&path));
+    }
+    Ok(Some(
+        std::env::join_paths(paths).context("failed to build PATH for task")?,
+    ))
+}
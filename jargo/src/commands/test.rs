@@ -0,0 +1,621 @@
+use anyhow::{bail, Result};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use jargo_core::affected::{self, AffectedTests};
+use jargo_core::compiler::{self, SourceSet};
+use jargo_core::context::GlobalContext;
+use jargo_core::coverage;
+use jargo_core::env;
+use jargo_core::errors::JargoError;
+use jargo_core::manifest::JargoToml;
+use jargo_core::message::Message;
+use jargo_core::mutation::{self, MutationOptions};
+use jargo_core::profiler::FlightRecording;
+use jargo_core::resolver::{self, ResolvedDeps};
+use jargo_core::test_filter::{self, TestFilter};
+use jargo_core::test_runner::{
+    self, TestCase, TestEngine, TestOptions, TestOutcome, TestRun, TestTimings,
+};
+use jargo_core::toolchain;
+
+#[derive(Default)]
+pub struct TestArgs {
+    pub profile_jvm: bool,
+    /// Git ref to compare against when only affected tests should run.
+    pub affected: Option<String>,
+    /// Only run tests matching one of these filters.
+    pub filters: Vec<TestFilter>,
+    /// `--tag`: JUnit tags to include, replacing `[test] tags`.
+    pub tags: Vec<String>,
+    /// `--exclude-tag`: JUnit tags to exclude, on top of `[test] exclude-tags`.
+    pub exclude_tags: Vec<String>,
+    /// Also write `target/test-reports/summary.json`.
+    pub json_summary: bool,
+    /// Run each test class in its own JVM and stop after the first class with
+    /// failures; tests within that class all run.
+    pub fail_fast: bool,
+    /// Number of slowest tests to list, overriding `[test] slowest`.
+    pub slowest: Option<usize>,
+    /// Run tests in a random order.
+    pub shuffle: bool,
+    /// Seed for the random order; implies `shuffle`.
+    pub seed: Option<u64>,
+    /// Only run the tests that failed in the last run.
+    pub rerun_failed: bool,
+    /// Also run the integration tests in `it/` once the unit tests pass.
+    pub integration: bool,
+    /// Record coverage with JaCoCo and report it under `target/coverage/`.
+    pub coverage: bool,
+    /// Run PIT mutation analysis once the unit tests pass.
+    pub mutation: bool,
+}
+
+/// Compiled test inputs shared by `jargo test` and `jargo graph`.
+pub struct PreparedTests {
+    pub engine: TestEngine,
+    /// Test runtime classpath: test classes, main classes, and dependency JARs.
+    pub runtime_classpath: Vec<PathBuf>,
+    /// Whether these are the integration tests in `it/`.
+    pub integration: bool,
+}
+
+/// Execute `jargo test`.
+pub fn exec(gctx: &GlobalContext, mut args: TestArgs) -> Result<()> {
+    let manifest_path = gctx.cwd.join("Jargo.toml");
+
+    if !manifest_path.exists() {
+        return Err(JargoError::ManifestNotFound.into());
+    }
+
+    let manifest = JargoToml::from_file(&manifest_path)
+        .map_err(|e| JargoError::ManifestParse(e.to_string()))?;
+
+    // Reject invalid settings before spending time on the tests
+    let threshold = if args.mutation {
+        manifest.get_mutation_threshold()?
+    } else {
+        None
+    };
+    let minimums = if args.coverage {
+        coverage::clean(&gctx.cwd)?;
+        manifest.get_coverage_minimums()?
+    } else {
+        Vec::new()
+    };
+    // One seed for both phases, so a printed seed reproduces the whole run
+    let shuffle = args.shuffle || manifest.test.as_ref().is_some_and(|t| t.shuffle);
+    // Fail-fast runs classes one at a time in name order, which a seed cannot reorder
+    if args.fail_fast && (shuffle || args.seed.is_some()) {
+        bail!("--fail-fast cannot be used with --shuffle, --seed, or `[test] shuffle`");
+    }
+    if args.seed.is_none() && shuffle {
+        args.seed = Some(random_seed());
+    }
+    if let Some(seed) = args.seed {
+        gctx.shell.status(
+            "Shuffling",
+            &format!(
+                "test order with seed {} (reproduce with --seed {})",
+                seed, seed
+            ),
+        );
+    }
+
+    let unit = run_all(gctx, &manifest, &args)?;
+    if args.coverage {
+        report_coverage(gctx, &manifest, &minimums)?;
+    }
+    match unit {
+        Some(prepared) if args.mutation => run_mutation(gctx, &manifest, &prepared, threshold),
+        _ => Ok(()),
+    }
+}
+
+/// A seed for `--shuffle` without `--seed`, from the clock.
+fn random_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+/// Run the unit tests and, with `--integration`, the integration tests.
+///
+/// Returns the compiled unit tests, or `None` when `test/` has no sources.
+fn run_all(
+    gctx: &GlobalContext,
+    manifest: &JargoToml,
+    args: &TestArgs,
+) -> Result<Option<PreparedTests>> {
+    if !args.integration {
+        let unit = prepare(gctx, manifest)?;
+        match &unit {
+            Some(prepared) => run(gctx, manifest, prepared, args)?,
+            None => gctx.shell.warn("no test sources found in test/"),
+        }
+        return Ok(unit);
+    }
+
+    let resolved = compile_main(gctx, manifest)?;
+    let unit = prepare_source_set(gctx, manifest, &resolved, SourceSet::Test)?;
+    if let Some(prepared) = &unit {
+        run(gctx, manifest, prepared, args)?;
+    }
+    match prepare_source_set(gctx, manifest, &resolved, SourceSet::IntegrationTest)? {
+        Some(prepared) => run(gctx, manifest, &prepared, args)?,
+        None if unit.is_none() => gctx.shell.warn("no test sources found in test/ or it/"),
+        None => gctx.shell.warn("no integration test sources found in it/"),
+    }
+    Ok(unit)
+}
+
+/// Run PIT over the main classes with the unit tests, print the mutation
+/// score and the surviving mutations, and check the `[test.mutation]`
+/// threshold.
+fn run_mutation(
+    gctx: &GlobalContext,
+    manifest: &JargoToml,
+    prepared: &PreparedTests,
+    threshold: Option<f64>,
+) -> Result<()> {
+    let toolchain = toolchain::resolve(&gctx.cwd, manifest)?;
+    let mut classpath = mutation::fetch_pit(gctx, manifest.get_test_framework())?;
+    classpath.extend(prepared.runtime_classpath.iter().cloned());
+    classpath.extend(prepared.engine.compile_jar().map(Path::to_path_buf));
+    let mut jvm_args = manifest.get_test_jvm_args().to_vec();
+    jvm_args.extend(
+        manifest
+            .get_test_system_properties()?
+            .iter()
+            .map(|(key, value)| format!("-D{}={}", key, value)),
+    );
+    let opts = MutationOptions {
+        classpath,
+        base_package: manifest.get_base_package(),
+        mutators: manifest.get_mutators().to_vec(),
+        jvm_args,
+        env: env::test_env(&gctx.cwd, manifest)?,
+    };
+
+    gctx.shell.status(
+        "Mutating",
+        &format!("{} v{}", manifest.package.name, manifest.package.version),
+    );
+    let report = mutation::run(gctx, &gctx.cwd, &toolchain, &opts)?;
+    if report.mutations.is_empty() {
+        gctx.shell.warn("PIT generated no mutations");
+        return Ok(());
+    }
+
+    let survivors = report.survivors();
+    if !survivors.is_empty() {
+        gctx.shell.print("");
+        for survivor in &survivors {
+            gctx.shell.print(format!(
+                "{}:{} {}.{}: {} ({})",
+                survivor.source_file,
+                survivor.line,
+                survivor.class,
+                survivor.method,
+                survivor.description,
+                survivor.status.to_lowercase().replace('_', " ")
+            ));
+        }
+        gctx.shell.print("");
+    }
+    gctx.shell.status(
+        "Mutation",
+        &format!(
+            "score {:.1}% ({} of {} mutations detected)",
+            report.score(),
+            report.detected(),
+            report.mutations.len()
+        ),
+    );
+    let display = report.html.strip_prefix(&gctx.cwd).unwrap_or(&report.html);
+    gctx.shell
+        .status("Generated", &display.display().to_string());
+
+    match threshold {
+        Some(threshold) if report.score() < threshold => {
+            Err(JargoError::MutationScoreTooLow(report.score(), threshold).into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Generate the coverage reports of the finished run and check them against
+/// the `[test.coverage]` minimums.
+fn report_coverage(
+    gctx: &GlobalContext,
+    manifest: &JargoToml,
+    minimums: &[(&str, f64)],
+) -> Result<()> {
+    let toolchain = toolchain::resolve(&gctx.cwd, manifest)?;
+    let Some(report) = coverage::report(
+        gctx,
+        &gctx.cwd,
+        &toolchain,
+        &manifest.package.name,
+        &manifest.get_base_package(),
+    )?
+    else {
+        gctx.shell.warn("no coverage data recorded");
+        return Ok(());
+    };
+
+    gctx.shell.status("Coverage", &report.summary());
+    let display = report.html.strip_prefix(&gctx.cwd).unwrap_or(&report.html);
+    gctx.shell
+        .status("Generated", &display.display().to_string());
+
+    let unmet = report.unmet(minimums);
+    if !unmet.is_empty() {
+        return Err(JargoError::CoverageTooLow(unmet.join(", ")).into());
+    }
+    Ok(())
+}
+
+/// Run already-compiled tests and render the results.
+pub fn run(
+    gctx: &GlobalContext,
+    manifest: &JargoToml,
+    prepared: &PreparedTests,
+    args: &TestArgs,
+) -> Result<()> {
+    let toolchain = toolchain::resolve(&gctx.cwd, manifest)?;
+    let (include_tags, exclude_tags) =
+        test_filter::tag_filters(manifest.test.as_ref(), &args.tags, &args.exclude_tags);
+    let mut jvm_args = resolver::agent_jvm_args(gctx, manifest)?;
+    if args.coverage {
+        jvm_args.push(coverage::agent_jvm_arg(gctx, &gctx.cwd)?);
+    }
+    jvm_args.extend(manifest.get_test_jvm_args().iter().cloned());
+    jvm_args.extend(
+        manifest
+            .get_test_system_properties()?
+            .iter()
+            .map(|(key, value)| format!("-D{}={}", key, value)),
+    );
+    let mut opts = TestOptions {
+        jvm_args,
+        env: env::test_env(&gctx.cwd, manifest)?,
+        include_tags,
+        exclude_tags,
+        fail_fast: args.fail_fast,
+        integration: prepared.integration,
+        seed: args.seed,
+        ..TestOptions::default()
+    };
+
+    if args.rerun_failed {
+        let failed = test_runner::load_failed(&gctx.cwd)?;
+        if failed.is_empty() {
+            gctx.shell.status("Finished", "no failed tests to rerun");
+            return Ok(());
+        }
+        let (methods, classes) = failed.into_iter().partition(|s| s.contains('#'));
+        opts.select_classes = classes;
+        opts.select_methods = methods;
+    }
+
+    if let Some(since) = &args.affected {
+        let base_package = manifest.get_base_package();
+        match affected::affected_tests(&gctx.cwd, &toolchain, &base_package, since)? {
+            AffectedTests::All(trigger) => {
+                gctx.shell.verbose(|sh| {
+                    sh.print(format!(
+                        "  [verbose] {} changed, running all tests",
+                        trigger
+                    ))
+                });
+            }
+            AffectedTests::Classes(classes) if classes.is_empty() => {
+                gctx.shell
+                    .status("Finished", &format!("no tests affected since {}", since));
+                return Ok(());
+            }
+            AffectedTests::Classes(classes) => opts.select_classes = classes,
+        }
+    }
+
+    if !args.filters.is_empty() {
+        let test_classes_dir = gctx.cwd.join(opts.classes_dir());
+        let selection =
+            test_filter::select(&test_classes_dir, &args.filters, &opts.select_classes)?;
+        if selection.is_empty() {
+            let filters: Vec<String> = args.filters.iter().map(TestFilter::to_string).collect();
+            gctx.shell
+                .warn(&format!("no tests match {}", filters.join(", ")));
+            return Ok(());
+        }
+        opts.select_classes = selection.classes;
+        opts.select_methods = selection.methods;
+    }
+
+    let kind = if opts.integration { "it" } else { "test" };
+    let recording = if args.profile_jvm {
+        let rec = FlightRecording::new(&gctx.cwd, &format!("{}-{}", manifest.package.name, kind))?;
+        opts.jvm_args.push(rec.jvm_arg());
+        Some(rec)
+    } else {
+        None
+    };
+
+    let what = if opts.integration {
+        "integration tests"
+    } else {
+        "tests"
+    };
+    gctx.shell
+        .status("Running", &format!("{} ({})", what, opts.classes_dir()));
+    let run = test_runner::run_tests(
+        gctx,
+        &gctx.cwd,
+        &toolchain,
+        &prepared.engine,
+        &prepared.runtime_classpath,
+        &opts,
+    )?;
+
+    if !opts.integration {
+        test_runner::save_failed(&gctx.cwd, &run)?;
+    }
+
+    gctx.shell
+        .verbose(|sh| sh.print(run.launcher_output.trim_end()));
+    render_run(gctx, &run, &manifest.get_base_package());
+    let mut timings = TestTimings::load(&gctx.cwd);
+    let slowest = args.slowest.unwrap_or_else(|| manifest.get_slowest_count());
+    render_slowest(gctx, &run, &timings, slowest, &manifest.get_base_package());
+    timings.record(&run);
+    timings.save(&gctx.cwd)?;
+    emit_results(gctx, &run, &manifest.package.name);
+
+    let json_summary = args.json_summary || manifest.test.as_ref().is_some_and(|t| t.json_summary);
+    if json_summary {
+        let reports_dir = gctx.cwd.join(opts.reports_dir());
+        let path =
+            test_runner::write_summary(&reports_dir, &manifest.package.name, &run, args.seed)?;
+        let display = path.strip_prefix(&gctx.cwd).unwrap_or(&path);
+        gctx.shell
+            .status("Generated", &display.display().to_string());
+    }
+
+    if let Some(rec) = recording {
+        let display = rec.path.strip_prefix(&gctx.cwd).unwrap_or(&rec.path);
+        gctx.shell
+            .status("Profiled", &display.display().to_string());
+    }
+
+    if !run.success() {
+        if let Some(seed) = args.seed {
+            gctx.shell.print(format!(
+                "test order seed: {} (reproduce with --seed {})",
+                seed, seed
+            ));
+        }
+        return Err(JargoError::TestsFailed(run.failed()).into());
+    }
+
+    Ok(())
+}
+
+/// Resolve dependencies and compile main and test sources.
+///
+/// Returns `None` when `test/` contains no Java sources.
+pub fn prepare(gctx: &GlobalContext, manifest: &JargoToml) -> Result<Option<PreparedTests>> {
+    let resolved = compile_main(gctx, manifest)?;
+    prepare_source_set(gctx, manifest, &resolved, SourceSet::Test)
+}
+
+/// Resolve dependencies and compile main sources.
+fn compile_main(gctx: &GlobalContext, manifest: &JargoToml) -> Result<ResolvedDeps> {
+    let resolved = resolver::resolve(gctx, &gctx.cwd, manifest)?;
+
+    gctx.shell.status(
+        "Compiling",
+        &format!(
+            "{} v{} (java {})",
+            manifest.package.name, manifest.package.version, manifest.package.java
+        ),
+    );
+
+    let compile_output = compiler::compile(gctx, &gctx.cwd, manifest, &resolved.compile_jars)?;
+    if !compile_output.success {
+        compile_output.print_errors(&gctx.shell, &manifest.package.name);
+        gctx.shell
+            .message(&Message::BuildFinished { success: false });
+        return Err(JargoError::CompilationFailed.into());
+    }
+
+    compile_output.print_warnings(&gctx.shell, &manifest.package.name);
+    Ok(resolved)
+}
+
+/// Compile `test/` or `it/` against the compiled main classes.
+///
+/// Returns `None` when the source set contains no Java sources.
+fn prepare_source_set(
+    gctx: &GlobalContext,
+    manifest: &JargoToml,
+    resolved: &ResolvedDeps,
+    set: SourceSet,
+) -> Result<Option<PreparedTests>> {
+    if !compiler::has_sources(&gctx.cwd, manifest, set)? {
+        return Ok(None);
+    }
+
+    let integration = set == SourceSet::IntegrationTest;
+    let engine = TestEngine::for_framework(gctx, manifest.get_test_framework())?;
+    let classes_dir = gctx.cwd.join(SourceSet::Main.output_dir());
+    let test_classes_dir = gctx.cwd.join(set.output_dir());
+    // Integration tests also see it-dependencies
+    let it_jars: &[PathBuf] = if integration { &resolved.it_jars } else { &[] };
+
+    // Test compile: main classes + compile deps + dev deps + JUnit (TestNG is a dev dep).
+    let mut test_compile_cp = vec![classes_dir.clone()];
+    test_compile_cp.extend(resolved.compile_jars.iter().cloned());
+    test_compile_cp.extend(resolved.test_jars.iter().cloned());
+    test_compile_cp.extend(it_jars.iter().cloned());
+    test_compile_cp.extend(engine.compile_jar().map(Path::to_path_buf));
+
+    let test_output = if integration {
+        compiler::compile_integration_tests(gctx, &gctx.cwd, manifest, &test_compile_cp)?
+    } else {
+        compiler::compile_tests(gctx, &gctx.cwd, manifest, &test_compile_cp)?
+    };
+    if !test_output.success {
+        test_output.print_errors(&gctx.shell, &manifest.package.name);
+        gctx.shell
+            .message(&Message::BuildFinished { success: false });
+        return Err(JargoError::CompilationFailed.into());
+    }
+
+    test_output.print_warnings(&gctx.shell, &manifest.package.name);
+
+    // Test runtime: test classes + main classes + runtime deps + dev deps.
+    // JUnit itself is provided by the console launcher.
+    let mut runtime_classpath = vec![test_classes_dir, classes_dir];
+    runtime_classpath.extend(resolved.runtime_jars.iter().cloned());
+    runtime_classpath.extend(resolved.test_jars.iter().cloned());
+    runtime_classpath.extend(it_jars.iter().cloned());
+
+    Ok(Some(PreparedTests {
+        engine,
+        runtime_classpath,
+        integration,
+    }))
+}
+
+/// Print Cargo-style per-test lines, failure details, and the summary line.
+fn render_run(gctx: &GlobalContext, run: &TestRun, base_package: &str) {
+    gctx.shell.print("");
+    gctx.shell.print(format!(
+        "running {} test{}",
+        run.cases.len(),
+        if run.cases.len() == 1 { "" } else { "s" }
+    ));
+    for case in &run.cases {
+        let result = match &case.outcome {
+            TestOutcome::Passed => "ok",
+            TestOutcome::Failed { .. } => "FAILED",
+            TestOutcome::Skipped(_) => "skipped",
+        };
+        gctx.shell.print(format!(
+            "test {} ... {}",
+            display_name(case, base_package),
+            result
+        ));
+    }
+
+    let failures: Vec<&TestCase> = run
+        .cases
+        .iter()
+        .filter(|c| matches!(c.outcome, TestOutcome::Failed { .. }))
+        .collect();
+
+    if !failures.is_empty() {
+        gctx.shell.print("");
+        gctx.shell.print("failures:");
+        for case in &failures {
+            gctx.shell.print("");
+            gctx.shell
+                .print(format!("---- {} ----", display_name(case, base_package)));
+            if let TestOutcome::Failed { message, details } = &case.outcome {
+                if details.trim().is_empty() {
+                    gctx.shell.print(message);
+                } else {
+                    gctx.shell.print(details.trim_end());
+                }
+            }
+            if !case.output.trim().is_empty() {
+                gctx.shell.print("---- output ----");
+                gctx.shell.print(case.output.trim_end());
+            }
+        }
+        gctx.shell.print("");
+        gctx.shell.print("failures:");
+        for case in &failures {
+            gctx.shell
+                .print(format!("    {}", display_name(case, base_package)));
+        }
+    }
+
+    gctx.shell.print("");
+    gctx.shell.print(format!(
+        "test result: {}. {} passed; {} failed; {} skipped; finished in {:.2}s",
+        if run.success() { "ok" } else { "FAILED" },
+        run.passed(),
+        run.failed(),
+        run.skipped(),
+        run.total_time()
+    ));
+    gctx.shell.print("");
+}
+
+/// List the `n` slowest tests of `run`, with their average over the runs
+/// recorded in `timings`.
+fn render_slowest(
+    gctx: &GlobalContext,
+    run: &TestRun,
+    timings: &TestTimings,
+    n: usize,
+    base_package: &str,
+) {
+    let slowest = test_runner::slowest(run, n);
+    if slowest.is_empty() {
+        return;
+    }
+    gctx.shell.print(format!(
+        "slowest {} test{}:",
+        slowest.len(),
+        if slowest.len() == 1 { "" } else { "s" }
+    ));
+    for case in slowest {
+        let history = match timings.average(case) {
+            Some((average, runs)) => format!(
+                " (avg {:.3}s over {} previous run{})",
+                average,
+                runs,
+                if runs == 1 { "" } else { "s" }
+            ),
+            None => String::new(),
+        };
+        gctx.shell.print(format!(
+            "    {:.3}s {}{}",
+            case.time,
+            display_name(case, base_package),
+            history
+        ));
+    }
+    gctx.shell.print("");
+}
+
+/// Emit one `test-result` message per case, then `build-finished`.
+fn emit_results(gctx: &GlobalContext, run: &TestRun, package: &str) {
+    for case in &run.cases {
+        gctx.shell.message(&Message::TestResult {
+            package,
+            class: &case.class_name,
+            name: &case.name,
+            outcome: case.outcome.as_str(),
+            time: case.time,
+            message: case.outcome.message(),
+        });
+    }
+    gctx.shell.message(&Message::BuildFinished {
+        success: run.success(),
+    });
+}
+
+/// `myapp.util.HelperTest` + `testX()` → `util.HelperTest::testX()`
+fn display_name(case: &TestCase, base_package: &str) -> String {
+    let class = case
+        .class_name
+        .strip_prefix(base_package)
+        .and_then(|c| c.strip_prefix('.'))
+        .unwrap_or(&case.class_name);
+    format!("{}::{}", class, case.name)
+}
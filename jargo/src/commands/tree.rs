@@ -0,0 +1,68 @@
+use anyhow::Result;
+
+use jargo_core::context::GlobalContext;
+use jargo_core::lockfile::LockFile;
+use jargo_core::workspace::{Member, Workspace};
+
+/// Execute `jargo tree --duplicates`: list the artifacts `members` lock at
+/// different versions in the workspace's Jargo.lock.
+pub fn exec_duplicates(
+    gctx: &GlobalContext,
+    workspace: &Workspace,
+    members: &[&Member],
+) -> Result<()> {
+    let lock_path = workspace.lock_path();
+    if !lock_path.exists() {
+        gctx.shell
+            .warn("the workspace has no Jargo.lock yet; build its members first");
+        return Ok(());
+    }
+    let mut lock = LockFile::read(&lock_path)?;
+    lock.member
+        .retain(|name, _| members.iter().any(|m| &m.name == name));
+
+    let duplicates = lock.duplicates();
+    for (artifact, versions) in &duplicates {
+        gctx.shell.print(artifact);
+        for (i, (version, names)) in versions.iter().enumerate() {
+            let branch = if i + 1 == versions.len() {
+                "└──"
+            } else {
+                "├──"
+            };
+            let mut labels = Vec::new();
+            for name in names {
+                let member = members
+                    .iter()
+                    .find(|m| &m.name == name)
+                    .expect("lock sections were filtered to the members");
+                // Members declaring the artifact themselves are where to converge
+                let manifest = workspace.manifest(member)?;
+                let declared = manifest
+                    .get_dependencies()?
+                    .iter()
+                    .chain(&manifest.get_dev_dependencies()?)
+                    .chain(&manifest.get_it_dependencies()?)
+                    .any(|d| format!("{}:{}", d.group, d.artifact) == *artifact);
+                labels.push(if declared {
+                    format!("{} (direct)", name)
+                } else {
+                    name.clone()
+                });
+            }
+            gctx.shell
+                .print(format!("{} {}: {}", branch, version, labels.join(", ")));
+        }
+    }
+
+    if duplicates.is_empty() {
+        gctx.shell
+            .status("Finished", "no artifact is locked at more than one version");
+    } else {
+        gctx.shell.warn(&format!(
+            "{} artifact(s) locked at more than one version across the workspace",
+            duplicates.len()
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,18 @@
+use anyhow::{bail, Result};
+
+use jargo_core::context::GlobalContext;
+use jargo_core::install;
+
+/// Execute `jargo uninstall`: remove the launchers and JAR that
+/// `jargo install` put in `~/.jargo/bin` for the package `name`.
+pub fn exec(gctx: &GlobalContext, name: &str) -> Result<()> {
+    let bin_dir = install::bin_dir(&gctx.jargo_home);
+    let removed = install::uninstall(&bin_dir, name)?;
+    if removed.is_empty() {
+        bail!("`{}` is not installed in {}", name, bin_dir.display());
+    }
+    for path in &removed {
+        gctx.shell.status("Removed", &path.display().to_string());
+    }
+    Ok(())
+}
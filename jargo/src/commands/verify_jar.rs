@@ -0,0 +1,45 @@
+use anyhow::Result;
+
+use crate::commands::build;
+use jargo_core::context::GlobalContext;
+use jargo_core::errors::JargoError;
+use jargo_core::manifest::JargoToml;
+use jargo_core::verify;
+
+/// Execute `jargo verify-jar`: build the JAR, then check its main class,
+/// required resources, duplicate entries, and class file versions.
+pub fn exec(gctx: &GlobalContext) -> Result<()> {
+    let manifest_path = gctx.cwd.join("Jargo.toml");
+
+    if !manifest_path.exists() {
+        return Err(JargoError::ManifestNotFound.into());
+    }
+
+    let manifest = JargoToml::from_file(&manifest_path)
+        .map_err(|e| JargoError::ManifestParse(e.to_string()))?;
+
+    build::exec(gctx, false, false, false)?;
+
+    let jar_path = gctx
+        .cwd
+        .join("target")
+        .join(format!("{}.jar", manifest.package.name));
+    let display = jar_path
+        .strip_prefix(&gctx.cwd)
+        .unwrap_or(&jar_path)
+        .display()
+        .to_string();
+
+    gctx.shell.status("Verifying", &display);
+    let problems = verify::verify_jar(&jar_path, &manifest)?;
+
+    if !problems.is_empty() {
+        for problem in &problems {
+            gctx.shell.print(format!("  {}", problem));
+        }
+        return Err(JargoError::JarVerificationFailed(problems.len()).into());
+    }
+
+    gctx.shell.status("Verified", &display);
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+use jargo_core::context::GlobalContext;
+use jargo_core::errors::JargoError;
+use jargo_core::manifest::JargoToml;
+use jargo_core::version::{self, Bump};
+use jargo_core::workspace::{Member, Workspace};
+
+/// Execute `jargo version <bump>` in a standalone package.
+pub fn exec(gctx: &GlobalContext, bump: &str, commit: bool, tag: bool) -> Result<()> {
+    let manifest_path = gctx.cwd.join("Jargo.toml");
+
+    if !manifest_path.exists() {
+        return Err(JargoError::ManifestNotFound.into());
+    }
+
+    bump_versions(gctx, &gctx.cwd, &[manifest_path], bump, commit, tag, false)
+}
+
+/// Execute `jargo version <bump>` for workspace `members`, each keeping its
+/// own version, with one commit for all of them.
+pub fn exec_members(
+    gctx: &GlobalContext,
+    workspace: &Workspace,
+    members: &[&Member],
+    bump: &str,
+    commit: bool,
+    tag: bool,
+) -> Result<()> {
+    let manifests: Vec<PathBuf> = members.iter().map(|m| m.dir.join("Jargo.toml")).collect();
+    bump_versions(gctx, &workspace.root, &manifests, bump, commit, tag, true)
+}
+
+fn bump_versions(
+    gctx: &GlobalContext,
+    repo: &Path,
+    manifests: &[PathBuf],
+    bump: &str,
+    commit: bool,
+    tag: bool,
+    in_workspace: bool,
+) -> Result<()> {
+    let bump: Bump = bump.parse()?;
+
+    // Work out every new version before touching any file
+    let mut bumped = Vec::new();
+    for path in manifests {
+        let manifest =
+            JargoToml::from_file(path).map_err(|e| JargoError::ManifestParse(e.to_string()))?;
+        let package = manifest.package;
+        let next = version::next_version(&package.version, &bump)?;
+        bumped.push((path, package.name, package.version, next));
+    }
+    let tags: Vec<String> = if tag {
+        bumped
+            .iter()
+            .map(|(_, name, _, next)| version::tag_name(name, next, in_workspace))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    version::check_tags_unused(repo, &tags)?;
+
+    for (path, name, current, next) in &bumped {
+        version::set_version(path, next)?;
+        gctx.shell
+            .status("Bumping", &format!("{} from {} to {}", name, current, next));
+    }
+
+    if commit || tag {
+        let changes: Vec<String> = bumped
+            .iter()
+            .map(|(_, name, _, next)| format!("{} to {}", name, next))
+            .collect();
+        let message = format!("Bump {}", changes.join(", "));
+        let paths: Vec<PathBuf> = bumped.iter().map(|(path, ..)| path.to_path_buf()).collect();
+        version::commit_and_tag(repo, &paths, &message, &tags)?;
+        gctx.shell.status("Committed", &message);
+        for tag in &tags {
+            gctx.shell.status("Tagged", tag);
+        }
+    }
+
+    if let [(_, _, _, next)] = &bumped[..] {
+        gctx.shell.print(next);
+    }
+    Ok(())
+}
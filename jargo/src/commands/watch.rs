@@ -0,0 +1,169 @@
+use anyhow::{bail, Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus};
+use std::time::Duration;
+#[cfg(unix)]
+use std::time::Instant;
+
+use jargo_core::context::GlobalContext;
+use jargo_core::errors::JargoError;
+use jargo_core::watch::{Watcher, WATCH_ENV};
+
+/// Commands `jargo watch` can re-run.
+const WATCHABLE: &[&str] = &["build", "check", "test", "run"];
+
+/// How often to check whether the running command has exited.
+const POLL: Duration = Duration::from_millis(100);
+
+/// How long a stopped command gets to exit after SIGTERM before it is killed.
+#[cfg(unix)]
+const GRACE: Duration = Duration::from_secs(5);
+
+/// Execute `jargo watch [COMMAND [ARGS...]]`.
+pub fn exec(gctx: &GlobalContext, command: Vec<String>) -> Result<()> {
+    let mut args = if command.is_empty() {
+        vec!["build".to_string()]
+    } else {
+        command
+    };
+    if !WATCHABLE.contains(&args[0].as_str()) {
+        bail!(
+            "`jargo watch` cannot re-run `{}`; expected one of: {}",
+            args[0],
+            WATCHABLE.join(", ")
+        );
+    }
+    let mut flags = [
+        gctx.shell.verbosity().flags(),
+        gctx.shell.color_choice().flags(),
+    ]
+    .concat();
+    if gctx.locked {
+        flags.push("--locked");
+    }
+    args.splice(0..0, flags.iter().map(|f| f.to_string()));
+    if !gctx.features.is_empty() {
+        args.splice(0..0, ["--features".to_string(), gctx.features.join(",")]);
+    }
+    watch(gctx, &args)
+}
+
+/// Execute `jargo <command> --watch ...` by re-running the same command line
+/// without `--watch`.
+pub fn exec_flag(gctx: &GlobalContext) -> Result<()> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    // The first `--watch` is ours; a later one belongs to `run`'s program args.
+    if let Some(i) = args.iter().position(|a| a == "--watch") {
+        args.remove(i);
+    }
+    watch(gctx, &args)
+}
+
+/// Run `jargo {args}` now and again after every batch of changes, stopping a
+/// command that is still running (e.g. `run`) before starting it again.
+fn watch(gctx: &GlobalContext, args: &[String]) -> Result<()> {
+    if !gctx.cwd.join("Jargo.toml").exists() {
+        return Err(JargoError::ManifestNotFound.into());
+    }
+    let exe = std::env::current_exe().context("could not locate the jargo executable")?;
+    let watcher = Watcher::new(&gctx.cwd)?;
+
+    gctx.shell.status(
+        "Watching",
+        &format!("{} (Ctrl-C to stop)", gctx.cwd.display()),
+    );
+    let mut child = Some(spawn(gctx, &exe, args)?);
+    loop {
+        let timeout = child.as_ref().map(|_| POLL);
+        if let Some(changed) = watcher.next_change(timeout)? {
+            if let Some(mut running) = child.take() {
+                gctx.shell.status("Stopping", "previous run");
+                stop(&mut running)?;
+            }
+            gctx.shell.status("Changed", &describe(&changed));
+            child = Some(spawn(gctx, &exe, args)?);
+        } else if let Some(running) = &mut child {
+            if let Some(status) = running.try_wait()? {
+                child = None;
+                let outcome = if status.success() { "ok" } else { "failed" };
+                gctx.shell
+                    .status("Watching", &format!("for changes (last run {})", outcome));
+            }
+        }
+    }
+}
+
+fn spawn(gctx: &GlobalContext, exe: &Path, args: &[String]) -> Result<Child> {
+    gctx.shell
+        .verbose(|sh| sh.print(format!("  [verbose] running `jargo {}`", args.join(" "))));
+    // Started where jargo was, so relative `--manifest-path` and
+    // `--target-dir` flags in `args` mean the same to the child
+    Command::new(exe)
+        .args(args)
+        .env(WATCH_ENV, "1")
+        .spawn()
+        .context("failed to start jargo")
+}
+
+/// Stop a running command. On Unix it gets SIGTERM first, so a JVM that
+/// `jargo run` exec'd runs its shutdown hooks, and SIGKILL once [`GRACE`]
+/// has passed; elsewhere it is killed right away.
+fn stop(child: &mut Child) -> Result<ExitStatus> {
+    #[cfg(unix)]
+    {
+        // SAFETY: plain syscall on the pid of a child that has not been reaped.
+        let sent = unsafe { libc::kill(child.id() as libc::pid_t, libc::SIGTERM) } == 0;
+        let deadline = Instant::now() + GRACE;
+        while sent && Instant::now() < deadline {
+            if let Some(status) = child.try_wait()? {
+                return Ok(status);
+            }
+            std::thread::sleep(POLL);
+        }
+    }
+    // Already exited when this fails; wait() reaps it either way
+    let _ = child.kill();
+    Ok(child.wait()?)
+}
+
+/// `src/Main.java`, or `src/Main.java and 2 more`.
+fn describe(changed: &[PathBuf]) -> String {
+    match changed {
+        [] => "project files".to_string(),
+        [one] => one.display().to_string(),
+        [first, rest @ ..] => format!("{} and {} more", first.display(), rest.len()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(describe(&[PathBuf::from("src/Main.java")]), "src/Main.java");
+        assert_eq!(
+            describe(&[
+                PathBuf::from("Jargo.toml"),
+                PathBuf::from("src/A.java"),
+                PathBuf::from("src/B.java")
+            ]),
+            "Jargo.toml and 2 more"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_stop_sends_sigterm_first() {
+        let mut child = Command::new("sh")
+            .args(["-c", "trap 'exit 3' TERM; while true; do sleep 0.1; done"])
+            .spawn()
+            .unwrap();
+        // Give sh time to install the trap
+        std::thread::sleep(Duration::from_millis(300));
+        let started = Instant::now();
+        let status = stop(&mut child).unwrap();
+        assert_eq!(status.code(), Some(3));
+        assert!(started.elapsed() < GRACE);
+    }
+}
@@ -1,52 +1,596 @@
 mod cli;
 mod commands;
+mod process;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use clap::Parser;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
-use cli::{Cli, Command};
+use cli::{Cli, Command, PackageSelection};
+use jargo_core::cleanup;
+use jargo_core::config::{self, ConfigKey};
+use jargo_core::context::GlobalContext;
+use jargo_core::errors::{self, ErrorKind, JargoError};
+use jargo_core::logging;
+use jargo_core::manifest::{self, JargoToml};
+use jargo_core::shell::{ColorChoice, MessageFormat, Verbosity};
+use jargo_core::workspace::{self, Member, MemberFingerprints, Workspace};
 
-fn main() -> Result<()> {
+fn main() {
     let cli = Cli::parse();
-    let gctx = jargo_core::context::GlobalContext::new(cli.verbose)?;
-
-    match cli.command {
-        Command::New { name, lib } => commands::new::exec(&gctx, &name, lib),
-        Command::Init { lib } => commands::init::exec(&gctx, lib),
-        Command::Build => commands::build::exec(&gctx),
-        Command::Run { args } => commands::run::exec(&gctx, args),
-        Command::Test => {
-            eprintln!("error: `test` is not yet implemented");
-            std::process::exit(1);
+    let json = cli.error_format == "json";
+    if let Err(err) = run(cli) {
+        if json {
+            eprintln!("{}", errors::to_json(&err));
+        } else {
+            eprintln!("Error: {:?}", err);
         }
-        Command::Check { .. } => {
-            eprintln!("error: `check` is not yet implemented");
-            std::process::exit(1);
+        std::process::exit(ErrorKind::of(&err).exit_code());
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    let verbosity = Verbosity::from_flags(cli.verbose, cli.quiet);
+    let mut gctx = GlobalContext::new(verbosity)?;
+    gctx.shell
+        .set_color_choice(ColorChoice::parse(&cli.color).unwrap_or_default());
+    logging::init(verbosity, gctx.shell.err_color());
+
+    // On Ctrl-C, remove half-written downloads, JARs and scratch directories
+    // before exiting the way an interrupted process would
+    let _ = ctrlc::set_handler(|| {
+        cleanup::remove_in_progress();
+        std::process::exit(130);
+    });
+    gctx.locked = cli.locked;
+    gctx.features = cli.features.clone();
+
+    // Flags win over every other layer. They are exported like environment
+    // variables, so child `jargo` processes and tools see them too.
+    if cli.offline {
+        gctx.config
+            .apply_flag(ConfigKey::Offline, "--offline", "true");
+    }
+    if let Some(dir) = &cli.target_dir {
+        let dir = gctx.cwd.join(dir);
+        gctx.config
+            .apply_flag(ConfigKey::TargetDir, "--target-dir", &dir.to_string_lossy());
+    }
+
+    // Run in the package of `--manifest-path`, or the one around the current
+    // directory as found by walking up to the nearest Jargo.toml; commands
+    // creating a package, or not about one, stay where they are
+    let stays = matches!(
+        cli.command,
+        Command::New { .. }
+            | Command::Init { .. }
+            | Command::Migrate
+            | Command::Config { .. }
+            | Command::Uninstall { .. }
+            | Command::Install {
+                coordinate: Some(_),
+                ..
+            }
+            | Command::Install { path: Some(_), .. }
+    );
+    if let Some(path) = &cli.manifest_path {
+        if stays {
+            return Err(JargoError::InvalidManifestPath(
+                path.display().to_string(),
+                "this command does not run in an existing package".to_string(),
+            )
+            .into());
         }
-        Command::Clean => commands::clean::exec(&gctx),
-        Command::Add { .. } => {
-            eprintln!("error: `add` is not yet implemented");
-            std::process::exit(1);
+        let root = manifest_dir(&gctx.cwd.join(path))?;
+        gctx = gctx.with_cwd(root);
+    } else if !stays {
+        if let Some(root) = manifest::find_project_root(&gctx.cwd) {
+            if root != gctx.cwd {
+                gctx.shell.verbose(|sh| {
+                    sh.print(format!(
+                        "  [verbose] using {}",
+                        root.join("Jargo.toml").display()
+                    ))
+                });
+                gctx = gctx.with_cwd(root);
+            }
         }
-        Command::Update => {
-            eprintln!("error: `update` is not yet implemented");
-            std::process::exit(1);
+    }
+
+    // `check --manifest` lists unknown keys itself
+    if !stays && !matches!(cli.command, Command::Check { manifest: true, .. }) {
+        commands::check::warn_unknown_keys(&gctx);
+    }
+
+    if let Command::Build { message_format, .. }
+    | Command::Check { message_format, .. }
+    | Command::Test { message_format, .. } = &cli.command
+    {
+        if message_format == "json" {
+            gctx.shell.set_message_format(MessageFormat::Json);
         }
-        Command::Tree => {
-            eprintln!("error: `tree` is not yet implemented");
-            std::process::exit(1);
+    }
+
+    // `new` may be creating a member the workspace already lists; `env`,
+    // `config`, and installing tools from elsewhere are not about one member
+    if !matches!(
+        cli.command,
+        Command::New { .. }
+            | Command::Env
+            | Command::Config { .. }
+            | Command::Uninstall { .. }
+            | Command::Install {
+                coordinate: Some(_),
+                ..
+            }
+            | Command::Install { path: Some(_), .. }
+    ) {
+        let workspace = match Workspace::load(&gctx.cwd)? {
+            Some(workspace) => Some(workspace),
+            None => Workspace::containing(&gctx.cwd)?,
+        };
+        if let Some(workspace) = workspace {
+            if let Some(dir) = config::target_dir(&workspace.root, None) {
+                workspace::link_target(&workspace.root, &dir)?;
+            }
+            return exec_workspace(&gctx, &workspace, cli.command);
+        }
+        if cli
+            .command
+            .package_selection()
+            .is_some_and(|s| !s.packages.is_empty())
+        {
+            bail!("`-p` selects workspace members, but this package is not in a workspace");
+        }
+        link_target_dir(&gctx)?;
+    }
+
+    if is_watch(&cli.command) {
+        return commands::watch::exec_flag(&gctx);
+    }
+
+    dispatch(&gctx, cli.command)
+}
+
+/// The package directory of the Jargo.toml at `path`.
+fn manifest_dir(path: &Path) -> Result<PathBuf, JargoError> {
+    let invalid = |reason: &str| {
+        JargoError::InvalidManifestPath(path.display().to_string(), reason.to_string())
+    };
+    if path.file_name().is_none_or(|name| name != "Jargo.toml") {
+        return Err(invalid("expected a path to a file named Jargo.toml"));
+    }
+    if !path.is_file() {
+        return Err(invalid("no such file"));
+    }
+    Ok(path.parent().map(Path::to_path_buf).unwrap_or_default())
+}
+
+/// Link the package's `target/` to `--target-dir`, `JARGO_TARGET_DIR`, or
+/// `[build] target-dir`, when one is set. A Jargo.toml that does not parse is
+/// left for the command to report.
+fn link_target_dir(gctx: &GlobalContext) -> Result<()> {
+    let manifest_path = gctx.cwd.join("Jargo.toml");
+    if !manifest_path.exists() {
+        return Ok(());
+    }
+    let manifest = JargoToml::from_file(&manifest_path).ok();
+    match config::target_dir(&gctx.cwd, manifest.as_ref()) {
+        Some(dir) => workspace::link_target(&gctx.cwd, &dir),
+        None => Ok(()),
+    }
+}
+
+fn is_watch(command: &Command) -> bool {
+    matches!(
+        command,
+        Command::Build { watch: true, .. }
+            | Command::Check { watch: true, .. }
+            | Command::Test { watch: true, .. }
+            | Command::Run { watch: true, .. }
+    )
+}
+
+/// Run `command` in a workspace, from its root or a member directory.
+///
+/// Commands with `-p`/`--workspace` run in the selected members after the
+/// members they depend on, stopping at the first failure. Without
+/// either flag, they run in the current member, or in every member at the
+/// root. Other commands only run in a member directory.
+fn exec_workspace(gctx: &GlobalContext, workspace: &Workspace, command: Command) -> Result<()> {
+    let current = workspace.member(&gctx.cwd);
+    let Some(selection) = command.package_selection() else {
+        let Some(member) = current else {
+            return Err(JargoError::WorkspaceRoot(
+                workspace.root.join("Jargo.toml").display().to_string(),
+            )
+            .into());
+        };
+        workspace.link_target(member)?;
+        return dispatch(gctx, command);
+    };
+
+    let whole = selection.packages.is_empty() && (selection.workspace || current.is_none());
+    let mut members: Vec<&Member> = Vec::new();
+    if whole {
+        members.extend(&workspace.members);
+    } else if selection.packages.is_empty() {
+        members.extend(current);
+    } else {
+        for member in &workspace.members {
+            if selection.packages.contains(&member.name) {
+                members.push(member);
+            }
+        }
+        for name in &selection.packages {
+            if !workspace.members.iter().any(|m| &m.name == name) {
+                bail!(
+                    "package `{}` is not a member of the workspace at {}",
+                    name,
+                    workspace.root.display()
+                );
+            }
+        }
+    }
+    // Each member enables the features it declares; one no member declares
+    // is a typo
+    for feature in &gctx.features {
+        let declared = workspace.members.iter().any(|member| {
+            workspace
+                .manifest(member)
+                .is_ok_and(|manifest| manifest.features.contains_key(feature))
+        });
+        if !declared {
+            bail!(
+                "unknown feature `{}`: no workspace member declares it in [features]",
+                feature
+            );
+        }
+    }
+
+    match &command {
+        // The whole workspace shares one target/
+        Command::Clean { .. } if whole => {
+            return dispatch(&gctx.with_cwd(workspace.root.clone()), command);
+        }
+        Command::Run { .. } if members.len() != 1 => {
+            let names: Vec<&str> = members.iter().map(|m| m.name.as_str()).collect();
+            bail!(
+                "`jargo run` runs a single package; select one of {} with -p",
+                names.join(", ")
+            );
+        }
+        _ if is_watch(&command) => match (current, &members[..]) {
+            (Some(member), [selected]) if member.dir == selected.dir => {
+                workspace.link_target(member)?;
+                return commands::watch::exec_flag(gctx);
+            }
+            _ => bail!("`--watch` only runs in the current member; run it in the member directory"),
+        },
+        _ => {}
+    }
+
+    if members.is_empty() {
+        gctx.shell.warn("the workspace has no members");
+    }
+    let fingerprints = match command {
+        Command::Clean { .. } | Command::Tree { .. } | Command::Version { .. } => HashMap::new(),
+        _ => workspace.fingerprints(&gctx.features)?,
+    };
+    // Builds and plain test runs are skipped in members where nothing they
+    // depend on changed since the last successful one
+    let run_in = |member: &Member, command: Command| {
+        workspace.link_target(member)?;
+        let fingerprint = fingerprint_kind(&command).zip(fingerprints.get(&member.name));
+        let mut recorded = MemberFingerprints::load(member);
+        if let Some((kind, fingerprint)) = &fingerprint {
+            let built =
+                !matches!(command, Command::Build { .. }) || workspace.jar_path(member).exists();
+            if built && recorded.is_fresh(kind, fingerprint) {
+                gctx.shell.status(
+                    "Fresh",
+                    &format!(
+                        "{} (unchanged since the last `jargo {}`)",
+                        member.name, kind
+                    ),
+                );
+                return Ok(());
+            }
         }
-        Command::Fmt => {
-            eprintln!("error: `fmt` is not yet implemented");
+        dispatch(&gctx.with_cwd(member.dir.clone()), command)?;
+        if let Some((kind, fingerprint)) = fingerprint {
+            recorded.runs.insert(kind, fingerprint.clone());
+            recorded.save(member)?;
+        }
+        Ok(())
+    };
+    match command {
+        Command::Tree {
+            duplicates: true, ..
+        } => commands::tree::exec_duplicates(gctx, workspace, &members),
+        // One commit and set of tags covers every bumped member
+        Command::Version {
+            bump, commit, tag, ..
+        } => commands::version::exec_members(gctx, workspace, &members, &bump, commit, tag),
+        Command::Clean { .. } | Command::Tree { .. } => {
+            for member in members {
+                run_in(member, command.clone())?;
+            }
+            Ok(())
+        }
+        // Members build in dependency order, independent ones in parallel,
+        // together with the members they depend on
+        Command::Build { .. } => {
+            let upstream = workspace.upstream(&members)?;
+            let members: Vec<&Member> = workspace
+                .members
+                .iter()
+                .filter(|m| members.iter().chain(&upstream).any(|s| s.dir == m.dir))
+                .collect();
+            workspace.run_in_order(&members, build_jobs(), |member| {
+                run_in(member, command.clone())
+            })
+        }
+        // Other commands run one member at a time, after building the
+        // members the selection depends on
+        _ => {
+            let upstream = workspace.upstream(&members)?;
+            let build = Command::Build {
+                keep_going: false,
+                uber: false,
+                thin: false,
+                message_format: "human".to_string(),
+                watch: false,
+                selection: PackageSelection::default(),
+            };
+            workspace.run_in_order(&upstream, build_jobs(), |member| {
+                run_in(member, build.clone())
+            })?;
+            workspace.run_in_order(&members, 1, |member| run_in(member, command.clone()))
+        }
+    }
+}
+
+/// The kind of run recorded in member fingerprints, when `command` can be
+/// skipped in an unchanged member: builds without `--keep-going` and test
+/// runs without options selecting or instrumenting tests.
+fn fingerprint_kind(command: &Command) -> Option<String> {
+    match command {
+        Command::Build {
+            keep_going: false,
+            uber,
+            thin,
+            ..
+        } => Some(
+            match (uber, thin) {
+                (true, _) => "build --uber",
+                (_, true) => "build --thin",
+                _ => "build",
+            }
+            .to_string(),
+        ),
+        Command::Test {
+            filters,
+            tags,
+            exclude_tags,
+            json_summary: false,
+            shuffle: false,
+            seed: None,
+            rerun_failed: false,
+            integration,
+            coverage: false,
+            mutation: false,
+            profile_jvm: false,
+            affected: false,
+            ..
+        } if filters.is_empty() && tags.is_empty() && exclude_tags.is_empty() => Some(
+            if *integration {
+                "test --integration"
+            } else {
+                "test"
+            }
+            .to_string(),
+        ),
+        _ => None,
+    }
+}
+
+/// How many workspace members build at once: one per CPU.
+fn build_jobs() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Run `command` in `gctx.cwd`.
+fn dispatch(gctx: &GlobalContext, command: Command) -> Result<()> {
+    let _span = tracing::debug_span!("command", dir = %gctx.cwd.display()).entered();
+    match command {
+        Command::New {
+            name,
+            lib,
+            workspace,
+            member,
+            deps,
+            no_git,
+            options,
+        } => {
+            if workspace {
+                commands::new::exec_workspace(gctx, &name, no_git)
+            } else if member {
+                commands::new::exec_member(gctx, &name, lib, &deps, &options)
+            } else {
+                commands::new::exec(gctx, &name, lib, no_git, &options)
+            }
+        }
+        Command::Init { lib, options } => commands::init::exec(gctx, lib, &options),
+        Command::Migrate => commands::migrate::exec(gctx),
+        Command::Build {
+            keep_going,
+            uber,
+            thin,
+            ..
+        } => commands::build::exec(gctx, keep_going, uber, thin),
+        Command::Dist => commands::dist::exec(gctx),
+        Command::Image { push, bin } => commands::image::exec(gctx, push, bin.as_deref()),
+        Command::Jlink => commands::jlink::exec(gctx),
+        Command::Native { bin } => commands::native::exec(gctx, bin.as_deref()),
+        Command::Package { package_type, bin } => {
+            commands::package::exec(gctx, package_type.as_deref(), bin.as_deref())
+        }
+        Command::VerifyJar => commands::verify_jar::exec(gctx),
+        Command::Run {
+            profile_jvm,
+            bin,
+            example,
+            debug,
+            suspend,
+            profile,
+            sysprops,
+            args,
+            ..
+        } => commands::run::exec(
+            gctx,
+            commands::run::RunArgs {
+                bin,
+                example,
+                main_class: None,
+                debug,
+                suspend,
+                profile,
+                sysprops,
+                profile_jvm,
+                args,
+            },
+        ),
+        Command::Exec { main_class, args } => commands::exec::exec(gctx, main_class, args),
+        Command::Repl => commands::repl::exec(gctx),
+        Command::Test {
+            profile_jvm,
+            affected,
+            since,
+            filters,
+            tags,
+            exclude_tags,
+            json_summary,
+            fail_fast,
+            slowest,
+            shuffle,
+            seed,
+            rerun_failed,
+            integration,
+            coverage,
+            mutation,
+            ..
+        } => commands::test::exec(
+            gctx,
+            commands::test::TestArgs {
+                profile_jvm,
+                affected: affected.then_some(since),
+                filters,
+                tags,
+                exclude_tags,
+                json_summary,
+                fail_fast,
+                slowest,
+                shuffle,
+                seed,
+                rerun_failed,
+                integration,
+                coverage,
+                mutation,
+            },
+        ),
+        Command::Bench {
+            filters,
+            forks,
+            warmup_iterations,
+            iterations,
+        } => commands::bench::exec(
+            gctx,
+            jargo_core::bench::BenchOptions {
+                filters,
+                forks,
+                warmup_iterations,
+                iterations,
+            },
+        ),
+        Command::Check { manifest: true, .. } => commands::check::exec_manifest(gctx),
+        Command::Check {
+            fmt,
+            license_header,
+            ..
+        } => commands::check::exec(gctx, fmt, license_header),
+        Command::Watch { command } => commands::watch::exec(gctx, command),
+        Command::Ci => commands::ci::exec(gctx),
+        Command::Clean { .. } => commands::clean::exec(gctx),
+        Command::Add {
+            coordinate,
+            version,
+        } => commands::add::exec(gctx, &coordinate, version.as_deref()),
+        Command::Update => {
+            gctx.shell.error("`update` is not yet implemented");
             std::process::exit(1);
         }
-        Command::Fix => {
-            eprintln!("error: `fix` is not yet implemented");
+        Command::Tree {
+            duplicates: true, ..
+        } => bail!(
+            "`--duplicates` compares the members of a workspace, but this package is not in one"
+        ),
+        Command::Tree { .. } => {
+            gctx.shell.error("`tree` is not yet implemented");
             std::process::exit(1);
         }
-        Command::Doc => {
-            eprintln!("error: `doc` is not yet implemented");
+        Command::Fmt { check } => commands::fmt::exec(gctx, check),
+        Command::Lint => commands::lint::exec(gctx),
+        Command::Fix {
+            imports: false,
+            license_header: false,
+        } => {
+            gctx.shell.error("fixing package declarations is not yet implemented; `fix --imports` organizes imports and `fix --license-header` inserts license headers");
             std::process::exit(1);
         }
+        Command::Fix {
+            imports,
+            license_header,
+        } => commands::fix::exec(gctx, imports, license_header),
+        Command::Doc {
+            open,
+            deny_warnings,
+        } => commands::doc::exec(gctx, open, deny_warnings),
+        Command::Deprecations => commands::deprecations::exec(gctx),
+        Command::Licenses => commands::licenses::exec(gctx),
+        Command::ApiCheck { baseline } => commands::api_check::exec(gctx, baseline.as_deref()),
+        Command::Pom => commands::pom::exec(gctx),
+        Command::Install {
+            coordinate,
+            path,
+            to_m2,
+            ..
+        } => match path {
+            Some(path) => commands::install::exec(&gctx.with_cwd(gctx.cwd.join(path)), None, to_m2),
+            None => commands::install::exec(gctx, coordinate.as_deref(), to_m2),
+        },
+        Command::Uninstall { name } => commands::uninstall::exec(gctx, &name),
+        Command::Publish {
+            repo,
+            check,
+            auto_release,
+            ..
+        } => commands::publish::exec(gctx, repo.as_deref(), check, auto_release),
+        Command::Release { bump, skip } => commands::release::exec(gctx, &bump, &skip),
+        Command::Version {
+            bump, commit, tag, ..
+        } => commands::version::exec(gctx, &bump, commit, tag),
+        Command::Deps { action } => commands::deps::exec(gctx, action),
+        Command::Graph { action } => commands::graph::exec(gctx, action),
+        Command::Ide { action } => commands::ide::exec(gctx, action),
+        Command::Daemon { action } => commands::daemon::exec(gctx, action),
+        Command::Env => commands::env::exec(gctx),
+        Command::Config { action } => commands::config::exec(gctx, action),
+        Command::RunTask { name, args } => commands::task::exec(gctx, name.as_deref(), &args),
+        Command::Task(mut argv) => {
+            let name = argv.remove(0);
+            commands::task::exec(gctx, Some(&name), &argv)
+        }
     }
 }
@@ -0,0 +1,153 @@
+use std::io;
+use std::process::{Command, ExitStatus};
+
+/// Run `cmd` to completion like [`Command::status`], but keep the child tied
+/// to this process on Unix: SIGINT and SIGTERM sent to `jargo` are forwarded
+/// to it (and `jargo` waits for it to exit instead of dying first), and on
+/// Linux the child gets SIGTERM if `jargo` is killed outright.
+pub fn run_foreground(cmd: &mut Command) -> io::Result<ExitStatus> {
+    #[cfg(unix)]
+    {
+        let _forwarding = signals::Forwarding::install();
+        #[cfg(target_os = "linux")]
+        signals::terminate_with_parent(cmd);
+        let mut child = cmd.spawn()?;
+        signals::Forwarding::set_child(child.id());
+        child.wait()
+    }
+    #[cfg(not(unix))]
+    cmd.status()
+}
+
+/// The exit code to leave with after a child exited with `status`: its own
+/// code, or `128 + signal` when a signal ended it, as shells report it.
+pub fn exit_code(status: ExitStatus) -> i32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
+        }
+    }
+    status.code().unwrap_or(1)
+}
+
+#[cfg(unix)]
+mod signals {
+    use std::process::Command;
+    use std::sync::atomic::{AtomicI32, Ordering};
+
+    /// Pid of the foreground child, or 0 before it is spawned.
+    static CHILD: AtomicI32 = AtomicI32::new(0);
+    /// Signal that arrived before the child was spawned.
+    static PENDING: AtomicI32 = AtomicI32::new(0);
+
+    const FORWARDED: [libc::c_int; 2] = [libc::SIGINT, libc::SIGTERM];
+
+    extern "C" fn forward(signal: libc::c_int) {
+        match CHILD.load(Ordering::SeqCst) {
+            0 => PENDING.store(signal, Ordering::SeqCst),
+            // SAFETY: kill is async-signal-safe.
+            pid => unsafe {
+                libc::kill(pid, signal);
+            },
+        }
+    }
+
+    /// Forwards [`FORWARDED`] signals to the child while alive, restoring the
+    /// previous handlers when dropped.
+    pub struct Forwarding {
+        previous: Vec<(libc::c_int, libc::sighandler_t)>,
+    }
+
+    impl Forwarding {
+        pub fn install() -> Self {
+            CHILD.store(0, Ordering::SeqCst);
+            PENDING.store(0, Ordering::SeqCst);
+            let handler = forward as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            let previous = FORWARDED
+                .iter()
+                // SAFETY: the handler only touches atomics and calls kill.
+                .map(|&signal| (signal, unsafe { libc::signal(signal, handler) }))
+                .collect();
+            Self { previous }
+        }
+
+        pub fn set_child(pid: u32) {
+            let pid = pid as i32;
+            CHILD.store(pid, Ordering::SeqCst);
+            let pending = PENDING.swap(0, Ordering::SeqCst);
+            if pending != 0 {
+                // SAFETY: plain syscall on our own, not yet reaped, child.
+                unsafe {
+                    libc::kill(pid, pending);
+                }
+            }
+        }
+    }
+
+    impl Drop for Forwarding {
+        fn drop(&mut self) {
+            for &(signal, handler) in &self.previous {
+                // SAFETY: restores the handler that was installed before.
+                unsafe {
+                    libc::signal(signal, handler);
+                }
+            }
+            CHILD.store(0, Ordering::SeqCst);
+        }
+    }
+
+    /// Have the kernel send SIGTERM to the child when `jargo` dies, so even a
+    /// SIGKILL does not leave an orphaned JVM behind.
+    #[cfg(target_os = "linux")]
+    pub fn terminate_with_parent(cmd: &mut Command) {
+        use std::os::unix::process::CommandExt;
+        // SAFETY: getpid is async-signal-safe.
+        let parent = unsafe { libc::getpid() };
+        // SAFETY: the closure only makes async-signal-safe syscalls.
+        unsafe {
+            cmd.pre_exec(move || {
+                libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGTERM);
+                // The parent may have died before prctl took effect
+                if libc::getppid() != parent {
+                    libc::raise(libc::SIGTERM);
+                }
+                Ok(())
+            });
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_exit_code() {
+        let status = Command::new("sh").args(["-c", "exit 3"]).status().unwrap();
+        assert_eq!(exit_code(status), 3);
+        let status = Command::new("sh")
+            .args(["-c", "kill -9 $$"])
+            .status()
+            .unwrap();
+        assert_eq!(exit_code(status), 128 + 9);
+    }
+
+    #[test]
+    fn test_forwards_sigterm() {
+        std::thread::spawn(|| {
+            std::thread::sleep(Duration::from_millis(500));
+            // SAFETY: the handler installed by run_foreground forwards it
+            unsafe {
+                libc::kill(libc::getpid(), libc::SIGTERM);
+            }
+        });
+        let status = run_foreground(
+            Command::new("sh").args(["-c", "trap 'exit 7' TERM; while true; do sleep 0.1; done"]),
+        )
+        .unwrap();
+        assert_eq!(exit_code(status), 7);
+    }
+}
@@ -1,3 +1,7 @@
+// The original tests pass argument arrays by reference
+#![allow(clippy::needless_borrows_for_generic_args)]
+
+use jargo_core::jargo_test_support::{FakeRepo, FixtureDep, ProjectBuilder, WorkspaceBuilder};
 use std::process::Command;
 use tempfile::TempDir;
 
@@ -41,6 +45,113 @@ fn test_build_simple_app() {
     assert!(stdout.contains("Finished JAR at"));
 }
 
+#[test]
+fn test_build_reports_diagnostics() {
+    let temp = TempDir::new().unwrap();
+    let project_path = temp.path().join("test-app");
+
+    Command::new(jargo_bin())
+        .args(["new", "test-app"])
+        .current_dir(temp.path())
+        .output()
+        .unwrap();
+
+    std::fs::write(
+        project_path.join("src/Main.java"),
+        "package test_app;\n\npublic class Main {\n    public static void main(String[] args) {\n        missing();\n    }\n}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(jargo_bin())
+        .arg("build")
+        .current_dir(&project_path)
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success(), "jargo build should fail");
+
+    // Cargo-style diagnostic with a project-relative location and caret
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("error: cannot find symbol"), "{}", stderr);
+    assert!(stderr.contains(" --> src/Main.java:5:9"), "{}", stderr);
+    assert!(stderr.contains("= symbol:"), "{}", stderr);
+}
+
+#[test]
+fn test_build_message_format_json() {
+    let temp = TempDir::new().unwrap();
+    let project_path = temp.path().join("test-app");
+
+    Command::new(jargo_bin())
+        .args(["new", "test-app"])
+        .current_dir(temp.path())
+        .output()
+        .unwrap();
+
+    let output = Command::new(jargo_bin())
+        .args(["build", "--message-format", "json"])
+        .current_dir(&project_path)
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "jargo build failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // stdout carries only JSON messages; status lines move to stderr
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert!(lines.iter().all(|l| l.starts_with('{')), "{}", stdout);
+    assert!(stdout.contains(r#""reason":"compiler-artifact""#));
+    assert!(stdout.contains(r#""kind":"jar""#));
+    assert_eq!(
+        lines.last().copied(),
+        Some(r#"{"reason":"build-finished","success":true}"#)
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Compiling test-app"));
+}
+
+#[test]
+fn test_check_message_format_json() {
+    let temp = TempDir::new().unwrap();
+    let project_path = temp.path().join("test-app");
+    std::fs::create_dir_all(project_path.join("src")).unwrap();
+    std::fs::write(
+        project_path.join("Jargo.toml"),
+        "[package]\nname = \"test-app\"\nversion = \"0.1.0\"\njava = \"17\"\n",
+    )
+    .unwrap();
+    std::fs::write(
+        project_path.join("src/Main.java"),
+        "package test_app;\n\npublic class Main {\n    public static void main(String[] args) {\n        foo();\n    }\n}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(jargo_bin())
+        .args(["check", "--message-format", "json"])
+        .current_dir(&project_path)
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success(), "check with errors succeeded");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert!(lines.iter().all(|l| l.starts_with('{')), "{}", stdout);
+    assert!(
+        stdout.contains(r#""reason":"compiler-message""#),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains(r#""file":"src/Main.java""#), "{}", stdout);
+    assert_eq!(
+        lines.last().copied(),
+        Some(r#"{"reason":"build-finished","success":false}"#)
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Checking test-app"));
+}
+
 #[test]
 fn test_jar_is_runnable() {
     let temp = TempDir::new().unwrap();
@@ -231,6 +342,353 @@ fn test_run_simple_app() {
     assert!(stdout.contains("Hello, World!"));
 }
 
+#[test]
+fn test_run_with_runtime_dependencies() {
+    let repo = FakeRepo::start();
+    repo.publish_pom(
+        "com.acme:widget:1.0",
+        &[FixtureDep::runtime("com.acme:gizmo:1.0")],
+        &["com/acme/Widget"],
+        17,
+    );
+    repo.publish_pom("com.acme:gizmo:1.0", &[], &["com/acme/Gizmo"], 17);
+
+    let project = ProjectBuilder::app("launch")
+        .dependency("com.acme:widget", "1.0")
+        .repository(&repo)
+        .file(
+            "src/Main.java",
+            concat!(
+                "package launch;\n",
+                "\n",
+                "public class Main {\n",
+                "    public static void main(String[] args) throws Exception {\n",
+                "        System.out.println(com.acme.Widget.class.getName());\n",
+                "        // Runtime-scope only: not on the compile classpath\n",
+                "        System.out.println(Class.forName(\"com.acme.Gizmo\").getName());\n",
+                "    }\n",
+                "}\n"
+            ),
+        )
+        .build();
+
+    let output = project.jargo(jargo_bin()).arg("run").output().unwrap();
+    assert!(
+        output.status.success(),
+        "jargo run failed:\nstdout: {}\nstderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("com.acme.Widget\ncom.acme.Gizmo\n"),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn test_run_bin_targets() {
+    let project = ProjectBuilder::app("tools")
+        .manifest(concat!(
+            "[[bin]]\nname = \"server\"\nmain-class = \"Main\"\n\n",
+            "[[bin]]\nname = \"migrate\"\nmain-class = \"Migrate\"\n"
+        ))
+        .file(
+            "src/Migrate.java",
+            concat!(
+                "package tools;\n",
+                "\n",
+                "public class Migrate {\n",
+                "    public static void main(String[] args) {\n",
+                "        System.out.println(\"migrating \" + String.join(\",\", args));\n",
+                "    }\n",
+                "}\n"
+            ),
+        )
+        .build();
+
+    let output = project.jargo(jargo_bin()).arg("run").output().unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("pass `--bin` with one of: server, migrate"),
+        "{}",
+        stderr
+    );
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["run", "--bin", "migrate", "--", "v2"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "jargo run --bin failed:\nstdout: {}\nstderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("migrating v2\n"), "{}", stdout);
+}
+
+#[test]
+fn test_run_example_in_lib() {
+    let project = ProjectBuilder::lib("greeter")
+        .file(
+            "examples/Quickstart.java",
+            concat!(
+                "package greeter;\n",
+                "\n",
+                "public class Quickstart {\n",
+                "    public static void main(String[] args) {\n",
+                "        System.out.println(greeter.support.Banner.wrap(Lib.greeting()));\n",
+                "    }\n",
+                "}\n"
+            ),
+        )
+        .file(
+            "examples/support/Banner.java",
+            concat!(
+                "package greeter.support;\n",
+                "\n",
+                "public class Banner {\n",
+                "    public static String wrap(String text) {\n",
+                "        return \"** \" + text + \" **\";\n",
+                "    }\n",
+                "}\n"
+            ),
+        )
+        .build();
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["run", "--example", "Banner"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("available: Quickstart"), "{}", stderr);
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["run", "--example", "Quickstart"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "jargo run --example failed:\nstdout: {}\nstderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("** Hello from greeter! **\n"), "{}", stdout);
+    assert!(project
+        .path("target/example-classes/greeter/Quickstart.class")
+        .exists());
+}
+
+#[test]
+fn test_run_with_debug_agent() {
+    let project = ProjectBuilder::app("debuggee").build();
+
+    // Port 0 lets the JDWP agent pick a free port
+    let output = project
+        .jargo(jargo_bin())
+        .args(["run", "--debug=0"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "jargo run --debug failed:\nstdout: {}\nstderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("JDWP listening on 127.0.0.1:0"),
+        "{}",
+        stdout
+    );
+    assert!(
+        stdout.contains("Listening for transport dt_socket at address:"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("Hello, World!"), "{}", stdout);
+}
+
+#[test]
+fn test_run_with_env_and_dotenv() {
+    let project = ProjectBuilder::app("envy")
+        .manifest("[run]\ndotenv = true\n\n[env]\nJARGO_IT_MODE = \"manifest\"\n")
+        .file(".env", "JARGO_IT_MODE=dotenv\nJARGO_IT_SECRET='s3cret'\n")
+        .file(
+            "src/Main.java",
+            concat!(
+                "package envy;\n",
+                "\n",
+                "public class Main {\n",
+                "    public static void main(String[] args) {\n",
+                "        System.out.println(System.getenv(\"JARGO_IT_MODE\"));\n",
+                "        System.out.println(System.getenv(\"JARGO_IT_SECRET\"));\n",
+                "    }\n",
+                "}\n"
+            ),
+        )
+        .build();
+
+    let output = project.jargo(jargo_bin()).arg("run").output().unwrap();
+    assert!(
+        output.status.success(),
+        "jargo run failed:\nstdout: {}\nstderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("manifest\ns3cret\n"), "{}", stdout);
+
+    // The caller's environment wins
+    let output = project
+        .jargo(jargo_bin())
+        .arg("run")
+        .env("JARGO_IT_MODE", "shell")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("shell\ns3cret\n"), "{}", stdout);
+}
+
+#[test]
+fn test_run_with_system_properties() {
+    let project = ProjectBuilder::app("props")
+        .manifest(concat!(
+            "[run]\n",
+            "jvm-args = [\"-Dapp.mode=from-jvm-args\"]\n",
+            "system-properties = { \"app.mode\" = \"dev\", \"config.path\" = \"conf/dev.yaml\" }\n",
+            "\n",
+            "[run.profiles.prod]\n",
+            "system-properties = { \"app.mode\" = \"prod\" }\n"
+        ))
+        .file(
+            "src/Main.java",
+            concat!(
+                "package props;\n",
+                "\n",
+                "public class Main {\n",
+                "    public static void main(String[] args) {\n",
+                "        System.out.println(System.getProperty(\"app.mode\"));\n",
+                "        System.out.println(System.getProperty(\"config.path\"));\n",
+                "    }\n",
+                "}\n"
+            ),
+        )
+        .build();
+
+    let output = project.jargo(jargo_bin()).arg("run").output().unwrap();
+    assert!(
+        output.status.success(),
+        "jargo run failed:\nstdout: {}\nstderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("dev\nconf/dev.yaml\n"), "{}", stdout);
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["run", "--sysprop", "config.path=conf/prod.yaml"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("dev\nconf/prod.yaml\n"), "{}", stdout);
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["run", "--profile", "prod"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("prod\nconf/dev.yaml\n"), "{}", stdout);
+}
+
+#[test]
+fn test_exec_main_class_in_lib() {
+    let project = ProjectBuilder::lib("greeter")
+        .file(
+            "src/tools/Migrate.java",
+            concat!(
+                "package greeter.tools;\n",
+                "\n",
+                "public class Migrate {\n",
+                "    public static void main(String[] args) {\n",
+                "        System.out.println(greeter.Lib.greeting() + \" \" + String.join(\" \", args));\n",
+                "    }\n",
+                "}\n"
+            ),
+        )
+        .build();
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["exec", "greeter.tools.Migrate", "--", "--target", "v2"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "jargo exec failed:\nstdout: {}\nstderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Hello from greeter! --target v2\n"),
+        "{}",
+        stdout
+    );
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["exec", "greeter.Missing"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_repl_with_project_classpath() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let project = ProjectBuilder::lib("greeter")
+        .manifest("[repl]\nimports = [\"greeter.*\"]\n")
+        .build();
+
+    let mut child = project
+        .jargo(jargo_bin())
+        .arg("repl")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"System.out.println(Lib.greeting());\n/exit\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(
+        output.status.success(),
+        "jargo repl failed:\nstdout: {}\nstderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Hello from greeter!"), "{}", stdout);
+}
+
 #[test]
 fn test_run_lib_project_fails() {
     let temp = TempDir::new().unwrap();
@@ -405,6 +863,55 @@ fn test_manifest_not_found_error() {
     assert!(stderr.contains("Jargo.toml not found"));
 }
 
+#[cfg(unix)]
+#[test]
+fn test_task_alias() {
+    let temp = TempDir::new().unwrap();
+    let project_path = temp.path().join("test-app");
+
+    Command::new(jargo_bin())
+        .args(["new", "test-app"])
+        .current_dir(temp.path())
+        .output()
+        .unwrap();
+
+    let manifest = project_path.join("Jargo.toml");
+    let mut content = std::fs::read_to_string(&manifest).unwrap();
+    content.push_str("\n[tasks]\ngreet = \"echo hello\"\nnested = \"jargo greet from-nested\"\n");
+    std::fs::write(&manifest, content).unwrap();
+
+    // `jargo <alias>` with appended args, including a task calling jargo itself
+    let output = Command::new(jargo_bin())
+        .args(["nested", "--loud"])
+        .current_dir(&project_path)
+        .env("PATH", "/usr/bin:/bin")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "task failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("hello from-nested --loud"), "{stdout}");
+
+    // Listing, and unknown names
+    let output = Command::new(jargo_bin())
+        .arg("run-task")
+        .current_dir(&project_path)
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("greet"));
+
+    let output = Command::new(jargo_bin())
+        .arg("nope")
+        .current_dir(&project_path)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("no such command"));
+}
+
 /// Phase 2: build a project that depends on jackson-databind, which uses parent POMs,
 /// `${project.version}` substitution, and `<dependencyManagement>` sections extensively.
 ///
@@ -497,3 +1004,2544 @@ fn test_build_with_parent_pom_dependency() {
         "expected JSON output, got: {stdout}"
     );
 }
+
+#[test]
+fn test_build_keep_going_writes_classes_for_sources_that_compile() {
+    let temp = TempDir::new().unwrap();
+    let project_path = temp.path().join("partial");
+    std::fs::create_dir_all(project_path.join("src/util")).unwrap();
+    std::fs::write(
+        project_path.join("Jargo.toml"),
+        "[package]\nname = \"partial\"\nversion = \"0.1.0\"\njava = \"17\"\n",
+    )
+    .unwrap();
+    let sources = [
+        (
+            "src/Main.java",
+            "package partial;\n\npublic class Main {\n    public static void main(String[] args) {\n        System.out.println(partial.util.Strings.shout(\"hi\"));\n    }\n}\n",
+        ),
+        (
+            "src/util/Strings.java",
+            "package partial.util;\n\npublic class Strings {\n    public static String shout(String s) {\n        return s.toUpperCase();\n    }\n}\n",
+        ),
+        (
+            "src/Broken.java",
+            "package partial;\n\npublic class Broken {\n    int value() {\n        return missing();\n    }\n}\n",
+        ),
+        (
+            "src/UsesBroken.java",
+            "package partial;\n\npublic class UsesBroken {\n    int value() {\n        return new Broken().value();\n    }\n}\n",
+        ),
+    ];
+    for (path, content) in sources {
+        std::fs::write(project_path.join(path), content).unwrap();
+    }
+
+    let output = Command::new(jargo_bin())
+        .args(["build", "--keep-going"])
+        .current_dir(&project_path)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success(), "build with errors succeeded");
+    assert!(stderr.contains("cannot find symbol"), "{}", stderr);
+
+    // Sources that compile get class files; a broken source and the ones
+    // that depend on it do not
+    let classes = project_path.join("target/classes/partial");
+    assert!(classes.join("Main.class").exists());
+    assert!(classes.join("util/Strings.class").exists());
+    assert!(!classes.join("Broken.class").exists());
+    assert!(!classes.join("UsesBroken.class").exists());
+    assert!(
+        stderr.contains("src/Broken.java was not compiled"),
+        "{}",
+        stderr
+    );
+    assert!(
+        stderr.contains("src/UsesBroken.java was not compiled"),
+        "{}",
+        stderr
+    );
+    assert!(!stderr.contains("src/Main.java was not compiled"));
+}
+
+/// Offline counterpart of `test_build_with_dependency`: resolves a dependency
+/// and its Gradle-module transitive from a local fake repository.
+#[test]
+fn test_build_with_fake_repository() {
+    let repo = FakeRepo::start();
+    repo.publish_module(
+        "com.acme:widget:1.0",
+        &[FixtureDep::compile("com.acme:core:2.0")],
+        &["com/acme/Widget"],
+        17,
+    );
+    repo.publish_pom("com.acme:core:2.0", &[], &["com/acme/Core"], 17);
+
+    let project = ProjectBuilder::app("fake-repo")
+        .dependency("com.acme:widget", "1.0")
+        .repository(&repo)
+        .file(
+            "src/Main.java",
+            concat!(
+                "package fakerepo;\n",
+                "\n",
+                "public class Main {\n",
+                "    public static void main(String[] args) {\n",
+                "        System.out.println(com.acme.Widget.class.getName());\n",
+                "        System.out.println(com.acme.Core.class.getName());\n",
+                "    }\n",
+                "}\n"
+            ),
+        )
+        .build();
+
+    let output = project.jargo(jargo_bin()).arg("run").output().unwrap();
+    assert!(
+        output.status.success(),
+        "jargo run failed:\nstdout: {}\nstderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("com.acme.Widget"), "{stdout}");
+    assert!(stdout.contains("com.acme.Core"), "{stdout}");
+
+    let lock = project.read("Jargo.lock");
+    assert!(lock.contains("widget") && lock.contains("core"), "{lock}");
+    assert!(repo
+        .requests()
+        .contains(&"/com/acme/widget/1.0/widget-1.0.module".to_string()));
+}
+
+#[test]
+fn test_build_uber_jar_with_relocation() {
+    let repo = FakeRepo::start();
+    repo.publish_pom("com.acme:widget:1.0", &[], &["com/acme/Widget"], 17);
+
+    let project = ProjectBuilder::app("shaded")
+        .dependency("com.acme:widget", "1.0")
+        .repository(&repo)
+        .manifest("[shade.relocate]\n\"com.acme\" = \"shaded.deps.acme\"\n")
+        .file(
+            "src/Main.java",
+            concat!(
+                "package shaded;\n",
+                "\n",
+                "public class Main {\n",
+                "    public static void main(String[] args) throws Exception {\n",
+                "        System.out.println(com.acme.Widget.class.getName());\n",
+                "        System.out.println(Class.forName(\"com.acme.Widget\").getName());\n",
+                "    }\n",
+                "}\n"
+            ),
+        )
+        .build();
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["build", "--uber"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "jargo build --uber failed:\nstdout: {}\nstderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // The dependency is bundled and relocated, so the JAR runs on its own
+    let output = Command::new("java")
+        .args(["-jar", "target/shaded.jar"])
+        .current_dir(project.root())
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "java -jar failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout.lines().collect::<Vec<_>>(),
+        ["shaded.deps.acme.Widget", "shaded.deps.acme.Widget"]
+    );
+}
+
+#[test]
+fn test_build_thin_jar_with_lib_dir() {
+    let repo = FakeRepo::start();
+    repo.publish_pom("com.acme:widget:1.0", &[], &["com/acme/Widget"], 17);
+
+    let project = ProjectBuilder::app("thin")
+        .dependency("com.acme:widget", "1.0")
+        .repository(&repo)
+        .file(
+            "src/Main.java",
+            concat!(
+                "package thin;\n",
+                "\n",
+                "public class Main {\n",
+                "    public static void main(String[] args) {\n",
+                "        System.out.println(com.acme.Widget.class.getName());\n",
+                "    }\n",
+                "}\n"
+            ),
+        )
+        .build();
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["build", "--thin"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "jargo build --thin failed:\nstdout: {}\nstderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(project.root().join("target/lib/widget-1.0.jar").exists());
+
+    // The JAR finds the dependency through its Class-Path header
+    let output = Command::new("java")
+        .args(["-jar", "target/thin.jar"])
+        .current_dir(project.root())
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "java -jar failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim(),
+        "com.acme.Widget"
+    );
+}
+
+#[test]
+fn test_jlink_image_runs() {
+    let project = ProjectBuilder::app("linked")
+        .manifest("[jlink]\nlauncher = \"linked-cli\"\n")
+        .file(
+            "src/Main.java",
+            concat!(
+                "package linked;\n",
+                "\n",
+                "public class Main {\n",
+                "    public static void main(String[] args) {\n",
+                "        System.out.println(java.sql.Types.class.getModule().getName());\n",
+                "    }\n",
+                "}\n"
+            ),
+        )
+        .build();
+
+    let output = project.jargo(jargo_bin()).arg("jlink").output().unwrap();
+    assert!(
+        output.status.success(),
+        "jargo jlink failed:\nstdout: {}\nstderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let image = project.root().join("target/image");
+    let modules = std::fs::read_to_string(image.join("release")).unwrap();
+    assert!(modules.contains("java.sql"), "{modules}");
+    assert!(!modules.contains("java.desktop"), "{modules}");
+
+    let output = Command::new(image.join("bin/linked-cli")).output().unwrap();
+    assert!(
+        output.status.success(),
+        "launcher failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "java.sql");
+}
+
+#[test]
+fn test_package_app_image() {
+    let project = ProjectBuilder::app("packaged")
+        .manifest("[jpackage]\nvendor = \"Acme\"\n")
+        .build();
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["package", "--type", "app-image"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "jargo package failed:\nstdout: {}\nstderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let launcher = project.root().join(if cfg!(target_os = "macos") {
+        "target/installer/packaged.app/Contents/MacOS/packaged"
+    } else if cfg!(windows) {
+        "target/installer/packaged/packaged.exe"
+    } else {
+        "target/installer/packaged/bin/packaged"
+    });
+    let output = Command::new(launcher).output().unwrap();
+    assert!(
+        output.status.success(),
+        "launcher failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim(),
+        "Hello, World!"
+    );
+}
+
+/// Verifies `jargo native` end to end.
+/// Requires a GraalVM JDK with native-image on JAVA_HOME. Run with:
+///   cargo test -- --include-ignored
+#[test]
+#[ignore]
+fn test_native_binary_runs() {
+    let project = ProjectBuilder::app("nat").build();
+
+    let output = project.jargo(jargo_bin()).arg("native").output().unwrap();
+    assert!(
+        output.status.success(),
+        "jargo native failed:\nstdout: {}\nstderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let output = Command::new(project.root().join("target/native/nat"))
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim(),
+        "Hello, World!"
+    );
+}
+
+#[test]
+fn test_image_tarball_from_scratch() {
+    let project = ProjectBuilder::app("boxed")
+        .manifest("[container]\nbase = \"scratch\"\nimage = \"acme/boxed:1.0\"\n")
+        .file("resources/app.properties", "mode=prod\n")
+        .build();
+
+    let output = project.jargo(jargo_bin()).arg("image").output().unwrap();
+    assert!(
+        output.status.success(),
+        "jargo image failed:\nstdout: {}\nstderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // Metadata entries are stored uncompressed in the tarball
+    let tarball = std::fs::read(project.root().join("target/container/boxed.tar")).unwrap();
+    let contents = String::from_utf8_lossy(&tarball);
+    assert!(contents.contains("oci-layout"));
+    assert!(contents.contains(r#""RepoTags":["acme/boxed:1.0"]"#));
+    assert!(contents.contains(r#""created_by":"jargo: resources""#));
+    assert!(contents.contains(r#""created_by":"jargo: classes""#));
+    assert!(contents.contains(
+        r#""Entrypoint":["java","-cp","/app/resources:/app/classes:/app/libs/*","boxed.Main"]"#
+    ));
+}
+
+#[test]
+fn test_build_uber_jar_minimized() {
+    let repo = FakeRepo::start();
+    repo.publish_pom(
+        "com.acme:widget:1.0",
+        &[],
+        &["com/acme/Widget", "com/acme/Gadget", "com/acme/Plugin"],
+        17,
+    );
+
+    let project = ProjectBuilder::app("slim")
+        .dependency("com.acme:widget", "1.0")
+        .repository(&repo)
+        .manifest("[shade]\nminimize = true\nkeep = [\"com.acme.Plugin\"]\n")
+        .file(
+            "src/Main.java",
+            concat!(
+                "package slim;\n",
+                "\n",
+                "public class Main {\n",
+                "    public static void main(String[] args) {\n",
+                "        System.out.println(com.acme.Widget.class.getName());\n",
+                "        // Names built at runtime are invisible to minimization\n",
+                "        for (String name : new String[] {\"Plugin\", \"Gadget\"}) {\n",
+                "            try {\n",
+                "                Class.forName(String.join(\".\", \"com\", \"acme\", name));\n",
+                "                System.out.println(name + \" kept\");\n",
+                "            } catch (ClassNotFoundException e) {\n",
+                "                System.out.println(name + \" dropped\");\n",
+                "            }\n",
+                "        }\n",
+                "    }\n",
+                "}\n"
+            ),
+        )
+        .build();
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["build", "--uber"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "jargo build --uber failed:\nstdout: {}\nstderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("1 unused dependency classes dropped"));
+
+    let output = Command::new("java")
+        .args(["-jar", "target/slim.jar"])
+        .current_dir(project.root())
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "java -jar failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout.lines().collect::<Vec<_>>(),
+        ["com.acme.Widget", "Plugin kept", "Gadget dropped"]
+    );
+}
+
+#[test]
+fn test_third_party_notices() {
+    let repo = FakeRepo::start();
+    repo.publish_pom("com.acme:widget:1.0", &[], &["com/acme/Widget"], 17);
+    repo.put(
+        "com/acme/widget/1.0/widget-1.0.pom",
+        concat!(
+            "<project>\n",
+            "  <groupId>com.acme</groupId>\n",
+            "  <artifactId>widget</artifactId>\n",
+            "  <version>1.0</version>\n",
+            "  <licenses>\n",
+            "    <license>\n",
+            "      <name>Apache License, Version 2.0</name>\n",
+            "      <url>https://www.apache.org/licenses/LICENSE-2.0.txt</url>\n",
+            "    </license>\n",
+            "  </licenses>\n",
+            "</project>\n"
+        ),
+    );
+
+    let project = ProjectBuilder::app("noticed")
+        .dependency("com.acme:widget", "1.0")
+        .repository(&repo)
+        .file(
+            "src/Main.java",
+            concat!(
+                "package noticed;\n",
+                "\n",
+                "public class Main {\n",
+                "    public static void main(String[] args) throws Exception {\n",
+                "        var notices = Main.class.getResourceAsStream(\"/META-INF/THIRD-PARTY.txt\");\n",
+                "        System.out.print(new String(notices.readAllBytes()));\n",
+                "    }\n",
+                "}\n"
+            ),
+        )
+        .build();
+    let expected = "\ncom.acme:widget:1.0\n    License: Apache License, Version 2.0 <https://www.apache.org/licenses/LICENSE-2.0.txt>\n";
+
+    let output = project.jargo(jargo_bin()).arg("dist").output().unwrap();
+    assert!(
+        output.status.success(),
+        "jargo dist failed:\nstdout: {}\nstderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let notices = project.read("target/dist/noticed/THIRD-PARTY.txt");
+    assert!(notices.starts_with("Third-party software bundled with noticed"));
+    assert!(notices.ends_with(expected), "{}", notices);
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["build", "--uber"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "jargo build --uber failed:\nstdout: {}\nstderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let output = Command::new("java")
+        .args(["-jar", "target/noticed.jar"])
+        .current_dir(project.root())
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "java -jar failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).ends_with(expected));
+}
+
+/// A workspace where `app` calls into the `core` lib member.
+fn two_member_workspace() -> jargo_core::jargo_test_support::TestProject {
+    WorkspaceBuilder::new()
+        .member(ProjectBuilder::lib("core"))
+        .member(ProjectBuilder::app("app").member_dependency("core").file(
+            "src/Main.java",
+            concat!(
+                "package app;\n",
+                "\n",
+                "public class Main {\n",
+                "    public static void main(String[] args) {\n",
+                "        System.out.println(core.Lib.greeting());\n",
+                "    }\n",
+                "}\n"
+            ),
+        ))
+        .build()
+}
+
+#[test]
+fn test_license_allowlist() {
+    let repo = FakeRepo::start();
+    repo.publish_pom("com.acme:widget:1.0", &[], &["com/acme/Widget"], 17);
+    repo.put(
+        "com/acme/widget/1.0/widget-1.0.pom",
+        concat!(
+            "<project>\n",
+            "  <groupId>com.acme</groupId>\n",
+            "  <artifactId>widget</artifactId>\n",
+            "  <version>1.0</version>\n",
+            "  <licenses>\n",
+            "    <license>\n",
+            "      <name>The Apache Software License, Version 2.0</name>\n",
+            "    </license>\n",
+            "  </licenses>\n",
+            "</project>\n"
+        ),
+    );
+    repo.publish_pom("com.acme:gadget:2.0", &[], &["com/acme/Gadget"], 17);
+
+    let project = ProjectBuilder::app("licensed")
+        .java("17")
+        .dependency("com.acme:widget", "1.0")
+        .dependency("com.acme:gadget", "2.0")
+        .manifest("[licenses]\nallow = [\"Apache-2.0\", \"MIT\"]\n")
+        .repository(&repo)
+        .build();
+
+    let output = project.jargo(jargo_bin()).arg("licenses").output().unwrap();
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Apache-2.0 (1)\n    com.acme:widget:1.0\n"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("(not declared) (1)\n    com.acme:gadget:2.0\n"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("  com.acme:gadget:2.0 declares no license in its POM"),
+        "{stdout}"
+    );
+
+    let output = project.jargo(jargo_bin()).arg("build").output().unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("1 dependency license(s) not allowed by [licenses] allow"));
+    assert!(!project.path("target/licensed.jar").exists());
+}
+
+#[test]
+fn test_workspace_build_order_and_fresh_members() {
+    let workspace = two_member_workspace();
+
+    let output = workspace.jargo(jargo_bin()).arg("build").output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "jargo build failed:\nstdout: {}\nstderr: {}",
+        stdout,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let core = stdout
+        .find("Compiling core")
+        .expect("core was not compiled");
+    let app = stdout.find("Compiling app").expect("app was not compiled");
+    assert!(core < app, "core must build before app: {stdout}");
+    assert!(workspace.path("target/core/core.jar").exists());
+    assert!(workspace.path("target/app/app.jar").exists());
+
+    // Nothing changed, so both members are skipped
+    let output = workspace.jargo(jargo_bin()).arg("build").output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Fresh core"), "{stdout}");
+    assert!(stdout.contains("Fresh app"), "{stdout}");
+    assert!(!stdout.contains("Compiling"), "{stdout}");
+
+    // A change in core rebuilds it and everything depending on it
+    workspace.write(
+        "core/src/Lib.java",
+        "package core;\n\npublic class Lib {\n    public static String greeting() {\n        return \"Changed!\";\n    }\n}\n",
+    );
+    let output = workspace.jargo(jargo_bin()).arg("build").output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Compiling core"), "{stdout}");
+    assert!(stdout.contains("Compiling app"), "{stdout}");
+
+    let output = workspace
+        .jargo(jargo_bin())
+        .args(["run", "-p", "app"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "jargo run -p app failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Changed!"));
+}
+
+#[test]
+fn test_workspace_package_selection() {
+    let workspace = two_member_workspace();
+
+    // -p builds the selected member only
+    let output = workspace
+        .jargo(jargo_bin())
+        .args(["build", "-p", "core"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Compiling core"), "{stdout}");
+    assert!(!stdout.contains("app"), "{stdout}");
+    assert!(!workspace.path("target/app/app.jar").exists());
+
+    // ... after the members it depends on
+    let output = workspace
+        .jargo(jargo_bin())
+        .args(["build", "-p", "app"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Fresh core"), "{stdout}");
+    assert!(stdout.contains("Compiling app"), "{stdout}");
+
+    // Inside a member, the current member is the default and --workspace
+    // selects all of them
+    let output = workspace
+        .jargo(jargo_bin())
+        .arg("clean")
+        .current_dir(workspace.path("core"))
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(!workspace.path("target/core/core.jar").exists());
+    assert!(workspace.path("target/app/app.jar").exists());
+
+    let output = workspace
+        .jargo(jargo_bin())
+        .args(["build", "--workspace"])
+        .current_dir(workspace.path("core"))
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Compiling core"), "{stdout}");
+    assert!(stdout.contains("app"), "{stdout}");
+    assert!(workspace.path("target/core/core.jar").exists());
+
+    let output = workspace
+        .jargo(jargo_bin())
+        .args(["build", "-p", "missing"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("package `missing` is not a member of the workspace"));
+}
+
+#[test]
+fn test_new_workspace_member() {
+    let temp = TempDir::new().unwrap();
+    let root = temp.path().join("ws");
+
+    let output = Command::new(jargo_bin())
+        .args(["new", "--workspace", "ws"])
+        .current_dir(temp.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "jargo new --workspace failed");
+
+    for args in [
+        &["new", "--member", "core", "--lib"][..],
+        &["new", "--member", "app", "--dep", "core"],
+    ] {
+        let output = Command::new(jargo_bin())
+            .args(args)
+            .current_dir(&root)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "jargo {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    let manifest = std::fs::read_to_string(root.join("Jargo.toml")).unwrap();
+    assert!(
+        manifest.contains("members = [\"core\", \"app\"]"),
+        "{manifest}"
+    );
+    let app = std::fs::read_to_string(root.join("app/Jargo.toml")).unwrap();
+    assert!(app.contains("\"core\" = { workspace = true }"), "{app}");
+    assert!(!root.join("app/.git").exists());
+
+    // A --dep must name an existing member
+    let output = Command::new(jargo_bin())
+        .args(["new", "--member", "cli", "--dep", "missing"])
+        .current_dir(&root)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+
+    let output = Command::new(jargo_bin())
+        .arg("build")
+        .current_dir(&root)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "jargo build failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(root.join("target/app/app.jar").exists());
+}
+
+#[test]
+fn test_tree_duplicates_across_members() {
+    let repo = FakeRepo::start();
+    repo.publish_pom("com.acme:widget:1.0", &[], &["com/acme/Widget"], 17);
+    repo.publish_pom("com.acme:widget:2.0", &[], &["com/acme/Widget"], 17);
+
+    let workspace = WorkspaceBuilder::new()
+        .member(ProjectBuilder::lib("core").dependency("com.acme:widget", "1.0"))
+        .member(
+            ProjectBuilder::app("app")
+                .member_dependency("core")
+                .dependency("com.acme:widget", "2.0"),
+        )
+        .repository(&repo)
+        .build();
+
+    let output = workspace.jargo(jargo_bin()).arg("build").output().unwrap();
+    assert!(
+        output.status.success(),
+        "jargo build failed:\nstdout: {}\nstderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let output = workspace
+        .jargo(jargo_bin())
+        .args(["tree", "--duplicates"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "jargo tree --duplicates failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("com.acme:widget"), "{stdout}");
+    assert!(stdout.contains("1.0: core (direct)"), "{stdout}");
+    assert!(stdout.contains("2.0: app (direct)"), "{stdout}");
+}
+
+#[test]
+fn test_publish_check() {
+    let repo = FakeRepo::start();
+    let project = ProjectBuilder::lib("parser")
+        .java("17")
+        .repository(&repo)
+        .build();
+    let manifest = |version: &str, metadata: &str| {
+        format!(
+            "[package]\nname = \"parser\"\nversion = \"{version}\"\ngroup = \"com.example\"\n\
+             type = \"lib\"\njava = \"17\"\nbase-package = \"parser\"\n{metadata}"
+        )
+    };
+    let check = || {
+        project
+            .jargo(jargo_bin())
+            .args(["publish", "--check"])
+            .output()
+            .unwrap()
+    };
+
+    project.write(
+        "Jargo.toml",
+        manifest("1.0.0-SNAPSHOT", "license = \"MIT\"\n"),
+    );
+    let output = check();
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("version `1.0.0-SNAPSHOT` is a snapshot"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("[package] has no description, repository, authors"),
+        "{stdout}"
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("publish check failed: 2 problem(s)"));
+
+    let metadata = "description = \"Parses things\"\nlicense = \"MIT\"\n\
+                    repository = \"https://github.com/example/parser\"\n\
+                    authors = [\"Jane Doe <jane@example.com>\"]\n";
+    project.write("Jargo.toml", manifest("1.0.0", metadata));
+    let output = check();
+    assert!(
+        output.status.success(),
+        "jargo publish --check failed:\nstdout: {}\nstderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("com.example:parser:1.0.0 is ready to publish"),
+        "{stdout}"
+    );
+    assert!(project.path("target/parser-javadoc.jar").exists());
+
+    repo.publish_pom("com.example:parser:1.0.0", &[], &[], 17);
+    let output = check();
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("`com.example:parser:1.0.0` is already published"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn test_publish_to_repository() {
+    let corp = FakeRepo::start();
+    let project = ProjectBuilder::lib("parser").java("17").build();
+    let manifest = project.read("Jargo.toml").replace(
+        "version = \"0.1.0\"",
+        "version = \"1.0.0\"\ngroup = \"com.example\"",
+    );
+    project.write(
+        "Jargo.toml",
+        format!("{}\n[publish.corp]\nurl = \"{}\"\n", manifest, corp.url()),
+    );
+    let publish = |repo: &str| {
+        project
+            .jargo(jargo_bin())
+            .args(["publish", "--repo", repo])
+            .env("NO_PROXY", "127.0.0.1,localhost")
+            .env("no_proxy", "127.0.0.1,localhost")
+            .output()
+            .unwrap()
+    };
+
+    let output = publish("corp");
+    assert!(
+        output.status.success(),
+        "jargo publish --repo corp failed:\nstdout: {}\nstderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let dir = corp.root().join("com/example/parser/1.0.0");
+    for file in [
+        "parser-1.0.0.jar",
+        "parser-1.0.0.jar.sha1",
+        "parser-1.0.0.pom",
+        "parser-1.0.0.pom.md5",
+        "parser-1.0.0-sources.jar",
+        "parser-1.0.0-javadoc.jar",
+    ] {
+        assert!(dir.join(file).exists(), "{} was not uploaded", file);
+    }
+
+    let output = publish("corp");
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("`com.example:parser:1.0.0` is already published"),
+        "{stdout}"
+    );
+
+    let output = publish("github");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("no `[publish.github]` in Jargo.toml; expected one of corp"));
+}
+
+#[test]
+fn test_release_without_publishing() {
+    let project = ProjectBuilder::lib("parser").java("17").build();
+    let manifest = project.read("Jargo.toml").replace(
+        "version = \"0.1.0\"",
+        "version = \"0.1.0-SNAPSHOT\" # released by jargo release",
+    );
+    project.write("Jargo.toml", manifest);
+    let git = |args: &[&str]| {
+        let output = Command::new("git")
+            .args([
+                "-c",
+                "user.name=jargo",
+                "-c",
+                "user.email=jargo@example.com",
+            ])
+            .args(args)
+            .current_dir(project.root())
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+    git(&["init", "-q"]);
+    git(&["add", "-A"]);
+    git(&["commit", "-q", "-m", "initial"]);
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["release", "--skip", "test", "--skip", "publish"])
+        .env("GIT_AUTHOR_NAME", "jargo")
+        .env("GIT_AUTHOR_EMAIL", "jargo@example.com")
+        .env("GIT_COMMITTER_NAME", "jargo")
+        .env("GIT_COMMITTER_EMAIL", "jargo@example.com")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "jargo release failed:\nstdout: {}\nstderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(project.path("target/parser.jar").exists());
+    assert!(project
+        .read("Jargo.toml")
+        .contains("version = \"0.1.1-SNAPSHOT\" # released by jargo release"));
+    assert_eq!(
+        git(&["log", "--format=%s"]),
+        "Start parser 0.1.1-SNAPSHOT\nRelease parser 0.1.0\ninitial\n"
+    );
+    assert!(git(&["show", "v0.1.0:Jargo.toml"]).contains("version = \"0.1.0\""));
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["release", "--skip", "lint"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown release step `lint`"));
+}
+
+#[test]
+fn test_integration_test_sources_in_it() {
+    let repo = FakeRepo::start();
+    repo.publish_pom("com.acme:fixture:1.0", &[], &["com/acme/Fixture"], 17);
+    // Stands in for the JUnit console launcher: enough to compile against,
+    // not to run tests
+    repo.publish_pom(
+        &format!(
+            "org.junit.platform:junit-platform-console-standalone:{}",
+            jargo_core::test_runner::JUNIT_PLATFORM_VERSION
+        ),
+        &[],
+        &["org/junit/platform/console/ConsoleLauncher"],
+        17,
+    );
+    let project = ProjectBuilder::lib("calc")
+        .java("17")
+        .repository(&repo)
+        .manifest("\n[it-dependencies]\n\"com.acme:fixture\" = \"1.0\"\n")
+        .file(
+            "src/Calc.java",
+            "package calc;\n\npublic class Calc {\n    public static int add(int a, int b) {\n        return a + b;\n    }\n}\n",
+        )
+        .file(
+            "it/db/CalcIT.java",
+            "package calc.db;\n\nclass CalcIT {\n    com.acme.Fixture fixture;\n\n    int sum() {\n        return calc.Calc.add(1, 2);\n    }\n}\n",
+        )
+        .build();
+
+    // Without --integration, it/ is not compiled
+    let output = project.jargo(jargo_bin()).arg("test").output().unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("no test sources found in test/"));
+    assert!(!project.path("target/it-classes").exists());
+
+    // it/ compiles under the base package against the main classes and
+    // [it-dependencies]; the stand-in launcher then fails to run it
+    let output = project
+        .jargo(jargo_bin())
+        .args(["test", "--integration"])
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!stderr.contains("error: "), "{}", stderr);
+    assert!(
+        project
+            .path("target/it-classes/calc/db/CalcIT.class")
+            .is_file(),
+        "{}",
+        stderr
+    );
+
+    // [it-dependencies] stay off the unit test classpath
+    project.write(
+        "test/FixtureTest.java",
+        "package calc;\n\nclass FixtureTest {\n    com.acme.Fixture fixture;\n}\n",
+    );
+    let output = project.jargo(jargo_bin()).arg("test").output().unwrap();
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("package com.acme does not exist"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// Runs JUnit tests, then only the ones affected by an uncommitted change.
+/// Fetches the JUnit console launcher from Maven Central, so it requires
+/// network access. Run with:
+///   cargo test -- --include-ignored
+#[test]
+#[ignore]
+fn test_affected_tests() {
+    let test_class = |name: &str, target: &str| {
+        format!(
+            concat!(
+                "package calc;\n",
+                "\n",
+                "import static org.junit.jupiter.api.Assertions.assertEquals;\n",
+                "\n",
+                "import org.junit.jupiter.api.Test;\n",
+                "\n",
+                "class {} {{\n",
+                "    @Test\n",
+                "    void adds() {{\n",
+                "        assertEquals(3, {}.add(1, 2));\n",
+                "    }}\n",
+                "}}\n"
+            ),
+            name, target
+        )
+    };
+    let source = |name: &str| {
+        format!(
+            "package calc;\n\npublic class {} {{\n    public static int add(int a, int b) {{\n        return a + b;\n    }}\n}}\n",
+            name
+        )
+    };
+    let project = ProjectBuilder::lib("calc")
+        .file("src/Calc.java", source("Calc"))
+        .file("src/Other.java", source("Other"))
+        .file("test/CalcTest.java", test_class("CalcTest", "Calc"))
+        .file("test/OtherTest.java", test_class("OtherTest", "Other"))
+        .build();
+    let git = |args: &[&str]| {
+        let status = Command::new("git")
+            .args([
+                "-c",
+                "user.name=jargo",
+                "-c",
+                "user.email=jargo@example.com",
+            ])
+            .args(args)
+            .current_dir(project.root())
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {:?} failed", args);
+    };
+    git(&["init", "-q"]);
+    git(&["add", "-A"]);
+    git(&["commit", "-q", "-m", "initial"]);
+
+    let output = project.jargo(jargo_bin()).arg("test").output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "jargo test failed:\nstdout: {}\nstderr: {}",
+        stdout,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(stdout.contains("running 2 tests"), "{stdout}");
+    assert!(stdout.contains("test result: ok. 2 passed"), "{stdout}");
+    assert!(project.path("target/test-reports").is_dir());
+
+    // Nothing changed since HEAD
+    let output = project
+        .jargo(jargo_bin())
+        .args(["test", "--affected"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("no tests affected since HEAD"), "{stdout}");
+
+    project.write("src/Calc.java", source("Calc").replace("a + b", "b + a"));
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["graph", "affected-tests"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "jargo graph affected-tests failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(stdout.contains("1 test class(es) since HEAD"), "{stdout}");
+    assert!(stdout.contains("calc.CalcTest"), "{stdout}");
+    assert!(!stdout.contains("OtherTest"), "{stdout}");
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["test", "--affected"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "jargo test --affected failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(stdout.contains("running 1 test"), "{stdout}");
+    assert!(stdout.contains("CalcTest"), "{stdout}");
+    assert!(!stdout.contains("OtherTest"), "{stdout}");
+}
+
+#[test]
+fn test_api_check_against_last_release() {
+    let repo = FakeRepo::start();
+    let project = ProjectBuilder::lib("parser")
+        .java("17")
+        .repository(&repo)
+        .build();
+    let manifest = project.read("Jargo.toml").replace(
+        "version = \"0.1.0\"",
+        "version = \"1.0.0\"\ngroup = \"com.example\"",
+    );
+    project.write("Jargo.toml", &manifest);
+    let lib = project.read("src/Lib.java");
+    project.write(
+        "src/Lib.java",
+        lib.replace(
+            "public class Lib {\n",
+            "public class Lib {\n    public static int answer() {\n        return 42;\n    }\n",
+        ),
+    );
+    let api_check = || {
+        project
+            .jargo(jargo_bin())
+            .arg("api-check")
+            .output()
+            .unwrap()
+    };
+
+    // Nothing released yet
+    let output = api_check();
+    assert!(
+        output.status.success(),
+        "jargo api-check failed:\nstdout: {}\nstderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("no release of parser before 1.0.0"));
+
+    repo.put(
+        "com/example/parser/1.0.0/parser-1.0.0.jar",
+        std::fs::read(project.path("target/parser.jar")).unwrap(),
+    );
+    repo.put(
+        "com/example/parser/maven-metadata.xml",
+        "<metadata><versioning><versions><version>1.0.0</version></versions></versioning></metadata>",
+    );
+    project.write("src/Lib.java", &lib);
+    project.write(
+        "Jargo.toml",
+        manifest.replace("version = \"1.0.0\"", "version = \"1.0.1\""),
+    );
+    let output = api_check();
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("parser.Lib: `int answer()` was removed"),
+        "{stdout}"
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains(
+        "`1.0.1` is a patch release after `1.0.0`, but the API changes need a major release"
+    ));
+
+    project.write(
+        "Jargo.toml",
+        manifest.replace("version = \"1.0.0\"", "version = \"2.0.0\""),
+    );
+    let output = api_check();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout)
+        .contains("parser 2.0.0 with 1.0.0: 1 breaking change(s), 0 addition(s)"));
+}
+
+#[test]
+fn test_check_without_jar() {
+    let project = ProjectBuilder::lib("parser").java("17").build();
+    let output = project.jargo(jargo_bin()).arg("check").output().unwrap();
+    assert!(
+        output.status.success(),
+        "jargo check failed:\nstdout: {}\nstderr: {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(project.path("target/classes/parser/Lib.class").exists());
+    assert!(!project.path("target/parser.jar").exists());
+
+    project.write("src/Lib.java", "package parser;\n\npublic class Lib {\n");
+    let output = project.jargo(jargo_bin()).arg("check").output().unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("javac compilation failed"));
+}
+
+/// Verifies `jargo fmt` and `jargo check --fmt` end to end.
+/// Fetches google-java-format from Maven Central. Run with:
+///   cargo test -- --include-ignored
+#[test]
+#[ignore]
+fn test_fmt_check_and_rewrite() {
+    let project = ProjectBuilder::lib("parser").java("17").build();
+    project.write(
+        "src/Lib.java",
+        "package parser;\n\npublic class Lib {\n  public static int answer() { return 42; }\n}\n",
+    );
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["check", "--fmt"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("src/Lib.java: line 4"), "{stdout}");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("1 file(s) not formatted"));
+
+    let output = project.jargo(jargo_bin()).arg("fmt").output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        project.read("src/Lib.java"),
+        "package parser;\n\npublic class Lib {\n    public static int answer() {\n        return 42;\n    }\n}\n"
+    );
+    let output = project
+        .jargo(jargo_bin())
+        .args(["fmt", "--check"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+}
+
+/// Verifies `jargo lint` renders Checkstyle violations like compiler errors
+/// and gates on `[lint] fail-on`. Resolves Checkstyle from Maven Central. Run with:
+///   cargo test -- --include-ignored
+#[test]
+#[ignore]
+fn test_lint_fail_on() {
+    let project = ProjectBuilder::lib("parser").java("17").build();
+    project.write(
+        "src/Lib.java",
+        "package parser;\n\nimport java.util.*;\n\npublic class Lib {\n    public static List<String> names() {\n        return new ArrayList<>();\n    }\n}\n",
+    );
+
+    let output = project.jargo(jargo_bin()).arg("lint").output().unwrap();
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("warning: Using the '.*' form of import"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("--> src/Lib.java:3:"), "{stderr}");
+
+    let manifest = project.read("Jargo.toml");
+    project.write(
+        "Jargo.toml",
+        &format!("{}\n[lint]\nfail-on = \"warning\"\n", manifest),
+    );
+    let output = project.jargo(jargo_bin()).arg("lint").output().unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("1 lint violation(s) at or above the `[lint] fail-on` severity"));
+}
+
+/// Verifies `[error-prone]` runs Error Prone during `jargo build` and reports
+/// its findings like javac errors. Resolves Error Prone from Maven Central. Run with:
+///   cargo test -- --include-ignored
+#[test]
+#[ignore]
+fn test_error_prone_build() {
+    let project = ProjectBuilder::lib("parser").java("17").build();
+    project.write(
+        "src/Lib.java",
+        "package parser;\n\npublic class Lib {\n    public static void check(int x) {\n        if (x < 0) {\n            new IllegalArgumentException(\"negative\");\n        }\n    }\n}\n",
+    );
+    let manifest = project.read("Jargo.toml");
+    project.write("Jargo.toml", &format!("{}\n[error-prone]\n", manifest));
+
+    let output = project.jargo(jargo_bin()).arg("build").output().unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("error: [DeadException]"), "{stderr}");
+    assert!(stderr.contains("--> src/Lib.java:6:"), "{stderr}");
+
+    project.write(
+        "Jargo.toml",
+        &format!(
+            "{}\n[error-prone.checks]\nDeadException = \"off\"\n",
+            manifest
+        ),
+    );
+    let output = project.jargo(jargo_bin()).arg("build").output().unwrap();
+    assert!(output.status.success());
+}
+
+/// Verifies `jargo fix --imports` with the built-in organizer, which the
+/// Eclipse formatter uses, drops unused imports and groups the rest.
+#[test]
+fn test_fix_imports_builtin() {
+    let project = ProjectBuilder::lib("parser").java("17").build();
+    project.write(
+        "src/Lib.java",
+        "package parser;\n\nimport java.util.Map;\nimport com.acme.Widget;\nimport java.util.List;\n\npublic class Lib {\n    List<String> names;\n}\n",
+    );
+    let manifest = project.read("Jargo.toml");
+    project.write(
+        "Jargo.toml",
+        &format!("{}\n[fmt]\nformatter = \"eclipse\"\n", manifest),
+    );
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["fix", "--imports"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("fixed imports in 1 of 1 file(s)"));
+    assert_eq!(
+        project.read("src/Lib.java"),
+        "package parser;\n\nimport java.util.List;\n\npublic class Lib {\n    List<String> names;\n}\n"
+    );
+}
+
+/// Verifies `jargo doc` writes the API docs to target/doc, linking JDK types
+/// through the cached JDK package list without going online.
+#[test]
+fn test_doc_links_jdk() {
+    let project = ProjectBuilder::lib("parser").java("17").build();
+    project.write(
+        "src/Lib.java",
+        "package parser;\n\n/** Parses things. */\npublic class Lib {\n    /** The name. */\n    public String name() { return \"lib\"; }\n}\n",
+    );
+    let element_list = project.home().join(".jargo/doc-links/jdk/17/element-list");
+    std::fs::create_dir_all(element_list.parent().unwrap()).unwrap();
+    std::fs::write(&element_list, "module:java.base\njava.lang\njava.util\n").unwrap();
+
+    let output = project.jargo(jargo_bin()).arg("doc").output().unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("generated target/doc/index.html"));
+    assert!(project.path("target/doc/index.html").exists());
+    assert!(project.read("target/doc/parser/Lib.html").contains(
+        "https://docs.oracle.com/en/java/javase/17/docs/api/java.base/java/lang/String.html"
+    ));
+}
+
+/// Verifies `[doc] doclint` reports missing comments as javadoc warnings,
+/// which only `--deny-warnings` turns into a failure.
+#[test]
+fn test_doc_deny_warnings() {
+    let project = ProjectBuilder::lib("parser").java("17").build();
+    project.write(
+        "src/Lib.java",
+        "package parser;\n\n/** Parses things. */\npublic class Lib {\n    public String name() { return \"lib\"; }\n}\n",
+    );
+    let manifest = project.read("Jargo.toml");
+    project.write(
+        "Jargo.toml",
+        &format!("{}\n[doc.doclint]\nall = true\n", manifest),
+    );
+    let element_list = project.home().join(".jargo/doc-links/jdk/17/element-list");
+    std::fs::create_dir_all(element_list.parent().unwrap()).unwrap();
+    std::fs::write(&element_list, "module:java.base\njava.lang\n").unwrap();
+
+    let output = project.jargo(jargo_bin()).arg("doc").output().unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}", stderr);
+    assert!(stderr.contains("no comment"), "{}", stderr);
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["doc", "--deny-warnings"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("javadoc failed"));
+}
+
+/// Verifies `jargo check --license-header` fails on a file without the
+/// `[lint.header]` header and passes once `jargo fix --license-header`
+/// inserted it.
+#[test]
+fn test_license_header() {
+    let project = ProjectBuilder::lib("parser").java("17").build();
+    project.write(
+        "src/Lib.java",
+        "// TODO: header\npackage parser;\n\npublic class Lib {}\n",
+    );
+    let manifest = project.read("Jargo.toml");
+    project.write(
+        "Jargo.toml",
+        &format!(
+            "{}\n[lint.header]\ntemplate = \"\"\"\nCopyright {{year}} Acme Corp.\nSPDX-License-Identifier: Apache-2.0\n\"\"\"\n",
+            manifest
+        ),
+    );
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["check", "--license-header"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("src/Lib.java"));
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("without the `[lint.header]` license header"));
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["fix", "--license-header"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let source = project.read("src/Lib.java");
+    assert!(source.starts_with("/*\n * Copyright 20"), "{}", source);
+    assert!(source.ends_with(
+        " * SPDX-License-Identifier: Apache-2.0\n */\n\npackage parser;\n\npublic class Lib {}\n"
+    ));
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["check", "--license-header"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// Verifies `jargo ide eclipse` links the flat `src/` in under the base
+/// package path and writes the classpath with the JRE for `java`.
+#[test]
+fn test_ide_eclipse() {
+    let project = ProjectBuilder::lib("parser").java("17").build();
+    project.write("resources/app.properties", "name=parser\n");
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["ide", "eclipse"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("wrote .project and .classpath"));
+
+    let dot_project = project.read(".project");
+    assert!(dot_project.contains("<name>parser</name>"));
+    assert!(dot_project
+        .contains("<name>jargo-src/parser</name>\n\t\t\t<type>2</type>\n\t\t\t<locationURI>PROJECT_LOC/src</locationURI>"));
+    let classpath = project.read(".classpath");
+    assert!(classpath.contains("<classpathentry kind=\"src\" path=\"jargo-src\"/>"));
+    assert!(classpath.contains("<classpathentry kind=\"src\" path=\"resources\"/>"));
+    assert!(classpath.contains("StandardVMType/JavaSE-17\"/>"));
+    assert!(project
+        .read(".settings/org.eclipse.jdt.core.prefs")
+        .contains("org.eclipse.jdt.core.compiler.compliance=17"));
+}
+
+/// Verifies `jargo ide vscode` points the Java extension at the staged
+/// sources, adds a launch configuration for the main class, and keeps the
+/// user's own settings and configurations when run again.
+#[test]
+fn test_ide_vscode() {
+    let project = ProjectBuilder::app("server").java("17").build();
+    project.write(
+        ".vscode/launch.json",
+        "{\"version\": \"0.2.0\", \"configurations\": [{\"type\": \"java\", \"name\": \"Attach\", \"request\": \"attach\", \"port\": 5005}]}",
+    );
+    project.write(".vscode/settings.json", "{\"editor.rulers\": [100]}");
+
+    for _ in 0..2 {
+        let output = project
+            .jargo(jargo_bin())
+            .args(["ide", "vscode"])
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    let settings = project.read(".vscode/settings.json");
+    assert!(settings.contains("\"editor.rulers\""), "{}", settings);
+    assert!(settings.contains("\"target/src-root\""), "{}", settings);
+    assert!(project.path("target/src-root/server/Main.java").exists());
+
+    let launch = project.read(".vscode/launch.json");
+    assert!(launch.contains("\"name\": \"Attach\""), "{}", launch);
+    assert_eq!(launch.matches("\"name\": \"jargo: server\"").count(), 1);
+    assert!(
+        launch.contains("\"mainClass\": \"server.Main\""),
+        "{}",
+        launch
+    );
+}
+
+#[test]
+fn test_verbosity_flags() {
+    let project = ProjectBuilder::app("quiet-app").java("17").build();
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["build", "--quiet"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(project.path("target/quiet-app.jar").exists());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "");
+
+    let output = project.jargo(jargo_bin()).args(["clean"]).output().unwrap();
+    assert!(output.status.success());
+    let output = project
+        .jargo(jargo_bin())
+        .args(["build", "-vv"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("@target/.jargo/javac-args.txt"),
+        "{}",
+        stdout
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("compile_source_set"), "{}", stderr);
+    assert!(stderr.contains("time.busy"), "{}", stderr);
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["build", "-v", "--quiet"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_color_flag() {
+    let project = ProjectBuilder::app("color-app").java("17").build();
+
+    // Piped output is plain by default
+    let output = project.jargo(jargo_bin()).arg("build").output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("   Compiling color-app"), "{}", stdout);
+    assert!(!stdout.contains('\x1b'), "{}", stdout);
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["clean", "--color", "always"])
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("\x1b[1;32m"), "{}", stdout);
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["build", "--color", "sometimes"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_env_and_config() {
+    let project = ProjectBuilder::app("env-app").java("17").build();
+    let jargo = |args: &[&str]| {
+        project
+            .jargo(jargo_bin())
+            .args(args)
+            .env_remove("JARGO_MAVEN_REPO")
+            .output()
+            .unwrap()
+    };
+
+    let output = jargo(&[
+        "config",
+        "set",
+        "repository",
+        "https://mirror.example.com/maven2",
+    ]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let output = jargo(&["config", "get", "repository"]);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "https://mirror.example.com/maven2\n"
+    );
+    assert!(!jargo(&["config", "set", "mirror", "https://x"])
+        .status
+        .success());
+    assert!(!jargo(&["config", "set", "cache-dir", "relative"])
+        .status
+        .success());
+
+    let output = jargo(&["env"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("repository = \"https://mirror.example.com/maven2\"  # config.toml"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("/cache\"  # default"), "{}", stdout);
+    assert!(stdout.contains("java = \"17\"  # Jargo.toml"), "{}", stdout);
+
+    let output = project
+        .jargo(jargo_bin())
+        .arg("env")
+        .env("JARGO_MAVEN_REPO", "https://override.example.com")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("repository = \"https://override.example.com\"  # JARGO_MAVEN_REPO"),
+        "{}",
+        stdout
+    );
+
+    assert!(jargo(&["config", "unset", "repository"]).status.success());
+    assert!(!jargo(&["config", "get", "repository"]).status.success());
+}
+
+#[test]
+fn test_config_layering() {
+    let repo = FakeRepo::start();
+    repo.publish_pom("com.acme:core:2.0", &[], &["com/acme/Core"], 17);
+    let project = ProjectBuilder::app("layers")
+        .java("17")
+        .dependency("com.acme:core", "2.0")
+        .repository(&repo)
+        .build();
+    let jargo_home = project.home().join(".jargo");
+    std::fs::create_dir_all(&jargo_home).unwrap();
+    std::fs::write(jargo_home.join("config.toml"), "offline = true\n").unwrap();
+
+    // The global config takes the network away
+    let output = project.jargo(jargo_bin()).arg("build").output().unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("jargo is offline"), "{}", stderr);
+
+    // The environment wins over the global config
+    let output = project
+        .jargo(jargo_bin())
+        .arg("build")
+        .env("JARGO_OFFLINE", "false")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // Flags win over the environment
+    let output = project
+        .jargo(jargo_bin())
+        .args(["env", "--offline"])
+        .env("JARGO_OFFLINE", "false")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("offline = \"true\"  # --offline"),
+        "{}",
+        stdout
+    );
+
+    // Jargo.toml moves the output, and --target-dir wins over it
+    let manifest = project.read("Jargo.toml");
+    project.write(
+        "Jargo.toml",
+        &format!("{}\n[build]\ntarget-dir = \"out\"\n", manifest),
+    );
+    let output = project.jargo(jargo_bin()).arg("build").output().unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(project.path("out/layers.jar").is_file());
+    assert!(project.path("target/layers.jar").is_file());
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["build", "--target-dir", "elsewhere"])
+        .env("JARGO_TARGET_DIR", project.path("ignored"))
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(project.path("elsewhere/layers/layers.jar").is_file());
+    assert!(!project.path("ignored").exists());
+
+    // The variable is namespaced by package and relative to the project root
+    let output = project
+        .jargo(jargo_bin())
+        .arg("build")
+        .env("JARGO_TARGET_DIR", "shared")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(project.path("shared/layers/layers.jar").is_file());
+
+    // A target directory holding the project is rejected, and never cleaned
+    project.write(
+        "Jargo.toml",
+        &format!("{}\n[build]\ntarget-dir = \".\"\n", manifest),
+    );
+    let output = project.jargo(jargo_bin()).arg("build").output().unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("contains the project"), "{}", stderr);
+    assert!(project.path("src").is_dir());
+
+    #[cfg(unix)]
+    {
+        project.write("Jargo.toml", &manifest);
+        std::fs::remove_file(project.path("target")).unwrap();
+        std::os::unix::fs::symlink(project.root(), project.path("target")).unwrap();
+        let output = project.jargo(jargo_bin()).arg("clean").output().unwrap();
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("refusing to remove"), "{}", stderr);
+        assert!(project.path("src").is_dir());
+    }
+}
+
+#[test]
+fn test_install_and_uninstall() {
+    let repo = FakeRepo::start();
+    let project = ProjectBuilder::app("greeter")
+        .java("17")
+        .manifest("[run]\nsystem-properties = { greeting = \"Hi\" }\n")
+        .file(
+            "src/Main.java",
+            "package greeter;\n\n\
+             public class Main {\n\
+             \x20   public static void main(String[] args) {\n\
+             \x20       System.out.println(System.getProperty(\"greeting\") + \", \" + args[0]);\n\
+             \x20   }\n\
+             }\n",
+        )
+        .repository(&repo)
+        .build();
+    let bin_dir = project.home().join(".jargo/bin");
+    let launcher = bin_dir.join(if cfg!(windows) {
+        "greeter.cmd"
+    } else {
+        "greeter"
+    });
+    let greet = || {
+        let output = Command::new(&launcher).arg("Ada").output().unwrap();
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+
+    let output = project.jargo(jargo_bin()).arg("install").output().unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}", stderr);
+    assert!(
+        String::from_utf8_lossy(&output.stdout).contains("Installed greeter v0.1.0 (greeter in")
+    );
+    assert!(stderr.contains("is not on your PATH"), "{}", stderr);
+    assert!(bin_dir.join("greeter.jar").is_file());
+    assert_eq!(greet(), "Hi, Ada");
+
+    // Publish the self-contained JAR, then install it by coordinate
+    repo.publish_pom("com.example:greeter:0.1.0", &[], &[], 17);
+    repo.put(
+        "com/example/greeter/0.1.0/greeter-0.1.0.jar",
+        std::fs::read(project.path("target/greeter.jar")).unwrap(),
+    );
+    let output = project
+        .jargo(jargo_bin())
+        .args(["uninstall", "greeter"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(!launcher.exists());
+    assert!(!bin_dir.join("greeter.jar").exists());
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["install", "com.example:greeter:0.1.0"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    // Without the project's [run] settings
+    assert_eq!(greet(), "null, Ada");
+
+    assert!(project
+        .jargo(jargo_bin())
+        .args(["uninstall", "greeter"])
+        .output()
+        .unwrap()
+        .status
+        .success());
+    let output = project
+        .jargo(jargo_bin())
+        .args(["uninstall", "greeter"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("`greeter` is not installed"));
+}
+
+#[test]
+fn test_new_options() {
+    let temp = TempDir::new().unwrap();
+    let project_path = temp.path().join("cli-tool");
+
+    let output = Command::new(jargo_bin())
+        .args([
+            "new",
+            "cli-tool",
+            "--java",
+            "17",
+            "--package",
+            "com.acme.tool",
+            "--main-class",
+            "App",
+            "--test-framework",
+            "testng",
+            "--no-git",
+        ])
+        .current_dir(temp.path())
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(!project_path.join(".git").exists());
+    assert!(project_path.join("test/AppTest.java").exists());
+    let manifest = std::fs::read_to_string(project_path.join("Jargo.toml")).unwrap();
+    assert!(manifest.contains("java = \"17\""), "{}", manifest);
+    assert!(manifest.contains("base-package = \"com.acme.tool\""));
+    assert!(manifest.contains("main-class = \"App\""));
+    assert!(manifest.contains("framework = \"testng\""));
+    assert!(manifest.contains("\"org.testng:testng\" = "));
+
+    let repo = FakeRepo::start();
+    repo.publish_pom(
+        "org.testng:testng:7.10.2",
+        &[],
+        &["org/testng/annotations/Test"],
+        11,
+    );
+    let output = Command::new(jargo_bin())
+        .arg("run")
+        .current_dir(&project_path)
+        .env("HOME", temp.path())
+        .env("JARGO_MAVEN_REPO", repo.url())
+        .env("NO_PROXY", "127.0.0.1,localhost")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Hello, World!"));
+
+    // Invalid options fail before anything is created
+    let output = Command::new(jargo_bin())
+        .args(["new", "bad", "--package", "com.2fast"])
+        .current_dir(temp.path())
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(!temp.path().join("bad").exists());
+
+    let output = Command::new(jargo_bin())
+        .args(["new", "bare", "--test-framework", "none"])
+        .current_dir(temp.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(!temp.path().join("bare/test").exists());
+}
+
+#[test]
+fn test_init_adopts_maven_layout() {
+    let temp = TempDir::new().unwrap();
+    let project_path = temp.path().join("shop");
+    let write = |path: &str, contents: &str| {
+        let path = project_path.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    };
+    write(
+        "src/main/java/com/acme/shop/Shop.java",
+        "package com.acme.shop;\n\n\
+         public class Shop {\n\
+         \x20   public static void main(String[] args) throws Exception {\n\
+         \x20       var props = new java.util.Properties();\n\
+         \x20       props.load(Shop.class.getResourceAsStream(\"/shop.properties\"));\n\
+         \x20       System.out.println(props.getProperty(\"name\") + \" \" + com.acme.shop.util.Prices.total());\n\
+         \x20   }\n\
+         }\n",
+    );
+    write(
+        "src/main/java/com/acme/shop/util/Prices.java",
+        "package com.acme.shop.util;\n\npublic class Prices { public static int total() { return 42; } }\n",
+    );
+    write("src/main/resources/shop.properties", "name=shop\n");
+    write(
+        "src/test/java/com/acme/shop/ShopTest.java",
+        "package com.acme.shop;\n\nclass ShopTest {}\n",
+    );
+    write(".gitignore", "build/\n");
+
+    let output = Command::new(jargo_bin())
+        .args(["init", "--java", "17"])
+        .current_dir(&project_path)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Moved Maven layout sources"), "{}", stdout);
+    assert!(project_path.join("src/Shop.java").is_file());
+    assert!(project_path.join("src/util/Prices.java").is_file());
+    assert!(project_path.join("test/ShopTest.java").is_file());
+    assert!(project_path.join("resources/shop.properties").is_file());
+    assert!(!project_path.join("src/main").exists());
+    assert!(!project_path.join("src.orig").exists());
+    let manifest = std::fs::read_to_string(project_path.join("Jargo.toml")).unwrap();
+    assert!(manifest.contains("base-package = \"com.acme.shop\""));
+    assert!(manifest.contains("main-class = \"Shop\""));
+    assert_eq!(
+        std::fs::read_to_string(project_path.join(".gitignore")).unwrap(),
+        "build/\ntarget/\n"
+    );
+
+    let output = Command::new(jargo_bin())
+        .arg("run")
+        .current_dir(&project_path)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("shop 42"));
+}
+
+#[test]
+fn test_migrate_gradle_build() {
+    let temp = TempDir::new().unwrap();
+    let project_path = temp.path().join("report");
+    let write = |path: &str, contents: &str| {
+        let path = project_path.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    };
+    write("settings.gradle.kts", "rootProject.name = \"report\"\n");
+    write(
+        "build.gradle.kts",
+        "plugins {\n    application\n}\n\n\
+         group = \"com.acme\"\n\
+         version = \"2.1.0\"\n\n\
+         java {\n    toolchain {\n        languageVersion = JavaLanguageVersion.of(17)\n    }\n}\n\n\
+         dependencies {\n\
+         \x20   implementation(\"com.acme:util:1.0\")\n\
+         \x20   implementation(platform(\"com.acme:bom:1.0\"))\n\
+         }\n\n\
+         application {\n    mainClass.set(\"com.acme.report.Report\")\n}\n",
+    );
+    write(
+        "src/main/java/com/acme/report/Report.java",
+        "package com.acme.report;\n\n\
+         public class Report {\n\
+         \x20   public static void main(String[] args) {\n\
+         \x20       System.out.println(\"report uses \" + com.acme.util.Helper.class.getSimpleName());\n\
+         \x20   }\n\
+         }\n",
+    );
+
+    let output = Command::new(jargo_bin())
+        .arg("migrate")
+        .current_dir(&project_path)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Created app `report` package from build.gradle.kts"),
+        "{}",
+        stdout
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("platform(\"com.acme:bom:1.0\")"));
+    assert!(project_path.join("src/Report.java").is_file());
+    assert!(project_path.join("build.gradle.kts").is_file());
+    let manifest = std::fs::read_to_string(project_path.join("Jargo.toml")).unwrap();
+    assert!(manifest.contains("version = \"2.1.0\""), "{}", manifest);
+    assert!(manifest.contains("group = \"com.acme\""));
+    assert!(manifest.contains("java = \"17\""));
+    assert!(manifest.contains("base-package = \"com.acme.report\""));
+    assert!(manifest.contains("main-class = \"Report\""));
+    assert!(manifest.contains("[dependencies]\n\"com.acme:util\" = \"1.0\"\n"));
+
+    let repo = FakeRepo::start();
+    repo.publish_pom("com.acme:util:1.0", &[], &["com/acme/util/Helper"], 17);
+    let output = Command::new(jargo_bin())
+        .arg("run")
+        .current_dir(&project_path)
+        .env("HOME", temp.path())
+        .env("JARGO_MAVEN_REPO", repo.url())
+        .env("NO_PROXY", "127.0.0.1,localhost")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("report uses Helper"));
+
+    let output = Command::new(jargo_bin())
+        .arg("migrate")
+        .current_dir(temp.path())
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("no Maven or Gradle build"));
+}
+
+#[test]
+fn test_exit_codes_and_error_format() {
+    let temp = TempDir::new().unwrap();
+    let output = Command::new(jargo_bin())
+        .arg("build")
+        .current_dir(temp.path())
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Jargo.toml not found"));
+
+    let project = ProjectBuilder::app("broken")
+        .java("17")
+        .file("src/Main.java", "package broken;\n\npublic class Main {\n")
+        .build();
+    let output = project
+        .jargo(jargo_bin())
+        .args(["build", "--error-format", "json"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(4));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let last = stderr.lines().last().unwrap();
+    assert_eq!(
+        last,
+        r#"{"causes":[],"exit_code":4,"kind":"compile","message":"javac compilation failed"}"#
+    );
+
+    let repo = FakeRepo::start();
+    let project = ProjectBuilder::app("unresolved")
+        .java("17")
+        .dependency("com.acme:missing", "1.0")
+        .repository(&repo)
+        .build();
+    let output = project.jargo(jargo_bin()).arg("build").output().unwrap();
+    assert_eq!(
+        output.status.code(),
+        Some(6),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn test_commands_find_project_root_from_subdirectory() {
+    let project = ProjectBuilder::app("nested")
+        .java("17")
+        .file(
+            "src/util/Greeting.java",
+            "package nested.util;\n\npublic class Greeting { public static String text() { return \"hi\"; } }\n",
+        )
+        .file(
+            "src/Main.java",
+            "package nested;\n\npublic class Main {\n    public static void main(String[] args) {\n        System.out.println(nested.util.Greeting.text());\n    }\n}\n",
+        )
+        .build();
+    let output = project
+        .jargo(jargo_bin())
+        .arg("run")
+        .current_dir(project.path("src/util"))
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("hi"));
+    assert!(project.path("target/classes").is_dir());
+    assert!(!project.path("src/util/target").exists());
+}
+
+#[test]
+fn test_manifest_path_and_target_dir_flags() {
+    let project = ProjectBuilder::app("pointed").java("17").build();
+    let outside = TempDir::new().unwrap();
+    let manifest = project.path("Jargo.toml");
+    let output = project
+        .jargo(jargo_bin())
+        .arg("build")
+        .arg("--manifest-path")
+        .arg(&manifest)
+        .args(["--target-dir", "out"])
+        .current_dir(outside.path())
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(outside.path().join("out/pointed.jar").is_file());
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["build", "--manifest-path", "src/Main.java"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("a file named Jargo.toml"));
+}
+
+#[test]
+fn test_check_manifest() {
+    let project = ProjectBuilder::app("strict")
+        .java("17")
+        .dependency("com.acme:lib:1.0", "1.0")
+        .dependency("com.acme:other", "")
+        .manifest("[run]\njvm-arg = [\"-Xmx1g\"]\n")
+        .build();
+    let output = project
+        .jargo(jargo_bin())
+        .args(["check", "--manifest"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("unknown key `run.jvm-arg` in Jargo.toml is ignored"));
+    assert!(stderr.contains("invalid dependency coordinate `com.acme:lib:1.0`"));
+    assert!(stderr.contains("invalid version `` for `com.acme:other`"));
+    assert!(stderr.contains("2 problem(s) in Jargo.toml"));
+
+    let project = ProjectBuilder::app("typo")
+        .java("17")
+        .manifest("[run]\njvm-arg = [\"-Xmx1g\"]\n")
+        .build();
+    let output = project.jargo(jargo_bin()).arg("build").output().unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown key `run.jvm-arg`"));
+    let output = project
+        .jargo(jargo_bin())
+        .args(["check", "--manifest"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Checked"));
+
+    let project = ProjectBuilder::app("tasks")
+        .java("17")
+        .manifest("[tasks]\nbuild = \"make\"\ne2e = \"sh e2e.sh\"\n")
+        .build();
+    let output = project
+        .jargo(jargo_bin())
+        .args(["check", "--manifest"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("task `build` in [tasks] has the name of a built-in command"),
+        "{}",
+        stderr
+    );
+    assert!(!stderr.contains("task `e2e`"), "{}", stderr);
+}
+
+#[test]
+fn test_lock_file_follows_manifest_changes() {
+    let repo = FakeRepo::start();
+    repo.publish_pom("com.acme:widget:1.0", &[], &["com/acme/Widget"], 17);
+    let project = ProjectBuilder::app("locked")
+        .java("17")
+        .dependency("com.acme:widget", "1.0")
+        .repository(&repo)
+        .build();
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["build", "--locked"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(6));
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("needs to be updated but --locked was passed"));
+
+    let output = project.jargo(jargo_bin()).arg("build").output().unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(project.read("Jargo.lock").contains("manifest-hash = "));
+    let output = project
+        .jargo(jargo_bin())
+        .args(["build", "--locked"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("Resolving"));
+
+    // Same version, new scope: the lock was written for another manifest
+    let manifest = project.read("Jargo.toml");
+    let changed = manifest.replace(
+        "\"com.acme:widget\" = \"1.0\"",
+        "\"com.acme:widget\" = { version = \"1.0\", scope = \"runtime\" }",
+    );
+    assert_ne!(manifest, changed);
+    std::fs::write(project.path("Jargo.toml"), changed).unwrap();
+    let output = project
+        .jargo(jargo_bin())
+        .args(["build", "--locked"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(6));
+
+    let output = project.jargo(jargo_bin()).arg("build").output().unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Resolving"));
+    assert!(project.read("Jargo.lock").contains("scope = \"runtime\""));
+}
+
+#[test]
+fn test_missing_dependency_suggests_jargo_add() {
+    let repo = FakeRepo::start();
+    repo.put(
+        "com/acme/widget/maven-metadata.xml",
+        "<metadata><versioning><versions><version>1.0</version><version>1.1</version>\
+         <version>2.0-SNAPSHOT</version></versions></versioning></metadata>",
+    );
+    let project = ProjectBuilder::app("suggest")
+        .java("17")
+        .repository(&repo)
+        .file(
+            "src/Main.java",
+            concat!(
+                "package suggest;\n",
+                "\n",
+                "import org.apache.commons.lang3.StringUtils;\n",
+                "\n",
+                "public class Main {\n",
+                "    public static void main(String[] args) {\n",
+                "        System.out.println(StringUtils.capitalize(\"hi\"));\n",
+                "    }\n",
+                "}\n"
+            ),
+        )
+        .build();
+    let output = project.jargo(jargo_bin()).arg("build").output().unwrap();
+    assert_eq!(output.status.code(), Some(4));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(
+            "= help: `org.apache.commons:commons-lang3` provides this package; \
+             add it with `jargo add org.apache.commons:commons-lang3`"
+        ),
+        "{stderr}"
+    );
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["add", "com.acme:widget"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("com.acme:widget v1.1"));
+    assert!(project
+        .read("Jargo.toml")
+        .contains("\"com.acme:widget\" = \"1.1\""));
+    let output = project
+        .jargo(jargo_bin())
+        .args(["add", "com.acme:widget", "--version", "1.0"])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&output.stderr).contains("already in [dependencies]"));
+}
+
+#[test]
+fn test_jdk_older_than_package_java() {
+    let project = ProjectBuilder::app("future").java("99").build();
+    let output = project.jargo(jargo_bin()).arg("build").output().unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("project requires Java 99 but found javac "),
+        "{stderr}"
+    );
+    assert!(stderr.contains("`[toolchain]`, `.jargo-java-version`, or JAVA_HOME"));
+    assert!(!stderr.contains("Usage: javac"));
+}
+
+#[test]
+fn test_build_reports_warnings() {
+    let main = concat!(
+        "package noisy;\n",
+        "\n",
+        "import java.util.ArrayList;\n",
+        "import java.util.List;\n",
+        "\n",
+        "public class Main {\n",
+        "    public static void main(String[] args) {\n",
+        "        List names = new ArrayList();\n",
+        "        names.add(\"x\");\n",
+        "        System.out.println(names);\n",
+        "    }\n",
+        "}\n"
+    );
+    let project = ProjectBuilder::app("noisy")
+        .java("17")
+        .manifest("[javac]\nlint = [\"all\"]\n")
+        .file("src/Main.java", main)
+        .build();
+    let output = project.jargo(jargo_bin()).arg("build").output().unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+    assert!(stderr.contains("warning: [rawtypes]"), "{stderr}");
+    assert!(stderr.contains("--> src/Main.java:8:"), "{stderr}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("JAR at target/noisy.jar ("), "{stdout}");
+    assert!(stdout.contains(" warnings)"), "{stdout}");
+
+    let project = ProjectBuilder::app("strict")
+        .java("17")
+        .manifest("[javac]\nlint = [\"all\"]\ndeny-warnings = true\n")
+        .file("src/Main.java", main.replace("noisy", "strict"))
+        .build();
+    let output = project.jargo(jargo_bin()).arg("build").output().unwrap();
+    assert_eq!(output.status.code(), Some(4));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("-Werror"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_build_with_kotlinc_backend() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let project = ProjectBuilder::app("kotlin")
+        .java("17")
+        .manifest("[build]\ncompiler = \"kotlinc\"\n")
+        .file("src/Main.kt", "fun main() {\n    foo()\n}\n")
+        .build();
+    // A kotlinc reporting one error, like the real one
+    let kotlin_home = project.path("kotlin");
+    std::fs::create_dir_all(kotlin_home.join("bin")).unwrap();
+    let kotlinc = kotlin_home.join("bin/kotlinc");
+    std::fs::write(
+        &kotlinc,
+        "#!/bin/sh\n\
+         echo 'src/Main.kt:2:5: error: unresolved reference: foo' >&2\n\
+         echo '    foo()' >&2\n\
+         echo '    ^' >&2\n\
+         exit 1\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&kotlinc, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let output = project
+        .jargo(jargo_bin())
+        .arg("build")
+        .env("KOTLIN_HOME", &kotlin_home)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(4));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("error: unresolved reference: foo"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("--> src/Main.kt:2:5"), "{stderr}");
+    let args = project.read("target/.jargo/kotlinc-args.txt");
+    assert!(args.contains("-jvm-target\n17\n"), "{args}");
+    assert!(args.contains("src/Main.kt"), "{args}");
+}
+
+#[test]
+fn test_features_enable_optional_dependencies() {
+    let repo = FakeRepo::start();
+    repo.publish_pom("com.acme:widget:1.0", &[], &["com/acme/Widget"], 17);
+    repo.publish_pom(
+        "com.acme:gauge:1.0",
+        &[FixtureDep::compile("com.acme:gauge-core:1.0")],
+        &["com/acme/Gauge"],
+        17,
+    );
+    repo.publish_pom("com.acme:gauge-core:1.0", &[], &["com/acme/GaugeCore"], 17);
+    let project = ProjectBuilder::app("featured")
+        .java("17")
+        .dependency("com.acme:widget", "1.0")
+        .manifest(concat!(
+            "\"com.acme:gauge\" = { version = \"1.0\", optional = true }\n",
+            "\n",
+            "[features]\n",
+            "metrics = [\"com.acme:gauge\"]\n",
+            "full = [\"metrics\"]\n",
+        ))
+        .repository(&repo)
+        .file(
+            "src/Main.java",
+            concat!(
+                "package featured;\n",
+                "\n",
+                "public class Main {\n",
+                "    public static void main(String[] args) {\n",
+                "        try {\n",
+                "            Class.forName(\"com.acme.GaugeCore\");\n",
+                "            System.out.println(\"metrics on\");\n",
+                "        } catch (ClassNotFoundException e) {\n",
+                "            System.out.println(\"metrics off\");\n",
+                "        }\n",
+                "    }\n",
+                "}\n"
+            ),
+        )
+        .build();
+
+    let run = |args: &[&str]| {
+        let output = project.jargo(jargo_bin()).args(args).output().unwrap();
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+    assert!(run(&["run"]).contains("metrics off"));
+    // The lock resolves every feature, naming the features of the entries
+    // only optional dependencies reach
+    let lock = project.read("Jargo.lock");
+    assert!(lock.contains("artifact = \"gauge-core\""), "{lock}");
+    assert_eq!(
+        lock.matches("features = [\n    \"full\",\n    \"metrics\",\n]")
+            .count(),
+        2,
+        "{lock}"
+    );
+    assert!(run(&["run", "--features", "metrics"]).contains("metrics on"));
+    assert!(run(&["run", "--features", "full", "--locked"]).contains("metrics on"));
+
+    let output = project
+        .jargo(jargo_bin())
+        .args(["build", "--features", "tracing"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("unknown feature `tracing`; [features] has: full, metrics"));
+}